@@ -0,0 +1,195 @@
+//! `emry apply`: apply a unified diff to the working tree, then re-index.
+//!
+//! Hunks are placed even when their file moved on since the patch was made,
+//! merging with working-tree edits that do not overlap them (see
+//! `emry_core::patch`). If any hunk conflicts nothing is written, and with
+//! `--dry-run` only the preview is shown. Commands that edit files, such as
+//! `emry rename`, go through the same plan, write and re-index steps.
+
+use anyhow::{anyhow, Context, Result};
+use console::Style;
+use emry_agent::project::{Components, RepoContext};
+use emry_core::import_paths::ImportResolver;
+use emry_core::patch::{parse_patch, FilePatch, FileResult, HunkStatus, PatchKind};
+use emry_engine::ingest::service::IngestionService;
+use std::collections::HashSet;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+
+use super::ui;
+
+/// One file of a patch, applied in memory against the working tree.
+pub(crate) struct PlannedFile {
+    pub path: PathBuf,
+    /// Path relative to the repository root, as named in the patch
+    pub display: String,
+    pub kind: PatchKind,
+    pub current: Option<String>,
+    pub result: FileResult,
+}
+
+pub async fn handle_apply(patch: PathBuf, dry_run: bool, config_path: Option<&Path>) -> Result<()> {
+    if !dry_run {
+        super::utils::ensure_writable("apply")?;
+    }
+    let text = if patch.as_os_str() == "-" {
+        let mut text = String::new();
+        std::io::stdin().read_to_string(&mut text)?;
+        text
+    } else {
+        std::fs::read_to_string(&patch).with_context(|| format!("failed to read {}", patch.display()))?
+    };
+    let patches = parse_patch(&text)?;
+    if patches.is_empty() {
+        return Err(anyhow!("{} contains no file changes", patch.display()));
+    }
+
+    let root = std::env::current_dir()?;
+    let plans = plan(&root, &patches)?;
+    ui::print_header(&format!("Apply {}", patch.display()));
+    if dry_run {
+        ui::print_diff(&text);
+    }
+    print_plan(&plans);
+
+    let conflicts = conflict_count(&plans);
+    if conflicts > 0 {
+        return Err(anyhow!("{} hunk(s) conflict with the working tree; nothing was written", conflicts));
+    }
+    if dry_run {
+        println!("\nDry run: nothing was written.");
+        return Ok(());
+    }
+    super::utils::ensure_no_daemon().await?;
+
+    let touched = write_plan(&plans)?;
+    ui::print_success(&format!("Patched {} file(s)", touched.len()));
+    if touched.is_empty() {
+        return Ok(());
+    }
+    match RepoContext::open(config_path, Components::ALL).await {
+        Ok(ctx) => {
+            let updated = reindex_touched("Apply", &ctx, touched).await?;
+            ui::print_success(&format!("Re-indexed {} file(s)", updated));
+        }
+        Err(e) => println!("{}", ui::palette().warning.apply_to(format!("Index not updated: {}", e))),
+    }
+    Ok(())
+}
+
+/// Apply `patches` in memory against the files under `root`. Paths that are
+/// absolute or climb out of `root` are refused.
+pub(crate) fn plan(root: &Path, patches: &[FilePatch]) -> Result<Vec<PlannedFile>> {
+    let mut plans = Vec::new();
+    for patch in patches {
+        if patch.path.components().any(|c| !matches!(c, Component::Normal(_) | Component::CurDir)) {
+            return Err(anyhow!("refusing to patch {} outside the repository", patch.path.display()));
+        }
+        let path = root.join(&patch.path);
+        let current = if path.exists() {
+            Some(std::fs::read_to_string(&path).with_context(|| format!("failed to read {}", path.display()))?)
+        } else {
+            None
+        };
+        let result = patch.apply(current.as_deref());
+        plans.push(PlannedFile { path, display: patch.path.display().to_string(), kind: patch.kind, current, result });
+    }
+    Ok(plans)
+}
+
+pub(crate) fn print_plan(plans: &[PlannedFile]) {
+    let palette = ui::palette();
+    for plan in plans {
+        let note = match plan.kind {
+            PatchKind::Modify => "",
+            PatchKind::Create => " (new file)",
+            PatchKind::Delete => " (deleted)",
+        };
+        println!("{}{}", palette.location.apply_to(&plan.display), Style::new().dim().apply_to(note));
+        for hunk in &plan.result.hunks {
+            let status = match hunk.status {
+                HunkStatus::Applied => palette.success.apply_to("applies"),
+                HunkStatus::AlreadyApplied => Style::new().dim().apply_to("already applied"),
+                HunkStatus::Merged => palette.warning.apply_to("merges with working-tree edits"),
+                HunkStatus::Conflict => palette.error.apply_to("conflicts with the working tree"),
+            };
+            println!("  @@ -{}  {}", hunk.old_start, status);
+        }
+    }
+}
+
+pub(crate) fn conflict_count(plans: &[PlannedFile]) -> usize {
+    plans
+        .iter()
+        .flat_map(|p| &p.result.hunks)
+        .filter(|h| h.status == HunkStatus::Conflict)
+        .count()
+}
+
+/// Write the planned files, all or nothing: a single conflict aborts before
+/// any file is touched, and every new version is written to a temporary file
+/// before any is moved into place, so a failed write changes no file.
+/// Returns the paths that changed on disk.
+pub(crate) fn write_plan(plans: &[PlannedFile]) -> Result<HashSet<PathBuf>> {
+    let conflicts = conflict_count(plans);
+    if conflicts > 0 {
+        return Err(anyhow!("{} hunk(s) conflict with the working tree; nothing was written", conflicts));
+    }
+    let mut staged: Vec<(&PlannedFile, Option<PathBuf>)> = Vec::new();
+    for plan in plans.iter().filter(|p| p.current != p.result.content) {
+        let tmp = match &plan.result.content {
+            Some(content) => match stage_file(&plan.path, content) {
+                Ok(tmp) => Some(tmp),
+                Err(e) => {
+                    for tmp in staged.iter().filter_map(|(_, tmp)| tmp.as_ref()) {
+                        let _ = std::fs::remove_file(tmp);
+                    }
+                    return Err(e);
+                }
+            },
+            None => None,
+        };
+        staged.push((plan, tmp));
+    }
+
+    let mut touched = HashSet::new();
+    for (plan, tmp) in staged {
+        match tmp {
+            Some(tmp) => std::fs::rename(&tmp, &plan.path)?,
+            None => std::fs::remove_file(&plan.path)?,
+        }
+        touched.insert(plan.path.clone());
+    }
+    Ok(touched)
+}
+
+/// Write `content` to a temporary file next to `path`, with the permissions
+/// of `path` if it exists, since the rename would otherwise reset them and
+/// scripts would lose `+x`.
+fn stage_file(path: &Path, content: &str) -> Result<PathBuf> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".emry-patch.tmp");
+    let tmp = PathBuf::from(tmp);
+    let written = std::fs::write(&tmp, content).and_then(|()| match std::fs::metadata(path) {
+        Ok(meta) => std::fs::set_permissions(&tmp, meta.permissions()),
+        Err(_) => Ok(()),
+    });
+    if let Err(e) = written {
+        let _ = std::fs::remove_file(&tmp);
+        return Err(e).with_context(|| format!("failed to write {}", path.display()));
+    }
+    Ok(tmp)
+}
+
+/// Bring the index up to date with files a command just wrote.
+pub(crate) async fn reindex_touched(source: &str, ctx: &RepoContext, touched: HashSet<PathBuf>) -> Result<usize> {
+    let store = ctx.surreal_store.clone()
+        .ok_or_else(|| anyhow!("SurrealStore not initialized. Run 'emry index' first."))?;
+    let ingestion = IngestionService::new(store.clone(), ctx.embedder.clone()).with_import_resolver(ImportResolver::load(&ctx.root));
+    let (updated, removed) =
+        super::watch::reindex_paths(source, &ctx.root, &ctx.config, &store, &ingestion, ctx.embedder.clone(), touched).await?;
+    Ok(updated + removed)
+}
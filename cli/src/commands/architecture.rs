@@ -5,14 +5,21 @@ use emry_agent::project as agent_context;
 use emry_agent::ops::architecture::ArchitectureTool;
 use std::path::Path;
 use std::sync::Arc;
-use emry_engine::search::service::SearchService;
 use emry_agent::ops::fs::FsTool;
+use emry_agent::ops::export::{write_graph, ExportFormat};
+use emry_agent::project::types::{GraphEdge, GraphNode, GraphSubgraph};
+use emry_core::entry_points::EntryPointKind;
+use std::collections::{BTreeMap, BTreeSet};
 
 use super::utils::render_markdown_answer;
 
-pub async fn handle_architecture(mode: String, verbose: bool, config_path: Option<&Path>) -> Result<()> {
+pub async fn handle_architecture(mode: String, verbose: bool, mermaid: bool, config_path: Option<&Path>) -> Result<()> {
     use super::ui;
     use console::Style;
+
+    if mermaid {
+        return print_module_graph(config_path).await;
+    }
     
     ui::print_header(&format!("Architecture Analysis (Mode: {})", mode));
 
@@ -25,10 +32,7 @@ pub async fn handle_architecture(mode: String, verbose: bool, config_path: Optio
     let store = ctx.surreal_store.clone()
         .ok_or_else(|| anyhow::anyhow!("SurrealStore not initialized. Run 'emry index' first."))?;
     
-    let search_service = Arc::new(SearchService::new(
-        store,
-        ctx.embedder.clone(),
-    ));
+    let search_service = Arc::new(ctx.search_service(store));
 
     let arch_impl = Arc::new(ArchitectureTool::new(ctx.clone()));
     let fs_tool = Arc::new(FsTool::new(ctx.clone()));
@@ -63,6 +67,7 @@ pub async fn handle_architecture(mode: String, verbose: bool, config_path: Optio
                      };
                      ui::print_panel("Observation", &truncated, Style::new().blue(), Some(Style::new().dim()));
                 }
+                emry_agent::cortex::CortexEvent::AnswerDelta(_) => {}
             }
         }
     }).await?;
@@ -72,3 +77,55 @@ pub async fn handle_architecture(mode: String, verbose: bool, config_path: Optio
 
     Ok(())
 }
+
+/// Module coupling as a Mermaid flowchart, straight from the index; the LLM
+/// report is skipped. Modules holding entry points are labelled with them,
+/// tests aside, and drawn even when nothing couples them to the rest.
+async fn print_module_graph(config_path: Option<&Path>) -> Result<()> {
+    let ctx = Arc::new(agent_context::RepoContext::open(config_path, agent_context::Components::STORE).await?);
+    let tool = ArchitectureTool::new(ctx);
+    let (coupling, _) = tool.analyze_structure().await?;
+
+    let mut entries: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for entry in tool.entry_points().await? {
+        if entry.entry_kind == EntryPointKind::Test {
+            continue;
+        }
+        let module = Path::new(&entry.file_path).parent().map(|p| p.display().to_string()).unwrap_or_else(|| "root".to_string());
+        entries.entry(module).or_default().push(format!("{} ({})", entry.label, entry.entry_kind.as_str()));
+    }
+
+    let modules: BTreeSet<&str> = coupling
+        .iter()
+        .flat_map(|c| [c.source_module.as_str(), c.target_module.as_str()])
+        .chain(entries.keys().map(String::as_str))
+        .collect();
+    let graph = GraphSubgraph {
+        nodes: modules
+            .into_iter()
+            .map(|m| GraphNode {
+                id: m.to_string(),
+                kind: "module".to_string(),
+                label: match entries.get(m) {
+                    Some(names) if names.len() > 3 => format!("{} ▶ {}, +{} more", m, names[..3].join(", "), names.len() - 3),
+                    Some(names) => format!("{} ▶ {}", m, names.join(", ")),
+                    None => m.to_string(),
+                },
+                file_path: m.to_string(),
+                canonical_id: None,
+            })
+            .collect(),
+        edges: coupling
+            .iter()
+            .map(|c| GraphEdge {
+                source: c.source_module.clone(),
+                target: c.target_module.clone(),
+                kind: format!("{} imports", c.strength),
+                line: None,
+                column: None,
+            })
+            .collect(),
+    };
+    write_graph(&graph, ExportFormat::Mermaid, &mut std::io::stdout().lock())?;
+    Ok(())
+}
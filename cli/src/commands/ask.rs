@@ -1,143 +1,271 @@
-use anyhow::{Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
+use emry_agent::cortex::citations;
 use emry_agent::cortex::context::AgentContext;
+use emry_agent::cortex::conversation::{compact, Conversation, ConversationStore};
 use emry_agent::cortex::Cortex;
-use emry_agent::cortex::tools::{
-    fs::{ListFilesTool, ReadFileTool, ViewFileOutlineTool, ViewCodeItemTool, ViewCodebaseMapTool},
-    graph::{InspectGraphTool, FindReferencesTool, GoToDefinitionTool, GetTypeDefinitionTool},
-    search::SearchCodeTool,
-    workflows::{ReadFilesTool as ReadFilesMacroTool, ExploreModuleTool, FindUsagesTool},
-    architecture::DescribeArchitectureTool,
-    impact::AnalyzeImpactTool,
-    focus::FocusTool,
-};
-use emry_agent::llm::OpenAIProvider;
-use emry_config::AgentConfig;
+use emry_agent::cortex::tools::edit::PendingEdits;
+use emry_agent::cortex::tools::registry::{default_tools, edit_tools, shell_tools};
+use emry_agent::llm::{OpenAIProvider, UsageTotals};
 use emry_agent::project as agent_context;
-use emry_engine::search::service::SearchService;
-use emry_agent::ops::fs::FsTool;
-use emry_agent::ops::graph::GraphTool;
-use emry_agent::ops::search::Search;
-use emry_agent::ops::architecture::ArchitectureTool;
+use emry_config::AgentStrategy;
+use emry_core::patch::parse_patch;
+use emry_core::scope::PathScope;
 
+use std::io::Write;
 use std::path::Path;
 use std::sync::Arc;
 
 use super::utils::render_markdown_answer;
 
-pub async fn handle_ask(query: String, verbose: bool, config_path: Option<&Path>) -> Result<()> {
+/// Answer `query`, saving the exchange as a conversation. With `resume`
+/// (`--continue`) or `session`, the latest or the given conversation is
+/// continued, in its scope unless `scope` overrides it. With `edit`, the
+/// agent may also propose file changes, applied once the user confirms them.
+/// With `rag`, or `agent.strategy: rag`, the question is answered in one call
+/// from a single search instead.
+pub async fn handle_ask(
+    query: String,
+    scope: Vec<String>,
+    verbose: bool,
+    resume: bool,
+    session: Option<String>,
+    edit: bool,
+    rag: bool,
+    config_path: Option<&Path>,
+) -> Result<()> {
     use super::ui;
     use console::Style;
 
+    if edit {
+        super::utils::ensure_writable("ask --edit")?;
+        super::utils::ensure_no_daemon().await?;
+    }
+
     if verbose {
         ui::print_header(&format!("Query: {}", query));
     }
 
-    let ctx = Arc::new(agent_context::RepoContext::from_env(config_path).await?);
+    let mut ctx = agent_context::RepoContext::from_env(config_path).await?;
+    let conversations = ConversationStore::new(&ctx.root);
+    let mut conversation = match session {
+        Some(id) => conversations.load(&id)?,
+        None if resume => conversations
+            .latest()?
+            .ok_or_else(|| anyhow!("no earlier conversation in this repository to continue"))?,
+        None => Conversation::new("ask", scope.clone()),
+    };
+    let scope = if scope.is_empty() { conversation.scope.clone() } else { scope };
+    conversation.scope = scope.clone();
+    if verbose && !conversation.turns.is_empty() {
+        ui::print_header(&format!("Continuing conversation {} ({} earlier turns)", conversation.id, conversation.turns.len()));
+    }
+    if !scope.is_empty() {
+        ctx = ctx.with_scope(PathScope::new(&scope)?);
+    }
+    let ctx = Arc::new(ctx);
 
     let api_key = std::env::var("OPENAI_API_KEY").context("OPENAI_API_KEY environment variable not set")?;
     let model = std::env::var("OPENAI_MODEL").unwrap_or_else(|_| "gpt-4o-mini".to_string());
-    let llm = OpenAIProvider::new(model, api_key, 60)?;
+    let llm = OpenAIProvider::new(model, api_key, 60)?.with_cache(ctx.llm_cache());
     
-    let _ = ctx.surreal_store.clone()
+    let store = ctx.surreal_store.clone()
         .ok_or_else(|| anyhow::anyhow!("SurrealStore not initialized in context"))?;
-    let search_service = Arc::new(SearchService::new(
-        ctx.surreal_store.clone().unwrap(),
-        ctx.embedder.clone(),
-    ));
-    
-    let mut agent_ctx = AgentContext::new(
-        ctx.clone(),
-        search_service.clone(),
-        AgentConfig::default(),
-    );
-
-    let search_impl = Arc::new(Search::new(ctx.clone(), search_service.clone()));
-    let search_tool = SearchCodeTool::new(search_impl);
-
-    let graph_impl = Arc::new(GraphTool::new(ctx.clone()));
-    let graph_tool = InspectGraphTool::new(graph_impl.clone(), ctx.clone());
-    let find_refs_tool = FindReferencesTool::new(graph_impl.clone());
-    let goto_def_tool = GoToDefinitionTool::new(graph_impl.clone());
-    let get_type_def_tool = GetTypeDefinitionTool::new(graph_impl.clone());
-    
-    let fs_impl = Arc::new(FsTool::new(ctx.clone()));
-
-    let arch_impl = Arc::new(ArchitectureTool::new(ctx.clone()));
-    let arch_tool = DescribeArchitectureTool::new(
-        arch_impl.clone(), 
-        fs_impl.clone(), 
-        llm.clone(), 
-        ctx.clone(), 
-        search_service.clone()
-    );
-
-    let impact_tool = AnalyzeImpactTool::new(
-        ctx.clone(),
-        llm.clone(),
-        fs_impl.clone(),
-        graph_impl.clone(),
-        search_service.clone()
-    )?;
+    let search_service = Arc::new(ctx.search_service(store.clone()));
     
-    let focus_tool = FocusTool::new(ctx.clone())?;
-
-    let fs_tool = ReadFileTool::new(fs_impl.clone());
-    let list_files_tool = ListFilesTool::new(fs_impl.clone());
-    let view_outline_tool = ViewFileOutlineTool::new(fs_impl.clone());
-    let view_code_item_tool = ViewCodeItemTool::new(fs_impl.clone());
-    let view_codebase_map_tool = ViewCodebaseMapTool::new(fs_impl.clone());
-
-    let read_files_macro_tool = ReadFilesMacroTool::new(fs_impl.clone());
-    let explore_module_tool = ExploreModuleTool::new(fs_impl.clone());
-    let find_usages_tool = FindUsagesTool::new(graph_impl.clone());
-
-    agent_ctx.register_tool(Arc::new(search_tool));
-    agent_ctx.register_tool(Arc::new(graph_tool));
-    agent_ctx.register_tool(Arc::new(fs_tool));
-    agent_ctx.register_tool(Arc::new(list_files_tool));
-    agent_ctx.register_tool(Arc::new(view_outline_tool));
-    agent_ctx.register_tool(Arc::new(view_code_item_tool));
-    agent_ctx.register_tool(Arc::new(view_codebase_map_tool));
-    agent_ctx.register_tool(Arc::new(find_refs_tool));
-    agent_ctx.register_tool(Arc::new(goto_def_tool));
-    agent_ctx.register_tool(Arc::new(get_type_def_tool));
-    agent_ctx.register_tool(Arc::new(read_files_macro_tool));
-    agent_ctx.register_tool(Arc::new(explore_module_tool));
-    agent_ctx.register_tool(Arc::new(find_usages_tool));
-    agent_ctx.register_tool(Arc::new(arch_tool));
-    agent_ctx.register_tool(Arc::new(impact_tool));
-    agent_ctx.register_tool(Arc::new(focus_tool));
-
+    let mut agent_config = ctx.config.agent.clone();
+    if rag {
+        agent_config.strategy = AgentStrategy::Rag;
+    }
+    let rag = agent_config.strategy == AgentStrategy::Rag;
+    if rag && edit {
+        bail!("--edit needs the agent's tools; it can't be combined with agent.strategy: rag");
+    }
+    let mut agent_ctx = AgentContext::new(ctx.clone(), search_service.clone(), agent_config);
 
+    for tool in default_tools(ctx.clone(), search_service.clone(), Some(&llm))?.into_iter().chain(shell_tools(ctx.clone())) {
+        agent_ctx.register_tool(tool);
+    }
+    let edits = PendingEdits::default();
+    if edit {
+        for tool in edit_tools(ctx.clone(), edits.clone()) {
+            agent_ctx.register_tool(tool);
+        }
+    }
+    conversation.restore(&mut agent_ctx);
 
     let mut cortex = Cortex::new(agent_ctx, llm);
+    let mut streamed = false;
+
+    // Tools enforce the scope; telling the model up front saves it from probing the boundary.
+    // In RAG mode the prompt is also the search query, and already asks for citations.
+    let mut prompt = if scope.is_empty() || rag {
+        query.clone()
+    } else {
+        format!("{}\n\n(Only files matching {} are available; answer from those.)", query, scope.join(", "))
+    };
+    let require_citations = ctx.config.agent.require_citations;
+    if require_citations && !rag {
+        prompt.push_str("\n\n(Cite the code you rely on as `path:line` or `path:start-end`.)");
+    }
+    if edit {
+        prompt.push_str(
+            "\n\n(Edit mode: make the changes this calls for with write_file or apply_patch, reading each file \
+             before changing it, then summarize them in your final answer. The user reviews the combined diff \
+             before anything is written.)",
+        );
+    }
+
+    let answer = cortex.run(&prompt, |event| {
+        if let emry_agent::cortex::CortexEvent::AnswerDelta(delta) = &event {
+            // Citations are checked on the whole answer, which is printed after.
+            if require_citations {
+                return;
+            }
+            if !streamed {
+                ui::print_header("Final Answer");
+                streamed = true;
+            }
+            print!("{}", delta);
+            let _ = std::io::stdout().flush();
+            return;
+        }
+
+        if !verbose {
+            // Compact live trace so long runs don't look stalled.
+            if let emry_agent::cortex::CortexEvent::ToolCall { name, args } = &event {
+                println!("{}", Style::new().dim().apply_to(format!("→ {}({})", name, args)));
+            }
+            return;
+        }
 
-    let answer = cortex.run(&query, |event| {
-        if verbose {
-            match event {
-                emry_agent::cortex::CortexEvent::StepStart(step) => {
-                    println!("\n{}", Style::new().dim().apply_to(format!("── Step {} ──", step)));
-                }
-                emry_agent::cortex::CortexEvent::Thought(thought) => {
-                    ui::print_panel("Thought", &thought, Style::new().green(), Some(Style::new().dim()));
-                }
-                emry_agent::cortex::CortexEvent::ToolCall { name, args } => {
-                    ui::print_panel("Tool Call", &format!("{}({})", name, args), Style::new().yellow(), Some(Style::new().dim()));
-                }
-                emry_agent::cortex::CortexEvent::ToolResult { name: _, result } => {
-                     let truncated = if result.len() > 300 {
-                         format!("{}...", &result[..300])
-                     } else {
-                         result
-                     };
-                     ui::print_panel("Observation", &truncated, Style::new().blue(), Some(Style::new().dim()));
-                }
+        match event {
+            emry_agent::cortex::CortexEvent::StepStart(step) => {
+                println!("\n{}", Style::new().dim().apply_to(format!("── Step {} ──", step)));
+            }
+            emry_agent::cortex::CortexEvent::Thought(thought) => {
+                ui::print_panel("Thought", &thought, Style::new().green(), Some(Style::new().dim()));
+            }
+            emry_agent::cortex::CortexEvent::ToolCall { name, args } => {
+                ui::print_panel("Tool Call", &format!("{}({})", name, args), Style::new().yellow(), Some(Style::new().dim()));
             }
+            emry_agent::cortex::CortexEvent::ToolResult { name: _, result } => {
+                 let truncated = if result.len() > 300 {
+                     format!("{}...", &result[..300])
+                 } else {
+                     result
+                 };
+                 ui::print_panel("Observation", &truncated, Style::new().blue(), Some(Style::new().dim()));
+            }
+            emry_agent::cortex::CortexEvent::AnswerDelta(_) => {}
         }
     }).await?;
-    
-    ui::print_header("Final Answer");
-    println!("{}", render_markdown_answer(&answer));
 
+    let answer = if require_citations {
+        let checked = citations::verify(store.as_ref(), &ctx.root, &answer).await?;
+        citations::enforce(&answer, &checked)
+    } else {
+        answer
+    };
+
+    if streamed {
+        println!();
+    } else {
+        ui::print_header("Final Answer");
+        println!("{}", render_markdown_answer(&answer));
+    }
+
+    cortex.ctx.record_turn(query.clone(), answer.clone());
+    save_conversation(&conversations, &mut conversation, &mut cortex).await;
+    // On stderr, so piping the answer leaves it out.
+    eprintln!("{}", Style::new().dim().apply_to(usage_summary(&cortex.llm.usage())));
+
+    if edit {
+        review_edits(&ctx, &edits, &conversation.id).await?;
+    }
+
+    let summary = answer.lines().map(str::trim).find(|l| !l.is_empty()).map(|l| l.chars().take(120).collect());
+    super::history::record_query("ask", &query, summary);
+
+    Ok(())
+}
+
+/// Show the edits proposed in `--edit` mode and apply them once confirmed.
+/// Declined, conflicting or unattended edits are saved as a patch for
+/// `emry apply` instead.
+async fn review_edits(ctx: &agent_context::RepoContext, edits: &PendingEdits, name: &str) -> Result<()> {
+    use super::ui;
+    use dialoguer::{theme::ColorfulTheme, Confirm};
+
+    if edits.is_empty() {
+        println!("\nNo edits were proposed.");
+        return Ok(());
+    }
+    let patch = edits.patch();
+    let files = edits.files().len();
+    ui::print_header(&format!("Proposed Edits ({} file{})", files, if files == 1 { "" } else { "s" }));
+    ui::print_diff(&patch);
+
+    // Files may have changed while the agent worked; the patch engine merges with that.
+    let plans = super::apply::plan(&ctx.root, &parse_patch(&patch)?)?;
+    let conflicts = super::apply::conflict_count(&plans);
+    if conflicts > 0 {
+        super::apply::print_plan(&plans);
+        println!("{}", ui::palette().warning.apply_to(format!("{} hunk(s) conflict with the working tree.", conflicts)));
+    }
+    let confirmed = conflicts == 0
+        && console::user_attended()
+        && Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt("Apply these edits?")
+            .default(false)
+            .interact()?;
+    if !confirmed {
+        let dir = ctx.root.join(".codeindex");
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join(format!("ask-{}.patch", name));
+        std::fs::write(&path, &patch).with_context(|| format!("failed to write {}", path.display()))?;
+        println!("Nothing was written. The edits were saved to {}; apply them with `emry apply {}`.", path.display(), path.display());
+        return Ok(());
+    }
+
+    let touched = super::apply::write_plan(&plans)?;
+    ui::print_success(&format!("Edited {} file(s)", touched.len()));
+    if !touched.is_empty() {
+        match super::apply::reindex_touched("Ask", ctx, touched).await {
+            Ok(updated) => ui::print_success(&format!("Re-indexed {} file(s)", updated)),
+            Err(e) => println!("{}", ui::palette().warning.apply_to(format!("Index not updated: {}", e))),
+        }
+    }
     Ok(())
 }
+
+/// One line of LLM calls, tokens and estimated cost, e.g. for after an answer.
+pub fn usage_summary(usage: &UsageTotals) -> String {
+    let mut line = format!("LLM usage: {} call{}", usage.calls, if usage.calls == 1 { "" } else { "s" });
+    if usage.cached_calls > 0 {
+        line.push_str(&format!(" ({} cached)", usage.cached_calls));
+    }
+    line.push_str(&format!(
+        ", {} prompt + {} completion tokens, ≈${:.4}",
+        usage.prompt_tokens, usage.completion_tokens, usage.cost_usd
+    ));
+    if usage.unpriced_calls > 0 {
+        line.push_str(&format!(" ({} call{} not priced)", usage.unpriced_calls, if usage.unpriced_calls == 1 { "" } else { "s" }));
+    }
+    line
+}
+
+/// Record the latest turn in `conversation`, summarizing older turns once
+/// there are more than the prompt shows, and save it. Failures are logged:
+/// the answer was already given.
+pub async fn save_conversation(store: &ConversationStore, conversation: &mut Conversation, cortex: &mut Cortex) {
+    if let Err(e) = compact(&mut cortex.ctx, &cortex.llm).await {
+        tracing::warn!("Failed to summarize earlier turns: {}", e);
+    }
+    conversation.update(&cortex.ctx);
+    if super::utils::read_only() {
+        return;
+    }
+    if let Err(e) = store.save(conversation) {
+        tracing::warn!("Failed to save conversation {}: {}", conversation.id, e);
+    }
+}
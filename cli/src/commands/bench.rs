@@ -0,0 +1,305 @@
+//! `emry bench-index`: indexing throughput and query latency on a synthetic repository.
+//!
+//! The corpus is generated from a seed, so runs with the same shape index the
+//! same files: nested module directories of Rust, Python or TypeScript files,
+//! each defining documented functions that call functions in other files by
+//! name, which gives the graph resolver cross-file work. Every `--variant`
+//! config indexes the corpus from scratch and then answers the same queries,
+//! half identifiers from the corpus and half phrases from its doc comments.
+//! The corpus lives in a temporary directory, removed afterwards unless
+//! `--keep` is given.
+
+use anyhow::{anyhow, Result};
+use clap::{Parser, ValueEnum};
+use emry_agent::project::{Components, RepoContext};
+use emry_config::Config;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use super::index::{run_index, DEFAULT_BATCH_SIZE};
+use super::ui;
+
+/// Words that names and doc comments are made of.
+const NOUNS: &[&str] = &[
+    "cache", "request", "session", "token", "index", "buffer", "record", "schema", "route", "query", "config",
+    "worker", "ledger", "invoice", "account", "payload", "cursor", "snapshot", "policy", "metric",
+];
+const VERBS: &[&str] = &[
+    "parse", "load", "store", "flush", "render", "resolve", "validate", "merge", "encode", "decode", "fetch",
+    "apply", "build", "split", "rotate", "publish",
+];
+
+#[derive(Parser)]
+pub struct BenchArgs {
+    /// Number of files in the synthetic repository
+    #[arg(long, value_name = "N_FILES")]
+    pub synthetic: usize,
+    /// Functions defined in each file
+    #[arg(long, default_value_t = 12)]
+    pub functions_per_file: usize,
+    /// Levels of nested module directories
+    #[arg(long, default_value_t = 3)]
+    pub dir_depth: usize,
+    /// Calls from each function into other files
+    #[arg(long, default_value_t = 2)]
+    pub calls_per_function: usize,
+    /// Language of the generated files
+    #[arg(long, value_enum, default_value_t = BenchLanguage::Mixed)]
+    pub language: BenchLanguage,
+    /// Queries timed after each index build
+    #[arg(long, default_value_t = 50)]
+    pub queries: usize,
+    /// Config file to benchmark; repeat to compare several (default: the current config)
+    #[arg(long, value_name = "FILE")]
+    pub variant: Vec<PathBuf>,
+    /// Seed for the generated corpus and queries
+    #[arg(long, default_value_t = 1)]
+    pub seed: u64,
+    /// Keep the generated repository and print its path
+    #[arg(long, default_value_t = false)]
+    pub keep: bool,
+    /// Print results as JSON
+    #[arg(long, default_value_t = false)]
+    pub json: bool,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum, Debug)]
+pub enum BenchLanguage {
+    Rust,
+    Python,
+    Typescript,
+    /// A third of each
+    Mixed,
+}
+
+#[derive(Debug, Serialize)]
+struct BenchResult {
+    config: String,
+    embedding: String,
+    files: usize,
+    chunks: usize,
+    embedded_chunks: usize,
+    index_secs: f64,
+    files_per_sec: f64,
+    index_bytes: u64,
+    query_p50_ms: f64,
+    query_p95_ms: f64,
+    query_max_ms: f64,
+}
+
+pub async fn handle_bench_index(args: BenchArgs, config_path: Option<&Path>) -> Result<()> {
+    if args.synthetic == 0 {
+        return Err(anyhow!("--synthetic needs at least one file"));
+    }
+    // Paths are resolved before moving into the generated repository.
+    let variants: Vec<Option<PathBuf>> = if args.variant.is_empty() {
+        let current = config_path.map(Path::to_path_buf).or_else(emry_config::loader::find_config_file);
+        vec![current.map(std::fs::canonicalize).transpose()?]
+    } else {
+        args.variant.iter().map(|p| std::fs::canonicalize(p).map(Some)).collect::<std::io::Result<_>>()?
+    };
+
+    let root = std::env::temp_dir().join(format!("emry-bench-{}", std::process::id()));
+    let mut rng = Rng(args.seed.max(1));
+    let names = generate(&root, &args, &mut rng)?;
+    let queries: Vec<String> = (0..args.queries)
+        .map(|i| match i % 2 {
+            0 => names[rng.below(names.len())].clone(),
+            _ => format!("{} {}", VERBS[rng.below(VERBS.len())], NOUNS[rng.below(NOUNS.len())]),
+        })
+        .collect();
+
+    let previous_dir = std::env::current_dir()?;
+    std::env::set_current_dir(&root)?;
+    let mut results = Vec::new();
+    let mut outcome = Ok(());
+    for variant in &variants {
+        match bench_variant(variant.as_deref(), &queries, args.json).await {
+            Ok(result) => results.push(result),
+            Err(e) => {
+                outcome = Err(e);
+                break;
+            }
+        }
+    }
+    std::env::set_current_dir(previous_dir)?;
+    if args.keep {
+        eprintln!("Synthetic repository kept at {}", root.display());
+    } else {
+        let _ = std::fs::remove_dir_all(&root);
+    }
+    outcome?;
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&results)?);
+        return Ok(());
+    }
+    ui::print_header(&format!(
+        "Index benchmark: {} files x {} functions, {} queries",
+        args.synthetic, args.functions_per_file, queries.len()
+    ));
+    for r in &results {
+        println!("{}", console::Style::new().bold().apply_to(&r.config));
+        ui::print_key_value("Embedding", &r.embedding);
+        ui::print_key_value(
+            "Indexing",
+            &format!("{:.1}s, {:.0} files/s ({} files, {} chunks, {} embedded)", r.index_secs, r.files_per_sec, r.files, r.chunks, r.embedded_chunks),
+        );
+        ui::print_key_value("Index size", &format!("{:.1} MiB", r.index_bytes as f64 / (1024.0 * 1024.0)));
+        ui::print_key_value(
+            "Query latency",
+            &format!("p50 {:.1} ms, p95 {:.1} ms, max {:.1} ms", r.query_p50_ms, r.query_p95_ms, r.query_max_ms),
+        );
+        println!();
+    }
+    Ok(())
+}
+
+/// Full rebuild of the current directory's index under `config_path`, then the queries.
+async fn bench_variant(config_path: Option<&Path>, queries: &[String], quiet: bool) -> Result<BenchResult> {
+    let config = match config_path {
+        Some(path) => Config::from_file(path)?,
+        None => Config::load()?,
+    };
+    let label = config_path.map_or_else(|| "default config".to_string(), |p| p.display().to_string());
+    if !quiet {
+        println!("Indexing with {}...", label);
+    }
+
+    let started = Instant::now();
+    run_index(true, DEFAULT_BATCH_SIZE, config_path, None).await?;
+    let index_time = started.elapsed();
+
+    let ctx = RepoContext::open(config_path, Components::ALL).await?;
+    let store = ctx.surreal_store.clone()
+        .ok_or_else(|| anyhow!("SurrealStore not initialized after indexing"))?;
+    let files = store.count_files().await?;
+    let (chunks, embedded_chunks) = store.count_embedded_chunks().await?;
+    let service = ctx.search_service(store);
+
+    // The first search opens indexes and warms caches; it isn't counted.
+    if let Some(first) = queries.first() {
+        service.search(first, 10, None).await?;
+    }
+    let mut latencies: Vec<Duration> = Vec::with_capacity(queries.len());
+    for query in queries {
+        let started = Instant::now();
+        service.search(query, 10, None).await?;
+        latencies.push(started.elapsed());
+    }
+    latencies.sort();
+    let percentile = |p: f64| {
+        latencies
+            .get(((latencies.len() as f64 * p).ceil() as usize).saturating_sub(1))
+            .map_or(0.0, |d| d.as_secs_f64() * 1000.0)
+    };
+
+    Ok(BenchResult {
+        config: label,
+        embedding: format!("{:?} ({})", config.embedding.backend, config.embedding.model_name),
+        files,
+        chunks,
+        embedded_chunks,
+        index_secs: index_time.as_secs_f64(),
+        files_per_sec: files as f64 / index_time.as_secs_f64().max(f64::EPSILON),
+        index_bytes: dir_size(Path::new(".codeindex")),
+        query_p50_ms: percentile(0.5),
+        query_p95_ms: percentile(0.95),
+        query_max_ms: percentile(1.0),
+    })
+}
+
+/// Write the synthetic repository under `root` and return its function names.
+fn generate(root: &Path, args: &BenchArgs, rng: &mut Rng) -> Result<Vec<String>> {
+    let _ = std::fs::remove_dir_all(root);
+    let functions = args.functions_per_file.max(1);
+    let names: Vec<String> = (0..args.synthetic * functions)
+        .map(|n| format!("{}_{}_{}", VERBS[n % VERBS.len()], NOUNS[(n / VERBS.len()) % NOUNS.len()], n))
+        .collect();
+
+    for file in 0..args.synthetic {
+        let language = match args.language {
+            BenchLanguage::Mixed => [BenchLanguage::Rust, BenchLanguage::Python, BenchLanguage::Typescript][file % 3],
+            language => language,
+        };
+        // Files spread over directories eight wide at each level.
+        let mut dir = root.join("src");
+        let mut rest = file;
+        for _ in 0..args.dir_depth {
+            dir = dir.join(format!("{}_{}", NOUNS[rest % NOUNS.len()], rest % 8));
+            rest /= 8;
+        }
+        std::fs::create_dir_all(&dir)?;
+
+        let mut source = String::new();
+        for f in 0..functions {
+            let name = &names[file * functions + f];
+            let callees: Vec<&str> = (0..args.calls_per_function).map(|_| names[rng.below(names.len())].as_str()).collect();
+            let doc = format!(
+                "{} the {} {} before it is {}ed",
+                VERBS[rng.below(VERBS.len())],
+                NOUNS[rng.below(NOUNS.len())],
+                NOUNS[rng.below(NOUNS.len())],
+                VERBS[rng.below(VERBS.len())]
+            );
+            source.push_str(&function(language, name, &doc, &callees));
+        }
+        let extension = match language {
+            BenchLanguage::Python => "py",
+            BenchLanguage::Typescript => "ts",
+            _ => "rs",
+        };
+        std::fs::write(dir.join(format!("file_{}.{}", file, extension)), source)?;
+    }
+    Ok(names)
+}
+
+fn function(language: BenchLanguage, name: &str, doc: &str, callees: &[&str]) -> String {
+    match language {
+        BenchLanguage::Python => {
+            let calls: String = callees.iter().map(|c| format!("    total += {}(total)\n", c)).collect();
+            format!("def {}(total):\n    \"\"\"{}.\"\"\"\n{}    return total + 1\n\n\n", name, doc, calls)
+        }
+        BenchLanguage::Typescript => {
+            let calls: String = callees.iter().map(|c| format!("  total += {}(total);\n", c)).collect();
+            format!("/** {}. */\nexport function {}(total: number): number {{\n{}  return total + 1;\n}}\n\n", doc, name, calls)
+        }
+        _ => {
+            let calls: String = callees.iter().map(|c| format!("    total += {}(total);\n", c)).collect();
+            format!("/// {}.\npub fn {}(mut total: u64) -> u64 {{\n{}    total + 1\n}}\n\n", doc, name, calls)
+        }
+    }
+}
+
+fn dir_size(dir: &Path) -> u64 {
+    walkdir(dir).iter().filter_map(|p| std::fs::metadata(p).ok()).map(|m| m.len()).sum()
+}
+
+fn walkdir(dir: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let mut stack = vec![dir.to_path_buf()];
+    while let Some(dir) = stack.pop() {
+        for entry in std::fs::read_dir(&dir).into_iter().flatten().flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                stack.push(path);
+            } else {
+                files.push(path);
+            }
+        }
+    }
+    files
+}
+
+/// xorshift64: reproducible without a dependency.
+struct Rng(u64);
+
+impl Rng {
+    fn below(&mut self, n: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % n as u64) as usize
+    }
+}
@@ -0,0 +1,328 @@
+//! Full-screen browser over ranked search hits (`emry search --interactive`).
+//!
+//! The hits are listed at the top with the selected one highlighted and its
+//! snippet previewed underneath, syntax-highlighted and numbered by file
+//! line. Up/Down (or `j`/`k`) move the selection, Enter opens the hit in
+//! `$VISUAL`/`$EDITOR` at its first line and comes back to the list, and
+//! `q` or Esc quits.
+
+use anyhow::{Context, Result};
+use console::{truncate_str, Key, Style, Term};
+use emry_core::models::{Language, SearchHit, SearchOutput};
+use std::io::Write;
+use std::path::Path;
+use std::process::Command;
+
+use super::ui::palette;
+
+/// Most rows the hit list takes; the rest of the screen previews.
+const MAX_LIST_ROWS: usize = 10;
+
+/// Browse `output` until the user quits; prints the hits instead when stdout
+/// isn't a terminal.
+pub fn browse(output: &SearchOutput) -> Result<()> {
+    let term = Term::stdout();
+    if !term.is_term() || output.hits.is_empty() {
+        super::search::print_hits(output, false, None);
+        return Ok(());
+    }
+
+    let mut screen = Screen::enter(term)?;
+    let mut selected = 0;
+    let mut status = String::new();
+    loop {
+        screen.draw(output, selected, &status)?;
+        status.clear();
+        match screen.term.read_key()? {
+            Key::ArrowUp | Key::Char('k') => selected = selected.saturating_sub(1),
+            Key::ArrowDown | Key::Char('j') => selected = (selected + 1).min(output.hits.len() - 1),
+            Key::PageUp => selected = selected.saturating_sub(MAX_LIST_ROWS),
+            Key::PageDown => selected = (selected + MAX_LIST_ROWS).min(output.hits.len() - 1),
+            Key::Home => selected = 0,
+            Key::End => selected = output.hits.len() - 1,
+            Key::Enter => {
+                let hit = &output.hits[selected];
+                if hit.origin.as_deref().is_some_and(|origin| origin != "local") {
+                    status = format!("{} is on {}, not this machine", hit.file_path, hit.origin.as_deref().unwrap_or(""));
+                    continue;
+                }
+                screen.leave()?;
+                let opened = open_in_editor(Path::new(&hit.file_path), hit.start_line);
+                screen = Screen::enter(Term::stdout())?;
+                if let Err(e) = opened {
+                    status = format!("{:#}", e);
+                }
+            }
+            Key::Escape | Key::Char('q') => return Ok(()),
+            _ => {}
+        }
+    }
+}
+
+/// The terminal's alternate screen, left again (cursor restored) on drop so
+/// the shell gets its scrollback back even when browsing fails.
+struct Screen {
+    term: Term,
+    active: bool,
+}
+
+impl Screen {
+    fn enter(term: Term) -> Result<Self> {
+        term.write_str("\x1b[?1049h")?;
+        term.hide_cursor()?;
+        Ok(Screen { term, active: true })
+    }
+
+    fn leave(&mut self) -> Result<()> {
+        if self.active {
+            self.active = false;
+            self.term.show_cursor()?;
+            self.term.write_str("\x1b[?1049l")?;
+        }
+        Ok(())
+    }
+
+    fn draw(&mut self, output: &SearchOutput, selected: usize, status: &str) -> Result<()> {
+        let (rows, cols) = self.term.size();
+        let (rows, cols) = (rows as usize, cols as usize);
+        let dim = Style::new().dim();
+        let mut lines = Vec::with_capacity(rows);
+
+        lines.push(palette().heading.apply_to(format!("Searching for: {}", output.query)).to_string());
+        let list_rows = output.hits.len().min(MAX_LIST_ROWS).min(rows.saturating_sub(6) / 2).max(1);
+        let first = (selected + 1).saturating_sub(list_rows);
+        for (i, hit) in output.hits.iter().enumerate().skip(first).take(list_rows) {
+            let entry = format!(" {} ", list_entry(hit));
+            let entry = truncate_str(&entry, cols, "…");
+            lines.push(if i == selected { Style::new().reverse().apply_to(entry).to_string() } else { entry.to_string() });
+        }
+
+        let hit = &output.hits[selected];
+        let location = format!("── {}:{}-{} ", hit.file_path, hit.start_line, hit.end_line);
+        let rule = format!("{}{}", location, "─".repeat(cols.saturating_sub(console::measure_text_width(&location))));
+        lines.push(palette().location.apply_to(truncate_str(&rule, cols, "")).to_string());
+
+        let preview_rows = rows.saturating_sub(lines.len() + 1);
+        let syntax = Syntax::for_language(&Language::from_path(Path::new(&hit.file_path)));
+        let mut in_comment = false;
+        for (i, line) in hit.snippet.lines().take(preview_rows).enumerate() {
+            let code = highlight(&line.replace('\t', "    "), &syntax, &mut in_comment);
+            let numbered = format!("{} {}", dim.apply_to(format!("{:>5} │", hit.start_line + i)), code);
+            lines.push(truncate_str(&numbered, cols, "…").to_string());
+        }
+        while lines.len() + 1 < rows {
+            lines.push(String::new());
+        }
+
+        let footer = if status.is_empty() {
+            format!("{}/{}  ↑/↓ select · Enter open in editor · q quit", selected + 1, output.hits.len())
+        } else {
+            status.to_string()
+        };
+        let footer_style = if status.is_empty() { dim } else { palette().warning.clone() };
+        lines.push(footer_style.apply_to(truncate_str(&footer, cols, "…")).to_string());
+
+        self.term.move_cursor_to(0, 0)?;
+        self.term.clear_screen()?;
+        self.term.write_str(&lines.join("\r\n"))?;
+        self.term.flush()?;
+        Ok(())
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        let _ = self.leave();
+    }
+}
+
+/// `#rank path:start-end`, with where the hit came from when results are merged.
+fn list_entry(hit: &SearchHit) -> String {
+    let origin = hit.origin.as_deref().map(|o| format!("[{}] ", o)).unwrap_or_default();
+    let modified = if hit.modified_since_index { " (modified since index)" } else { "" };
+    format!("#{} {}{}:{}-{}{}", hit.rank, origin, hit.file_path, hit.start_line, hit.end_line, modified)
+}
+
+/// Open `path` at `line` in `$VISUAL`, else `$EDITOR`, else `vi`, and wait
+/// for it to exit. Editors that take `file:line` get that; the rest `+line`.
+fn open_in_editor(path: &Path, line: usize) -> Result<()> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .ok()
+        .filter(|e| !e.trim().is_empty())
+        .unwrap_or_else(|| "vi".to_string());
+    let mut words = editor.split_whitespace();
+    let program = words.next().unwrap_or("vi");
+    let mut command = Command::new(program);
+    command.args(words);
+
+    let name = Path::new(program).file_stem().and_then(|s| s.to_str()).unwrap_or("");
+    let at = format!("{}:{}", path.display(), line);
+    match name {
+        "code" | "code-insiders" | "codium" | "cursor" => command.arg("--goto").arg(at),
+        "subl" | "zed" | "hx" => command.arg(at),
+        _ => command.arg(format!("+{}", line)).arg(path),
+    };
+    let status = command.status().with_context(|| format!("could not start editor '{}'", program))?;
+    if !status.success() {
+        anyhow::bail!("editor '{}' exited with {}", program, status);
+    }
+    Ok(())
+}
+
+/// Just enough of a language's lexical syntax to color a preview.
+struct Syntax {
+    line_comment: Option<&'static str>,
+    /// `/* */` comments
+    block_comments: bool,
+    /// `'` opens strings rather than Rust's lifetimes and char literals only.
+    single_quote_strings: bool,
+    keywords: &'static [&'static str],
+}
+
+const RUST_KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern", "false", "fn", "for", "if",
+    "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return", "self", "Self", "static", "struct",
+    "super", "trait", "true", "type", "unsafe", "use", "where", "while",
+];
+const PYTHON_KEYWORDS: &[&str] = &[
+    "and", "as", "assert", "async", "await", "break", "class", "continue", "def", "del", "elif", "else", "except", "False",
+    "finally", "for", "from", "global", "if", "import", "in", "is", "lambda", "None", "nonlocal", "not", "or", "pass", "raise",
+    "return", "self", "True", "try", "while", "with", "yield",
+];
+const GO_KEYWORDS: &[&str] = &[
+    "break", "case", "chan", "const", "continue", "default", "defer", "else", "false", "for", "func", "go", "goto", "if",
+    "import", "interface", "map", "nil", "package", "range", "return", "select", "struct", "switch", "true", "type", "var",
+];
+const JS_KEYWORDS: &[&str] = &[
+    "async", "await", "break", "case", "catch", "class", "const", "continue", "default", "delete", "do", "else", "enum",
+    "export", "extends", "false", "finally", "for", "from", "function", "if", "implements", "import", "in", "instanceof",
+    "interface", "let", "new", "null", "of", "private", "protected", "public", "readonly", "return", "static", "super",
+    "switch", "this", "throw", "true", "try", "type", "typeof", "undefined", "var", "void", "while", "yield",
+];
+const C_FAMILY_KEYWORDS: &[&str] = &[
+    "abstract", "auto", "bool", "break", "case", "catch", "char", "class", "const", "continue", "default", "delete", "do",
+    "double", "else", "enum", "extends", "extern", "false", "final", "float", "for", "if", "implements", "import", "int",
+    "interface", "long", "namespace", "new", "null", "nullptr", "override", "package", "private", "protected", "public",
+    "return", "short", "sizeof", "static", "struct", "switch", "template", "this", "throw", "throws", "true", "try",
+    "typedef", "union", "unsigned", "using", "virtual", "void", "volatile", "while",
+];
+const RUBY_KEYWORDS: &[&str] = &[
+    "begin", "class", "def", "do", "else", "elsif", "end", "ensure", "false", "if", "module", "next", "nil", "require",
+    "rescue", "return", "self", "then", "true", "unless", "until", "when", "while", "yield",
+];
+const PHP_KEYWORDS: &[&str] = &[
+    "abstract", "as", "class", "const", "else", "elseif", "extends", "false", "foreach", "for", "function", "if",
+    "implements", "interface", "namespace", "new", "null", "private", "protected", "public", "return", "static", "this",
+    "throw", "true", "try", "use", "while",
+];
+
+impl Syntax {
+    fn for_language(language: &Language) -> Self {
+        let c_like = |keywords| Syntax { line_comment: Some("//"), block_comments: true, single_quote_strings: true, keywords };
+        let hash = |keywords| Syntax { line_comment: Some("#"), block_comments: false, single_quote_strings: true, keywords };
+        match language {
+            Language::Rust => Syntax { single_quote_strings: false, ..c_like(RUST_KEYWORDS) },
+            Language::Go => c_like(GO_KEYWORDS),
+            Language::JavaScript | Language::TypeScript | Language::Vue | Language::Svelte => c_like(JS_KEYWORDS),
+            Language::Java | Language::CSharp | Language::C | Language::Cpp => c_like(C_FAMILY_KEYWORDS),
+            Language::Php => c_like(PHP_KEYWORDS),
+            Language::Python => hash(PYTHON_KEYWORDS),
+            Language::Ruby => hash(RUBY_KEYWORDS),
+            Language::Yaml | Language::Toml => hash(&[]),
+            _ => Syntax { line_comment: None, block_comments: false, single_quote_strings: true, keywords: &[] },
+        }
+    }
+}
+
+/// `line` with comments, strings, numbers and keywords colored.
+/// `in_comment` carries an open `/* */` comment from line to line.
+fn highlight(line: &str, syntax: &Syntax, in_comment: &mut bool) -> String {
+    let comment = Style::new().dim().italic();
+    let string = Style::new().green();
+    let number = Style::new().cyan();
+    let keyword = Style::new().magenta().bold();
+
+    let mut out = String::new();
+    let mut i = 0;
+    while i < line.len() {
+        let rest = &line[i..];
+        if *in_comment {
+            let close = rest.find("*/");
+            let end = close.map_or(rest.len(), |e| e + 2);
+            *in_comment = close.is_none();
+            out.push_str(&comment.apply_to(&rest[..end]).to_string());
+            i += end;
+            continue;
+        }
+        if syntax.line_comment.is_some_and(|marker| rest.starts_with(marker)) {
+            out.push_str(&comment.apply_to(rest).to_string());
+            break;
+        }
+        if syntax.block_comments && rest.starts_with("/*") {
+            *in_comment = true;
+            out.push_str(&comment.apply_to("/*").to_string());
+            i += 2;
+            continue;
+        }
+
+        let c = rest.chars().next().unwrap();
+        let len = if c == '"' || c == '`' || (c == '\'' && syntax.single_quote_strings) {
+            string_len(rest, c)
+        } else if c == '\'' {
+            char_literal_len(rest)
+        } else {
+            None
+        };
+        if let Some(len) = len {
+            out.push_str(&string.apply_to(&rest[..len]).to_string());
+            i += len;
+            continue;
+        }
+
+        if c.is_alphanumeric() || c == '_' {
+            let end = rest.find(|ch: char| !(ch.is_alphanumeric() || ch == '_')).unwrap_or(rest.len());
+            let word = &rest[..end];
+            if c.is_ascii_digit() {
+                out.push_str(&number.apply_to(word).to_string());
+            } else if syntax.keywords.contains(&word) {
+                out.push_str(&keyword.apply_to(word).to_string());
+            } else {
+                out.push_str(word);
+            }
+            i += end;
+            continue;
+        }
+        out.push(c);
+        i += c.len_utf8();
+    }
+    out
+}
+
+/// Length of the string literal opening `rest` with `quote`, through its
+/// closing quote or the end of the line.
+fn string_len(rest: &str, quote: char) -> Option<usize> {
+    let mut escaped = false;
+    for (i, c) in rest.char_indices().skip(1) {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == quote {
+            return Some(i + c.len_utf8());
+        }
+    }
+    Some(rest.len())
+}
+
+/// Length of the Rust char literal opening `rest` (`'x'`, `'\n'`,
+/// `'\u{1F600}'`); `None` for a lifetime.
+fn char_literal_len(rest: &str) -> Option<usize> {
+    let first = rest[1..].chars().next()?;
+    if first == '\\' {
+        // Skip the escaped character, which may itself be a quote.
+        rest.get(3..)?.find('\'').filter(|&e| e < 10).map(|e| e + 4)
+    } else {
+        rest[1 + first.len_utf8()..].starts_with('\'').then(|| first.len_utf8() + 2)
+    }
+}
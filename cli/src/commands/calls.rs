@@ -0,0 +1,139 @@
+//! `emry callers` / `emry callees`: a symbol's transitive call tree.
+//!
+//! The same `calls` edges as `emry graph --kinds calls --direction ...
+//! --max-hops N`, resolved from a fuzzy name and printed as an indented tree.
+//! Callers are located at their call site and callees at their definition,
+//! so every line opens where the next hop happens.
+
+use anyhow::Result;
+use clap::Parser;
+use console::Style;
+use emry_agent::ops::graph::{CallTreeNode, GraphDirection, GraphTool};
+use emry_agent::project::{Components, RepoContext};
+use std::path::Path;
+use std::sync::Arc;
+
+use super::ui;
+
+#[derive(Parser)]
+pub struct CallTreeArgs {
+    /// Symbol name (fuzzy-matched) or node id
+    pub symbol: String,
+    /// Hops to follow
+    #[arg(long, default_value_t = 3)]
+    pub depth: usize,
+    /// Only match symbols in files whose path contains this
+    #[arg(long)]
+    pub file: Option<String>,
+    /// Print the tree as JSON
+    #[arg(long, default_value_t = false)]
+    pub json: bool,
+}
+
+/// Print the callers (`callers`) or callees of `args.symbol`.
+pub async fn handle_call_tree(args: CallTreeArgs, callers: bool, config_path: Option<&Path>) -> Result<()> {
+    let ctx = RepoContext::open(config_path, Components::STORE).await?;
+    if ctx.surreal_store.is_none() {
+        return Err(anyhow::anyhow!("SurrealStore not initialized. Run 'emry index' first."));
+    }
+    let graph = GraphTool::new(Arc::new(ctx));
+    let direction = if callers { GraphDirection::In } else { GraphDirection::Out };
+
+    let mut result = graph.call_tree(&args.symbol, direction, args.depth, args.file.as_deref()).await?;
+    if let Some(candidates) = result.candidates {
+        if args.json {
+            println!("{}", serde_json::json!({ "disambiguation": true, "candidates": candidates }));
+            return Ok(());
+        }
+        use dialoguer::{theme::ColorfulTheme, Select};
+
+        let items: Vec<String> = candidates
+            .iter()
+            .map(|c| format!("{} ({})\n   File: {}", c.label, c.kind, c.file_path))
+            .collect();
+        let Some(idx) = Select::with_theme(&ColorfulTheme::default())
+            .with_prompt(format!("{} symbols match '{}'", candidates.len(), args.symbol))
+            .default(0)
+            .items(&items)
+            .interact_opt()?
+        else {
+            println!("Selection cancelled");
+            return Ok(());
+        };
+        result = graph.call_tree(&candidates[idx].id, direction, args.depth, None).await?;
+    }
+    let tree = result.tree.ok_or_else(|| anyhow::anyhow!("Symbol '{}' not found.", args.symbol))?;
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&tree)?);
+        return Ok(());
+    }
+
+    let relation = if callers { "callers" } else { "callees" };
+    if tree.children.is_empty() {
+        println!("No indexed {} of {}.", relation, tree.label);
+        return Ok(());
+    }
+    let cwd = std::env::current_dir().unwrap_or_default();
+    let mut out = format!(
+        "{}  {}\n",
+        Style::new().bold().cyan().apply_to(&tree.label),
+        ui::palette().location.apply_to(location(&cwd, &tree.file_path, tree.line.map(|l| (l, 0))))
+    );
+    render(&mut out, &tree.children, "", callers, &tree.file_path, &cwd);
+    let total = count(&tree) - 1;
+    out.push_str(&format!(
+        "\n{} {} within {} hop{}\n",
+        total,
+        relation,
+        args.depth,
+        if args.depth == 1 { "" } else { "s" }
+    ));
+    ui::page(&out);
+    Ok(())
+}
+
+fn render(out: &mut String, nodes: &[CallTreeNode], prefix: &str, callers: bool, parent_file: &str, cwd: &Path) {
+    let dim = Style::new().dim();
+    for (i, node) in nodes.iter().enumerate() {
+        let last = i + 1 == nodes.len();
+        // A caller is shown at its call site; a callee at its definition, with the call in the parent.
+        let (place, note) = if callers {
+            let site = node.call_site.or(node.line.map(|l| (l, 0)));
+            (location(cwd, &node.file_path, site), String::new())
+        } else {
+            let note = node.call_site.map_or(String::new(), |(line, column)| {
+                format!("  called at {}", location(cwd, parent_file, Some((line, column))))
+            });
+            (location(cwd, &node.file_path, node.line.map(|l| (l, 0))), note)
+        };
+        let repeated = if node.repeated { "  (expanded elsewhere)" } else { "" };
+        out.push_str(&format!(
+            "{}{} {}  {}{}{}\n",
+            dim.apply_to(prefix),
+            dim.apply_to(if last { "└──" } else { "├──" }),
+            Style::new().bold().apply_to(&node.label),
+            ui::palette().location.apply_to(place),
+            dim.apply_to(note),
+            dim.apply_to(repeated),
+        ));
+        let child_prefix = format!("{}{}", prefix, if last { "    " } else { "│   " });
+        render(out, &node.children, &child_prefix, callers, &node.file_path, cwd);
+    }
+}
+
+/// `path:line[:column]` relative to `cwd`, as editors and terminals open it.
+fn location(cwd: &Path, file: &str, at: Option<(usize, usize)>) -> String {
+    let file = Path::new(file);
+    let file = file.strip_prefix(cwd).unwrap_or(file).display();
+    match at {
+        Some((line, 0)) => format!("{}:{}", file, line),
+        Some((line, column)) => format!("{}:{}:{}", file, line, column),
+        None => file.to_string(),
+    }
+}
+
+/// Distinct symbols in the tree, counting repeats once.
+fn count(node: &CallTreeNode) -> usize {
+    usize::from(!node.repeated) + node.children.iter().map(count).sum::<usize>()
+}
@@ -1,21 +1,21 @@
 use anyhow::Result;
 use emry_agent::ops::fs::FsTool;
-use emry_agent::project::RepoContext;
+use emry_agent::project::{Components, RepoContext};
 use std::path::Path;
 use std::sync::Arc;
 
 pub async fn handle_cat(paths: Vec<String>, config_path: Option<&Path>) -> Result<()> {
-    let ctx = Arc::new(RepoContext::from_env(config_path).await?);
+    let ctx = Arc::new(RepoContext::open(config_path, Components::NONE).await?);
     let fs_tool = FsTool::new(ctx.clone());
 
     let path_bufs: Vec<std::path::PathBuf> = paths.iter().map(std::path::PathBuf::from).collect();
     let results = fs_tool.read_files_concurrent(path_bufs).await;
 
+    let mut out = String::new();
     for (path, content) in results {
-        println!("--- {} ---", path.display());
-        println!("{}", content);
-        println!();
+        out.push_str(&format!("--- {} ---\n{}\n\n", path.display(), content));
     }
+    super::ui::page(&out);
 
     Ok(())
 }
@@ -0,0 +1,523 @@
+//! Interactive console chat over a persistent agent session.
+//!
+//! Every question runs the agent with the earlier turns in its prompt, and the
+//! answer's `path:line` citations are listed as numbered sources right after
+//! it. Slash commands search, open files and change the scope without leaving
+//! the session; `/open <n>` opens a source from the last answer or search.
+//! Output taller than the terminal (files, `/trace`) goes through `$PAGER`.
+//!
+//! Each answer and search is appended to a markdown log under
+//! `.codeindex/sessions/` as it happens, so nothing is lost with the terminal;
+//! `/export` writes the whole session to a markdown or JSON file. The
+//! conversation itself is saved next to the log after every answer, for
+//! `emry sessions` and `emry ask --continue`; once it outgrows the prompt,
+//! older turns are summarized into the agent's memory.
+
+use anyhow::{Context, Result};
+use console::Style;
+use dialoguer::theme::ColorfulTheme;
+use dialoguer::Confirm;
+use emry_agent::cortex::context::AgentContext;
+use emry_agent::cortex::conversation::{Conversation, ConversationStore};
+use emry_agent::cortex::citations::citations;
+use emry_agent::cortex::tools::registry::{default_tools, shell_tools};
+use emry_agent::cortex::{Cortex, CortexEvent};
+use emry_agent::llm::OpenAIProvider;
+use emry_agent::ops::fs::FsTool;
+use emry_agent::project::RepoContext;
+use emry_core::error::ErrorKind;
+use emry_core::scope::PathScope;
+use emry_engine::search::service::SearchService;
+use indicatif::ProgressBar;
+use serde::Serialize;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::task::JoinHandle;
+
+use super::ask::save_conversation;
+use super::index::{run_index, DEFAULT_BATCH_SIZE};
+use super::search::{print_hits, ranked_search_output};
+use super::ui;
+use super::utils::render_markdown_answer;
+
+const SEARCH_LIMIT: usize = 10;
+/// Lines shown by `/open path:line` after the cited line.
+const OPEN_CONTEXT_LINES: usize = 20;
+/// Lines of each cited span shown under an answer.
+const SOURCE_PREVIEW_LINES: usize = 3;
+
+const HELP: &str = "\
+/search <query>        ranked search over the index
+/open <n|path[:a[-b]]> show a numbered source, or a file (optionally a line range)
+/scope [glob...|off]   show, set or clear the paths retrieval is restricted to
+/trace                 page through the agent steps behind the last answer
+/reset                 forget the conversation so far
+/export [file]         save this session as markdown, or JSON for a .json file
+/help                  show this help
+/exit                  leave the chat";
+
+/// A file span cited by an answer or returned by `/search`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Source {
+    path: String,
+    start: usize,
+    end: usize,
+}
+
+struct Session {
+    /// Unscoped context; scoped contexts are derived from it.
+    base: Arc<RepoContext>,
+    ctx: Arc<RepoContext>,
+    search: Arc<SearchService>,
+    cortex: Cortex,
+    llm: OpenAIProvider,
+    sources: Vec<Source>,
+    /// Steps, tool calls and tool results of the last answer.
+    trace: Vec<String>,
+    log: SessionLog,
+    /// Saved after every answer, so `emry ask --continue` can pick it up.
+    conversation: Conversation,
+}
+
+/// Everything asked and found in one chat, mirrored to a markdown file as it grows.
+#[derive(Default)]
+struct SessionLog {
+    path: Option<PathBuf>,
+    entries: Vec<LogEntry>,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum LogEntry {
+    Answer { question: String, answer: String, sources: Vec<String> },
+    Search { query: String, results: Vec<String> },
+}
+
+pub async fn handle_chat(scope: Vec<String>, config_path: Option<&Path>) -> Result<()> {
+    let api_key = std::env::var("OPENAI_API_KEY").context("OPENAI_API_KEY environment variable not set")?;
+    let model = std::env::var("OPENAI_MODEL").unwrap_or_else(|_| "gpt-4o-mini".to_string());
+    let llm = OpenAIProvider::new(model, api_key, 60)?;
+    let mut scope = if scope.is_empty() { None } else { Some(PathScope::new(&scope)?) };
+
+    // Without an index, offer to build one in the background and open the
+    // session once it is ready; until then the prompt shows build progress.
+    let (mut session, mut build) = match RepoContext::from_env(config_path).await {
+        Ok(base) => (Some(Session::open(Arc::new(base), scope.take(), llm.clone(), None)?), None),
+        Err(e) if ErrorKind::of(&e) == ErrorKind::IndexNotFound && !super::utils::read_only() && offer_index_build()? => {
+            (None, Some(IndexBuild::spawn(config_path)))
+        }
+        Err(e) => return Err(e),
+    };
+
+    ui::print_header("emry chat");
+    println!("{}", Style::new().dim().apply_to("Ask about the codebase, or /help for commands. Ctrl-D to quit."));
+    if let Some(scope) = session.as_ref().and_then(|s| s.ctx.scope.as_ref()).or(scope.as_ref()) {
+        println!("{}", Style::new().dim().apply_to(format!("Scope: {}", scope.patterns().join(", "))));
+    }
+
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    loop {
+        let label = match &build {
+            Some(build) => format!("emry [{}]>", build.progress()),
+            None => "emry>".to_string(),
+        };
+        print!("\n{} ", ui::palette().prompt.apply_to(label));
+        std::io::stdout().flush()?;
+
+        let line = tokio::select! {
+            line = lines.next_line() => line?,
+            result = IndexBuild::finished(&mut build) => {
+                build = None;
+                if let Err(e) = result {
+                    return Err(e.context("indexing failed"));
+                }
+                let base = Arc::new(RepoContext::from_env(config_path).await?);
+                session = Some(Session::open(base, scope.take(), llm.clone(), None)?);
+                println!();
+                ui::print_success("Index ready.");
+                continue;
+            }
+        };
+        let Some(line) = line else {
+            println!();
+            break;
+        };
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let Some(session) = session.as_mut() else {
+            match line {
+                "/exit" | "/quit" => break,
+                "/help" => println!("{}", HELP),
+                _ => println!(
+                    "{}",
+                    Style::new().dim().apply_to("Still indexing; questions and commands work once the build finishes.")
+                ),
+            }
+            continue;
+        };
+        let result = match line.strip_prefix('/') {
+            Some(command) => match session.command(command).await {
+                Ok(true) => break,
+                Ok(false) => Ok(()),
+                Err(e) => Err(e),
+            },
+            None => session.ask(line).await,
+        };
+        // A failed turn shouldn't end the session.
+        if let Err(e) = result {
+            ui::print_error(&format!("{:#}", e));
+        }
+    }
+    if let Some(path) = session.as_ref().and_then(|s| s.log.written()) {
+        println!("{}", Style::new().dim().apply_to(format!("Session saved to {}", path.display())));
+    }
+    Ok(())
+}
+
+/// Ask whether to build a missing index; never asks when not attached to a terminal.
+fn offer_index_build() -> Result<bool> {
+    if !console::user_attended() {
+        return Ok(false);
+    }
+    Ok(Confirm::with_theme(&ColorfulTheme::default())
+        .with_prompt("This branch has no index yet. Build it in the background?")
+        .default(true)
+        .interact()?)
+}
+
+/// `emry index` running in a background task, reporting on a hidden progress bar.
+struct IndexBuild {
+    task: JoinHandle<Result<()>>,
+    status: ProgressBar,
+}
+
+impl IndexBuild {
+    fn spawn(config_path: Option<&Path>) -> Self {
+        let status = ProgressBar::hidden();
+        let config = config_path.map(Path::to_path_buf);
+        let bar = status.clone();
+        let task = tokio::spawn(async move { run_index(false, DEFAULT_BATCH_SIZE, config.as_deref(), Some(bar)).await });
+        Self { task, status }
+    }
+
+    /// Current stage and how far along it is, e.g. `Reading files 120/800`.
+    fn progress(&self) -> String {
+        match self.status.length() {
+            Some(len) if len > 0 => format!("{} {}/{}", self.status.message(), self.status.position(), len),
+            _ => self.status.message(),
+        }
+    }
+
+    /// Resolves when the build in `build` ends; never, if there is none.
+    async fn finished(build: &mut Option<IndexBuild>) -> Result<()> {
+        match build {
+            Some(build) => (&mut build.task).await?,
+            None => std::future::pending().await,
+        }
+    }
+}
+
+impl Session {
+    /// Open a session, continuing `conversation` when given.
+    fn open(base: Arc<RepoContext>, scope: Option<PathScope>, llm: OpenAIProvider, conversation: Option<Conversation>) -> Result<Self> {
+        let ctx = Arc::new(base.rescoped(scope));
+        let store = ctx.surreal_store.clone()
+            .ok_or_else(|| anyhow::anyhow!("SurrealStore not initialized in context"))?;
+        let search = Arc::new(ctx.search_service(store));
+
+        let mut agent_ctx = AgentContext::new(ctx.clone(), search.clone(), ctx.config.agent.clone());
+        for tool in default_tools(ctx.clone(), search.clone(), Some(&llm))?.into_iter().chain(shell_tools(ctx.clone())) {
+            agent_ctx.register_tool(tool);
+        }
+        let patterns = ctx.scope.as_ref().map(|s| s.patterns().to_vec()).unwrap_or_default();
+        let mut conversation = conversation.unwrap_or_else(|| Conversation::new("chat", Vec::new()));
+        conversation.scope = patterns;
+        conversation.restore(&mut agent_ctx);
+
+        Ok(Self {
+            base,
+            ctx,
+            search,
+            cortex: Cortex::new(agent_ctx, llm.clone()),
+            llm,
+            sources: Vec::new(),
+            trace: Vec::new(),
+            log: SessionLog::new(&base.root),
+            conversation,
+        })
+    }
+
+    async fn ask(&mut self, question: &str) -> Result<()> {
+        let mut prompt = format!(
+            "{}\n\n(Cite the code you rely on as `path:line` or `path:start-end`.)",
+            question
+        );
+        if let Some(scope) = &self.ctx.scope {
+            prompt.push_str(&format!(
+                "\n(Only files matching {} are available; answer from those.)",
+                scope.patterns().join(", ")
+            ));
+        }
+
+        let mut streamed = false;
+        let mut trace = Vec::new();
+        let answer = self.cortex.run(&prompt, |event| match event {
+            CortexEvent::AnswerDelta(delta) => {
+                if !streamed {
+                    println!();
+                    streamed = true;
+                }
+                print!("{}", delta);
+                let _ = std::io::stdout().flush();
+            }
+            CortexEvent::ToolCall { name, args } => {
+                println!("{}", Style::new().dim().apply_to(format!("→ {}({})", name, args)));
+                trace.push(format!("→ {}({})", name, args));
+            }
+            CortexEvent::StepStart(step) => trace.push(format!("\n── step {} ──", step)),
+            CortexEvent::Thought(thought) => trace.push(thought),
+            CortexEvent::ToolResult { name, result } => trace.push(format!("← {}\n{}", name, result.trim_end())),
+        }).await?;
+        self.trace = trace;
+
+        if streamed {
+            println!();
+        } else {
+            println!("\n{}", render_markdown_answer(&answer));
+        }
+
+        self.sources = citations(&answer)
+            .into_iter()
+            .map(|c| Source { path: c.path, start: c.start, end: c.end })
+            .filter(|s| self.ctx.root.join(&s.path).is_file() && self.ctx.in_scope(Path::new(&s.path)))
+            .collect();
+        self.print_sources();
+        self.log.record(LogEntry::Answer {
+            question: question.to_string(),
+            answer: answer.clone(),
+            sources: self.sources.iter().map(source_label).collect(),
+        });
+        self.cortex.ctx.record_turn(question.to_string(), answer);
+        let store = ConversationStore::new(&self.base.root);
+        save_conversation(&store, &mut self.conversation, &mut self.cortex).await;
+        Ok(())
+    }
+
+    fn print_sources(&self) {
+        if self.sources.is_empty() {
+            return;
+        }
+        println!("\n{}", Style::new().bold().apply_to("Sources"));
+        let fs = FsTool::new(self.ctx.clone());
+        for (i, source) in self.sources.iter().enumerate() {
+            println!(
+                "{} {}",
+                ui::palette().location.apply_to(format!("[{}]", i + 1)),
+                source_label(source)
+            );
+            let end = source.end.min(source.start + SOURCE_PREVIEW_LINES - 1);
+            if let Ok(text) = fs.read_file_span(Path::new(&source.path), source.start, end) {
+                for line in text.lines() {
+                    println!("    {}", Style::new().dim().apply_to(line.trim_end()));
+                }
+            }
+        }
+    }
+
+    /// Run a slash command; `Ok(true)` ends the session.
+    async fn command(&mut self, input: &str) -> Result<bool> {
+        let (name, arg) = input.split_once(char::is_whitespace).unwrap_or((input, ""));
+        let arg = arg.trim();
+        match name {
+            "exit" | "quit" => return Ok(true),
+            "help" => println!("{}", HELP),
+            "reset" => {
+                let agent = &mut self.cortex.ctx;
+                agent.transcript.clear();
+                agent.summary = None;
+                agent.memory.clear();
+                agent.history.clear();
+                self.conversation = Conversation::new("chat", self.conversation.scope.clone());
+                self.sources.clear();
+                self.trace.clear();
+                println!("Conversation cleared.");
+            }
+            "search" if !arg.is_empty() => {
+                let output = ranked_search_output(&self.ctx, &self.search, arg, SEARCH_LIMIT, false, false, false).await?;
+                print_hits(&output, false, None);
+                self.sources = output
+                    .hits
+                    .iter()
+                    .map(|h| Source { path: h.file_path.clone(), start: h.start_line, end: h.end_line })
+                    .collect();
+                self.log.record(LogEntry::Search {
+                    query: arg.to_string(),
+                    results: self.sources.iter().map(source_label).collect(),
+                });
+            }
+            "export" => {
+                let path = if arg.is_empty() {
+                    PathBuf::from(format!("emry-chat-{}.md", unix_time()))
+                } else {
+                    PathBuf::from(arg)
+                };
+                self.log.export(&path)?;
+                ui::print_success(&format!("Exported session to {}", path.display()));
+            }
+            "trace" if self.trace.is_empty() => println!("No agent trace yet; ask a question first."),
+            "trace" => ui::page(&format!("{}\n", self.trace.join("\n"))),
+            "open" if !arg.is_empty() => self.open_source(arg)?,
+            "scope" => self.set_scope(arg)?,
+            "search" | "open" => println!("Usage: /{} <{}>", name, if name == "search" { "query" } else { "n|path" }),
+            _ => println!("Unknown command /{}. Type /help for commands.", name),
+        }
+        Ok(false)
+    }
+
+    fn open_source(&self, arg: &str) -> Result<()> {
+        let source = match arg.parse::<usize>() {
+            Ok(n) => self
+                .sources
+                .get(n.wrapping_sub(1))
+                .cloned()
+                .ok_or_else(|| anyhow::anyhow!("no source [{}]; sources come from the last answer or /search", n))?,
+            Err(_) => parse_location(arg).ok_or_else(|| anyhow::anyhow!("expected <n> or <path[:start[-end]]>"))?,
+        };
+
+        // A bare line shows what follows it; no line shows the whole file.
+        let end = if source.start > 0 && source.end == source.start {
+            source.start + OPEN_CONTEXT_LINES
+        } else {
+            source.end
+        };
+        let text = FsTool::new(self.ctx.clone()).read_file_span(Path::new(&source.path), source.start, end)?;
+
+        ui::print_header(&source_label(&source));
+        let first = source.start.max(1);
+        let width = (first + text.lines().count()).to_string().len();
+        let numbered: String = text
+            .lines()
+            .enumerate()
+            .map(|(i, line)| format!("{} {}\n", Style::new().dim().apply_to(format!("{:>width$} │", first + i)), line))
+            .collect();
+        ui::page(&numbered);
+        Ok(())
+    }
+
+    fn set_scope(&mut self, arg: &str) -> Result<()> {
+        let scope = match arg {
+            "" => {
+                match &self.ctx.scope {
+                    Some(scope) => println!("Scope: {}", scope.patterns().join(", ")),
+                    None => println!("Scope: whole repository"),
+                }
+                return Ok(());
+            }
+            "off" | "clear" => None,
+            globs => Some(PathScope::new(&globs.split_whitespace().map(String::from).collect::<Vec<_>>())?),
+        };
+
+        let conversation = self.conversation.clone();
+        let log = std::mem::take(&mut self.log);
+        *self = Session::open(self.base.clone(), scope, self.llm.clone(), Some(conversation))?;
+        self.log = log;
+        match &self.ctx.scope {
+            Some(scope) => println!("Scope set to {}", scope.patterns().join(", ")),
+            None => println!("Scope cleared; searching the whole repository."),
+        }
+        Ok(())
+    }
+}
+
+impl SessionLog {
+    fn new(root: &Path) -> Self {
+        let path = root.join(".codeindex").join("sessions").join(format!("{}.md", unix_time()));
+        Self { path: Some(path).filter(|_| !super::utils::read_only()), entries: Vec::new() }
+    }
+
+    /// The log file, once something has been written to it.
+    fn written(&self) -> Option<&Path> {
+        self.path.as_deref().filter(|p| p.is_file())
+    }
+
+    fn record(&mut self, entry: LogEntry) {
+        if let Some(path) = &self.path {
+            let appended = path.parent().map_or(Ok(()), std::fs::create_dir_all).and_then(|_| {
+                let mut file = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+                file.write_all(entry.markdown().as_bytes())
+            });
+            // Losing the log shouldn't interrupt the chat; stop trying after the first failure.
+            if let Err(e) = appended {
+                tracing::warn!("Failed to write session log {}: {}", path.display(), e);
+                self.path = None;
+            }
+        }
+        self.entries.push(entry);
+    }
+
+    fn export(&self, path: &Path) -> Result<()> {
+        let content = if path.extension().is_some_and(|e| e == "json") {
+            serde_json::to_string_pretty(&self.entries)?
+        } else {
+            self.entries.iter().map(LogEntry::markdown).collect()
+        };
+        std::fs::write(path, content).with_context(|| format!("failed to write {}", path.display()))
+    }
+}
+
+impl LogEntry {
+    fn markdown(&self) -> String {
+        let (heading, body, items) = match self {
+            LogEntry::Answer { question, answer, sources } => (format!("## {}", question), answer.trim(), sources),
+            LogEntry::Search { query, results } => (format!("## /search {}", query), "", results),
+        };
+        let mut out = format!("{}\n\n", heading);
+        if !body.is_empty() {
+            out.push_str(&format!("{}\n\n", body));
+        }
+        for (i, item) in items.iter().enumerate() {
+            out.push_str(&format!("{}. `{}`\n", i + 1, item));
+        }
+        if !items.is_empty() {
+            out.push('\n');
+        }
+        out
+    }
+}
+
+fn unix_time() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+fn source_label(source: &Source) -> String {
+    match (source.start, source.end) {
+        (0, _) => source.path.clone(),
+        (start, end) if end > start => format!("{}:{}-{}", source.path, start, end),
+        (start, _) => format!("{}:{}", source.path, start),
+    }
+}
+
+/// Parse `path`, `path:line` or `path:start-end`.
+fn parse_location(arg: &str) -> Option<Source> {
+    let (path, range) = match arg.rsplit_once(':') {
+        Some((path, range)) if range.chars().next().is_some_and(|c| c.is_ascii_digit()) => (path, Some(range)),
+        _ => (arg, None),
+    };
+    let (start, end) = match range {
+        None => (0, 0),
+        Some(range) => match range.split_once('-') {
+            Some((a, b)) => (a.parse().ok()?, b.parse().ok()?),
+            None => {
+                let line = range.parse().ok()?;
+                (line, line)
+            }
+        },
+    };
+    Some(Source { path: path.trim_start_matches("./").to_string(), start, end })
+}
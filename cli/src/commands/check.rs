@@ -0,0 +1,65 @@
+//! CI gate on the static impact of a branch.
+//!
+//! Diffs the working tree against the merge-base with `--against`, finds the
+//! changed symbols and their callers in the code graph, and prints the public
+//! APIs among them as JSON on stdout. No LLM is involved, so the report is
+//! deterministic and cheap enough for every pipeline run.
+
+use anyhow::{Context, Result};
+use emry_agent::project::{Components, RepoContext};
+use emry_agent::workflows::impact::check_impact;
+use serde_json::json;
+use std::path::Path;
+
+use super::impact::git_diff;
+
+/// Returns `false` when more public APIs are impacted than `fail_on_impact` allows.
+pub async fn handle_check(against: String, fail_on_impact: Option<usize>, config_path: Option<&Path>) -> Result<bool> {
+    let base = merge_base(&against)?;
+    let diffs = git_diff(&base)?;
+
+    let ctx = RepoContext::open(config_path, Components::STORE).await?;
+    if ctx.surreal_store.is_none() {
+        return Err(anyhow::anyhow!("SurrealStore not initialized. Run 'emry index' first."));
+    }
+    let check = check_impact(&ctx, &diffs).await?;
+
+    let passed = fail_on_impact.map_or(true, |max| check.public_apis.len() <= max);
+    let report = json!({
+        "against": against,
+        "merge_base": base,
+        "passed": passed,
+        "fail_on_impact": fail_on_impact,
+        "changed_files": check.changed_files,
+        "changed_symbols": check.changed_symbols,
+        "downstream_callers": check.downstream_callers,
+        "impacted_public_apis": check.public_apis.len(),
+        "public_apis": check.public_apis,
+    });
+    println!("{}", serde_json::to_string_pretty(&report)?);
+
+    if !passed {
+        eprintln!(
+            "Impact check failed: {} public APIs impacted (limit {}).",
+            check.public_apis.len(),
+            fail_on_impact.unwrap_or_default()
+        );
+    }
+    Ok(passed)
+}
+
+/// The commit `rev` and HEAD diverged from, so only this branch's changes count.
+fn merge_base(rev: &str) -> Result<String> {
+    let output = std::process::Command::new("git")
+        .args(["merge-base", rev, "HEAD"])
+        .output()
+        .context("failed to run git merge-base")?;
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(
+            "git merge-base {} HEAD failed: {}",
+            rev,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
@@ -0,0 +1,152 @@
+//! `emry clones`: groups of near-identical code across files.
+//!
+//! Each embedded chunk is looked up in the vector index for its nearest
+//! chunks; pairs in different files at or above `--threshold` cosine
+//! similarity are linked, and linked chunks form a clone group. Exact copies,
+//! which the index stores once, join their group at similarity 1.
+
+use anyhow::{anyhow, bail, Result};
+use console::Style;
+use emry_agent::project::{Components, RepoContext};
+use emry_core::clones::{group_pairs, SimilarPair};
+use emry_core::models::ChunkLocation;
+use futures::stream::{self, StreamExt};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use super::ui;
+
+/// Neighbours looked up per chunk.
+const NEIGHBORS: usize = 10;
+/// Vector lookups in flight at once.
+const CONCURRENCY: usize = 8;
+
+#[derive(Debug, Serialize)]
+struct CloneGroup {
+    /// Cosine similarity of the group's least similar linked pair
+    similarity: f32,
+    /// Lines in the longest member
+    lines: usize,
+    members: Vec<ChunkLocation>,
+}
+
+pub async fn handle_clones(
+    threshold: f32,
+    min_lines: usize,
+    path: Option<String>,
+    json: bool,
+    config_path: Option<&Path>,
+) -> Result<()> {
+    if !(threshold > 0.0 && threshold <= 1.0) {
+        bail!("--threshold must be in (0, 1], got {}", threshold);
+    }
+    let ctx = RepoContext::open(config_path, Components::STORE).await?;
+    let store = ctx.surreal_store.clone()
+        .ok_or_else(|| anyhow!("SurrealStore not initialized. Run 'emry index' first."))?;
+
+    let chunks: Vec<_> = store
+        .embedded_chunks()
+        .await?
+        .into_iter()
+        .filter(|c| c.end_line + 1 - c.start_line.min(c.end_line) >= min_lines)
+        .collect();
+    if chunks.is_empty() {
+        bail!("no embedded chunks of {} or more lines; clone detection needs an index built with embeddings", min_lines);
+    }
+    let index: HashMap<String, usize> = chunks.iter().enumerate().map(|(i, c)| (c.id.to_string(), i)).collect();
+    let mut locations: Vec<ChunkLocation> = chunks
+        .iter()
+        .map(|c| ChunkLocation { file_path: c.path.clone(), start_line: c.start_line, end_line: c.end_line })
+        .collect();
+
+    let neighbors: Vec<(usize, Vec<emry_store::clones::Neighbor>)> = stream::iter(chunks.iter().enumerate())
+        .map(|(i, chunk)| {
+            let store = store.clone();
+            async move { (i, store.nearest_chunks(chunk.embedding.clone(), NEIGHBORS + 1).await) }
+        })
+        .buffer_unordered(CONCURRENCY)
+        .map(|(i, found)| found.map(|n| (i, n)))
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<Result<_>>()?;
+
+    let mut seen: HashSet<(usize, usize)> = HashSet::new();
+    let mut pairs: Vec<SimilarPair> = Vec::new();
+    for (a, found) in neighbors {
+        for neighbor in found.into_iter().filter(|n| n.similarity >= threshold) {
+            let Some(&b) = index.get(&neighbor.id.to_string()) else { continue };
+            if a != b && locations[a].file_path != locations[b].file_path && seen.insert((a.min(b), a.max(b))) {
+                pairs.push(SimilarPair { a, b, similarity: neighbor.similarity.min(1.0) });
+            }
+        }
+    }
+    // Exact copies are stored once; each links to the chunk holding its text.
+    for (canonical, copies) in store.all_duplicate_locations().await? {
+        let Some(&a) = index.get(&canonical) else { continue };
+        for copy in copies {
+            if copy.file_path != locations[a].file_path {
+                locations.push(copy);
+                pairs.push(SimilarPair { a, b: locations.len() - 1, similarity: 1.0 });
+            }
+        }
+    }
+
+    let mut groups: Vec<CloneGroup> = group_pairs(locations.len(), &pairs)
+        .into_iter()
+        .map(|set| {
+            let mut members: Vec<ChunkLocation> = set
+                .members
+                .iter()
+                .map(|&i| {
+                    let location = &locations[i];
+                    let file = Path::new(&location.file_path);
+                    ChunkLocation {
+                        file_path: file.strip_prefix(&ctx.root).unwrap_or(file).display().to_string(),
+                        ..location.clone()
+                    }
+                })
+                .collect();
+            members.sort_by(|a, b| a.file_path.cmp(&b.file_path).then(a.start_line.cmp(&b.start_line)));
+            let lines = members.iter().map(|m| m.end_line + 1 - m.start_line.min(m.end_line)).max().unwrap_or(0);
+            CloneGroup { similarity: set.similarity, lines, members }
+        })
+        .filter(|g| path.as_deref().map_or(true, |p| g.members.iter().any(|m| m.file_path.contains(p))))
+        .collect();
+    groups.sort_by(|a, b| (b.members.len() * b.lines).cmp(&(a.members.len() * a.lines)));
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&groups)?);
+        return Ok(());
+    }
+    if groups.is_empty() {
+        println!("No clones found at {:.0}% similarity.", threshold * 100.0);
+        return Ok(());
+    }
+
+    ui::print_header("Clone Groups");
+    let mut out = String::new();
+    for (i, group) in groups.iter().enumerate() {
+        out.push_str(&format!(
+            "{} {}\n",
+            Style::new().bold().apply_to(format!("#{}", i + 1)),
+            Style::new().dim().apply_to(format!(
+                "{} copies, {} lines, {:.0}% similar",
+                group.members.len(),
+                group.lines,
+                group.similarity * 100.0
+            ))
+        ));
+        for member in &group.members {
+            out.push_str(&format!(
+                "  {}\n",
+                ui::palette().location.apply_to(format!("{}:{}-{}", member.file_path, member.start_line, member.end_line))
+            ));
+        }
+    }
+    let copies: usize = groups.iter().map(|g| g.members.len()).sum();
+    out.push_str(&format!("\n{} clone groups, {} chunks.\n", groups.len(), copies));
+    ui::page(&out);
+    Ok(())
+}
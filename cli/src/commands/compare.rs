@@ -0,0 +1,138 @@
+//! Side-by-side results for two queries, or one query under two configs.
+//!
+//! Meant for tuning: the right column marks where each hit sat on the left
+//! (`↑2`, `↓1`, `=`) or that the left column didn't return it at all.
+
+use anyhow::Result;
+use console::{Style, Term};
+use emry_agent::project::RepoContext;
+use emry_config::Config;
+use emry_core::models::{SearchHit, SearchOutput};
+use serde_json::json;
+use std::path::{Path, PathBuf};
+
+use super::search::ranked_search_output;
+use super::ui;
+
+/// Column width when the terminal size is unknown.
+const DEFAULT_COLUMN_WIDTH: usize = 50;
+
+pub async fn handle_compare(
+    query: String,
+    other: Option<String>,
+    against: Option<PathBuf>,
+    limit: usize,
+    json: bool,
+    config_path: Option<&Path>,
+) -> Result<()> {
+    if other.is_none() && against.is_none() {
+        return Err(anyhow::anyhow!("nothing to compare: give a second query or --against <config>"));
+    }
+    let ctx = RepoContext::from_env(config_path).await?;
+    let store = ctx
+        .surreal_store
+        .clone()
+        .ok_or_else(|| anyhow::anyhow!("SurrealStore not initialized. Run 'emry index' first."))?;
+    let right_ctx = match &against {
+        Some(path) => ctx.reconfigured(Config::from_file(path)?),
+        None => ctx.reconfigured(ctx.config.clone()),
+    };
+    let right_query = other.unwrap_or_else(|| query.clone());
+
+    let left_search = ctx.search_service(store.clone());
+    let right_search = right_ctx.search_service(store);
+    let left = ranked_search_output(&ctx, &left_search, &query, limit, false, false, false).await?;
+    let right = ranked_search_output(&right_ctx, &right_search, &right_query, limit, false, false, false).await?;
+
+    if json {
+        let moves: Vec<Option<usize>> = right.hits.iter().map(|h| rank_in(h, &left)).collect();
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&json!({
+                "left": left,
+                "right": right,
+                "right_rank_on_left": moves,
+                "shared": moves.iter().filter(|m| m.is_some()).count(),
+            }))?
+        );
+        return Ok(());
+    }
+
+    let width = Term::stdout()
+        .size_checked()
+        .map(|(_, cols)| (cols as usize).saturating_sub(9) / 2)
+        .unwrap_or(DEFAULT_COLUMN_WIDTH);
+    let left_title = match &against {
+        Some(_) => format!("{} (current config)", left.query),
+        None => left.query.clone(),
+    };
+    let right_title = match &against {
+        Some(path) => format!("{} ({})", right.query, path.display()),
+        None => right.query.clone(),
+    };
+    ui::print_header("Compare");
+    println!(
+        "   {} │      {}",
+        Style::new().bold().apply_to(fit(&left_title, width)),
+        Style::new().bold().apply_to(fit(&right_title, width))
+    );
+
+    let mut shared = 0;
+    for i in 0..left.hits.len().max(right.hits.len()) {
+        let left_cell = left.hits.get(i).map(label).unwrap_or_default();
+        let (marker, right_cell) = match right.hits.get(i) {
+            Some(hit) => {
+                let left_rank = rank_in(hit, &left);
+                if left_rank.is_some() {
+                    shared += 1;
+                }
+                (movement(left_rank, hit.rank), label(hit))
+            }
+            None => (" ".repeat(4), String::new()),
+        };
+        println!(
+            "{} {} │ {} {}",
+            Style::new().dim().apply_to(format!("{:>2}", i + 1)),
+            fit(&left_cell, width),
+            marker,
+            fit(&right_cell, width)
+        );
+    }
+    println!(
+        "\n{} of {} right-hand hits also appear on the left.",
+        shared,
+        right.hits.len()
+    );
+    Ok(())
+}
+
+/// 1-based rank of the same chunk in `output`.
+fn rank_in(hit: &SearchHit, output: &SearchOutput) -> Option<usize> {
+    output.hits.iter().find(|h| h.chunk_id == hit.chunk_id).map(|h| h.rank)
+}
+
+/// How far a hit moved going from the left ranking to the right one, padded to four columns.
+fn movement(left_rank: Option<usize>, right_rank: usize) -> String {
+    let (text, style) = match left_rank {
+        None => ("new".to_string(), Style::new().yellow()),
+        Some(left) if left == right_rank => ("=".to_string(), Style::new().dim()),
+        Some(left) if left > right_rank => (format!("↑{}", left - right_rank), Style::new().green()),
+        Some(left) => (format!("↓{}", right_rank - left), Style::new().red()),
+    };
+    style.apply_to(format!("{:>4}", text)).to_string()
+}
+
+fn label(hit: &SearchHit) -> String {
+    format!("{}:{}-{} ({:.3})", hit.file_path, hit.start_line, hit.end_line, hit.score)
+}
+
+/// Pad or cut `text` to exactly `width` characters, keeping the end of long paths.
+fn fit(text: &str, width: usize) -> String {
+    let len = text.chars().count();
+    if len <= width {
+        format!("{:<width$}", text)
+    } else {
+        let tail: String = text.chars().skip(len + 1 - width).collect();
+        format!("…{}", tail)
+    }
+}
@@ -0,0 +1,46 @@
+//! Candidates for shell completion, one per line.
+//!
+//! Symbol and file ids are printed in fuzzy-match order, so a completion
+//! function can pass the current word straight through:
+//! `emry graph --node "$(emry complete node srchsvc | head -1)"`.
+
+use anyhow::Result;
+use clap::Subcommand;
+use emry_agent::ops::graph::GraphTool;
+use emry_agent::project::{Components, RepoContext};
+use std::path::Path;
+use std::sync::Arc;
+
+#[derive(Subcommand)]
+pub enum CompleteTarget {
+    /// Graph node ids (symbols and files) matching a prefix or abbreviation
+    Node {
+        /// What has been typed so far
+        #[arg(default_value = "")]
+        prefix: String,
+        /// Maximum candidates to print
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+        /// Append a tab and "label (kind, file)" to each id, for shells that show descriptions
+        #[arg(long, default_value_t = false)]
+        describe: bool,
+    },
+}
+
+pub async fn handle_complete(target: CompleteTarget, config_path: Option<&Path>) -> Result<()> {
+    let CompleteTarget::Node { prefix, limit, describe } = target;
+
+    let ctx = RepoContext::open(config_path, Components::STORE).await?;
+    if ctx.surreal_store.is_none() {
+        return Err(anyhow::anyhow!("SurrealStore not initialized. Run 'emry index' first."));
+    }
+
+    for candidate in GraphTool::new(Arc::new(ctx)).complete(&prefix, limit).await? {
+        if describe {
+            println!("{}\t{} ({}, {})", candidate.id, candidate.label, candidate.kind, candidate.file_path);
+        } else {
+            println!("{}", candidate.id);
+        }
+    }
+    Ok(())
+}
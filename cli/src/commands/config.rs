@@ -0,0 +1,119 @@
+//! `emry config`: inspect, scaffold and validate configuration.
+//!
+//! `show` prints the effective settings with where each came from (default,
+//! config file, profile or `EMRY_*` variable), `init` writes a commented `.emry.toml`,
+//! `validate` checks a file without touching the index and `schema` emits the
+//! JSON Schema editors can use for completion.
+
+use anyhow::{anyhow, Result};
+use clap::Subcommand;
+use console::Style;
+use emry_config::loader::provenance::{load_with_provenance, Origin};
+use emry_config::loader::{file::load_from_file, find_config_file, DEFAULT_CONFIG_FILES};
+use emry_config::schema::{json_schema, template, toml_value};
+use std::path::{Path, PathBuf};
+
+use super::ui;
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum ConfigAction {
+    /// Print the effective config and where each setting comes from
+    Show {
+        /// Only list settings that differ from the defaults
+        #[arg(long, default_value_t = false)]
+        changed: bool,
+        /// Emit the settings as JSON
+        #[arg(long, default_value_t = false)]
+        json: bool,
+    },
+    /// Write a commented `.emry.toml` listing every setting and its default
+    Init {
+        /// Overwrite an existing file
+        #[arg(long, default_value_t = false)]
+        force: bool,
+    },
+    /// Check a config file (default: the one `emry` would load) without indexing
+    Validate {
+        file: Option<PathBuf>,
+    },
+    /// Print the JSON Schema of the config file format
+    Schema,
+}
+
+pub async fn handle_config(action: ConfigAction, config_path: Option<&Path>) -> Result<()> {
+    match action {
+        ConfigAction::Show { changed, json } => show(config_path, changed, json),
+        ConfigAction::Init { force } => {
+            super::utils::ensure_writable("config init")?;
+            init(force)
+        }
+        ConfigAction::Validate { file } => validate(file.as_deref().or(config_path)),
+        ConfigAction::Schema => {
+            println!("{}", serde_json::to_string_pretty(&json_schema())?);
+            Ok(())
+        }
+    }
+}
+
+fn show(config_path: Option<&Path>, changed: bool, json: bool) -> Result<()> {
+    let (_, mut entries) = load_with_provenance(config_path)?;
+    if changed {
+        entries.retain(|e| e.origin != Origin::Default);
+    }
+    if json {
+        println!("{}", serde_json::to_string_pretty(&entries)?);
+        return Ok(());
+    }
+
+    ui::print_header("Effective Config");
+    let width = entries.iter().map(|e| e.key.len()).max().unwrap_or(0);
+    for entry in &entries {
+        let value = toml_value(&entry.value).unwrap_or_else(|| "(not set)".to_string());
+        let origin = match &entry.origin {
+            Origin::Default => "default".to_string(),
+            Origin::File(path) => path.display().to_string(),
+            Origin::Profile(name) => format!("profile {}", name),
+            Origin::Env => "environment".to_string(),
+        };
+        let value_style = if entry.origin == Origin::Default { Style::new() } else { Style::new().bold() };
+        println!(
+            "{:width$} = {}  {}",
+            entry.key,
+            value_style.apply_to(value),
+            Style::new().dim().apply_to(format!("({})", origin)),
+            width = width
+        );
+    }
+    if changed && entries.is_empty() {
+        println!("All settings are at their defaults.");
+    }
+    Ok(())
+}
+
+fn init(force: bool) -> Result<()> {
+    let path = Path::new(DEFAULT_CONFIG_FILES[0]);
+    if path.exists() && !force {
+        return Err(anyhow!("{} already exists; pass --force to overwrite it", path.display()));
+    }
+    std::fs::write(path, template())?;
+    ui::print_success(&format!("Wrote {}", path.display()));
+
+    let shadowed: Vec<&str> = DEFAULT_CONFIG_FILES[1..].iter().copied().filter(|f| Path::new(f).exists()).collect();
+    if !shadowed.is_empty() {
+        println!(
+            "{}",
+            ui::palette().warning.apply_to(format!("{} is read first, so {} will be ignored.", path.display(), shadowed.join(", ")))
+        );
+    }
+    Ok(())
+}
+
+fn validate(file: Option<&Path>) -> Result<()> {
+    let path = file
+        .map(Path::to_path_buf)
+        .or_else(find_config_file)
+        .ok_or_else(|| anyhow!("no config file found (looked for {})", DEFAULT_CONFIG_FILES.join(", ")))?;
+    load_from_file(&path)?;
+    ui::print_success(&format!("{} is valid.", path.display()));
+    Ok(())
+}
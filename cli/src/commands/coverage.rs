@@ -0,0 +1,103 @@
+//! `emry coverage`: import test coverage into the index.
+//!
+//! Reports are matched to indexed files by path: absolute paths under the
+//! repository, paths relative to it or to a Cobertura `<source>`, and failing
+//! those the longest trailing part of the path that names a file here, so
+//! reports produced on a CI machine with another checkout path still line up.
+//! `emry impact` then lists changed lines no test ran, and `ranking.coverage`
+//! boosts tested code in search.
+
+use anyhow::{Context, Result};
+use clap::Subcommand;
+use emry_agent::project::{Components, RepoContext};
+use emry_core::coverage::parse_report;
+use emry_engine::ingest::pipeline::compute_hash;
+use emry_store::coverage::CoverageRecord;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::ui;
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum CoverageAction {
+    /// Read LCOV or Cobertura XML reports, replacing earlier coverage of the files they cover
+    Import {
+        #[arg(required = true)]
+        reports: Vec<PathBuf>,
+    },
+    /// Show how many files have coverage
+    Status,
+    /// Forget all imported coverage
+    Clear,
+}
+
+pub async fn handle_coverage(action: CoverageAction, config_path: Option<&Path>) -> Result<()> {
+    if !matches!(action, CoverageAction::Status) {
+        super::utils::ensure_writable("coverage")?;
+        super::utils::ensure_no_daemon().await?;
+    }
+    let ctx = RepoContext::open(config_path, Components::STORE).await?;
+    let store = ctx.surreal_store.clone()
+        .ok_or_else(|| anyhow::anyhow!("SurrealStore not initialized. Run 'emry index' first."))?;
+
+    match action {
+        CoverageAction::Import { reports } => {
+            let imported = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+            for report_path in reports {
+                let content = std::fs::read_to_string(&report_path)
+                    .with_context(|| format!("failed to read {}", report_path.display()))?;
+                let report = parse_report(&content).with_context(|| format!("{}", report_path.display()))?;
+
+                let (mut matched, mut unmatched) = (0, Vec::new());
+                for (written, lines) in report.files {
+                    let Some(path) = resolve(&written, &report.sources, &ctx.root) else {
+                        unmatched.push(written);
+                        continue;
+                    };
+                    let key = path.display().to_string();
+                    if store.get_file(&key).await?.is_none() {
+                        unmatched.push(written);
+                        continue;
+                    }
+                    let hash = compute_hash(&std::fs::read_to_string(&path)?);
+                    store.set_coverage(CoverageRecord { path: key, hash, imported, lines }).await?;
+                    matched += 1;
+                }
+
+                ui::print_key_value(&report_path.display().to_string(), &format!("{} indexed files", matched));
+                if !unmatched.is_empty() {
+                    let shown: Vec<&str> = unmatched.iter().take(5).map(String::as_str).collect();
+                    let more = if unmatched.len() > shown.len() { format!(" and {} more", unmatched.len() - shown.len()) } else { String::new() };
+                    println!("  skipped {} not indexed here: {}{}", unmatched.len(), shown.join(", "), more);
+                }
+            }
+        }
+        CoverageAction::Status => {
+            ui::print_key_value("Files with coverage", &store.count_coverage().await?.to_string());
+            ui::print_key_value("Search boost (ranking.coverage)", &ctx.config.ranking.coverage.to_string());
+        }
+        CoverageAction::Clear => {
+            store.clear_coverage().await?;
+            println!("Cleared imported coverage.");
+        }
+    }
+    Ok(())
+}
+
+/// The file under `root` a report path refers to.
+fn resolve(written: &str, sources: &[String], root: &Path) -> Option<PathBuf> {
+    let written = Path::new(written);
+    let candidates = std::iter::once(written.to_path_buf()).chain(sources.iter().map(|s| Path::new(s).join(written)));
+    for candidate in candidates {
+        let relative = candidate.strip_prefix(root).unwrap_or(&candidate);
+        let parts: Vec<_> = relative.components().filter(|c| matches!(c, std::path::Component::Normal(_))).collect();
+        // Longest suffix first: `/ci/build/repo/src/lib.rs` -> `src/lib.rs`.
+        for skip in 0..parts.len() {
+            let path = parts[skip..].iter().fold(root.to_path_buf(), |p, c| p.join(c));
+            if path.is_file() {
+                return Some(path);
+            }
+        }
+    }
+    None
+}
@@ -0,0 +1,75 @@
+//! Terminal restore and crash logs for panics and interrupts.
+//!
+//! Prompts and progress bars hide the cursor while they draw; a panic or a
+//! Ctrl-C in the middle of one would leave it hidden. The panic hook and the
+//! interrupt handler both show it again first. Panics are also written to
+//! `.codeindex/logs/`, where `emry report-issue` picks up the latest one.
+
+use std::backtrace::Backtrace;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Exit code after Ctrl-C, as shells report for SIGINT.
+const INTERRUPTED_EXIT_CODE: i32 = 130;
+
+/// Install the panic hook, and unless the command handles Ctrl-C itself
+/// (`watch`, `daemon`), an interrupt handler that restores the terminal and exits.
+pub fn install(handle_interrupt: bool) {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        restore_terminal();
+        default_hook(info);
+        if let Some(path) = write_crash_log(&info.to_string()) {
+            eprintln!("emry crashed; details were written to {}", path.display());
+        }
+    }));
+
+    if handle_interrupt {
+        tokio::spawn(async {
+            if tokio::signal::ctrl_c().await.is_ok() {
+                restore_terminal();
+                std::process::exit(INTERRUPTED_EXIT_CODE);
+            }
+        });
+    }
+}
+
+/// The most recent crash log under `index_dir` (the `.codeindex` directory).
+pub fn latest_crash_log(index_dir: &Path) -> Option<PathBuf> {
+    std::fs::read_dir(index_dir.join("logs"))
+        .ok()?
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.file_name().is_some_and(|n| n.to_string_lossy().starts_with("crash-")))
+        .max()
+}
+
+fn restore_terminal() {
+    let _ = console::Term::stdout().show_cursor();
+    let _ = console::Term::stderr().show_cursor();
+}
+
+/// Write the panic and a backtrace to `.codeindex/logs/crash-<unix time>.log`,
+/// if this is an indexed repository.
+fn write_crash_log(panic: &str) -> Option<PathBuf> {
+    let index = Path::new(".codeindex");
+    if !index.is_dir() || super::utils::read_only() {
+        return None;
+    }
+    let dir = index.join("logs");
+    std::fs::create_dir_all(&dir).ok()?;
+    let stamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let path = dir.join(format!("crash-{}.log", stamp));
+    let mut file = std::fs::File::create(&path).ok()?;
+    writeln!(
+        file,
+        "emry {}\ncommand: {}\n\n{}\n\n{}",
+        env!("CARGO_PKG_VERSION"),
+        std::env::args().skip(1).collect::<Vec<_>>().join(" "),
+        panic,
+        Backtrace::force_capture()
+    )
+    .ok()?;
+    Some(path)
+}
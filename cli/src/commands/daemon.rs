@@ -0,0 +1,427 @@
+//! Background daemon that keeps the repository context open between commands.
+//!
+//! Opening the store (and loading the embedder and reranker) dominates the
+//! latency of short commands on large repos. `emry daemon start` opens them
+//! once and serves line-delimited JSON-RPC on `<index_dir>/daemon.sock`: the
+//! MCP tool methods plus `emry/search`, `emry/status` and `emry/shutdown`.
+//! Ranked searches dispatch to the daemon when it is running and fall back to
+//! opening the index directly otherwise, or when they name their own config
+//! or profile. Other commands can't open the store while the daemon holds its
+//! lock and fail with `EmryError::DaemonRunning`, unless they are read-only.
+//!
+//! With `--listen ADDR` the daemon also serves the same protocol over TCP, so
+//! a shared index (say of a whole monorepo) can be searched from other
+//! machines (see `federation`). TCP clients only get `emry/search` and
+//! `emry/status`, and when `EMRY_SERVER_TOKEN` is set they must send it as
+//! the request's `token`. The connection is not encrypted; keep it on a
+//! trusted network or behind an SSH tunnel.
+
+use anyhow::{anyhow, Context, Result};
+use clap::Subcommand;
+use emry_agent::cortex::tools::registry::default_tools;
+use emry_agent::llm::OpenAIProvider;
+use emry_agent::project as agent_context;
+use emry_core::models::SearchOutput;
+use emry_engine::search::service::SearchService;
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream, UnixListener, UnixStream};
+use tokio::sync::Notify;
+
+use super::mcp::McpServer;
+use super::ui;
+use super::utils::current_branch;
+
+/// How long `daemon start` waits for the daemon to open the index.
+const START_TIMEOUT: Duration = Duration::from_secs(60);
+/// How long a remote server gets to accept a connection.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+/// Methods served to TCP clients; the rest (file and graph tools, shutdown) stay local.
+const REMOTE_METHODS: &[&str] = &["emry/search", "emry/status"];
+/// Shared secret TCP clients must send, when set on the server.
+pub const SERVER_TOKEN_ENV: &str = "EMRY_SERVER_TOKEN";
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum DaemonAction {
+    /// Start the daemon in the background
+    Start {
+        /// Also serve searches over TCP on this address (e.g. 0.0.0.0:7878)
+        #[arg(long)]
+        listen: Option<String>,
+    },
+    /// Stop a running daemon
+    Stop,
+    /// Show whether a daemon is serving this repository
+    Status,
+    /// Serve in the foreground (used by `start`)
+    #[command(hide = true)]
+    Run {
+        #[arg(long)]
+        listen: Option<String>,
+    },
+}
+
+/// Socket for the current repository and branch.
+pub fn socket_path() -> Result<PathBuf> {
+    let root = std::env::current_dir()?;
+    Ok(root.join(".codeindex").join("branches").join(current_branch()).join("daemon.sock"))
+}
+
+pub async fn handle_daemon(action: DaemonAction, config_path: Option<&Path>) -> Result<()> {
+    match action {
+        DaemonAction::Start { listen } => start(config_path, listen.as_deref()).await,
+        DaemonAction::Stop => stop().await,
+        DaemonAction::Status => status().await,
+        DaemonAction::Run { listen } => run(config_path, listen.as_deref()).await,
+    }
+}
+
+async fn start(config_path: Option<&Path>, listen: Option<&str>) -> Result<()> {
+    // The daemon writes its socket and log into the index directory.
+    super::utils::ensure_writable("daemon start")?;
+    if let Some(mut client) = DaemonClient::connect().await {
+        let status = client.call("emry/status", json!({})).await?;
+        println!("Daemon already running (pid {})", status["pid"]);
+        return Ok(());
+    }
+
+    let socket = socket_path()?;
+    let index_dir = socket.parent().ok_or_else(|| anyhow!("invalid socket path"))?;
+    if !index_dir.exists() {
+        return Err(emry_core::error::EmryError::IndexNotFound { path: index_dir.to_path_buf() }.into());
+    }
+    let log = std::fs::File::create(index_dir.join("daemon.log"))?;
+
+    let mut cmd = std::process::Command::new(std::env::current_exe()?);
+    if let Some(path) = config_path {
+        cmd.arg("--config").arg(path);
+    }
+    cmd.args(["daemon", "run"]);
+    if let Some(addr) = listen {
+        cmd.arg("--listen").arg(addr);
+    }
+    cmd.stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::from(log));
+    // Own process group, so Ctrl-C in the starting terminal doesn't reach the daemon.
+    std::os::unix::process::CommandExt::process_group(&mut cmd, 0);
+    let child = cmd.spawn().context("failed to spawn daemon")?;
+
+    let deadline = Instant::now() + START_TIMEOUT;
+    while Instant::now() < deadline {
+        if DaemonClient::connect().await.is_some() {
+            println!("Daemon started (pid {}), serving {}", child.id(), socket.display());
+            return Ok(());
+        }
+        tokio::time::sleep(Duration::from_millis(200)).await;
+    }
+    Err(anyhow!(
+        "daemon did not come up within {}s; see {}",
+        START_TIMEOUT.as_secs(),
+        index_dir.join("daemon.log").display()
+    ))
+}
+
+async fn stop() -> Result<()> {
+    match DaemonClient::connect().await {
+        Some(mut client) => {
+            client.call("emry/shutdown", json!({})).await?;
+            println!("Daemon stopped");
+        }
+        None => println!("No daemon running"),
+    }
+    Ok(())
+}
+
+async fn status() -> Result<()> {
+    let Some(mut client) = DaemonClient::connect().await else {
+        println!("No daemon running");
+        return Ok(());
+    };
+    let status = client.call("emry/status", json!({})).await?;
+    ui::print_header("Daemon");
+    ui::print_key_value("PID", &status["pid"].to_string());
+    ui::print_key_value("Root", status["root"].as_str().unwrap_or("-"));
+    ui::print_key_value("Branch", status["branch"].as_str().unwrap_or("-"));
+    ui::print_key_value("Uptime", &format!("{}s", status["uptime_secs"]));
+    ui::print_key_value("Requests", &status["requests"].to_string());
+    ui::print_key_value("Semantic", &status["semantic"].to_string());
+    ui::print_key_value("Listening", status["listen"].as_str().unwrap_or("-"));
+    Ok(())
+}
+
+struct Daemon {
+    ctx: Arc<agent_context::RepoContext>,
+    search: Arc<SearchService>,
+    /// `--mode lexical` searches, without the embedder and reranker
+    lexical: Arc<SearchService>,
+    mcp: McpServer,
+    started: Instant,
+    requests: AtomicU64,
+    shutdown: Notify,
+    /// TCP address also served, if any
+    listen: Option<String>,
+    /// Required `token` of TCP requests (`EMRY_SERVER_TOKEN`)
+    token: Option<String>,
+}
+
+impl Daemon {
+    /// Answer one request; `remote` for requests that came in over TCP.
+    async fn handle(&self, msg: Value, remote: bool) -> Option<Value> {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        let method = msg["method"].as_str().unwrap_or("");
+        if remote {
+            let refusal = if self.token.as_deref().is_some_and(|t| msg["token"].as_str() != Some(t)) {
+                Some((-32001, "unauthorized: missing or wrong token".to_string()))
+            } else if !REMOTE_METHODS.contains(&method) {
+                Some((-32601, format!("method not available remotely: {}", method)))
+            } else {
+                None
+            };
+            if let Some((code, message)) = refusal {
+                let id = msg.get("id").cloned()?;
+                return Some(json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } }));
+            }
+        }
+        let result = match method {
+            "emry/search" => self.search(&msg["params"]).await.map_err(|e| e.to_string()),
+            "emry/status" => Ok(json!({
+                "pid": std::process::id(),
+                "root": self.ctx.root.display().to_string(),
+                "branch": self.ctx.branch,
+                "uptime_secs": self.started.elapsed().as_secs(),
+                "requests": self.requests.load(Ordering::Relaxed),
+                "semantic": self.ctx.capabilities().semantic,
+                "listen": self.listen,
+            })),
+            // The connection signals shutdown once this reply has been written.
+            "emry/shutdown" => Ok(json!({})),
+            _ => return self.mcp.handle(msg).await,
+        };
+
+        let id = msg.get("id").cloned()?;
+        Some(match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err(message) => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": { "code": -32000, "message": message }
+            }),
+        })
+    }
+
+    async fn search(&self, params: &Value) -> Result<Value> {
+        let query = params["query"].as_str().ok_or_else(|| anyhow!("missing query"))?;
+        let limit = params["limit"].as_u64().unwrap_or(10) as usize;
+        let smart = params["smart"].as_bool().unwrap_or(false);
+        let show_diff = params["show_diff"].as_bool().unwrap_or(false);
+        let explain = params["explain"].as_bool().unwrap_or(false);
+        let search = match params["mode"].as_str() {
+            Some("lexical") => &self.lexical,
+            Some("semantic") if !self.ctx.capabilities().semantic => {
+                return Err(anyhow!("semantic mode requested but no embedder configured"));
+            }
+            _ => &self.search,
+        };
+        let output = super::search::ranked_search_output(&self.ctx, search, query, limit, smart, show_diff, explain).await?;
+        Ok(serde_json::to_value(output)?)
+    }
+}
+
+async fn run(config_path: Option<&Path>, listen: Option<&str>) -> Result<()> {
+    let ctx = Arc::new(agent_context::RepoContext::from_env(config_path).await?);
+    let store = ctx.surreal_store.clone()
+        .ok_or_else(|| anyhow!("SurrealStore not initialized. Run 'emry index' first."))?;
+    let search = Arc::new(ctx.search_service(store.clone()));
+    let mut lexical = ctx.rescoped(None);
+    lexical.embedder = None;
+    lexical.reranker = None;
+    let lexical = Arc::new(lexical.search_service(store));
+
+    let llm = match std::env::var("OPENAI_API_KEY") {
+        Ok(key) => {
+            let model = std::env::var("OPENAI_MODEL").unwrap_or_else(|_| "gpt-4o-mini".to_string());
+            Some(OpenAIProvider::new(model, key, 60)?)
+        }
+        Err(_) => None,
+    };
+    let mcp = McpServer::new(default_tools(ctx.clone(), search.clone(), llm.as_ref())?);
+
+    let socket = ctx.index_dir.join("daemon.sock");
+    // A socket file nobody answers on is left over from a daemon that didn't exit cleanly.
+    if socket.exists() {
+        if UnixStream::connect(&socket).await.is_ok() {
+            return Err(anyhow!("a daemon is already serving {}", socket.display()));
+        }
+        std::fs::remove_file(&socket)?;
+    }
+    let listener = UnixListener::bind(&socket)
+        .with_context(|| format!("failed to bind {}", socket.display()))?;
+    eprintln!("emry daemon listening on {}", socket.display());
+    let tcp = match listen {
+        Some(addr) => {
+            let tcp = TcpListener::bind(addr).await.with_context(|| format!("failed to bind {}", addr))?;
+            eprintln!("emry daemon serving searches on {}", addr);
+            Some(tcp)
+        }
+        None => None,
+    };
+
+    let daemon = Arc::new(Daemon {
+        ctx,
+        search,
+        lexical,
+        mcp,
+        started: Instant::now(),
+        requests: AtomicU64::new(0),
+        shutdown: Notify::new(),
+        listen: listen.map(str::to_string),
+        token: std::env::var(SERVER_TOKEN_ENV).ok().filter(|t| !t.is_empty()),
+    });
+
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => {
+                let (stream, _) = accepted?;
+                let (read, write) = stream.into_split();
+                let daemon = daemon.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = serve_connection(read, write, daemon, false).await {
+                        tracing::debug!("daemon connection error: {}", e);
+                    }
+                });
+            }
+            Some(accepted) = accept_tcp(tcp.as_ref()) => {
+                let (stream, peer) = accepted?;
+                let (read, write) = stream.into_split();
+                let daemon = daemon.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = serve_connection(read, write, daemon, true).await {
+                        tracing::debug!("connection error from {}: {}", peer, e);
+                    }
+                });
+            }
+            _ = daemon.shutdown.notified() => break,
+            _ = tokio::signal::ctrl_c() => break,
+        }
+    }
+
+    let _ = std::fs::remove_file(&socket);
+    eprintln!("emry daemon stopped");
+    Ok(())
+}
+
+/// The next TCP connection, or never when not listening on TCP.
+async fn accept_tcp(tcp: Option<&TcpListener>) -> Option<std::io::Result<(TcpStream, std::net::SocketAddr)>> {
+    match tcp {
+        Some(tcp) => Some(tcp.accept().await),
+        None => std::future::pending().await,
+    }
+}
+
+async fn serve_connection<R, W>(read: R, mut write: W, daemon: Arc<Daemon>, remote: bool) -> Result<()>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let mut lines = BufReader::new(read).lines();
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let mut shutdown = false;
+        let response = match serde_json::from_str::<Value>(&line) {
+            Ok(msg) => {
+                shutdown = !remote && msg["method"] == "emry/shutdown";
+                daemon.handle(msg, remote).await
+            }
+            Err(e) => Some(json!({
+                "jsonrpc": "2.0",
+                "id": Value::Null,
+                "error": { "code": -32700, "message": format!("Parse error: {}", e) }
+            })),
+        };
+        if let Some(response) = response {
+            write.write_all(format!("{}\n", response).as_bytes()).await?;
+            write.flush().await?;
+        }
+        if shutdown {
+            daemon.shutdown.notify_one();
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Connection to a running daemon for the current repository, or to a
+/// remote one serving over TCP.
+pub struct DaemonClient {
+    lines: tokio::io::Lines<BufReader<Box<dyn AsyncRead + Unpin + Send>>>,
+    write: Box<dyn AsyncWrite + Unpin + Send>,
+    next_id: u64,
+    /// Sent with every request to a remote daemon (`EMRY_SERVER_TOKEN`)
+    token: Option<String>,
+}
+
+impl DaemonClient {
+    fn new(read: Box<dyn AsyncRead + Unpin + Send>, write: Box<dyn AsyncWrite + Unpin + Send>, token: Option<String>) -> Self {
+        Self { lines: BufReader::new(read).lines(), write, next_id: 1, token }
+    }
+
+    /// Connect if a daemon is serving this repository; `None` means open the index directly.
+    pub async fn connect() -> Option<Self> {
+        let stream = UnixStream::connect(socket_path().ok()?).await.ok()?;
+        let (read, write) = stream.into_split();
+        Some(Self::new(Box::new(read), Box::new(write), None))
+    }
+
+    /// Connect to a daemon started with `--listen` at `addr` (`host:port`).
+    pub async fn connect_remote(addr: &str) -> Result<Self> {
+        let stream = tokio::time::timeout(CONNECT_TIMEOUT, TcpStream::connect(addr))
+            .await
+            .map_err(|_| anyhow!("timed out connecting to {}", addr))?
+            .with_context(|| format!("failed to connect to {}", addr))?;
+        let (read, write) = stream.into_split();
+        let token = std::env::var(SERVER_TOKEN_ENV).ok().filter(|t| !t.is_empty());
+        Ok(Self::new(Box::new(read), Box::new(write), token))
+    }
+
+    /// Send one request and wait for its result.
+    pub async fn call(&mut self, method: &str, params: Value) -> Result<Value> {
+        let id = self.next_id;
+        self.next_id += 1;
+        let mut request = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+        if let Some(token) = &self.token {
+            request["token"] = json!(token);
+        }
+        self.write.write_all(format!("{}\n", request).as_bytes()).await?;
+        self.write.flush().await?;
+
+        let line = self.lines.next_line().await?
+            .ok_or_else(|| anyhow!("daemon closed the connection"))?;
+        let mut response: Value = serde_json::from_str(&line)?;
+        if let Some(error) = response.get("error") {
+            return Err(anyhow!("daemon: {}", error["message"].as_str().unwrap_or("request failed")));
+        }
+        Ok(response["result"].take())
+    }
+
+    pub async fn search(
+        &mut self,
+        query: &str,
+        limit: usize,
+        mode: Option<&str>,
+        smart: bool,
+        show_diff: bool,
+        explain: bool,
+    ) -> Result<SearchOutput> {
+        let params = json!({ "query": query, "limit": limit, "mode": mode, "smart": smart, "show_diff": show_diff, "explain": explain });
+        let result = self.call("emry/search", params).await?;
+        Ok(serde_json::from_value(result)?)
+    }
+}
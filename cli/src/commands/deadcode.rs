@@ -0,0 +1,70 @@
+//! `emry deadcode`: functions and methods the index never sees run.
+//!
+//! A candidate has no incoming `calls` or `imports` edge and isn't an entry
+//! point (`main`, a route or command handler, a test, a library root's public
+//! item), so nothing in the repository or outside it is known to reach it.
+//! Calls through trait objects, reflection or names the resolver couldn't
+//! tie to a definition also leave no edge, so review before deleting.
+
+use anyhow::Result;
+use console::Style;
+use emry_agent::project::{Components, RepoContext};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use super::ui;
+
+pub async fn handle_deadcode(path: Option<String>, json: bool, config_path: Option<&Path>) -> Result<()> {
+    let ctx = RepoContext::open(config_path, Components::STORE).await?;
+    let store = ctx.surreal_store.clone()
+        .ok_or_else(|| anyhow::anyhow!("SurrealStore not initialized. Run 'emry index' first."))?;
+
+    let candidates: Vec<_> = store
+        .unreferenced_symbols()
+        .await?
+        .into_iter()
+        .filter(|s| path.as_deref().map_or(true, |p| s.file_path.contains(p)))
+        .collect();
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&candidates)?);
+        return Ok(());
+    }
+    if candidates.is_empty() {
+        println!("No unreferenced functions found.");
+        return Ok(());
+    }
+
+    let mut by_file: BTreeMap<String, Vec<_>> = BTreeMap::new();
+    for symbol in &candidates {
+        let file = Path::new(&symbol.file_path);
+        let file = file.strip_prefix(&ctx.root).unwrap_or(file).display().to_string();
+        by_file.entry(file).or_default().push(symbol);
+    }
+
+    ui::print_header("Unreferenced Functions");
+    let mut out = String::new();
+    for (file, symbols) in &by_file {
+        out.push_str(&format!("{}\n", Style::new().bold().apply_to(file)));
+        for symbol in symbols {
+            let name = match &symbol.parent_scope {
+                Some(scope) => format!("{}::{}", scope, symbol.label),
+                None => symbol.label.clone(),
+            };
+            let line = symbol.start_line.map_or(String::new(), |l| format!(":{}", l));
+            out.push_str(&format!(
+                "  {}  {} {}\n",
+                ui::palette().location.apply_to(format!("{}{}", file, line)),
+                name,
+                Style::new().dim().apply_to(format!("({})", symbol.kind))
+            ));
+        }
+    }
+    out.push_str(&format!(
+        "\n{} candidates in {} files. Dynamic dispatch and unresolved calls leave no edge; review before removing.\n",
+        candidates.len(),
+        by_file.len()
+    ));
+    ui::page(&out);
+    Ok(())
+}
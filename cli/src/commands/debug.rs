@@ -8,7 +8,7 @@ pub async fn handle_debug(config_path: Option<&Path>) -> Result<()> {
 
     ui::print_header("Debug: Database Stats");
 
-    let ctx = emry_agent::project::context::RepoContext::from_env(config_path).await?;
+    let ctx = emry_agent::project::RepoContext::open(config_path, emry_agent::project::Components::STORE).await?;
     
     ui::print_panel("Info", &format!("Connecting to store at: {}", ctx.index_dir.display()), Style::new().dim(), None);
     
@@ -0,0 +1,106 @@
+use anyhow::Result;
+use console::Style;
+use emry_agent::project::{Components, RepoContext};
+use emry_agent::workflows::doctor::{diagnose, repair, HealthReport};
+use indicatif::ProgressBar;
+use std::path::Path;
+
+use super::index::{run_index, DEFAULT_BATCH_SIZE};
+use super::ui;
+
+pub async fn handle_doctor(advise: bool, repair_index: bool, json: bool, config_path: Option<&Path>) -> Result<()> {
+    if repair_index {
+        super::utils::ensure_writable("doctor --repair")?;
+        super::utils::ensure_no_daemon().await?;
+    }
+    let ctx = RepoContext::open(config_path, Components::STORE).await?;
+    if ctx.surreal_store.is_none() {
+        return Err(anyhow::anyhow!("SurrealStore not initialized. Run 'emry index' first."));
+    }
+    let mut report = diagnose(&ctx).await?;
+    if !advise {
+        report.recommendations.clear();
+    }
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print_report(&report, advise);
+    }
+    if !repair_index || report.integrity.is_clean() {
+        return Ok(());
+    }
+
+    let dropped = repair(&ctx, &report.integrity).await?;
+    // Indexing opens the store itself.
+    drop(ctx);
+    if !json {
+        println!("\nReindexing {} affected files...", dropped);
+    }
+    let status = json.then(ProgressBar::hidden);
+    run_index(false, DEFAULT_BATCH_SIZE, config_path, status).await?;
+    if !json {
+        ui::print_success(&format!("Repaired {} dangling edges and {} files.", report.integrity.dangling_edges, dropped));
+    }
+    Ok(())
+}
+
+fn print_report(report: &HealthReport, advise: bool) {
+    ui::print_header("Index Health");
+    ui::print_key_value("Score", &format!("{}/100", report.score));
+    ui::print_key_value(
+        "Unresolved references",
+        &format!("{:.1}% ({} of {})", report.unresolved_ratio() * 100.0, report.unresolved, report.references),
+    );
+    for lang in &report.languages {
+        ui::print_key_value(
+            &format!(" - {}", lang.language),
+            &format!("{} of {} unresolved, {} parse errors in {} files", lang.unresolved, lang.references, lang.parse_errors, lang.files),
+        );
+    }
+    ui::print_key_value(
+        "Parse errors",
+        &format!("{:.1}% ({} of {} files)", report.parse_error_rate() * 100.0, report.parse_errors, report.files),
+    );
+    ui::print_key_value(
+        "Stale files",
+        &format!("{:.1}% ({} of {})", report.stale_ratio() * 100.0, report.stale_files, report.files),
+    );
+    ui::print_key_value(
+        "Vector coverage",
+        &format!("{:.1}% ({} of {} chunks)", report.vector_coverage() * 100.0, report.embedded_chunks, report.chunks),
+    );
+    if report.duplicate_chunks > 0 {
+        ui::print_key_value("Duplicate chunks", &format!("{} (sharing an embedding with an identical chunk)", report.duplicate_chunks));
+    }
+    let integrity = &report.integrity;
+    ui::print_key_value(
+        "Integrity",
+        &if integrity.is_clean() {
+            "ok".to_string()
+        } else {
+            format!(
+                "{} dangling edges, {} files without chunks, {} files with unembedded chunks",
+                integrity.dangling_edges,
+                integrity.files_without_chunks.len(),
+                integrity.files_missing_embeddings.len()
+            )
+        },
+    );
+
+    if !advise {
+        println!("\nRun `emry doctor --advise` for recommendations.");
+        return;
+    }
+    if report.recommendations.is_empty() {
+        ui::print_success("No recommendations; the index is healthy.");
+        return;
+    }
+    for rec in &report.recommendations {
+        let mut content = format!("{}\n{}", rec.issue, rec.action);
+        if let Some(config) = &rec.config {
+            content.push_str(&format!("\n\n{}", config));
+        }
+        ui::print_panel(&format!("#{}", rec.priority), &content, Style::new().yellow(), None);
+    }
+}
@@ -1,15 +1,24 @@
 use anyhow::{Context, Result};
 use emry_agent::llm::OpenAIProvider;
 use emry_agent::project as agent_context;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use emry_engine::search::service::SearchService;
 use emry_agent::ops::fs::FsTool;
+use emry_agent::workflows::file_explain::{render_facts, FileExplainWorkflow};
 use emry_agent::workflows::functional::FunctionalWorkflow;
 
 use super::utils::render_markdown_answer;
 
-pub async fn handle_explain(verbose: bool, config_path: Option<&Path>) -> Result<()> {
+pub async fn handle_explain(
+    path: Option<PathBuf>,
+    facts_only: bool,
+    verbose: bool,
+    config_path: Option<&Path>,
+) -> Result<()> {
+    if let Some(path) = path {
+        return handle_file_explain(&path, facts_only, verbose, config_path).await;
+    }
+
     use super::ui;
     use console::Style;
     
@@ -19,15 +28,12 @@ pub async fn handle_explain(verbose: bool, config_path: Option<&Path>) -> Result
 
     let api_key = std::env::var("OPENAI_API_KEY").context("OPENAI_API_KEY environment variable not set")?;
     let model = std::env::var("OPENAI_MODEL").unwrap_or_else(|_| "gpt-4o-mini".to_string());
-    let llm = OpenAIProvider::new(model, api_key, 60)?;
+    let llm = OpenAIProvider::new(model, api_key, 60)?.with_cache(ctx.llm_cache());
     
     let store = ctx.surreal_store.clone()
         .ok_or_else(|| anyhow::anyhow!("SurrealStore not initialized. Run 'emry index' first."))?;
     
-    let search_service = Arc::new(SearchService::new(
-        store,
-        ctx.embedder.clone(),
-    ));
+    let search_service = Arc::new(ctx.search_service(store));
 
     let fs_tool = Arc::new(FsTool::new(ctx.clone()));
     
@@ -60,6 +66,7 @@ pub async fn handle_explain(verbose: bool, config_path: Option<&Path>) -> Result
                      };
                      ui::print_panel("Observation", &truncated, Style::new().blue(), Some(Style::new().dim()));
                 }
+                emry_agent::cortex::CortexEvent::AnswerDelta(_) => {}
             }
         }
     }).await?;
@@ -69,3 +76,36 @@ pub async fn handle_explain(verbose: bool, config_path: Option<&Path>) -> Result
 
     Ok(())
 }
+
+async fn handle_file_explain(path: &Path, facts_only: bool, verbose: bool, config_path: Option<&Path>) -> Result<()> {
+    use super::ui;
+
+    let ctx = Arc::new(agent_context::RepoContext::open(config_path, agent_context::Components::STORE).await?);
+    let store = ctx.surreal_store.clone()
+        .ok_or_else(|| anyhow::anyhow!("SurrealStore not initialized. Run 'emry index' first."))?;
+    let fs_tool = Arc::new(FsTool::new(ctx.clone()));
+
+    let workflow = FileExplainWorkflow::new(ctx.clone(), store, fs_tool);
+    let facts = workflow.gather(path).await?;
+    let rendered = render_facts(&facts);
+
+    if facts_only {
+        println!("{}", rendered);
+        return Ok(());
+    }
+
+    let api_key = std::env::var("OPENAI_API_KEY").context("OPENAI_API_KEY environment variable not set (use --facts-only to skip the narrative)")?;
+    let model = std::env::var("OPENAI_MODEL").unwrap_or_else(|_| "gpt-4o-mini".to_string());
+    let llm = OpenAIProvider::new(model, api_key, 60)?.with_cache(ctx.llm_cache());
+
+    if verbose {
+        ui::print_header("Indexed Facts");
+        println!("{}", render_markdown_answer(&rendered));
+    }
+
+    let narrative = workflow.narrate(&llm, &facts).await?;
+    ui::print_header(&format!("Role of {}", facts.path));
+    println!("{}", render_markdown_answer(&narrative));
+
+    Ok(())
+}
@@ -1,15 +1,26 @@
 use anyhow::Result;
 use emry_agent::ops::fs::FsTool;
-use emry_agent::project::RepoContext;
+use emry_agent::project::{Components, RepoContext};
 use std::path::Path;
 use std::sync::Arc;
 
-pub async fn handle_explore(path: String, depth: usize, config_path: Option<&Path>) -> Result<()> {
-    let ctx = Arc::new(RepoContext::from_env(config_path).await?);
+use super::ui;
+
+pub async fn handle_explore(path: String, depth: usize, all: bool, config_path: Option<&Path>) -> Result<()> {
+    let ctx = Arc::new(RepoContext::open(config_path, Components::NONE).await?);
     let fs_tool = FsTool::new(ctx.clone());
 
-    let result = fs_tool.explore_module(&path, depth).await?;
-    println!("{}", result);
+    let files: Vec<_> = fs_tool.list_files(Path::new(&path), depth, None)?.into_iter().filter(|e| !e.is_dir).collect();
+    let dir = ctx.root.join(&path).canonicalize()?;
+    let shown = ui::shown(&files, all);
+
+    let mut out = format!("Exploration of '{}':\n\nFile Tree:\n", path);
+    for entry in shown {
+        let relative = entry.path.strip_prefix(&dir).unwrap_or(&entry.path);
+        out.push_str(&format!("[FILE] {}\n", relative.display()));
+    }
+    out.push_str(&ui::more_hint(files.len(), shown.len()));
+    ui::page(&out);
 
     Ok(())
 }
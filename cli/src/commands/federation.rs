@@ -0,0 +1,98 @@
+//! Searching this repository's index together with a remote one.
+//!
+//! A team can keep a daemon serving a shared index (of the whole monorepo,
+//! say) with `emry daemon start --listen ADDR`. With `search.remote` set to
+//! that address, ranked searches query both and merge the results: hits
+//! alternate between the two lists in rank order, since scores from indexes
+//! with different corpus statistics don't compare, and each hit is labelled
+//! with its origin. Remote hits for files that also exist in this checkout
+//! are dropped, as the local index has the fresher copy. When the server
+//! can't be reached, the local results are shown on their own.
+
+use anyhow::{anyhow, Result};
+use emry_core::models::{SearchHit, SearchOutput, SourceTotal};
+use serde_json::json;
+use std::path::Path;
+use std::time::Duration;
+
+use super::daemon::DaemonClient;
+use super::ui;
+
+/// How long the remote server gets to answer a search.
+const REMOTE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Origin of hits from this repository's index.
+pub const LOCAL_ORIGIN: &str = "local";
+
+/// `local` merged with the remote server's results for the same query, up to
+/// `limit` hits. Falls back to `local` alone, with a warning, when the server
+/// fails.
+pub async fn with_remote(local: SearchOutput, addr: &str, limit: usize, smart: bool, explain: bool) -> SearchOutput {
+    let remote = tokio::time::timeout(REMOTE_TIMEOUT, remote_output(addr, &local.query, limit, smart, explain))
+        .await
+        .unwrap_or_else(|_| Err(anyhow!("no answer within {}s", REMOTE_TIMEOUT.as_secs())));
+    match remote {
+        Ok((root, remote)) => {
+            let local_root = std::env::current_dir().unwrap_or_default();
+            merge(local, remote, addr, Path::new(&root), &local_root, limit)
+        }
+        Err(e) => {
+            eprintln!("{}", ui::palette().warning.apply_to(format!("Remote search on {} failed: {:#}", addr, e)));
+            local
+        }
+    }
+}
+
+/// The remote server's repository root and its results for `query`.
+async fn remote_output(addr: &str, query: &str, limit: usize, smart: bool, explain: bool) -> Result<(String, SearchOutput)> {
+    let mut client = DaemonClient::connect_remote(addr).await?;
+    let status = client.call("emry/status", json!({})).await?;
+    let root = status["root"].as_str().unwrap_or_default().to_string();
+    // Diffs against the server's working tree would say nothing about this one.
+    let output = client.search(query, limit, None, smart, false, explain).await?;
+    Ok((root, output))
+}
+
+fn merge(local: SearchOutput, remote: SearchOutput, origin: &str, remote_root: &Path, local_root: &Path, limit: usize) -> SearchOutput {
+    let mut local_hits = local.hits.into_iter().map(|hit| labelled(hit, LOCAL_ORIGIN));
+    let mut remote_hits = remote.hits.into_iter().filter_map(|mut hit| {
+        let Ok(relative) = Path::new(&hit.file_path).strip_prefix(remote_root).map(Path::to_path_buf) else {
+            return Some(labelled(hit, origin));
+        };
+        if local_root.join(&relative).exists() {
+            return None;
+        }
+        hit.file_path = relative.display().to_string();
+        Some(labelled(hit, origin))
+    });
+
+    let mut hits = Vec::new();
+    while hits.len() < limit {
+        let (a, b) = (local_hits.next(), remote_hits.next());
+        if a.is_none() && b.is_none() {
+            break;
+        }
+        hits.extend(a.into_iter().chain(b));
+    }
+    hits.truncate(limit);
+    for (i, hit) in hits.iter_mut().enumerate() {
+        hit.rank = i + 1;
+    }
+
+    let remote_totals = remote.totals.into_iter().map(|t| SourceTotal {
+        source: format!("{} on {}", t.source, origin),
+        matches: t.matches,
+    });
+    SearchOutput {
+        query: local.query,
+        did_you_mean: local.did_you_mean,
+        not_indexed: if hits.is_empty() { local.not_indexed } else { Vec::new() },
+        hits,
+        totals: local.totals.into_iter().chain(remote_totals).collect(),
+    }
+}
+
+fn labelled(mut hit: SearchHit, origin: &str) -> SearchHit {
+    hit.origin = Some(origin.to_string());
+    hit
+}
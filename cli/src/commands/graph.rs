@@ -1,16 +1,23 @@
 use anyhow::Result;
-use clap::{Parser, ValueEnum};
+use clap::{Parser, Subcommand, ValueEnum};
 use emry_agent::project as agent_context;
 use emry_agent::project::types::GraphSubgraph;
+use emry_agent::ops::export::{full_graph, write_graph, ExportFormat, ExportScope};
 use emry_agent::ops::graph::{GraphTool, GraphDirection as ToolGraphDirection};
-use std::path::Path;
+use emry_core::models::EdgeKind;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 #[derive(Parser)]
+#[command(subcommand_negates_reqs = true)]
 pub struct GraphArgs {
+    #[command(subcommand)]
+    pub action: Option<GraphAction>,
     /// The node ID to start from (e.g., a file path, chunk ID, or symbol ID)
-    #[arg(long)]
-    pub node: String,
+    #[arg(long, required = true)]
+    pub node: Option<String>,
     /// Filter by file path (e.g., "cli/src/commands" or "ask.rs")
     #[arg(long)]
     pub file: Option<String>,
@@ -20,7 +27,7 @@ pub struct GraphArgs {
     /// Maximum number of hops (depth) to traverse
     #[arg(long, default_value_t = 1)]
     pub max_hops: u8,
-    /// Filter by relation kinds (e.g., calls, imports, defines)
+    /// Filter by relation kinds (e.g., calls, imports, defines, inherits, implements)
     #[arg(long)]
     pub kinds: Vec<String>,
     /// Filter by node kind (file, symbol, chunk) to resolve ambiguity
@@ -29,9 +36,59 @@ pub struct GraphArgs {
     /// Output in JSON format
     #[arg(long, default_value_t = false)]
     pub json: bool,
+    /// Print the subgraph as a Mermaid diagram
+    #[arg(long, default_value_t = false, conflicts_with = "json")]
+    pub mermaid: bool,
     /// Show chunk nodes (hidden by default to reduce noise)
     #[arg(long, default_value_t = false)]
     pub show_chunks: bool,
+    /// List every neighbor and edge instead of the first `core.list_limit`
+    #[arg(long, default_value_t = false)]
+    pub all: bool,
+}
+
+#[derive(Subcommand)]
+pub enum GraphAction {
+    /// Export the graph (or a subgraph around --node) for Graphviz/Gephi/Mermaid/Neo4j
+    Export(ExportArgs),
+}
+
+#[derive(Parser)]
+pub struct ExportArgs {
+    /// Output format
+    #[arg(long, value_enum, default_value_t = CliExportFormat::Dot)]
+    pub format: CliExportFormat,
+    /// Node granularity for a full export
+    #[arg(long, value_enum, default_value_t = CliExportScope::Symbol)]
+    pub scope: CliExportScope,
+    /// Export only the neighbourhood of this node instead of the full graph
+    #[arg(long)]
+    pub node: Option<String>,
+    /// Hops around --node to include
+    #[arg(long, default_value_t = 2)]
+    pub depth: usize,
+    /// Write to a file instead of stdout
+    #[arg(long, short)]
+    pub output: Option<PathBuf>,
+    /// Load the Cypher export straight into the Neo4j at this bolt URI, through `cypher-shell`
+    #[arg(long, value_name = "BOLT_URI", conflicts_with = "output")]
+    pub load: Option<String>,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum, Debug)]
+pub enum CliExportFormat {
+    Dot,
+    Graphml,
+    Jsonl,
+    Mermaid,
+    Cypher,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum, Debug)]
+pub enum CliExportScope {
+    File,
+    Symbol,
+    All,
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug)]
@@ -53,12 +110,23 @@ impl From<GraphDirection> for ToolGraphDirection {
 
 
 
+/// With `--mermaid`, the subgraph is printed as a Mermaid diagram and nothing
+/// else goes to stdout.
 pub async fn handle_graph(args: GraphArgs, config_path: Option<&Path>) -> Result<()> {
     use super::ui;
 
-    ui::print_header(&format!("Graph: {}", args.node));
+    if let Some(GraphAction::Export(export)) = args.action {
+        return handle_export(export, config_path).await;
+    }
+    let node = args.node.clone().ok_or_else(|| anyhow::anyhow!("--node is required"))?;
+    let mermaid = args.mermaid;
+
+    if !mermaid {
+        ui::print_header(&format!("Graph: {}", node));
+    }
+    let note = |text: String| if mermaid { eprintln!("{}", text) } else { println!("{}", text) };
 
-    let ctx = agent_context::RepoContext::from_env(config_path).await?;
+    let ctx = agent_context::RepoContext::open(config_path, agent_context::Components::STORE).await?;
     
     if ctx.surreal_store.is_none() {
         return Err(anyhow::anyhow!("SurrealStore not initialized. Run 'emry index' first."));
@@ -68,7 +136,7 @@ pub async fn handle_graph(args: GraphArgs, config_path: Option<&Path>) -> Result
     let graph_tool = GraphTool::new(ctx.clone());
 
     let direction = args.direction.into();
-    let result = graph_tool.graph(&args.node, direction, args.max_hops as usize, args.file.as_deref()).await;
+    let result = graph_tool.graph(&node, direction, args.max_hops as usize, args.file.as_deref()).await;
 
     match result {
         Ok(graph_res) => {
@@ -83,7 +151,7 @@ pub async fn handle_graph(args: GraphArgs, config_path: Option<&Path>) -> Result
                 
                 use dialoguer::{theme::ColorfulTheme, Select};
 
-                println!("\nFound {} symbols matching '{}':", candidates.len(), args.node);
+                note(format!("\nFound {} symbols matching '{}':", candidates.len(), node));
                 
                 let selections: Vec<String> = candidates.iter()
                     .map(|c| format!("{} ({})\n   File: {}\n   ID: {}", c.label, c.kind, c.file_path, c.id))
@@ -97,7 +165,7 @@ pub async fn handle_graph(args: GraphArgs, config_path: Option<&Path>) -> Result
 
                 if let Some(idx) = selection {
                     let selected = &candidates[idx];
-                    println!("\nQuerying: {}\n", selected.id);
+                    note(format!("\nQuerying: {}\n", selected.id));
                     
                     let final_result = graph_tool.graph(
                         &selected.id, 
@@ -106,7 +174,7 @@ pub async fn handle_graph(args: GraphArgs, config_path: Option<&Path>) -> Result
                         None
                     ).await?;
                     
-                    process_and_output(final_result.subgraph, &selected.label, &args.kinds, args.json)?;
+                    process_and_output(final_result.subgraph, &selected.label, &args.kinds, args.json, mermaid, args.all)?;
                     return Ok(());
                 } else {
                     println!("Selection cancelled");
@@ -114,7 +182,7 @@ pub async fn handle_graph(args: GraphArgs, config_path: Option<&Path>) -> Result
                 }
             }
             
-            process_and_output(graph_res.subgraph, &args.node, &args.kinds, args.json)?;
+            process_and_output(graph_res.subgraph, &node, &args.kinds, args.json, mermaid, args.all)?;
         }
         Err(e) => {
             if args.json {
@@ -128,30 +196,136 @@ pub async fn handle_graph(args: GraphArgs, config_path: Option<&Path>) -> Result
     Ok(())
 }
 
+async fn handle_export(args: ExportArgs, config_path: Option<&Path>) -> Result<()> {
+    if args.load.is_some() && args.format != CliExportFormat::Cypher {
+        return Err(anyhow::anyhow!("--load needs --format cypher"));
+    }
+    let ctx = Arc::new(agent_context::RepoContext::open(config_path, agent_context::Components::STORE).await?);
+    let store = ctx.surreal_store.clone()
+        .ok_or_else(|| anyhow::anyhow!("SurrealStore not initialized. Run 'emry index' first."))?;
+
+    let graph = match &args.node {
+        Some(node) => {
+            let result = GraphTool::new(ctx.clone())
+                .graph(node, ToolGraphDirection::Both, args.depth, None)
+                .await?;
+            if let Some(candidates) = result.candidates {
+                let ids: Vec<String> = candidates.iter().map(|c| format!("  {} ({})", c.id, c.file_path)).collect();
+                return Err(emry_core::error::EmryError::InvalidQuery(format!(
+                    "'{}' matches {} nodes; pass one of these ids to --node:\n{}",
+                    node,
+                    candidates.len(),
+                    ids.join("\n")
+                ))
+                .into());
+            }
+            result.subgraph
+        }
+        None => {
+            let scope = match args.scope {
+                CliExportScope::File => ExportScope::File,
+                CliExportScope::Symbol => ExportScope::Symbol,
+                CliExportScope::All => ExportScope::All,
+            };
+            full_graph(&store, scope).await?
+        }
+    };
+
+    let format = match args.format {
+        CliExportFormat::Dot => ExportFormat::Dot,
+        CliExportFormat::Graphml => ExportFormat::GraphMl,
+        CliExportFormat::Jsonl => ExportFormat::Jsonl,
+        CliExportFormat::Mermaid => ExportFormat::Mermaid,
+        CliExportFormat::Cypher => ExportFormat::Cypher,
+    };
+    if let Some(uri) = &args.load {
+        return load_into_neo4j(&graph, uri);
+    }
+    match &args.output {
+        Some(path) => {
+            let mut out = std::io::BufWriter::new(std::fs::File::create(path)?);
+            write_graph(&graph, format, &mut out)?;
+            out.flush()?;
+            eprintln!(
+                "Exported {} nodes and {} edges to {}",
+                graph.nodes.len(),
+                graph.edges.len(),
+                path.display()
+            );
+        }
+        None => {
+            let stdout = std::io::stdout();
+            let mut out = std::io::BufWriter::new(stdout.lock());
+            write_graph(&graph, format, &mut out)?;
+            out.flush()?;
+        }
+    }
+    Ok(())
+}
+
+/// Pipe the Cypher export to `cypher-shell -a uri`, which speaks bolt and
+/// reads `NEO4J_USERNAME`, `NEO4J_PASSWORD` and `NEO4J_DATABASE` itself.
+fn load_into_neo4j(graph: &GraphSubgraph, uri: &str) -> Result<()> {
+    let mut child = Command::new("cypher-shell")
+        .args(["-a", uri, "--format", "plain"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .spawn()
+        .map_err(|e| anyhow::anyhow!("failed to run cypher-shell ({}); install Neo4j's cypher-shell, or write the script with --format cypher and load it yourself", e))?;
+    if let Some(stdin) = child.stdin.take() {
+        let mut input = std::io::BufWriter::new(stdin);
+        write_graph(graph, ExportFormat::Cypher, &mut input)?;
+        input.flush()?;
+    }
+    let status = child.wait()?;
+    if !status.success() {
+        return Err(anyhow::anyhow!("cypher-shell exited with {}", status));
+    }
+    eprintln!("Loaded {} nodes and {} edges into {}", graph.nodes.len(), graph.edges.len(), uri);
+    Ok(())
+}
+
 fn process_and_output(
     mut subgraph: GraphSubgraph,
     source_label: &str,
     kinds: &[String],
     json: bool,
+    mermaid: bool,
+    all: bool,
 ) -> Result<()> {
     if !kinds.is_empty() {
-        subgraph.edges.retain(|e| kinds.contains(&e.kind));
+        let wanted = kinds
+            .iter()
+            .map(|k| {
+                EdgeKind::from_name(k).ok_or_else(|| {
+                    let known: Vec<&str> = EdgeKind::ALL.iter().map(|k| k.as_str()).collect();
+                    anyhow::anyhow!("Unknown edge kind '{}' (expected one of: {})", k, known.join(", "))
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        subgraph
+            .edges
+            .retain(|e| EdgeKind::from_name(&e.kind).is_some_and(|k| wanted.contains(&k)));
     }
 
     if json {
         println!("{}", serde_json::to_string_pretty(&subgraph)?);
+    } else if mermaid {
+        let mut out = std::io::stdout().lock();
+        write_graph(&subgraph, ExportFormat::Mermaid, &mut out)?;
     } else {
         if subgraph.nodes.is_empty() {
             println!("No nodes found for '{}'", source_label);
         } else {
-            print_subgraph(&subgraph, source_label);
+            print_subgraph(&subgraph, source_label, all);
         }
     }
     Ok(())
 }
 
-fn print_subgraph(subgraph: &GraphSubgraph, source_node: &str) {
+fn print_subgraph(subgraph: &GraphSubgraph, source_node: &str, all: bool) {
     use console::Style;
+    use super::ui;
     use std::collections::HashMap;
 
     let node_labels: HashMap<&str, &str> = subgraph
@@ -159,6 +333,12 @@ fn print_subgraph(subgraph: &GraphSubgraph, source_node: &str) {
         .iter()
         .map(|n| (n.id.as_str(), n.label.as_str()))
         .collect();
+    let node_files: HashMap<&str, &str> = subgraph
+        .nodes
+        .iter()
+        .map(|n| (n.id.as_str(), n.file_path.as_str()))
+        .collect();
+    let cwd = std::env::current_dir().unwrap_or_default();
 
     let neighbors: Vec<_> = subgraph
         .nodes
@@ -166,29 +346,33 @@ fn print_subgraph(subgraph: &GraphSubgraph, source_node: &str) {
         .filter(|n| n.id != source_node && n.label != source_node) // Filter out start node if present
         .collect();
 
-    println!(
-        "\nFound {} neighbors for '{}':",
+    let mut out = format!(
+        "\nFound {} neighbors for '{}':\n",
         neighbors.len(),
         Style::new().bold().cyan().apply_to(source_node)
     );
 
-    for (i, node) in neighbors.iter().enumerate() {
-        println!(
-            "{} {} ({})",
+    let shown = ui::shown(&neighbors, all);
+    for (i, node) in shown.iter().enumerate() {
+        out.push_str(&format!(
+            "{} {} ({})\n",
             Style::new().dim().apply_to(format!("{}.", i + 1)),
             Style::new().bold().apply_to(&node.label),
             Style::new().dim().apply_to(&node.id)
-        );
+        ));
     }
+    out.push_str(&ui::more_hint(neighbors.len(), shown.len()));
 
     if !subgraph.edges.is_empty() {
-        println!("\nEdges:");
-        for edge in &subgraph.edges {
-            let kind_style = match edge.kind.as_str() {
-                "calls" => Style::new().yellow(),
-                "imports" => Style::new().magenta(),
-                "defines" => Style::new().blue(),
-                _ => Style::new().white(),
+        out.push_str("\nEdges:\n");
+        let edges = ui::shown(&subgraph.edges, all);
+        for edge in edges {
+            let kind_style = match EdgeKind::from_name(&edge.kind) {
+                Some(EdgeKind::Calls) => Style::new().yellow(),
+                Some(EdgeKind::Imports) => Style::new().magenta(),
+                Some(EdgeKind::Defines) => Style::new().blue(),
+                Some(EdgeKind::Inherits | EdgeKind::Implements) => Style::new().green(),
+                Some(EdgeKind::Contains) | None => Style::new().white(),
             };
 
             let source_label = node_labels
@@ -200,8 +384,18 @@ fn print_subgraph(subgraph: &GraphSubgraph, source_node: &str) {
                 .copied()
                 .unwrap_or(edge.target.as_str());
 
-            println!(
-                "  {} {} {}",
+            // `path:line:column`, which editors and terminals open at the call.
+            let call_site = match (edge.line, node_files.get(edge.source.as_str())) {
+                (Some(line), Some(file)) => {
+                    let file = Path::new(file);
+                    let file = file.strip_prefix(&cwd).unwrap_or(file);
+                    format!("  at {}:{}:{}", file.display(), line, edge.column.unwrap_or(1))
+                }
+                _ => String::new(),
+            };
+
+            out.push_str(&format!(
+                "  {} {} {}{}\n",
                 format!(
                     "{} ({})",
                     Style::new().bold().apply_to(source_label),
@@ -212,8 +406,11 @@ fn print_subgraph(subgraph: &GraphSubgraph, source_node: &str) {
                     "{} ({})",
                     Style::new().bold().apply_to(target_label),
                     Style::new().dim().apply_to(&edge.target)
-                )
-            );
+                ),
+                ui::palette().location.apply_to(call_site)
+            ));
         }
+        out.push_str(&ui::more_hint(subgraph.edges.len(), edges.len()));
     }
+    ui::page(&out);
 }
@@ -0,0 +1,188 @@
+//! `emry history`: a symbol's changes across index runs, or past queries.
+//!
+//! Every `emry search` and `emry ask` appends its query, command line and top
+//! result to `.codeindex/queries.jsonl`. Without a symbol, `emry history`
+//! lists that log; `--rerun N` runs entry N again with the same arguments.
+
+use anyhow::{anyhow, Result};
+use console::Style;
+use emry_agent::project::{Components, RepoContext};
+use emry_core::error::EmryError;
+use emry_store::SymbolHistoryRecord;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use super::ui;
+
+/// Searches and questions, one JSON object per line, under `.codeindex/`.
+const QUERY_LOG: &str = "queries.jsonl";
+/// The query log is rotated to `queries.jsonl.1` past this size.
+const MAX_QUERY_LOG_BYTES: u64 = 1024 * 1024;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct QueryLogEntry {
+    timestamp: u64,
+    /// `search` or `ask`.
+    command: String,
+    query: String,
+    /// Command line after the binary name, replayed by `--rerun`.
+    args: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    top_result: Option<String>,
+}
+
+pub async fn handle_history(
+    symbol: Option<String>,
+    limit: usize,
+    grep: Option<String>,
+    rerun: Option<usize>,
+    config_path: Option<&Path>,
+) -> Result<()> {
+    match symbol {
+        Some(symbol) => symbol_history(symbol, limit, config_path).await,
+        None => query_history(limit, grep.as_deref(), rerun),
+    }
+}
+
+/// Append a query to `.codeindex/queries.jsonl`, if this is an indexed repository.
+pub fn record_query(command: &str, query: &str, top_result: Option<String>) {
+    let dir = Path::new(".codeindex");
+    if !dir.is_dir() || super::utils::read_only() {
+        return;
+    }
+    let path = dir.join(QUERY_LOG);
+    if std::fs::metadata(&path).map_or(false, |m| m.len() > MAX_QUERY_LOG_BYTES) {
+        let _ = std::fs::rename(&path, dir.join(format!("{}.1", QUERY_LOG)));
+    }
+    let entry = QueryLogEntry {
+        timestamp: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+        command: command.to_string(),
+        query: query.to_string(),
+        args: std::env::args().skip(1).collect(),
+        top_result,
+    };
+    let Ok(line) = serde_json::to_string(&entry) else { return };
+    if let Ok(mut file) = std::fs::OpenOptions::new().create(true).append(true).open(&path) {
+        let _ = writeln!(file, "{}", line);
+    }
+}
+
+/// Logged queries, oldest first, including the rotated log.
+fn load_queries() -> Vec<QueryLogEntry> {
+    let dir = Path::new(".codeindex");
+    [dir.join(format!("{}.1", QUERY_LOG)), dir.join(QUERY_LOG)]
+        .iter()
+        .filter_map(|path| std::fs::read_to_string(path).ok())
+        .flat_map(|text| text.lines().filter_map(|l| serde_json::from_str(l).ok()).collect::<Vec<_>>())
+        .collect()
+}
+
+fn query_history(limit: usize, grep: Option<&str>, rerun: Option<usize>) -> Result<()> {
+    let entries = load_queries();
+    if let Some(n) = rerun {
+        let entry = n
+            .checked_sub(1)
+            .and_then(|i| entries.get(i))
+            .ok_or_else(|| EmryError::NotFound(format!("no query #{} in the history", n)))?;
+        println!("{}", Style::new().dim().apply_to(format!("Re-running: emry {}", entry.args.join(" "))));
+        let status = std::process::Command::new(std::env::current_exe()?).args(&entry.args).status()?;
+        if !status.success() {
+            return Err(anyhow!("re-run of query #{} exited with {}", n, status));
+        }
+        return Ok(());
+    }
+
+    // Numbers are positions in the whole log, so `--rerun` takes them as shown.
+    let grep = grep.map(str::to_lowercase);
+    let matching: Vec<(usize, &QueryLogEntry)> = entries
+        .iter()
+        .enumerate()
+        .filter(|(_, e)| grep.as_ref().map_or(true, |g| e.query.to_lowercase().contains(g)))
+        .map(|(i, e)| (i + 1, e))
+        .collect();
+    if matching.is_empty() {
+        println!("No queries recorded{}.", if grep.is_some() { " matching that term" } else { "" });
+        return Ok(());
+    }
+
+    ui::print_header("Query History");
+    for (n, entry) in &matching[matching.len().saturating_sub(limit)..] {
+        println!(
+            "{} {} {}  {}",
+            Style::new().dim().apply_to(format!("{:>4}.", n)),
+            Style::new().dim().apply_to(format!("[{} @ {}]", entry.command, entry.timestamp)),
+            Style::new().bold().apply_to(&entry.query),
+            Style::new().dim().apply_to(filters(entry)),
+        );
+        if let Some(top) = &entry.top_result {
+            println!("       {} {}", Style::new().dim().apply_to("→"), ui::palette().location.apply_to(top));
+        }
+    }
+    print!("{}", ui::more_hint(matching.len(), limit.min(matching.len())));
+    println!("\nRun `emry history --rerun N` to repeat a query.");
+    Ok(())
+}
+
+/// The logged arguments besides the command and the query itself.
+fn filters(entry: &QueryLogEntry) -> String {
+    entry
+        .args
+        .iter()
+        .filter(|a| **a != entry.command && **a != entry.query)
+        .cloned()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+async fn symbol_history(symbol: String, limit: usize, config_path: Option<&Path>) -> Result<()> {
+    let ctx = RepoContext::open(config_path, Components::STORE).await?;
+    let store = ctx.surreal_store.clone()
+        .ok_or_else(|| anyhow::anyhow!("SurrealStore not initialized in context"))?;
+
+    let entries = store.symbol_history(&symbol).await?;
+    if entries.is_empty() {
+        return Err(EmryError::NotFound(format!("no history recorded for symbol '{}'", symbol)).into());
+    }
+
+    // A bare name can match symbols in several files; report each separately.
+    let mut groups: Vec<(String, Vec<&SymbolHistoryRecord>)> = Vec::new();
+    for entry in &entries {
+        match groups.iter_mut().find(|(id, _)| *id == entry.symbol) {
+            Some((_, list)) => list.push(entry),
+            None => groups.push((entry.symbol.clone(), vec![entry])),
+        }
+    }
+
+    for (_, versions) in groups {
+        let latest = versions[0];
+        let first = versions[versions.len() - 1];
+        ui::print_header(&format!("{} ({}:{}-{})", latest.name, latest.file_path, latest.start_line, latest.end_line));
+
+        let runs_since_first = store.count_commits_since(first.timestamp).await.unwrap_or(0);
+        let runs_since_change = store.count_commits_since(latest.timestamp).await.unwrap_or(0);
+        ui::print_key_value("Last changed", &format!("{} @ {}", latest.commit_id, latest.timestamp));
+        ui::print_key_value(
+            "Changed in",
+            &format!("{} of {} index runs since first indexed", versions.len(), runs_since_first.max(versions.len())),
+        );
+        ui::print_key_value("Unchanged for", &format!("{} runs", runs_since_change.saturating_sub(1)));
+
+        println!("Versions:");
+        for v in versions.iter().take(limit) {
+            println!(
+                " - {} @ {}  L{}-L{}  hash {}  chunks: {}",
+                v.commit_id,
+                v.timestamp,
+                v.start_line,
+                v.end_line,
+                &v.content_hash[..v.content_hash.len().min(12)],
+                if v.chunks.is_empty() { "-".to_string() } else { v.chunks.join(", ") },
+            );
+        }
+        print!("{}", ui::more_hint(versions.len(), limit.min(versions.len())));
+        println!();
+    }
+
+    Ok(())
+}
@@ -1,23 +1,46 @@
 use anyhow::{Context, Result};
 use emry_agent::cortex::tools::impact::AnalyzeImpactTool;
+use emry_core::diff::{parse_unified_diff, FileDiff};
 
 use emry_agent::project as agent_context;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use serde_json::json;
 use super::utils::render_markdown_answer;
 
 use emry_agent::llm::OpenAIProvider;
-use emry_engine::search::service::SearchService;
 use emry_agent::ops::fs::FsTool;
 use emry_agent::ops::graph::GraphTool;
 
-pub async fn handle_impact(file_path: PathBuf, start_line: usize, end_line: usize, verbose: bool, config_path: Option<&Path>) -> Result<()> {
+/// What `emry impact` analyzes.
+pub enum ImpactTarget {
+    Range { file: PathBuf, start: usize, end: usize },
+    /// Uncommitted changes relative to a revision, as reported by `git diff`.
+    Diff { rev: String },
+}
+
+pub async fn handle_impact(target: ImpactTarget, verbose: bool, config_path: Option<&Path>) -> Result<()> {
     use super::ui;
     use console::Style;
     use emry_agent::cortex::CortexEvent;
-    
-    ui::print_header(&format!("Impact Analysis: {}:{}-{}", file_path.display(), start_line, end_line));
+
+    let diffs = match &target {
+        ImpactTarget::Range { file, start, end } => {
+            ui::print_header(&format!("Impact Analysis: {}:{}-{}", file.display(), start, end));
+            Vec::new()
+        }
+        ImpactTarget::Diff { rev } => {
+            let diffs = git_diff(rev)?;
+            if diffs.is_empty() {
+                println!("No changes against {}.", rev);
+                return Ok(());
+            }
+            ui::print_header(&format!("Impact Analysis: changes against {}", rev));
+            for diff in &diffs {
+                println!("{} ({} hunk{})", diff.path.display(), diff.changed_ranges.len(), if diff.changed_ranges.len() == 1 { "" } else { "s" });
+            }
+            diffs
+        }
+    };
 
     let ctx = Arc::new(agent_context::RepoContext::from_env(config_path).await?);
 
@@ -28,10 +51,7 @@ pub async fn handle_impact(file_path: PathBuf, start_line: usize, end_line: usiz
     let store = ctx.surreal_store.clone()
         .ok_or_else(|| anyhow::anyhow!("SurrealStore not initialized. Run 'emry index' first."))?;
     
-    let search = Arc::new(SearchService::new(
-        store,
-        ctx.embedder.clone(),
-    ));
+    let search = Arc::new(ctx.search_service(store));
 
     let fs = Arc::new(FsTool::new(ctx.clone()));
     let graph = Arc::new(GraphTool::new(ctx.clone()));
@@ -44,15 +64,9 @@ pub async fn handle_impact(file_path: PathBuf, start_line: usize, end_line: usiz
         search
     )?;
 
-    let _args = json!({
-        "file_path": file_path.to_string_lossy(),
-        "start_line": start_line,
-        "end_line": end_line
-    });
-    
     ui::print_panel("Running", "Analyze semantic impact...", console::Style::new().yellow(), None);
-    
-    let report = impact_tool.run_analysis(&file_path.to_string_lossy(), start_line, end_line, move |event| {
+
+    let on_event = move |event: CortexEvent| {
         if verbose {
             match event {
                 CortexEvent::StepStart(step) => {
@@ -72,12 +86,39 @@ pub async fn handle_impact(file_path: PathBuf, start_line: usize, end_line: usiz
                      };
                      ui::print_panel("Observation", &truncated, Style::new().blue(), Some(Style::new().dim()));
                 }
+                CortexEvent::AnswerDelta(_) => {}
             }
         }
-    }).await?;
+    };
+    let report = match &target {
+        ImpactTarget::Range { file, start, end } => {
+            impact_tool.run_analysis(&file.to_string_lossy(), *start, *end, on_event).await?
+        }
+        ImpactTarget::Diff { rev } => {
+            let files = diffs.len();
+            let change = format!("{} file{} relative to `{}`", files, if files == 1 { "" } else { "s" }, rev);
+            impact_tool.run_diff_analysis(&change, &diffs, on_event).await?
+        }
+    };
     
     ui::print_header("Impact Report");
     println!("{}", render_markdown_answer(&report));
 
     Ok(())
 }
+
+/// Changed line ranges of the working tree against `rev`, relative to the current directory.
+pub fn git_diff(rev: &str) -> Result<Vec<FileDiff>> {
+    let output = std::process::Command::new("git")
+        .args(["diff", "--relative", "--unified=0", "--no-color", "--no-ext-diff", rev])
+        .output()
+        .context("failed to run git diff")?;
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(
+            "git diff {} failed: {}",
+            rev,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(parse_unified_diff(&String::from_utf8_lossy(&output.stdout)))
+}
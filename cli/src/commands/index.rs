@@ -1,7 +1,10 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use clap::Subcommand;
+use emry_agent::project::{Components, RepoContext};
 use emry_config::Config;
 use emry_agent::project::embedder::{select_embedder, get_embedding_dimension};
 use emry_core::models::Language;
+use emry_core::import_paths::ImportResolver;
 use emry_core::scanner::scan_repo;
 
 use emry_engine::ingest::pipeline::{compute_hash, FileInput};
@@ -9,6 +12,7 @@ use emry_engine::ingest::service::IngestionService;
 use emry_store::{SurrealStore, FileRecord};
 use futures::stream::{self, StreamExt};
 use indicatif::{ProgressBar, ProgressStyle};
+use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
@@ -17,6 +21,76 @@ use tracing::{info, trace};
 
 use super::utils::current_branch;
 
+/// Files analyzed and embedded per step before being queued for writing
+/// (`emry index --batch-size`); the checkpoint is saved after each step.
+pub const DEFAULT_BATCH_SIZE: usize = 64;
+
+/// Progress of an unfinished run, in the branch's index directory.
+pub(crate) const CHECKPOINT_FILE: &str = "index.checkpoint.json";
+
+/// Files the last scan left out for size or binary content, for `emry status`.
+pub const SKIPPED_FILES: &str = "skipped_files.json";
+
+/// Prepared files allowed to wait for the node writer before analysis pauses.
+const WRITE_QUEUE_CAPACITY: usize = 128;
+
+const BAR_TEMPLATE: &str = "{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({eta}) {msg}";
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum IndexAction {
+    /// Upload this branch's index to URL (http(s)://, s3:// or gs://)
+    Push { url: String },
+    /// Replace this branch's index with one uploaded by `push`, then index what changed since
+    Pull {
+        url: String,
+        /// Replace an existing local index
+        #[arg(long, default_value_t = false)]
+        force: bool,
+    },
+    /// Write a canonical JSON dump of the index, for comparing builds
+    Export {
+        /// Output file (default: stdout)
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+}
+
+/// Where an index run's timestamps come from.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Clock {
+    System,
+    /// Seconds since the epoch, for reproducible builds (`--deterministic`)
+    Fixed(u64),
+}
+
+impl Clock {
+    /// `SOURCE_DATE_EPOCH` when set, as for other reproducible builds, else
+    /// the commit time of `HEAD`, else the epoch itself.
+    fn for_checkout() -> Self {
+        let commit_time = || {
+            let out = std::process::Command::new("git").args(["log", "-1", "--format=%ct"]).output().ok()?;
+            String::from_utf8(out.stdout).ok()?.trim().parse().ok()
+        };
+        let secs = std::env::var("SOURCE_DATE_EPOCH")
+            .ok()
+            .and_then(|s| s.trim().parse().ok())
+            .or_else(commit_time)
+            .unwrap_or(0);
+        Clock::Fixed(secs)
+    }
+
+    fn now_millis(&self) -> u128 {
+        match self {
+            Clock::System => SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis()).unwrap_or(0),
+            Clock::Fixed(secs) => *secs as u128 * 1000,
+        }
+    }
+
+    fn now_secs(&self) -> u64 {
+        (self.now_millis() / 1000) as u64
+    }
+}
+
 #[derive(Default)]
 struct IndexStats {
     new_files: usize,
@@ -25,11 +99,105 @@ struct IndexStats {
     skipped_files: usize,
 }
 
-pub async fn handle_index(full: bool, config_path: Option<&Path>) -> Result<()> {
+/// Files whose nodes an unfinished run already wrote, with the hash they
+/// were written at. Their edges and neighbourhoods are still owed: a rerun
+/// sees their hashes unchanged and would otherwise skip them for good.
+#[derive(Default, Serialize, Deserialize)]
+struct Checkpoint {
+    written: HashMap<PathBuf, String>,
+}
+
+impl Checkpoint {
+    fn load(path: &Path) -> Self {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    /// Write through a temporary file so an interruption never leaves half a checkpoint.
+    fn save(&self, path: &Path) -> Result<()> {
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_string(self)?)?;
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    }
+}
+
+/// With `deterministic`, the index is rebuilt from scratch with every
+/// timestamp taken from `Clock::for_checkout`, so two runs over the same
+/// commit give the same `emry index export`.
+pub async fn handle_index(full: bool, batch_size: usize, deterministic: bool, config_path: Option<&Path>) -> Result<()> {
+    if deterministic {
+        build_index(true, batch_size, Clock::for_checkout(), config_path, None).await
+    } else {
+        run_index(full, batch_size, config_path, None).await
+    }
+}
+
+pub async fn handle_index_action(action: IndexAction, config_path: Option<&Path>) -> Result<()> {
+    match action {
+        IndexAction::Push { url } => super::replica::push(&url, config_path).await,
+        IndexAction::Pull { url, force } => {
+            super::utils::ensure_writable("index pull")?;
+            super::replica::pull(&url, force, config_path).await
+        }
+        IndexAction::Export { output } => export(output, config_path).await,
+    }
+}
+
+async fn export(output: Option<PathBuf>, config_path: Option<&Path>) -> Result<()> {
+    let ctx = RepoContext::open(config_path, Components::STORE).await?;
+    let store = ctx.surreal_store.clone()
+        .ok_or_else(|| anyhow!("SurrealStore not initialized. Run 'emry index' first."))?;
+    let mut json = serde_json::to_string_pretty(&store.export_snapshot().await?)?;
+    json.push('\n');
+    match output {
+        Some(path) => {
+            std::fs::write(&path, json)?;
+            super::ui::print_success(&format!("Wrote {}", path.display()));
+        }
+        None => print!("{}", json),
+    }
+    Ok(())
+}
+
+/// Build or update the index. With `status`, nothing is drawn or printed:
+/// each stage resets that bar and reports its message, position and length
+/// on it, for callers that render progress themselves (e.g. `emry chat`).
+///
+/// Node writes are checkpointed every `batch_size` files, so a run that is
+/// interrupted picks up where it stopped instead of re-embedding everything.
+pub async fn run_index(
+    full: bool,
+    batch_size: usize,
+    config_path: Option<&Path>,
+    status: Option<ProgressBar>,
+) -> Result<()> {
+    build_index(full, batch_size, Clock::System, config_path, status).await
+}
+
+/// `run_index` with timestamps from `clock`.
+async fn build_index(
+    full: bool,
+    batch_size: usize,
+    clock: Clock,
+    config_path: Option<&Path>,
+    status: Option<ProgressBar>,
+) -> Result<()> {
+    super::utils::ensure_writable("index")?;
+    let batch_size = batch_size.max(1);
+    let say = |msg: &str| {
+        if status.is_none() {
+            println!("{}", msg);
+        }
+    };
     let root = std::env::current_dir()?;
     let branch = current_branch();
     let index_dir = root.join(".codeindex").join("branches").join(branch);
 
+    super::utils::ensure_no_daemon().await?;
+
     let config = if let Some(p) = config_path {
         Config::from_file(p)?
     } else {
@@ -38,7 +206,7 @@ pub async fn handle_index(full: bool, config_path: Option<&Path>) -> Result<()>
 
     if index_dir.exists() {
         if full {
-            println!("Full rebuild requested; clearing existing index...");
+            say("Full rebuild requested; clearing existing index...");
             let _ = std::fs::remove_dir_all(&index_dir);
         } else {
             info!(
@@ -49,6 +217,15 @@ pub async fn handle_index(full: bool, config_path: Option<&Path>) -> Result<()>
     }
     std::fs::create_dir_all(&index_dir)?;
 
+    let checkpoint_path = index_dir.join(CHECKPOINT_FILE);
+    let mut checkpoint = Checkpoint::load(&checkpoint_path);
+    if !checkpoint.written.is_empty() {
+        say(&format!(
+            "Resuming an interrupted run ({} files already written).",
+            checkpoint.written.len()
+        ));
+    }
+
     // Select embedder
     let embedder = select_embedder(&config.embedding).await.ok();
     let embedder_for_manager = embedder.clone();
@@ -56,23 +233,42 @@ pub async fn handle_index(full: bool, config_path: Option<&Path>) -> Result<()>
     
     // Initialize SurrealStore
     let surreal_path = index_dir.join("surreal.db");
-    let surreal_store = Arc::new(SurrealStore::new(&surreal_path, vector_dim).await?);
-    let ingestion_service = IngestionService::new(surreal_store.clone(), embedder_for_manager.clone());
-
-    let spinner_style = ProgressStyle::default_spinner()
-        .tick_chars("⠋⠙⠹⠸⠼⠴⠦⠧⠇⠏")
-        .template("{spinner:.green} {msg}")
-        .unwrap();
+    let surreal_store = Arc::new(SurrealStore::open_quantized(&surreal_path, vector_dim, config.embedding.quantization).await?);
+    super::utils::ensure_quantization(&surreal_store, &config.embedding)?;
+    let ingestion_service = Arc::new(
+        IngestionService::new(surreal_store.clone(), embedder_for_manager.clone())
+            .with_import_resolver(ImportResolver::load(&root)),
+    );
 
-    let spinner = ProgressBar::new_spinner();
-    spinner.set_style(spinner_style.clone());
-    spinner.set_message("Scanning repository...");
-    spinner.enable_steady_tick(Duration::from_millis(100));
+    let spinner = match &status {
+        Some(_) => stage_bar(&status, 0, "Scanning repository..."),
+        None => {
+            let spinner_style = ProgressStyle::default_spinner()
+                .tick_chars("⠋⠙⠹⠸⠼⠴⠦⠧⠇⠏")
+                .template("{spinner:.green} {msg}")
+                .unwrap();
+            let spinner = ProgressBar::new_spinner();
+            spinner.set_style(spinner_style);
+            spinner.set_message("Scanning repository...");
+            spinner.enable_steady_tick(Duration::from_millis(100));
+            spinner
+        }
+    };
 
-    let scanned_files = scan_repo(&root, &config.core);
+    let scan = scan_repo(&root, &config.core, &config.documents);
+    let scanned_files = scan.files;
     trace!("Scanned {} files.", scanned_files.len());
     spinner.finish_and_clear();
-    println!("Found {} source files to index.", scanned_files.len());
+    say(&format!("Found {} source files to index.", scanned_files.len()));
+    if !scan.skipped.is_empty() {
+        say(&format!(
+            "Skipped {} files that are larger than core.max_file_size_kb or binary; see `emry status`.",
+            scan.skipped.len()
+        ));
+    }
+    if let Err(e) = std::fs::write(index_dir.join(SKIPPED_FILES), serde_json::to_string(&scan.skipped)?) {
+        eprintln!("Failed to record skipped files: {}", e);
+    }
 
     // Load prior metadata
     let existing_files = surreal_store.list_files().await?;
@@ -84,13 +280,7 @@ pub async fn handle_index(full: bool, config_path: Option<&Path>) -> Result<()>
     let current_paths: HashSet<PathBuf> = scanned_files.iter().map(|f| f.path.clone()).collect();
     let mut stats = IndexStats::default();
 
-    let commit_id = format!(
-        "commit:{}",
-        SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .map(|d| d.as_millis())
-            .unwrap_or(0)
-    );
+    let commit_id = format!("commit:{}", clock.now_millis());
     let mut removed_files: Vec<PathBuf> = Vec::new();
 
     for (path, _rec) in meta_by_path.iter() {
@@ -113,14 +303,7 @@ pub async fn handle_index(full: bool, config_path: Option<&Path>) -> Result<()>
     let concurrency = 8;
     let num_scanned_files = scanned_files.len();
     
-    let pb = ProgressBar::new(num_scanned_files as u64);
-    pb.set_style(
-        ProgressStyle::default_bar()
-            .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({eta}) {msg}")
-            .unwrap()
-            .progress_chars("=>-"),
-    );
-    pb.set_message("Reading files");
+    let pb = stage_bar(&status, num_scanned_files as u64, "Reading files");
 
     let read_results: Vec<FileRead> =
         stream::iter(scanned_files.clone().into_iter().map(|file| async move {
@@ -131,18 +314,17 @@ pub async fn handle_index(full: bool, config_path: Option<&Path>) -> Result<()>
                     return None;
                 }
             };
-            let last_modified = tokio::fs::metadata(&file.path)
-                .await
-                .ok()
-                .and_then(|m| m.modified().ok())
-                .and_then(|ts| ts.duration_since(UNIX_EPOCH).ok())
-                .map(|d| d.as_secs())
-                .unwrap_or_else(|| {
-                    SystemTime::now()
-                        .duration_since(UNIX_EPOCH)
-                        .map(|d| d.as_secs())
-                        .unwrap_or(0)
-                });
+            // Checkout times differ between machines, so a fixed clock stands in for them.
+            let last_modified = match clock {
+                Clock::Fixed(secs) => secs,
+                Clock::System => tokio::fs::metadata(&file.path)
+                    .await
+                    .ok()
+                    .and_then(|m| m.modified().ok())
+                    .and_then(|ts| ts.duration_since(UNIX_EPOCH).ok())
+                    .map(|d| d.as_secs())
+                    .unwrap_or_else(|| clock.now_secs()),
+            };
             let hash = compute_hash(&content);
             Some(FileRead {
                 path: file.path,
@@ -156,19 +338,18 @@ pub async fn handle_index(full: bool, config_path: Option<&Path>) -> Result<()>
         .filter_map(|x| async move { x })
         .collect()
         .await;
+    // Reads finish in any order; everything downstream sees the files sorted.
+    let mut read_results = read_results;
+    read_results.sort_by(|a, b| a.path.cmp(&b.path));
 
     pb.finish_with_message("File reading complete");
 
-    let pb_proc = ProgressBar::new(read_results.len() as u64);
-    pb_proc.set_style(
-        ProgressStyle::default_bar()
-            .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({eta}) {msg}")
-            .unwrap()
-            .progress_chars("=>-"),
-    );
-    pb_proc.set_message("Processing changes");
+    let pb_proc = stage_bar(&status, read_results.len() as u64, "Processing changes");
 
     let mut work_items: Vec<FileInput> = Vec::new();
+    // Written by an interrupted run and unchanged since: analyzed again for edges, not re-embedded.
+    let mut resumed: Vec<FileInput> = Vec::new();
+    let mut pending = Checkpoint::default();
     for (_i, fr) in read_results.into_iter().enumerate() {
         pb_proc.inc(1);
 
@@ -180,6 +361,18 @@ pub async fn handle_index(full: bool, config_path: Option<&Path>) -> Result<()>
             if prev.hash == fr.hash {
                 // No change
                 stats.skipped_files += 1;
+                if checkpoint.written.get(&fr.path) == Some(&fr.hash) {
+                    pending.written.insert(fr.path.clone(), fr.hash.clone());
+                    resumed.push(FileInput {
+                        path: fr.path,
+                        language: fr.language,
+                        file_id: 0,
+                        file_node_id,
+                        hash: fr.hash,
+                        content: fr.content,
+                        last_modified: fr.last_modified,
+                    });
+                }
                 continue;
             } else {
                 // Changed: delete old version first (clears chunks/symbols)
@@ -201,67 +394,90 @@ pub async fn handle_index(full: bool, config_path: Option<&Path>) -> Result<()>
         });
     }
     pb_proc.finish_with_message("Change detection complete");
+    // Only entries still owed carry over; the rest were removed or changed since.
+    checkpoint = pending;
 
-    if work_items.is_empty() {
-        println!("No new or updated files to index.");
+    let mut changed_symbols = 0;
+    if work_items.is_empty() && resumed.is_empty() {
+        say("No new or updated files to index.");
     } else {
-        println!("Analyzing source code...");
-        let pb_analyze = ProgressBar::new(work_items.len() as u64);
-        pb_analyze.set_style(ProgressStyle::default_bar()
-            .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({eta}) {msg}")
-            .unwrap()
-            .progress_chars("=>-"));
-        pb_analyze.set_message("Parsing & Chunking");
-
         use emry_engine::ingest::pipeline::{analyze_source_files, generate_embeddings};
+        use emry_engine::ingest::service::IngestionContext;
+        use emry_engine::ingest::writer::NodeWriteQueue;
+
+        // Files are analyzed and embedded in batches and handed to a bounded
+        // write queue, so node writes for one batch overlap with the next.
+        say("Analyzing, embedding and ingesting nodes...");
+        let pb_nodes = stage_bar(&status, work_items.len() as u64, "Ingesting nodes");
+        pb_nodes.enable_steady_tick(Duration::from_millis(100));
+
+        let queue = NodeWriteQueue::spawn(ingestion_service.clone(), WRITE_QUEUE_CAPACITY);
+        let ticker = {
+            let progress = queue.progress();
+            let pb = pb_nodes.clone();
+            tokio::spawn(async move {
+                loop {
+                    pb.set_position(progress.written() as u64);
+                    pb.set_message(format!("write queue: {}", progress.depth()));
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                }
+            })
+        };
+
+        // The writer is first-in first-out, so its count says which queued files are written.
+        let progress = queue.progress();
+        let mut queued: Vec<(PathBuf, String)> = Vec::new();
+        let mut checkpointed = 0;
+        let mut remaining = work_items;
+        while !remaining.is_empty() {
+            let batch: Vec<FileInput> = remaining.drain(..remaining.len().min(batch_size)).collect();
+            let mut prepared = analyze_source_files(batch, &config, concurrency).await;
+            if let Some(emb) = &embedder {
+                generate_embeddings(&mut prepared, emb.clone(), &config).await;
+            }
+            for file in prepared {
+                queued.push((file.path.clone(), file.hash.clone()));
+                queue.push(IngestionContext::new(file)).await?;
+            }
 
-        pb_analyze.enable_steady_tick(Duration::from_millis(100));
-        let mut prepared = analyze_source_files(work_items, &config, concurrency).await;
-        pb_analyze.finish_with_message("Analysis complete");
-
-        if let Some(emb) = embedder {
-            println!("Generating embeddings...");
-            let pb_embed = ProgressBar::new_spinner();
-             pb_embed.set_style(ProgressStyle::default_spinner()
-                .tick_chars("⠋⠙⠹⠸⠼⠴⠦⠧⠇⠏")
-                .template("{spinner:.green} {msg}")
-                .unwrap());
-            pb_embed.set_message("Embedding chunks...");
-            pb_embed.enable_steady_tick(Duration::from_millis(100));
-            
-            generate_embeddings(&mut prepared, emb).await;
-            
-            pb_embed.finish_and_clear();
-            println!("Embeddings generated");
+            let written = progress.written().min(queued.len());
+            if written > checkpointed {
+                checkpoint.written.extend(queued[checkpointed..written].iter().cloned());
+                checkpointed = written;
+                if let Err(e) = checkpoint.save(&checkpoint_path) {
+                    eprintln!("Failed to save index checkpoint: {}", e);
+                }
+            }
         }
 
-        
-        use emry_engine::ingest::service::IngestionContext;
+        let mut contexts = queue.finish().await?;
+        ticker.abort();
+        pb_nodes.set_position(contexts.len() as u64);
+        pb_nodes.finish_with_message("Nodes ingested");
+        checkpoint.written.extend(queued);
+        if let Err(e) = checkpoint.save(&checkpoint_path) {
+            eprintln!("Failed to save index checkpoint: {}", e);
+        }
 
-        // Create contexts
-        let contexts: Vec<IngestionContext> = prepared.into_iter().map(IngestionContext::new).collect();
+        if !resumed.is_empty() {
+            say(&format!("Linking {} files written by the interrupted run...", resumed.len()));
+            let prepared = analyze_source_files(resumed, &config, concurrency).await;
+            contexts.extend(prepared.into_iter().map(IngestionContext::new));
+        }
 
-        let pb_nodes = ProgressBar::new(contexts.len() as u64);
-        pb_nodes.set_style(ProgressStyle::default_bar()
-            .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({eta}) {msg}")
-            .unwrap()
-            .progress_chars("=>-"));
-        pb_nodes.set_message("Ingesting nodes");
+        let run_timestamp = clock.now_secs();
+        let versions = contexts.iter().flat_map(|c| c.symbol_versions(&commit_id, run_timestamp)).collect();
+        match surreal_store.record_symbol_versions(versions).await {
+            Ok(n) => changed_symbols = n,
+            Err(e) => eprintln!("Failed to record symbol history: {}", e),
+        }
 
-        for ctx in &contexts {
-            if let Err(e) = ingestion_service.ingest_nodes(ctx).await {
-                 eprintln!("Failed to ingest nodes for {}: {}", ctx.file.path.display(), e);
-            }
-            pb_nodes.inc(1);
+        // All symbols now exist, so edge resolution can skip lookups for names defined nowhere.
+        if let Err(e) = surreal_store.warm_symbol_filter().await {
+            eprintln!("Failed to build symbol name filter: {}", e);
         }
-        pb_nodes.finish_with_message("Nodes ingested");
 
-        let pb_edges = ProgressBar::new(contexts.len() as u64);
-        pb_edges.set_style(ProgressStyle::default_bar()
-            .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({eta}) {msg}")
-            .unwrap()
-            .progress_chars("=>-"));
-        pb_edges.set_message("Ingesting edges");
+        let pb_edges = stage_bar(&status, contexts.len() as u64, "Ingesting edges");
 
         for ctx in &contexts {
             if let Err(e) = ingestion_service.ingest_edges(ctx).await {
@@ -270,6 +486,24 @@ pub async fn handle_index(full: bool, config_path: Option<&Path>) -> Result<()>
             pb_edges.inc(1);
         }
         pb_edges.finish_with_message("Edges ingested");
+
+        // Precompute bounded neighbourhoods so query-time graph boosts are single lookups.
+        let max_hops = config.graph.max_depth;
+        let refreshed = if full {
+            surreal_store.rebuild_neighborhoods(max_hops).await
+        } else {
+            let changed: Vec<String> = contexts.iter().flat_map(|c| c.id_map.values().cloned()).collect();
+            surreal_store.refresh_neighborhoods(&changed, max_hops).await
+        };
+        match refreshed {
+            Ok(n) => trace!("Updated {} graph neighbourhoods", n),
+            Err(e) => eprintln!("Failed to update graph neighbourhoods: {}", e),
+        }
+    }
+
+    // Unchanged files keep their owners, so this also picks up CODEOWNERS edits.
+    if let Err(e) = super::owners::refresh_ownership(&surreal_store, &root, &config.owners).await {
+        eprintln!("Failed to update file owners: {}", e);
     }
 
     let note = format!(
@@ -277,12 +511,30 @@ pub async fn handle_index(full: bool, config_path: Option<&Path>) -> Result<()>
         stats.new_files, stats.updated_files, stats.removed_files, stats.skipped_files
     );
     
-    surreal_store.add_commit(commit_id, SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .map(|d| d.as_secs())
-            .unwrap_or(0), note).await?;
+    surreal_store.add_commit(commit_id, clock.now_secs(), note, changed_symbols).await?;
+    let _ = std::fs::remove_file(&checkpoint_path);
 
-    use super::ui;
-    ui::print_success("Indexing complete!");
+    if status.is_none() {
+        super::ui::print_success("Indexing complete!");
+    }
     Ok(())
 }
+
+/// The progress bar for one indexing stage: a new terminal bar, or the
+/// caller's `status` bar reset for this stage.
+fn stage_bar(status: &Option<ProgressBar>, len: u64, message: &str) -> ProgressBar {
+    match status {
+        Some(bar) => {
+            bar.reset();
+            bar.set_length(len);
+            bar.set_message(message.to_string());
+            bar.clone()
+        }
+        None => {
+            let pb = ProgressBar::new(len);
+            pb.set_style(ProgressStyle::default_bar().template(BAR_TEMPLATE).unwrap().progress_chars("=>-"));
+            pb.set_message(message.to_string());
+            pb
+        }
+    }
+}
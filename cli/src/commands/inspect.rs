@@ -1,10 +1,7 @@
 use anyhow::Result;
 use clap::Parser;
 use emry_agent::project as agent_context;
-use emry_agent::project::embedder::get_embedding_dimension;
-use emry_store::SurrealStore;
 use std::path::Path;
-use std::sync::Arc;
 use console::Style;
 use super::ui;
 
@@ -17,16 +14,9 @@ pub struct InspectArgs {
 pub async fn handle_inspect(args: InspectArgs, config_path: Option<&Path>) -> Result<()> {
     ui::print_header(&format!("Inspecting: {}", args.id));
 
-    let ctx = agent_context::RepoContext::from_env(config_path).await?;
-    
-    // Initialize SurrealStore if not already in context
-    let surreal_store = if let Some(store) = ctx.surreal_store {
-        store
-    } else {
-        let surreal_path = ctx.index_dir.join("surreal.db");
-        let vector_dim = get_embedding_dimension(&ctx.config.embedding);
-        Arc::new(SurrealStore::new(&surreal_path, vector_dim).await?)
-    };
+    let ctx = agent_context::RepoContext::open(config_path, agent_context::Components::STORE).await?;
+    let surreal_store = ctx.surreal_store
+        .ok_or_else(|| anyhow::anyhow!("SurrealStore not initialized"))?;
 
     // 1. Try as Chunk
     if let Ok(Some(chunk)) = surreal_store.get_chunk(&args.id).await {
@@ -0,0 +1,312 @@
+//! `emry lsp`: a minimal Language Server Protocol server over stdio, backed
+//! by the index rather than a compiler.
+//!
+//! It answers `workspace/symbol` from the symbol table and
+//! `textDocument/definition` and `textDocument/references` from the call
+//! graph, resolving the identifier under the cursor by name. That gives
+//! editors cross-file navigation for every language emry indexes, including
+//! those without a language server of their own. Results are as fresh as the
+//! last `emry index` (or `emry watch`).
+
+use anyhow::{anyhow, bail, Context, Result};
+use emry_agent::project::{Components, RepoContext};
+use emry_store::{SurrealGraphNode, SurrealStore};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+
+/// Most symbols one `workspace/symbol` request returns.
+const MAX_WORKSPACE_SYMBOLS: usize = 200;
+
+pub async fn handle_lsp(config_path: Option<&Path>) -> Result<()> {
+    let ctx = RepoContext::open(config_path, Components::STORE).await?;
+    let store = ctx.surreal_store.clone()
+        .ok_or_else(|| anyhow!("SurrealStore not initialized. Run 'emry index' first."))?;
+    serve_stdio(LspServer::new(ctx.root.clone(), store)).await
+}
+
+/// Transport-independent JSON-RPC handler for the LSP requests emry serves.
+pub struct LspServer {
+    root: PathBuf,
+    store: Arc<SurrealStore>,
+    /// Text of the documents the editor has open, by URI; unsaved edits
+    /// would otherwise put the cursor on the wrong identifier.
+    documents: Mutex<HashMap<String, String>>,
+}
+
+type RpcResult = std::result::Result<Value, (i64, String)>;
+
+impl LspServer {
+    pub fn new(root: PathBuf, store: Arc<SurrealStore>) -> Self {
+        Self { root, store, documents: Mutex::new(HashMap::new()) }
+    }
+
+    /// Handle one JSON-RPC message. Notifications yield `None`.
+    pub async fn handle(&self, msg: Value) -> Option<Value> {
+        let method = msg["method"].as_str().unwrap_or("");
+        let params = msg.get("params").cloned().unwrap_or(Value::Null);
+        let Some(id) = msg.get("id").cloned() else {
+            self.notify(method, &params);
+            return None;
+        };
+
+        let result = match method {
+            "initialize" => Ok(json!({
+                "capabilities": {
+                    // Full document sync: every change carries the whole text.
+                    "textDocumentSync": 1,
+                    "workspaceSymbolProvider": true,
+                    "definitionProvider": true,
+                    "referencesProvider": true
+                },
+                "serverInfo": { "name": "emry", "version": env!("CARGO_PKG_VERSION") }
+            })),
+            "shutdown" => Ok(Value::Null),
+            "workspace/symbol" => self.workspace_symbols(&params).await,
+            "textDocument/definition" => self.definition(&params).await,
+            "textDocument/references" => self.references(&params).await,
+            _ => Err((-32601, format!("Method not found: {}", method))),
+        };
+
+        Some(match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err((code, message)) => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": { "code": code, "message": message }
+            }),
+        })
+    }
+
+    fn notify(&self, method: &str, params: &Value) {
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or("").to_string();
+        let mut documents = self.documents.lock().unwrap();
+        match method {
+            "textDocument/didOpen" => {
+                if let Some(text) = params["textDocument"]["text"].as_str() {
+                    documents.insert(uri, text.to_string());
+                }
+            }
+            "textDocument/didChange" => {
+                let changes = params["contentChanges"].as_array();
+                if let Some(text) = changes.and_then(|c| c.last()).and_then(|c| c["text"].as_str()) {
+                    documents.insert(uri, text.to_string());
+                }
+            }
+            "textDocument/didClose" => {
+                documents.remove(&uri);
+            }
+            _ => {}
+        }
+    }
+
+    async fn workspace_symbols(&self, params: &Value) -> RpcResult {
+        let query = params["query"].as_str().unwrap_or("").to_lowercase();
+        let symbols = self.store.list_all_symbols().await.map_err(internal)?;
+        let matches: Vec<Value> = symbols
+            .iter()
+            .filter(|s| s.label.to_lowercase().contains(&query))
+            .take(MAX_WORKSPACE_SYMBOLS)
+            .map(|s| json!({
+                "name": s.label,
+                "kind": symbol_kind(&s.kind),
+                "location": self.location(&s.file_path, s.start_line.unwrap_or(1), 1),
+            }))
+            .collect();
+        Ok(json!(matches))
+    }
+
+    async fn definition(&self, params: &Value) -> RpcResult {
+        let Some(name) = self.identifier_at(params) else { return Ok(Value::Null) };
+        let definitions = self.store.find_definition(&name).await.map_err(internal)?;
+        Ok(json!(definitions.iter().map(|d| self.definition_location(d)).collect::<Vec<_>>()))
+    }
+
+    async fn references(&self, params: &Value) -> RpcResult {
+        let Some(name) = self.identifier_at(params) else { return Ok(Value::Null) };
+        let include_declaration = params["context"]["includeDeclaration"].as_bool().unwrap_or(false);
+        let definitions = self.store.find_definition(&name).await.map_err(internal)?;
+
+        let mut locations = Vec::new();
+        for definition in &definitions {
+            if include_declaration {
+                locations.push(self.definition_location(definition));
+            }
+            for site in self.store.call_sites(&definition.id.to_string()).await.map_err(internal)? {
+                locations.push(self.location(&site.file_path, site.line, site.column.unwrap_or(1)));
+            }
+        }
+        Ok(json!(locations))
+    }
+
+    fn definition_location(&self, node: &SurrealGraphNode) -> Value {
+        self.location(&node.file_path, node.start_line.unwrap_or(1), 1)
+    }
+
+    /// An LSP `Location` for a 1-based `line` and byte `column` of `file`;
+    /// LSP counts both from 0, and columns in UTF-16 code units, so the
+    /// column is converted against the file's text when it can be read.
+    fn location(&self, file: &str, line: usize, column: usize) -> Value {
+        let path = self.root.join(file);
+        let uri = path_to_uri(&path);
+        let row = line.saturating_sub(1);
+        let prefix = column.saturating_sub(1);
+        let character = match prefix {
+            0 => 0,
+            _ => self
+                .text(&uri, &path)
+                .and_then(|text| text.lines().nth(row).map(|l| utf16_len(l.get(..prefix).unwrap_or(l))))
+                .unwrap_or(prefix),
+        };
+        let position = json!({ "line": row, "character": character });
+        json!({ "uri": uri, "range": { "start": position, "end": position } })
+    }
+
+    /// The identifier under the request's `textDocument` and `position`.
+    fn identifier_at(&self, params: &Value) -> Option<String> {
+        let uri = params["textDocument"]["uri"].as_str()?;
+        let row = params["position"]["line"].as_u64()? as usize;
+        let character = params["position"]["character"].as_u64()? as usize;
+        let text = self.text(uri, &uri_to_path(uri)?)?;
+        let line = text.lines().nth(row)?;
+
+        // Position's character counts UTF-16 code units.
+        let mut units = 0;
+        let offset = line
+            .char_indices()
+            .find(|(_, c)| {
+                units += c.len_utf16();
+                units > character
+            })
+            .map_or(line.len(), |(i, _)| i);
+        identifier_around(line, offset)
+    }
+
+    /// The editor's copy of `uri` when it's open, else the file on disk.
+    fn text(&self, uri: &str, path: &Path) -> Option<String> {
+        if let Some(text) = self.documents.lock().unwrap().get(uri) {
+            return Some(text.clone());
+        }
+        std::fs::read_to_string(path).ok()
+    }
+}
+
+fn internal(e: anyhow::Error) -> (i64, String) {
+    (-32603, e.to_string())
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+/// The identifier containing, or ending right before, byte `offset` of `line`.
+fn identifier_around(line: &str, offset: usize) -> Option<String> {
+    let start = line[..offset].rfind(|c: char| !is_identifier_char(c)).map_or(0, |i| i + line[i..].chars().next().unwrap().len_utf8());
+    let end = line[offset..].find(|c: char| !is_identifier_char(c)).map_or(line.len(), |i| offset + i);
+    let word = &line[start..end];
+    (!word.is_empty() && !word.starts_with(|c: char| c.is_ascii_digit())).then(|| word.to_string())
+}
+
+fn utf16_len(s: &str) -> usize {
+    s.chars().map(char::len_utf16).sum()
+}
+
+/// LSP `SymbolKind` for an index symbol kind.
+fn symbol_kind(kind: &str) -> u8 {
+    match kind {
+        "module" | "namespace" => 2,
+        "class" => 5,
+        "method" => 6,
+        "property" => 7,
+        "field" => 8,
+        "constructor" => 9,
+        "enum" => 10,
+        "interface" | "trait" => 11,
+        "function" | "macro" => 12,
+        "constant" => 14,
+        "enum_variant" => 22,
+        "struct" => 23,
+        "type" => 26,
+        _ => 13,
+    }
+}
+
+fn path_to_uri(path: &Path) -> String {
+    let mut uri = String::from("file://");
+    for byte in path.to_string_lossy().bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => uri.push(byte as char),
+            _ => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    uri
+}
+
+fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    let encoded = uri.strip_prefix("file://")?.as_bytes();
+    let mut bytes = Vec::with_capacity(encoded.len());
+    let mut i = 0;
+    while i < encoded.len() {
+        let escaped = (encoded[i] == b'%')
+            .then(|| encoded.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match escaped {
+            Some(byte) => {
+                bytes.push(byte);
+                i += 3;
+            }
+            None => {
+                bytes.push(encoded[i]);
+                i += 1;
+            }
+        }
+    }
+    Some(PathBuf::from(String::from_utf8(bytes).ok()?))
+}
+
+/// Serve `server` over stdin/stdout with LSP's `Content-Length` framing until
+/// the client sends `exit` or closes stdin.
+async fn serve_stdio(server: LspServer) -> Result<()> {
+    let mut reader = BufReader::new(tokio::io::stdin());
+    let mut stdout = tokio::io::stdout();
+
+    loop {
+        let mut length = None;
+        loop {
+            let mut header = String::new();
+            if reader.read_line(&mut header).await? == 0 {
+                return Ok(());
+            }
+            let header = header.trim();
+            if header.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = header.split_once(':') {
+                if name.eq_ignore_ascii_case("content-length") {
+                    length = Some(value.trim().parse::<usize>().context("invalid Content-Length header")?);
+                }
+            }
+        }
+        let Some(length) = length else { bail!("LSP message without a Content-Length header") };
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body).await?;
+
+        let response = match serde_json::from_slice::<Value>(&body) {
+            Ok(msg) if msg["method"] == "exit" => return Ok(()),
+            Ok(msg) => server.handle(msg).await,
+            Err(e) => Some(json!({
+                "jsonrpc": "2.0",
+                "id": Value::Null,
+                "error": { "code": -32700, "message": format!("Parse error: {}", e) }
+            })),
+        };
+        if let Some(response) = response {
+            let body = response.to_string();
+            stdout.write_all(format!("Content-Length: {}\r\n\r\n{}", body.len(), body).as_bytes()).await?;
+            stdout.flush().await?;
+        }
+    }
+}
@@ -1,25 +1,190 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
+use emry_agent::ops::export::{write_graph, ExportFormat};
 use emry_agent::ops::fs::FsTool;
-use emry_agent::project::RepoContext;
-use std::path::Path;
+use emry_agent::project::types::{GraphEdge, GraphNode, GraphSubgraph};
+use emry_agent::project::{Components, RepoContext};
+use emry_core::entry_points::EntryPointKind;
+use emry_core::map::{render_codebase_map, render_ranked_map, MapEntry, RankedSymbol};
+use emry_core::models::EdgeKind;
+use emry_core::pagerank::pagerank;
+use emry_store::entry_points::EntryPointNode;
+use emry_store::SurrealStore;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
-pub async fn handle_codebase_map(depth: usize, verbose: bool, config_path: Option<&Path>) -> Result<()> {
+/// Symbols listed per file in the tree.
+const SYMBOLS_PER_FILE: usize = 5;
+
+pub async fn handle_codebase_map(
+    depth: usize,
+    verbose: bool,
+    mermaid: bool,
+    tokens: Option<usize>,
+    focus: Vec<String>,
+    config_path: Option<&Path>,
+) -> Result<()> {
     use super::ui;
     use console::Style;
 
-    ui::print_header("Codebase Map");
-
-    let ctx = Arc::new(RepoContext::from_env(config_path).await?);
+    let ctx = Arc::new(RepoContext::open(config_path, Components::STORE).await?);
     let fs_tool = FsTool::new(ctx.clone());
 
+    let mut entries = fs_tool.codebase_entries(depth)?;
+    let mut ranked = Vec::new();
+    if let Some(store) = &ctx.surreal_store {
+        add_entry_points(&mut entries, store.entry_points().await?, &ctx.root);
+        ranked = rank_symbols(store, &ctx.root, &focus).await?;
+        add_ranked_symbols(&mut entries, &ranked);
+    }
+    if ranked.is_empty() && (tokens.is_some() || !focus.is_empty()) {
+        bail!("--tokens and --focus rank indexed symbols. Run 'emry index' first.");
+    }
+
+    if let Some(tokens) = tokens.filter(|_| !mermaid) {
+        print!("{}", render_ranked_map(&ctx.root, &ranked, tokens));
+        return Ok(());
+    }
+
+    if mermaid {
+        let graph = map_graph(&entries);
+        write_graph(&graph, ExportFormat::Mermaid, &mut std::io::stdout().lock())?;
+        return Ok(());
+    }
+
+    ui::print_header("Codebase Map");
+
     if verbose {
         ui::print_panel("Step", "Generating codebase map...", Style::new().blue(), Some(Style::new().dim()));
     }
 
-    let map = fs_tool.generate_codebase_map(depth)?;
-    
-    println!("{}", map);
+    let map = render_codebase_map(&ctx.root, &entries);
+    ui::page(&format!("{}\n", map));
 
     Ok(())
 }
+
+/// Indexed symbols by PageRank over call and import edges, highest first.
+/// With `focus` paths, teleports go only to symbols defined under them, so
+/// the ranking favours what that part of the code uses.
+async fn rank_symbols(store: &SurrealStore, root: &Path, focus: &[String]) -> Result<Vec<RankedSymbol>> {
+    let symbols = store.list_all_symbols().await?;
+    let mut index: HashMap<String, usize> = symbols.iter().enumerate().map(|(i, s)| (s.id.to_string(), i)).collect();
+    let mut edges = Vec::new();
+    for kind in [EdgeKind::Calls, EdgeKind::Imports] {
+        for (from, to) in store.list_edges(kind).await? {
+            let Some(&to) = index.get(&to) else { continue };
+            // Importing files join the graph as sources.
+            let next = index.len();
+            let from = *index.entry(from).or_insert(next);
+            if from != to {
+                edges.push((from, to));
+            }
+        }
+    }
+
+    let relative = |file: &str| Path::new(file).strip_prefix(root).unwrap_or(Path::new(file)).to_path_buf();
+    let focus: Vec<PathBuf> = focus.iter().map(|f| PathBuf::from(f.trim_start_matches("./"))).collect();
+    let personalization: Option<Vec<f64>> = (!focus.is_empty()).then(|| {
+        (0..index.len())
+            .map(|i| match symbols.get(i) {
+                Some(s) if focus.iter().any(|f| relative(&s.file_path).starts_with(f)) => 1.0,
+                _ => 0.0,
+            })
+            .collect()
+    });
+    if personalization.as_ref().is_some_and(|p| !p.contains(&1.0)) {
+        bail!("no indexed symbols under --focus {}", focus.iter().map(|f| f.display().to_string()).collect::<Vec<_>>().join(", "));
+    }
+    let rank = pagerank(index.len(), &edges, personalization.as_deref());
+
+    let mut ranked: Vec<RankedSymbol> = symbols
+        .into_iter()
+        .zip(rank)
+        .map(|(s, rank)| RankedSymbol { path: relative(&s.file_path), name: s.label, kind: s.kind, line: s.start_line, rank })
+        .collect();
+    ranked.sort_by(|a, b| b.rank.total_cmp(&a.rank).then_with(|| a.path.cmp(&b.path)).then(a.line.cmp(&b.line)));
+    Ok(ranked)
+}
+
+/// List each file's highest-ranked symbols in place of the parser's first few.
+fn add_ranked_symbols(entries: &mut [MapEntry], ranked: &[RankedSymbol]) {
+    let mut by_file: HashMap<&Path, Vec<String>> = HashMap::new();
+    for symbol in ranked {
+        let names = by_file.entry(symbol.path.as_path()).or_default();
+        if names.len() < SYMBOLS_PER_FILE && !names.contains(&symbol.name) {
+            names.push(symbol.name.clone());
+        }
+    }
+    for entry in entries.iter_mut().filter(|e| !e.is_dir) {
+        if let Some(names) = by_file.remove(entry.path.as_path()) {
+            entry.symbols = names;
+        }
+    }
+}
+
+/// List each file's indexed entry points, tests aside, as `name (kind)`.
+fn add_entry_points(entries: &mut [MapEntry], found: Vec<EntryPointNode>, root: &Path) {
+    let mut by_file: HashMap<PathBuf, Vec<String>> = HashMap::new();
+    for entry in found.into_iter().filter(|e| e.entry_kind != EntryPointKind::Test) {
+        let path = Path::new(&entry.file_path);
+        let relative = path.strip_prefix(root).unwrap_or(path).to_path_buf();
+        by_file.entry(relative).or_default().push(format!("{} ({})", entry.label, entry.entry_kind.as_str()));
+    }
+    for entry in entries.iter_mut().filter(|e| !e.is_dir) {
+        if let Some(names) = by_file.remove(&entry.path) {
+            entry.entry_points = names;
+        }
+    }
+}
+
+/// The map as a tree: the root contains directories and files, and files
+/// define their listed symbols and entry points, the latter marked `▶`.
+fn map_graph(entries: &[MapEntry]) -> GraphSubgraph {
+    let node = |id: String, kind: &str, label: String, file_path: String| GraphNode {
+        id,
+        kind: kind.to_string(),
+        label,
+        file_path,
+        canonical_id: None,
+    };
+    let edge = |source: &str, target: &str, kind: EdgeKind| GraphEdge {
+        source: source.to_string(),
+        target: target.to_string(),
+        kind: kind.as_str().to_string(),
+        line: None,
+        column: None,
+    };
+
+    let mut graph = GraphSubgraph { nodes: vec![node(".".to_string(), "dir", ".".to_string(), String::new())], edges: Vec::new() };
+    for entry in entries {
+        let path = entry.path.display().to_string();
+        let parent = match entry.path.parent() {
+            Some(p) if !p.as_os_str().is_empty() => p.display().to_string(),
+            _ => ".".to_string(),
+        };
+        let name = entry.path.file_name().unwrap_or_default().to_string_lossy();
+        let label = if entry.is_dir { format!("{}/", name) } else { name.to_string() };
+        graph.nodes.push(node(path.clone(), if entry.is_dir { "dir" } else { "file" }, label, path.clone()));
+        graph.edges.push(edge(&parent, &path, EdgeKind::Contains));
+        for symbol in &entry.symbols {
+            let id = format!("{}::{}", path, symbol);
+            graph.nodes.push(node(id.clone(), "symbol", symbol.clone(), path.clone()));
+            graph.edges.push(edge(&path, &id, EdgeKind::Defines));
+        }
+        for entry_point in &entry.entry_points {
+            let name = entry_point.split(' ').next().unwrap_or(entry_point);
+            let id = format!("{}::{}", path, name);
+            let label = format!("▶ {}", entry_point);
+            // A listed symbol keeps its node and gains the marker.
+            match graph.nodes.iter_mut().find(|n| n.id == id) {
+                Some(existing) => existing.label = label,
+                None => {
+                    graph.nodes.push(node(id.clone(), "entry_point", label, path.clone()));
+                    graph.edges.push(edge(&path, &id, EdgeKind::Defines));
+                }
+            }
+        }
+    }
+    graph
+}
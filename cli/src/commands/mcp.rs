@@ -0,0 +1,281 @@
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use emry_agent::cortex::tool::Tool;
+use emry_agent::cortex::tools::registry::default_tools;
+use emry_agent::llm::OpenAIProvider;
+use emry_agent::project as agent_context;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+
+const PROTOCOL_VERSION: &str = "2024-11-05";
+/// Largest message body the SSE transport accepts.
+const MAX_BODY: usize = 4 * 1024 * 1024;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+pub enum McpTransport {
+    Stdio,
+    Sse,
+}
+
+pub async fn handle_mcp(transport: McpTransport, port: u16, config_path: Option<&Path>) -> Result<()> {
+    let ctx = Arc::new(agent_context::RepoContext::from_env(config_path).await?);
+    let store = ctx.surreal_store.clone()
+        .ok_or_else(|| anyhow::anyhow!("SurrealStore not initialized in context"))?;
+    let search_service = Arc::new(ctx.search_service(store));
+
+    // LLM-backed tools are exposed only when a key is configured.
+    let llm = match std::env::var("OPENAI_API_KEY") {
+        Ok(key) => {
+            let model = std::env::var("OPENAI_MODEL").unwrap_or_else(|_| "gpt-4o-mini".to_string());
+            Some(OpenAIProvider::new(model, key, 60)?)
+        }
+        Err(_) => None,
+    };
+
+    let tools = default_tools(ctx, search_service, llm.as_ref())?;
+    let server = Arc::new(McpServer::new(tools));
+
+    match transport {
+        McpTransport::Stdio => serve_stdio(server).await,
+        McpTransport::Sse => serve_sse(server, port).await,
+    }
+}
+
+/// Transport-independent JSON-RPC handler for the MCP tool surface.
+pub struct McpServer {
+    tools: HashMap<String, Arc<dyn Tool>>,
+}
+
+impl McpServer {
+    pub fn new(tools: Vec<Arc<dyn Tool>>) -> Self {
+        let tools = tools.into_iter().map(|t| (t.name().to_string(), t)).collect();
+        Self { tools }
+    }
+
+    /// Handle one JSON-RPC message. Notifications yield `None`.
+    pub async fn handle(&self, msg: Value) -> Option<Value> {
+        let id = msg.get("id").cloned()?;
+        let method = msg["method"].as_str().unwrap_or("");
+        let params = msg.get("params").cloned().unwrap_or(Value::Null);
+
+        let result = match method {
+            "initialize" => Ok(json!({
+                "protocolVersion": PROTOCOL_VERSION,
+                "capabilities": { "tools": {} },
+                "serverInfo": { "name": "emry", "version": env!("CARGO_PKG_VERSION") }
+            })),
+            "ping" => Ok(json!({})),
+            "tools/list" => {
+                let mut tools: Vec<Value> = self.tools.values().map(|t| json!({
+                    "name": t.name(),
+                    "description": t.description(),
+                    "inputSchema": t.schema(),
+                })).collect();
+                tools.sort_by(|a, b| a["name"].as_str().cmp(&b["name"].as_str()));
+                Ok(json!({ "tools": tools }))
+            }
+            "tools/call" => self.call_tool(&params).await,
+            _ => Err((-32601, format!("Method not found: {}", method))),
+        };
+
+        Some(match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err((code, message)) => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": { "code": code, "message": message }
+            }),
+        })
+    }
+
+    async fn call_tool(&self, params: &Value) -> std::result::Result<Value, (i64, String)> {
+        let name = params["name"].as_str().ok_or((-32602, "Missing tool name".to_string()))?;
+        let tool = self.tools.get(name).ok_or_else(|| (-32602, format!("Unknown tool: {}", name)))?;
+        let args = params.get("arguments").cloned().unwrap_or_else(|| json!({}));
+
+        // Tool failures are reported in-band so the client model can react to them.
+        let (text, is_error) = match tool.execute(args).await {
+            Ok(text) => (text, false),
+            Err(e) => (format!("Error executing tool '{}': {}", name, e), true),
+        };
+        Ok(json!({
+            "content": [{ "type": "text", "text": text }],
+            "isError": is_error
+        }))
+    }
+}
+
+fn parse_error(e: serde_json::Error) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": Value::Null,
+        "error": { "code": -32700, "message": format!("Parse error: {}", e) }
+    })
+}
+
+async fn serve_stdio(server: Arc<McpServer>) -> Result<()> {
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    let mut stdout = tokio::io::stdout();
+
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str::<Value>(&line) {
+            Ok(msg) => server.handle(msg).await,
+            Err(e) => Some(parse_error(e)),
+        };
+        if let Some(response) = response {
+            stdout.write_all(format!("{}\n", response).as_bytes()).await?;
+            stdout.flush().await?;
+        }
+    }
+    Ok(())
+}
+
+type Sessions = Arc<Mutex<HashMap<String, mpsc::UnboundedSender<Value>>>>;
+
+async fn serve_sse(server: Arc<McpServer>, port: u16) -> Result<()> {
+    let listener = TcpListener::bind(("127.0.0.1", port))
+        .await
+        .with_context(|| format!("failed to bind 127.0.0.1:{}", port))?;
+    eprintln!("MCP SSE server listening on http://127.0.0.1:{}/sse", port);
+
+    let sessions: Sessions = Arc::new(Mutex::new(HashMap::new()));
+
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let server = server.clone();
+        let sessions = sessions.clone();
+        tokio::spawn(async move {
+            if let Err(e) = handle_http(stream, server, sessions, port).await {
+                tracing::debug!("MCP connection error: {}", e);
+            }
+        });
+    }
+}
+
+async fn handle_http(stream: TcpStream, server: Arc<McpServer>, sessions: Sessions, port: u16) -> Result<()> {
+    let mut reader = BufReader::new(stream);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).await?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or("").to_string();
+    let target = parts.next().unwrap_or("").to_string();
+
+    let mut content_length = 0usize;
+    let mut host = None;
+    let mut origin = None;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header).await? == 0 || header == "\r\n" || header == "\n" {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            let name = name.trim();
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap_or(0);
+            } else if name.eq_ignore_ascii_case("host") {
+                host = Some(value.trim().to_string());
+            } else if name.eq_ignore_ascii_case("origin") {
+                origin = Some(value.trim().to_string());
+            }
+        }
+    }
+
+    // A web page can reach this port through DNS rebinding; only serve requests addressed to it locally.
+    if !local_request(host.as_deref(), origin.as_deref(), port) {
+        let mut stream = reader.into_inner();
+        stream.write_all(b"HTTP/1.1 403 Forbidden\r\nContent-Length: 0\r\n\r\n").await?;
+        return Ok(());
+    }
+
+    let (path, query) = target.split_once('?').unwrap_or((target.as_str(), ""));
+
+    match (method.as_str(), path) {
+        ("GET", "/sse") => {
+            let session_id = session_id()?;
+            let (tx, mut rx) = mpsc::unbounded_channel();
+            sessions.lock().unwrap().insert(session_id.clone(), tx);
+
+            let mut stream = reader.into_inner();
+            stream.write_all(
+                b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: keep-alive\r\n\r\n",
+            ).await?;
+            stream.write_all(format!("event: endpoint\ndata: /message?sessionId={}\n\n", session_id).as_bytes()).await?;
+            stream.flush().await?;
+
+            while let Some(msg) = rx.recv().await {
+                let frame = format!("event: message\ndata: {}\n\n", msg);
+                if stream.write_all(frame.as_bytes()).await.is_err() {
+                    break;
+                }
+                let _ = stream.flush().await;
+            }
+            sessions.lock().unwrap().remove(&session_id);
+            Ok(())
+        }
+        ("POST", "/message") => {
+            if content_length > MAX_BODY {
+                let mut stream = reader.into_inner();
+                stream.write_all(b"HTTP/1.1 413 Payload Too Large\r\nContent-Length: 0\r\n\r\n").await?;
+                return Ok(());
+            }
+            let mut body = vec![0u8; content_length];
+            reader.read_exact(&mut body).await?;
+            let mut stream = reader.into_inner();
+
+            let session_id = query
+                .split('&')
+                .find_map(|kv| kv.strip_prefix("sessionId="))
+                .unwrap_or("");
+            let sender = sessions.lock().unwrap().get(session_id).cloned();
+            let Some(sender) = sender else {
+                stream.write_all(b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n").await?;
+                return Ok(());
+            };
+
+            stream.write_all(b"HTTP/1.1 202 Accepted\r\nContent-Length: 0\r\n\r\n").await?;
+            stream.flush().await?;
+
+            let response = match serde_json::from_slice::<Value>(&body) {
+                Ok(msg) => server.handle(msg).await,
+                Err(e) => Some(parse_error(e)),
+            };
+            if let Some(response) = response {
+                let _ = sender.send(response);
+            }
+            Ok(())
+        }
+        _ => {
+            let mut stream = reader.into_inner();
+            stream.write_all(b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n").await?;
+            Ok(())
+        }
+    }
+}
+
+/// Whether a request was sent to this server as `127.0.0.1:<port>` or
+/// `localhost:<port>` and, if from a web page, from a local one.
+fn local_request(host: Option<&str>, origin: Option<&str>, port: u16) -> bool {
+    let host_ok = host.is_some_and(|h| h == format!("127.0.0.1:{}", port) || h == format!("localhost:{}", port));
+    let origin_ok = origin.map_or(true, |o| {
+        let authority = o.strip_prefix("http://").or_else(|| o.strip_prefix("https://")).unwrap_or("");
+        let name = authority.rsplit_once(':').map_or(authority, |(name, _)| name);
+        name == "127.0.0.1" || name == "localhost"
+    });
+    host_ok && origin_ok
+}
+
+/// A random session id, so that no other client can post into a session it didn't open.
+fn session_id() -> Result<String> {
+    let mut bytes = [0u8; 16];
+    std::io::Read::read_exact(&mut std::fs::File::open("/dev/urandom")?, &mut bytes)?;
+    Ok(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
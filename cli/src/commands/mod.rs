@@ -1,13 +1,38 @@
+pub mod apply;
 pub mod ask;
+pub mod bench;
+pub mod browse;
+pub mod calls;
 
 pub mod cat;
+pub mod chat;
+pub mod check;
+pub mod compare;
+pub mod config;
+pub mod coverage;
+pub mod complete;
+pub mod crash;
+pub mod daemon;
+pub mod clones;
+pub mod deadcode;
+pub mod outline;
+pub mod owners;
+pub mod pack;
+pub mod doctor;
 pub mod explore;
+pub mod federation;
 pub mod graph;
+pub mod history;
 pub mod index;
 pub mod inspect;
 pub mod regex_utils;
+pub mod rename;
+pub mod replica;
+pub mod report_issue;
 pub mod search;
+pub mod sessions;
 pub mod status;
+pub mod tune;
 pub mod ui;
 pub mod utils;
 pub mod architecture;
@@ -16,24 +41,53 @@ pub mod focus;
 pub mod map;
 pub mod debug;
 pub mod explain;
+pub mod summarize;
+pub mod watch;
+pub mod mcp;
+pub mod lsp;
 
+pub use apply::handle_apply;
 pub use ask::handle_ask;
+pub use bench::{handle_bench_index, BenchArgs};
+pub use calls::{handle_call_tree, CallTreeArgs};
 pub use cat::handle_cat;
+pub use chat::handle_chat;
+pub use check::handle_check;
+pub use compare::handle_compare;
+pub use config::{handle_config, ConfigAction};
+pub use coverage::{handle_coverage, CoverageAction};
+pub use complete::{handle_complete, CompleteTarget};
+pub use daemon::{handle_daemon, DaemonAction};
+pub use sessions::{handle_sessions, SessionsAction};
+pub use clones::handle_clones;
+pub use deadcode::handle_deadcode;
+pub use outline::handle_outline;
+pub use owners::handle_owners;
+pub use pack::handle_pack;
+pub use doctor::handle_doctor;
 pub use explore::handle_explore;
 pub use graph::{handle_graph, GraphArgs};
-pub use index::handle_index;
+pub use history::handle_history;
+pub use index::{handle_index, handle_index_action, IndexAction};
 pub use inspect::{handle_inspect, InspectArgs};
+pub use rename::handle_rename;
+pub use report_issue::handle_report_issue;
 pub use search::{handle_search, CliSearchMode};
 pub use status::handle_status;
+pub use tune::handle_tune;
 pub use architecture::handle_architecture;
-pub use impact::handle_impact;
+pub use impact::{handle_impact, ImpactTarget};
 pub use focus::handle_focus;
 pub use map::handle_codebase_map;
 pub use debug::handle_debug;
 pub use explain::handle_explain;
+pub use summarize::handle_summarize;
+pub use watch::handle_watch;
+pub use mcp::{handle_mcp, McpTransport};
+pub use lsp::handle_lsp;
 
 
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 use std::path::PathBuf;
 
 #[derive(Parser)]
@@ -45,15 +99,46 @@ pub struct Cli {
 
     #[arg(short, long, value_name = "FILE")]
     pub config: Option<PathBuf>,
+
+    /// Apply a `[profile.<NAME>]` section of the config (same as EMRY_PROFILE)
+    #[arg(long, value_name = "NAME", global = true)]
+    pub profile: Option<String>,
+
+    /// Output format: json emits errors as an {"error": {...}} envelope on stderr
+    #[arg(long, value_enum, global = true, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+
+    /// Print long output straight to the terminal instead of through the pager
+    #[arg(long, global = true)]
+    pub no_pager: bool,
+
+    /// Never write to the index or the repository (same as `core.read_only`)
+    #[arg(long, global = true)]
+    pub read_only: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    Text,
+    Json,
 }
 
 #[derive(Subcommand)]
 pub enum Commands {
-    /// Index the current repository
+    /// Index the current repository, or push, pull or export a built index
+    #[command(args_conflicts_with_subcommands = true)]
     Index {
+        #[command(subcommand)]
+        action: Option<IndexAction>,
         /// Force a full rebuild
         #[arg(long)]
         full: bool,
+        /// Files analyzed and embedded between checkpoints
+        #[arg(long, default_value_t = index::DEFAULT_BATCH_SIZE)]
+        batch_size: usize,
+        /// Rebuild reproducibly: same commit, same `emry index export` (timestamps from SOURCE_DATE_EPOCH or HEAD)
+        #[arg(long, default_value_t = false)]
+        deterministic: bool,
     },
     /// Search the index
     Search {
@@ -76,6 +161,10 @@ pub enum Commands {
         #[arg(long)]
         path: Option<String>,
 
+        /// Only search files this CODEOWNERS owner (or top blame author) owns, e.g. `@org/payments`
+        #[arg(long, conflicts_with_all = ["remote", "symbol", "regex"])]
+        owner: Option<String>,
+
         /// Search for symbol definitions (name match)
         #[arg(long)]
         symbol: bool,
@@ -84,26 +173,139 @@ pub enum Commands {
         #[arg(long)]
         regex: bool,
 
+        /// Search doc comments and docstrings instead of code
+        #[arg(long, conflicts_with_all = ["symbol", "regex", "smart", "remote"])]
+        docs: bool,
+
         /// Do not apply ignore rules (gitignore/config) for regex/grep search
         #[arg(long, default_value_t = false)]
         no_ignore: bool,
 
+        /// Only keep lexical hits containing the query words with the same case (`case:yes`)
+        #[arg(long, default_value_t = false)]
+        case_sensitive: bool,
+
+        /// Only keep lexical hits containing the query words as whole words (`word:yes`)
+        #[arg(long, default_value_t = false)]
+        word: bool,
+
+        /// List every match instead of the top results, a page of `--top` at a time
+        #[arg(long, default_value_t = false)]
+        all: bool,
+
         /// Enable smart search (Query Rewriting + Subgraph Retrieval)
         #[arg(long, default_value_t = false)]
         smart: bool,
+
+        /// Emit results as JSON (ranked hits with per-component scores)
+        #[arg(long, default_value_t = false)]
+        json: bool,
+
+        /// Browse the results full-screen: select with the arrow keys, preview the highlighted chunk, Enter opens it in $EDITOR
+        #[arg(long, default_value_t = false, conflicts_with_all = ["json", "all", "symbol", "regex", "show_diff"])]
+        interactive: bool,
+
+        /// For hits whose file changed since indexing, show a diff against the working tree
+        #[arg(long, default_value_t = false)]
+        show_diff: bool,
+
+        /// Show how each stage of the ranking pipeline scored every hit (also added to `--json`)
+        #[arg(long, default_value_t = false)]
+        explain_ranking: bool,
+
+        /// Search for the "did you mean" correction of misspelled identifiers instead of the query
+        #[arg(long, default_value_t = false)]
+        autocorrect: bool,
+
+        /// Also search the emry server at this address (`host:port`), overriding `search.remote`
+        #[arg(long, conflicts_with = "local")]
+        remote: Option<String>,
+
+        /// Only search this repository's index, even when `search.remote` is set
+        #[arg(long, default_value_t = false)]
+        local: bool,
+    },
+    /// Search ranking settings for the best MRR on a labeled query set and write them to the config
+    Tune {
+        /// JSON list of {"query": ..., "relevant": ["path" or "path:line", ...]}
+        queries: PathBuf,
+        /// Results scored per query
+        #[arg(long, default_value_t = 10)]
+        top: usize,
+        /// Report the best settings without writing them
+        #[arg(long, default_value_t = false)]
+        dry_run: bool,
+    },
+    /// Compare results side by side: two queries, or one query under another config
+    Compare {
+        /// Query for the left column
+        query: String,
+        /// Query for the right column (defaults to the left one)
+        other: Option<String>,
+        /// Config file for the right column, e.g. with different ranking weights
+        #[arg(long, value_name = "FILE")]
+        against: Option<PathBuf>,
+        /// Number of results per side
+        #[arg(long, default_value_t = 10)]
+        top: usize,
+        /// Emit both result lists as JSON
+        #[arg(long, default_value_t = false)]
+        json: bool,
     },
     /// Ask about codebase in natural language
     Ask {
         /// The question
         query: String,
+        /// Restrict search, file and graph tools to these paths (globs, repeatable)
+        #[arg(long, value_name = "GLOB")]
+        scope: Vec<String>,
         /// Show verbose output (thoughts, tool calls, observations)
         #[arg(long, default_value_t = false)]
         verbose: bool,
+        /// Continue the most recent conversation (from `ask` or `chat`) instead of starting a new one
+        #[arg(long = "continue", default_value_t = false, conflicts_with = "session")]
+        resume: bool,
+        /// Continue this conversation (see `emry sessions list`)
+        #[arg(long, value_name = "ID")]
+        session: Option<String>,
+        /// Let the agent propose file edits, shown as a diff to confirm before they are applied
+        #[arg(long, default_value_t = false)]
+        edit: bool,
+        /// Answer in one call from a single search, without the agent loop (same as `agent.strategy: rag`)
+        #[arg(long, default_value_t = false, conflicts_with = "edit")]
+        rag: bool,
+    },
+    /// List, show or delete the saved conversations of `ask` and `chat`
+    Sessions {
+        #[command(subcommand)]
+        action: SessionsAction,
+    },
+    /// Interactive chat with a persistent agent session and slash commands
+    Chat {
+        /// Restrict search, file and graph tools to these paths (globs, repeatable)
+        #[arg(long, value_name = "GLOB")]
+        scope: Vec<String>,
     },
     /// Query the code graph directly
     Graph(GraphArgs),
+    /// Show a symbol's transitive callers as a tree
+    Callers(CallTreeArgs),
+    /// Show a symbol's transitive callees as a tree
+    Callees(CallTreeArgs),
+    /// Measure indexing throughput and query latency on a generated repository
+    #[command(hide = true)]
+    BenchIndex(BenchArgs),
+    /// Print completion candidates for shell integrations
+    Complete {
+        #[command(subcommand)]
+        target: CompleteTarget,
+    },
     /// Show status (not yet implemented)
-    Status,
+    Status {
+        /// List every skipped file instead of the first `core.list_limit`
+        #[arg(long, default_value_t = false)]
+        all: bool,
+    },
     /// Inspect a node by ID
     Inspect(InspectArgs),
     /// Batch read files
@@ -119,6 +321,9 @@ pub enum Commands {
         /// Depth of exploration
         #[arg(long, default_value_t = 1)]
         depth: usize,
+        /// List every file instead of the first `core.list_limit`
+        #[arg(long, default_value_t = false)]
+        all: bool,
     },
     /// Analyze codebase architecture
     Architecture {
@@ -128,19 +333,58 @@ pub enum Commands {
         /// Show verbose output (progress steps)
         #[arg(long, default_value_t = false)]
         verbose: bool,
+        /// Print the module coupling from the index as a Mermaid flowchart instead (no LLM call)
+        #[arg(long, default_value_t = false)]
+        mermaid: bool,
     },
     /// Analyze impact of changes
     Impact {
         /// File path
-        file: PathBuf,
+        #[arg(required_unless_present = "diff", conflicts_with = "diff")]
+        file: Option<PathBuf>,
         /// Start line
-        start: usize,
+        #[arg(required_unless_present = "diff")]
+        start: Option<usize>,
         /// End line
-        end: usize,
+        #[arg(required_unless_present = "diff")]
+        end: Option<usize>,
+        /// Analyze uncommitted changes against a revision (default HEAD) instead of a line range
+        #[arg(long, value_name = "REV", num_args = 0..=1, default_missing_value = "HEAD")]
+        diff: Option<String>,
         /// Show verbose output
         #[arg(long, default_value_t = false)]
         verbose: bool,
     },
+    /// Apply a unified diff to the working tree, merging with local edits, and re-index touched files
+    Apply {
+        /// Patch file, or '-' to read it from stdin
+        patch: PathBuf,
+        /// Show the diff and how each hunk would apply without writing anything
+        #[arg(long, default_value_t = false)]
+        dry_run: bool,
+    },
+    /// Rename a symbol across the workspace; previews the edits unless --apply is given
+    Rename {
+        /// Current name of the symbol
+        symbol: String,
+        /// Name to give it
+        new_name: String,
+        /// Pick the definition in this file when several share the name
+        #[arg(long)]
+        file: Option<String>,
+        /// Edit verified references, write the rest to a patch and re-index touched files
+        #[arg(long, default_value_t = false)]
+        apply: bool,
+    },
+    /// Report public APIs impacted by this branch as JSON, for CI gating
+    Check {
+        /// Base branch or revision; changes since its merge-base with HEAD are checked
+        #[arg(long, value_name = "BASE")]
+        against: String,
+        /// Exit with code 11 when more than N public APIs are impacted
+        #[arg(long, value_name = "N")]
+        fail_on_impact: Option<usize>,
+    },
     /// Smart Focus (Auto-Context)
     Focus {
         /// Topic to focus on
@@ -149,6 +393,63 @@ pub enum Commands {
         #[arg(long, default_value_t = false)]
         verbose: bool,
     },
+    /// List functions nothing calls or imports that aren't entry points
+    Deadcode {
+        /// Only list symbols in files whose path contains this
+        #[arg(long)]
+        path: Option<String>,
+        /// Print candidates as JSON
+        #[arg(long, default_value_t = false)]
+        json: bool,
+    },
+    /// Find groups of near-identical chunks across files, for consolidation
+    Clones {
+        /// Minimum cosine similarity between linked chunks
+        #[arg(long, default_value_t = 0.9)]
+        threshold: f32,
+        /// Ignore chunks shorter than this many lines
+        #[arg(long, default_value_t = 5)]
+        min_lines: usize,
+        /// Only list groups with a member in a file whose path contains this
+        #[arg(long)]
+        path: Option<String>,
+        /// Print groups as JSON
+        #[arg(long, default_value_t = false)]
+        json: bool,
+    },
+    /// Bundle ranked chunks, file outlines and graph paths into one token-budgeted context for an LLM
+    Pack {
+        /// Query whose ranked chunks to pack
+        query: Option<String>,
+        /// Pack these files whole, or with a query, only search them (repeatable)
+        #[arg(long = "file", value_name = "PATH")]
+        files: Vec<String>,
+        /// Token budget for the whole bundle (cl100k tokens)
+        #[arg(long, default_value_t = 8000)]
+        budget_tokens: usize,
+        /// Number of ranked chunks to consider
+        #[arg(long, default_value_t = 30)]
+        top: usize,
+        /// Write the bundle to a file instead of stdout
+        #[arg(long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+    /// Show the symbol tree of a file with signatures, visibility, line ranges and doc comments
+    Outline {
+        /// File to outline
+        file: String,
+        /// Print the tree as JSON
+        #[arg(long, default_value_t = false)]
+        json: bool,
+    },
+    /// Show the CODEOWNERS owners and top blame authors of a file, directory or symbol
+    Owners {
+        /// Path, or the name of a symbol whose defining files to look up
+        target: String,
+        /// Print owners as JSON
+        #[arg(long, default_value_t = false)]
+        json: bool,
+    },
     /// Generate a high-level map of the codebase
     Map {
         /// Depth of traversal
@@ -157,13 +458,103 @@ pub enum Commands {
         /// Show verbose output
         #[arg(long, default_value_t = false)]
         verbose: bool,
+        /// Print the highest-ranked symbols, grouped by file, in at most this many tokens
+        #[arg(long)]
+        tokens: Option<usize>,
+        /// Rank symbols by their relevance to these files or directories (repeatable)
+        #[arg(long)]
+        focus: Vec<String>,
+        /// Draw the tree as a Mermaid flowchart
+        #[arg(long, default_value_t = false, conflicts_with = "tokens")]
+        mermaid: bool,
     },
     /// Debug database stats
     Debug,
-    /// Explain the project functionality and capabilities
+    /// Bundle anonymized diagnostics into a tarball to attach to a bug report (nothing is uploaded)
+    ReportIssue {
+        /// Where to write the tarball (default: ./emry-report-<timestamp>.tar.gz)
+        #[arg(long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+    /// Score index health: unresolved references, parse errors, stale files, vector coverage
+    Doctor {
+        /// Print prioritized recommendations with the config changes they need
+        #[arg(long, default_value_t = false)]
+        advise: bool,
+        /// Clear dangling edges and reindex files with missing chunks or embeddings
+        #[arg(long, default_value_t = false)]
+        repair: bool,
+        /// Emit the report as JSON
+        #[arg(long, default_value_t = false)]
+        json: bool,
+    },
+    /// Explain the project functionality and capabilities, or the role of one file
     Explain {
+        /// File to explain instead of the whole project
+        path: Option<PathBuf>,
+        /// With a path: print the indexed facts without asking the LLM for a narrative
+        #[arg(long, default_value_t = false)]
+        facts_only: bool,
         /// Show verbose output
         #[arg(long, default_value_t = false)]
         verbose: bool,
     },
+    /// Summarize files, then directories, then the repository, for explain, architecture and ask
+    Summarize {
+        /// Rewrite every summary, not only those whose files changed
+        #[arg(long, default_value_t = false)]
+        force: bool,
+        /// Count the summaries to write without calling the LLM
+        #[arg(long, default_value_t = false)]
+        dry_run: bool,
+    },
+    /// Watch the repository and re-index changed files
+    Watch {
+        /// Quiet period (ms) before a burst of changes is re-indexed; a burst is flushed after 5s at most
+        #[arg(long, default_value_t = 300)]
+        debounce_ms: u64,
+    },
+    /// Show when a symbol's source last changed across index runs, or past searches and questions
+    History {
+        /// Symbol name or full symbol id; without one, list logged queries
+        symbol: Option<String>,
+        /// Maximum versions to list per symbol, or queries to list
+        #[arg(long, default_value_t = 10)]
+        limit: usize,
+        /// List every version or query, ignoring --limit
+        #[arg(long, default_value_t = false)]
+        all: bool,
+        /// Only list queries containing this term
+        #[arg(long, value_name = "TERM")]
+        grep: Option<String>,
+        /// Run logged query N again with the same arguments
+        #[arg(long, value_name = "N", conflicts_with = "symbol")]
+        rerun: Option<usize>,
+    },
+    /// Show, scaffold, validate or describe the configuration
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+    /// Import test coverage (LCOV, Cobertura) for `impact` and search ranking
+    Coverage {
+        #[command(subcommand)]
+        action: CoverageAction,
+    },
+    /// Keep the index open in a background process that other commands dispatch to
+    Daemon {
+        #[command(subcommand)]
+        action: DaemonAction,
+    },
+    /// Serve search/graph/fs tools over the Model Context Protocol
+    Mcp {
+        /// Transport: 'stdio' for editor integrations, 'sse' for HTTP clients
+        #[arg(long, value_enum, default_value_t = McpTransport::Stdio)]
+        transport: McpTransport,
+        /// Port for the SSE transport
+        #[arg(long, default_value_t = 3917)]
+        port: u16,
+    },
+    /// Serve workspace symbols, definitions and references from the index over the Language Server Protocol (stdio)
+    Lsp,
 }
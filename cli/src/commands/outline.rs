@@ -0,0 +1,70 @@
+//! `emry outline`: the symbol tree of one file, with each symbol's
+//! visibility, signature, line range and doc comment.
+//!
+//! The file is parsed as it is on disk, so the outline needs no index and
+//! reflects edits made since the last one.
+
+use anyhow::{bail, Result};
+use console::Style;
+use emry_agent::ops::fs::FsTool;
+use emry_agent::project::{Components, RepoContext};
+use emry_core::symbols::{symbol_tree, SymbolTree};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use super::ui;
+
+pub async fn handle_outline(file: String, json: bool, config_path: Option<&Path>) -> Result<()> {
+    let ctx = Arc::new(RepoContext::open(config_path, Components::NONE).await?);
+    let fs_tool = FsTool::new(ctx.clone());
+
+    let symbols = fs_tool.outline(&PathBuf::from(&file))?;
+    if symbols.is_empty() && !json {
+        bail!("no symbols found in {} (unsupported language, or nothing defined)", file);
+    }
+    let tree = symbol_tree(symbols);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&tree)?);
+        return Ok(());
+    }
+
+    ui::print_header(&format!("Outline of {}", file));
+    let mut out = String::new();
+    for node in &tree {
+        render(node, 0, &mut out);
+    }
+    ui::page(&out);
+    Ok(())
+}
+
+/// `[visibility] signature  Lstart-end`, then the doc comment, then children one level in.
+fn render(node: &SymbolTree, depth: usize, out: &mut String) {
+    let symbol = &node.symbol;
+    let indent = "  ".repeat(depth);
+    let dim = Style::new().dim();
+    let heading = match &symbol.signature {
+        Some(signature) => signature.clone(),
+        None => format!("{} {}", symbol.kind, symbol.name),
+    };
+    // Rust and Java spell visibility in the signature; show it only where it's implied.
+    let visibility = match &symbol.visibility {
+        Some(v) if !heading.split_whitespace().next().is_some_and(|w| w.starts_with(v.as_str())) => format!("[{}] ", v),
+        _ => String::new(),
+    };
+    out.push_str(&format!(
+        "{}{}{}  {}\n",
+        indent,
+        dim.apply_to(visibility),
+        Style::new().bold().apply_to(heading),
+        ui::palette().location.apply_to(format!("L{}-{}", symbol.start_line, symbol.end_line))
+    ));
+    if let Some(doc) = &symbol.doc_comment {
+        for line in doc.lines() {
+            out.push_str(&format!("{}  {}\n", indent, dim.apply_to(line)));
+        }
+    }
+    for child in &node.children {
+        render(child, depth + 1, out);
+    }
+}
@@ -0,0 +1,205 @@
+//! `emry owners`: who owns a file, directory or symbol.
+//!
+//! Owners come from the repository's CODEOWNERS file, read fresh on every
+//! call so edits show up before the next index run; the index keeps a copy on
+//! each `file` record for `emry search --owner`. With `owners.blame` set,
+//! indexing also records each file's top `git blame` authors by line count.
+
+use anyhow::{anyhow, Result};
+use console::Style;
+use emry_agent::project::{Components, RepoContext};
+use emry_config::OwnersConfig;
+use emry_core::owners::{parse_blame_porcelain, AuthorShare, CodeOwners};
+use emry_store::SurrealStore;
+use futures::stream::{self, StreamExt};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+use super::ui;
+
+/// `git blame` processes run at once while indexing.
+const BLAME_CONCURRENCY: usize = 8;
+
+#[derive(Debug, Serialize)]
+struct FileOwners {
+    path: String,
+    /// The CODEOWNERS pattern that assigns the file
+    rule: Option<String>,
+    owners: Vec<String>,
+    authors: Vec<AuthorShare>,
+}
+
+pub async fn handle_owners(target: String, json: bool, config_path: Option<&Path>) -> Result<()> {
+    let ctx = RepoContext::open(config_path, Components::STORE).await?;
+    let store = ctx.surreal_store.clone()
+        .ok_or_else(|| anyhow!("SurrealStore not initialized. Run 'emry index' first."))?;
+    let codeowners = CodeOwners::find(&ctx.root)?;
+
+    // A path on disk, else the files defining a symbol of that name.
+    let candidate = ctx.root.join(&target);
+    let paths: Vec<String> = if candidate.exists() {
+        let candidate = candidate.canonicalize()?;
+        vec![candidate.to_string_lossy().to_string()]
+    } else {
+        let mut files: Vec<String> = store.find_definition(&target).await?.into_iter().map(|n| n.file_path).collect();
+        files.sort();
+        files.dedup();
+        if files.is_empty() {
+            return Err(anyhow!("'{}' is neither a path nor an indexed symbol", target));
+        }
+        files
+    };
+
+    let indexed: HashMap<String, Vec<AuthorShare>> = store
+        .file_ownership()
+        .await?
+        .into_iter()
+        .map(|f| (f.path, f.authors.unwrap_or_default()))
+        .collect();
+    let mut files: Vec<FileOwners> = Vec::new();
+    for path in &paths {
+        // A directory covers every indexed file below it.
+        let prefix = format!("{}/", path.trim_end_matches('/'));
+        let mut matched: Vec<&String> = indexed.keys().filter(|p| *p == path || p.starts_with(&prefix)).collect();
+        if matched.is_empty() {
+            matched.push(path);
+        }
+        matched.sort();
+        for file in matched {
+            let relative = Path::new(file).strip_prefix(&ctx.root).unwrap_or(Path::new(file));
+            let rule = codeowners.as_ref().and_then(|(_, c)| c.rule_for(relative));
+            files.push(FileOwners {
+                path: relative.display().to_string(),
+                rule: rule.as_ref().map(|r| r.pattern.to_string()),
+                owners: rule.map(|r| r.owners.to_vec()).unwrap_or_default(),
+                authors: indexed.get(file).cloned().unwrap_or_default(),
+            });
+        }
+    }
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&files)?);
+        return Ok(());
+    }
+
+    ui::print_header(&format!("Owners of {}", target));
+    match &codeowners {
+        Some((path, _)) => ui::print_key_value("CODEOWNERS", &path.strip_prefix(&ctx.root).unwrap_or(path).display().to_string()),
+        None => ui::print_key_value("CODEOWNERS", "none found"),
+    }
+    if let [file] = files.as_slice() {
+        ui::print_key_value("File", &file.path);
+        print_file(file);
+    } else {
+        print_summary(&files);
+    }
+    Ok(())
+}
+
+fn print_file(file: &FileOwners) {
+    let owners = if file.owners.is_empty() { "unowned".to_string() } else { file.owners.join(", ") };
+    let rule = file.rule.as_ref().map_or(String::new(), |r| format!(" (rule {})", r));
+    ui::print_key_value("Owners", &format!("{}{}", owners, Style::new().dim().apply_to(rule)));
+    print_authors(&file.authors);
+}
+
+/// Files per owner and lines per author across many files.
+fn print_summary(files: &[FileOwners]) {
+    let mut owners: HashMap<&str, usize> = HashMap::new();
+    let mut unowned = 0;
+    for file in files {
+        if file.owners.is_empty() {
+            unowned += 1;
+        }
+        for owner in &file.owners {
+            *owners.entry(owner.as_str()).or_default() += 1;
+        }
+    }
+    let mut owners: Vec<_> = owners.into_iter().collect();
+    owners.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    ui::print_key_value("Files", &files.len().to_string());
+    ui::print_key_value("Owners", "");
+    for (owner, count) in owners {
+        println!("  {}  {}", Style::new().bold().apply_to(owner), Style::new().dim().apply_to(format!("{} files", count)));
+    }
+    if unowned > 0 {
+        println!("  {}", Style::new().dim().apply_to(format!("unowned: {} files", unowned)));
+    }
+
+    let mut authors: HashMap<String, AuthorShare> = HashMap::new();
+    for share in files.iter().flat_map(|f| &f.authors) {
+        authors
+            .entry(share.email.to_lowercase())
+            .or_insert_with(|| AuthorShare { lines: 0, ..share.clone() })
+            .lines += share.lines;
+    }
+    let mut authors: Vec<AuthorShare> = authors.into_values().collect();
+    authors.sort_by(|a, b| b.lines.cmp(&a.lines).then_with(|| a.email.cmp(&b.email)));
+    authors.truncate(5);
+    print_authors(&authors);
+}
+
+fn print_authors(authors: &[AuthorShare]) {
+    if authors.is_empty() {
+        return;
+    }
+    let total: usize = authors.iter().map(|a| a.lines).sum();
+    ui::print_key_value("Top authors", "");
+    for author in authors {
+        println!(
+            "  {} <{}>  {}",
+            author.name,
+            author.email,
+            Style::new().dim().apply_to(format!("{} lines, {:.0}%", author.lines, 100.0 * author.lines as f64 / total.max(1) as f64))
+        );
+    }
+}
+
+/// Apply CODEOWNERS to every indexed file and, with `owners.blame`, blame the
+/// files recorded without authors (new or re-ingested since the last run).
+pub(crate) async fn refresh_ownership(store: &SurrealStore, root: &Path, config: &OwnersConfig) -> Result<()> {
+    let codeowners = CodeOwners::find(root)?.map(|(_, c)| c);
+    let changed: Vec<(String, Vec<String>)> = store
+        .file_ownership()
+        .await?
+        .into_iter()
+        .filter_map(|f| {
+            let relative = Path::new(&f.path).strip_prefix(root).unwrap_or(Path::new(&f.path));
+            let owners = codeowners.as_ref().map(|c| c.owners_of(relative)).unwrap_or_default();
+            (owners != f.owners).then_some((f.path, owners))
+        })
+        .collect();
+    store.set_file_owners(changed).await?;
+
+    if !config.blame {
+        return Ok(());
+    }
+    let blamed: Vec<(String, Vec<AuthorShare>)> = stream::iter(store.files_without_authors().await?)
+        .map(|path| async move {
+            let authors = blame(root, &path).await;
+            (path, authors)
+        })
+        .buffer_unordered(BLAME_CONCURRENCY)
+        .collect()
+        .await;
+    for (path, mut authors) in blamed {
+        authors.truncate(config.blame_authors);
+        store.set_file_authors(&path, authors).await?;
+    }
+    Ok(())
+}
+
+/// Authors of `path` by line count; empty when git can't blame it (untracked, or no git).
+async fn blame(root: &Path, path: &str) -> Vec<AuthorShare> {
+    let output = tokio::process::Command::new("git")
+        .current_dir(root)
+        .args(["blame", "--line-porcelain", "-w", "--"])
+        .arg(path)
+        .output()
+        .await;
+    match output {
+        Ok(output) if output.status.success() => parse_blame_porcelain(&String::from_utf8_lossy(&output.stdout)),
+        _ => Vec::new(),
+    }
+}
@@ -0,0 +1,215 @@
+//! `emry pack`: one token-budgeted bundle of context to paste into an LLM.
+//!
+//! The bundle holds, in order, a header, an outline of every packed file
+//! (its indexed symbols), the graph paths that boosted hits, and the code:
+//! ranked chunks for a query, or whole files for a file list. Each piece is
+//! counted with the chunker's tokenizer (cl100k) and pieces are added by
+//! priority, code first, skipping any that would exceed `--budget-tokens`.
+
+use anyhow::{anyhow, bail, Context, Result};
+use emry_agent::project::{Components, RepoContext};
+use emry_core::chunking::tokenizer::count_tokens;
+use emry_core::models::{RankingStep, ScoredChunk};
+use emry_engine::search::pipeline::FileSetFilter;
+use emry_engine::search::snippet::resolve_snippet;
+use emry_store::SurrealStore;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+const RULE: &str = "================================================================";
+
+/// A piece of code in the bundle.
+struct Section {
+    file: String,
+    lines: Option<(usize, usize)>,
+    text: String,
+}
+
+impl Section {
+    fn render(&self) -> String {
+        let location = match self.lines {
+            Some((start, end)) => format!("{} (lines {}-{})", self.file, start, end),
+            None => self.file.clone(),
+        };
+        format!("{}\nFile: {}\n{}\n{}\n\n", RULE, location, RULE, self.text.trim_end())
+    }
+}
+
+pub async fn handle_pack(
+    query: Option<String>,
+    files: Vec<String>,
+    budget_tokens: usize,
+    top: usize,
+    output: Option<PathBuf>,
+    config_path: Option<&Path>,
+) -> Result<()> {
+    if query.is_none() && files.is_empty() {
+        bail!("give a query, --file paths, or both");
+    }
+    let ctx = RepoContext::open(config_path, if query.is_some() { Components::ALL } else { Components::STORE }).await?;
+    let store = ctx.surreal_store.clone()
+        .ok_or_else(|| anyhow!("SurrealStore not initialized. Run 'emry index' first."))?;
+    let files: Vec<PathBuf> = files
+        .iter()
+        .map(|f| ctx.root.join(f).canonicalize().with_context(|| format!("no such file: {}", f)))
+        .collect::<Result<_>>()?;
+
+    let (sections, graph_paths) = match &query {
+        Some(query) => ranked_sections(&ctx, &store, query, &files, top).await?,
+        None => (file_sections(&ctx.root, &files)?, Vec::new()),
+    };
+
+    let mut header = String::from("# Context pack\n\n");
+    if let Some(query) = &query {
+        header.push_str(&format!("Query: {}\n", query));
+    }
+    header.push_str(&format!("Repository: {}\n\n", ctx.root.display()));
+    let mut used = count_tokens(&header);
+
+    // Code first, in rank order; a section that doesn't fit is skipped for smaller ones.
+    let mut packed: Vec<&Section> = Vec::new();
+    let mut omitted = 0;
+    for section in &sections {
+        let tokens = count_tokens(&section.render());
+        if used + tokens <= budget_tokens {
+            used += tokens;
+            packed.push(section);
+        } else {
+            omitted += 1;
+        }
+    }
+
+    let mut seen = std::collections::HashSet::new();
+    let packed_files: Vec<&str> = packed.iter().copied().map(|s| s.file.as_str()).filter(|f| seen.insert(*f)).collect();
+    let mut outline = String::new();
+    for file in &packed_files {
+        let line = outline_line(&store, &ctx.root, file).await?;
+        let tokens = count_tokens(&line);
+        if used + tokens <= budget_tokens {
+            used += tokens;
+            outline.push_str(&line);
+        }
+    }
+    let mut paths = String::new();
+    for path in &graph_paths {
+        let line = format!("- {}\n", path);
+        let tokens = count_tokens(&line);
+        if used + tokens <= budget_tokens {
+            used += tokens;
+            paths.push_str(&line);
+        }
+    }
+
+    let mut bundle = header;
+    if !outline.is_empty() {
+        bundle.push_str(&format!("## Outline\n\n{}\n", outline));
+    }
+    if !paths.is_empty() {
+        bundle.push_str(&format!("## Graph paths\n\n{}\n", paths));
+    }
+    bundle.push_str("## Code\n\n");
+    for section in &packed {
+        bundle.push_str(&section.render());
+    }
+    // Headings, and tokens merging across piece boundaries, can push the whole past the sum.
+    while count_tokens(&bundle) > budget_tokens {
+        let Some(last) = packed.pop() else { break };
+        let rendered = last.render();
+        bundle.truncate(bundle.len() - rendered.len());
+        omitted += 1;
+    }
+
+    let total = count_tokens(&bundle);
+    match output {
+        Some(path) => {
+            std::fs::write(&path, &bundle).with_context(|| format!("failed to write {}", path.display()))?;
+            println!("Wrote {}", path.display());
+        }
+        None => print!("{}", bundle),
+    }
+    eprintln!(
+        "Packed {} sections from {} files, {} of {} tokens{}",
+        packed.len(),
+        packed_files.len(),
+        total,
+        budget_tokens,
+        if omitted > 0 { format!("; {} sections did not fit", omitted) } else { String::new() }
+    );
+    Ok(())
+}
+
+/// Ranked chunks for `query`, limited to `files` when any are given, and the
+/// graph paths behind their boosts.
+async fn ranked_sections(
+    ctx: &RepoContext,
+    store: &Arc<SurrealStore>,
+    query: &str,
+    files: &[PathBuf],
+    top: usize,
+) -> Result<(Vec<Section>, Vec<String>)> {
+    let mut service = ctx.search_service(store.clone());
+    if !files.is_empty() {
+        service = service.with_filter(Arc::new(FileSetFilter::new(files.iter().map(|f| f.to_string_lossy().to_string()))));
+    }
+    let hits: Vec<ScoredChunk> = service.search_explained(query, top, None).await?;
+
+    let mut sections = Vec::new();
+    let mut graph_paths = Vec::new();
+    for hit in &hits {
+        let chunk = &hit.chunk;
+        let snippet = resolve_snippet(
+            &ctx.root,
+            &chunk.file_path,
+            chunk.start_line,
+            chunk.end_line,
+            &chunk.content,
+            ctx.config.search.snippet_source,
+        );
+        sections.push(Section {
+            file: relative(&ctx.root, &chunk.file_path),
+            lines: Some((chunk.start_line, chunk.end_line)),
+            text: snippet.text,
+        });
+        for step in &hit.ranking {
+            if let RankingStep::Graph { from, to, hops, .. } = step {
+                let path = format!("{} -> {}, {} hop(s)", from, to, hops);
+                if !graph_paths.contains(&path) {
+                    graph_paths.push(path);
+                }
+            }
+        }
+    }
+    Ok((sections, graph_paths))
+}
+
+/// `files` whole, in the order given.
+fn file_sections(root: &Path, files: &[PathBuf]) -> Result<Vec<Section>> {
+    files
+        .iter()
+        .map(|path| {
+            let text = std::fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+            Ok(Section { file: relative(root, path), lines: None, text })
+        })
+        .collect()
+}
+
+/// `file: symbol (kind, line), ...` from the index.
+async fn outline_line(store: &SurrealStore, root: &Path, file: &str) -> Result<String> {
+    let absolute = root.join(file);
+    let symbols = store.list_file_symbols(&absolute.to_string_lossy()).await?;
+    if symbols.is_empty() {
+        return Ok(format!("- {}\n", file));
+    }
+    let symbols: Vec<String> = symbols
+        .iter()
+        .map(|s| match s.start_line {
+            Some(line) => format!("{} ({} L{})", s.label, s.kind, line),
+            None => format!("{} ({})", s.label, s.kind),
+        })
+        .collect();
+    Ok(format!("- {}: {}\n", file, symbols.join(", ")))
+}
+
+fn relative(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root).unwrap_or(path).display().to_string()
+}
@@ -0,0 +1,169 @@
+//! `emry rename`: rename a symbol across the workspace.
+//!
+//! Matches in files the index links to the definition (its own file and the
+//! files of its callers) are rewritten when they parse as identifiers. Any
+//! other spelling of the name, in comments, strings or files the graph does
+//! not connect, goes into a patch under `.codeindex/` to review rather than
+//! being edited blind. Edits are written through `emry apply`'s patch engine;
+//! without `--apply` nothing is written.
+
+use anyhow::{anyhow, Result};
+use console::Style;
+use emry_agent::project::{Components, RepoContext};
+use emry_core::diff::unified_diff;
+use emry_core::error::EmryError;
+use emry_core::models::Language;
+use emry_core::patch::parse_patch;
+use emry_core::rename::{find_occurrences, is_identifier, replace_occurrences, Occurrence};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use super::ui;
+
+/// Matches of the old name in one file.
+struct FileMatches {
+    path: PathBuf,
+    content: String,
+    occurrences: Vec<Occurrence>,
+    /// Whether the index links this file to the renamed definition
+    linked: bool,
+}
+
+impl FileMatches {
+    fn is_safe(&self, occurrence: &Occurrence) -> bool {
+        self.linked && occurrence.verified
+    }
+}
+
+pub async fn handle_rename(
+    symbol: String,
+    new_name: String,
+    file: Option<String>,
+    apply: bool,
+    config_path: Option<&Path>,
+) -> Result<()> {
+    if !is_identifier(&symbol) || !is_identifier(&new_name) {
+        return Err(anyhow!("both names must be plain identifiers (got '{}' and '{}')", symbol, new_name));
+    }
+    if symbol == new_name {
+        return Err(anyhow!("'{}' already has that name", symbol));
+    }
+    if apply {
+        super::utils::ensure_writable("rename --apply")?;
+        super::utils::ensure_no_daemon().await?;
+    }
+
+    let components = if apply { Components::ALL } else { Components::STORE };
+    let ctx = RepoContext::open(config_path, components).await?;
+    let store = ctx.surreal_store.clone()
+        .ok_or_else(|| anyhow!("SurrealStore not initialized. Run 'emry index' first."))?;
+
+    let mut definitions = store.find_definition(&symbol).await?;
+    if let Some(file) = &file {
+        definitions.retain(|d| d.file_path.contains(file.as_str()));
+    }
+    let definition = match definitions.as_slice() {
+        [] => return Err(EmryError::NotFound(format!("no definition of '{}' in the index", symbol)).into()),
+        [definition] => definition,
+        several => {
+            let places: Vec<String> = several.iter().map(|d| format!("{} ({})", d.file_path, d.kind)).collect();
+            return Err(anyhow!(
+                "'{}' is defined in {} places; pick one with --file:\n  {}",
+                symbol,
+                several.len(),
+                places.join("\n  ")
+            ));
+        }
+    };
+
+    let mut linked: HashSet<String> = HashSet::from([definition.file_path.clone()]);
+    for reference in store.find_references(&definition.id.to_string()).await? {
+        linked.insert(reference.file_path);
+    }
+
+    let mut files = Vec::new();
+    for record in store.list_files().await? {
+        if !record.content.contains(symbol.as_str()) {
+            continue;
+        }
+        let path = PathBuf::from(&record.path);
+        let Ok(content) = std::fs::read_to_string(&path) else { continue };
+        let occurrences = find_occurrences(&content, &Language::from_path(&path), &symbol);
+        if !occurrences.is_empty() {
+            files.push(FileMatches { linked: linked.contains(&record.path), path, content, occurrences });
+        }
+    }
+    files.sort_by(|a, b| (!a.linked, &a.path).cmp(&(!b.linked, &b.path)));
+
+    ui::print_header(&format!("Rename {} → {}", symbol, new_name));
+    if !store.find_definition(&new_name).await?.is_empty() {
+        println!(
+            "{}",
+            ui::palette().warning.apply_to(format!("'{}' is already defined; check the result for clashes.", new_name))
+        );
+    }
+    let (mut safe, mut review) = (0, 0);
+    for matches in &files {
+        let rel = matches.path.strip_prefix(&ctx.root).unwrap_or(&matches.path);
+        println!("{}", ui::palette().location.apply_to(rel.display()));
+        let lines: Vec<&str> = matches.content.lines().collect();
+        for occurrence in &matches.occurrences {
+            let text = lines.get(occurrence.line - 1).map_or("", |l| l.trim());
+            if matches.is_safe(occurrence) {
+                safe += 1;
+                println!("  {:>5}  {}", occurrence.line, text);
+            } else {
+                review += 1;
+                println!("  {:>5}  {}  {}", occurrence.line, Style::new().dim().apply_to(text), Style::new().yellow().apply_to("(review)"));
+            }
+        }
+    }
+    println!(
+        "\n{} verified reference(s) to rename, {} match(es) to review, in {} file(s).",
+        safe,
+        review,
+        files.len()
+    );
+    if !apply {
+        println!("Run again with --apply to make the edits.");
+        return Ok(());
+    }
+
+    let (mut edits, mut review_patch) = (String::new(), String::new());
+    for matches in &files {
+        let renamed = replace_occurrences(
+            &matches.content,
+            matches.occurrences.iter().filter(|o| matches.is_safe(o)),
+            &new_name,
+        );
+        let everything = replace_occurrences(&matches.content, &matches.occurrences, &new_name);
+        let rel = matches.path.strip_prefix(&ctx.root).unwrap_or(&matches.path).to_string_lossy();
+        edits.push_str(&unified_diff(&rel, &matches.content, &renamed));
+        // The review patch goes on top of the applied edits, so its base is `renamed`.
+        review_patch.push_str(&unified_diff(&rel, &renamed, &everything));
+    }
+
+    // Going through the patch engine merges with anything edited since the files were read.
+    let plans = super::apply::plan(&ctx.root, &parse_patch(&edits)?)?;
+    if super::apply::conflict_count(&plans) > 0 {
+        super::apply::print_plan(&plans);
+    }
+    let touched = super::apply::write_plan(&plans)?;
+    ui::print_success(&format!("Renamed {} reference(s) in {} file(s)", safe, touched.len()));
+
+    if !review_patch.is_empty() {
+        let patch_path = ctx.root.join(".codeindex").join(format!("rename-{}-{}.patch", symbol, new_name));
+        std::fs::write(&patch_path, review_patch)?;
+        println!(
+            "Matches to review were written to {}; preview them with `emry apply --dry-run {}`.",
+            patch_path.display(),
+            patch_path.display()
+        );
+    }
+
+    if !touched.is_empty() {
+        let updated = super::apply::reindex_touched("Rename", &ctx, touched).await?;
+        ui::print_success(&format!("Re-indexed {} file(s)", updated));
+    }
+    Ok(())
+}
@@ -0,0 +1,313 @@
+//! `emry index push|pull`: share a built index between machines.
+//!
+//! `push` packs the current branch's index together with a manifest naming
+//! the commit it was built at; `pull` unpacks it in place of the local index
+//! and re-indexes only the files that differ from that commit, so a fresh
+//! devcontainer or Codespace starts warm instead of embedding the whole
+//! repository. Remotes are `http(s)://` URLs (GET and PUT, sending
+//! `EMRY_INDEX_TOKEN` as a bearer token when set), `s3://` and `gs://`; the
+//! transfers go through `curl`, the `aws` CLI and `gcloud` respectively.
+
+use anyhow::{anyhow, Context, Result};
+use emry_agent::project::embedder::get_embedding_dimension;
+use emry_agent::project::{Components, RepoContext};
+use emry_config::Config;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use super::ui;
+use super::utils::current_branch;
+
+/// Written into the index directory on push and read back on pull.
+const MANIFEST_FILE: &str = "replica.json";
+
+/// Bumped when the archive layout or manifest changes incompatibly.
+const FORMAT_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct Manifest {
+    format: u32,
+    emry_version: String,
+    /// `HEAD` when the index was pushed; `None` outside a git checkout
+    commit: Option<String>,
+    /// Files that differed from `commit` when the index was pushed, relative to `root`
+    #[serde(default)]
+    uncommitted: Vec<PathBuf>,
+    branch: String,
+    /// Indexed paths are absolute, so the index only fits a checkout at this path.
+    root: PathBuf,
+    embedding_model: String,
+    embedding_dimension: Option<usize>,
+}
+
+/// Where an index archive is stored.
+enum Remote {
+    Http(String),
+    S3(String),
+    Gcs(String),
+}
+
+impl Remote {
+    fn parse(url: &str) -> Result<Self> {
+        if url.starts_with("http://") || url.starts_with("https://") {
+            Ok(Remote::Http(url.to_string()))
+        } else if url.starts_with("s3://") {
+            Ok(Remote::S3(url.to_string()))
+        } else if url.starts_with("gs://") {
+            Ok(Remote::Gcs(url.to_string()))
+        } else {
+            Err(anyhow!("unsupported index URL '{}'; use http(s)://, s3:// or gs://", url))
+        }
+    }
+
+    fn download(&self, dest: &Path) -> Result<()> {
+        match self {
+            Remote::Http(url) => {
+                let (mut cmd, token) = curl(url);
+                cmd.arg("--output").arg(dest);
+                run(cmd, "curl", token.as_deref())
+            }
+            Remote::S3(url) => {
+                let mut cmd = Command::new("aws");
+                cmd.args(["s3", "cp", "--only-show-errors", url]).arg(dest);
+                run(cmd, "aws", None)
+            }
+            Remote::Gcs(url) => {
+                let mut cmd = Command::new("gcloud");
+                cmd.args(["storage", "cp", url]).arg(dest);
+                run(cmd, "gcloud", None)
+            }
+        }
+    }
+
+    fn upload(&self, src: &Path) -> Result<()> {
+        match self {
+            Remote::Http(url) => {
+                let (mut cmd, token) = curl(url);
+                cmd.arg("--upload-file").arg(src);
+                run(cmd, "curl", token.as_deref())
+            }
+            Remote::S3(url) => {
+                let mut cmd = Command::new("aws");
+                cmd.args(["s3", "cp", "--only-show-errors"]).arg(src).arg(url);
+                run(cmd, "aws", None)
+            }
+            Remote::Gcs(url) => {
+                let mut cmd = Command::new("gcloud");
+                cmd.args(["storage", "cp"]).arg(src).arg(url);
+                run(cmd, "gcloud", None)
+            }
+        }
+    }
+}
+
+pub(crate) async fn push(url: &str, config_path: Option<&Path>) -> Result<()> {
+    let remote = Remote::parse(url)?;
+    // Copying the database while the daemon writes to it could upload a torn snapshot.
+    super::utils::ensure_no_daemon().await?;
+
+    let root = std::env::current_dir()?;
+    let branch = current_branch();
+    let index_dir = root.join(".codeindex").join("branches").join(&branch);
+    if !index_dir.join("surreal.db").exists() {
+        return Err(anyhow!("no index for branch {}; run `emry index` first", branch));
+    }
+    if index_dir.join(super::index::CHECKPOINT_FILE).exists() {
+        return Err(anyhow!("the last `emry index` run did not finish; run it again before pushing"));
+    }
+    let config = load_config(config_path)?;
+
+    let commit = git(&["rev-parse", "HEAD"]).ok().map(|s| s.trim().to_string());
+    let uncommitted = match &commit {
+        Some(commit) => changed_since(commit)?,
+        None => Vec::new(),
+    };
+    let manifest = Manifest {
+        format: FORMAT_VERSION,
+        emry_version: env!("CARGO_PKG_VERSION").to_string(),
+        commit: commit.clone(),
+        uncommitted,
+        branch: branch.clone(),
+        root: root.clone(),
+        embedding_model: config.embedding.model_name.clone(),
+        embedding_dimension: get_embedding_dimension(&config.embedding),
+    };
+    std::fs::write(index_dir.join(MANIFEST_FILE), serde_json::to_string_pretty(&manifest)?)?;
+
+    let archive = std::env::temp_dir().join(format!("emry-index-{}.tar.gz", std::process::id()));
+    let result = pack(&index_dir, &archive).and_then(|_| {
+        let size = std::fs::metadata(&archive).map(|m| m.len()).unwrap_or(0);
+        remote.upload(&archive)?;
+        Ok(size)
+    });
+    let _ = std::fs::remove_file(&archive);
+    let size = result?;
+
+    ui::print_success(&format!("Pushed the {} index ({:.1} MB) to {}", branch, size as f64 / 1_048_576.0, url));
+    match commit {
+        Some(commit) if manifest.uncommitted.is_empty() => println!("Built at commit {}.", short(&commit)),
+        Some(commit) => println!(
+            "Built at commit {} plus {} uncommitted file(s), which pulls re-index.",
+            short(&commit),
+            manifest.uncommitted.len()
+        ),
+        None => println!("Not a git checkout: pulls will check every file for changes."),
+    }
+    Ok(())
+}
+
+pub(crate) async fn pull(url: &str, force: bool, config_path: Option<&Path>) -> Result<()> {
+    let remote = Remote::parse(url)?;
+    super::utils::ensure_no_daemon().await?;
+
+    let root = std::env::current_dir()?;
+    let branch = current_branch();
+    let index_dir = root.join(".codeindex").join("branches").join(&branch);
+    if index_dir.join("surreal.db").exists() && !force {
+        return Err(anyhow!("an index already exists for branch {}; pass --force to replace it", branch));
+    }
+    let config = load_config(config_path)?;
+
+    // Unpack next to the index so moving it into place is a rename, not a copy.
+    let staging = root.join(".codeindex").join(format!("pull-{}", std::process::id()));
+    let unpacked = staging.join("index");
+    std::fs::create_dir_all(&unpacked)?;
+    let result = fetch(&remote, &staging, &unpacked).and_then(|manifest| {
+        check_compatible(&manifest, &root, &config)?;
+        if index_dir.exists() {
+            std::fs::remove_dir_all(&index_dir)?;
+        }
+        if let Some(parent) = index_dir.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::rename(&unpacked, &index_dir)?;
+        Ok(manifest)
+    });
+    let _ = std::fs::remove_dir_all(&staging);
+    let manifest = result?;
+
+    let built_at = manifest.commit.as_deref().map_or("an unknown commit".to_string(), short);
+    ui::print_success(&format!("Pulled the index built at {} into branch {}", built_at, branch));
+
+    // Catch up with what changed since the push: the diff when the commit is known here,
+    // otherwise a regular incremental run, which compares every file's hash.
+    let changed = manifest.commit.as_deref().and_then(|commit| changed_since(commit).ok());
+    let Some(changed) = changed else {
+        println!("Cannot diff against {} here; checking every file for changes.", built_at);
+        return super::index::run_index(false, super::index::DEFAULT_BATCH_SIZE, config_path, None).await;
+    };
+    let touched: HashSet<PathBuf> = changed.iter().chain(&manifest.uncommitted).map(|p| root.join(p)).collect();
+    if touched.is_empty() {
+        println!("The working tree matches the pulled index.");
+        return Ok(());
+    }
+    let ctx = RepoContext::open(config_path, Components::ALL).await?;
+    let updated = super::apply::reindex_touched("Pull", &ctx, touched).await?;
+    ui::print_success(&format!("Re-indexed {} file(s) changed since {}", updated, built_at));
+    Ok(())
+}
+
+/// Download and unpack the archive into `unpacked`, returning its manifest.
+fn fetch(remote: &Remote, staging: &Path, unpacked: &Path) -> Result<Manifest> {
+    let archive = staging.join("index.tar.gz");
+    remote.download(&archive)?;
+    unpack(&archive, unpacked)?;
+    let manifest = std::fs::read_to_string(unpacked.join(MANIFEST_FILE))
+        .context("the archive has no manifest; was it uploaded by `emry index push`?")?;
+    Ok(serde_json::from_str(&manifest)?)
+}
+
+fn check_compatible(manifest: &Manifest, root: &Path, config: &Config) -> Result<()> {
+    if manifest.format != FORMAT_VERSION {
+        return Err(anyhow!(
+            "the index was pushed by emry {} in archive format {}; this emry reads format {}",
+            manifest.emry_version,
+            manifest.format,
+            FORMAT_VERSION
+        ));
+    }
+    if manifest.root != root {
+        return Err(anyhow!(
+            "the index was built in {} and records absolute paths; pull it into a checkout at that path",
+            manifest.root.display()
+        ));
+    }
+    let dimension = get_embedding_dimension(&config.embedding);
+    if dimension.is_some() && dimension != manifest.embedding_dimension {
+        return Err(anyhow!(
+            "the index was built with embedding model {} ({} dimensions) but this config embeds with {} ({} dimensions)",
+            manifest.embedding_model,
+            manifest.embedding_dimension.map_or("no".to_string(), |d| d.to_string()),
+            config.embedding.model_name,
+            dimension.map_or("no".to_string(), |d| d.to_string())
+        ));
+    }
+    Ok(())
+}
+
+fn load_config(config_path: Option<&Path>) -> Result<Config> {
+    Ok(match config_path {
+        Some(p) => Config::from_file(p)?,
+        None => Config::load()?,
+    })
+}
+
+/// Files that differ from `rev` in the working tree, tracked or not, relative to the current directory.
+fn changed_since(rev: &str) -> Result<Vec<PathBuf>> {
+    let tracked = git(&["diff", "--name-only", "--relative", "--no-renames", rev])?;
+    let untracked = git(&["ls-files", "--others", "--exclude-standard"])?;
+    Ok(tracked.lines().chain(untracked.lines()).filter(|l| !l.is_empty()).map(PathBuf::from).collect())
+}
+
+fn git(args: &[&str]) -> Result<String> {
+    let output = Command::new("git").args(args).output().context("failed to run git")?;
+    if !output.status.success() {
+        return Err(anyhow!("git {} failed: {}", args.join(" "), String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn pack(dir: &Path, archive: &Path) -> Result<()> {
+    let mut cmd = Command::new("tar");
+    cmd.arg("-czf").arg(archive).arg("-C").arg(dir).arg(".");
+    run(cmd, "tar", None)
+}
+
+fn unpack(archive: &Path, dir: &Path) -> Result<()> {
+    let mut cmd = Command::new("tar");
+    cmd.arg("-xzf").arg(archive).arg("-C").arg(dir);
+    run(cmd, "tar", None)
+}
+
+/// A `curl` invocation for `url`, and the auth header to feed it on stdin
+/// (kept off the command line, where other users could read it).
+fn curl(url: &str) -> (Command, Option<String>) {
+    let mut cmd = Command::new("curl");
+    cmd.args(["--fail", "--silent", "--show-error", "--location"]);
+    let token = std::env::var("EMRY_INDEX_TOKEN").ok().filter(|t| !t.is_empty());
+    if token.is_some() {
+        cmd.args(["--header", "@-"]);
+    }
+    cmd.arg(url);
+    (cmd, token.map(|t| format!("Authorization: Bearer {}\n", t)))
+}
+
+fn run(mut cmd: Command, tool: &str, input: Option<&str>) -> Result<()> {
+    cmd.stdin(if input.is_some() { Stdio::piped() } else { Stdio::null() });
+    let mut child = cmd.spawn().with_context(|| format!("failed to run {}; is it installed?", tool))?;
+    if let (Some(input), Some(mut stdin)) = (input, child.stdin.take()) {
+        stdin.write_all(input.as_bytes())?;
+    }
+    let status = child.wait()?;
+    if !status.success() {
+        return Err(anyhow!("{} exited with {}", tool, status));
+    }
+    Ok(())
+}
+
+fn short(commit: &str) -> String {
+    commit.chars().take(12).collect()
+}
@@ -0,0 +1,226 @@
+//! One-shot diagnostics bundle for bug reports.
+//!
+//! Nothing is sent anywhere: `emry report-issue` writes a tarball the user
+//! can review and attach themselves. It holds versions, the effective config
+//! with secrets redacted, the doctor report without file paths, and the most
+//! recent command errors and crash log. Absolute paths of the repository and the home
+//! directory are replaced with `<repo>` and `~` throughout.
+
+use anyhow::{Context, Result};
+use emry_agent::project::{Components, RepoContext};
+use emry_agent::workflows::doctor::diagnose;
+use emry_config::Config;
+use emry_core::error::ErrorKind;
+use serde_json::{json, Value};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::ui;
+
+/// Command errors, one JSON object per line, under `.codeindex/`.
+const ERROR_LOG: &str = "errors.log";
+/// The error log is rotated to `errors.log.1` past this size.
+const MAX_ERROR_LOG_BYTES: u64 = 256 * 1024;
+/// Most recent errors (and daemon log lines) included in a report.
+const MAX_REPORTED_ERRORS: usize = 50;
+/// Config keys whose string values are replaced with `<redacted>`.
+const SECRET_KEYS: &[&str] = &["key", "secret", "token", "password", "api_base"];
+/// Environment variables whose presence (never value) is reported.
+const REPORTED_ENV_PREFIXES: &[&str] = &["EMRY_", "OPENAI_", "RERANK_", "OLLAMA_"];
+
+pub async fn handle_report_issue(output: Option<PathBuf>, config_path: Option<&Path>) -> Result<()> {
+    let root = std::env::current_dir().context("failed to get current directory")?;
+    let anonymize = Anonymizer::new(&root);
+    let stamp = unix_time();
+
+    let staging = std::env::temp_dir().join(format!("emry-report-{}-{}", std::process::id(), stamp));
+    std::fs::create_dir_all(&staging)?;
+    let result = collect(&root, &staging, &anonymize, config_path).await.and_then(|_| {
+        let output = output.unwrap_or_else(|| root.join(format!("emry-report-{}.tar.gz", stamp)));
+        archive(&staging, &output)?;
+        Ok(output)
+    });
+    let _ = std::fs::remove_dir_all(&staging);
+    let output = result?;
+
+    ui::print_success(&format!("Wrote {}", output.display()));
+    println!("Review its contents before attaching it to an issue; nothing has been uploaded.");
+    Ok(())
+}
+
+/// Append a failed command's error to `.codeindex/errors.log`, if this is an indexed repository.
+pub fn record_error(kind: ErrorKind, message: &str, err: &anyhow::Error) {
+    let dir = Path::new(".codeindex");
+    if !dir.is_dir() || super::utils::read_only() {
+        return;
+    }
+    let path = dir.join(ERROR_LOG);
+    if std::fs::metadata(&path).map_or(false, |m| m.len() > MAX_ERROR_LOG_BYTES) {
+        let _ = std::fs::rename(&path, dir.join(format!("{}.1", ERROR_LOG)));
+    }
+    let entry = json!({
+        "timestamp": unix_time(),
+        "version": env!("CARGO_PKG_VERSION"),
+        "command": std::env::args().nth(1),
+        "code": kind.code(),
+        "message": message,
+        "chain": err.chain().skip(1).map(|c| c.to_string()).collect::<Vec<_>>(),
+    });
+    if let Ok(mut file) = std::fs::OpenOptions::new().create(true).append(true).open(&path) {
+        let _ = writeln!(file, "{}", entry);
+    }
+}
+
+async fn collect(root: &Path, staging: &Path, anonymize: &Anonymizer, config_path: Option<&Path>) -> Result<()> {
+    std::fs::write(staging.join("versions.json"), serde_json::to_string_pretty(&versions())?)?;
+
+    let config = match config_path {
+        Some(path) => Config::from_file(path),
+        None => Config::load(),
+    };
+    let config = match config {
+        Ok(config) => {
+            let mut value = serde_json::to_value(&config)?;
+            redact(&mut value);
+            serde_json::to_string_pretty(&value)?
+        }
+        Err(e) => format!("config failed to load: {}", e),
+    };
+    std::fs::write(staging.join("config.json"), anonymize.apply(&config))?;
+
+    let doctor = match RepoContext::open(config_path, Components::STORE).await {
+        Ok(ctx) if ctx.surreal_store.is_some() => match diagnose(&ctx).await {
+            Ok(mut report) => {
+                // File paths can name the project's internals.
+                report.parse_error_files.clear();
+                report.integrity.files_without_chunks.clear();
+                report.integrity.files_missing_embeddings.clear();
+                report.integrity.affected_files.clear();
+                for rec in &mut report.recommendations {
+                    rec.config = None;
+                }
+                serde_json::to_string_pretty(&report)?
+            }
+            Err(e) => format!("doctor failed: {:#}", e),
+        },
+        Ok(_) => "doctor skipped: store could not be opened".to_string(),
+        Err(e) => format!("doctor skipped: {:#}", e),
+    };
+    std::fs::write(staging.join("doctor.json"), anonymize.apply(&doctor))?;
+
+    let index = root.join(".codeindex");
+    let mut errors = String::new();
+    for log in [index.join(format!("{}.1", ERROR_LOG)), index.join(ERROR_LOG)] {
+        errors.push_str(&std::fs::read_to_string(log).unwrap_or_default());
+    }
+    std::fs::write(staging.join("errors.jsonl"), anonymize.apply(&tail(&errors, MAX_REPORTED_ERRORS)))?;
+
+    if let Some(crash) = super::crash::latest_crash_log(&index) {
+        let log = std::fs::read_to_string(crash).unwrap_or_default();
+        std::fs::write(staging.join("crash.log"), anonymize.apply(&log))?;
+    }
+
+    let daemon = daemon_logs(&index.join("branches"));
+    if !daemon.is_empty() {
+        std::fs::write(staging.join("daemon.log"), anonymize.apply(&tail(&daemon, MAX_REPORTED_ERRORS)))?;
+    }
+    Ok(())
+}
+
+fn versions() -> Value {
+    let command_version = |cmd: &str| {
+        std::process::Command::new(cmd)
+            .arg("--version")
+            .output()
+            .ok()
+            .filter(|o| o.status.success())
+            .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+    };
+    let env_vars: Vec<String> = std::env::vars()
+        .map(|(name, _)| name)
+        .filter(|name| REPORTED_ENV_PREFIXES.iter().any(|p| name.starts_with(p)))
+        .collect();
+    json!({
+        "emry": env!("CARGO_PKG_VERSION"),
+        "onnx_feature": cfg!(feature = "onnx"),
+        "os": std::env::consts::OS,
+        "arch": std::env::consts::ARCH,
+        "git": command_version("git"),
+        "ollama": command_version("ollama"),
+        "env_vars_set": env_vars,
+    })
+}
+
+/// Replace the string value of every secret-looking key, at any depth.
+fn redact(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, v) in map.iter_mut() {
+                let key = key.to_lowercase();
+                if v.is_string() && SECRET_KEYS.iter().any(|s| key.contains(s)) {
+                    *v = Value::String("<redacted>".to_string());
+                } else {
+                    redact(v);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact),
+        _ => {}
+    }
+}
+
+/// Contents of every branch's `daemon.log`.
+fn daemon_logs(branches: &Path) -> String {
+    let Ok(entries) = std::fs::read_dir(branches) else {
+        return String::new();
+    };
+    let mut paths: Vec<PathBuf> = entries.flatten().map(|e| e.path().join("daemon.log")).filter(|p| p.is_file()).collect();
+    paths.sort();
+    paths.iter().filter_map(|p| std::fs::read_to_string(p).ok()).collect()
+}
+
+fn tail(text: &str, lines: usize) -> String {
+    let all: Vec<&str> = text.lines().collect();
+    let mut out = all[all.len().saturating_sub(lines)..].join("\n");
+    out.push('\n');
+    out
+}
+
+fn archive(dir: &Path, output: &Path) -> Result<()> {
+    let status = std::process::Command::new("tar")
+        .arg("-czf")
+        .arg(output)
+        .arg("-C")
+        .arg(dir)
+        .arg(".")
+        .status()
+        .context("failed to run tar")?;
+    if !status.success() {
+        return Err(anyhow::anyhow!("tar exited with {}", status));
+    }
+    Ok(())
+}
+
+fn unix_time() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+/// Replaces the repository root and home directory in collected text.
+struct Anonymizer {
+    replacements: Vec<(String, &'static str)>,
+}
+
+impl Anonymizer {
+    fn new(root: &Path) -> Self {
+        let mut replacements = vec![(root.to_string_lossy().into_owned(), "<repo>")];
+        if let Some(home) = std::env::var_os("HOME").filter(|h| !h.is_empty()) {
+            replacements.push((home.to_string_lossy().into_owned(), "~"));
+        }
+        Self { replacements }
+    }
+
+    fn apply(&self, text: &str) -> String {
+        self.replacements.iter().fold(text.to_string(), |acc, (from, to)| acc.replace(from.as_str(), to))
+    }
+}
@@ -1,19 +1,32 @@
 use anyhow::Result;
 use clap::ValueEnum;
 use emry_agent::project as agent_context;
-use emry_core::models::Language;
+use emry_core::models::{ChunkLocation, Language, NotIndexed, RankingStep, ScoredChunk, SearchHit, SearchOutput, SourceTotal};
+use emry_config::SnippetSource;
+use emry_engine::search::diagnostics::explain_empty;
+use emry_engine::search::matching::MatchOptions;
+use emry_engine::search::pipeline::FileSetFilter;
 use emry_engine::search::service::SearchService;
+use emry_store::SurrealStore;
+use emry_engine::search::snippet::{read_span, resolve_snippet, span_diff, Snippet};
 use std::path::Path;
 use std::path::PathBuf;
+use std::sync::Arc;
 
+use super::browse::browse;
+use super::daemon::DaemonClient;
+use super::federation;
 use super::regex_utils;
 use super::utils::{build_single_globset, path_matches};
 use emry_agent::ops::rewriter::QueryRewriter;
 use emry_agent::llm::OpenAIProvider;
 
-use super::ui;
+use super::ui::{self, palette};
 use console::Style;
 
+/// Locations of identical chunks listed under a hit.
+const DUPLICATES_SHOWN: usize = 3;
+
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
 pub enum CliSearchMode {
     Lexical,
@@ -21,29 +34,191 @@ pub enum CliSearchMode {
     Hybrid,
 }
 
+impl CliSearchMode {
+    /// Name sent to the daemon, as spelled on the command line.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CliSearchMode::Lexical => "lexical",
+            CliSearchMode::Semantic => "semantic",
+            CliSearchMode::Hybrid => "hybrid",
+        }
+    }
+}
+
 pub async fn handle_search(
     query: String,
     config_path: Option<&Path>,
     limit: usize,
-    _mode: Option<CliSearchMode>,
+    mode: Option<CliSearchMode>,
     lang: Option<String>,
     path: Option<String>,
+    owner: Option<String>,
     symbol: bool,
     regex: bool,
+    docs: bool,
     no_ignore: bool,
+    matching: MatchOptions,
+    all: bool,
     smart: bool,
+    json: bool,
+    interactive: bool,
+    show_diff: bool,
+    explain: bool,
+    autocorrect: bool,
+    remote: Option<String>,
+    local: bool,
 ) -> Result<()> {
-    ui::print_header(&format!("Searching for: {}{}", query, if smart { " (Smart)" } else { "" }));
+    let logged = query.clone();
+    // `--remote` wins over `search.remote`; `--local` ignores both, and ownership is only known locally.
+    let remote = if local || owner.is_some() { None } else { remote.or_else(|| configured_remote(config_path)) };
+    let top = run_search(
+        query, config_path, limit, mode, lang, path, owner, symbol, regex, docs, no_ignore, matching, all, smart, json,
+        interactive, show_diff, explain, autocorrect, remote,
+    )
+    .await?;
+    super::history::record_query("search", &logged, top);
+    Ok(())
+}
 
-    let ctx = agent_context::RepoContext::from_env(config_path).await?;
+/// Run the search and print it; returns the top result for the query log.
+async fn run_search(
+    query: String,
+    config_path: Option<&Path>,
+    limit: usize,
+    mode: Option<CliSearchMode>,
+    lang: Option<String>,
+    path: Option<String>,
+    owner: Option<String>,
+    symbol: bool,
+    regex: bool,
+    docs: bool,
+    no_ignore: bool,
+    matching: MatchOptions,
+    all: bool,
+    smart: bool,
+    json: bool,
+    interactive: bool,
+    show_diff: bool,
+    explain: bool,
+    autocorrect: bool,
+    remote: Option<String>,
+) -> Result<Option<String>> {
+    if json && (symbol || regex) {
+        return Err(emry_core::error::EmryError::InvalidQuery(
+            "--json is only supported for ranked search, not --symbol or --regex".to_string(),
+        )
+        .into());
+    }
+    if explain && (symbol || regex || smart) {
+        return Err(emry_core::error::EmryError::InvalidQuery(
+            "--explain-ranking is only supported for ranked search, not --symbol, --regex or --smart".to_string(),
+        )
+        .into());
+    }
 
+    // Ranked searches read the modifiers back out of the query, also in the daemon.
+    let query = if symbol || regex { query } else { format!("{}{}", query, matching.modifiers()) };
+
+    // `--all`, `--docs`, `--lang` and `--path` search the local index only.
+    let by_file = lang.is_some() || path.is_some();
+    if let Some(remote) = remote.filter(|_| !symbol && !regex && !all && !docs && !by_file) {
+        let output = match daemon_for(config_path).await {
+            Some(mut client) => {
+                let output = client.search(&query, limit, mode.map(CliSearchMode::as_str), smart, show_diff, explain).await?;
+                match output.did_you_mean.clone().filter(|_| autocorrect) {
+                    Some(corrected) => client.search(&corrected, limit, mode.map(CliSearchMode::as_str), smart, show_diff, explain).await?,
+                    None => output,
+                }
+            }
+            None => indexed_output(&query, config_path, limit, mode, None, None, None, false, false, smart, show_diff, explain, autocorrect).await?,
+        };
+        let output = federation::with_remote(output, &remote, limit, smart, explain).await;
+        if json {
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        } else if interactive {
+            browse(&output)?;
+        } else {
+            let applied = output.query != query;
+            ui::print_header(&format!("Searching for: {}{}", output.query, if smart { " (Smart)" } else { "" }));
+            print_correction(&query, if applied { Some(output.query.as_str()) } else { output.did_you_mean.as_deref() }, applied);
+            print_hits(&output, show_diff, None);
+        }
+        return Ok(top_hit(&output));
+    }
+
+    // The daemon's searches can't take an owner, language or path filter or search docs.
+    if !symbol && !regex && !docs && owner.is_none() && !by_file {
+        if let Some(mut client) = daemon_for(config_path).await {
+            let mode = mode.map(CliSearchMode::as_str);
+            let mut output = client.search(&query, limit, mode, smart, show_diff, explain).await?;
+            let corrected = output.did_you_mean.clone().filter(|_| autocorrect);
+            if let Some(corrected) = &corrected {
+                output = client.search(corrected, limit, mode, smart, show_diff, explain).await?;
+            }
+            let full = full_limit(&output.totals, limit);
+            if all && full > limit {
+                output = client.search(&output.query.clone(), full, mode, smart, show_diff, explain).await?;
+            }
+            if json {
+                println!("{}", serde_json::to_string_pretty(&output)?);
+            } else if interactive {
+                browse(&output)?;
+            } else {
+                ui::print_header(&format!("Searching for: {}{}", output.query, if smart { " (Smart)" } else { "" }));
+                print_correction(&query, corrected.as_deref().or(output.did_you_mean.as_deref()), corrected.is_some());
+                print_hits(&output, show_diff, all.then_some(limit));
+            }
+            return Ok(top_hit(&output));
+        }
+    }
+
+    if json {
+        return handle_json_search(
+            &query, config_path, limit, mode, owner.as_deref(), lang.as_deref(), path.as_deref(), docs, all, smart, show_diff, explain,
+            autocorrect,
+        )
+        .await;
+    }
+    if interactive {
+        let output = indexed_output(
+            &query, config_path, limit, mode, owner.as_deref(), lang.as_deref(), path.as_deref(), docs, false, smart, false, explain,
+            autocorrect,
+        )
+        .await?;
+        browse(&output)?;
+        return Ok(top_hit(&output));
+    }
+
+    let header = |query: &str| ui::print_header(&format!("Searching for: {}{}", query, if smart { " (Smart)" } else { "" }));
+    if symbol || regex {
+        header(&query);
+    }
+
+    // Symbol, regex and lexical searches never touch the embedder, so skip probing it.
+    let lexical_only = symbol || regex || match mode {
+        Some(m) => m == CliSearchMode::Lexical,
+        None => false,
+    };
+    let components = if lexical_only {
+        agent_context::Components::STORE
+    } else {
+        agent_context::Components::ALL
+    };
+    let ctx = agent_context::RepoContext::open(config_path, components).await?;
+    if !lexical_only {
+        require_semantic(&ctx, mode)?;
+    }
 
-    let embedder = ctx.embedder.clone();
-    
     let surreal_store = ctx.surreal_store.clone()
         .ok_or_else(|| anyhow::anyhow!("SurrealStore not initialized in context"))?;
-    let search_service = SearchService::new(surreal_store.clone(), embedder.clone());
-    
+    let search_service = if docs {
+        require_docs(&surreal_store).await?;
+        ctx.docs_search_service(surreal_store.clone())
+    } else {
+        ctx.search_service(surreal_store.clone())
+    };
+    let search_service = owned_by(search_service, &surreal_store, owner.as_deref()).await?;
+
     if symbol {
         return handle_symbol_search(&query, &ctx, limit, lang, path).await;
     }
@@ -51,19 +226,370 @@ pub async fn handle_search(
     if regex {
         return handle_regex_search(&query, &ctx, lang, path, no_ignore);
     }
+    let search_service = matching_files(search_service, &surreal_store, &ctx.root, lang.as_deref(), path.as_deref()).await?;
+
+    let suggestion = search_service.did_you_mean(&query).await;
+    let original = query;
+    let query = match &suggestion {
+        Some(corrected) if autocorrect => corrected.clone(),
+        _ => original.clone(),
+    };
+    header(&query);
+    print_correction(&original, suggestion.as_deref(), autocorrect);
+
+    let page = all.then_some(limit);
+    let limit = if all { full_limit(&search_service.totals(&query, None).await, limit) } else { limit };
+    handle_smart_search(&query, &ctx, &search_service, limit, page, smart, show_diff, explain).await
+}
+
+/// `search.remote` from the config, if it loads and sets one.
+fn configured_remote(config_path: Option<&Path>) -> Option<String> {
+    let config = match config_path {
+        Some(path) => emry_config::Config::from_file(path),
+        None => emry_config::Config::load(),
+    };
+    config.ok()?.search.remote
+}
 
-    handle_smart_search(&query, &ctx, &search_service, limit, smart).await?;
+/// The first hit as `path:line`.
+fn top_hit(output: &SearchOutput) -> Option<String> {
+    output.hits.first().map(|h| format!("{}:{}", h.file_path, h.start_line))
+}
+
+/// For `--all`: a limit covering every match any source reported.
+fn full_limit(totals: &[SourceTotal], limit: usize) -> usize {
+    totals.iter().map(|t| t.matches).max().unwrap_or(0).max(limit)
+}
+
+/// `service` limited to the files `owner` owns, when one is given.
+async fn owned_by(service: SearchService, store: &SurrealStore, owner: Option<&str>) -> Result<SearchService> {
+    let Some(owner) = owner else {
+        return Ok(service);
+    };
+    let files = store.files_owned_by(owner).await?;
+    if files.is_empty() {
+        return Err(emry_core::error::EmryError::InvalidQuery(format!(
+            "no indexed files are owned by '{}'; see `emry owners`",
+            owner
+        ))
+        .into());
+    }
+    Ok(service.with_filter(Arc::new(FileSetFilter::new(files))))
+}
 
+/// `service` limited to the indexed files in language `lang` whose paths
+/// match the glob `path`, when either is given. The pipeline searches only
+/// those files, so the filters don't cut into `--top`.
+async fn matching_files(
+    service: SearchService,
+    store: &SurrealStore,
+    root: &Path,
+    lang: Option<&str>,
+    path: Option<&str>,
+) -> Result<SearchService> {
+    if lang.is_none() && path.is_none() {
+        return Ok(service);
+    }
+    let language = match lang.map(|name| (name, Language::from_name(name))) {
+        Some((name, Language::Unknown)) => {
+            return Err(emry_core::error::EmryError::InvalidQuery(format!("unknown language '{}'", name)).into());
+        }
+        other => other.map(|(_, language)| language),
+    };
+    let matcher = build_single_globset(path);
+    let files: Vec<String> = store
+        .list_file_languages()
+        .await?
+        .into_iter()
+        .filter(|(_, l)| language.map_or(true, |language| Language::from_name(l) == language))
+        .filter(|(p, _)| path_matches(&matcher, root, Path::new(p)))
+        .map(|(p, _)| p)
+        .collect();
+    if files.is_empty() {
+        return Err(emry_core::error::EmryError::InvalidQuery("no indexed files match --lang/--path".to_string()).into());
+    }
+    Ok(service.with_filter(Arc::new(FileSetFilter::new(files))))
+}
+
+/// Fail `--docs` searches of an index without documentation, e.g. one built before docs were extracted.
+async fn require_docs(store: &SurrealStore) -> Result<()> {
+    if store.count_documented_chunks().await? == 0 {
+        return Err(emry_core::error::EmryError::InvalidQuery(
+            "the index holds no doc comments; re-index to extract them".to_string(),
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// Fail explicit semantic searches without an embedder; hybrid quietly degrades to lexical.
+fn require_semantic(ctx: &agent_context::RepoContext, mode: Option<CliSearchMode>) -> Result<()> {
+    if mode == Some(CliSearchMode::Semantic) && !ctx.capabilities().semantic {
+        return Err(emry_core::error::EmryError::EmbedderUnavailable(
+            "semantic mode requested but no embedder configured".into(),
+        )
+        .into());
+    }
     Ok(())
 }
 
+async fn handle_json_search(
+    query: &str,
+    config_path: Option<&Path>,
+    limit: usize,
+    mode: Option<CliSearchMode>,
+    owner: Option<&str>,
+    lang: Option<&str>,
+    path: Option<&str>,
+    docs: bool,
+    all: bool,
+    smart: bool,
+    show_diff: bool,
+    explain: bool,
+    autocorrect: bool,
+) -> Result<Option<String>> {
+    let output =
+        indexed_output(query, config_path, limit, mode, owner, lang, path, docs, all, smart, show_diff, explain, autocorrect).await?;
+    println!("{}", serde_json::to_string_pretty(&output)?);
+    Ok(top_hit(&output))
+}
+
+/// The daemon serving this repository, unless the search names its own
+/// `--config` or `--profile`: the daemon answers with the configuration it
+/// was started with, so those searches open the index directly.
+async fn daemon_for(config_path: Option<&Path>) -> Option<DaemonClient> {
+    if config_path.is_some() || std::env::var_os(emry_config::PROFILE_ENV).is_some() {
+        return None;
+    }
+    DaemonClient::connect().await
+}
+
+/// Ranked hits from the index opened here rather than through the daemon.
+async fn indexed_output(
+    query: &str,
+    config_path: Option<&Path>,
+    limit: usize,
+    mode: Option<CliSearchMode>,
+    owner: Option<&str>,
+    lang: Option<&str>,
+    path: Option<&str>,
+    docs: bool,
+    all: bool,
+    smart: bool,
+    show_diff: bool,
+    explain: bool,
+    autocorrect: bool,
+) -> Result<SearchOutput> {
+    let components = if mode == Some(CliSearchMode::Lexical) {
+        agent_context::Components::STORE
+    } else {
+        agent_context::Components::ALL
+    };
+    let ctx = agent_context::RepoContext::open(config_path, components).await?;
+    if mode != Some(CliSearchMode::Lexical) {
+        require_semantic(&ctx, mode)?;
+    }
+    let store = ctx.surreal_store.clone()
+        .ok_or_else(|| anyhow::anyhow!("SurrealStore not initialized in context"))?;
+    let search_service = if docs {
+        require_docs(&store).await?;
+        ctx.docs_search_service(store.clone())
+    } else {
+        ctx.search_service(store.clone())
+    };
+    let search_service = owned_by(search_service, &store, owner).await?;
+    let search_service = matching_files(search_service, &store, &ctx.root, lang, path).await?;
+    let corrected = if autocorrect { search_service.did_you_mean(query).await } else { None };
+    let query = corrected.as_deref().unwrap_or(query);
+    let limit = if all { full_limit(&search_service.totals(query, None).await, limit) } else { limit };
+
+    ranked_search_output(&ctx, &search_service, query, limit, smart, show_diff, explain).await
+}
+
+/// Ranked hits as emitted by `--json`, with snippets resolved and optional diffs.
+/// With `explain`, each hit carries its ranking trace (ignored for smart search).
+pub async fn ranked_search_output(
+    ctx: &agent_context::RepoContext,
+    search_service: &SearchService,
+    query: &str,
+    limit: usize,
+    smart: bool,
+    show_diff: bool,
+    explain: bool,
+) -> Result<SearchOutput> {
+    let mut keywords = None;
+    let scored: Vec<ScoredChunk> = if smart {
+        keywords = match std::env::var("OPENAI_API_KEY") {
+            Ok(api_key) => {
+                let model = std::env::var("OPENAI_MODEL").unwrap_or_else(|_| "gpt-4o-mini".to_string());
+                match OpenAIProvider::new(model, api_key, 60) {
+                    Ok(llm) => QueryRewriter::new(llm).rewrite(query).await.ok().map(|e| e.keywords),
+                    Err(_) => None,
+                }
+            }
+            Err(_) => None,
+        };
+        search_service.search_with_context(query, limit, keywords.as_deref()).await?.anchors
+    } else if explain {
+        search_service.search_explained(query, limit, None).await?
+    } else {
+        search_service.search_scored(query, limit, None).await?
+    };
+
+    let ids: Vec<String> = scored.iter().map(|s| s.chunk.id.clone()).collect();
+    let mut duplicates = search_service.duplicate_locations(&ids).await;
+    let not_indexed = match ctx.surreal_store.as_deref().filter(|_| scored.is_empty()) {
+        Some(store) => explain_empty(store, &ctx.config, &ctx.root, query).await.unwrap_or_else(|e| {
+            tracing::warn!("could not explain the empty result: {}", e);
+            Vec::new()
+        }),
+        None => Vec::new(),
+    };
+    Ok(SearchOutput {
+        query: query.to_string(),
+        did_you_mean: search_service.did_you_mean(query).await,
+        totals: search_service.totals(query, keywords.as_deref()).await,
+        hits: scored
+            .iter()
+            .enumerate()
+            .map(|(i, s)| {
+                let mut hit = SearchHit::from_scored(i + 1, s);
+                hit.duplicates = duplicates.remove(&s.chunk.id).unwrap_or_default();
+                let snippet = hit_snippet(ctx, &s.chunk.file_path, hit.start_line, hit.end_line, &hit.snippet);
+                if show_diff && snippet.modified {
+                    hit.diff = span_diff(&s.chunk.file_path, hit.start_line, &hit.snippet, snippet.current.as_deref());
+                }
+                hit.snippet = snippet.text;
+                hit.modified_since_index = snippet.modified;
+                hit
+            })
+            .collect(),
+        not_indexed,
+    })
+}
+
+/// "Did you mean" for a query with misspelled identifiers, or, when the
+/// correction was searched instead, which query it replaced.
+fn print_correction(query: &str, suggestion: Option<&str>, applied: bool) {
+    let Some(suggestion) = suggestion else { return };
+    let dim = Style::new().dim();
+    if applied {
+        println!("{}\n", dim.apply_to(format!("Showing results for '{}' instead of '{}'", suggestion, query)));
+    } else {
+        println!(
+            "Did you mean: {}? {}\n",
+            Style::new().bold().apply_to(suggestion),
+            dim.apply_to("(--autocorrect searches it instead)")
+        );
+    }
+}
+
+/// Print ranked hits, e.g. as served by the daemon, pausing every `page` hits.
+/// Hits that carry a ranking trace are followed by it.
+pub fn print_hits(output: &SearchOutput, show_diff: bool, page: Option<usize>) {
+    if output.hits.is_empty() {
+        println!("No matches found.");
+        print_not_indexed(&output.not_indexed);
+        return;
+    }
+    if output.totals.is_empty() {
+        println!("Found {} matches:", output.hits.len());
+    } else {
+        let totals: Vec<String> = output.totals.iter().map(|t| format!("{} {}", t.matches, t.source)).collect();
+        println!("Found {} matches, showing {}:", totals.join(", "), output.hits.len());
+    }
+    for (i, hit) in output.hits.iter().enumerate() {
+        if page.is_some_and(|page| i > 0 && i % page == 0) && !more(i, output.hits.len()) {
+            break;
+        }
+        let file = match &hit.origin {
+            Some(origin) => format!("[{}] {}", origin, hit.file_path),
+            None => hit.file_path.clone(),
+        };
+        ui::print_search_match(hit.rank, &file, hit.start_line, hit.end_line, &hit.snippet, &hit.tags, hit.modified_since_index);
+        if !hit.duplicates.is_empty() {
+            print_duplicates(&hit.duplicates);
+        }
+        if show_diff && hit.modified_since_index {
+            match &hit.diff {
+                Some(diff) => ui::print_diff(diff),
+                None => println!("{}\n", Style::new().yellow().apply_to("file no longer exists")),
+            }
+        }
+        if !hit.ranking.is_empty() {
+            print_ranking(hit.score, &hit.ranking);
+        }
+    }
+}
+
+/// Likely reasons behind an empty result, one line per file.
+fn print_not_indexed(not_indexed: &[NotIndexed]) {
+    let (files, index): (Vec<&NotIndexed>, Vec<&NotIndexed>) = not_indexed.iter().partition(|n| n.file_path.is_some());
+    for entry in index {
+        println!("{}", palette().warning.apply_to(entry.reason.to_string()));
+    }
+    if files.is_empty() {
+        return;
+    }
+    println!("\nFiles containing the query that the index can't return:");
+    for entry in files {
+        let path = entry.file_path.as_deref().unwrap_or_default();
+        println!("  {}  {}", palette().location.apply_to(path), Style::new().dim().apply_to(&entry.reason));
+    }
+}
+
+/// Where else a hit's text is indexed, up to `DUPLICATES_SHOWN` locations.
+fn print_duplicates(locations: &[ChunkLocation]) {
+    let mut shown: Vec<String> = locations
+        .iter()
+        .take(DUPLICATES_SHOWN)
+        .map(|l| format!("{}:{}-{}", l.file_path, l.start_line, l.end_line))
+        .collect();
+    if locations.len() > DUPLICATES_SHOWN {
+        shown.push(format!("{} more", locations.len() - DUPLICATES_SHOWN));
+    }
+    println!("  {}\n", Style::new().dim().apply_to(format!("also in {}", shown.join(", "))));
+}
+
+/// One line per ranking stage, ending with the final score.
+fn print_ranking(score: f32, steps: &[RankingStep]) {
+    let dim = Style::new().dim();
+    for step in steps {
+        let (stage, detail) = match step {
+            RankingStep::Retrieved { source, rank, score, matched_terms, distance } => {
+                let mut detail = format!("{} #{} score {:.4}", source, rank, score);
+                if !matched_terms.is_empty() {
+                    detail.push_str(&format!(", matched {}", matched_terms.join(", ")));
+                }
+                if let Some(distance) = distance {
+                    detail.push_str(&format!(", distance {:.4}", distance));
+                }
+                ("retrieved", detail)
+            }
+            RankingStep::Fused { source, contribution } => ("fused", format!("{} +{:.4}", source, contribution)),
+            RankingStep::Intent { intent, reasons, factor } => {
+                ("intent", format!("{} x{:.2} ({})", intent, factor, reasons.join("; ")))
+            }
+            RankingStep::Graph { from, to, hops, boost } => {
+                ("graph", format!("{} -> {}, {} hop(s), +{:.4}", from, to, hops, boost))
+            }
+            RankingStep::Coverage { ratio, factor } => {
+                ("coverage", format!("{:.0}% of lines tested, x{:.2}", ratio * 100.0, factor))
+            }
+            RankingStep::Reranked { score } => ("reranked", format!("cross-encoder score {:.4}", score)),
+        };
+        println!("  {} {}", Style::new().cyan().apply_to(format!("{:<9}", stage)), dim.apply_to(detail));
+    }
+    println!("  {} {}\n", Style::new().cyan().apply_to(format!("{:<9}", "final")), dim.apply_to(format!("{:.4}", score)));
+}
+
 async fn handle_symbol_search(
     query: &str,
     ctx: &agent_context::RepoContext,
     _limit: usize,
     lang: Option<String>,
     path: Option<String>,
-) -> Result<()> {
+) -> Result<Option<String>> {
     let root = &ctx.root;
     let matcher = build_single_globset(path.as_deref());
     let lang_filter = lang.as_deref().map(Language::from_name);
@@ -102,7 +628,7 @@ async fn handle_symbol_search(
             println!("   {}", Style::new().dim().apply_to(format!("ID: {}", id)));
         }
     }
-    Ok(())
+    Ok(matches.first().map(|(name, file_path, _)| format!("{} ({})", name, file_path.display())))
 }
 
 fn handle_regex_search(
@@ -111,13 +637,14 @@ fn handle_regex_search(
     lang: Option<String>,
     path: Option<String>,
     no_ignore: bool,
-) -> Result<()> {
+) -> Result<Option<String>> {
     let root = &ctx.root;
     let config = &ctx.config;
     let matcher = build_single_globset(path.as_deref());
     let lang_filter = lang.as_deref().map(Language::from_name);
     
     let matches = regex_utils::regex_search(root, query, &config.core, !no_ignore)?;
+    let mut top = None;
     
     if matches.is_empty() {
         println!("No matches for regex '{}'.", query);
@@ -135,19 +662,49 @@ fn handle_regex_search(
                 continue;
             }
             let rel = p.strip_prefix(root).unwrap_or(&p);
-            ui::print_search_match(0, &rel.to_string_lossy(), line, line, &content);
+            top.get_or_insert_with(|| format!("{}:{}", rel.display(), line));
+            ui::print_search_match(0, &rel.to_string_lossy(), line, line, &content, &[], false);
         }
     }
-    Ok(())
+    Ok(top)
+}
+
+/// Ask whether to print more hits; always yes when not interactive.
+fn more(shown: usize, total: usize) -> bool {
+    let term = console::Term::stdout();
+    if !term.is_term() {
+        return true;
+    }
+    println!(
+        "{}",
+        Style::new().dim().apply_to(format!("-- {} of {} shown; Enter for more, q to stop --", shown, total))
+    );
+    term.read_line().map_or(false, |line| !line.trim().eq_ignore_ascii_case("q"))
+}
+
+/// Snippet for a hit, read from the index or working tree per `search.snippet_source`.
+fn hit_snippet(ctx: &agent_context::RepoContext, path: &Path, start: usize, end: usize, indexed: &str) -> Snippet {
+    resolve_snippet(&ctx.root, path, start, end, indexed, ctx.config.search.snippet_source)
+}
+
+fn print_hit_diff(path: &Path, start: usize, indexed: &str, snippet: &Snippet) {
+    match span_diff(path, start, indexed, snippet.current.as_deref()) {
+        Some(diff) => ui::print_diff(&diff),
+        None => println!("{}\n", Style::new().yellow().apply_to("file no longer exists")),
+    }
 }
 
 async fn handle_smart_search(
     query: &str,
-    _ctx: &agent_context::RepoContext,
+    ctx: &agent_context::RepoContext,
     search_service: &SearchService,
     limit: usize,
+    page: Option<usize>,
     smart: bool,
-) -> Result<()> {
+    show_diff: bool,
+    explain: bool,
+) -> Result<Option<String>> {
+    let mut top = None;
     if smart {
         let keywords = if let Ok(api_key) = std::env::var("OPENAI_API_KEY") {
             let model = std::env::var("OPENAI_MODEL").unwrap_or_else(|_| "gpt-4o-mini".to_string());
@@ -170,6 +727,11 @@ async fn handle_smart_search(
 
         let context_graph = search_service.search_with_context(query, limit, keywords.as_deref()).await?;
         let grouped = context_graph.group_by_symbol();
+        top = grouped
+            .groups
+            .first()
+            .map(|g| format!("{} ({})", g.symbol.name, g.symbol.file_path.display()))
+            .or_else(|| grouped.unassigned.first().map(|a| format!("{}:{}", a.chunk.file_path.display(), a.chunk.start_line)));
         
         if grouped.groups.is_empty() && grouped.unassigned.is_empty() {
             println!("No smart matches found.");
@@ -182,6 +744,23 @@ async fn handle_smart_search(
                 let start_line = group.anchors.iter().map(|c| c.chunk.start_line).min().unwrap_or(0);
                 let end_line = group.anchors.iter().map(|c| c.chunk.end_line).max().unwrap_or(0);
                 let content = emry_core::models::ScoredChunk::concatenate_chunks(&group.anchors);
+                let anchor_diffs: Vec<Option<String>> = group.anchors.iter()
+                    .map(|a| {
+                        let c = &a.chunk;
+                        let snippet = hit_snippet(ctx, &c.file_path, c.start_line, c.end_line, &c.content);
+                        snippet.modified.then(|| {
+                            span_diff(&c.file_path, c.start_line, &c.content, snippet.current.as_deref())
+                                .unwrap_or_else(|| "file no longer exists".to_string())
+                        })
+                    })
+                    .collect();
+                let modified = anchor_diffs.iter().any(Option::is_some);
+                let content = match ctx.config.search.snippet_source {
+                    SnippetSource::PreferWorkingTree => {
+                        read_span(&ctx.root, &group.symbol.file_path, start_line, end_line).unwrap_or(content)
+                    }
+                    SnippetSource::PreferIndex => content,
+                };
 
                 match_index += 1;
                 println!("{} {} {} {}", 
@@ -190,6 +769,12 @@ async fn handle_smart_search(
                     Style::new().bold().cyan().apply_to(&group.symbol.name),
                     Style::new().dim().apply_to(format!("({}:{}-{})", group.symbol.file_path.display(), start_line, end_line))
                 );
+                if modified {
+                    println!("  {}", Style::new().yellow().apply_to("(modified since index)"));
+                    if show_diff {
+                        anchor_diffs.iter().flatten().for_each(|d| ui::print_diff(d));
+                    }
+                }
                 
                 if !group.calls.is_empty() {
                     print!("  {} Calls: ", Style::new().dim().apply_to("↳"));
@@ -208,36 +793,28 @@ async fn handle_smart_search(
                 println!("Other Matches:");
                 for anchor in grouped.unassigned {
                     match_index += 1;
+                    let c = &anchor.chunk;
+                    let snippet = hit_snippet(ctx, &c.file_path, c.start_line, c.end_line, &c.content);
                     ui::print_search_match(
                         match_index,
-                        &anchor.chunk.file_path.display().to_string(),
-                        anchor.chunk.start_line,
-                        anchor.chunk.end_line,
-                        &anchor.chunk.content
+                        &c.file_path.display().to_string(),
+                        c.start_line,
+                        c.end_line,
+                        &snippet.text,
+                        &c.tags,
+                        snippet.modified,
                     );
+                    if show_diff && snippet.modified {
+                        print_hit_diff(&c.file_path, c.start_line, &c.content, &snippet);
+                    }
                 }
             }
         }
     } else {
-        let results = search_service.search(query, limit, None).await?;
-        
-        if results.is_empty() {
-            println!("No semantic matches found.");
-        } else {
-            println!("Found {} semantic matches:", results.len());
-            for (i, chunk) in results.iter().enumerate() {
-                let file_id = chunk.file.id.to_string();
-                let path = file_id.strip_prefix("file:").unwrap_or(&file_id);
-                ui::print_search_match(
-                    i + 1,
-                    path,
-                    chunk.start_line,
-                    chunk.end_line,
-                    &chunk.content
-                );
-            }
-        }
+        let output = ranked_search_output(ctx, search_service, query, limit, false, show_diff, explain).await?;
+        print_hits(&output, show_diff, page);
+        return Ok(top_hit(&output));
     }
 
-    Ok(())
+    Ok(top)
 }
\ No newline at end of file
@@ -0,0 +1,126 @@
+//! `emry sessions`: the conversations `emry ask` and `emry chat` saved under
+//! `.codeindex/sessions/`, which `emry ask --continue` resumes.
+
+use anyhow::{bail, Result};
+use clap::Subcommand;
+use console::Style;
+use emry_agent::cortex::conversation::{Conversation, ConversationStore};
+use emry_agent::project::{Components, RepoContext};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::ui;
+use super::utils::render_markdown_answer;
+
+/// Characters of a conversation's first question shown by `list`.
+const TITLE_CHARS: usize = 70;
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum SessionsAction {
+    /// List conversations, most recent first
+    List {
+        /// List every conversation instead of the first `core.list_limit`
+        #[arg(long, default_value_t = false)]
+        all: bool,
+        #[arg(long, default_value_t = false)]
+        json: bool,
+    },
+    /// Print a conversation's questions and answers
+    Show {
+        id: String,
+        #[arg(long, default_value_t = false)]
+        json: bool,
+    },
+    /// Delete a conversation and its chat log
+    Delete { id: String },
+}
+
+pub async fn handle_sessions(action: SessionsAction, config_path: Option<&Path>) -> Result<()> {
+    let ctx = RepoContext::open(config_path, Components::NONE).await?;
+    let store = ConversationStore::new(&ctx.root);
+    match action {
+        SessionsAction::List { all, json } => list(&store, all, json),
+        SessionsAction::Show { id, json } => show(&store.load(&id)?, json),
+        SessionsAction::Delete { id } => {
+            super::utils::ensure_writable("sessions delete")?;
+            if !store.delete(&id)? {
+                bail!("no conversation '{}'; `emry sessions list` shows them", id);
+            }
+            ui::print_success(&format!("Deleted conversation {}", id));
+            Ok(())
+        }
+    }
+}
+
+fn list(store: &ConversationStore, all: bool, json: bool) -> Result<()> {
+    let conversations = store.list()?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&conversations)?);
+        return Ok(());
+    }
+    if conversations.is_empty() {
+        println!("No saved conversations. `emry ask` and `emry chat` save them as you go.");
+        return Ok(());
+    }
+
+    ui::print_header("Conversations");
+    let dim = Style::new().dim();
+    let shown = ui::shown(&conversations, all);
+    for conversation in shown {
+        let title: String = conversation.title().chars().take(TITLE_CHARS).collect();
+        let ellipsis = if conversation.title().chars().count() > TITLE_CHARS { "…" } else { "" };
+        println!(
+            "{}  {}  {}{}",
+            ui::palette().location.apply_to(&conversation.id),
+            dim.apply_to(format!(
+                "[{}, {} turn{}, {}]",
+                conversation.command,
+                conversation.turns.len(),
+                if conversation.turns.len() == 1 { "" } else { "s" },
+                ago(conversation.updated)
+            )),
+            title,
+            ellipsis
+        );
+    }
+    print!("{}", ui::more_hint(conversations.len(), shown.len()));
+    println!("{}", dim.apply_to("Resume the latest with `emry ask --continue`, or one with `--session <id>`."));
+    Ok(())
+}
+
+fn show(conversation: &Conversation, json: bool) -> Result<()> {
+    if json {
+        println!("{}", serde_json::to_string_pretty(conversation)?);
+        return Ok(());
+    }
+    ui::print_header(&format!("Conversation {}", conversation.id));
+    let dim = Style::new().dim();
+    if !conversation.scope.is_empty() {
+        println!("{}", dim.apply_to(format!("Scope: {}", conversation.scope.join(", "))));
+    }
+    let mut out = String::new();
+    if let Some(summary) = &conversation.summary {
+        out.push_str(&format!(
+            "{}\n{}\n\n",
+            Style::new().bold().apply_to(format!("Summary of the first {} turns", conversation.summarized)),
+            dim.apply_to(summary)
+        ));
+    }
+    for turn in &conversation.turns {
+        out.push_str(&format!("{} {}\n", ui::palette().prompt.apply_to(">"), Style::new().bold().apply_to(&turn.question)));
+        out.push_str(&format!("{}\n\n", render_markdown_answer(&turn.answer)));
+    }
+    ui::page(&out);
+    Ok(())
+}
+
+/// How long ago `timestamp` (Unix seconds) was, to the largest whole unit.
+fn ago(timestamp: u64) -> String {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    match now.saturating_sub(timestamp) {
+        s if s < 60 => "just now".to_string(),
+        s if s < 3600 => format!("{}m ago", s / 60),
+        s if s < 86_400 => format!("{}h ago", s / 3600),
+        s => format!("{}d ago", s / 86_400),
+    }
+}
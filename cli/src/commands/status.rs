@@ -1,10 +1,12 @@
 use anyhow::Result;
 use emry_agent::project as agent_context;
+use emry_core::scanner::{SkipReason, SkippedFile};
 use std::path::Path;
+use super::index::SKIPPED_FILES;
 use super::ui;
 
-pub async fn handle_status(config_path: Option<&Path>) -> Result<()> {
-    let ctx = agent_context::RepoContext::from_env(config_path).await?;
+pub async fn handle_status(all: bool, config_path: Option<&Path>) -> Result<()> {
+    let ctx = agent_context::RepoContext::open(config_path, agent_context::Components::ALL).await?;
     let root = ctx.root.clone();
     let config = ctx.config.clone();
     let branch = ctx.branch.clone();
@@ -17,6 +19,22 @@ pub async fn handle_status(config_path: Option<&Path>) -> Result<()> {
         &format!("default_mode={:?}, top_k={}", config.search.mode, config.search.top_k),
     );
 
+    let caps = ctx.capabilities();
+    let flag = |on: bool| if on { "enabled" } else { "disabled" };
+    ui::print_key_value("Capabilities", "");
+    ui::print_key_value(" - lexical", flag(caps.lexical));
+    ui::print_key_value(" - graph", flag(caps.graph));
+    ui::print_key_value(
+        " - semantic",
+        &if caps.semantic {
+            format!("enabled ({:?})", config.embedding.backend)
+        } else if config.embedding.backend == emry_config::EmbeddingBackend::None {
+            "disabled (embedding.backend: none)".to_string()
+        } else {
+            "disabled (no embedder reachable)".to_string()
+        },
+    );
+
     let surreal_exists = index_dir.join("surreal.db").exists();
 
     ui::print_key_value("Index directory", &index_dir.display().to_string());
@@ -29,6 +47,7 @@ pub async fn handle_status(config_path: Option<&Path>) -> Result<()> {
         if let Ok(count) = surreal.count_files().await {
              ui::print_key_value("Files tracked", &count.to_string());
         }
+        print_skipped(&index_dir, &root, all);
 
         // Show recent commit log entries for lineage
         if let Ok(entries) = surreal.list_commits(5).await {
@@ -45,3 +64,36 @@ pub async fn handle_status(config_path: Option<&Path>) -> Result<()> {
 
     Ok(())
 }
+
+/// Files the last index run left out, largest first.
+fn print_skipped(index_dir: &Path, root: &Path, all: bool) {
+    let Some(mut skipped) = std::fs::read_to_string(index_dir.join(SKIPPED_FILES))
+        .ok()
+        .and_then(|s| serde_json::from_str::<Vec<SkippedFile>>(&s).ok())
+    else {
+        return;
+    };
+    if skipped.is_empty() {
+        return;
+    }
+    let size = |f: &SkippedFile| match f.reason {
+        SkipReason::TooLarge { size_kb } => size_kb,
+        SkipReason::Binary => 0,
+    };
+    skipped.sort_by_key(|f| std::cmp::Reverse(size(f)));
+    let binary = skipped.iter().filter(|f| f.reason == SkipReason::Binary).count();
+    ui::print_key_value(
+        "Files skipped",
+        &format!("{} ({} over core.max_file_size_kb, {} binary)", skipped.len(), skipped.len() - binary, binary),
+    );
+    let shown = ui::shown(&skipped, all);
+    for file in shown {
+        let path = file.path.strip_prefix(root).unwrap_or(&file.path).display();
+        let reason = match file.reason {
+            SkipReason::TooLarge { size_kb } => format!("{} KB", size_kb),
+            SkipReason::Binary => "binary".to_string(),
+        };
+        ui::print_key_value(&format!(" - {}", path), &reason);
+    }
+    print!("{}", ui::more_hint(skipped.len(), shown.len()));
+}
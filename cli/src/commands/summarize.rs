@@ -0,0 +1,62 @@
+//! `emry summarize`: bottom-up summaries of files, directories and the
+//! repository, stored in the index for `explain`, `architecture` and the
+//! agent (see `emry_agent::workflows::summarize`).
+
+use anyhow::{anyhow, Context, Result};
+use emry_agent::llm::OpenAIProvider;
+use emry_agent::project::{Components, RepoContext};
+use emry_agent::workflows::summarize::{self, SummarizeWorkflow};
+use emry_core::summary::SummaryLevel;
+use indicatif::{ProgressBar, ProgressStyle};
+use std::path::Path;
+
+use super::ui;
+
+pub async fn handle_summarize(force: bool, dry_run: bool, config_path: Option<&Path>) -> Result<()> {
+    use console::Style;
+
+    if !dry_run {
+        super::utils::ensure_writable("summarize")?;
+        super::utils::ensure_no_daemon().await?;
+    }
+    let ctx = RepoContext::open(config_path, Components::STORE).await?;
+    let store = ctx.surreal_store.clone()
+        .ok_or_else(|| anyhow!("SurrealStore not initialized. Run 'emry index' first."))?;
+
+    let plan = summarize::plan(&store, force).await?;
+    let count = |level: SummaryLevel| plan.stale.iter().filter(|t| t.level == level).count();
+    let (files, modules) = (count(SummaryLevel::File), count(SummaryLevel::Module) + count(SummaryLevel::Repo));
+    let shared = plan.stale.iter().filter(|t| t.copy_of.is_some()).count();
+    if dry_run || (plan.stale.is_empty() && plan.obsolete.is_empty()) {
+        println!(
+            "{} file ({} shared with identical files) and {} directory summaries to write, {} up to date, {} to remove.",
+            files, shared, modules, plan.current, plan.obsolete.len()
+        );
+        return Ok(());
+    }
+
+    let api_key = std::env::var("OPENAI_API_KEY").context("OPENAI_API_KEY environment variable not set")?;
+    let model = std::env::var("OPENAI_MODEL").unwrap_or_else(|_| "gpt-4o-mini".to_string());
+    let llm = OpenAIProvider::new(model, api_key, 60)?.with_cache(ctx.llm_cache());
+    let workflow = SummarizeWorkflow::new(store, llm.clone());
+
+    ui::print_header(&format!("Summarizing {} files and {} directories", files - shared, modules));
+    let bar = ProgressBar::new(plan.stale.len() as u64);
+    bar.set_style(ProgressStyle::default_bar().template("[{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} {msg}").unwrap().progress_chars("=>-"));
+    let stats = workflow
+        .run(plan, |target| {
+            bar.set_message(if target.path.is_empty() { "(repository)".to_string() } else { target.path.clone() });
+            bar.inc(1);
+        })
+        .await;
+    bar.finish_and_clear();
+    // On stderr, like `ask`; printed even when a call failed part way.
+    eprintln!("{}", Style::new().dim().apply_to(super::ask::usage_summary(&llm.usage())));
+    let stats = stats.context("summarizing stopped; summaries written so far are kept, run it again to resume")?;
+
+    ui::print_success(&format!(
+        "Wrote {} summaries ({} shared with identical files), kept {}, removed {}.",
+        stats.written, stats.shared, stats.kept, stats.removed
+    ));
+    Ok(())
+}
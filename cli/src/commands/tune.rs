@@ -0,0 +1,242 @@
+//! `emry tune`: search ranking settings against a labeled query set.
+//!
+//! Every point of a grid over `ranking.fusion`, the `weighted` fusion weights,
+//! `ranking.sparse`, `ranking.graph` and `graph.decay` runs the whole query set
+//! and is scored by mean reciprocal rank (see `emry_engine::search::eval`).
+//! When a point beats the current settings, the settings that changed are
+//! written to the config file, keeping its comments and layout. Query
+//! embeddings are computed once and reused across the grid.
+//!
+//! `bm25.k1` and `bm25.b` are not searched: they are fixed when the full-text
+//! index is built, so each trial would have to rebuild it.
+
+use anyhow::{anyhow, Context, Result};
+use console::Style;
+use emry_agent::project::embedder::CachedEmbedder;
+use emry_agent::project::RepoContext;
+use emry_config::loader::formats::toml::parse_with_path;
+use emry_config::loader::{find_config_file, DEFAULT_CONFIG_FILES};
+use emry_config::schema::{set_toml_values, toml_value};
+use emry_config::{Config, FusionStrategy};
+use emry_engine::search::eval::{HitSpan, Label, LabeledQuery, Metrics};
+use indicatif::{ProgressBar, ProgressStyle};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use super::ui;
+
+const FUSIONS: &[FusionStrategy] = &[FusionStrategy::Max, FusionStrategy::Weighted, FusionStrategy::Rrf, FusionStrategy::Zscore];
+/// (`ranking.lexical`, `ranking.vector`), which only `weighted` fusion reads
+const FUSION_WEIGHTS: &[(f32, f32)] = &[(0.7, 0.3), (0.5, 0.5), (0.3, 0.7)];
+const SPARSE_WEIGHTS: &[f32] = &[0.0, 0.3, 0.6];
+const GRAPH_WEIGHTS: &[f32] = &[0.0, 0.1, 0.2, 0.3];
+const GRAPH_DECAYS: &[f32] = &[0.5, 0.8];
+
+/// Settings reported next to the baseline.
+const SHOWN_TRIALS: usize = 5;
+
+/// One point of the grid.
+#[derive(Debug, Clone, PartialEq)]
+struct Trial {
+    fusion: FusionStrategy,
+    lexical: f32,
+    vector: f32,
+    sparse: f32,
+    graph: f32,
+    decay: f32,
+}
+
+impl Trial {
+    fn of(config: &Config) -> Self {
+        Trial {
+            fusion: config.ranking.fusion,
+            lexical: config.ranking.lexical,
+            vector: config.ranking.vector,
+            sparse: config.ranking.sparse,
+            graph: config.ranking.graph,
+            decay: config.graph.decay,
+        }
+    }
+
+    fn apply(&self, config: &mut Config) {
+        config.ranking.fusion = self.fusion;
+        config.ranking.lexical = self.lexical;
+        config.ranking.vector = self.vector;
+        config.ranking.sparse = self.sparse;
+        config.ranking.graph = self.graph;
+        config.graph.decay = self.decay;
+    }
+
+    /// Dotted keys and TOML values of the settings that differ from `base`.
+    fn changes(&self, base: &Trial) -> Vec<(String, String)> {
+        let render = |value: serde_json::Value| toml_value(&value).unwrap_or_default();
+        let mut changes = Vec::new();
+        if self.fusion != base.fusion {
+            changes.push(("ranking.fusion".to_string(), render(serde_json::json!(fusion_name(self.fusion)))));
+        }
+        for (key, value, was) in [
+            ("ranking.lexical", self.lexical, base.lexical),
+            ("ranking.vector", self.vector, base.vector),
+            ("ranking.sparse", self.sparse, base.sparse),
+            ("ranking.graph", self.graph, base.graph),
+            ("graph.decay", self.decay, base.decay),
+        ] {
+            if (value - was).abs() > f32::EPSILON {
+                changes.push((key.to_string(), render(serde_json::json!(value))));
+            }
+        }
+        changes
+    }
+
+    fn describe(&self) -> String {
+        let mut parts = vec![format!("fusion={}", fusion_name(self.fusion))];
+        if self.fusion == FusionStrategy::Weighted {
+            parts.push(format!("lexical={} vector={}", self.lexical, self.vector));
+        }
+        parts.push(format!("sparse={}", self.sparse));
+        parts.push(format!("graph={}", self.graph));
+        if self.graph > 0.0 {
+            parts.push(format!("decay={}", self.decay));
+        }
+        parts.join(" ")
+    }
+}
+
+fn fusion_name(fusion: FusionStrategy) -> &'static str {
+    match fusion {
+        FusionStrategy::Max => "max",
+        FusionStrategy::Weighted => "weighted",
+        FusionStrategy::Rrf => "rrf",
+        FusionStrategy::Zscore => "zscore",
+    }
+}
+
+/// Every grid point, varying only the settings that take effect in it.
+fn grid(base: &Trial) -> Vec<Trial> {
+    let mut trials = Vec::new();
+    for &fusion in FUSIONS {
+        let weights: Vec<(f32, f32)> = if fusion == FusionStrategy::Weighted {
+            FUSION_WEIGHTS.to_vec()
+        } else {
+            vec![(base.lexical, base.vector)]
+        };
+        for &(lexical, vector) in &weights {
+            for &sparse in SPARSE_WEIGHTS {
+                for &graph in GRAPH_WEIGHTS {
+                    let decays = if graph > 0.0 { GRAPH_DECAYS } else { std::slice::from_ref(&base.decay) };
+                    for &decay in decays {
+                        trials.push(Trial { fusion, lexical, vector, sparse, graph, decay });
+                    }
+                }
+            }
+        }
+    }
+    trials
+}
+
+pub async fn handle_tune(queries: PathBuf, top: usize, dry_run: bool, config_path: Option<&Path>) -> Result<()> {
+    if !dry_run {
+        super::utils::ensure_writable("tune")?;
+    }
+    let text = std::fs::read_to_string(&queries).with_context(|| format!("failed to read {}", queries.display()))?;
+    let labeled: Vec<LabeledQuery> =
+        serde_json::from_str(&text).with_context(|| format!("{} is not a JSON list of {{query, relevant}}", queries.display()))?;
+    if labeled.is_empty() {
+        return Err(anyhow!("{} contains no queries", queries.display()));
+    }
+    let labels: Vec<Vec<Label>> = labeled.iter().map(|q| q.relevant.iter().map(|l| Label::parse(l)).collect()).collect();
+
+    let mut ctx = RepoContext::from_env(config_path).await?;
+    let store = ctx
+        .surreal_store
+        .clone()
+        .ok_or_else(|| anyhow!("SurrealStore not initialized. Run 'emry index' first."))?;
+    for label in labels.iter().flatten() {
+        if !ctx.root.join(&label.path).exists() {
+            println!("{}", ui::palette().warning.apply_to(format!("Labeled file {} does not exist", label.path)));
+        }
+    }
+    ctx.embedder = ctx.embedder.take().map(|e| Arc::new(CachedEmbedder::new(e)) as _);
+
+    let base = Trial::of(&ctx.config);
+    let mut trials = grid(&base);
+    trials.retain(|t| *t != base);
+    trials.insert(0, base.clone());
+
+    ui::print_header(&format!("Tuning ranking on {} queries", labeled.len()));
+    let bar = ProgressBar::new(trials.len() as u64);
+    bar.set_style(ProgressStyle::default_bar().template("[{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} settings").unwrap().progress_chars("=>-"));
+    let mut results: Vec<(Trial, Metrics)> = Vec::with_capacity(trials.len());
+    for trial in trials {
+        let mut config = ctx.config.clone();
+        trial.apply(&mut config);
+        let search = ctx.reconfigured(config).search_service(store.clone());
+        let mut per_query = Vec::with_capacity(labeled.len());
+        for (query, labels) in labeled.iter().zip(&labels) {
+            let hits: Vec<HitSpan> = search
+                .search_scored(&query.query, top, None)
+                .await?
+                .iter()
+                .map(|s| HitSpan {
+                    path: s.chunk.file_path.strip_prefix(&ctx.root).unwrap_or(&s.chunk.file_path).to_string_lossy().replace('\\', "/"),
+                    start_line: s.chunk.start_line,
+                    end_line: s.chunk.end_line,
+                })
+                .collect();
+            per_query.push(Metrics::of_query(labels, &hits));
+        }
+        results.push((trial, Metrics::mean(&per_query)));
+        bar.inc(1);
+    }
+    bar.finish_and_clear();
+
+    let baseline = results[0].1;
+    // Stable, so the baseline stays first among equals and is only replaced by a real gain.
+    results.sort_by(|a, b| {
+        b.1.mrr
+            .partial_cmp(&a.1.mrr)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then(b.1.recall.partial_cmp(&a.1.recall).unwrap_or(std::cmp::Ordering::Equal))
+    });
+    let dim = Style::new().dim();
+    println!("{}", dim.apply_to(format!("{:>6}  {:>9}  settings", "MRR", format!("recall@{}", top))));
+    println!("{:>6.3}  {:>9.3}  {} {}", baseline.mrr, baseline.recall, base.describe(), dim.apply_to("(current)"));
+    for (trial, metrics) in results.iter().filter(|(t, _)| *t != base).take(SHOWN_TRIALS) {
+        println!("{:>6.3}  {:>9.3}  {}", metrics.mrr, metrics.recall, trial.describe());
+    }
+
+    let (best, metrics) = &results[0];
+    if !metrics.beats(&baseline) {
+        println!("\nNo setting beats the current ranking on this query set; nothing to change.");
+        return Ok(());
+    }
+    let changes = best.changes(&base);
+    println!();
+    for (key, value) in &changes {
+        println!("  {} = {}", key, value);
+    }
+    if dry_run {
+        println!("\nDry run: nothing was written.");
+        return Ok(());
+    }
+
+    let path = config_path
+        .map(Path::to_path_buf)
+        .or_else(find_config_file)
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_CONFIG_FILES[0]));
+    if path.extension().and_then(|e| e.to_str()) != Some("toml") {
+        return Err(anyhow!("{} is not TOML; set the values above in it yourself", path.display()));
+    }
+    let current = if path.exists() { std::fs::read_to_string(&path)? } else { String::new() };
+    let updated = set_toml_values(&current, &changes);
+    parse_with_path(&updated, path.to_str()).with_context(|| format!("updating {} would leave it invalid", path.display()))?;
+    std::fs::write(&path, updated)?;
+    ui::print_success(&format!(
+        "Wrote {} setting(s) to {} (MRR {:.3} -> {:.3})",
+        changes.len(),
+        path.display(),
+        baseline.mrr,
+        metrics.mrr
+    ));
+    Ok(())
+}
@@ -1,16 +1,145 @@
 use console::Style;
+use emry_config::{CoreConfig, UiConfig, UiTheme};
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+
+/// Styles for each output role, from `ui.theme` with `ui.colors` overrides.
+pub struct Palette {
+    pub heading: Style,
+    pub location: Style,
+    pub prompt: Style,
+    pub success: Style,
+    pub warning: Style,
+    pub error: Style,
+}
+
+static PALETTE: OnceLock<Palette> = OnceLock::new();
+
+impl Palette {
+    fn from_config(config: &UiConfig) -> Self {
+        let base = match config.theme {
+            UiTheme::Dark => Palette {
+                heading: Style::new().bold().cyan(),
+                location: Style::new().bold().blue(),
+                prompt: Style::new().bold().cyan(),
+                success: Style::new().green().bold(),
+                warning: Style::new().yellow(),
+                error: Style::new().red().bold(),
+            },
+            UiTheme::Light => Palette {
+                heading: Style::new().bold().blue(),
+                location: Style::new().bold().magenta(),
+                prompt: Style::new().bold().blue(),
+                success: Style::new().green().bold(),
+                warning: Style::new().magenta(),
+                error: Style::new().red().bold(),
+            },
+            UiTheme::Mono => Palette {
+                heading: Style::new().bold(),
+                location: Style::new().bold(),
+                prompt: Style::new().bold(),
+                success: Style::new().bold(),
+                warning: Style::new().italic(),
+                error: Style::new().bold(),
+            },
+        };
+        let pick = |style: Style, custom: &Option<String>| custom.as_deref().map_or(style, Style::from_dotted_str);
+        let colors = &config.colors;
+        Palette {
+            heading: pick(base.heading, &colors.heading),
+            location: pick(base.location, &colors.location),
+            prompt: pick(base.prompt, &colors.prompt),
+            success: pick(base.success, &colors.success),
+            warning: pick(base.warning, &colors.warning),
+            error: pick(base.error, &colors.error),
+        }
+    }
+}
+
+/// Set the palette from config; only the first call has an effect.
+pub fn init_theme(config: &UiConfig) {
+    let _ = PALETTE.set(Palette::from_config(config));
+}
+
+/// The configured palette, or the default dark one.
+pub fn palette() -> &'static Palette {
+    PALETTE.get_or_init(|| Palette::from_config(&UiConfig::default()))
+}
+
+/// How long output is paged and lists are cut, from `core.pager` and `core.list_limit`.
+struct Output {
+    /// `None` when paging is off.
+    pager: Option<String>,
+    list_limit: usize,
+}
+
+static OUTPUT: OnceLock<Output> = OnceLock::new();
+
+impl Output {
+    fn from_config(config: &CoreConfig, no_pager: bool) -> Self {
+        // An empty `core.pager` or `$PAGER` turns paging off rather than falling back.
+        let pager = config
+            .pager
+            .clone()
+            .or_else(|| std::env::var("PAGER").ok())
+            .unwrap_or_else(|| "less -R".to_string());
+        let pager = (!no_pager && !pager.trim().is_empty()).then(|| pager.trim().to_string());
+        Output { pager, list_limit: config.list_limit }
+    }
+}
+
+/// Set the pager and list limit from config, with paging off for
+/// `--no-pager`; only the first call has an effect.
+pub fn init_output(config: &CoreConfig, no_pager: bool) {
+    let _ = OUTPUT.set(Output::from_config(config, no_pager));
+}
+
+fn output() -> &'static Output {
+    OUTPUT.get_or_init(|| Output::from_config(&CoreConfig::default(), false))
+}
+
+/// The part of `items` to list: the first `core.list_limit`, or all of them with `all`.
+pub fn shown<T>(items: &[T], all: bool) -> &[T] {
+    let limit = output().list_limit;
+    if all || limit == 0 {
+        items
+    } else {
+        &items[..items.len().min(limit)]
+    }
+}
+
+/// The line closing a list cut by `shown`, or an empty string when nothing was cut.
+pub fn more_hint(total: usize, shown: usize) -> String {
+    if total <= shown {
+        return String::new();
+    }
+    format!("{}\n", Style::new().dim().apply_to(format!("  ... {} more, use --all", total - shown)))
+}
 
 pub fn print_header(title: &str) {
-    println!("\n{}", Style::new().bold().cyan().apply_to(title));
+    println!("\n{}", palette().heading.apply_to(title));
     println!("{}", Style::new().dim().apply_to("─".repeat(title.len())));
 }
 
 pub fn print_success(msg: &str) {
-    println!("{} {}", Style::new().green().bold().apply_to("SUCCESS:"), msg);
+    println!("{} {}", palette().success.apply_to("SUCCESS:"), msg);
 }
 
 pub fn print_error(msg: &str) {
-    eprintln!("{} {}", Style::new().red().bold().apply_to("ERROR:"), msg);
+    eprintln!("{} {}", palette().error.apply_to("ERROR:"), msg);
+}
+
+/// Machine-readable error envelope: `{"error": {"code", "message", "exit_code"}}`.
+pub fn print_error_json(kind: emry_core::error::ErrorKind, message: &str) {
+    let envelope = serde_json::json!({
+        "error": {
+            "code": kind.code(),
+            "message": message,
+            "exit_code": kind.exit_code(),
+        }
+    });
+    eprintln!("{}", envelope);
 }
 
 pub fn print_panel(title: &str, content: &str, border_color: Style, content_style: Option<Style>) {
@@ -47,17 +176,71 @@ pub fn print_panel(title: &str, content: &str, border_color: Style, content_styl
     println!("{}", border_color.apply_to(format!("└{}┘", "─".repeat(width - 2))));
 }
 
-pub fn print_search_match(i: usize, file: &str, start_line: usize, end_line: usize, content: &str) {
+pub fn print_search_match(i: usize, file: &str, start_line: usize, end_line: usize, content: &str, tags: &[String], modified: bool) {
     let header = if start_line == end_line {
         format!("#{} {}:{}", i, file, start_line)
     } else {
         format!("#{} {}:{}-{}", i, file, start_line, end_line)
     };
-    println!("{}", Style::new().bold().blue().apply_to(header));
+    let mut line = palette().location.apply_to(header).to_string();
+    if !tags.is_empty() {
+        line.push_str(&format!(" {}", Style::new().dim().apply_to(format!("[{}]", tags.join(", ")))));
+    }
+    if modified {
+        line.push_str(&format!(" {}", palette().warning.apply_to("(modified since index)")));
+    }
+    println!("{}", line);
     println!("{}", Style::new().dim().apply_to(content.trim()));
     println!();
 }
 
+/// Print a unified diff with added/removed lines colored.
+pub fn print_diff(diff: &str) {
+    for line in diff.lines() {
+        let style = if line.starts_with("+++") || line.starts_with("---") {
+            Style::new().bold()
+        } else if line.starts_with('+') {
+            Style::new().green()
+        } else if line.starts_with('-') {
+            Style::new().red()
+        } else if line.starts_with("@@") {
+            Style::new().cyan()
+        } else {
+            Style::new().dim()
+        };
+        println!("{}", style.apply_to(line));
+    }
+    println!();
+}
+
+/// Print `text`, through the pager (`core.pager`, `$PAGER`, else `less -R`)
+/// when it is taller than the terminal, so long output can be scrolled, paged
+/// and searched.
+pub fn page(text: &str) {
+    let fits = console::Term::stdout()
+        .size_checked()
+        .map_or(true, |(rows, _)| text.lines().count() < rows as usize);
+    let Some(pager) = output().pager.as_deref().filter(|_| !fits) else {
+        print!("{}", text);
+        return;
+    };
+    let mut words = pager.split_whitespace();
+    let spawned = Command::new(words.next().unwrap_or("less"))
+        .args(words)
+        .stdin(Stdio::piped())
+        .spawn();
+    match spawned {
+        Ok(mut child) => {
+            if let Some(mut stdin) = child.stdin.take() {
+                // The pager may quit before reading everything.
+                let _ = stdin.write_all(text.as_bytes());
+            }
+            let _ = child.wait();
+        }
+        Err(_) => print!("{}", text),
+    }
+}
+
 pub fn print_key_value(key: &str, value: &str) {
     println!(
         "{}: {}",
@@ -2,8 +2,50 @@
 use globset::{Glob, GlobSet, GlobSetBuilder};
 use std::path::Path;
 use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
 use termimad::{FmtText, MadSkin};
 
+static READ_ONLY: AtomicBool = AtomicBool::new(false);
+
+/// Run this process read-only (`--read-only` or `core.read_only`).
+pub fn set_read_only(read_only: bool) {
+    READ_ONLY.store(read_only, Ordering::Relaxed);
+}
+
+/// Whether this process is read-only; the query, error, crash and session
+/// logs under `.codeindex` are not written then.
+pub fn read_only() -> bool {
+    READ_ONLY.load(Ordering::Relaxed)
+}
+
+/// Refuse a command that writes to the index or the repository in read-only mode.
+pub fn ensure_writable(command: &str) -> anyhow::Result<()> {
+    if read_only() {
+        anyhow::bail!("`emry {}` writes to the index or the repository and is disabled in read-only mode", command);
+    }
+    Ok(())
+}
+
+/// Refuse a command that writes to the index while the daemon holds it open.
+pub async fn ensure_no_daemon() -> anyhow::Result<()> {
+    if super::daemon::DaemonClient::connect().await.is_some() {
+        return Err(emry_core::error::EmryError::DaemonRunning.into());
+    }
+    Ok(())
+}
+
+/// Refuse to add vectors to an index built with another `embedding.quantization`.
+pub fn ensure_quantization(store: &emry_store::SurrealStore, config: &emry_config::EmbeddingConfig) -> anyhow::Result<()> {
+    if store.has_vector_index() && store.quantization() != config.quantization {
+        anyhow::bail!(
+            "the index was built with embedding.quantization: {}; run `emry index --full` to rebuild it with {}",
+            store.quantization().as_str(),
+            config.quantization.as_str()
+        );
+    }
+    Ok(())
+}
+
 pub fn current_branch() -> String {
     if let Ok(out) = Command::new("git")
         .arg("rev-parse")
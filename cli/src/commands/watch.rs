@@ -0,0 +1,209 @@
+use anyhow::{Context, Result};
+use emry_agent::project::embedder::{get_embedding_dimension, select_embedder};
+use emry_config::Config;
+use emry_core::import_paths::ImportResolver;
+use emry_core::scanner::{scan_repo, PathFilter};
+use emry_core::traits::Embedder;
+use emry_engine::ingest::pipeline::{analyze_source_files, compute_hash, generate_embeddings, FileInput};
+use emry_engine::ingest::service::{IngestionContext, IngestionService};
+use emry_store::SurrealStore;
+use notify::{RecursiveMode, Watcher};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+
+use super::ui;
+use super::utils::current_branch;
+
+/// Longest a burst of changes is collected before it is re-indexed anyway,
+/// so a tree that never goes quiet for the debounce still gets indexed.
+const MAX_BURST: Duration = Duration::from_secs(5);
+
+pub async fn handle_watch(debounce_ms: u64, config_path: Option<&Path>) -> Result<()> {
+    super::utils::ensure_writable("watch")?;
+    let root = std::env::current_dir()?;
+    let index_dir = root.join(".codeindex").join("branches").join(current_branch());
+    if !index_dir.exists() {
+        return Err(emry_core::error::EmryError::IndexNotFound { path: index_dir }.into());
+    }
+
+    super::utils::ensure_no_daemon().await?;
+
+    let config = if let Some(p) = config_path {
+        Config::from_file(p)?
+    } else {
+        Config::load()?
+    };
+
+    let embedder = select_embedder(&config.embedding).await.ok();
+    let vector_dim = get_embedding_dimension(&config.embedding);
+    let store = Arc::new(SurrealStore::open_quantized(&index_dir.join("surreal.db"), vector_dim, config.embedding.quantization).await?);
+    super::utils::ensure_quantization(&store, &config.embedding)?;
+    store.warm_symbol_filter().await?;
+    let ingestion = IngestionService::new(store.clone(), embedder.clone()).with_import_resolver(ImportResolver::load(&root));
+
+    let (tx, mut rx) = mpsc::unbounded_channel::<PathBuf>();
+    let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+        if let Ok(event) = res {
+            for path in event.paths {
+                let _ = tx.send(path);
+            }
+        }
+    })
+    .context("failed to start filesystem watcher")?;
+    watcher
+        .watch(&root, RecursiveMode::Recursive)
+        .context("failed to watch repository root")?;
+
+    ui::print_header(&format!("Watching {}", root.display()));
+    println!("Press Ctrl-C to stop.");
+
+    // Writes the index can't pick up, such as build output or the store's own
+    // files under `.codeindex`, neither start a burst nor extend one.
+    let filter = PathFilter::new(&root, &config.core);
+    let debounce = Duration::from_millis(debounce_ms);
+
+    loop {
+        let first = tokio::select! {
+            p = rx.recv() => match p {
+                Some(p) => p,
+                None => break,
+            },
+            _ = tokio::signal::ctrl_c() => break,
+        };
+        if filter.excludes(&first) {
+            continue;
+        }
+
+        // Collect the burst until the tree is quiet for `debounce`, or for at most `MAX_BURST`.
+        let mut changed: HashSet<PathBuf> = HashSet::new();
+        changed.insert(first);
+        let deadline = Instant::now() + MAX_BURST.max(debounce);
+        let mut quiet_at = Instant::now() + debounce;
+        while let Ok(Some(p)) = tokio::time::timeout_at(quiet_at.min(deadline), rx.recv()).await {
+            if !filter.excludes(&p) {
+                changed.insert(p);
+                quiet_at = Instant::now() + debounce;
+            }
+        }
+
+        match reindex_paths("Watch", &root, &config, &store, &ingestion, embedder.clone(), changed).await {
+            Ok((updated, removed)) if updated + removed > 0 => {
+                ui::print_success(&format!("Re-indexed {} file(s), removed {}", updated, removed));
+            }
+            Ok(_) => {}
+            Err(e) => ui::print_error(&format!("Re-index failed: {}", e)),
+        }
+    }
+
+    println!("Stopped watching.");
+    Ok(())
+}
+
+/// Re-index the changed paths that `scan_repo` would pick up and drop deleted ones.
+/// The run is logged with `source` (e.g. "Watch") in its commit note.
+pub(crate) async fn reindex_paths(
+    source: &str,
+    root: &Path,
+    config: &Config,
+    store: &Arc<SurrealStore>,
+    ingestion: &IngestionService,
+    embedder: Option<Arc<dyn Embedder + Send + Sync>>,
+    changed: HashSet<PathBuf>,
+) -> Result<(usize, usize)> {
+    // Rescanning applies exactly the same include/exclude and ignore rules as `emry index`.
+    let indexable: HashMap<PathBuf, _> = scan_repo(root, &config.core, &config.documents)
+        .files
+        .into_iter()
+        .map(|f| (f.path, f.language))
+        .collect();
+
+    let mut work_items = Vec::new();
+    let mut removed = 0;
+
+    for path in changed {
+        let key = path.to_string_lossy().to_string();
+        let Some(language) = indexable.get(&path) else {
+            if !path.exists() && store.get_file(&key).await?.is_some() {
+                store.delete_file(&key).await?;
+                removed += 1;
+            }
+            continue;
+        };
+
+        let Ok(content) = tokio::fs::read_to_string(&path).await else {
+            continue;
+        };
+        let hash = compute_hash(&content);
+        // Ingesting the new version replaces the stored one, which stays until then.
+        if store.get_file(&key).await?.is_some_and(|prev| prev.hash == hash) {
+            continue;
+        }
+
+        let last_modified = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        work_items.push(FileInput {
+            path: path.clone(),
+            language: language.clone(),
+            file_id: 0,
+            file_node_id: format!("file:{}", key),
+            hash,
+            content,
+            last_modified,
+        });
+    }
+
+    let updated = work_items.len();
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    let commit_id = format!("commit:{}", now.as_millis());
+    let mut changed_symbols = 0;
+    if updated > 0 {
+        let mut prepared = analyze_source_files(work_items, config, 8).await;
+        if let Some(emb) = embedder {
+            generate_embeddings(&mut prepared, emb, config).await;
+        }
+
+        let contexts: Vec<IngestionContext> = prepared.into_iter().map(IngestionContext::new).collect();
+        for ctx in &contexts {
+            if let Err(e) = ingestion.ingest_nodes(ctx).await {
+                eprintln!("Failed to ingest nodes for {}: {}", ctx.file.path.display(), e);
+            }
+        }
+        let versions = contexts.iter().flat_map(|c| c.symbol_versions(&commit_id, now.as_secs())).collect();
+        match store.record_symbol_versions(versions).await {
+            Ok(n) => changed_symbols = n,
+            Err(e) => eprintln!("Failed to record symbol history: {}", e),
+        }
+        for ctx in &contexts {
+            if let Err(e) = ingestion.ingest_edges(ctx).await {
+                eprintln!("Failed to ingest edges for {}: {}", ctx.file.path.display(), e);
+            }
+        }
+
+        let changed: Vec<String> = contexts.iter().flat_map(|c| c.id_map.values().cloned()).collect();
+        if let Err(e) = store.refresh_neighborhoods(&changed, config.graph.max_depth).await {
+            eprintln!("Failed to update graph neighbourhoods: {}", e);
+        }
+        if let Err(e) = super::owners::refresh_ownership(store, root, &config.owners).await {
+            eprintln!("Failed to update file owners: {}", e);
+        }
+    }
+
+    if updated + removed > 0 {
+        store
+            .add_commit(
+                commit_id,
+                now.as_secs(),
+                format!("{}: updated={}, removed={}", source, updated, removed),
+                changed_symbols,
+            )
+            .await?;
+    }
+
+    Ok((updated, removed))
+}
@@ -2,25 +2,68 @@ mod commands;
 
 use anyhow::Result;
 use clap::Parser;
-use commands::{Cli, Commands};
+use commands::{Cli, Commands, OutputFormat};
+use emry_config::Config;
+use emry_core::error::ErrorKind;
+use emry_engine::search::matching::MatchOptions;
 
-#[tokio::main]
-async fn main() -> Result<()> {
+/// `emry check --fail-on-impact` exceeded its limit; see the exit code table in the README.
+const IMPACT_GATE_EXIT_CODE: i32 = 11;
+
+fn main() -> Result<()> {
     let cli = Cli::parse();
 
+    // Every config load, including the daemon's and those of the processes
+    // it spawns, reads the profile from here. Set before the runtime starts
+    // its worker threads, as changing the environment is only sound while
+    // the process has a single thread.
+    if let Some(profile) = &cli.profile {
+        std::env::set_var(emry_config::PROFILE_ENV, profile);
+    }
+    // Likewise for read-only mode, which every store opened later honours.
+    if cli.read_only {
+        std::env::set_var("EMRY_CORE_READ_ONLY", "true");
+    }
+
+    tokio::runtime::Builder::new_multi_thread().enable_all().build()?.block_on(run(cli))
+}
+
+async fn run(cli: Cli) -> Result<()> {
     tracing_subscriber::fmt()
         .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
         .with_writer(std::io::stderr)
         .init();
 
+    commands::crash::install(!matches!(cli.command, Commands::Watch { .. } | Commands::Daemon { .. }));
+
+    // Commands load the config again and report its errors; here it only sets
+    // colors, the pager and how long lists get.
+    let config = match cli.config.as_deref() {
+        Some(path) => Config::from_file(path),
+        None => Config::load(),
+    };
+    let core = match config {
+        Ok(config) => {
+            commands::ui::init_theme(&config.ui);
+            config.core
+        }
+        Err(_) => Default::default(),
+    };
+    commands::ui::init_output(&core, cli.no_pager);
+    commands::utils::set_read_only(core.read_only || cli.read_only);
+
+    let format = cli.format;
     let exit_code = match cli.command {
-        Commands::Index { full } => {
-            match commands::handle_index(full, cli.config.as_deref()).await {
+        Commands::Index { action: Some(action), .. } => {
+            match commands::handle_index_action(action, cli.config.as_deref()).await {
+                Ok(_) => 0,
+                Err(e) => report_error(format, "Index failed", &e),
+            }
+        }
+        Commands::Index { action: None, full, batch_size, deterministic } => {
+            match commands::handle_index(full, batch_size, deterministic, cli.config.as_deref()).await {
                 Ok(_) => 0,
-                Err(e) => {
-                    commands::ui::print_error(&format!("Index failed: {}", e));
-                    1
-                }
+                Err(e) => report_error(format, "Index failed", &e),
             }
         }
         Commands::Search {
@@ -29,11 +72,23 @@ async fn main() -> Result<()> {
             mode,
             lang,
             path,
+            owner,
             symbol,
 
             regex,
+            docs,
             no_ignore,
+            case_sensitive,
+            word,
+            all,
             smart,
+            json,
+            interactive,
+            show_diff,
+            explain_ranking,
+            autocorrect,
+            remote,
+            local,
         } => match commands::handle_search(
             query,
             cli.config.as_deref(),
@@ -41,107 +96,217 @@ async fn main() -> Result<()> {
             mode,
             lang,
             path,
+            owner,
             symbol,
 
             regex,
+            docs,
             no_ignore,
+            MatchOptions { case_sensitive, whole_word: word },
+            all,
             smart,
+            json,
+            interactive,
+            show_diff,
+            explain_ranking,
+            autocorrect,
+            remote,
+            local,
         )
         .await
         {
             Ok(_) => 0,
-            Err(e) => {
-                commands::ui::print_error(&format!("Search failed: {}", e));
-                1
-            }
+            Err(e) => report_error(format, "Search failed", &e),
         },
-        Commands::Ask { query, verbose } => {
-            match commands::handle_ask(query, verbose, cli.config.as_deref()).await {
+        Commands::Tune { queries, top, dry_run } => {
+            match commands::handle_tune(queries, top, dry_run, cli.config.as_deref()).await {
+                Ok(_) => 0,
+                Err(e) => report_error(format, "Tune failed", &e),
+            }
+        }
+        Commands::Compare { query, other, against, top, json } => {
+            match commands::handle_compare(query, other, against, top, json, cli.config.as_deref()).await {
                 Ok(_) => 0,
-                Err(e) => {
-                    commands::ui::print_error(&format!("Ask failed: {}", e));
-                    1
-                }
+                Err(e) => report_error(format, "Compare failed", &e),
             }
         }
+        Commands::Ask { query, scope, verbose, resume, session, edit, rag } => {
+            match commands::handle_ask(query, scope, verbose, resume, session, edit, rag, cli.config.as_deref()).await {
+                Ok(_) => 0,
+                Err(e) => report_error(format, "Ask failed", &e),
+            }
+        }
+        Commands::Sessions { action } => match commands::handle_sessions(action, cli.config.as_deref()).await {
+            Ok(_) => 0,
+            Err(e) => report_error(format, "Sessions failed", &e),
+        },
+        Commands::Chat { scope } => match commands::handle_chat(scope, cli.config.as_deref()).await {
+            Ok(_) => 0,
+            Err(e) => report_error(format, "Chat failed", &e),
+        },
         Commands::Graph(args) => match commands::handle_graph(args, cli.config.as_deref()).await {
             Ok(_) => 0,
-            Err(e) => {
-                commands::ui::print_error(&format!("Graph command failed: {}", e));
-                1
-            }
+            Err(e) => report_error(format, "Graph command failed", &e),
         },
-        Commands::Status => match commands::handle_status(cli.config.as_deref()).await {
+        Commands::Callers(args) => match commands::handle_call_tree(args, true, cli.config.as_deref()).await {
             Ok(_) => 0,
-            Err(e) => {
-                commands::ui::print_error(&format!("Status failed: {}", e));
-                1
-            }
+            Err(e) => report_error(format, "Callers failed", &e),
+        },
+        Commands::Callees(args) => match commands::handle_call_tree(args, false, cli.config.as_deref()).await {
+            Ok(_) => 0,
+            Err(e) => report_error(format, "Callees failed", &e),
+        },
+        Commands::BenchIndex(args) => match commands::handle_bench_index(args, cli.config.as_deref()).await {
+            Ok(_) => 0,
+            Err(e) => report_error(format, "Benchmark failed", &e),
+        },
+        Commands::Complete { target } => match commands::handle_complete(target, cli.config.as_deref()).await {
+            Ok(_) => 0,
+            Err(e) => report_error(format, "Completion failed", &e),
+        },
+        Commands::Status { all } => match commands::handle_status(all, cli.config.as_deref()).await {
+            Ok(_) => 0,
+            Err(e) => report_error(format, "Status failed", &e),
         },
         Commands::Inspect(args) => match commands::handle_inspect(args, cli.config.as_deref()).await {
             Ok(_) => 0,
-            Err(e) => {
-                commands::ui::print_error(&format!("Inspect failed: {}", e));
-                1
-            }
+            Err(e) => report_error(format, "Inspect failed", &e),
         },
         Commands::Cat { files } => match commands::handle_cat(files, cli.config.as_deref()).await {
             Ok(_) => 0,
-            Err(e) => {
-                commands::ui::print_error(&format!("Cat failed: {}", e));
-                1
-            }
+            Err(e) => report_error(format, "Cat failed", &e),
         },
-        Commands::Explore { path, depth } => match commands::handle_explore(path, depth, cli.config.as_deref()).await {
+        Commands::Explore { path, depth, all } => match commands::handle_explore(path, depth, all, cli.config.as_deref()).await {
             Ok(_) => 0,
-            Err(e) => {
-                commands::ui::print_error(&format!("Explore failed: {}", e));
-                1
-            }
+            Err(e) => report_error(format, "Explore failed", &e),
         },
-        Commands::Architecture { mode, verbose } => match commands::handle_architecture(mode, verbose, cli.config.as_deref()).await {
+        Commands::Architecture { mode, verbose, mermaid } => match commands::handle_architecture(mode, verbose, mermaid, cli.config.as_deref()).await {
             Ok(_) => 0,
-            Err(e) => {
-                commands::ui::print_error(&format!("Architecture analysis failed: {}", e));
-                1
-            }
+            Err(e) => report_error(format, "Architecture analysis failed", &e),
         },
-        Commands::Impact { file, start, end, verbose } => match commands::handle_impact(file, start, end, verbose, cli.config.as_deref()).await {
-            Ok(_) => 0,
-            Err(e) => {
-                commands::ui::print_error(&format!("Impact analysis failed: {}", e));
-                1
+        Commands::Impact { file, start, end, diff, verbose } => {
+            let target = match (diff, file, start, end) {
+                (Some(rev), ..) => commands::ImpactTarget::Diff { rev },
+                (None, Some(file), Some(start), Some(end)) => commands::ImpactTarget::Range { file, start, end },
+                // clap requires the range whenever --diff is absent.
+                _ => unreachable!("impact requires <FILE> <START> <END> or --diff"),
+            };
+            match commands::handle_impact(target, verbose, cli.config.as_deref()).await {
+                Ok(_) => 0,
+                Err(e) => report_error(format, "Impact analysis failed", &e),
             }
+        }
+        Commands::Check { against, fail_on_impact } => match commands::handle_check(against, fail_on_impact, cli.config.as_deref()).await {
+            Ok(true) => 0,
+            Ok(false) => IMPACT_GATE_EXIT_CODE,
+            Err(e) => report_error(format, "Check failed", &e),
         },
         Commands::Focus { topic, verbose } => match commands::handle_focus(topic, verbose, cli.config.as_deref()).await {
             Ok(_) => 0,
-            Err(e) => {
-                commands::ui::print_error(&format!("Focus failed: {}", e));
-                1
+            Err(e) => report_error(format, "Focus failed", &e),
+        },
+        Commands::Map { depth, verbose, tokens, focus, mermaid } => {
+            match commands::handle_codebase_map(depth, verbose, mermaid, tokens, focus, cli.config.as_deref()).await {
+                Ok(_) => 0,
+                Err(e) => report_error(format, "Map generation failed", &e),
             }
+        }
+        Commands::Debug => match commands::handle_debug(cli.config.as_deref()).await {
+            Ok(_) => 0,
+            Err(e) => report_error(format, "Debug failed", &e),
         },
-        Commands::Map { depth, verbose } => match commands::handle_codebase_map(depth, verbose, cli.config.as_deref()).await {
+        Commands::Deadcode { path, json } => match commands::handle_deadcode(path, json, cli.config.as_deref()).await {
             Ok(_) => 0,
-            Err(e) => {
-                commands::ui::print_error(&format!("Map generation failed: {}", e));
-                1
+            Err(e) => report_error(format, "Deadcode failed", &e),
+        },
+        Commands::Clones { threshold, min_lines, path, json } => {
+            match commands::handle_clones(threshold, min_lines, path, json, cli.config.as_deref()).await {
+                Ok(_) => 0,
+                Err(e) => report_error(format, "Clones failed", &e),
+            }
+        }
+        Commands::Pack { query, files, budget_tokens, top, output } => {
+            match commands::handle_pack(query, files, budget_tokens, top, output, cli.config.as_deref()).await {
+                Ok(_) => 0,
+                Err(e) => report_error(format, "Pack failed", &e),
             }
+        }
+        Commands::Outline { file, json } => match commands::handle_outline(file, json, cli.config.as_deref()).await {
+            Ok(_) => 0,
+            Err(e) => report_error(format, "Outline failed", &e),
         },
-        Commands::Debug => match commands::handle_debug(cli.config.as_deref()).await {
+        Commands::Owners { target, json } => match commands::handle_owners(target, json, cli.config.as_deref()).await {
             Ok(_) => 0,
-            Err(e) => {
-                commands::ui::print_error(&format!("Debug failed: {}", e));
-                1
-            }
+            Err(e) => report_error(format, "Owners failed", &e),
+        },
+        Commands::Doctor { advise, repair, json } => match commands::handle_doctor(advise, repair, json, cli.config.as_deref()).await {
+            Ok(_) => 0,
+            Err(e) => report_error(format, "Doctor failed", &e),
+        },
+        Commands::ReportIssue { output } => match commands::handle_report_issue(output, cli.config.as_deref()).await {
+            Ok(_) => 0,
+            Err(e) => report_error(format, "Report failed", &e),
+        },
+        Commands::Explain { path, facts_only, verbose } => match commands::handle_explain(path, facts_only, verbose, cli.config.as_deref()).await {
+            Ok(_) => 0,
+            Err(e) => report_error(format, "Explain failed", &e),
         },
-        Commands::Explain { verbose } => match commands::handle_explain(verbose, cli.config.as_deref()).await {
+        Commands::Summarize { force, dry_run } => match commands::handle_summarize(force, dry_run, cli.config.as_deref()).await {
             Ok(_) => 0,
-            Err(e) => {
-                commands::ui::print_error(&format!("Explain failed: {}", e));
-                1
+            Err(e) => report_error(format, "Summarize failed", &e),
+        },
+        Commands::Apply { patch, dry_run } => match commands::handle_apply(patch, dry_run, cli.config.as_deref()).await {
+            Ok(_) => 0,
+            Err(e) => report_error(format, "Apply failed", &e),
+        },
+        Commands::Rename { symbol, new_name, file, apply } => match commands::handle_rename(symbol, new_name, file, apply, cli.config.as_deref()).await {
+            Ok(_) => 0,
+            Err(e) => report_error(format, "Rename failed", &e),
+        },
+        Commands::Watch { debounce_ms } => match commands::handle_watch(debounce_ms, cli.config.as_deref()).await {
+            Ok(_) => 0,
+            Err(e) => report_error(format, "Watch failed", &e),
+        },
+        Commands::History { symbol, limit, all, grep, rerun } => {
+            let limit = if all { usize::MAX } else { limit };
+            match commands::handle_history(symbol, limit, grep, rerun, cli.config.as_deref()).await {
+                Ok(_) => 0,
+                Err(e) => report_error(format, "History failed", &e),
             }
+        }
+        Commands::Config { action } => match commands::handle_config(action, cli.config.as_deref()).await {
+            Ok(_) => 0,
+            Err(e) => report_error(format, "Config failed", &e),
+        },
+        Commands::Coverage { action } => match commands::handle_coverage(action, cli.config.as_deref()).await {
+            Ok(_) => 0,
+            Err(e) => report_error(format, "Coverage failed", &e),
+        },
+        Commands::Daemon { action } => match commands::handle_daemon(action, cli.config.as_deref()).await {
+            Ok(_) => 0,
+            Err(e) => report_error(format, "Daemon failed", &e),
+        },
+        Commands::Mcp { transport, port } => match commands::handle_mcp(transport, port, cli.config.as_deref()).await {
+            Ok(_) => 0,
+            Err(e) => report_error(format, "MCP server failed", &e),
+        },
+        Commands::Lsp => match commands::handle_lsp(cli.config.as_deref()).await {
+            Ok(_) => 0,
+            Err(e) => report_error(format, "LSP server failed", &e),
         },
     };
 
     std::process::exit(exit_code);
 }
+
+/// Print a failed command's error in the requested format and return its exit code.
+fn report_error(format: OutputFormat, context: &str, e: &anyhow::Error) -> i32 {
+    let kind = ErrorKind::of(e);
+    let message = format!("{}: {}", context, e);
+    match format {
+        OutputFormat::Text => commands::ui::print_error(&message),
+        OutputFormat::Json => commands::ui::print_error_json(kind, &message),
+    }
+    commands::report_issue::record_error(kind, &message, e);
+    kind.exit_code()
+}
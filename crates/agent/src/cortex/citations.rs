@@ -0,0 +1,214 @@
+//! `path:line` citations in agent answers, and checking them against the
+//! file contents in the index (`agent.require_citations`).
+
+use anyhow::Result;
+use emry_store::SurrealStore;
+use regex::{Captures, Regex};
+use std::collections::HashSet;
+use std::fmt;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Characters of a cited span's first line shown in the Sources section.
+const EXCERPT_CHARS: usize = 80;
+/// Marker appended to citations the index can't back up.
+pub const UNVERIFIED: &str = "[unverified]";
+
+/// A `path:line` or `path:start-end` location cited by an answer.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Citation {
+    pub path: String,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl fmt::Display for Citation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.end > self.start {
+            write!(f, "{}:{}-{}", self.path, self.start, self.end)
+        } else {
+            write!(f, "{}:{}", self.path, self.start)
+        }
+    }
+}
+
+fn pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| Regex::new(r"([A-Za-z0-9_.\-/]+\.[A-Za-z0-9]+):(\d+)(?:-(\d+))?").expect("valid citation regex"))
+}
+
+fn from_captures(c: &Captures) -> Option<Citation> {
+    let start: usize = c[2].parse().ok()?;
+    let end = c.get(3).and_then(|m| m.as_str().parse().ok()).unwrap_or(start).max(start);
+    Some(Citation { path: c[1].trim_start_matches("./").to_string(), start, end })
+}
+
+/// `path:line` / `path:start-end` citations in `answer`, in order of first appearance.
+pub fn citations(answer: &str) -> Vec<Citation> {
+    let mut seen = HashSet::new();
+    pattern()
+        .captures_iter(answer)
+        .filter_map(|c| from_captures(&c))
+        .filter(|c| seen.insert(c.clone()))
+        .collect()
+}
+
+/// What the index says about a citation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    /// The file is indexed and has the lines; `excerpt` is the span's first non-blank line.
+    Verified { excerpt: String },
+    /// No indexed file has this path.
+    UnknownFile,
+    /// The file is indexed but shorter than the cited lines.
+    PastEnd { lines: usize },
+}
+
+#[derive(Debug, Clone)]
+pub struct CheckedCitation {
+    pub citation: Citation,
+    pub verdict: Verdict,
+}
+
+impl CheckedCitation {
+    pub fn is_verified(&self) -> bool {
+        matches!(self.verdict, Verdict::Verified { .. })
+    }
+}
+
+/// Check each citation in `answer` against the indexed content of its file.
+/// Files are indexed under absolute paths, so relative citations are taken
+/// relative to `root`.
+pub async fn verify(store: &SurrealStore, root: &Path, answer: &str) -> Result<Vec<CheckedCitation>> {
+    let mut checked = Vec::new();
+    for citation in citations(answer) {
+        let path = root.join(&citation.path);
+        let verdict = match store.get_file(&path.to_string_lossy()).await? {
+            None => Verdict::UnknownFile,
+            Some(file) => {
+                let lines: Vec<&str> = file.content.lines().collect();
+                if citation.start == 0 || citation.end > lines.len() {
+                    Verdict::PastEnd { lines: lines.len() }
+                } else {
+                    let excerpt = lines[citation.start - 1..citation.end]
+                        .iter()
+                        .map(|l| l.trim())
+                        .find(|l| !l.is_empty())
+                        .unwrap_or("")
+                        .chars()
+                        .take(EXCERPT_CHARS)
+                        .collect();
+                    Verdict::Verified { excerpt }
+                }
+            }
+        };
+        checked.push(CheckedCitation { citation, verdict });
+    }
+    Ok(checked)
+}
+
+/// `answer` with unverified citations marked [`UNVERIFIED`] and a Sources
+/// section listing the verified ones, or noting that there were none.
+pub fn enforce(answer: &str, checked: &[CheckedCitation]) -> String {
+    let unverified: HashSet<&Citation> = checked.iter().filter(|c| !c.is_verified()).map(|c| &c.citation).collect();
+    let mut out = pattern()
+        .replace_all(answer, |c: &Captures| match from_captures(c) {
+            Some(citation) if unverified.contains(&citation) => format!("{} {}", &c[0], UNVERIFIED),
+            _ => c[0].to_string(),
+        })
+        .trim_end()
+        .to_string();
+
+    out.push_str("\n\n## Sources\n");
+    let verified: Vec<_> = checked.iter().filter(|c| c.is_verified()).collect();
+    if verified.is_empty() {
+        out.push_str("_No citation in this answer could be checked against the index._\n");
+    }
+    for c in &verified {
+        match &c.verdict {
+            Verdict::Verified { excerpt } if !excerpt.is_empty() => out.push_str(&format!("- `{}` `{}`\n", c.citation, excerpt)),
+            _ => out.push_str(&format!("- `{}`\n", c.citation)),
+        }
+    }
+    if !unverified.is_empty() {
+        let reasons: Vec<String> = checked
+            .iter()
+            .filter_map(|c| match c.verdict {
+                Verdict::UnknownFile => Some(format!("`{}`: no such indexed file", c.citation)),
+                Verdict::PastEnd { lines } => Some(format!("`{}`: the file has {} lines", c.citation, lines)),
+                Verdict::Verified { .. } => None,
+            })
+            .collect();
+        out.push_str(&format!("\n_Marked {}: {}._\n", UNVERIFIED, reasons.join("; ")));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_citations_are_parsed_once_in_order() {
+        let answer = "The store opens in `crates/store/src/lib.rs:40-52`, called from ./cli/src/main.rs:12 \
+                      (see crates/store/src/lib.rs:40-52 again) and v1.2 is not a citation.";
+        let cited = citations(answer);
+        assert_eq!(
+            cited,
+            vec![
+                Citation { path: "crates/store/src/lib.rs".to_string(), start: 40, end: 52 },
+                Citation { path: "cli/src/main.rs".to_string(), start: 12, end: 12 },
+            ]
+        );
+        assert_eq!(cited[0].to_string(), "crates/store/src/lib.rs:40-52");
+        assert_eq!(cited[1].to_string(), "cli/src/main.rs:12");
+    }
+
+    #[test]
+    fn test_enforce_marks_unverified_and_lists_sources() {
+        let answer = "Parsing happens in src/parse.rs:10, errors in src/error.rs:3 and src/gone.rs:1.";
+        let cite = |path: &str, line: usize| Citation { path: path.to_string(), start: line, end: line };
+        let checked = vec![
+            CheckedCitation { citation: cite("src/parse.rs", 10), verdict: Verdict::Verified { excerpt: "fn parse()".to_string() } },
+            CheckedCitation { citation: cite("src/error.rs", 3), verdict: Verdict::PastEnd { lines: 2 } },
+            CheckedCitation { citation: cite("src/gone.rs", 1), verdict: Verdict::UnknownFile },
+        ];
+        let out = enforce(answer, &checked);
+        assert!(out.starts_with(
+            "Parsing happens in src/parse.rs:10, errors in src/error.rs:3 [unverified] and src/gone.rs:1 [unverified]."
+        ));
+        assert!(out.contains("## Sources\n- `src/parse.rs:10` `fn parse()`\n"));
+        assert!(out.contains("`src/error.rs:3`: the file has 2 lines; `src/gone.rs:1`: no such indexed file"));
+
+        assert!(enforce("No code cited.", &[]).contains("_No citation in this answer could be checked"));
+    }
+
+    #[tokio::test]
+    async fn test_verify_against_indexed_files() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let store = SurrealStore::open(&dir.path().join("db"), None).await?;
+        let root = Path::new("/repo");
+        let file = emry_store::FileRecord {
+            id: None,
+            path: "/repo/src/parse.rs".to_string(),
+            language: "rust".to_string(),
+            content: "\n    fn parse() {}\n}\n".to_string(),
+            hash: String::new(),
+            last_modified: 0,
+        };
+        store.add_file_nodes(&file, &[], &[], &Default::default()).await?;
+
+        let answer = "See src/parse.rs:1-2, /repo/src/parse.rs:2, src/parse.rs:9 and src/gone.rs:1.";
+        let verdicts: Vec<Verdict> = verify(&store, root, answer).await?.into_iter().map(|c| c.verdict).collect();
+        assert_eq!(
+            verdicts,
+            vec![
+                Verdict::Verified { excerpt: "fn parse() {}".to_string() },
+                Verdict::Verified { excerpt: "fn parse() {}".to_string() },
+                Verdict::PastEnd { lines: 3 },
+                Verdict::UnknownFile,
+            ]
+        );
+        Ok(())
+    }
+}
@@ -14,12 +14,25 @@ pub struct Step {
     pub error: Option<String>,
 }
 
+/// A completed question/answer exchange in an interactive session.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Turn {
+    pub question: String,
+    pub answer: String,
+}
+
 pub struct AgentContext {
     pub repo_context: Arc<RepoContext>,
     pub search_service: Arc<SearchService>,
     pub tools: HashMap<String, Arc<dyn Tool>>,
     pub history: Vec<Step>,
     pub memory: Vec<String>, // "Facts" derived from observations
+    /// Earlier turns of the session, oldest first; empty for one-shot questions.
+    pub transcript: Vec<Turn>,
+    /// Summary of turns older than `transcript`, from resumed or compacted conversations.
+    pub summary: Option<String>,
+    /// Repository and top-level directory summaries from `emry summarize`, if any.
+    pub overview: Option<String>,
     pub config: emry_config::AgentConfig,
 }
 
@@ -35,6 +48,9 @@ impl AgentContext {
             tools: HashMap::new(),
             history: Vec::new(),
             memory: Vec::new(),
+            transcript: Vec::new(),
+            summary: None,
+            overview: None,
             config,
         }
     }
@@ -51,6 +67,10 @@ impl AgentContext {
         self.memory.push(fact);
     }
     
+    pub fn record_turn(&mut self, question: String, answer: String) {
+        self.transcript.push(Turn { question, answer });
+    }
+
     pub fn get_tool(&self, name: &str) -> Option<Arc<dyn Tool>> {
         self.tools.get(name).cloned()
     }
@@ -0,0 +1,217 @@
+//! Conversations that outlive the process: `emry ask --continue` and
+//! `emry chat` pick up where an earlier run left off.
+//!
+//! Each conversation is a JSON file under `.codeindex/sessions/`, next to the
+//! markdown logs chat writes. It keeps every turn; once there are more than
+//! the prompt shows, the older ones are folded into a running summary that
+//! the agent sees instead of them.
+
+use crate::cortex::context::{AgentContext, Turn};
+use crate::cortex::strategy::MAX_PROMPT_TURNS;
+use crate::llm::{Message, OpenAIProvider};
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Recent turns kept verbatim when older ones are summarized.
+const KEPT_TURNS: usize = MAX_PROMPT_TURNS / 2;
+const SUMMARY_MAX_TOKENS: u32 = 400;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Conversation {
+    pub id: String,
+    /// `ask` or `chat`.
+    pub command: String,
+    pub created: u64,
+    pub updated: u64,
+    /// Path globs retrieval was restricted to.
+    #[serde(default)]
+    pub scope: Vec<String>,
+    pub turns: Vec<Turn>,
+    /// The first `summarized` turns, condensed; the agent's prompt carries
+    /// this in place of them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    #[serde(default)]
+    pub summarized: usize,
+}
+
+impl Conversation {
+    pub fn new(command: &str, scope: Vec<String>) -> Self {
+        let now = unix_time();
+        Self {
+            id: now.to_string(),
+            command: command.to_string(),
+            created: now,
+            updated: now,
+            scope,
+            turns: Vec::new(),
+            summary: None,
+            summarized: 0,
+        }
+    }
+
+    /// Seed `ctx` with this conversation: its summary and the turns after it.
+    pub fn restore(&self, ctx: &mut AgentContext) {
+        ctx.summary = self.summary.clone();
+        ctx.transcript = self.turns[self.summarized.min(self.turns.len())..].to_vec();
+    }
+
+    /// Record `ctx`'s latest turn and summary after a question.
+    pub fn update(&mut self, ctx: &AgentContext) {
+        if let Some(turn) = ctx.transcript.last() {
+            self.turns.push(turn.clone());
+        }
+        self.summary = ctx.summary.clone();
+        self.summarized = self.turns.len().saturating_sub(ctx.transcript.len());
+        self.updated = unix_time();
+    }
+
+    /// The first question, as a title for listings.
+    pub fn title(&self) -> &str {
+        self.turns.first().map_or("(empty)", |t| t.question.as_str())
+    }
+}
+
+/// Conversations of one repository, one JSON file each.
+pub struct ConversationStore {
+    dir: PathBuf,
+}
+
+impl ConversationStore {
+    pub fn new(root: &Path) -> Self {
+        Self { dir: root.join(".codeindex").join("sessions") }
+    }
+
+    /// All conversations, most recently updated first.
+    pub fn list(&self) -> Result<Vec<Conversation>> {
+        let entries = match std::fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e).with_context(|| format!("failed to read {}", self.dir.display())),
+        };
+        let mut conversations: Vec<Conversation> = entries
+            .filter_map(|e| e.ok())
+            .filter(|e| e.path().extension().is_some_and(|x| x == "json"))
+            .filter_map(|e| {
+                let parsed = std::fs::read_to_string(e.path()).ok().and_then(|text| serde_json::from_str(&text).ok());
+                if parsed.is_none() {
+                    tracing::warn!("Skipping unreadable conversation {}", e.path().display());
+                }
+                parsed
+            })
+            .collect();
+        conversations.sort_by(|a, b| b.updated.cmp(&a.updated).then_with(|| b.id.cmp(&a.id)));
+        Ok(conversations)
+    }
+
+    /// The most recently updated conversation, if any.
+    pub fn latest(&self) -> Result<Option<Conversation>> {
+        Ok(self.list()?.into_iter().next())
+    }
+
+    pub fn load(&self, id: &str) -> Result<Conversation> {
+        let path = self.path(id)?;
+        let text = std::fs::read_to_string(&path)
+            .map_err(|_| anyhow!("no conversation '{}'; `emry sessions list` shows them", id))?;
+        serde_json::from_str(&text).with_context(|| format!("failed to parse {}", path.display()))
+    }
+
+    pub fn save(&self, conversation: &Conversation) -> Result<()> {
+        std::fs::create_dir_all(&self.dir).with_context(|| format!("failed to create {}", self.dir.display()))?;
+        let path = self.path(&conversation.id)?;
+        // Write then rename, so an interrupted save leaves the previous version.
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_string_pretty(conversation)?)
+            .with_context(|| format!("failed to write {}", tmp.display()))?;
+        std::fs::rename(&tmp, &path).with_context(|| format!("failed to write {}", path.display()))
+    }
+
+    /// Remove a conversation and its chat log; `false` if there was none.
+    pub fn delete(&self, id: &str) -> Result<bool> {
+        let path = self.path(id)?;
+        if !path.is_file() {
+            return Ok(false);
+        }
+        std::fs::remove_file(&path).with_context(|| format!("failed to remove {}", path.display()))?;
+        let _ = std::fs::remove_file(path.with_extension("md"));
+        Ok(true)
+    }
+
+    fn path(&self, id: &str) -> Result<PathBuf> {
+        if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+            return Err(anyhow!("invalid conversation id '{}'", id));
+        }
+        Ok(self.dir.join(format!("{}.json", id)))
+    }
+}
+
+/// Fold all but the last few turns of `ctx.transcript` into `ctx.summary`
+/// once there are more than the prompt shows. Returns how many were folded.
+pub async fn compact(ctx: &mut AgentContext, llm: &OpenAIProvider) -> Result<usize> {
+    if ctx.transcript.len() <= MAX_PROMPT_TURNS {
+        return Ok(0);
+    }
+    let folded = ctx.transcript.len() - KEPT_TURNS;
+    let mut content = String::new();
+    if let Some(summary) = &ctx.summary {
+        content.push_str(&format!("## Summary So Far\n{}\n\n", summary));
+    }
+    content.push_str("## Turns To Fold In\n");
+    for turn in &ctx.transcript[..folded] {
+        content.push_str(&format!("User: {}\nAssistant: {}\n\n", turn.question, turn.answer));
+    }
+    let messages = [
+        Message {
+            role: "system".to_string(),
+            content: "You maintain the memory of a conversation about a codebase. Merge the summary so far \
+                      and the new turns into one concise summary: what the user is trying to do, what was \
+                      established (with the files, symbols and `path:line` locations involved) and what is \
+                      still open. Return plain bullet points."
+                .to_string(),
+        },
+        Message { role: "user".to_string(), content },
+    ];
+    let summary = llm.chat_with_limit(&messages, Some(SUMMARY_MAX_TOKENS)).await?;
+    ctx.summary = Some(summary.trim().to_string());
+    ctx.transcript.drain(..folded);
+    Ok(folded)
+}
+
+fn unix_time() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_store_round_trip_and_latest() -> Result<()> {
+        let temp = TempDir::new()?;
+        let store = ConversationStore::new(temp.path());
+        assert!(store.latest()?.is_none());
+
+        let mut older = Conversation::new("ask", Vec::new());
+        older.id = "1".to_string();
+        older.updated = 10;
+        older.turns.push(Turn { question: "Where is the store opened?".to_string(), answer: "In lib.rs:40".to_string() });
+        let mut newer = Conversation::new("chat", vec!["src/**".to_string()]);
+        newer.id = "2".to_string();
+        newer.updated = 20;
+        store.save(&older)?;
+        store.save(&newer)?;
+
+        assert_eq!(store.latest()?.unwrap().id, "2");
+        let loaded = store.load("1")?;
+        assert_eq!(loaded.title(), "Where is the store opened?");
+        assert!(store.load("../1").is_err());
+
+        assert!(store.delete("2")?);
+        assert!(!store.delete("2")?);
+        assert_eq!(store.list()?.len(), 1);
+        Ok(())
+    }
+}
@@ -0,0 +1,115 @@
+//! The critic pass (`agent.critic`): before an answer is returned, the code
+//! it cites is read back from disk and the answer model checks each claim
+//! against it, correcting or dropping the ones the code doesn't support.
+
+use crate::cortex::citations::{citations, Citation};
+use crate::cortex::context::AgentContext;
+use crate::llm::{JsonSchemaSpec, Message, OpenAIProvider};
+use anyhow::Result;
+use std::path::{Component, Path};
+
+/// Citations checked per answer; later ones are left as they are.
+const MAX_CITATIONS: usize = 12;
+/// Lines of a cited span shown to the critic, and around it.
+const MAX_SPAN_LINES: usize = 40;
+const CONTEXT_LINES: usize = 2;
+/// Room for the list of issues on top of the answer's own token budget.
+const ISSUES_TOKENS: u32 = 400;
+
+/// An answer after the critic pass.
+#[derive(Debug, Clone)]
+pub struct Review {
+    pub answer: String,
+    /// What the critic found wrong; empty when every citation held up.
+    pub issues: Vec<String>,
+}
+
+/// Check `answer`'s citations against the files and let `llm` correct it.
+/// Answers without citations are returned as they are.
+pub async fn review(ctx: &AgentContext, llm: &OpenAIProvider, query: &str, answer: &str) -> Result<Review> {
+    let cited = citations(answer);
+    if cited.is_empty() {
+        return Ok(Review { answer: answer.to_string(), issues: Vec::new() });
+    }
+
+    let mut evidence = String::new();
+    let mut unreadable = Vec::new();
+    for citation in cited.iter().take(MAX_CITATIONS) {
+        match read_span(ctx, citation) {
+            Ok(text) => evidence.push_str(&format!("### {}\n```\n{}\n```\n\n", citation, text)),
+            Err(reason) => {
+                evidence.push_str(&format!("### {}\n(not checkable: {})\n\n", citation, reason));
+                unreadable.push(format!("{}: {}", citation, reason));
+            }
+        }
+    }
+
+    let messages = [
+        Message {
+            role: "system".to_string(),
+            content: "You review an answer about a codebase against the code it cites. For each citation, decide \
+                      whether the lines shown (numbered, with a little context) support what the answer says about \
+                      them. List the problems you find, one short sentence each, and return the answer corrected: \
+                      point a citation at the right lines when the code shown makes them clear, otherwise drop or \
+                      qualify the unsupported claim. Leave everything else as it is. If nothing is wrong, return the \
+                      answer unchanged with no issues."
+                .to_string(),
+        },
+        Message {
+            role: "user".to_string(),
+            content: format!("## Question\n{}\n\n## Answer\n{}\n\n## Cited Code\n{}", query, answer, evidence),
+        },
+    ];
+    let schema = serde_json::json!({
+        "type": "object",
+        "properties": {
+            "issues": { "type": "array", "items": { "type": "string" } },
+            "answer": { "type": "string", "description": "The corrected answer, in the original's format" }
+        },
+        "required": ["issues", "answer"],
+        "additionalProperties": false
+    });
+    let response = llm
+        .chat_with_schema_and_limit(
+            &messages,
+            JsonSchemaSpec { name: "cortex_critic".to_string(), schema },
+            Some(ctx.config.max_tokens.saturating_add(ISSUES_TOKENS)),
+        )
+        .await?;
+    let verdict = crate::cortex::strategy::parse_json_response(&response)?;
+
+    let mut issues = unreadable;
+    issues.extend(
+        verdict["issues"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|i| i.as_str())
+            .map(str::to_string),
+    );
+    let revised = verdict["answer"].as_str().map(str::trim).filter(|a| !a.is_empty());
+    Ok(Review { answer: revised.unwrap_or(answer).to_string(), issues })
+}
+
+/// The cited lines of `citation`, numbered, with a few lines around them.
+/// Fails with the reason when the file can't be read or the lines don't exist.
+fn read_span(ctx: &AgentContext, citation: &Citation) -> std::result::Result<String, String> {
+    let path = Path::new(&citation.path);
+    if path.components().any(|c| !matches!(c, Component::Normal(_) | Component::CurDir)) {
+        return Err("not a path inside the repository".to_string());
+    }
+    if let Some(scope) = &ctx.repo_context.scope {
+        if !scope.contains(path) {
+            return Err("outside the scope".to_string());
+        }
+    }
+    let text = std::fs::read_to_string(ctx.repo_context.root.join(path)).map_err(|_| "no such file".to_string())?;
+    let lines: Vec<&str> = text.lines().collect();
+    if citation.start == 0 || citation.start > lines.len() {
+        return Err(format!("line {} is past the end of the file ({} lines)", citation.start, lines.len()));
+    }
+    let end = citation.end.min(lines.len()).min(citation.start + MAX_SPAN_LINES - 1);
+    let first = citation.start.saturating_sub(CONTEXT_LINES).max(1);
+    let last = (end + CONTEXT_LINES).min(lines.len());
+    Ok((first..=last).map(|n| format!("{:>5} {}", n, lines[n - 1])).collect::<Vec<_>>().join("\n"))
+}
@@ -1,9 +1,14 @@
+pub mod citations;
 pub mod context;
+pub mod conversation;
+pub mod critic;
 pub mod tool;
 pub mod tools;
 pub mod prompts;
+pub mod strategy;
 
 use crate::cortex::context::AgentContext;
+use crate::cortex::strategy::{strategy_for, LoopStrategy};
 use crate::llm::OpenAIProvider;
 use anyhow::Result;
 
@@ -13,138 +18,73 @@ pub enum CortexEvent {
     Thought(String),
     ToolCall { name: String, args: serde_json::Value },
     ToolResult { name: String, result: String },
+    /// A fragment of the final answer, emitted while it is being generated.
+    AnswerDelta(String),
 }
 
 pub struct Cortex {
     pub ctx: AgentContext,
+    /// Runs the tool steps: `llm.planner_model` when set.
     pub llm: OpenAIProvider,
+    /// Writes final answers and reviews them: `llm.answer_model` when set.
+    pub answer_llm: OpenAIProvider,
+    pub strategy: Box<dyn LoopStrategy>,
 }
 
 impl Cortex {
+    /// Creates a Cortex using the loop strategy selected by `agent.strategy`.
     pub fn new(ctx: AgentContext, llm: OpenAIProvider) -> Self {
-        Self { ctx, llm }
+        let strategy = strategy_for(ctx.config.strategy);
+        Self::with_strategy(ctx, llm, strategy)
+    }
+
+    /// `llm` runs every call unless `llm.planner_model` or `llm.answer_model`
+    /// name other models for the steps or the answer.
+    pub fn with_strategy(ctx: AgentContext, llm: OpenAIProvider, strategy: Box<dyn LoopStrategy>) -> Self {
+        let models = &ctx.repo_context.config.llm;
+        let answer_llm = models.answer_model.as_deref().map_or_else(|| llm.clone(), |m| llm.with_model(m));
+        let llm = models.planner_model.as_deref().map_or_else(|| llm.clone(), |m| llm.with_model(m));
+        Self { ctx, llm, answer_llm, strategy }
     }
 
     pub async fn run<F>(&mut self, query: &str, mut on_event: F) -> Result<String> 
     where
         F: FnMut(CortexEvent) + Send,
     {
-        self.ctx.history.clear();
-        let max_steps = self.ctx.config.max_steps;
-        
-        let mut messages = Vec::new();
-        
-        let mut system_prompt = crate::cortex::prompts::SYSTEM_PROMPT.to_string();
-        
-        system_prompt.push_str(&format!(
-            "\n\n# WORKSPACE CONTEXT\n\
-             Workspace Root: {}\n\
-             All file operations must use paths within this workspace. \
-             Use '.' to refer to the workspace root.\n",
-            self.ctx.repo_context.root.display()
-        ));
-        
-        system_prompt.push_str("\n\n# AVAILABLE TOOLS\n");
-        for tool in self.ctx.tools.values() {
-            system_prompt.push_str(&format!("- {}: {}\n  Schema: {}\n", tool.name(), tool.description(), tool.schema()));
+        // `agent.max_cost_usd` and `agent.max_run_tokens` apply per question.
+        self.llm.reset_usage();
+        if let Some(store) = &self.ctx.repo_context.surreal_store {
+            let scope = self.ctx.repo_context.scope.as_ref();
+            self.ctx.overview = crate::workflows::summarize::overview(store, scope).await.unwrap_or_else(|e| {
+                tracing::warn!("Failed to load the repository summaries: {}", e);
+                None
+            });
         }
-        messages.push(crate::llm::Message {
-            role: "system".to_string(),
-            content: system_prompt,
-        });
-        
-        let mut user_content = format!("## Current Task\nQuery: {}\n\n", query);
-        if !self.ctx.memory.is_empty() {
-            user_content.push_str("## Memory\n");
-            for item in &self.ctx.memory {
-                user_content.push_str(&format!("- {}\n", item));
-            }
+        if !self.ctx.config.critic {
+            return self.strategy.run(&mut self.ctx, &self.llm, &self.answer_llm, query, &mut on_event).await;
         }
-        messages.push(crate::llm::Message {
-            role: "user".to_string(),
-            content: user_content,
-        });
-
-        for step_count in 1..=max_steps {
-            on_event(CortexEvent::StepStart(step_count));
 
-            let schema = serde_json::json!({
-                "type": "object",
-                "properties": {
-                    "thought": { "type": "string", "description": "Reasoning for the next step" },
-                    "action": { "type": "string", "description": "Name of the tool to execute" },
-                    "args": { "type": "object", "description": "Arguments for the tool" }
-                },
-                "required": ["thought", "action", "args"],
-                "additionalProperties": false
-            });
-
-            let response = self.llm.chat_with_schema(
-                &messages,
-                crate::llm::JsonSchemaSpec {
-                    name: "cortex_step".to_string(),
-                    schema,
-                }
-            ).await?;
-            
-            let step_data: serde_json::Value = serde_json::from_str(&response)
-                .or_else(|_| serde_json::from_str(response.trim()))
-                .map_err(|e| anyhow::anyhow!("Failed to parse LLM response: {}. Raw response: '{}'", e, response))?;
-                
-            let thought = step_data["thought"].as_str().unwrap_or("").to_string();
-            let action = step_data["action"].as_str().unwrap_or("").to_string();
-            let args = step_data["args"].clone();
-
-            on_event(CortexEvent::Thought(thought.clone()));
-            
-            // Record Assistant Message
-            messages.push(crate::llm::Message {
-                role: "assistant".to_string(),
-                content: response.clone(),
-            });
-
-            if action == "final_answer" {
-                let answer = &args["answer"];
-                return Ok(if answer.is_string() {
-                    answer.as_str().unwrap_or("").to_string()
-                } else {
-                    serde_json::to_string_pretty(answer).unwrap_or_else(|_| "".to_string())
-                });
+        // The critic may rewrite the answer, so it isn't streamed.
+        let mut forward = |event: CortexEvent| {
+            if !matches!(event, CortexEvent::AnswerDelta(_)) {
+                on_event(event);
             }
-            
-            on_event(CortexEvent::ToolCall { name: action.clone(), args: args.clone() });
-
-            let tool_name = action.clone();
-            let tool_result = if let Some(tool) = self.ctx.tools.get(&tool_name) {
-                match tool.execute(args.clone()).await {
-                    Ok(res) => res,
-                    Err(e) => format!("Error executing tool '{}': {}", tool_name, e),
-                }
-            } else {
-                format!("Tool '{}' not found. Available tools: {:?}", tool_name, self.ctx.tools.keys())
-            };
-
-            on_event(CortexEvent::ToolResult { name: tool_name.clone(), result: tool_result.clone() });
-
-            messages.push(crate::llm::Message {
-                role: "user".to_string(),
-                content: format!("Observation: {}", tool_result),
-            });
-
-            self.ctx.add_step(crate::cortex::context::Step {
-                step_id: step_count,
-                thought: thought.clone(),
-                action: action.clone(),
-                args: args.clone(),
-                observation: tool_result.clone(),
-                error: None,
-            });
-            
-            if self.ctx.history.len() >= max_steps {
-                return Ok("Reached maximum steps without final answer.".to_string());
+        };
+        let answer = self.strategy.run(&mut self.ctx, &self.llm, &self.answer_llm, query, &mut forward).await?;
+        if let Some(reason) = self.llm.usage().budget_exceeded(&self.ctx.config) {
+            forward(CortexEvent::Thought(format!("Skipped checking the answer's citations: {}.", reason)));
+            return Ok(answer);
+        }
+        let review = match critic::review(&self.ctx, &self.answer_llm, query, &answer).await {
+            Ok(review) => review,
+            Err(e) => {
+                tracing::warn!("Critic pass failed, keeping the unchecked answer: {}", e);
+                return Ok(answer);
             }
+        };
+        if !review.issues.is_empty() {
+            forward(CortexEvent::Thought(format!("Critic: {}", review.issues.join("; "))));
         }
-        
-        Ok("Max steps reached without final answer.".to_string())
+        Ok(review.answer)
     }
 }
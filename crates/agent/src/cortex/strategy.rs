@@ -0,0 +1,708 @@
+use crate::cortex::context::{AgentContext, Step};
+use crate::cortex::CortexEvent;
+use crate::llm::{JsonSchemaSpec, Message, OpenAIProvider};
+use anyhow::Result;
+use async_trait::async_trait;
+use emry_config::AgentStrategy;
+use emry_core::chunking::tokenizer::count_tokens;
+use emry_core::map::{codebase_entries, render_codebase_map};
+
+/// A control loop that drives the agent from a query to a final answer.
+///
+/// Strategies share the same `AgentContext` (tools, memory, history) and emit
+/// the same `CortexEvent`s, so callers can swap them without other changes.
+#[async_trait]
+pub trait LoopStrategy: Send + Sync {
+    fn name(&self) -> &str;
+
+    /// Answer `query`, choosing tools with `llm` and writing the final answer
+    /// with `answer_llm` (the same model unless `llm.answer_model` is set).
+    async fn run(
+        &self,
+        ctx: &mut AgentContext,
+        llm: &OpenAIProvider,
+        answer_llm: &OpenAIProvider,
+        query: &str,
+        on_event: &mut (dyn FnMut(CortexEvent) + Send),
+    ) -> Result<String>;
+}
+
+pub fn strategy_for(kind: AgentStrategy) -> Box<dyn LoopStrategy> {
+    match kind {
+        AgentStrategy::React => Box::new(ReactStrategy),
+        AgentStrategy::PlanExecute => Box::new(PlanExecuteStrategy),
+        AgentStrategy::Rag => Box::new(RagStrategy),
+        AgentStrategy::Deep => Box::new(DeepStrategy),
+    }
+}
+
+fn build_system_prompt(ctx: &AgentContext, base: &str) -> String {
+    let mut system_prompt = base.to_string();
+
+    system_prompt.push_str(&format!(
+        "\n\n# WORKSPACE CONTEXT\n\
+         Workspace Root: {}\n\
+         All file operations must use paths within this workspace. \
+         Use '.' to refer to the workspace root.\n",
+        ctx.repo_context.root.display()
+    ));
+
+    if let Some(overview) = &ctx.overview {
+        system_prompt.push_str(&format!("\n# REPOSITORY OVERVIEW\n{}\n", overview));
+    }
+
+    system_prompt.push_str("\n\n# AVAILABLE TOOLS\n");
+    for tool in ctx.tools.values() {
+        system_prompt.push_str(&format!("- {}: {}\n  Schema: {}\n", tool.name(), tool.description(), tool.schema()));
+    }
+    system_prompt
+}
+
+/// Earlier turns included in the prompt, most recent last.
+pub(crate) const MAX_PROMPT_TURNS: usize = 6;
+/// Earlier answers are clipped to this many characters in the prompt.
+const MAX_TURN_ANSWER_CHARS: usize = 1500;
+
+fn build_user_prompt(ctx: &AgentContext, query: &str) -> String {
+    let mut user_content = String::new();
+    if let Some(summary) = &ctx.summary {
+        user_content.push_str(&format!("## Earlier In This Conversation\n{}\n\n", summary));
+    }
+    if !ctx.transcript.is_empty() {
+        user_content.push_str("## Conversation So Far\n");
+        let skip = ctx.transcript.len().saturating_sub(MAX_PROMPT_TURNS);
+        for turn in &ctx.transcript[skip..] {
+            let answer: String = turn.answer.chars().take(MAX_TURN_ANSWER_CHARS).collect();
+            user_content.push_str(&format!("User: {}\nAssistant: {}\n\n", turn.question, answer));
+        }
+    }
+    user_content.push_str(&format!("## Current Task\nQuery: {}\n\n", query));
+    if !ctx.memory.is_empty() {
+        user_content.push_str("## Memory\n");
+        for item in &ctx.memory {
+            user_content.push_str(&format!("- {}\n", item));
+        }
+    }
+    user_content
+}
+
+pub(crate) fn parse_json_response(response: &str) -> Result<serde_json::Value> {
+    serde_json::from_str(response)
+        .or_else(|_| serde_json::from_str(response.trim()))
+        .map_err(|e| anyhow::anyhow!("Failed to parse LLM response: {}. Raw response: '{}'", e, response))
+}
+
+async fn execute_tool(ctx: &AgentContext, name: &str, args: serde_json::Value) -> String {
+    if let Some(tool) = ctx.tools.get(name) {
+        match tool.execute(args).await {
+            Ok(res) => res,
+            Err(e) => format!("Error executing tool '{}': {}", name, e),
+        }
+    } else {
+        format!("Tool '{}' not found. Available tools: {:?}", name, ctx.tools.keys())
+    }
+}
+
+/// Thought -> action -> observation loop, one tool call per step.
+pub struct ReactStrategy;
+
+#[async_trait]
+impl LoopStrategy for ReactStrategy {
+    fn name(&self) -> &str {
+        "react"
+    }
+
+    async fn run(
+        &self,
+        ctx: &mut AgentContext,
+        llm: &OpenAIProvider,
+        answer_llm: &OpenAIProvider,
+        query: &str,
+        on_event: &mut (dyn FnMut(CortexEvent) + Send),
+    ) -> Result<String> {
+        ctx.history.clear();
+        let max_steps = ctx.config.max_steps;
+        // With a separate answer model, the planner's final answer is only a draft.
+        let delegate = answer_llm.model != llm.model;
+
+        let mut messages = vec![
+            Message {
+                role: "system".to_string(),
+                content: build_system_prompt(ctx, crate::cortex::prompts::SYSTEM_PROMPT),
+            },
+            Message {
+                role: "user".to_string(),
+                content: build_user_prompt(ctx, query),
+            },
+        ];
+
+        for step_count in 1..=max_steps {
+            if let Some(reason) = llm.usage().budget_exceeded(&ctx.config) {
+                return Ok(format!("Stopped before step {}: {}.", step_count, reason));
+            }
+            on_event(CortexEvent::StepStart(step_count));
+
+            let schema = serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "thought": { "type": "string", "description": "Reasoning for the next step" },
+                    "action": { "type": "string", "description": "Name of the tool to execute" },
+                    "args": { "type": "object", "description": "Arguments for the tool" }
+                },
+                "required": ["thought", "action", "args"],
+                "additionalProperties": false
+            });
+
+            let mut answer_stream = AnswerStream::default();
+            let mut on_delta = |delta: &str| {
+                let text = answer_stream.push(delta);
+                if !text.is_empty() && !delegate {
+                    on_event(CortexEvent::AnswerDelta(text));
+                }
+            };
+            let response = llm.chat_with_schema_stream(
+                &messages,
+                JsonSchemaSpec {
+                    name: "cortex_step".to_string(),
+                    schema,
+                },
+                &mut on_delta,
+            ).await?;
+
+            let step_data = parse_json_response(&response)?;
+
+            let thought = step_data["thought"].as_str().unwrap_or("").to_string();
+            let action = step_data["action"].as_str().unwrap_or("").to_string();
+            let args = step_data["args"].clone();
+
+            on_event(CortexEvent::Thought(thought.clone()));
+
+            // Record Assistant Message
+            messages.push(Message {
+                role: "assistant".to_string(),
+                content: response.clone(),
+            });
+
+            if action == "final_answer" {
+                let answer = &args["answer"];
+                let answer = if answer.is_string() {
+                    answer.as_str().unwrap_or("").to_string()
+                } else {
+                    serde_json::to_string_pretty(answer).unwrap_or_else(|_| "".to_string())
+                };
+                if !delegate {
+                    return Ok(answer);
+                }
+                let mut notes = observations(ctx);
+                notes.push_str(&format!("## Draft Answer\n{}\n", answer));
+                return synthesize(ctx, answer_llm, query, &notes, on_event).await;
+            }
+
+            on_event(CortexEvent::ToolCall { name: action.clone(), args: args.clone() });
+
+            let tool_result = execute_tool(ctx, &action, args.clone()).await;
+
+            on_event(CortexEvent::ToolResult { name: action.clone(), result: tool_result.clone() });
+
+            messages.push(Message {
+                role: "user".to_string(),
+                content: format!("Observation: {}", tool_result),
+            });
+
+            ctx.add_step(Step {
+                step_id: step_count,
+                thought,
+                action,
+                args,
+                observation: tool_result,
+                error: None,
+            });
+
+            if ctx.history.len() >= max_steps {
+                return Ok("Reached maximum steps without final answer.".to_string());
+            }
+        }
+
+        Ok("Max steps reached without final answer.".to_string())
+    }
+}
+
+/// Drafts the full list of tool calls up front, runs them in order, then
+/// synthesizes an answer from the collected observations in a single call.
+pub struct PlanExecuteStrategy;
+
+#[async_trait]
+impl LoopStrategy for PlanExecuteStrategy {
+    fn name(&self) -> &str {
+        "plan_execute"
+    }
+
+    async fn run(
+        &self,
+        ctx: &mut AgentContext,
+        llm: &OpenAIProvider,
+        answer_llm: &OpenAIProvider,
+        query: &str,
+        on_event: &mut (dyn FnMut(CortexEvent) + Send),
+    ) -> Result<String> {
+        ctx.history.clear();
+        let max_steps = ctx.config.max_steps;
+
+        let system = Message {
+            role: "system".to_string(),
+            content: build_system_prompt(ctx, crate::cortex::prompts::PLANNER_PROMPT),
+        };
+        let user = Message {
+            role: "user".to_string(),
+            content: build_user_prompt(ctx, query),
+        };
+
+        let schema = serde_json::json!({
+            "type": "object",
+            "properties": {
+                "thought": { "type": "string", "description": "Overall approach" },
+                "steps": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "action": { "type": "string", "description": "Name of the tool to execute" },
+                            "args": { "type": "object", "description": "Arguments for the tool" }
+                        },
+                        "required": ["action", "args"],
+                        "additionalProperties": false
+                    }
+                }
+            },
+            "required": ["thought", "steps"],
+            "additionalProperties": false
+        });
+
+        let response = llm.chat_with_schema(
+            &[system.clone(), user.clone()],
+            JsonSchemaSpec {
+                name: "cortex_plan".to_string(),
+                schema,
+            }
+        ).await?;
+        let plan = parse_json_response(&response)?;
+
+        on_event(CortexEvent::Thought(plan["thought"].as_str().unwrap_or("").to_string()));
+
+        let steps = plan["steps"].as_array().cloned().unwrap_or_default();
+
+        for (i, step) in steps.into_iter().take(max_steps).enumerate() {
+            let step_count = i + 1;
+            on_event(CortexEvent::StepStart(step_count));
+
+            let action = step["action"].as_str().unwrap_or("").to_string();
+            let args = step["args"].clone();
+
+            on_event(CortexEvent::ToolCall { name: action.clone(), args: args.clone() });
+            let tool_result = execute_tool(ctx, &action, args.clone()).await;
+            on_event(CortexEvent::ToolResult { name: action.clone(), result: tool_result.clone() });
+
+            ctx.add_step(Step {
+                step_id: step_count,
+                thought: String::new(),
+                action,
+                args,
+                observation: tool_result,
+                error: None,
+            });
+        }
+
+        if let Some(reason) = llm.usage().budget_exceeded(&ctx.config) {
+            return Ok(format!("Stopped before writing the answer: {}.", reason));
+        }
+        synthesize(ctx, answer_llm, query, &observations(ctx), on_event).await
+    }
+}
+
+/// Sub-agents a deep run starts at most, however many sub-questions are drafted.
+const MAX_SUBAGENTS: usize = 4;
+
+/// A lead call splits the question into sub-questions, each investigated by a
+/// ReAct sub-agent with an equal share of `max_steps`, then the answer is
+/// synthesized from their findings and observations. Sub-agents run one after
+/// another on the same tools; their answers are not streamed.
+pub struct DeepStrategy;
+
+#[async_trait]
+impl LoopStrategy for DeepStrategy {
+    fn name(&self) -> &str {
+        "deep"
+    }
+
+    async fn run(
+        &self,
+        ctx: &mut AgentContext,
+        llm: &OpenAIProvider,
+        answer_llm: &OpenAIProvider,
+        query: &str,
+        on_event: &mut (dyn FnMut(CortexEvent) + Send),
+    ) -> Result<String> {
+        ctx.history.clear();
+        let max_steps = ctx.config.max_steps;
+
+        let schema = serde_json::json!({
+            "type": "object",
+            "properties": {
+                "thought": { "type": "string", "description": "How the question divides" },
+                "subtasks": {
+                    "type": "array",
+                    "items": { "type": "string", "description": "A self-contained sub-question" }
+                }
+            },
+            "required": ["thought", "subtasks"],
+            "additionalProperties": false
+        });
+        let response = llm.chat_with_schema(
+            &[
+                Message {
+                    role: "system".to_string(),
+                    content: build_system_prompt(ctx, crate::cortex::prompts::DECOMPOSE_PROMPT),
+                },
+                Message {
+                    role: "user".to_string(),
+                    content: build_user_prompt(ctx, query),
+                },
+            ],
+            JsonSchemaSpec {
+                name: "cortex_decompose".to_string(),
+                schema,
+            }
+        ).await?;
+        let plan = parse_json_response(&response)?;
+        on_event(CortexEvent::Thought(plan["thought"].as_str().unwrap_or("").to_string()));
+
+        let mut subtasks: Vec<String> = plan["subtasks"]
+            .as_array()
+            .map(|items| items.iter().filter_map(|t| t.as_str()).map(str::trim).filter(|t| !t.is_empty()).map(String::from).collect())
+            .unwrap_or_default();
+        subtasks.truncate(MAX_SUBAGENTS.min(max_steps.max(1)));
+        if subtasks.is_empty() {
+            subtasks.push(query.to_string());
+        }
+
+        let share = (max_steps / subtasks.len()).max(1);
+        let mut history = Vec::new();
+        let mut findings = String::new();
+        for (i, subtask) in subtasks.iter().enumerate() {
+            if let Some(reason) = llm.usage().budget_exceeded(&ctx.config) {
+                findings.push_str(&format!("### Sub-question {}: {}
+Not investigated: {}.
+
+", i + 1, subtask, reason));
+                continue;
+            }
+            on_event(CortexEvent::Thought(format!("Sub-agent {}: {}", i + 1, subtask)));
+
+            // Sub-agents number their steps from 1; keep the run's numbering continuous.
+            let offset = history.len();
+            let mut forward = |event: CortexEvent| match event {
+                CortexEvent::StepStart(n) => on_event(CortexEvent::StepStart(offset + n)),
+                CortexEvent::AnswerDelta(_) => {}
+                other => on_event(other),
+            };
+            let sub_query = format!("{}
+(Part of the question: {})", subtask, query);
+            ctx.config.max_steps = share;
+            let finding = ReactStrategy.run(ctx, llm, llm, &sub_query, &mut forward).await;
+            ctx.config.max_steps = max_steps;
+
+            for mut step in std::mem::take(&mut ctx.history) {
+                step.step_id += offset;
+                history.push(step);
+            }
+            findings.push_str(&format!("### Sub-question {}: {}
+{}
+
+", i + 1, subtask, finding?));
+        }
+        ctx.history = history;
+
+        if let Some(reason) = answer_llm.usage().budget_exceeded(&ctx.config) {
+            return Ok(format!("Stopped before writing the answer: {}.", reason));
+        }
+        let notes = format!("{}## Sub-agent Findings\n{}", observations(ctx), findings);
+        synthesize(ctx, answer_llm, query, &notes, on_event).await
+    }
+}
+
+/// Tokens of retrieved code packed into a RAG prompt.
+const RAG_CODE_TOKENS: usize = 6000;
+/// Tokens of repository map in a RAG prompt, and the depth it is walked to.
+const RAG_MAP_TOKENS: usize = 1500;
+const RAG_MAP_DEPTH: usize = 3;
+
+/// No tools and no loop: one hybrid search for the query, then a single call
+/// answering from the top `max_observations` chunks and an outline of the
+/// repository. Much faster and cheaper for questions the first search covers.
+pub struct RagStrategy;
+
+#[async_trait]
+impl LoopStrategy for RagStrategy {
+    fn name(&self) -> &str {
+        "rag"
+    }
+
+    async fn run(
+        &self,
+        ctx: &mut AgentContext,
+        _llm: &OpenAIProvider,
+        answer_llm: &OpenAIProvider,
+        query: &str,
+        on_event: &mut (dyn FnMut(CortexEvent) + Send),
+    ) -> Result<String> {
+        ctx.history.clear();
+        if let Some(reason) = answer_llm.usage().budget_exceeded(&ctx.config) {
+            return Ok(format!("Stopped before searching: {}.", reason));
+        }
+        on_event(CortexEvent::StepStart(1));
+
+        let limit = ctx.config.max_observations;
+        let args = serde_json::json!({ "query": query, "limit": limit });
+        on_event(CortexEvent::ToolCall { name: "search_code".to_string(), args: args.clone() });
+        let corrected = ctx.search_service.did_you_mean(query).await;
+        let hits = ctx.search_service.search(corrected.as_deref().unwrap_or(query), limit, None).await?;
+
+        let mut code = String::new();
+        let mut packed = Vec::new();
+        let mut used = 0;
+        for hit in &hits {
+            let path = match &hit.file.id {
+                surrealdb::sql::Id::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            let location = format!("{}:{}-{}", path, hit.start_line, hit.end_line);
+            let numbered: Vec<String> =
+                hit.content.lines().enumerate().map(|(i, line)| format!("{:>5} {}", hit.start_line + i, line)).collect();
+            let section = format!("### {}\n```\n{}\n```\n\n", location, numbered.join("\n"));
+            // Skip rather than stop, so a long chunk doesn't crowd out the ones after it.
+            let tokens = count_tokens(&section);
+            if used + tokens > RAG_CODE_TOKENS {
+                continue;
+            }
+            used += tokens;
+            code.push_str(&section);
+            packed.push(location);
+        }
+        let observation = if packed.is_empty() {
+            "No results found.".to_string()
+        } else {
+            format!("Retrieved {} chunks:\n{}", packed.len(), packed.join("\n"))
+        };
+        on_event(CortexEvent::ToolResult { name: "search_code".to_string(), result: observation.clone() });
+        ctx.add_step(Step {
+            step_id: 1,
+            thought: String::new(),
+            action: "search_code".to_string(),
+            args,
+            observation,
+            error: None,
+        });
+
+        let messages = vec![
+            Message {
+                role: "system".to_string(),
+                content: "You are Cortex, an AI coding agent. Answer the user's question from the retrieved code and \
+                          the repository map provided. Cite the code you rely on as `path:line` or `path:start-end`, \
+                          using the line numbers shown. If the code shown doesn't answer the question, say what is \
+                          missing instead of guessing."
+                    .to_string(),
+            },
+            Message {
+                role: "user".to_string(),
+                content: format!(
+                    "{}{}## Repository Map\n{}\n## Retrieved Code\n{}",
+                    build_user_prompt(ctx, query),
+                    ctx.overview.as_deref().map_or(String::new(), |o| format!("## Repository Overview\n{}\n\n", o)),
+                    repo_map(ctx),
+                    if code.is_empty() { "(no matching code)\n" } else { &code }
+                ),
+            },
+        ];
+        let mut on_delta = |delta: &str| on_event(CortexEvent::AnswerDelta(delta.to_string()));
+        answer_llm.chat_stream(&messages, Some(ctx.config.max_tokens), &mut on_delta).await
+    }
+}
+
+/// An outline of the repository, limited to the scope, within `RAG_MAP_TOKENS`.
+fn repo_map(ctx: &AgentContext) -> String {
+    let repo = &ctx.repo_context;
+    let Ok(mut entries) = codebase_entries(&repo.root, RAG_MAP_DEPTH, &repo.config.core.exclude_paths) else {
+        return String::new();
+    };
+    if let Some(scope) = &repo.scope {
+        entries.retain(|e| if e.is_dir { scope.may_contain(&e.path) } else { scope.contains(&e.path) });
+    }
+    clip_lines(&render_codebase_map(&repo.root, &entries), RAG_MAP_TOKENS)
+}
+
+/// The leading whole lines of `text` that fit in `max_tokens`, with a marker
+/// when some were cut.
+fn clip_lines(text: &str, max_tokens: usize) -> String {
+    let mut out = String::new();
+    let mut used = 0;
+    for line in text.lines() {
+        used += count_tokens(line) + 1;
+        if used > max_tokens {
+            out.push_str("…\n");
+            break;
+        }
+        out.push_str(line);
+        out.push('\n');
+    }
+    out
+}
+
+/// The observations of the steps taken so far, for a synthesis prompt.
+fn observations(ctx: &AgentContext) -> String {
+    ctx.history
+        .iter()
+        .map(|step| format!("### Step {}: {} {}\n{}\n\n", step.step_id, step.action, step.args, step.observation))
+        .collect()
+}
+
+/// Write the final answer from `notes` (observations, a draft) with `llm`,
+/// streaming it as `AnswerDelta`s.
+async fn synthesize(
+    ctx: &AgentContext,
+    llm: &OpenAIProvider,
+    query: &str,
+    notes: &str,
+    on_event: &mut (dyn FnMut(CortexEvent) + Send),
+) -> Result<String> {
+    let messages = vec![
+        Message {
+            role: "system".to_string(),
+            content: "You are Cortex, an AI coding agent. Answer the user's question using only the observations provided. Cite file paths where relevant.".to_string(),
+        },
+        Message {
+            role: "user".to_string(),
+            content: format!("{}## Observations\n{}", build_user_prompt(ctx, query), notes),
+        },
+    ];
+
+    let mut on_delta = |delta: &str| on_event(CortexEvent::AnswerDelta(delta.to_string()));
+    llm.chat_stream(&messages, Some(ctx.config.max_tokens), &mut on_delta).await
+}
+
+/// Extracts `args.answer` from a streamed ReAct step while it is generated.
+///
+/// Stays silent unless the step's action is `final_answer` and the answer is a
+/// JSON string; incomplete escape sequences are held back until the next delta.
+#[derive(Default)]
+struct AnswerStream {
+    buf: String,
+    pos: Option<usize>,
+    finished: bool,
+}
+
+impl AnswerStream {
+    fn push(&mut self, delta: &str) -> String {
+        self.buf.push_str(delta);
+        if self.finished {
+            return String::new();
+        }
+
+        let start = match self.pos {
+            Some(pos) => pos,
+            None => {
+                if json_string_after(&self.buf, "\"action\"").map(|v| v.starts_with("\"final_answer\"")) != Some(true) {
+                    return String::new();
+                }
+                let Some(value) = json_string_after(&self.buf, "\"answer\"") else {
+                    return String::new();
+                };
+                if !value.starts_with('"') {
+                    // Non-string answers are rendered once the step completes.
+                    self.finished = !value.is_empty();
+                    return String::new();
+                }
+                self.buf.len() - value.len() + 1
+            }
+        };
+
+        let mut out = String::new();
+        let rest = &self.buf[start..];
+        let mut consumed = 0;
+        let mut chars = rest.char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.finished = true;
+                    consumed = i + 1;
+                    break;
+                }
+                '\\' => {
+                    let Some((_, esc)) = chars.next() else { break };
+                    match esc {
+                        'n' => out.push('\n'),
+                        't' => out.push('\t'),
+                        'r' => out.push('\r'),
+                        'u' => {
+                            let Some(hex) = rest.get(i + 2..i + 6) else { break };
+                            if let Some(ch) = u32::from_str_radix(hex, 16).ok().and_then(char::from_u32) {
+                                out.push(ch);
+                            }
+                            chars.nth(3);
+                            consumed = i + 6;
+                            continue;
+                        }
+                        other => out.push(other),
+                    }
+                    consumed = i + 2;
+                }
+                c => {
+                    out.push(c);
+                    consumed = i + c.len_utf8();
+                }
+            }
+        }
+        self.pos = Some(start + consumed);
+        out
+    }
+}
+
+/// The text following `key` and its colon, or `None` if the colon has not arrived yet.
+fn json_string_after<'a>(buf: &'a str, key: &str) -> Option<&'a str> {
+    let rest = &buf[buf.find(key)? + key.len()..];
+    rest.trim_start().strip_prefix(':').map(|v| v.trim_start())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stream(chunks: &[&str]) -> String {
+        let mut s = AnswerStream::default();
+        chunks.iter().map(|c| s.push(c)).collect()
+    }
+
+    #[test]
+    fn test_answer_stream_extracts_final_answer() {
+        let out = stream(&[
+            r#"{"thought":"done","action":"final_"#,
+            r#"answer","args":{"answer":"Line one\"#,
+            r#"nLine \"two\"""#,
+            r#"}}"#,
+        ]);
+        assert_eq!(out, "Line one\nLine \"two\"");
+    }
+
+    #[test]
+    fn test_answer_stream_ignores_tool_steps() {
+        let out = stream(&[r#"{"thought":"look","action":"search_code","args":{"query":"answer"}}"#]);
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn test_clip_lines_keeps_whole_lines_within_budget() {
+        let text = "- src/\n  - lib.rs\n  - main.rs\n";
+        assert_eq!(clip_lines(text, 1000), text);
+        let clipped = clip_lines(text, count_tokens("- src/") + 1);
+        assert_eq!(clipped, "- src/\n…\n");
+    }
+}
@@ -0,0 +1,274 @@
+use crate::cortex::tool::Tool;
+use crate::project::context::RepoContext;
+use super::shell::resolve;
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
+use emry_core::diff::file_diff;
+use emry_core::patch::parse_patch;
+use emry_core::scope::PathScope;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// A file as it is on disk and as the agent proposes it; `None` means the
+/// file doesn't (or shouldn't) exist.
+#[derive(Debug, Clone)]
+struct StagedFile {
+    original: Option<String>,
+    proposed: Option<String>,
+}
+
+/// Edits proposed by `write_file` and `apply_patch` during a run. Nothing is
+/// written: the caller shows [`PendingEdits::patch`] for review and applies
+/// it once confirmed.
+#[derive(Clone, Default)]
+pub struct PendingEdits(Arc<Mutex<BTreeMap<PathBuf, StagedFile>>>);
+
+impl PendingEdits {
+    pub fn is_empty(&self) -> bool {
+        self.0.lock().unwrap().values().all(|f| f.original == f.proposed)
+    }
+
+    /// Files with a proposed change, relative to the repository root.
+    pub fn files(&self) -> Vec<PathBuf> {
+        self.0.lock().unwrap().iter().filter(|(_, f)| f.original != f.proposed).map(|(p, _)| p.clone()).collect()
+    }
+
+    /// All proposed changes as one unified diff against the files as they
+    /// were when first edited.
+    pub fn patch(&self) -> String {
+        self.0
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(path, f)| file_diff(&path.to_string_lossy(), f.original.as_deref(), f.proposed.as_deref()))
+            .collect()
+    }
+
+    pub fn clear(&self) {
+        self.0.lock().unwrap().clear();
+    }
+
+    /// `rel` with the edits proposed so far, else as on disk.
+    fn current(&self, root: &Path, rel: &Path) -> Result<Option<String>> {
+        if let Some(staged) = self.0.lock().unwrap().get(rel) {
+            return Ok(staged.proposed.clone());
+        }
+        read_existing(&root.join(rel))
+    }
+
+    /// Propose `content` for `rel`, diffing later against the file on disk.
+    fn stage(&self, root: &Path, rel: &Path, content: Option<String>) -> Result<String> {
+        let mut files = self.0.lock().unwrap();
+        if !files.contains_key(rel) {
+            let original = read_existing(&root.join(rel))?;
+            files.insert(rel.to_path_buf(), StagedFile { proposed: original.clone(), original });
+        }
+        let staged = files.get_mut(rel).unwrap();
+        let diff = file_diff(&rel.to_string_lossy(), staged.proposed.as_deref(), content.as_deref());
+        staged.proposed = content;
+        Ok(diff)
+    }
+}
+
+fn read_existing(path: &Path) -> Result<Option<String>> {
+    match std::fs::read_to_string(path) {
+        Ok(text) => Ok(Some(text)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e).with_context(|| format!("failed to read {}", path.display())),
+    }
+}
+
+/// `path` relative to the repository `root`, refusing paths that leave it
+/// (also through symlinks), point into `.git` or `.codeindex`, or fall
+/// outside `scope`.
+fn editable_path(root: &Path, scope: Option<&PathScope>, path: &str) -> Result<PathBuf> {
+    let path = Path::new(path);
+    let rel = path.strip_prefix(root).unwrap_or(path);
+    if rel.as_os_str().is_empty() || rel.components().any(|c| !matches!(c, Component::Normal(_) | Component::CurDir)) {
+        bail!("'{}' is not a file path inside the repository", path.display());
+    }
+    let rel: PathBuf = rel.components().filter(|c| *c != Component::CurDir).collect();
+    let canonical_root = root.canonicalize().context("failed to resolve the repository root")?;
+    let Ok(resolved) = resolve(&canonical_root.join(&rel)).strip_prefix(&canonical_root).map(Path::to_path_buf) else {
+        bail!("'{}' leads outside the repository", rel.display());
+    };
+    if [&rel, &resolved].iter().any(|p| p.starts_with(".git") || p.starts_with(".codeindex")) {
+        bail!("'{}' is not editable", rel.display());
+    }
+    if let Some(scope) = scope {
+        if !scope.contains(&rel) {
+            bail!("'{}' is outside the scope ({})", rel.display(), scope.patterns().join(", "));
+        }
+    }
+    Ok(rel)
+}
+
+const STAGED_NOTE: &str = "Staged, not written: all proposed edits are shown to the user as one diff to confirm after your final answer.";
+
+/// Proposes a file's full new content.
+pub struct WriteFileTool {
+    ctx: Arc<RepoContext>,
+    edits: PendingEdits,
+}
+
+impl WriteFileTool {
+    pub fn new(ctx: Arc<RepoContext>, edits: PendingEdits) -> Self {
+        Self { ctx, edits }
+    }
+}
+
+#[async_trait]
+impl Tool for WriteFileTool {
+    fn name(&self) -> &str {
+        "write_file"
+    }
+
+    fn description(&self) -> &str {
+        "Propose the complete new content of a file, creating it if it doesn't exist. Best for new or short files; use apply_patch for targeted changes to existing ones. Returns the resulting diff."
+    }
+
+    fn schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "path": { "type": "string", "description": "File path relative to the repository root." },
+                "content": { "type": "string", "description": "The file's entire new content." }
+            },
+            "required": ["path", "content"]
+        })
+    }
+
+    async fn execute(&self, args: Value) -> Result<String> {
+        let path = args["path"].as_str().ok_or_else(|| anyhow!("Missing 'path' argument"))?;
+        let content = args["content"].as_str().ok_or_else(|| anyhow!("Missing 'content' argument"))?;
+        let rel = editable_path(&self.ctx.root, self.ctx.scope.as_ref(), path)?;
+        let diff = self.edits.stage(&self.ctx.root, &rel, Some(content.to_string()))?;
+        if diff.is_empty() {
+            return Ok(format!("{} already has this content; nothing to change.", rel.display()));
+        }
+        Ok(format!("{}\n\n{}", STAGED_NOTE, diff))
+    }
+}
+
+/// Proposes changes given as a unified diff.
+pub struct ApplyPatchTool {
+    ctx: Arc<RepoContext>,
+    edits: PendingEdits,
+}
+
+impl ApplyPatchTool {
+    pub fn new(ctx: Arc<RepoContext>, edits: PendingEdits) -> Self {
+        Self { ctx, edits }
+    }
+}
+
+#[async_trait]
+impl Tool for ApplyPatchTool {
+    fn name(&self) -> &str {
+        "apply_patch"
+    }
+
+    fn description(&self) -> &str {
+        "Propose changes as a unified diff (`--- a/path`, `+++ b/path`, `@@` hunks with 3 lines of context; `/dev/null` to create or delete a file). Hunks are matched against the file including edits proposed earlier, even if line numbers are off. Returns the resulting diff, or which hunks did not match."
+    }
+
+    fn schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "patch": { "type": "string", "description": "Unified diff of one or more files." }
+            },
+            "required": ["patch"]
+        })
+    }
+
+    async fn execute(&self, args: Value) -> Result<String> {
+        let text = args["patch"].as_str().ok_or_else(|| anyhow!("Missing 'patch' argument"))?;
+        let patches = parse_patch(text)?;
+        if patches.is_empty() {
+            bail!("the patch contains no file changes");
+        }
+
+        // Check every file before staging any, so a bad hunk leaves nothing half-applied.
+        let mut results = Vec::new();
+        for patch in &patches {
+            let rel = editable_path(&self.ctx.root, self.ctx.scope.as_ref(), &patch.path.to_string_lossy())?;
+            let result = patch.apply(self.edits.current(&self.ctx.root, &rel)?.as_deref());
+            if result.has_conflicts() {
+                let lines: Vec<String> = result
+                    .hunks
+                    .iter()
+                    .filter(|h| h.status == emry_core::patch::HunkStatus::Conflict)
+                    .map(|h| h.old_start.to_string())
+                    .collect();
+                bail!(
+                    "hunks at line(s) {} of {} don't match the file; read it again and resend the whole patch",
+                    lines.join(", "),
+                    rel.display()
+                );
+            }
+            results.push((rel, result.content));
+        }
+        let mut diff = String::new();
+        for (rel, content) in results {
+            diff.push_str(&self.edits.stage(&self.ctx.root, &rel, content)?);
+        }
+        Ok(format!("{}\n\n{}", STAGED_NOTE, diff))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_staged_edits_compose_against_disk() -> Result<()> {
+        let temp = TempDir::new()?;
+        let root = temp.path();
+        std::fs::write(root.join("lib.rs"), "fn a() {}\nfn b() {}\n")?;
+        let edits = PendingEdits::default();
+
+        edits.stage(root, Path::new("lib.rs"), Some("fn a() {}\nfn b() { todo!() }\n".to_string()))?;
+        let current = edits.current(root, Path::new("lib.rs"))?.unwrap();
+        edits.stage(root, Path::new("lib.rs"), Some(current.replace("fn a()", "pub fn a()")))?;
+        edits.stage(root, Path::new("new.rs"), Some("mod lib;\n".to_string()))?;
+
+        assert_eq!(edits.files(), vec![PathBuf::from("lib.rs"), PathBuf::from("new.rs")]);
+        let patch = edits.patch();
+        assert!(patch.contains("-fn a() {}\n-fn b() {}\n+pub fn a() {}\n+fn b() { todo!() }\n"));
+        assert!(patch.contains("--- /dev/null\n+++ b/new.rs\n"));
+        assert_eq!(std::fs::read_to_string(root.join("lib.rs"))?, "fn a() {}\nfn b() {}\n");
+        Ok(())
+    }
+
+    #[test]
+    fn test_editable_path_stays_in_repository() -> Result<()> {
+        let temp = TempDir::new()?;
+        let outside = TempDir::new()?;
+        let root = temp.path();
+        std::fs::create_dir_all(root.join(".git"))?;
+        let editable = |path: &str| editable_path(root, None, path);
+
+        assert_eq!(editable("src/lib.rs")?, PathBuf::from("src/lib.rs"));
+        assert_eq!(editable("./src/lib.rs")?, PathBuf::from("src/lib.rs"));
+        assert_eq!(editable(root.join("src/lib.rs").to_str().unwrap())?, PathBuf::from("src/lib.rs"));
+        assert!(editable("../other/lib.rs").is_err());
+        assert!(editable("src/../../lib.rs").is_err());
+        assert!(editable("/etc/passwd").is_err());
+        assert!(editable(".git/config").is_err());
+        assert!(editable(".codeindex/branches/main/surreal.db").is_err());
+
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(outside.path(), root.join("docs"))?;
+            std::os::unix::fs::symlink(root.join(".git"), root.join("meta"))?;
+            assert!(editable("docs/passwd").is_err());
+            assert!(editable("docs/new/file.md").is_err());
+            assert!(editable("meta/config").is_err());
+        }
+        Ok(())
+    }
+}
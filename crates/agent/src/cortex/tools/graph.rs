@@ -273,3 +273,111 @@ impl Tool for GetTypeDefinitionTool {
     }
 }
 
+
+pub struct FindPathsTool {
+    inner: Arc<InnerGraphTool>,
+}
+
+impl FindPathsTool {
+    pub fn new(inner: Arc<InnerGraphTool>) -> Self {
+        Self { inner }
+    }
+}
+
+#[async_trait]
+impl Tool for FindPathsTool {
+    fn name(&self) -> &str {
+        "find_paths"
+    }
+
+    fn description(&self) -> &str {
+        "Find the shortest call/import chains between two symbols. Returns each path as a readable chain with file:line for every hop. Use this to answer 'how does X reach Y?' questions."
+    }
+
+    fn schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "from": {
+                    "type": "string",
+                    "description": "The Node ID or name of the starting symbol."
+                },
+                "to": {
+                    "type": "string",
+                    "description": "The Node ID or name of the destination symbol."
+                },
+                "file_filter": {
+                    "type": "string",
+                    "description": "Optional file path filter used when resolving names to nodes."
+                },
+                "max_hops": {
+                    "type": "integer",
+                    "default": 6
+                },
+                "max_paths": {
+                    "type": "integer",
+                    "default": 3
+                }
+            },
+            "required": ["from", "to"]
+        })
+    }
+
+    async fn execute(&self, args: Value) -> Result<String> {
+        let from = args["from"]
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("Missing 'from' argument"))?;
+        let to = args["to"]
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("Missing 'to' argument"))?;
+        let file_filter = args["file_filter"].as_str();
+        let max_hops = args["max_hops"].as_u64().unwrap_or(6) as usize;
+        let max_paths = args["max_paths"].as_u64().unwrap_or(3) as usize;
+
+        let result = match self.inner.shortest_paths(from, to, max_hops, max_paths, file_filter).await {
+            Ok(result) => result,
+            Err(e) if e.to_string().contains("not found") => {
+                return Ok(format!("{} Try 'search_code' or 'go_to_definition' to find the exact name or ID.", e));
+            }
+            Err(e) => return Err(e),
+        };
+
+        if let Some(candidates) = result.candidates {
+            let mut response = String::from("An endpoint is ambiguous. Candidates:\n\n");
+            for (i, cand) in candidates.iter().enumerate() {
+                response.push_str(&format!(
+                    "{}. {} ({})\n   File: {}\n   ID: {}\n\n",
+                    i + 1, cand.label, cand.kind, cand.file_path, cand.id
+                ));
+            }
+            response.push_str("Please call 'find_paths' again with specific node IDs or a 'file_filter'.");
+            return Ok(response);
+        }
+
+        if result.paths.is_empty() {
+            return Ok(format!("No call/import path from '{}' to '{}' within {} hops.", from, to, max_hops));
+        }
+
+        let mut out = format!("Found {} shortest path(s) from '{}' to '{}':\n\n", result.paths.len(), from, to);
+        for (i, path) in result.paths.iter().enumerate() {
+            out.push_str(&format!("{}. ({} hops)\n", i + 1, path.relations.len()));
+            for (j, hop) in path.hops.iter().enumerate() {
+                let location = match hop.line {
+                    Some(line) => format!("{}:{}", hop.file_path, line),
+                    None => hop.file_path.clone(),
+                };
+                if j == 0 {
+                    out.push_str(&format!("   {} ({}) [{}]\n", hop.label, hop.kind, location));
+                } else {
+                    out.push_str(&format!(
+                        "   --{}--> {} ({}) [{}]\n",
+                        path.relations[j - 1], hop.label, hop.kind, location
+                    ));
+                }
+            }
+            out.push('\n');
+        }
+
+        Ok(out)
+    }
+}
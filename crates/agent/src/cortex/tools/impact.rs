@@ -9,6 +9,7 @@ use emry_engine::search::service::SearchService;
 use crate::ops::fs::FsTool;
 use crate::ops::graph::GraphTool;
 use crate::cortex::CortexEvent;
+use emry_core::diff::FileDiff;
 
 use crate::workflows::impact::ImpactWorkflow;
 
@@ -33,6 +34,12 @@ impl AnalyzeImpactTool {
     {
         self.workflow.run_analysis(file_path, start_line, end_line, callback).await
     }
+
+    pub async fn run_diff_analysis<F>(&self, change: &str, diffs: &[FileDiff], callback: F) -> Result<String>
+    where F: FnMut(CortexEvent) + Send + Sync + 'static
+    {
+        self.workflow.run_diff_analysis(change, diffs, callback).await
+    }
 }
 
 #[async_trait]
@@ -5,3 +5,6 @@ pub mod workflows;
 pub mod architecture;
 pub mod impact;
 pub mod focus;
+pub mod edit;
+pub mod registry;
+pub mod shell;
@@ -0,0 +1,96 @@
+use crate::cortex::tool::Tool;
+use crate::cortex::tools::{
+    architecture::DescribeArchitectureTool,
+    edit::{ApplyPatchTool, PendingEdits, WriteFileTool},
+    focus::FocusTool,
+    fs::{ListFilesTool, ReadFileTool, ViewCodeItemTool, ViewCodebaseMapTool, ViewFileOutlineTool},
+    graph::{FindPathsTool, FindReferencesTool, GetTypeDefinitionTool, GoToDefinitionTool, InspectGraphTool},
+    impact::AnalyzeImpactTool,
+    search::SearchCodeTool,
+    shell::RunCommandTool,
+    workflows::{ExploreModuleTool, FindUsagesTool, ReadFilesTool as ReadFilesMacroTool},
+};
+use crate::llm::OpenAIProvider;
+use crate::ops::architecture::ArchitectureTool;
+use crate::ops::fs::FsTool;
+use crate::ops::graph::GraphTool;
+use crate::ops::search::Search;
+use crate::project::context::RepoContext;
+use anyhow::Result;
+use emry_engine::search::service::SearchService;
+use std::sync::Arc;
+
+/// The standard tool set exposed to Cortex and to external clients.
+///
+/// Tools that run their own LLM workflows (architecture, impact) are only
+/// included when an `llm` is provided. `run_command` is never part of this
+/// set, since MCP clients are served it too; see `shell_tools`.
+pub fn default_tools(
+    ctx: Arc<RepoContext>,
+    search_service: Arc<SearchService>,
+    llm: Option<&OpenAIProvider>,
+) -> Result<Vec<Arc<dyn Tool>>> {
+    let search_impl = Arc::new(Search::new(ctx.clone(), search_service.clone()));
+    let graph_impl = Arc::new(GraphTool::new(ctx.clone()));
+    let fs_impl = Arc::new(FsTool::new(ctx.clone()));
+
+    let mut tools: Vec<Arc<dyn Tool>> = vec![
+        Arc::new(SearchCodeTool::new(search_impl)),
+        Arc::new(InspectGraphTool::new(graph_impl.clone(), ctx.clone())),
+        Arc::new(ReadFileTool::new(fs_impl.clone())),
+        Arc::new(ListFilesTool::new(fs_impl.clone())),
+        Arc::new(ViewFileOutlineTool::new(fs_impl.clone())),
+        Arc::new(ViewCodeItemTool::new(fs_impl.clone())),
+        Arc::new(ViewCodebaseMapTool::new(fs_impl.clone())),
+        Arc::new(FindReferencesTool::new(graph_impl.clone())),
+        Arc::new(GoToDefinitionTool::new(graph_impl.clone())),
+        Arc::new(GetTypeDefinitionTool::new(graph_impl.clone())),
+        Arc::new(FindPathsTool::new(graph_impl.clone())),
+        Arc::new(ReadFilesMacroTool::new(fs_impl.clone())),
+        Arc::new(ExploreModuleTool::new(fs_impl.clone())),
+        Arc::new(FindUsagesTool::new(graph_impl.clone())),
+    ];
+
+    if let Some(llm) = llm {
+        let arch_impl = Arc::new(ArchitectureTool::new(ctx.clone()));
+        tools.push(Arc::new(DescribeArchitectureTool::new(
+            arch_impl,
+            fs_impl.clone(),
+            llm.clone(),
+            ctx.clone(),
+            search_service.clone(),
+        )));
+        tools.push(Arc::new(AnalyzeImpactTool::new(
+            ctx.clone(),
+            llm.clone(),
+            fs_impl.clone(),
+            graph_impl.clone(),
+            search_service.clone(),
+        )?));
+    }
+
+    tools.push(Arc::new(FocusTool::new(ctx)?));
+
+    Ok(tools)
+}
+
+/// The tools of edit mode (`emry ask --edit`). They stage changes in `edits`
+/// rather than writing them, for the caller to show and apply once confirmed.
+pub fn edit_tools(ctx: Arc<RepoContext>, edits: PendingEdits) -> Vec<Arc<dyn Tool>> {
+    vec![
+        Arc::new(WriteFileTool::new(ctx.clone(), edits.clone())),
+        Arc::new(ApplyPatchTool::new(ctx, edits)),
+    ]
+}
+
+/// The tools of the interactive agents (`emry ask`, `emry chat`) that run
+/// programs: `run_command` when `agent.allow_shell` is set. Not offered over
+/// MCP, whose clients are any local process.
+pub fn shell_tools(ctx: Arc<RepoContext>) -> Vec<Arc<dyn Tool>> {
+    // Commands could read and write past the scope and read-only mode.
+    if ctx.config.agent.allow_shell && !ctx.config.core.read_only && ctx.scope.is_none() {
+        vec![Arc::new(RunCommandTool::new(ctx))]
+    } else {
+        Vec::new()
+    }
+}
@@ -13,6 +13,31 @@ impl SearchCodeTool {
     pub fn new(inner: Arc<InnerSearchTool>) -> Self {
         Self { inner }
     }
+
+    async fn execute_many(&self, queries: &[String], limit: usize) -> Result<String> {
+        if queries.is_empty() {
+            return Err(anyhow::anyhow!("'queries' must contain at least one query"));
+        }
+        let hits = self.inner.search_many(queries, limit).await?;
+        if hits.is_empty() {
+            return Ok("No results found.".to_string());
+        }
+
+        let mut out = String::new();
+        for hit in hits {
+            let file_id = hit.chunk.file.id.to_string();
+            let matched: Vec<&str> = hit.queries.iter().map(|&i| queries[i].as_str()).collect();
+            out.push_str(&format!(
+                "File: {}:{}-{}\n  Matched: {}\n  Content:\n    {}\n\n",
+                file_id.strip_prefix("file:").unwrap_or(&file_id),
+                hit.chunk.start_line,
+                hit.chunk.end_line,
+                matched.join(", "),
+                hit.chunk.content.trim().replace('\n', "\n    ")
+            ));
+        }
+        Ok(out)
+    }
 }
 
 #[async_trait]
@@ -22,7 +47,7 @@ impl Tool for SearchCodeTool {
     }
 
     fn description(&self) -> &str {
-        "Search the codebase for code snippets using semantic and lexical search. Use this to find relevant code when you have a general idea or keywords."
+        "Search the codebase for code snippets using semantic and lexical search. Use this to find relevant code when you have a general idea or keywords. Pass 'keywords' to also match chunks by their indexed tags, or 'queries' to run several searches at once."
     }
 
     fn schema(&self) -> Value {
@@ -33,29 +58,52 @@ impl Tool for SearchCodeTool {
                     "type": "string",
                     "description": "The search query (e.g., 'feature name', 'error message')"
                 },
+                "keywords": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Domain terms (e.g., 'jwt', 'session'); chunks tagged with them are included"
+                },
+                "queries": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Several queries to run concurrently; overlapping results are merged"
+                },
                 "limit": {
                     "type": "integer",
                     "description": "Max number of results (default: 10)",
                     "default": 10
                 }
             },
-            "required": ["query"]
+            "required": []
         })
     }
 
     async fn execute(&self, args: Value) -> Result<String> {
+        if let Some(list) = args["queries"].as_array() {
+            let queries: Vec<String> = list.iter().filter_map(|q| q.as_str().map(String::from)).collect();
+            let limit = args["limit"].as_u64().unwrap_or(10) as usize;
+            return self.execute_many(&queries, limit).await;
+        }
+
         let query = args["query"]
             .as_str()
-            .ok_or_else(|| anyhow::anyhow!("Missing 'query' argument"))?;
+            .ok_or_else(|| anyhow::anyhow!("Missing 'query' or 'queries' argument"))?;
         let limit = args["limit"].as_u64().unwrap_or(10) as usize;
+        let keywords: Option<Vec<String>> = args["keywords"]
+            .as_array()
+            .map(|list| list.iter().filter_map(|k| k.as_str().map(String::from)).collect());
+
+        let context_graph = self.inner.search_with_context(query, limit, keywords.as_deref()).await?;
+        let note = match self.inner.did_you_mean(query).await {
+            Some(corrected) => format!("Searched for '{}' instead of '{}' (misspelled identifier).\n\n", corrected, query),
+            None => String::new(),
+        };
 
-        let context_graph = self.inner.search_with_context(query, limit, true).await?;
-        
         if context_graph.anchors.is_empty() {
-            return Ok("No results found.".to_string());
+            return Ok(format!("{}No results found.", note));
         }
 
-        let mut out = String::new();
+        let mut out = note;
         
         let grouped = context_graph.group_by_symbol();
 
@@ -66,6 +114,12 @@ impl Tool for SearchCodeTool {
 
             out.push_str(&format!("Symbol: {} ({})\n", group.symbol.name, group.symbol.kind));
             out.push_str(&format!("  File: {}:{}-{}\n", group.symbol.file_path.display(), start_line, end_line));
+            let mut tags: Vec<&str> = group.anchors.iter().flat_map(|a| a.chunk.tags.iter().map(String::as_str)).collect();
+            let mut seen = std::collections::HashSet::new();
+            tags.retain(|t| seen.insert(*t));
+            if !tags.is_empty() {
+                out.push_str(&format!("  Tags: {}\n", tags.join(", ")));
+            }
             
             if !group.calls.is_empty() {
                 out.push_str("  Calls: ");
@@ -85,11 +139,17 @@ impl Tool for SearchCodeTool {
             out.push_str("Other Matches:\n");
             for anchor in grouped.unassigned {
                 out.push_str(&format!(
-                    "  File: {}\n  Line {}-{}: {:.2}\n  Content:\n    {}\n\n",
+                    "  File: {}\n  Line {}-{}: {:.2}\n",
                     anchor.chunk.file_path.display(),
                     anchor.chunk.start_line,
                     anchor.chunk.end_line,
                     anchor.score,
+                ));
+                if !anchor.chunk.tags.is_empty() {
+                    out.push_str(&format!("  Tags: {}\n", anchor.chunk.tags.join(", ")));
+                }
+                out.push_str(&format!(
+                    "  Content:\n    {}\n\n",
                     anchor.chunk.content.trim().replace('\n', "\n    ")
                 ));
             }
@@ -0,0 +1,229 @@
+use crate::cortex::tool::Tool;
+use crate::project::context::RepoContext;
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::path::{Component, Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Characters of each output stream returned to the model; the middle of
+/// longer output is cut, since errors and summaries sit at the ends.
+const MAX_OUTPUT_CHARS: usize = 6000;
+
+/// Runs an allow-listed program (`agent.shell_commands`) in the repository,
+/// without a shell, and returns its exit status and output. Only registered
+/// when `agent.allow_shell` is set. Not a sandbox: see `check_command`.
+pub struct RunCommandTool {
+    ctx: Arc<RepoContext>,
+    description: String,
+}
+
+impl RunCommandTool {
+    pub fn new(ctx: Arc<RepoContext>) -> Self {
+        let description = format!(
+            "Run a command in the repository and return its exit status, stdout and stderr, e.g. to type-check, \
+             run tests or count matches. Runs without a shell (no pipes, redirects or globs); the program must be \
+             one of: {}. Arguments may not name absolute paths or leave the repository.",
+            ctx.config.agent.shell_commands.join(", ")
+        );
+        Self { ctx, description }
+    }
+
+    /// The directory to run in: `cwd` relative to the repository root, which
+    /// it may not leave.
+    fn working_dir(&self, cwd: Option<&str>) -> Result<PathBuf> {
+        let root = self.ctx.root.canonicalize().context("failed to resolve the repository root")?;
+        let Some(cwd) = cwd.filter(|c| !c.is_empty()) else { return Ok(root) };
+        let dir = root
+            .join(cwd)
+            .canonicalize()
+            .map_err(|_| anyhow!("working directory '{}' does not exist", cwd))?;
+        if !dir.starts_with(&root) || !dir.is_dir() {
+            bail!("working directory '{}' is not a directory inside the repository", cwd);
+        }
+        Ok(dir)
+    }
+}
+
+/// Options that make a program run another one: `rg --pre` pipes each file
+/// through a command of the caller's choosing.
+const EXEC_OPTIONS: &[(&str, &[&str])] = &[("rg", &["--pre", "--pre-glob"])];
+
+/// Refuse programs outside `allowed`, options that run other programs, and
+/// arguments reaching outside `root` from `dir`, the working directory.
+///
+/// This only confines paths named on the command line: allowed programs can
+/// still run project code (`cargo test`, `cargo build` with a `build.rs`,
+/// `go run`), which may do anything.
+fn check_command(program: &str, args: &[String], allowed: &[String], dir: &Path, root: &Path) -> Result<()> {
+    if !allowed.iter().any(|a| a == program) {
+        bail!("'{}' is not an allowed command; allowed: {}", program, allowed.join(", "));
+    }
+    let exec_options = EXEC_OPTIONS.iter().find(|(p, _)| *p == program).map_or(&[][..], |(_, o)| *o);
+    for arg in args {
+        // `--manifest-path=/x` hides a path behind a flag.
+        let (flag, value) = arg.split_once('=').unwrap_or((arg.as_str(), arg.as_str()));
+        if exec_options.contains(&flag) {
+            bail!("'{} {}' runs other programs and is not allowed", program, flag);
+        }
+        // So does `-C/tmp`, a short option with its value attached.
+        let attached = arg
+            .strip_prefix('-')
+            .filter(|rest| rest.starts_with(|c: char| c.is_ascii_alphabetic()))
+            .map(|rest| &rest[1..])
+            .filter(|rest| !rest.is_empty());
+        for value in std::iter::once(value).chain(attached) {
+            if !inside(value, dir, root) {
+                bail!("argument '{}' points outside the repository", arg);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Whether `value`, taken as a path from `dir`, stays inside `root`.
+fn inside(value: &str, dir: &Path, root: &Path) -> bool {
+    let path = Path::new(value);
+    if path.is_absolute() || value.starts_with('~') || path.components().any(|c| c == Component::ParentDir) {
+        return false;
+    }
+    // A symlink inside the repository may still lead out of it.
+    resolve(&dir.join(path)).starts_with(root)
+}
+
+/// `path` with its longest existing prefix canonicalized, so symlinks along
+/// it are followed even when the rest does not exist yet.
+pub(super) fn resolve(path: &Path) -> PathBuf {
+    for existing in path.ancestors() {
+        if let Ok(resolved) = existing.canonicalize() {
+            let rest = path.strip_prefix(existing).unwrap_or(Path::new(""));
+            return if rest.as_os_str().is_empty() { resolved } else { resolved.join(rest) };
+        }
+    }
+    path.to_path_buf()
+}
+
+/// `text` with its middle replaced by a marker when longer than `max` characters.
+fn truncate_middle(text: &str, max: usize) -> String {
+    let total = text.chars().count();
+    if total <= max {
+        return text.to_string();
+    }
+    let head: String = text.chars().take(max / 2).collect();
+    let tail: String = text.chars().skip(total - max / 2).collect();
+    format!("{}\n… {} characters omitted …\n{}", head, total - head.chars().count() - tail.chars().count(), tail)
+}
+
+#[async_trait]
+impl Tool for RunCommandTool {
+    fn name(&self) -> &str {
+        "run_command"
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "command": {
+                    "type": "string",
+                    "description": "Program to run, e.g. 'cargo'."
+                },
+                "args": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Arguments, one per item, e.g. ['check', '--quiet']."
+                },
+                "cwd": {
+                    "type": "string",
+                    "description": "Directory to run in, relative to the repository root (default: the root)."
+                }
+            },
+            "required": ["command"]
+        })
+    }
+
+    async fn execute(&self, args: Value) -> Result<String> {
+        let config = &self.ctx.config.agent;
+        let program = args["command"].as_str().ok_or_else(|| anyhow!("Missing 'command' argument"))?;
+        let arguments: Vec<String> = args["args"]
+            .as_array()
+            .map(|a| a.iter().map(|v| v.as_str().map_or_else(|| v.to_string(), str::to_string)).collect())
+            .unwrap_or_default();
+        let dir = self.working_dir(args["cwd"].as_str())?;
+        let root = self.ctx.root.canonicalize().context("failed to resolve the repository root")?;
+        check_command(program, &arguments, &config.shell_commands, &dir, &root)?;
+
+        let mut command = tokio::process::Command::new(program);
+        command
+            .args(&arguments)
+            .current_dir(&dir)
+            .stdin(Stdio::null())
+            .kill_on_drop(true);
+        let timeout = Duration::from_secs(config.shell_timeout_secs);
+        let output = match tokio::time::timeout(timeout, command.output()).await {
+            Ok(output) => output.with_context(|| format!("failed to run '{}'", program))?,
+            Err(_) => bail!("'{}' did not finish within {}s (agent.shell_timeout_secs)", program, config.shell_timeout_secs),
+        };
+
+        let status = match output.status.code() {
+            Some(code) => format!("exit code {}", code),
+            None => "killed by a signal".to_string(),
+        };
+        let mut result = format!("$ {} {}\n({})\n", program, arguments.join(" "), status);
+        for (name, stream) in [("stdout", &output.stdout), ("stderr", &output.stderr)] {
+            let text = String::from_utf8_lossy(stream);
+            if !text.trim().is_empty() {
+                result.push_str(&format!("--- {} ---\n{}\n", name, truncate_middle(text.trim_end(), MAX_OUTPUT_CHARS)));
+            }
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_command_confines_program_and_paths() -> Result<()> {
+        let repo = tempfile::tempdir()?;
+        let outside = tempfile::tempdir()?;
+        let root = repo.path().canonicalize()?;
+        std::fs::create_dir(root.join("src"))?;
+        let allowed = vec!["cargo".to_string(), "rg".to_string()];
+        let args = |a: &[&str]| a.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let check = |program: &str, a: &[&str]| check_command(program, &args(a), &allowed, &root, &root);
+
+        assert!(check("cargo", &["check", "-p", "emry-agent"]).is_ok());
+        assert!(check("rg", &["fn main", "src/"]).is_ok());
+        assert!(check("rg", &["todo", "src/new.rs"]).is_ok());
+        assert!(check("sh", &["-c", "ls"]).is_err());
+        assert!(check("rg", &["secret", "/etc"]).is_err());
+        assert!(check("rg", &["secret", "../other"]).is_err());
+        assert!(check("cargo", &["check", "--manifest-path=/tmp/Cargo.toml"]).is_err());
+        assert!(check("rg", &["--pre", "sh", "x", "src/"]).is_err());
+        assert!(check("rg", &["--pre-glob=*.rs", "x"]).is_err());
+        assert!(check("rg", &["-f/etc/shadow", "x"]).is_err());
+        assert!(check("cargo", &["-C/tmp/evil", "build"]).is_err());
+        assert!(check("rg", &["-g../*.rs", "x"]).is_err());
+        assert!(check("rg", &["-in", "todo", "src/"]).is_ok());
+
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(outside.path(), root.join("escape"))?;
+            assert!(check("rg", &["secret", "escape"]).is_err());
+            assert!(check("rg", &["secret", "escape/missing.txt"]).is_err());
+        }
+
+        let long = "x".repeat(100);
+        let cut = truncate_middle(&long, 10);
+        assert!(cut.starts_with("xxxxx\n… 90 characters omitted …\nxxxxx"));
+        Ok(())
+    }
+}
@@ -0,0 +1,26 @@
+use emry_core::error::EmryError;
+
+#[derive(Debug, thiserror::Error)]
+pub enum AgentError {
+    #[error("{provider} API error: {status} - {body}")]
+    LlmApi {
+        provider: &'static str,
+        status: u16,
+        body: String,
+    },
+
+    #[error("Invalid LLM response: {0}")]
+    LlmResponse(String),
+
+    #[error("{0}")]
+    EmbedderUnavailable(String),
+}
+
+impl From<AgentError> for EmryError {
+    fn from(e: AgentError) -> Self {
+        match e {
+            AgentError::EmbedderUnavailable(_) => EmryError::EmbedderUnavailable(Box::new(e)),
+            AgentError::LlmApi { .. } | AgentError::LlmResponse(_) => EmryError::Llm(Box::new(e)),
+        }
+    }
+}
@@ -1,4 +1,5 @@
 pub mod cortex;
+pub mod error;
 pub mod llm;
 pub mod project;
 pub mod ops;
@@ -0,0 +1,72 @@
+//! `llm.cache`: serve repeated LLM requests from the index.
+//!
+//! A request is identified by a hash of everything that shapes the response
+//! (endpoint, model, messages, response schema and token limit), so only
+//! byte-identical requests hit. Write failures are ignored; the cache never
+//! fails a call.
+
+use emry_config::LlmCacheConfig;
+use emry_store::SurrealStore;
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Clone)]
+pub struct LlmCache {
+    store: Arc<SurrealStore>,
+    ttl_secs: u64,
+    max_entries: usize,
+}
+
+impl LlmCache {
+    /// `None` when `llm.cache.enabled` is off.
+    pub fn from_config(store: Arc<SurrealStore>, config: &LlmCacheConfig) -> Option<Self> {
+        config.enabled.then(|| Self {
+            store,
+            ttl_secs: config.ttl_secs,
+            max_entries: config.max_entries,
+        })
+    }
+
+    /// Cache key for a request body as sent to the API.
+    pub fn key(api_base: &str, body: &serde_json::Value) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(api_base.as_bytes());
+        hasher.update(b"\0");
+        hasher.update(body.to_string().as_bytes());
+        hex::encode(hasher.finalize())
+    }
+
+    pub async fn get(&self, key: &str) -> Option<String> {
+        let not_before = now().saturating_sub(self.ttl_secs);
+        self.store.cached_llm_response(key, not_before).await.ok().flatten()
+    }
+
+    pub async fn put(&self, key: &str, model: &str, response: &str) {
+        let now = now();
+        if self.store.cache_llm_response(key, model, response, now).await.is_ok() {
+            let _ = self.store.prune_llm_cache(now.saturating_sub(self.ttl_secs), self.max_entries).await;
+        }
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_covers_endpoint_and_body() {
+        let body = serde_json::json!({ "model": "gpt-4o-mini", "messages": [{ "role": "user", "content": "hi" }] });
+        let key = LlmCache::key("https://api.openai.com/v1", &body);
+        assert_eq!(key, LlmCache::key("https://api.openai.com/v1", &body.clone()));
+        assert_ne!(key, LlmCache::key("http://localhost:8080/v1", &body));
+
+        let mut limited = body.clone();
+        limited["max_tokens"] = serde_json::json!(100);
+        assert_ne!(key, LlmCache::key("https://api.openai.com/v1", &limited));
+    }
+}
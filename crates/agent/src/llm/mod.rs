@@ -1,4 +1,12 @@
+pub mod cache;
+pub mod usage;
+
+pub use cache::LlmCache;
+pub use usage::{TokenUsage, UsageMeter, UsageTotals};
+
+use crate::error::AgentError;
 use anyhow::Result;
+use emry_core::error::EmryError;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
@@ -58,6 +66,10 @@ pub struct OpenAIProvider {
     pub api_key: String,
     pub client: reqwest::Client,
     pub api_base: String,
+    /// Responses served from and stored in the index, when `llm.cache` is enabled.
+    pub cache: Option<LlmCache>,
+    /// Tokens and estimated cost of the calls made so far, shared by clones.
+    pub usage: UsageMeter,
 }
 
 #[derive(Debug, Clone)]
@@ -66,6 +78,64 @@ pub struct JsonSchemaSpec {
     pub schema: serde_json::Value,
 }
 
+/// Callback receiving each content delta of a streamed completion.
+pub type DeltaSink<'a> = &'a mut (dyn FnMut(&str) + Send);
+
+/// Incremental decoder for OpenAI `text/event-stream` chat completions.
+///
+/// Bytes may arrive split anywhere (mid-line or mid-codepoint); only complete
+/// `data:` lines are decoded.
+#[derive(Debug, Default)]
+pub struct SseDecoder {
+    buf: Vec<u8>,
+    done: bool,
+    usage: Option<TokenUsage>,
+}
+
+impl SseDecoder {
+    /// Feed a chunk of the response body and return the content deltas it completed.
+    pub fn push(&mut self, chunk: &[u8]) -> Result<Vec<String>> {
+        self.buf.extend_from_slice(chunk);
+        let mut deltas = Vec::new();
+
+        while let Some(pos) = self.buf.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.buf.drain(..=pos).collect();
+            let line = String::from_utf8_lossy(&line);
+            let Some(data) = line.trim().strip_prefix("data:") else {
+                continue;
+            };
+            let data = data.trim();
+            if data == "[DONE]" {
+                self.done = true;
+                continue;
+            }
+
+            let json: serde_json::Value = serde_json::from_str(data)?;
+            if let Some(error) = json.get("error") {
+                return Err(EmryError::from(AgentError::LlmResponse(format!("OpenAI API returned error: {}", error))).into());
+            }
+            if let Some(usage) = json.get("usage").and_then(TokenUsage::from_json) {
+                self.usage = Some(usage);
+            }
+            if let Some(delta) = json["choices"][0]["delta"]["content"].as_str() {
+                if !delta.is_empty() {
+                    deltas.push(delta.to_string());
+                }
+            }
+        }
+        Ok(deltas)
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+
+    /// Usage from the stream's final chunk, sent when the request asked for it.
+    pub fn usage(&self) -> Option<TokenUsage> {
+        self.usage
+    }
+}
+
 impl OpenAIProvider {
     pub fn new(model: String, api_key: String, timeout_secs: u64) -> Result<Self> {
         let client = reqwest::Client::builder()
@@ -76,6 +146,8 @@ impl OpenAIProvider {
             api_key,
             client,
             api_base: "https://api.openai.com/v1".to_string(),
+            cache: None,
+            usage: UsageMeter::default(),
         })
     }
 
@@ -88,19 +160,43 @@ impl OpenAIProvider {
             api_key,
             client,
             api_base,
+            cache: None,
+            usage: UsageMeter::default(),
         })
     }
 
+    /// Usage recorded since the provider was created or `reset_usage` was called.
+    pub fn usage(&self) -> UsageTotals {
+        self.usage.totals()
+    }
+
+    pub fn reset_usage(&self) {
+        self.usage.reset();
+    }
+
+    /// The same provider calling `model`, sharing this one's cache and usage meter.
+    pub fn with_model(&self, model: &str) -> Self {
+        Self { model: model.to_string(), ..self.clone() }
+    }
+
+    /// Serve identical requests from `cache` (see `RepoContext::llm_cache`).
+    pub fn with_cache(mut self, cache: Option<LlmCache>) -> Self {
+        self.cache = cache;
+        self
+    }
+
     async fn chat_inner(
         &self,
         messages: &[Message],
         response_format: Option<JsonSchemaSpec>,
         max_tokens: Option<u32>,
+        on_delta: Option<DeltaSink<'_>>,
     ) -> Result<String> {
         let url = format!("{}/chat/completions", self.api_base.trim_end_matches('/'));
         let mut body = serde_json::json!({
             "model": self.model,
-            "messages": messages
+            "messages": messages,
+            "stream": on_delta.is_some()
         });
         if let Some(mt) = max_tokens {
             body["max_tokens"] = serde_json::json!(mt);
@@ -116,30 +212,82 @@ impl OpenAIProvider {
             });
         }
 
-        let res = self
+        // Streaming doesn't change the response, so it's left out of the key.
+        let key = self.cache.as_ref().map(|_| {
+            let mut keyed = body.clone();
+            keyed["stream"] = serde_json::json!(false);
+            LlmCache::key(&self.api_base, &keyed)
+        });
+        if let (Some(cache), Some(key)) = (&self.cache, &key) {
+            if let Some(content) = cache.get(key).await {
+                self.usage.record_cached();
+                if let Some(on_delta) = on_delta {
+                    on_delta(&content);
+                }
+                return Ok(content);
+            }
+        }
+
+        // Streams only report usage when asked to, in one last chunk.
+        if on_delta.is_some() {
+            body["stream_options"] = serde_json::json!({ "include_usage": true });
+        }
+        let content = self.send(url, &body, on_delta).await?;
+        if let (Some(cache), Some(key)) = (&self.cache, &key) {
+            cache.put(key, &self.model, &content).await;
+        }
+        Ok(content)
+    }
+
+    async fn send(&self, url: String, body: &serde_json::Value, on_delta: Option<DeltaSink<'_>>) -> Result<String> {
+        let mut res = self
             .client
             .post(url)
             .header("Authorization", format!("Bearer {}", self.api_key))
-            .json(&body)
+            .json(body)
             .send()
             .await?;
 
         if !res.status().is_success() {
             let status = res.status();
             let text = res.text().await.unwrap_or_default();
-            return Err(anyhow::anyhow!("OpenAI API error: {} - {}", status, text));
+            return Err(EmryError::from(AgentError::LlmApi {
+                provider: "OpenAI",
+                status: status.as_u16(),
+                body: text,
+            })
+            .into());
+        }
+
+        if let Some(on_delta) = on_delta {
+            let mut decoder = SseDecoder::default();
+            let mut content = String::new();
+            while let Some(chunk) = res.chunk().await? {
+                for delta in decoder.push(&chunk)? {
+                    on_delta(&delta);
+                    content.push_str(&delta);
+                }
+                if decoder.is_done() {
+                    break;
+                }
+            }
+            self.usage.record(&self.model, decoder.usage());
+            return Ok(content);
         }
 
         let json: serde_json::Value = res.json().await?;
 
         if let Some(error) = json.get("error") {
-            return Err(anyhow::anyhow!("OpenAI API returned error: {}", error));
+            return Err(EmryError::from(AgentError::LlmResponse(format!("OpenAI API returned error: {}", error))).into());
         }
+        self.usage.record(&self.model, json.get("usage").and_then(TokenUsage::from_json));
 
         json["choices"][0]["message"]["content"]
             .as_str()
             .map(|s| s.to_string())
-            .ok_or_else(|| anyhow::anyhow!("Invalid response format: missing content in choices"))
+            .ok_or_else(|| {
+                EmryError::from(AgentError::LlmResponse("missing content in choices".to_string())).into()
+            })
     }
 
     pub async fn chat_with_schema(
@@ -147,7 +295,17 @@ impl OpenAIProvider {
         messages: &[Message],
         schema: JsonSchemaSpec,
     ) -> Result<String> {
-        self.chat_inner(messages, Some(schema), None).await
+        self.chat_inner(messages, Some(schema), None, None).await
+    }
+
+    /// Like `chat_with_schema`, but streams the raw JSON as it is generated.
+    pub async fn chat_with_schema_stream(
+        &self,
+        messages: &[Message],
+        schema: JsonSchemaSpec,
+        on_delta: DeltaSink<'_>,
+    ) -> Result<String> {
+        self.chat_inner(messages, Some(schema), None, Some(on_delta)).await
     }
 
     pub async fn chat_with_schema_and_limit(
@@ -156,7 +314,7 @@ impl OpenAIProvider {
         schema: JsonSchemaSpec,
         max_tokens: Option<u32>,
     ) -> Result<String> {
-        self.chat_inner(messages, Some(schema), max_tokens).await
+        self.chat_inner(messages, Some(schema), max_tokens, None).await
     }
 
     pub async fn chat_with_limit(
@@ -164,14 +322,24 @@ impl OpenAIProvider {
         messages: &[Message],
         max_tokens: Option<u32>,
     ) -> Result<String> {
-        self.chat_inner(messages, None, max_tokens).await
+        self.chat_inner(messages, None, max_tokens, None).await
+    }
+
+    /// Like `chat_with_limit`, but passes each content delta to `on_delta` as it arrives.
+    pub async fn chat_stream(
+        &self,
+        messages: &[Message],
+        max_tokens: Option<u32>,
+        on_delta: DeltaSink<'_>,
+    ) -> Result<String> {
+        self.chat_inner(messages, None, max_tokens, Some(on_delta)).await
     }
 }
 
 #[async_trait]
 impl ModelProvider for OpenAIProvider {
     async fn chat(&self, messages: &[Message]) -> Result<String> {
-        self.chat_inner(messages, None, None).await
+        self.chat_inner(messages, None, None, None).await
     }
 }
 
@@ -184,3 +352,41 @@ impl emry_core::traits::LLM for OpenAIProvider {
         }]).await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sse_decoder_handles_split_chunks() {
+        let mut decoder = SseDecoder::default();
+        let stream = "data: {\"choices\":[{\"delta\":{\"content\":\"Hel\"}}]}\n\n\
+                      data: {\"choices\":[{\"delta\":{\"content\":\"lo\"}}]}\n\n\
+                      data: [DONE]\n\n";
+        let (a, b) = stream.as_bytes().split_at(30);
+
+        let mut deltas = decoder.push(a).unwrap();
+        assert!(deltas.is_empty());
+        deltas.extend(decoder.push(b).unwrap());
+
+        assert_eq!(deltas, vec!["Hel", "lo"]);
+        assert!(decoder.is_done());
+    }
+
+    #[test]
+    fn test_sse_decoder_captures_usage_chunk() {
+        let mut decoder = SseDecoder::default();
+        let stream = "data: {\"choices\":[{\"delta\":{\"content\":\"Hi\"}}]}\n\n\
+                      data: {\"choices\":[],\"usage\":{\"prompt_tokens\":12,\"completion_tokens\":3}}\n\n\
+                      data: [DONE]\n\n";
+        assert_eq!(decoder.push(stream.as_bytes()).unwrap(), vec!["Hi"]);
+        assert_eq!(decoder.usage(), Some(TokenUsage { prompt_tokens: 12, completion_tokens: 3 }));
+    }
+
+    #[test]
+    fn test_sse_decoder_surfaces_errors() {
+        let mut decoder = SseDecoder::default();
+        let err = decoder.push(b"data: {\"error\":{\"message\":\"quota\"}}\n").unwrap_err();
+        assert!(err.to_string().contains("quota"));
+    }
+}
@@ -0,0 +1,150 @@
+//! Token usage and estimated cost of the calls an `OpenAIProvider` makes.
+//!
+//! Prices are per million tokens, as published by OpenAI; models not in the
+//! table (local or proxied ones) are counted in tokens only.
+
+use emry_config::AgentConfig;
+use std::sync::{Arc, Mutex};
+
+/// USD per million (prompt, completion) tokens, by model name prefix. More
+/// specific prefixes come first.
+const PRICES: &[(&str, f64, f64)] = &[
+    ("gpt-4o-mini", 0.15, 0.60),
+    ("gpt-4o", 2.50, 10.00),
+    ("gpt-4.1-nano", 0.10, 0.40),
+    ("gpt-4.1-mini", 0.40, 1.60),
+    ("gpt-4.1", 2.00, 8.00),
+    ("o3-mini", 1.10, 4.40),
+    ("gpt-3.5-turbo", 0.50, 1.50),
+];
+
+/// USD per million prompt and completion tokens of `model`, if known.
+pub fn price_per_million(model: &str) -> Option<(f64, f64)> {
+    PRICES
+        .iter()
+        .find(|(prefix, _, _)| model.starts_with(prefix))
+        .map(|&(_, prompt, completion)| (prompt, completion))
+}
+
+/// Tokens one completion reported in its `usage` object.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TokenUsage {
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+}
+
+impl TokenUsage {
+    /// Parse the `usage` object of a response or final stream chunk.
+    pub fn from_json(usage: &serde_json::Value) -> Option<Self> {
+        Some(Self {
+            prompt_tokens: usage.get("prompt_tokens")?.as_u64()?,
+            completion_tokens: usage["completion_tokens"].as_u64().unwrap_or(0),
+        })
+    }
+}
+
+/// Usage accumulated since the meter was last reset.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UsageTotals {
+    /// Calls made, including those answered from the cache.
+    pub calls: usize,
+    /// Calls answered from the LLM cache, which cost nothing.
+    pub cached_calls: usize,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    /// Estimated cost of the priced calls.
+    pub cost_usd: f64,
+    /// Calls to a model without a known price, or that reported no usage.
+    pub unpriced_calls: usize,
+}
+
+impl UsageTotals {
+    pub fn total_tokens(&self) -> u64 {
+        self.prompt_tokens + self.completion_tokens
+    }
+
+    /// Why another step would go over `agent.max_cost_usd` or
+    /// `agent.max_run_tokens`, if it would.
+    pub fn budget_exceeded(&self, config: &AgentConfig) -> Option<String> {
+        if let Some(limit) = config.max_run_tokens {
+            if self.total_tokens() >= limit {
+                return Some(format!("used {} tokens of the {} allowed (agent.max_run_tokens)", self.total_tokens(), limit));
+            }
+        }
+        if let Some(limit) = config.max_cost_usd {
+            if self.cost_usd >= limit {
+                return Some(format!("spent ≈${:.4} of the ${} allowed (agent.max_cost_usd)", self.cost_usd, limit));
+            }
+        }
+        None
+    }
+}
+
+/// Shared running totals; clones of a provider record into the same meter.
+#[derive(Debug, Clone, Default)]
+pub struct UsageMeter(Arc<Mutex<UsageTotals>>);
+
+impl UsageMeter {
+    /// Record a call to `model`; `usage` is `None` when the API didn't report it.
+    pub fn record(&self, model: &str, usage: Option<TokenUsage>) {
+        let mut totals = self.0.lock().unwrap();
+        totals.calls += 1;
+        let Some(usage) = usage else {
+            totals.unpriced_calls += 1;
+            return;
+        };
+        totals.prompt_tokens += usage.prompt_tokens;
+        totals.completion_tokens += usage.completion_tokens;
+        match price_per_million(model) {
+            Some((prompt, completion)) => {
+                totals.cost_usd +=
+                    (usage.prompt_tokens as f64 * prompt + usage.completion_tokens as f64 * completion) / 1_000_000.0;
+            }
+            None => totals.unpriced_calls += 1,
+        }
+    }
+
+    pub fn record_cached(&self) {
+        let mut totals = self.0.lock().unwrap();
+        totals.calls += 1;
+        totals.cached_calls += 1;
+    }
+
+    pub fn totals(&self) -> UsageTotals {
+        self.0.lock().unwrap().clone()
+    }
+
+    pub fn reset(&self) {
+        *self.0.lock().unwrap() = UsageTotals::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_meter_prices_known_models_and_enforces_limits() {
+        let meter = UsageMeter::default();
+        meter.record("gpt-4o-mini-2024-07-18", Some(TokenUsage { prompt_tokens: 1_000_000, completion_tokens: 500_000 }));
+        meter.record("llama3", Some(TokenUsage { prompt_tokens: 100, completion_tokens: 10 }));
+        meter.record_cached();
+
+        let totals = meter.totals();
+        assert_eq!(totals.calls, 3);
+        assert_eq!(totals.cached_calls, 1);
+        assert_eq!(totals.unpriced_calls, 1);
+        assert_eq!(totals.total_tokens(), 1_500_110);
+        assert!((totals.cost_usd - 0.45).abs() < 1e-9);
+
+        let mut config = AgentConfig::default();
+        assert!(totals.budget_exceeded(&config).is_none());
+        config.max_cost_usd = Some(0.40);
+        assert!(totals.budget_exceeded(&config).unwrap().contains("max_cost_usd"));
+        config = AgentConfig { max_run_tokens: Some(1_000), ..AgentConfig::default() };
+        assert!(totals.budget_exceeded(&config).unwrap().contains("max_run_tokens"));
+
+        meter.reset();
+        assert_eq!(meter.totals(), UsageTotals::default());
+    }
+}
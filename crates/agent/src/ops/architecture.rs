@@ -2,6 +2,7 @@ use crate::project::context::RepoContext;
 use anyhow::{anyhow, Result};
 use std::sync::Arc;
 use emry_store::{ModuleCoupling, CentralNode};
+use emry_store::entry_points::EntryPointNode;
 
 pub struct ArchitectureTool {
     ctx: Arc<RepoContext>,
@@ -22,6 +23,14 @@ impl ArchitectureTool {
         Ok((coupling, central_nodes))
     }
 
+    /// Symbols run from outside the code: `main`, route and command handlers,
+    /// tests and library roots' public items.
+    pub async fn entry_points(&self) -> Result<Vec<EntryPointNode>> {
+        let store = self.ctx.surreal_store.as_ref()
+            .ok_or_else(|| anyhow!("SurrealStore not initialized"))?;
+        store.entry_points().await
+    }
+
     pub fn get_root(&self) -> std::path::PathBuf {
         self.ctx.root.clone()
     }
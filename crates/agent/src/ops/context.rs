@@ -18,8 +18,7 @@ pub struct SmartContext {
 impl SmartContext {
     pub fn new(ctx: Arc<RepoContext>) -> Result<Self> {
         let store = ctx.surreal_store.clone().ok_or_else(|| anyhow::anyhow!("Store not available"))?;
-        let embedder = ctx.embedder.clone();
-        let search_service = Arc::new(SearchService::new(store, embedder));
+        let search_service = Arc::new(ctx.search_service(store));
         let fs_tool = FsTool::new(ctx.clone());
         
         Ok(Self {
@@ -0,0 +1,418 @@
+//! Graph export to DOT (Graphviz), GraphML (Gephi, yEd), JSON Lines,
+//! Mermaid (Markdown docs, GitHub comments) and Cypher (Neo4j).
+//!
+//! Node ids are the store's record ids (`file:⟨src/lib.rs⟩`,
+//! `symbol:⟨src/lib.rs::parse⟩`), which are stable across index runs, and
+//! edges carry their relation name (`calls`, `imports`, `defines`, `contains`,
+//! `inherits`, `implements`). Mermaid ids can't hold those, so Mermaid nodes
+//! are numbered in order and labelled instead.
+//!
+//! The Cypher script `MERGE`s on those ids, so loading it again updates the
+//! graph in place: every node is an `:EmryNode` plus `:File`, `:Chunk` or
+//! `:Symbol`, and relations are typed `CALLS`, `IMPORTS` and so on.
+
+use crate::project::types::{GraphEdge, GraphNode, GraphSubgraph};
+use anyhow::Result;
+use emry_core::models::EdgeKind;
+use emry_store::{SurrealGraphNode, SurrealStore};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::{self, Write};
+use surrealdb::sql::Thing;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Dot,
+    GraphMl,
+    Jsonl,
+    Mermaid,
+    Cypher,
+}
+
+/// Which nodes a full export contains.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportScope {
+    /// Files only; symbol-level calls/imports are lifted to the files that contain them.
+    File,
+    /// Symbols with the calls/imports between them.
+    Symbol,
+    /// Files, symbols and chunks with every relation.
+    All,
+}
+
+fn to_node(n: SurrealGraphNode) -> GraphNode {
+    GraphNode {
+        id: n.id.to_string(),
+        kind: n.kind,
+        label: n.label,
+        file_path: n.file_path,
+        canonical_id: None,
+    }
+}
+
+/// Load the whole graph at `scope` from the store.
+pub async fn full_graph(store: &SurrealStore, scope: ExportScope) -> Result<GraphSubgraph> {
+    let files: Vec<GraphNode> = store.list_file_nodes().await?.into_iter().map(to_node).collect();
+    let symbols: Vec<GraphNode> = store.list_all_symbols().await?.into_iter().map(to_node).collect();
+    let chunks: Vec<GraphNode> = match scope {
+        ExportScope::Symbol => Vec::new(),
+        _ => store.list_chunk_nodes().await?.into_iter().map(to_node).collect(),
+    };
+
+    let kinds: Vec<EdgeKind> = match scope {
+        ExportScope::All => EdgeKind::ALL.to_vec(),
+        _ => EdgeKind::ALL.into_iter().filter(EdgeKind::is_dependency).collect(),
+    };
+    let mut raw_edges = Vec::new();
+    for kind in kinds {
+        for (source, target) in store.list_edges(kind).await? {
+            raw_edges.push(GraphEdge { source, target, kind: kind.as_str().to_string(), line: None, column: None });
+        }
+    }
+
+    let graph = match scope {
+        ExportScope::All => {
+            let nodes: Vec<GraphNode> = files.into_iter().chain(symbols).chain(chunks).collect();
+            let known: HashSet<&str> = nodes.iter().map(|n| n.id.as_str()).collect();
+            let edges = raw_edges
+                .into_iter()
+                .filter(|e| known.contains(e.source.as_str()) && known.contains(e.target.as_str()))
+                .collect();
+            GraphSubgraph { nodes, edges }
+        }
+        ExportScope::Symbol => {
+            let known: HashSet<&str> = symbols.iter().map(|n| n.id.as_str()).collect();
+            let edges = raw_edges
+                .into_iter()
+                .filter(|e| known.contains(e.source.as_str()) && known.contains(e.target.as_str()))
+                .collect();
+            GraphSubgraph { nodes: symbols, edges }
+        }
+        ExportScope::File => {
+            // Imports can originate from a file, chunk or symbol; map all of them to their file.
+            let file_of: HashMap<&str, String> = symbols
+                .iter()
+                .chain(chunks.iter())
+                .map(|n| (n.id.as_str(), Thing::from(("file", n.file_path.as_str())).to_string()))
+                .chain(files.iter().map(|f| (f.id.as_str(), f.id.clone())))
+                .collect();
+
+            let mut seen = HashSet::new();
+            let mut edges = Vec::new();
+            for e in raw_edges {
+                let (Some(source), Some(target)) = (file_of.get(e.source.as_str()), file_of.get(e.target.as_str())) else {
+                    continue;
+                };
+                if source != target && seen.insert((source.clone(), target.clone(), e.kind.clone())) {
+                    edges.push(GraphEdge { source: source.clone(), target: target.clone(), kind: e.kind, line: None, column: None });
+                }
+            }
+            GraphSubgraph { nodes: files, edges }
+        }
+    };
+    Ok(graph)
+}
+
+/// Serialize `graph` in `format`.
+pub fn write_graph<W: Write>(graph: &GraphSubgraph, format: ExportFormat, out: &mut W) -> io::Result<()> {
+    match format {
+        ExportFormat::Dot => write_dot(graph, out),
+        ExportFormat::GraphMl => write_graphml(graph, out),
+        ExportFormat::Jsonl => write_jsonl(graph, out),
+        ExportFormat::Mermaid => write_mermaid(graph, out),
+        ExportFormat::Cypher => write_cypher(graph, out),
+    }
+}
+
+fn dot_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn write_dot<W: Write>(graph: &GraphSubgraph, out: &mut W) -> io::Result<()> {
+    writeln!(out, "digraph emry {{")?;
+    writeln!(out, "  node [shape=box, fontname=\"Helvetica\"];")?;
+    for n in &graph.nodes {
+        writeln!(
+            out,
+            "  \"{}\" [label=\"{}\", kind=\"{}\", file=\"{}\"];",
+            dot_escape(&n.id),
+            dot_escape(&n.label),
+            dot_escape(&n.kind),
+            dot_escape(&n.file_path)
+        )?;
+    }
+    for e in &graph.edges {
+        writeln!(
+            out,
+            "  \"{}\" -> \"{}\" [label=\"{}\"];",
+            dot_escape(&e.source),
+            dot_escape(&e.target),
+            dot_escape(&e.kind)
+        )?;
+    }
+    writeln!(out, "}}")
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+fn write_graphml<W: Write>(graph: &GraphSubgraph, out: &mut W) -> io::Result<()> {
+    writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(out, r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns">"#)?;
+    writeln!(out, r#"  <key id="label" for="node" attr.name="label" attr.type="string"/>"#)?;
+    writeln!(out, r#"  <key id="kind" for="node" attr.name="kind" attr.type="string"/>"#)?;
+    writeln!(out, r#"  <key id="file" for="node" attr.name="file" attr.type="string"/>"#)?;
+    writeln!(out, r#"  <key id="relation" for="edge" attr.name="relation" attr.type="string"/>"#)?;
+    writeln!(out, r#"  <graph id="emry" edgedefault="directed">"#)?;
+    for n in &graph.nodes {
+        writeln!(out, r#"    <node id="{}">"#, xml_escape(&n.id))?;
+        writeln!(out, r#"      <data key="label">{}</data>"#, xml_escape(&n.label))?;
+        writeln!(out, r#"      <data key="kind">{}</data>"#, xml_escape(&n.kind))?;
+        writeln!(out, r#"      <data key="file">{}</data>"#, xml_escape(&n.file_path))?;
+        writeln!(out, "    </node>")?;
+    }
+    for e in &graph.edges {
+        writeln!(
+            out,
+            r#"    <edge source="{}" target="{}"><data key="relation">{}</data></edge>"#,
+            xml_escape(&e.source),
+            xml_escape(&e.target),
+            xml_escape(&e.kind)
+        )?;
+    }
+    writeln!(out, "  </graph>")?;
+    writeln!(out, "</graphml>")
+}
+
+/// One JSON object per line: `{"type":"node",...}` records first, then `{"type":"edge",...}`.
+fn write_jsonl<W: Write>(graph: &GraphSubgraph, out: &mut W) -> io::Result<()> {
+    for n in &graph.nodes {
+        let line = serde_json::json!({
+            "type": "node",
+            "id": n.id,
+            "label": n.label,
+            "kind": n.kind,
+            "file": n.file_path,
+        });
+        writeln!(out, "{}", line)?;
+    }
+    for e in &graph.edges {
+        let line = serde_json::json!({
+            "type": "edge",
+            "source": e.source,
+            "target": e.target,
+            "relation": e.kind,
+        });
+        writeln!(out, "{}", line)?;
+    }
+    Ok(())
+}
+
+/// Mermaid label text: quotes and angle brackets as entity codes, since
+/// labels are rendered as HTML.
+fn mermaid_escape(s: &str) -> String {
+    s.replace('"', "#quot;").replace('<', "#lt;").replace('>', "#gt;").replace('|', "#124;")
+}
+
+/// A `classDiagram` when every edge is `inherits` or `implements`, else a
+/// left-to-right `flowchart` with the relation on each edge.
+fn write_mermaid<W: Write>(graph: &GraphSubgraph, out: &mut W) -> io::Result<()> {
+    let mut ids: HashMap<&str, String> = HashMap::new();
+    let mut labels: Vec<(String, &str)> = Vec::new();
+    // Edges may reach past the listed nodes; those ends are labelled by id.
+    let ends = graph.edges.iter().flat_map(|e| [&e.source, &e.target]).map(|end| (end, end));
+    for (id, label) in graph.nodes.iter().map(|n| (&n.id, &n.label)).chain(ends) {
+        if !ids.contains_key(id.as_str()) {
+            let mermaid_id = format!("n{}", ids.len());
+            ids.insert(id, mermaid_id.clone());
+            labels.push((mermaid_id, label));
+        }
+    }
+
+    let hierarchy = !graph.edges.is_empty()
+        && graph.edges.iter().all(|e| {
+            matches!(EdgeKind::from_name(&e.kind), Some(EdgeKind::Inherits | EdgeKind::Implements))
+        });
+    if hierarchy {
+        writeln!(out, "classDiagram")?;
+        for (id, label) in &labels {
+            writeln!(out, "  class {}[\"{}\"]", id, mermaid_escape(label))?;
+        }
+        for e in &graph.edges {
+            // Arrows point at the supertype.
+            let arrow = if e.kind == EdgeKind::Implements.as_str() { "<|.." } else { "<|--" };
+            writeln!(out, "  {} {} {}", ids[e.target.as_str()], arrow, ids[e.source.as_str()])?;
+        }
+    } else {
+        writeln!(out, "flowchart LR")?;
+        for (id, label) in &labels {
+            writeln!(out, "  {}[\"{}\"]", id, mermaid_escape(label))?;
+        }
+        for e in &graph.edges {
+            writeln!(out, "  {} -->|{}| {}", ids[e.source.as_str()], mermaid_escape(&e.kind), ids[e.target.as_str()])?;
+        }
+    }
+    Ok(())
+}
+
+/// Rows per `UNWIND` statement, small enough for Neo4j's default transaction memory.
+const CYPHER_BATCH: usize = 500;
+
+/// A single-quoted Cypher string literal.
+fn cypher_string(s: &str) -> String {
+    let escaped = s.replace('\\', "\\\\").replace('\'', "\\'").replace('\n', "\\n").replace('\r', "\\r");
+    format!("'{}'", escaped)
+}
+
+/// `calls` -> `CALLS`; anything but letters, digits and `_` becomes `_`.
+fn cypher_relation(kind: &str) -> String {
+    let name: String = kind.chars().map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' }).collect();
+    if name.starts_with(|c: char| c.is_ascii_digit()) { format!("_{}", name) } else { name }
+}
+
+/// Batched `UNWIND ... MERGE` statements: a uniqueness constraint, the
+/// nodes by label, then the relations by type. Relations whose ends aren't
+/// in the graph match nothing and are skipped by Neo4j.
+fn write_cypher<W: Write>(graph: &GraphSubgraph, out: &mut W) -> io::Result<()> {
+    writeln!(out, "// emry code graph: {} nodes, {} relations", graph.nodes.len(), graph.edges.len())?;
+    writeln!(out, "CREATE CONSTRAINT emry_node_id IF NOT EXISTS FOR (n:EmryNode) REQUIRE n.id IS UNIQUE;")?;
+
+    let mut by_label: BTreeMap<&str, Vec<&GraphNode>> = BTreeMap::new();
+    for n in &graph.nodes {
+        let label = match n.kind.as_str() {
+            "file" => "File",
+            "chunk" => "Chunk",
+            _ => "Symbol",
+        };
+        by_label.entry(label).or_default().push(n);
+    }
+    for (label, nodes) in by_label {
+        for batch in nodes.chunks(CYPHER_BATCH) {
+            let rows: Vec<String> = batch
+                .iter()
+                .map(|n| {
+                    format!(
+                        "{{id: {}, label: {}, kind: {}, file: {}}}",
+                        cypher_string(&n.id),
+                        cypher_string(&n.label),
+                        cypher_string(&n.kind),
+                        cypher_string(&n.file_path)
+                    )
+                })
+                .collect();
+            writeln!(
+                out,
+                "UNWIND [{}] AS row MERGE (n:EmryNode {{id: row.id}}) SET n:{}, n.label = row.label, n.kind = row.kind, n.file = row.file;",
+                rows.join(", "),
+                label
+            )?;
+        }
+    }
+
+    let mut by_type: BTreeMap<String, Vec<&GraphEdge>> = BTreeMap::new();
+    for e in &graph.edges {
+        by_type.entry(cypher_relation(&e.kind)).or_default().push(e);
+    }
+    for (relation, edges) in by_type {
+        for batch in edges.chunks(CYPHER_BATCH) {
+            let rows: Vec<String> = batch
+                .iter()
+                .map(|e| {
+                    let site = match (e.line, e.column) {
+                        (Some(line), Some(column)) => format!(", line: {}, column: {}", line, column),
+                        (Some(line), None) => format!(", line: {}", line),
+                        _ => String::new(),
+                    };
+                    format!("{{source: {}, target: {}{}}}", cypher_string(&e.source), cypher_string(&e.target), site)
+                })
+                .collect();
+            writeln!(
+                out,
+                "UNWIND [{}] AS row MATCH (a:EmryNode {{id: row.source}}) MATCH (b:EmryNode {{id: row.target}}) \
+                 MERGE (a)-[r:{}]->(b) SET r.line = row.line, r.column = row.column;",
+                rows.join(", "),
+                relation
+            )?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> GraphSubgraph {
+        GraphSubgraph {
+            nodes: vec![GraphNode {
+                id: "symbol:⟨a.rs::f⟩".to_string(),
+                kind: "function".to_string(),
+                label: "f<\"T\">".to_string(),
+                file_path: "a.rs".to_string(),
+                canonical_id: None,
+            }],
+            edges: vec![GraphEdge {
+                source: "symbol:⟨a.rs::f⟩".to_string(),
+                target: "symbol:⟨a.rs::f⟩".to_string(),
+                kind: "calls".to_string(),
+                line: None,
+                column: None,
+            }],
+        }
+    }
+
+    #[test]
+    fn test_dot_and_graphml_escape_labels() {
+        let mut dot = Vec::new();
+        write_graph(&sample(), ExportFormat::Dot, &mut dot).unwrap();
+        assert!(String::from_utf8(dot).unwrap().contains(r#"label="f<\"T\">""#));
+
+        let mut xml = Vec::new();
+        write_graph(&sample(), ExportFormat::GraphMl, &mut xml).unwrap();
+        assert!(String::from_utf8(xml).unwrap().contains("f&lt;&quot;T&quot;&gt;"));
+    }
+
+    #[test]
+    fn test_mermaid_flowchart_and_class_diagram() {
+        let mut buf = Vec::new();
+        write_graph(&sample(), ExportFormat::Mermaid, &mut buf).unwrap();
+        assert_eq!(String::from_utf8(buf).unwrap(), "flowchart LR\n  n0[\"f#lt;#quot;T#quot;#gt;\"]\n  n0 -->|calls| n0\n");
+
+        let mut hierarchy = sample();
+        hierarchy.edges[0].kind = "implements".to_string();
+        hierarchy.edges[0].target = "symbol:⟨a.rs::Run⟩".to_string();
+        let mut buf = Vec::new();
+        write_graph(&hierarchy, ExportFormat::Mermaid, &mut buf).unwrap();
+        let text = String::from_utf8(buf).unwrap();
+        assert!(text.starts_with("classDiagram\n"));
+        assert!(text.contains("  class n1[\"symbol:⟨a.rs::Run⟩\"]\n  n1 <|.. n0\n"));
+    }
+
+    #[test]
+    fn test_cypher_merges_nodes_and_typed_relations() {
+        let mut graph = sample();
+        graph.nodes[0].label = "it's\\".to_string();
+        let mut buf = Vec::new();
+        write_graph(&graph, ExportFormat::Cypher, &mut buf).unwrap();
+        let text = String::from_utf8(buf).unwrap();
+        assert!(text.contains("CREATE CONSTRAINT emry_node_id IF NOT EXISTS"));
+        assert!(text.contains("{id: 'symbol:⟨a.rs::f⟩', label: 'it\\'s\\\\', kind: 'function', file: 'a.rs'}"));
+        assert!(text.contains("SET n:Symbol,"));
+        assert!(text.contains("MERGE (a)-[r:CALLS]->(b)"));
+        assert_eq!(cypher_relation("3 imports"), "_3_IMPORTS");
+    }
+
+    #[test]
+    fn test_jsonl_one_record_per_line() {
+        let mut buf = Vec::new();
+        write_graph(&sample(), ExportFormat::Jsonl, &mut buf).unwrap();
+        let text = String::from_utf8(buf).unwrap();
+        let records: Vec<serde_json::Value> = text.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[1]["relation"], "calls");
+    }
+}
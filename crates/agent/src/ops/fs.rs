@@ -53,6 +53,18 @@ impl FsTool {
                 workspace_root.display()
             ));
         }
+
+        if let Some(scope) = &self.ctx.scope {
+            let rel = canonical.strip_prefix(workspace_root).unwrap_or(&canonical);
+            let visible = if canonical.is_dir() { scope.may_contain(rel) } else { scope.contains(rel) };
+            if !visible {
+                return Err(anyhow!(
+                    "Access denied: path '{}' is outside the scope ({})",
+                    rel.display(),
+                    scope.patterns().join(", ")
+                ));
+            }
+        }
         
         Ok(canonical)
     }
@@ -97,6 +109,14 @@ impl FsTool {
                     }
 
                     let is_dir = entry.file_type().map(|ft| ft.is_dir()).unwrap_or(false);
+
+                    if let Some(scope) = &self.ctx.scope {
+                        let rel_path = path.strip_prefix(&self.ctx.root).unwrap_or(path);
+                        let visible = if is_dir { scope.may_contain(rel_path) } else { scope.contains(rel_path) };
+                        if !visible {
+                            continue;
+                        }
+                    }
                     
                     entries.push(DirEntry {
                         path: path.to_path_buf(),
@@ -221,6 +241,11 @@ impl FsTool {
         emry_core::map::generate_codebase_map(workspace_root, max_depth, exclude_paths)
     }
 
+    /// The entries behind `generate_codebase_map`, for other renderings.
+    pub fn codebase_entries(&self, max_depth: usize) -> Result<Vec<emry_core::map::MapEntry>> {
+        emry_core::map::codebase_entries(&self.ctx.root, max_depth, &self.ctx.config.core.exclude_paths)
+    }
+
     pub async fn explore_module(&self, path: &str, depth: usize) -> Result<String> {
         let dir_path = self.validate_and_resolve_path(Path::new(path))?;
         
@@ -274,7 +299,9 @@ mod tests {
             index_dir: root.join(".codeindex"),
             config,
             embedder: None,
+            reranker: None,
             surreal_store: None,
+            scope: None,
         });
 
         let fs_tool = FsTool::new(ctx);
@@ -2,16 +2,20 @@ use crate::project::context::RepoContext;
 use crate::project::types::{GraphEdge, GraphSubgraph};
 use anyhow::{anyhow, Result};
 
-use std::collections::{HashSet, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::Arc;
 
 use emry_core::models::paths::Path;
 
 use serde::Serialize;
-use emry_store::SurrealGraphNode;
+use emry_core::models::EdgeKind;
+use emry_store::{Direction, GraphBackend, SurrealGraphNode};
 
 use futures::future::BoxFuture;
 
+/// Candidates offered when a node name only matches fuzzily.
+const MAX_FUZZY_CANDIDATES: usize = 10;
+
 pub struct GraphTool {
     ctx: Arc<RepoContext>,
 }
@@ -38,6 +42,53 @@ pub struct GraphResult {
     pub candidates: Option<Vec<CandidateNode>>,  // None = success, Some = needs disambiguation
 }
 
+#[derive(Debug, Clone, Serialize)]
+pub struct PathHop {
+    pub id: String,
+    pub label: String,
+    pub kind: String,
+    pub file_path: String,
+    pub line: Option<usize>,
+}
+
+/// A chain of nodes from source to target; `relations[i]` links `hops[i]` to `hops[i + 1]`.
+#[derive(Debug, Clone, Serialize)]
+pub struct SymbolPath {
+    pub hops: Vec<PathHop>,
+    pub relations: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ShortestPathsResult {
+    pub paths: Vec<SymbolPath>,
+    pub candidates: Option<Vec<CandidateNode>>,  // None = success, Some = needs disambiguation
+}
+
+/// A symbol and its callers (or callees), nested by hop.
+#[derive(Debug, Clone, Serialize)]
+pub struct CallTreeNode {
+    pub id: String,
+    pub label: String,
+    pub file_path: String,
+    /// First line of the definition
+    pub line: Option<usize>,
+    /// Line and column of the call linking this node to its parent, in the
+    /// caller's file: this node's for a callers tree, the parent's for callees.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub call_site: Option<(usize, usize)>,
+    /// Expanded elsewhere in the tree (recursion or a shared caller), so its
+    /// children are not repeated here.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub repeated: bool,
+    pub children: Vec<CallTreeNode>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CallTreeResult {
+    pub tree: Option<CallTreeNode>,
+    pub candidates: Option<Vec<CandidateNode>>,  // None = success, Some = needs disambiguation
+}
+
 #[derive(Debug, Serialize)]
 pub struct UsageSnippet {
     pub file_path: String,
@@ -71,23 +122,65 @@ impl GraphTool {
             .ok_or_else(|| anyhow!("SurrealStore not initialized"))?;
         
         if let Ok(Some(n)) = store.get_node(symbol).await {
-            return Ok((Some(n), None));
+            if self.visible(&n) {
+                return Ok((Some(n), None));
+            }
         }
         
-        let matches = store.find_nodes_by_label(symbol, file_filter).await?;
+        let mut matches = store.find_nodes_by_label(symbol, file_filter).await?;
+        matches.retain(|n| self.visible(n));
         if matches.len() > 1 {
-             let candidates = matches.into_iter().map(|n| CandidateNode {
-                id: n.id.to_string(),
-                label: n.label,
-                kind: n.kind,
-                file_path: n.file_path,
-            }).collect();
-            return Ok((None, Some(candidates)));
+            return Ok((None, Some(matches.into_iter().map(Self::to_candidate).collect())));
+        }
+
+        if matches.is_empty() {
+            // Nothing contains the name as typed; fall back to fuzzy matches so
+            // typos and abbreviations still resolve or offer a choice.
+            matches = self.fuzzy_nodes(symbol, file_filter, MAX_FUZZY_CANDIDATES).await?;
+            if matches.len() > 1 {
+                return Ok((None, Some(matches.into_iter().map(Self::to_candidate).collect())));
+            }
         }
 
         Ok((matches.into_iter().next(), None))
     }
 
+    /// Symbol and file nodes ranked by how well their label matches `query`,
+    /// for shell completion and interactive node pickers.
+    pub async fn complete(&self, query: &str, limit: usize) -> Result<Vec<CandidateNode>> {
+        let nodes = self.fuzzy_nodes(query, None, limit).await?;
+        Ok(nodes.into_iter().map(Self::to_candidate).collect())
+    }
+
+    async fn fuzzy_nodes(&self, query: &str, file_filter: Option<&str>, limit: usize) -> Result<Vec<SurrealGraphNode>> {
+        let store = self.ctx.surreal_store.as_ref()
+            .ok_or_else(|| anyhow!("SurrealStore not initialized"))?;
+
+        let mut nodes = store.list_all_symbols().await?;
+        nodes.extend(store.list_file_nodes().await?);
+
+        let mut scored: Vec<(u32, SurrealGraphNode)> = nodes
+            .into_iter()
+            .filter(|n| self.visible(n) && file_filter.map_or(true, |f| n.file_path.contains(f)))
+            .filter_map(|n| fuzzy_score(query, &n.label).map(|score| (score, n)))
+            .collect();
+        scored.sort_by(|a, b| {
+            b.0.cmp(&a.0)
+                .then_with(|| a.1.label.len().cmp(&b.1.label.len()))
+                .then_with(|| a.1.file_path.cmp(&b.1.file_path))
+        });
+        Ok(scored.into_iter().take(limit).map(|(_, n)| n).collect())
+    }
+
+    fn to_candidate(n: SurrealGraphNode) -> CandidateNode {
+        CandidateNode {
+            id: n.id.to_string(),
+            label: n.label,
+            kind: n.kind,
+            file_path: n.file_path,
+        }
+    }
+
     async fn graph_out(&self, symbol: &str, file_filter: Option<&str>) -> Result<GraphResult> {
         let (node, candidates) = self.get_start_node_or_candidates(symbol, file_filter).await?;
         if let Some(candidates) = candidates {
@@ -100,15 +193,22 @@ impl GraphTool {
         subgraph.nodes.push(Self::to_graph_node(start_node.clone()));
 
         let store = self.ctx.surreal_store.as_ref().unwrap();
-        let edges = store.get_neighbors(&start_node_id, "out").await?;
+        let edges = store.neighbors(&start_node_id, Direction::Out).await?;
         
         for edge in edges {
+            let target = match store.get_node_by_thing(&edge.target).await {
+                Ok(Some(target)) if !self.visible(&target) => continue,
+                Ok(target) => target,
+                Err(_) => None,
+            };
             subgraph.edges.push(GraphEdge {
                 source: edge.source.to_string(),
                 target: edge.target.to_string(),
                 kind: edge.relation,
+                line: edge.line,
+                column: edge.column,
             });
-            if let Ok(Some(target)) = store.get_node_by_thing(&edge.target).await {
+            if let Some(target) = target {
                 subgraph.nodes.push(Self::to_graph_node(target));
             }
         }
@@ -136,16 +236,23 @@ impl GraphTool {
         while let Some((current_node_id, hops)) = q.pop_front() {
             if hops >= max_hops { continue; }
 
-            let in_edges = store.get_neighbors(&current_node_id, "in").await?;
+            let in_edges = store.neighbors(&current_node_id, Direction::In).await?;
             for edge in in_edges {
+                let source_node = match store.get_node_by_thing(&edge.source).await {
+                    Ok(Some(n)) if !self.visible(&n) => continue,
+                    Ok(n) => n,
+                    Err(_) => None,
+                };
                 let source_id = edge.source.to_string();
                 subgraph.edges.push(GraphEdge {
                     source: source_id.clone(),
                     target: current_node_id.clone(),
                     kind: edge.relation,
+                    line: edge.line,
+                    column: edge.column,
                 });
 
-                if let Ok(Some(source_node)) = store.get_node_by_thing(&edge.source).await {
+                if let Some(source_node) = source_node {
                     subgraph.nodes.push(Self::to_graph_node(source_node));
                 }
 
@@ -193,6 +300,170 @@ impl GraphTool {
         Ok(GraphResult { subgraph, paths: vec![], candidates: None })
     }
 
+    /// Transitive callers (`In`) or callees (`Out`) of `symbol` along `calls`
+    /// edges, up to `max_depth` hops. Each symbol is expanded once, at its
+    /// first (shallowest-path) appearance; `Both` is treated as `Out`.
+    pub async fn call_tree(
+        &self,
+        symbol: &str,
+        direction: GraphDirection,
+        max_depth: usize,
+        file_filter: Option<&str>,
+    ) -> Result<CallTreeResult> {
+        let (root, candidates) = self.get_start_node_or_candidates(symbol, file_filter).await?;
+        if let Some(candidates) = candidates {
+            return Ok(CallTreeResult { tree: None, candidates: Some(candidates) });
+        }
+        let root = root.ok_or_else(|| anyhow!("Symbol '{}' not found.", symbol))?;
+        let direction = match direction {
+            GraphDirection::In => Direction::In,
+            GraphDirection::Out | GraphDirection::Both => Direction::Out,
+        };
+
+        let mut tree = Self::to_tree_node(root, None);
+        let mut expanded: HashSet<String> = HashSet::from([tree.id.clone()]);
+        // Breadth first, so a symbol reachable at several depths is expanded at the shallowest.
+        let mut frontier: Vec<Vec<usize>> = vec![Vec::new()];
+        for _ in 0..max_depth {
+            let mut next = Vec::new();
+            for path in frontier {
+                let parent_id = Self::tree_node_mut(&mut tree, &path).id.clone();
+                let mut children = Vec::new();
+                for edge in self.call_edges(&parent_id, direction).await? {
+                    let other = match direction {
+                        Direction::In => &edge.source,
+                        Direction::Out => &edge.target,
+                    };
+                    let Some(node) = self.store_node(other).await? else { continue };
+                    let call_site = edge.line.map(|line| (line, edge.column.unwrap_or(1)));
+                    let mut child = Self::to_tree_node(node, call_site);
+                    child.repeated = !expanded.insert(child.id.clone());
+                    children.push(child);
+                }
+                children.sort_by(|a, b| (&a.file_path, a.line, &a.label).cmp(&(&b.file_path, b.line, &b.label)));
+                children.dedup_by(|a, b| a.id == b.id);
+                for (i, child) in children.iter().enumerate() {
+                    if !child.repeated {
+                        next.push(path.iter().copied().chain([i]).collect());
+                    }
+                }
+                Self::tree_node_mut(&mut tree, &path).children = children;
+            }
+            frontier = next;
+        }
+        Ok(CallTreeResult { tree: Some(tree), candidates: None })
+    }
+
+    async fn call_edges(&self, id: &str, direction: Direction) -> Result<Vec<emry_store::SurrealGraphEdge>> {
+        let store = self.ctx.surreal_store.as_ref()
+            .ok_or_else(|| anyhow!("SurrealStore not initialized"))?;
+        store.neighbors_of_kind(id, direction, &[EdgeKind::Calls]).await
+    }
+
+    /// The visible node behind `id`, if any.
+    async fn store_node(&self, id: &surrealdb::sql::Thing) -> Result<Option<SurrealGraphNode>> {
+        let store = self.ctx.surreal_store.as_ref()
+            .ok_or_else(|| anyhow!("SurrealStore not initialized"))?;
+        Ok(store.get_node_by_thing(id).await?.filter(|n| self.visible(n)))
+    }
+
+    fn to_tree_node(n: SurrealGraphNode, call_site: Option<(usize, usize)>) -> CallTreeNode {
+        CallTreeNode {
+            id: n.id.to_string(),
+            label: n.label,
+            file_path: n.file_path,
+            line: n.start_line,
+            call_site,
+            repeated: false,
+            children: Vec::new(),
+        }
+    }
+
+    fn tree_node_mut<'t>(tree: &'t mut CallTreeNode, path: &[usize]) -> &'t mut CallTreeNode {
+        path.iter().fold(tree, |node, &i| &mut node.children[i])
+    }
+
+    /// Finds the shortest call/import chains from `from` to `to`, following outgoing edges.
+    pub async fn shortest_paths(
+        &self,
+        from: &str,
+        to: &str,
+        max_hops: usize,
+        max_paths: usize,
+        file_filter: Option<&str>,
+    ) -> Result<ShortestPathsResult> {
+        let (source, candidates) = self.get_start_node_or_candidates(from, file_filter).await?;
+        if let Some(candidates) = candidates {
+            return Ok(ShortestPathsResult { paths: vec![], candidates: Some(candidates) });
+        }
+        let source = source.ok_or_else(|| anyhow!("Symbol '{}' not found.", from))?;
+
+        let (target, candidates) = self.get_start_node_or_candidates(to, file_filter).await?;
+        if let Some(candidates) = candidates {
+            return Ok(ShortestPathsResult { paths: vec![], candidates: Some(candidates) });
+        }
+        let target = target.ok_or_else(|| anyhow!("Symbol '{}' not found.", to))?;
+
+        let store = self.ctx.surreal_store.as_ref()
+            .ok_or_else(|| anyhow!("SurrealStore not initialized"))?;
+
+        let source_id = source.id.to_string();
+        let target_id = target.id.to_string();
+
+        let mut nodes: HashMap<String, SurrealGraphNode> = HashMap::new();
+        nodes.insert(source_id.clone(), source);
+        nodes.insert(target_id.clone(), target);
+
+        let mut search = PathSearch::new(source_id, target_id);
+        while let Some((current, d)) = search.next(max_hops) {
+            let edges = store
+                .neighbors_of_kind(&current, Direction::Out, &[EdgeKind::Calls, EdgeKind::Imports])
+                .await?;
+            for edge in edges {
+                let next = edge.target.to_string();
+                if search.is_new(&next) && !nodes.contains_key(&next) {
+                    match store.get_node_by_thing(&edge.target).await {
+                        Ok(Some(n)) if !self.visible(&n) => continue,
+                        Ok(Some(n)) => {
+                            nodes.insert(next.clone(), n);
+                        }
+                        _ => {}
+                    }
+                }
+                search.reach(&current, d, next, edge.relation);
+            }
+        }
+        let raw_paths = search.paths(max_paths);
+
+        let paths = raw_paths
+            .into_iter()
+            .map(|(ids, relations)| SymbolPath {
+                hops: ids
+                    .into_iter()
+                    .map(|id| match nodes.get(&id) {
+                        Some(n) => PathHop {
+                            id: id.clone(),
+                            label: n.label.clone(),
+                            kind: n.kind.clone(),
+                            file_path: n.file_path.clone(),
+                            line: n.start_line,
+                        },
+                        None => PathHop {
+                            label: id.clone(),
+                            id,
+                            kind: "unknown".to_string(),
+                            file_path: String::new(),
+                            line: None,
+                        },
+                    })
+                    .collect(),
+                relations,
+            })
+            .collect();
+
+        Ok(ShortestPathsResult { paths, candidates: None })
+    }
+
     fn to_graph_node(n: SurrealGraphNode) -> crate::project::types::GraphNode {
         crate::project::types::GraphNode {
             id: n.id.to_string(),
@@ -203,22 +474,31 @@ impl GraphTool {
         }
     }
 
+    /// Whether a node lies inside the context's scope (always true without one).
+    fn visible(&self, n: &SurrealGraphNode) -> bool {
+        self.ctx.in_scope(std::path::Path::new(&n.file_path))
+    }
+
     pub async fn find_references(&self, symbol_id: &str) -> Result<Vec<SurrealGraphNode>> {
         let store = self.ctx.surreal_store.as_ref()
             .ok_or_else(|| anyhow!("SurrealStore not initialized"))?;
-        store.find_references(symbol_id).await
+        let mut refs = store.find_references(symbol_id).await?;
+        refs.retain(|n| self.visible(n));
+        Ok(refs)
     }
 
     pub async fn find_definition(&self, symbol_name: &str) -> Result<Vec<SurrealGraphNode>> {
         let store = self.ctx.surreal_store.as_ref()
             .ok_or_else(|| anyhow!("SurrealStore not initialized"))?;
-        store.find_definition(symbol_name).await
+        let mut defs = store.find_definition(symbol_name).await?;
+        defs.retain(|n| self.visible(n));
+        Ok(defs)
     }
 
     pub async fn get_type_definition(&self, symbol_name: &str) -> Result<Option<SurrealGraphNode>> {
         let store = self.ctx.surreal_store.as_ref()
             .ok_or_else(|| anyhow!("SurrealStore not initialized"))?;
-        store.get_type_definition(symbol_name).await
+        Ok(store.get_type_definition(symbol_name).await?.filter(|n| self.visible(n)))
     }
 
     pub async fn find_usages(&self, symbol: &str) -> Result<Vec<UsageSnippet>> {
@@ -263,3 +543,168 @@ impl GraphTool {
         Ok(snippets)
     }
 }
+
+/// How well `query` matches a node label, case-insensitively; `None` when the
+/// query's characters don't all appear in order. Exact names beat prefixes,
+/// prefixes beat substrings, and subsequences (`srchsvc` for `SearchService`)
+/// score by how tightly their characters cluster. File labels also match on
+/// their file name, so `main.rs` finds `cli/src/main.rs`.
+pub fn fuzzy_score(query: &str, label: &str) -> Option<u32> {
+    let query = query.to_lowercase();
+    let label = label.to_lowercase();
+    if query.is_empty() {
+        return Some(0);
+    }
+    let name = label.rsplit(['/', ':']).next().unwrap_or(&label);
+
+    if name == query || label == query {
+        return Some(1000);
+    }
+    if name.starts_with(&query) || label.starts_with(&query) {
+        return Some(800);
+    }
+    if label.contains(&query) {
+        return Some(600);
+    }
+
+    let mut chars = label.char_indices();
+    let mut first = None;
+    let mut last = 0;
+    for q in query.chars() {
+        let (i, _) = chars.by_ref().find(|&(_, c)| c == q)?;
+        first.get_or_insert(i);
+        last = i;
+    }
+    let span = last - first.unwrap_or(0) + 1;
+    let gaps = (span - query.len().min(span)) as u32;
+    Some(400u32.saturating_sub(gaps * 10).max(1))
+}
+
+/// Layered BFS for `shortest_paths`, keeping every parent at the shortest
+/// depth so that all equally short chains can be reconstructed afterwards.
+struct PathSearch {
+    source: String,
+    target: String,
+    depth: HashMap<String, usize>,
+    /// `(parent, relation)` of each reached node, one per shortest route in.
+    parents: HashMap<String, Vec<(String, String)>>,
+    queue: VecDeque<String>,
+}
+
+impl PathSearch {
+    fn new(source: String, target: String) -> Self {
+        let mut depth = HashMap::new();
+        depth.insert(source.clone(), 0);
+        let queue = VecDeque::from([source.clone()]);
+        Self { source, target, depth, parents: HashMap::new(), queue }
+    }
+
+    /// The next node to expand and its depth. Nodes `max_hops` away, or no
+    /// closer than the target once it is reached, are not expanded.
+    fn next(&mut self, max_hops: usize) -> Option<(String, usize)> {
+        while let Some(current) = self.queue.pop_front() {
+            let d = self.depth[&current];
+            if d >= max_hops || self.depth.get(&self.target).is_some_and(|&t| d >= t) {
+                continue;
+            }
+            return Some((current, d));
+        }
+        None
+    }
+
+    /// Whether `node` has not been reached yet.
+    fn is_new(&self, node: &str) -> bool {
+        !self.depth.contains_key(node)
+    }
+
+    /// Record the edge `from -relation-> to` found while expanding `from` at depth `d`.
+    fn reach(&mut self, from: &str, d: usize, to: String, relation: String) {
+        match self.depth.get(&to) {
+            Some(&nd) if nd == d + 1 => {
+                self.parents.entry(to).or_default().push((from.to_string(), relation));
+            }
+            Some(_) => {}
+            None => {
+                self.depth.insert(to.clone(), d + 1);
+                self.parents.entry(to.clone()).or_default().push((from.to_string(), relation));
+                self.queue.push_back(to);
+            }
+        }
+    }
+
+    /// Up to `max_paths` shortest chains as `(node ids, relations)`, source
+    /// first; empty when the target wasn't reached.
+    fn paths(&self, max_paths: usize) -> Vec<(Vec<String>, Vec<String>)> {
+        let mut raw_paths: Vec<(Vec<String>, Vec<String>)> = Vec::new();
+        if !self.depth.contains_key(&self.target) {
+            return raw_paths;
+        }
+        // Walk parent links back from the target, stopping once enough paths are collected.
+        let mut stack = vec![(self.target.clone(), vec![self.target.clone()], Vec::<String>::new())];
+        while let Some((current, ids, relations)) = stack.pop() {
+            if raw_paths.len() >= max_paths {
+                break;
+            }
+            if current == self.source {
+                let mut ids = ids;
+                let mut relations = relations;
+                ids.reverse();
+                relations.reverse();
+                raw_paths.push((ids, relations));
+                continue;
+            }
+            if let Some(ps) = self.parents.get(&current) {
+                for (parent, relation) in ps.iter().rev() {
+                    let mut ids = ids.clone();
+                    let mut relations = relations.clone();
+                    ids.push(parent.clone());
+                    relations.push(relation.clone());
+                    stack.push((parent.clone(), ids, relations));
+                }
+            }
+        }
+        raw_paths
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_score_ranks_exact_prefix_substring_subsequence() {
+        let exact = fuzzy_score("searchservice", "SearchService").unwrap();
+        let prefix = fuzzy_score("search", "SearchService").unwrap();
+        let substring = fuzzy_score("service", "SearchService").unwrap();
+        let subsequence = fuzzy_score("srchsvc", "SearchService").unwrap();
+        assert!(exact > prefix && prefix > substring && substring > subsequence);
+
+        assert_eq!(fuzzy_score("main.rs", "cli/src/main.rs"), Some(1000));
+        assert_eq!(fuzzy_score("xyz", "SearchService"), None);
+        assert!(fuzzy_score("sse", "SearchService") > fuzzy_score("sse", "SomeLongishEnumerator"));
+    }
+
+    fn chains(edges: &[(&str, &str)], from: &str, to: &str, max_hops: usize) -> Vec<Vec<String>> {
+        let mut search = PathSearch::new(from.to_string(), to.to_string());
+        while let Some((current, d)) = search.next(max_hops) {
+            for (_, next) in edges.iter().filter(|(a, _)| *a == current) {
+                search.reach(&current, d, next.to_string(), "calls".to_string());
+            }
+        }
+        search.paths(10).into_iter().map(|(ids, _)| ids).collect()
+    }
+
+    #[test]
+    fn test_path_search_finds_shortest_chains() {
+        let edges = [("a", "b"), ("b", "c"), ("c", "d"), ("a", "e"), ("e", "d"), ("a", "f"), ("f", "d"), ("x", "a")];
+        let mut found = chains(&edges, "a", "d", 5);
+        found.sort();
+        assert_eq!(found, vec![vec!["a", "e", "d"], vec!["a", "f", "d"]]);
+
+        // Within the hop limit, and not at all past it.
+        assert_eq!(chains(&edges, "a", "c", 2), vec![vec!["a", "b", "c"]]);
+        assert!(chains(&edges, "a", "c", 1).is_empty());
+        // Edges are followed forwards only.
+        assert!(chains(&edges, "a", "x", 5).is_empty());
+    }
+}
@@ -1,3 +1,4 @@
+pub mod export;
 pub mod fs;
 pub mod graph;
 pub mod search;
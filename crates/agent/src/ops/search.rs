@@ -2,13 +2,14 @@ use crate::project::context::RepoContext;
 use crate::project::types::SymbolHit;
 use anyhow::Result;
 use emry_core::models::{Language, ScoredChunk};
-use emry_engine::search::service::SearchService;
+use emry_engine::search::service::{MultiSearchHit, SearchService};
 use std::path::PathBuf;
 use std::sync::Arc;
 
 use serde::Serialize;
 
 pub struct Search {
+    ctx: Arc<RepoContext>,
     service: Arc<SearchService>,
 }
 
@@ -19,11 +20,22 @@ pub struct SearchResult {
 }
 
 impl Search {
-    pub fn new(_ctx: Arc<RepoContext>, service: Arc<SearchService>) -> Self {
-        Self { service }
+    pub fn new(ctx: Arc<RepoContext>, service: Arc<SearchService>) -> Self {
+        Self { ctx, service }
+    }
+
+    /// `query` with misspelled identifiers corrected, when it has any.
+    pub async fn did_you_mean(&self, query: &str) -> Option<String> {
+        self.service.did_you_mean(query).await
+    }
+
+    /// Agent searches always run the corrected query.
+    async fn corrected(&self, query: &str) -> String {
+        self.did_you_mean(query).await.unwrap_or_else(|| query.to_string())
     }
 
     pub async fn search(&self, query: &str, limit: usize) -> Result<SearchResult> {
+        let query = &self.corrected(query).await;
         let results = self.service.search(query, limit, None).await?;
         
         let chunks: Vec<ScoredChunk> = results.into_iter().map(|c| {
@@ -37,11 +49,14 @@ impl Search {
                     content_hash: "".to_string(),
                     embedding: c.embedding,
                     scope_path: c.scopes,
-                    language: Language::Unknown,
+                    language: c.language.as_deref().map_or(Language::Unknown, Language::from_name),
                     start_byte: None,
                     end_byte: None,
                     node_type: "".to_string(),
                     parent_scope: None,
+                    tags: c.tags,
+                    doc: c.doc,
+                    doc_embedding: None,
                 },
                 score: 1.0,
                 lexical_score: None,
@@ -50,6 +65,8 @@ impl Search {
                 symbol_boost: None,
                 graph_path: None,
                 graph_distance: None,
+                rerank_score: None,
+                ranking: Vec::new(),
             }
         }).collect();
 
@@ -61,6 +78,9 @@ impl Search {
                     continue;
                 }
                 let file_path = PathBuf::from(&node.file_path);
+                if !self.ctx.in_scope(&file_path) {
+                    continue;
+                }
                 let language = file_path
                     .extension()
                     .and_then(|e| e.to_str())
@@ -87,6 +107,8 @@ impl Search {
                         language: language.clone(),
                         doc_comment: None,
                         parent_scope: None,
+                        signature: None,
+                        visibility: None,
                     },
                 });
             }
@@ -95,8 +117,18 @@ impl Search {
         Ok(SearchResult { chunks, symbols })
     }
 
-    pub async fn search_with_context(&self, query: &str, limit: usize, _smart: bool) -> Result<emry_core::models::ContextGraph> {
-        self.service.search_with_context(query, limit, None).await
+    pub async fn search_many(&self, queries: &[String], limit: usize) -> Result<Vec<MultiSearchHit>> {
+        let mut corrected = Vec::with_capacity(queries.len());
+        for query in queries {
+            corrected.push(self.corrected(query).await);
+        }
+        self.service.search_many(&corrected, limit).await
+    }
+
+    /// `keywords` also match chunks by their index-time tags.
+    pub async fn search_with_context(&self, query: &str, limit: usize, keywords: Option<&[String]>) -> Result<emry_core::models::ContextGraph> {
+        let query = self.corrected(query).await;
+        self.service.search_with_context(&query, limit, keywords).await
     }
 
 }
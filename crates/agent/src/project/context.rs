@@ -1,6 +1,16 @@
 use super::embedder::{select_embedder, get_embedding_dimension};
-use anyhow::{anyhow, Context, Result};
+use super::reranker::select_reranker;
+use crate::llm::LlmCache;
+use anyhow::{Context, Result};
 use emry_config::Config;
+use emry_core::error::EmryError;
+use emry_core::scope::PathScope;
+use emry_core::traits::Reranker;
+use emry_engine::search::expansion::QueryExpander;
+use emry_engine::search::fusion::fuser_for;
+use emry_engine::search::intent::IntentScorer;
+use emry_engine::search::pipeline::{CjkSource, ScopeFilter, SparseSource};
+use emry_engine::search::service::SearchService;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::sync::Arc;
@@ -12,20 +22,50 @@ pub struct RepoContext {
     pub index_dir: PathBuf,
     pub config: Config,
     pub embedder: Option<Arc<dyn emry_core::traits::Embedder + Send + Sync>>,
+    pub reranker: Option<Arc<dyn Reranker>>,
     pub surreal_store: Option<Arc<emry_store::SurrealStore>>,
+    /// Paths retrieval is restricted to; `None` means the whole repository.
+    pub scope: Option<PathScope>,
+}
+
+/// Which heavyweight components to open alongside the repo context.
+///
+/// Opening the embedder may probe remote services and opening the store
+/// loads the vector index, so commands should request only what they use.
+/// The reranker (when `rerank.enabled`) is opened together with the embedder.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Components {
+    pub embedder: bool,
+    pub store: bool,
+}
+
+impl Components {
+    pub const ALL: Components = Components { embedder: true, store: true };
+    pub const STORE: Components = Components { embedder: false, store: true };
+    pub const NONE: Components = Components { embedder: false, store: false };
+}
+
+/// Search features available with the components that were opened.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capabilities {
+    pub lexical: bool,
+    pub graph: bool,
+    pub semantic: bool,
 }
 
 impl RepoContext {
     /// Build a context from the current working directory and optional config path.
     pub async fn from_env(config_path: Option<&Path>) -> Result<Self> {
+        Self::open(config_path, Components::ALL).await
+    }
+
+    /// Like `from_env`, but only initializes the requested components.
+    pub async fn open(config_path: Option<&Path>, components: Components) -> Result<Self> {
         let root = std::env::current_dir().context("failed to get current directory")?;
         let branch = current_branch();
         let index_dir = root.join(".codeindex").join("branches").join(&branch);
         if !index_dir.exists() {
-            return Err(anyhow!(
-                "Index not found at {}. Run `emry index --full` first.",
-                index_dir.display()
-            ));
+            return Err(EmryError::IndexNotFound { path: index_dir }.into());
         }
 
         let config = if let Some(path) = config_path {
@@ -35,12 +75,41 @@ impl RepoContext {
         };
 
         // Try to initialize embedder using config/environment.
-        let embedder = select_embedder(&config.embedding).await.ok();
-        let vector_dim = get_embedding_dimension(&config.embedding);
+        let embedder = if components.embedder {
+            select_embedder(&config.embedding).await.ok()
+        } else {
+            None
+        };
+
+        let reranker = if components.embedder {
+            match select_reranker(&config.rerank) {
+                Ok(r) => r,
+                Err(e) => {
+                    eprintln!("Reranker disabled: {}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
 
         // Initialize SurrealStore
-        let surreal_path = index_dir.join("surreal.db");
-        let surreal_store = emry_store::SurrealStore::new(&surreal_path, vector_dim).await.ok().map(Arc::new);
+        let surreal_store = if components.store {
+            // A running daemon holds the store's lock; a read-only open reads a snapshot instead.
+            if !config.core.read_only && daemon_running(&index_dir) {
+                return Err(EmryError::DaemonRunning.into());
+            }
+            let vector_dim = get_embedding_dimension(&config.embedding);
+            let surreal_path = index_dir.join("surreal.db");
+            let store = if config.core.read_only {
+                emry_store::SurrealStore::open_read_only(&surreal_path, vector_dim).await
+            } else {
+                emry_store::SurrealStore::open_quantized(&surreal_path, vector_dim, config.embedding.quantization).await
+            };
+            store.ok().map(Arc::new)
+        } else {
+            None
+        };
 
         Ok(Self {
             root,
@@ -48,9 +117,106 @@ impl RepoContext {
             index_dir,
             config,
             embedder,
+            reranker,
             surreal_store,
+            scope: None,
         })
     }
+
+    /// Search service over `store` using this context's embedder and, if configured,
+    /// reranker, with graph boosts from `graph` and `ranking.graph`, intent boosts
+    /// from `ranking.intent_profiles`, the fusion strategy from `ranking.fusion`
+    /// and a sparse source when `ranking.sparse` is set; results are restricted
+    /// to the context's scope when one is set.
+    pub fn search_service(&self, store: Arc<emry_store::SurrealStore>) -> SearchService {
+        let service = match &self.reranker {
+            Some(reranker) => SearchService::with_reranker(
+                store.clone(),
+                self.embedder.clone(),
+                reranker.clone(),
+                self.config.rerank.top_n,
+                &self.config.graph,
+                &self.config.ranking,
+            ),
+            None => SearchService::new(store.clone(), self.embedder.clone(), &self.config.graph, &self.config.ranking),
+        }
+        .with_scorer(Arc::new(IntentScorer::new(self.config.ranking.intent_profiles.clone())))
+        .with_fuser(fuser_for(&self.config.ranking));
+        let service = if self.config.ranking.sparse > 0.0 {
+            service.with_source(Arc::new(SparseSource::new(store.clone(), self.config.ranking.sparse)))
+        } else {
+            service
+        };
+        let service = if self.config.bm25.cjk_segmentation {
+            service.with_source(Arc::new(CjkSource::new(store.clone())))
+        } else {
+            service
+        };
+        let service = if self.config.search.query_expansion {
+            service.with_query_expander(Arc::new(QueryExpander::new(store)))
+        } else {
+            service
+        };
+        match &self.scope {
+            Some(scope) => service.with_filter(Arc::new(ScopeFilter::new(scope.clone()))),
+            None => service,
+        }
+    }
+
+    /// Like `search_service`, but over doc comments and docstrings, with the
+    /// configured fusion strategy and the context's scope.
+    pub fn docs_search_service(&self, store: Arc<emry_store::SurrealStore>) -> SearchService {
+        let service = SearchService::for_docs(store, self.embedder.clone()).with_fuser(fuser_for(&self.config.ranking));
+        match &self.scope {
+            Some(scope) => service.with_filter(Arc::new(ScopeFilter::new(scope.clone()))),
+            None => service,
+        }
+    }
+
+    /// Response cache for LLM calls, when `llm.cache.enabled` is set and the store is open.
+    pub fn llm_cache(&self) -> Option<LlmCache> {
+        LlmCache::from_config(self.surreal_store.clone()?, &self.config.llm.cache)
+    }
+
+        /// Restrict retrieval (search, file access, graph results) to `scope`.
+    pub fn with_scope(mut self, scope: PathScope) -> Self {
+        self.scope = Some(scope);
+        self
+    }
+
+    /// A context sharing this one's store, embedder and reranker with a different scope.
+    pub fn rescoped(&self, scope: Option<PathScope>) -> Self {
+        Self {
+            root: self.root.clone(),
+            branch: self.branch.clone(),
+            index_dir: self.index_dir.clone(),
+            config: self.config.clone(),
+            embedder: self.embedder.clone(),
+            reranker: self.reranker.clone(),
+            surreal_store: self.surreal_store.clone(),
+            scope,
+        }
+    }
+
+    /// A context sharing this one's store, embedder and reranker under another
+    /// config, e.g. to compare ranking settings; components are not reopened.
+    pub fn reconfigured(&self, config: Config) -> Self {
+        Self { config, ..self.rescoped(self.scope.clone()) }
+    }
+
+    /// Whether a repo-relative path is visible under the context's scope.
+    pub fn in_scope(&self, path: &Path) -> bool {
+        self.scope.as_ref().map_or(true, |s| s.contains(path))
+    }
+
+    pub fn capabilities(&self) -> Capabilities {
+        let store = self.surreal_store.as_ref();
+        Capabilities {
+            lexical: store.is_some(),
+            graph: store.is_some(),
+            semantic: self.embedder.is_some() && store.map(|s| s.has_vector_index()).unwrap_or(false),
+        }
+    }
 }
 
 fn current_branch() -> String {
@@ -71,3 +237,9 @@ fn current_branch() -> String {
     }
     "default".to_string()
 }
+
+/// Whether a daemon answers on the index's socket; a socket file nobody
+/// answers on is left over from a daemon that didn't exit cleanly.
+fn daemon_running(index_dir: &Path) -> bool {
+    std::os::unix::net::UnixStream::connect(index_dir.join("daemon.sock")).is_ok()
+}
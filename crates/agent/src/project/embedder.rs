@@ -1,6 +1,8 @@
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
+use crate::error::AgentError;
 use emry_config::{EmbeddingBackend, EmbeddingConfig};
+use emry_core::error::EmryError;
 use emry_core::traits::Embedder;
 use reqwest::Client;
 use serde::Deserialize;
@@ -10,6 +12,17 @@ use std::sync::Arc;
 /// Select an embedder based on configuration and available environment variables.
 /// Mirrors the CLI selection logic: prefer OpenAI if key is set, otherwise try Ollama.
 pub async fn select_embedder(config: &EmbeddingConfig) -> Result<Arc<dyn Embedder + Send + Sync>> {
+    if config.backend == EmbeddingBackend::None {
+        return Err(EmryError::from(AgentError::EmbedderUnavailable(
+            "embedding.backend is 'none'".to_string(),
+        ))
+        .into());
+    }
+
+    if config.backend == EmbeddingBackend::Onnx {
+        return select_onnx_embedder(config);
+    }
+
     if let Ok(api_key) = env::var("OPENAI_API_KEY") {
         let model = if config.backend == EmbeddingBackend::External && !config.model_name.is_empty()
         {
@@ -33,21 +46,52 @@ pub async fn select_embedder(config: &EmbeddingConfig) -> Result<Arc<dyn Embedde
         Ok(ollama) => Ok(Arc::new(ollama)),
         Err(err) => {
             eprintln!("Failed to init Ollama embedder: {}", err);
-            Err(anyhow!("No embedder available"))
+            Err(EmryError::from(AgentError::EmbedderUnavailable("No embedder available".to_string())).into())
         }
     }
 }
 
+#[cfg(feature = "onnx")]
+fn select_onnx_embedder(config: &EmbeddingConfig) -> Result<Arc<dyn Embedder + Send + Sync>> {
+    let model_path = config
+        .model_path
+        .as_ref()
+        .ok_or_else(|| anyhow!("embedding.model_path is required for the onnx backend"))?;
+    super::onnx::OnnxEmbedder::load(model_path)
+        .map(|e| Arc::new(e) as Arc<dyn Embedder + Send + Sync>)
+        .map_err(|e| EmryError::from(AgentError::EmbedderUnavailable(format!("{:#}", e))).into())
+}
+
+#[cfg(not(feature = "onnx"))]
+fn select_onnx_embedder(_config: &EmbeddingConfig) -> Result<Arc<dyn Embedder + Send + Sync>> {
+    Err(EmryError::from(AgentError::EmbedderUnavailable(
+        "embedding.backend is 'onnx' but emry was built without the `onnx` feature".to_string(),
+    ))
+    .into())
+}
+
 /// Determine the embedding dimension based on the configuration and environment.
-pub fn get_embedding_dimension(config: &EmbeddingConfig) -> usize {
+///
+/// Returns `None` when embeddings are disabled, so no vector index is created.
+pub fn get_embedding_dimension(config: &EmbeddingConfig) -> Option<usize> {
+    if config.backend == EmbeddingBackend::None {
+        return None;
+    }
+    if let Some(dim) = config.dimension {
+        return Some(dim);
+    }
+    if config.backend == EmbeddingBackend::Onnx {
+        return Some(384); // all-MiniLM-L6-v2 and similar small sentence-transformers
+    }
+
     if let Ok(_) = env::var("OPENAI_API_KEY") {
         if config.backend == EmbeddingBackend::External {
-             return 1536; // OpenAI text-embedding-3-small/large
+             return Some(1536); // OpenAI text-embedding-3-small/large
         }
     }
     
     // Default to 768 for local/ollama (nomic-embed-text)
-    768
+    Some(768)
 }
 
 struct ExternalEmbedder {
@@ -99,7 +143,11 @@ impl Embedder for ExternalEmbedder {
         if !resp.status().is_success() {
             let status = resp.status();
             let body = resp.text().await.unwrap_or_default();
-            return Err(anyhow!("OpenAI embeddings failed ({}): {}", status, body));
+            return Err(EmryError::from(AgentError::EmbedderUnavailable(format!(
+                "OpenAI embeddings failed ({}): {}",
+                status, body
+            )))
+            .into());
         }
 
         let parsed: OpenAIEmbeddingResponse = resp.json().await?;
@@ -151,7 +199,11 @@ impl Embedder for OllamaEmbedder {
         if !resp.status().is_success() {
             let status = resp.status();
             let body = resp.text().await.unwrap_or_default();
-            return Err(anyhow!("Ollama embeddings failed ({}): {}", status, body));
+            return Err(EmryError::from(AgentError::EmbedderUnavailable(format!(
+                "Ollama embeddings failed ({}): {}",
+                status, body
+            )))
+            .into());
         }
 
         let json: serde_json::Value = resp.json().await?;
@@ -173,3 +225,36 @@ impl Embedder for OllamaEmbedder {
         Ok(embeddings)
     }
 }
+
+/// Remembers embeddings by text, for running the same queries many times
+/// (e.g. `emry tune` trying ranking settings against a fixed query set).
+pub struct CachedEmbedder {
+    inner: Arc<dyn Embedder + Send + Sync>,
+    cache: std::sync::Mutex<std::collections::HashMap<String, Vec<f32>>>,
+}
+
+impl CachedEmbedder {
+    pub fn new(inner: Arc<dyn Embedder + Send + Sync>) -> Self {
+        Self { inner, cache: Default::default() }
+    }
+}
+
+#[async_trait]
+impl Embedder for CachedEmbedder {
+    async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        if let Some(embedding) = self.cache.lock().unwrap().get(text) {
+            return Ok(embedding.clone());
+        }
+        let embedding = self.inner.embed(text).await?;
+        self.cache.lock().unwrap().insert(text.to_string(), embedding.clone());
+        Ok(embedding)
+    }
+
+    async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let mut embeddings = Vec::with_capacity(texts.len());
+        for text in texts {
+            embeddings.push(self.embed(text).await?);
+        }
+        Ok(embeddings)
+    }
+}
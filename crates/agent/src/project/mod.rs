@@ -1,5 +1,8 @@
 pub mod context;
 pub mod embedder;
+#[cfg(feature = "onnx")]
+pub mod onnx;
+pub mod reranker;
 pub mod types;
 
-pub use context::RepoContext;
\ No newline at end of file
+pub use context::{Capabilities, Components, RepoContext};
\ No newline at end of file
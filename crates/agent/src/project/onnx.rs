@@ -0,0 +1,175 @@
+//! In-process sentence-transformer models via ONNX Runtime.
+//!
+//! Expects a directory exported the usual way for sentence-transformers:
+//! `model.onnx` (BERT-style inputs) next to a Hugging Face `tokenizer.json`.
+//! Bi-encoders expose `last_hidden_state`, which is mean-pooled over the
+//! attention mask and L2-normalized. Cross-encoders expose `logits` with one
+//! relevance score per (query, document) pair.
+
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use emry_core::traits::{Embedder, Reranker};
+use ndarray::{Array2, Axis};
+use ort::session::Session;
+use std::path::Path;
+use std::sync::Arc;
+use tokenizers::Tokenizer;
+
+/// Longest input (in tokens) fed to the model; longer chunks are truncated.
+const MAX_TOKENS: usize = 512;
+
+pub struct OnnxEmbedder {
+    session: Arc<Session>,
+    tokenizer: Arc<Tokenizer>,
+}
+
+fn load_model(model_dir: &Path) -> Result<(Arc<Session>, Arc<Tokenizer>)> {
+    let model_file = model_dir.join("model.onnx");
+    let tokenizer_file = model_dir.join("tokenizer.json");
+
+    let session = Session::builder()
+        .and_then(|b| b.commit_from_file(&model_file))
+        .with_context(|| format!("failed to load ONNX model {}", model_file.display()))?;
+
+    let mut tokenizer = Tokenizer::from_file(&tokenizer_file)
+        .map_err(|e| anyhow!("failed to load tokenizer {}: {}", tokenizer_file.display(), e))?;
+    tokenizer
+        .with_truncation(Some(tokenizers::TruncationParams {
+            max_length: MAX_TOKENS,
+            ..Default::default()
+        }))
+        .map_err(|e| anyhow!("invalid tokenizer truncation: {}", e))?;
+    tokenizer.with_padding(Some(tokenizers::PaddingParams::default()));
+
+    Ok((Arc::new(session), Arc::new(tokenizer)))
+}
+
+impl OnnxEmbedder {
+    pub fn load(model_dir: &Path) -> Result<Self> {
+        let (session, tokenizer) = load_model(model_dir)?;
+        Ok(Self { session, tokenizer })
+    }
+}
+
+/// Padded `input_ids`, `attention_mask` and `token_type_ids` for a batch.
+fn input_arrays(encodings: &[tokenizers::Encoding]) -> (Array2<i64>, Array2<i64>, Array2<i64>) {
+    let batch = encodings.len();
+    let seq_len = encodings.iter().map(|e| e.get_ids().len()).max().unwrap_or(0);
+
+    let mut ids = Array2::<i64>::zeros((batch, seq_len));
+    let mut mask = Array2::<i64>::zeros((batch, seq_len));
+    let mut type_ids = Array2::<i64>::zeros((batch, seq_len));
+    for (i, enc) in encodings.iter().enumerate() {
+        for (j, ((&id, &m), &t)) in enc
+            .get_ids()
+            .iter()
+            .zip(enc.get_attention_mask())
+            .zip(enc.get_type_ids())
+            .enumerate()
+        {
+            ids[[i, j]] = id as i64;
+            mask[[i, j]] = m as i64;
+            type_ids[[i, j]] = t as i64;
+        }
+    }
+    (ids, mask, type_ids)
+}
+
+fn embed_sync(session: &Session, tokenizer: &Tokenizer, texts: Vec<String>) -> Result<Vec<Vec<f32>>> {
+    let encodings = tokenizer
+        .encode_batch(texts, true)
+        .map_err(|e| anyhow!("tokenization failed: {}", e))?;
+    let batch = encodings.len();
+    let (ids, mask, type_ids) = input_arrays(&encodings);
+
+    let outputs = session.run(ort::inputs![
+        "input_ids" => ids.view(),
+        "attention_mask" => mask.view(),
+        "token_type_ids" => type_ids.view(),
+    ]?)?;
+    let hidden = outputs["last_hidden_state"].try_extract_tensor::<f32>()?;
+
+    let mut embeddings = Vec::with_capacity(batch);
+    for (i, row) in hidden.axis_iter(Axis(0)).enumerate() {
+        let dim = row.shape()[1];
+        let mut pooled = vec![0.0f32; dim];
+        let mut count = 0.0f32;
+        for (j, token) in row.axis_iter(Axis(0)).enumerate() {
+            if mask[[i, j]] == 0 {
+                continue;
+            }
+            count += 1.0;
+            for (p, v) in pooled.iter_mut().zip(token.iter()) {
+                *p += v;
+            }
+        }
+
+        let count = count.max(1.0);
+        pooled.iter_mut().for_each(|p| *p /= count);
+        let norm = pooled.iter().map(|v| v * v).sum::<f32>().sqrt().max(1e-12);
+        pooled.iter_mut().for_each(|p| *p /= norm);
+        embeddings.push(pooled);
+    }
+    Ok(embeddings)
+}
+
+#[async_trait]
+impl Embedder for OnnxEmbedder {
+    async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        let mut list = self.embed_batch(&[text.to_string()]).await?;
+        list.pop().ok_or_else(|| anyhow!("Empty embedding response"))
+    }
+
+    async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        // Inference is CPU-bound; keep it off the async runtime's worker threads.
+        let session = self.session.clone();
+        let tokenizer = self.tokenizer.clone();
+        let texts = texts.to_vec();
+        tokio::task::spawn_blocking(move || embed_sync(&session, &tokenizer, texts)).await?
+    }
+}
+
+/// Cross-encoder reranker (e.g. an ms-marco MiniLM export).
+pub struct OnnxCrossEncoder {
+    session: Arc<Session>,
+    tokenizer: Arc<Tokenizer>,
+}
+
+impl OnnxCrossEncoder {
+    pub fn load(model_dir: &Path) -> Result<Self> {
+        let (session, tokenizer) = load_model(model_dir)?;
+        Ok(Self { session, tokenizer })
+    }
+}
+
+fn score_sync(session: &Session, tokenizer: &Tokenizer, query: String, documents: Vec<String>) -> Result<Vec<f32>> {
+    let pairs: Vec<(String, String)> = documents.into_iter().map(|d| (query.clone(), d)).collect();
+    let encodings = tokenizer
+        .encode_batch(pairs, true)
+        .map_err(|e| anyhow!("tokenization failed: {}", e))?;
+    let (ids, mask, type_ids) = input_arrays(&encodings);
+
+    let outputs = session.run(ort::inputs![
+        "input_ids" => ids.view(),
+        "attention_mask" => mask.view(),
+        "token_type_ids" => type_ids.view(),
+    ]?)?;
+    let logits = outputs["logits"].try_extract_tensor::<f32>()?;
+
+    // [batch, 1] for relevance heads; take the first logit of each row either way.
+    Ok(logits
+        .axis_iter(Axis(0))
+        .map(|row| row.iter().next().copied().unwrap_or(f32::MIN))
+        .collect())
+}
+
+#[async_trait]
+impl Reranker for OnnxCrossEncoder {
+    async fn score(&self, query: &str, documents: &[String]) -> Result<Vec<f32>> {
+        let session = self.session.clone();
+        let tokenizer = self.tokenizer.clone();
+        let query = query.to_string();
+        let documents = documents.to_vec();
+        tokio::task::spawn_blocking(move || score_sync(&session, &tokenizer, query, documents)).await?
+    }
+}
@@ -0,0 +1,112 @@
+use crate::error::AgentError;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use emry_config::{RerankBackend, RerankConfig};
+use emry_core::error::EmryError;
+use emry_core::traits::Reranker;
+use reqwest::Client;
+use serde::Deserialize;
+use std::env;
+use std::sync::Arc;
+
+/// Build the reranker described by `config`, or `None` when reranking is disabled.
+pub fn select_reranker(config: &RerankConfig) -> Result<Option<Arc<dyn Reranker>>> {
+    if !config.enabled {
+        return Ok(None);
+    }
+    match config.backend {
+        RerankBackend::Api => {
+            let api_key = env::var("RERANK_API_KEY").map_err(|_| {
+                anyhow!("rerank.backend is 'api' but RERANK_API_KEY is not set")
+            })?;
+            let api_base = config
+                .api_base
+                .clone()
+                .unwrap_or_else(|| "https://api.cohere.com/v2".to_string());
+            Ok(Some(Arc::new(ApiReranker::new(config.model.clone(), api_key, api_base))))
+        }
+        RerankBackend::Onnx => select_onnx_reranker(config).map(Some),
+    }
+}
+
+#[cfg(feature = "onnx")]
+fn select_onnx_reranker(config: &RerankConfig) -> Result<Arc<dyn Reranker>> {
+    let model_path = config
+        .model_path
+        .as_ref()
+        .ok_or_else(|| anyhow!("rerank.model_path is required for the onnx backend"))?;
+    Ok(Arc::new(super::onnx::OnnxCrossEncoder::load(model_path)?))
+}
+
+#[cfg(not(feature = "onnx"))]
+fn select_onnx_reranker(_config: &RerankConfig) -> Result<Arc<dyn Reranker>> {
+    Err(anyhow!("rerank.backend is 'onnx' but emry was built without the `onnx` feature"))
+}
+
+/// Cohere-compatible `POST {api_base}/rerank` client (Cohere, Jina, Voyage, self-hosted TEI).
+struct ApiReranker {
+    model: String,
+    api_key: String,
+    api_base: String,
+    client: Client,
+}
+
+impl ApiReranker {
+    fn new(model: String, api_key: String, api_base: String) -> Self {
+        Self {
+            model,
+            api_key,
+            api_base,
+            client: Client::new(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct RerankResponse {
+    results: Vec<RerankResult>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RerankResult {
+    index: usize,
+    relevance_score: f32,
+}
+
+#[async_trait]
+impl Reranker for ApiReranker {
+    async fn score(&self, query: &str, documents: &[String]) -> Result<Vec<f32>> {
+        let resp = self
+            .client
+            .post(format!("{}/rerank", self.api_base.trim_end_matches('/')))
+            .bearer_auth(&self.api_key)
+            .json(&serde_json::json!({
+                "model": self.model,
+                "query": query,
+                "documents": documents,
+                "top_n": documents.len(),
+            }))
+            .send()
+            .await?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let body = resp.text().await.unwrap_or_default();
+            return Err(EmryError::from(AgentError::LlmApi {
+                provider: "rerank",
+                status: status.as_u16(),
+                body,
+            })
+            .into());
+        }
+
+        let parsed: RerankResponse = resp.json().await?;
+        let mut scores = vec![f32::MIN; documents.len()];
+        for r in parsed.results {
+            if let Some(slot) = scores.get_mut(r.index) {
+                *slot = r.relevance_score;
+            }
+        }
+        Ok(scores)
+    }
+}
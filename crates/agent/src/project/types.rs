@@ -34,6 +34,11 @@ pub struct GraphEdge {
     pub source: String,
     pub target: String,
     pub kind: String,
+    /// Call site in the source's file, for `calls` edges
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub column: Option<usize>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -3,6 +3,7 @@ use crate::ops::fs::FsTool;
 use crate::ops::search::Search;
 use crate::ops::graph::GraphTool;
 use anyhow::Result;
+use emry_core::entry_points::EntryPointKind;
 use emry_core::traits::LLM;
 use emry_engine::search::service::SearchService;
 use crate::project::context::RepoContext;
@@ -110,6 +111,14 @@ Return the final report in Markdown. Be specific.
              send_step(format!("Top central nodes: {}", top_nodes.join(", ")));
         }
             
+        // Tests say little about the architecture; the other kinds are its front doors.
+        let entry_points: Vec<String> = self.inner.entry_points().await?
+            .into_iter()
+            .filter(|e| e.entry_kind != EntryPointKind::Test)
+            .map(|e| format!("- {} ({}) in {}", e.label, e.entry_kind.as_str(), e.file_path))
+            .collect();
+        send_step(format!("Found {} entry points.", entry_points.len()));
+
         send_step("Sampling content from central hubs...".to_string());
         let mut hub_summaries = String::new();
         for node in central_nodes.iter().take(3) {
@@ -118,6 +127,14 @@ Return the final report in Markdown. Be specific.
              hub_summaries.push_str(&format!("\n--- File: {} (In-Degree: {}) ---\n{}\n", node.file_path, node.in_degree, content));
         }
             
+        let overview = match &self.ctx.surreal_store {
+            Some(store) => crate::workflows::summarize::overview(store, self.ctx.scope.as_ref()).await?,
+            None => None,
+        };
+        if overview.is_some() {
+            send_step("Including repository and module summaries...".to_string());
+        }
+
         send_step("Synthesizing architectural description...".to_string());
         
         let prompt = format!(
@@ -126,16 +143,22 @@ Return the final report in Markdown. Be specific.
             {:#?}\n\n\
             ## Central Hubs (High In-Degree Nodes)\n\
             {:#?}\n\n\
+            ## Entry Points (main, route and command handlers, library roots)\n\
+            {}\n\n\
             ## Key File Samples (Top Hubs)\n\
             {}\n\n\
+            ## Repository and Module Summaries\n\
+            {}\n\n\
             ## Instructions\n\
-            1. Identify the main architectural layers (e.g., Core, Infrastructure, API).\n\
+            1. Identify the main architectural layers (e.g., Core, Infrastructure, API) and how requests enter them from the entry points.\n\
             2. Describe the data flow and key abstractions.\n\
             3. Identify any potential architectural violations or circular dependencies.\n\
             4. Write in a clear, narrative style.",
             coupling.iter().take(20).collect::<Vec<_>>(),
             central_nodes,
-            hub_summaries
+            if entry_points.is_empty() { "(none detected)".to_string() } else { entry_points[..entry_points.len().min(40)].join("\n") },
+            hub_summaries,
+            overview.as_deref().unwrap_or("(not generated; see `emry summarize`)")
         );
             
         let report = self.llm.complete(&prompt).await?;
@@ -0,0 +1,387 @@
+use crate::project::context::RepoContext;
+use anyhow::{anyhow, Result};
+use emry_config::{Config, EmbeddingBackend};
+use emry_core::models::Language;
+use emry_core::relations::extract_calls_imports;
+use emry_core::tags_extractor::has_parse_errors;
+use emry_engine::ingest::pipeline::compute_hash;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::path::Path;
+
+/// Score lost when every reference is unresolved, every file fails to parse, etc.
+const UNRESOLVED_WEIGHT: f32 = 30.0;
+const PARSE_ERROR_WEIGHT: f32 = 25.0;
+const STALE_WEIGHT: f32 = 25.0;
+const VECTOR_WEIGHT: f32 = 20.0;
+
+/// Ratios above these produce a recommendation.
+const UNRESOLVED_THRESHOLD: f32 = 0.5;
+const PARSE_ERROR_THRESHOLD: f32 = 0.02;
+const STALE_THRESHOLD: f32 = 0.0;
+const VECTOR_THRESHOLD: f32 = 0.95;
+/// Above this share of stale files a full rebuild beats an incremental one.
+const FULL_REINDEX_THRESHOLD: f32 = 0.5;
+/// Directories with parse errors named in the exclude suggestion.
+const MAX_SUGGESTED_EXCLUDES: usize = 5;
+
+/// Call and import references of one language, and how many name no indexed symbol.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LanguageHealth {
+    pub language: String,
+    pub files: usize,
+    pub parse_errors: usize,
+    pub references: usize,
+    pub unresolved: usize,
+}
+
+/// A fix for one finding; lower `priority` comes first.
+#[derive(Debug, Clone, Serialize)]
+pub struct Recommendation {
+    pub priority: usize,
+    pub issue: String,
+    pub action: String,
+    /// YAML to merge into the config file, when the fix is a config change.
+    pub config: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct HealthReport {
+    /// 0-100; each metric below takes off up to its weight.
+    pub score: u8,
+    pub files: usize,
+    pub parse_errors: usize,
+    /// Indexed files whose working-tree content differs or that no longer exist.
+    pub stale_files: usize,
+    /// Distinct chunks; identical copies are counted in `duplicate_chunks`.
+    pub chunks: usize,
+    pub embedded_chunks: usize,
+    pub duplicate_chunks: usize,
+    pub references: usize,
+    pub unresolved: usize,
+    pub languages: Vec<LanguageHealth>,
+    /// Files tree-sitter could not parse cleanly.
+    pub parse_error_files: Vec<String>,
+    pub integrity: IntegrityReport,
+    pub recommendations: Vec<Recommendation>,
+}
+
+/// Store inconsistencies left by interrupted or partial writes.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct IntegrityReport {
+    /// Non-empty files that are tracked but have no chunks.
+    pub files_without_chunks: Vec<String>,
+    /// Files with chunks lacking an embedding; empty without an embedding backend.
+    pub files_missing_embeddings: Vec<String>,
+    /// Edges whose source or target no longer exists.
+    pub dangling_edges: usize,
+    /// Files `repair` reindexes.
+    pub affected_files: Vec<String>,
+    #[serde(skip)]
+    dangling_edge_ids: Vec<String>,
+}
+
+impl IntegrityReport {
+    pub fn is_clean(&self) -> bool {
+        self.dangling_edges == 0 && self.affected_files.is_empty()
+    }
+}
+
+impl HealthReport {
+    pub fn unresolved_ratio(&self) -> f32 {
+        ratio(self.unresolved, self.references)
+    }
+
+    pub fn parse_error_rate(&self) -> f32 {
+        ratio(self.parse_errors, self.files)
+    }
+
+    pub fn stale_ratio(&self) -> f32 {
+        ratio(self.stale_files, self.files)
+    }
+
+    /// Share of chunks with an embedding; 1.0 for an empty index.
+    pub fn vector_coverage(&self) -> f32 {
+        if self.chunks == 0 {
+            1.0
+        } else {
+            ratio(self.embedded_chunks, self.chunks)
+        }
+    }
+}
+
+/// Measure the index against the working tree and score it.
+pub async fn diagnose(ctx: &RepoContext) -> Result<HealthReport> {
+    let store = ctx.surreal_store.as_ref().ok_or_else(|| anyhow!("SurrealStore not initialized"))?;
+
+    let symbols: HashSet<String> = store.list_all_symbols().await?.into_iter().map(|s| s.label).collect();
+    let chunked: HashSet<String> = store.files_with_chunks().await?.into_iter().collect();
+    let mut tracked: HashSet<String> = HashSet::new();
+    let mut report = HealthReport::default();
+    let mut languages: BTreeMap<String, LanguageHealth> = BTreeMap::new();
+
+    for file in store.list_files().await? {
+        report.files += 1;
+        let language = Language::from_name(&file.language);
+        let stats = languages.entry(file.language.clone()).or_insert_with(|| LanguageHealth {
+            language: file.language.clone(),
+            ..Default::default()
+        });
+        stats.files += 1;
+        tracked.insert(file.path.clone());
+        if !chunked.contains(&file.path) && !file.content.trim().is_empty() {
+            report.integrity.files_without_chunks.push(file.path.clone());
+        }
+
+        match std::fs::read_to_string(ctx.root.join(&file.path)) {
+            Ok(current) if compute_hash(&current) == file.hash => {}
+            _ => report.stale_files += 1,
+        }
+
+        if has_parse_errors(&file.content, &language) == Some(true) {
+            stats.parse_errors += 1;
+            report.parse_errors += 1;
+            report.parse_error_files.push(file.path.clone());
+        }
+
+        let Ok((calls, imports)) = extract_calls_imports(&language, &file.content) else {
+            continue;
+        };
+        for relation in calls.iter().chain(imports.iter()) {
+            stats.references += 1;
+            if !symbols.contains(last_segment(&relation.name)) {
+                stats.unresolved += 1;
+            }
+        }
+    }
+
+    report.languages = languages.into_values().filter(|l| l.references > 0 || l.parse_errors > 0).collect();
+    report.references = report.languages.iter().map(|l| l.references).sum();
+    report.unresolved = report.languages.iter().map(|l| l.unresolved).sum();
+    report.parse_error_files.sort();
+    (report.chunks, report.embedded_chunks) = store.count_embedded_chunks().await?;
+    report.duplicate_chunks = store.count_duplicate_chunks().await?;
+
+    let integrity = &mut report.integrity;
+    if ctx.config.embedding.backend != EmbeddingBackend::None {
+        integrity.files_missing_embeddings = store.files_missing_embeddings().await?;
+    }
+    let mut affected: BTreeSet<String> = integrity.files_without_chunks.iter().cloned().collect();
+    affected.extend(integrity.files_missing_embeddings.iter().cloned());
+    for edge in store.dangling_edges().await? {
+        // Sources in deleted files need no reindex; the edge just goes.
+        if let Some(file) = edge.source_file.filter(|f| tracked.contains(f)) {
+            affected.insert(file);
+        }
+        integrity.dangling_edge_ids.push(edge.id);
+    }
+    integrity.dangling_edges = integrity.dangling_edge_ids.len();
+    integrity.files_without_chunks.sort();
+    integrity.files_missing_embeddings.sort();
+    integrity.affected_files = affected.into_iter().collect();
+
+    report.score = score(&report, &ctx.config);
+    report.recommendations = advise(&report, &ctx.config, &ctx.root);
+    Ok(report)
+}
+
+/// Delete dangling edges and drop the affected files from the index, so the
+/// next `emry index` run picks them up as new. Returns the files dropped.
+pub async fn repair(ctx: &RepoContext, integrity: &IntegrityReport) -> Result<usize> {
+    let store = ctx.surreal_store.as_ref().ok_or_else(|| anyhow!("SurrealStore not initialized"))?;
+    store.delete_edges(&integrity.dangling_edge_ids).await?;
+    for path in &integrity.affected_files {
+        store.delete_file(path).await?;
+    }
+    Ok(integrity.affected_files.len())
+}
+
+fn score(report: &HealthReport, config: &Config) -> u8 {
+    let vector_penalty = if config.embedding.backend == EmbeddingBackend::None {
+        0.0
+    } else {
+        VECTOR_WEIGHT * (1.0 - report.vector_coverage())
+    };
+    let penalty = UNRESOLVED_WEIGHT * report.unresolved_ratio()
+        + PARSE_ERROR_WEIGHT * report.parse_error_rate()
+        + STALE_WEIGHT * report.stale_ratio()
+        + vector_penalty;
+    (100.0 - penalty).round().clamp(0.0, 100.0) as u8
+}
+
+/// Recommendations for the findings in `report`, most score-relevant first.
+fn advise(report: &HealthReport, config: &Config, root: &Path) -> Vec<Recommendation> {
+    // (score impact, recommendation)
+    let mut found: Vec<(f32, Recommendation)> = Vec::new();
+
+    let stale = report.stale_ratio();
+    if stale > STALE_THRESHOLD {
+        let (action, config_change) = if stale > FULL_REINDEX_THRESHOLD {
+            ("Rebuild the index with `emry index --full`.", None)
+        } else if !config.core.auto_index_on_search {
+            (
+                "Run `emry index`, or let searches refresh the index when it falls behind.",
+                Some("core:\n  auto_index_on_search: true".to_string()),
+            )
+        } else {
+            ("Run `emry index`, or keep `emry watch` running while you work.", None)
+        };
+        found.push((STALE_WEIGHT * stale, Recommendation {
+            priority: 0,
+            issue: format!("{} of {} indexed files changed since the last index run", report.stale_files, report.files),
+            action: action.to_string(),
+            config: config_change,
+        }));
+    }
+
+    if !report.integrity.is_clean() {
+        let integrity = &report.integrity;
+        found.push((STALE_WEIGHT * ratio(integrity.affected_files.len().max(1), report.files), Recommendation {
+            priority: 0,
+            issue: format!(
+                "{} dangling edges, {} files without chunks, {} files with unembedded chunks",
+                integrity.dangling_edges,
+                integrity.files_without_chunks.len(),
+                integrity.files_missing_embeddings.len()
+            ),
+            action: "Run `emry doctor --repair` to clear the leftovers and reindex the affected files.".to_string(),
+            config: None,
+        }));
+    }
+
+    let parse_errors = report.parse_error_rate();
+    if parse_errors > PARSE_ERROR_THRESHOLD {
+        let mut dirs: Vec<String> = report
+            .parse_error_files
+            .iter()
+            .map(|p| Path::new(p).parent().map(|d| d.to_string_lossy().into_owned()).unwrap_or_default())
+            .filter(|d| !d.is_empty())
+            .collect();
+        dirs.dedup();
+        dirs.truncate(MAX_SUGGESTED_EXCLUDES);
+        let config_change = (!dirs.is_empty()).then(|| {
+            let globs: Vec<String> = dirs.iter().map(|d| format!("    - \"{}/**\"", d)).collect();
+            format!("core:\n  exclude_paths:\n{}", globs.join("\n"))
+        });
+        found.push((PARSE_ERROR_WEIGHT * parse_errors, Recommendation {
+            priority: 0,
+            issue: format!("{} of {} files have syntax errors", report.parse_errors, report.files),
+            action: "Exclude generated, minified or vendored files that fail to parse.".to_string(),
+            config: config_change,
+        }));
+    }
+
+    for lang in &report.languages {
+        let unresolved = ratio(lang.unresolved, lang.references);
+        if unresolved <= UNRESOLVED_THRESHOLD {
+            continue;
+        }
+        let issue = format!(
+            "{:.0}% of {} calls and imports name no indexed symbol",
+            unresolved * 100.0,
+            lang.language
+        );
+        let (action, config_change) = match Language::from_name(&lang.language) {
+            Language::TypeScript | Language::JavaScript
+                if !root.join("tsconfig.json").exists() && !root.join("jsconfig.json").exists() =>
+            {
+                (
+                    "Put the tsconfig.json (or jsconfig.json) that defines path aliases at the repository root, then run `emry index --full`.",
+                    None,
+                )
+            }
+            Language::Rust if !root.join("Cargo.toml").exists() => (
+                "Index from the Cargo workspace root so crate paths resolve, then run `emry index --full`.",
+                None,
+            ),
+            _ if config.core.exclude_paths.is_empty() => (
+                "References into dependencies never resolve; if vendored code is indexed, exclude it so the graph covers only your code.",
+                Some("core:\n  exclude_paths:\n    - \"**/node_modules/**\"\n    - \"**/vendor/**\"".to_string()),
+            ),
+            _ => (
+                "Most of these are calls into external libraries; index the packages you need resolved, or accept the gap.",
+                None,
+            ),
+        };
+        // Spread the global weight by each language's share of references.
+        let share = ratio(lang.references, report.references);
+        found.push((UNRESOLVED_WEIGHT * unresolved * share, Recommendation {
+            priority: 0,
+            issue,
+            action: action.to_string(),
+            config: config_change,
+        }));
+    }
+
+    if config.embedding.backend == EmbeddingBackend::None {
+        found.push((0.0, Recommendation {
+            priority: 0,
+            issue: "Semantic search is disabled (embedding.backend: none)".to_string(),
+            action: "Enable an embedding backend, then run `emry index --full`.".to_string(),
+            config: Some("embedding:\n  backend: local".to_string()),
+        }));
+    } else if report.vector_coverage() < VECTOR_THRESHOLD {
+        found.push((VECTOR_WEIGHT * (1.0 - report.vector_coverage()), Recommendation {
+            priority: 0,
+            issue: format!("{} of {} chunks have no embedding", report.chunks - report.embedded_chunks, report.chunks),
+            action: "Check the embedder with `emry status`, then run `emry index --full`.".to_string(),
+            config: None,
+        }));
+    }
+
+    found.sort_by(|a, b| b.0.total_cmp(&a.0));
+    found
+        .into_iter()
+        .enumerate()
+        .map(|(i, (_, rec))| Recommendation { priority: i + 1, ..rec })
+        .collect()
+}
+
+/// The referenced symbol of a call or import: `a::b::c`, `a.b.c` and `a/b/c` all name `c`.
+fn last_segment(name: &str) -> &str {
+    name.rsplit([':', '.', '/']).next().unwrap_or(name)
+}
+
+fn ratio(part: usize, whole: usize) -> f32 {
+    if whole == 0 {
+        0.0
+    } else {
+        part as f32 / whole as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_score_and_priorities() {
+        let report = HealthReport {
+            files: 10,
+            stale_files: 8,
+            parse_errors: 2,
+            parse_error_files: vec!["web/dist/app.min.js".to_string(), "web/dist/vendor.min.js".to_string()],
+            chunks: 100,
+            embedded_chunks: 100,
+            references: 10,
+            unresolved: 2,
+            languages: vec![LanguageHealth {
+                language: "rust".to_string(),
+                files: 10,
+                parse_errors: 2,
+                references: 10,
+                unresolved: 2,
+            }],
+            ..Default::default()
+        };
+        let config = Config::default();
+        // 100 - 30*0.2 - 25*0.2 - 25*0.8
+        assert_eq!(score(&report, &config), 69);
+
+        let recs = advise(&report, &config, Path::new("."));
+        assert_eq!(recs.len(), 2);
+        assert_eq!(recs[0].priority, 1);
+        assert!(recs[0].action.contains("--full"));
+        assert!(recs[1].config.as_deref().unwrap().contains("web/dist/**"));
+    }
+}
@@ -0,0 +1,258 @@
+//! Per-file explanation: what a file defines, what it depends on, and who uses it.
+//!
+//! Facts come straight from the index (symbols plus calls/imports edges that
+//! cross the file boundary) and the file's outline; the LLM only turns them
+//! into a short narrative. The rendered facts are useful on their own as
+//! agent context, so they are exposed separately from the narrative.
+
+use crate::llm::{Message, OpenAIProvider};
+use crate::ops::fs::FsTool;
+use crate::project::context::RepoContext;
+use anyhow::Result;
+use emry_core::error::EmryError;
+use emry_core::models::EdgeKind;
+use emry_core::summary::SummaryLevel;
+use emry_store::summary::SummaryRecord;
+use emry_store::{Direction, GraphBackend, SurrealGraphNode, SurrealStore};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use surrealdb::sql::Thing;
+
+/// Inbound/outbound edges listed per direction.
+const MAX_EDGES: usize = 25;
+/// Files listed as key callers.
+const MAX_CALLERS: usize = 5;
+const NARRATIVE_MAX_TOKENS: u32 = 900;
+
+/// An edge between a symbol in the explained file and one elsewhere.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrossEdge {
+    /// Name of the symbol (or file) on this side.
+    pub local: String,
+    /// Name of the symbol (or file) on the other side.
+    pub remote: String,
+    pub remote_file: String,
+    pub relation: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct FileFacts {
+    pub path: String,
+    pub symbols: Vec<SurrealGraphNode>,
+    pub outbound: Vec<CrossEdge>,
+    pub inbound: Vec<CrossEdge>,
+    /// Files calling into this one, by number of call sites, most first.
+    pub key_callers: Vec<(String, usize)>,
+    pub outline: Option<String>,
+    /// Summaries of the file and its enclosing directories from `emry summarize`, innermost first.
+    pub summaries: Vec<SummaryRecord>,
+    /// Other files with identical content, which share this file's summary.
+    pub copies: Vec<String>,
+}
+
+pub struct FileExplainWorkflow {
+    ctx: Arc<RepoContext>,
+    store: Arc<SurrealStore>,
+    fs: Arc<FsTool>,
+}
+
+impl FileExplainWorkflow {
+    pub fn new(ctx: Arc<RepoContext>, store: Arc<SurrealStore>, fs: Arc<FsTool>) -> Self {
+        Self { ctx, store, fs }
+    }
+
+    /// Collect the indexed facts about `path` (absolute or relative to the repo root).
+    pub async fn gather(&self, path: &Path) -> Result<FileFacts> {
+        let rel = path.strip_prefix(&self.ctx.root).unwrap_or(path);
+        let rel = rel.strip_prefix("./").unwrap_or(rel).to_string_lossy().to_string();
+
+        let file_id = Thing::from(("file", rel.as_str())).to_string();
+        if self.store.get_node(&file_id).await?.is_none() {
+            return Err(EmryError::NotFound(format!("file '{}' is not in the index", rel)).into());
+        }
+
+        let symbols = self.store.list_file_symbols(&rel).await?;
+        let mut local: Vec<(String, String)> = vec![(file_id, rel.clone())];
+        local.extend(symbols.iter().map(|s| (s.id.to_string(), s.label.clone())));
+
+        let kinds = [EdgeKind::Calls, EdgeKind::Imports];
+        let mut nodes: HashMap<String, Option<SurrealGraphNode>> = HashMap::new();
+        let mut outbound = Vec::new();
+        let mut inbound = Vec::new();
+
+        for (id, name) in &local {
+            for (direction, into) in [(Direction::Out, &mut outbound), (Direction::In, &mut inbound)] {
+                for edge in self.store.neighbors_of_kind(id, direction, &kinds).await? {
+                    let other = match direction {
+                        Direction::Out => &edge.target,
+                        Direction::In => &edge.source,
+                    };
+                    let key = other.to_string();
+                    if !nodes.contains_key(&key) {
+                        let node = self.store.get_node_by_thing(other).await?;
+                        nodes.insert(key.clone(), node);
+                    }
+                    let Some(node) = nodes[&key].as_ref() else {
+                        continue;
+                    };
+                    if node.file_path == rel {
+                        continue;
+                    }
+                    into.push(CrossEdge {
+                        local: name.clone(),
+                        remote: node.label.clone(),
+                        remote_file: node.file_path.clone(),
+                        relation: edge.relation.clone(),
+                    });
+                }
+            }
+        }
+        dedup(&mut outbound);
+        dedup(&mut inbound);
+        let key_callers = key_callers(&inbound);
+        let summaries = crate::workflows::summarize::enclosing_summaries(&self.store, &rel).await?;
+        let copies = match summaries.first().filter(|s| s.level == SummaryLevel::File) {
+            Some(summary) => {
+                let mut paths = self.store.summary_paths_with_hash(SummaryLevel::File, &summary.hash).await?;
+                paths.retain(|p| *p != rel);
+                paths
+            }
+            None => Vec::new(),
+        };
+
+        Ok(FileFacts {
+            outline: self.fs.generate_outline(Path::new(&rel)).ok(),
+            summaries,
+            copies,
+            path: rel,
+            symbols,
+            outbound,
+            inbound,
+            key_callers,
+        })
+    }
+
+    /// Turn `facts` into a concise narrative of the file's role.
+    pub async fn narrate(&self, llm: &OpenAIProvider, facts: &FileFacts) -> Result<String> {
+        let messages = [
+            Message {
+                role: "system".to_string(),
+                content: "You write onboarding documentation for a codebase. Given facts about one file, \
+                          explain in a few short paragraphs what the file is for, its main entry points, \
+                          what it relies on, and who relies on it. Use only the facts given; do not invent \
+                          symbols. Return Markdown."
+                    .to_string(),
+            },
+            Message {
+                role: "user".to_string(),
+                content: render_facts(facts),
+            },
+        ];
+        llm.chat_with_limit(&messages, Some(NARRATIVE_MAX_TOKENS)).await
+    }
+}
+
+fn dedup(edges: &mut Vec<CrossEdge>) {
+    let mut seen = std::collections::HashSet::new();
+    edges.retain(|e| seen.insert(e.clone()));
+}
+
+fn key_callers(inbound: &[CrossEdge]) -> Vec<(String, usize)> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for e in inbound.iter().filter(|e| e.relation == EdgeKind::Calls.as_str()) {
+        *counts.entry(e.remote_file.as_str()).or_default() += 1;
+    }
+    let mut callers: Vec<(String, usize)> = counts.into_iter().map(|(f, n)| (f.to_string(), n)).collect();
+    callers.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    callers.truncate(MAX_CALLERS);
+    callers
+}
+
+/// Markdown rendering of `facts`, used as the LLM prompt and as standalone agent context.
+pub fn render_facts(facts: &FileFacts) -> String {
+    let mut out = format!("# File: `{}`\n\n", facts.path);
+
+    if !facts.summaries.is_empty() {
+        out.push_str("## Summaries\n");
+        for s in &facts.summaries {
+            let subject = match s.level {
+                SummaryLevel::File if !facts.copies.is_empty() => {
+                    let copies: Vec<String> = facts.copies.iter().map(|p| format!("`{}`", p)).collect();
+                    format!("This file (shared with identical {})", copies.join(", "))
+                }
+                SummaryLevel::File => "This file".to_string(),
+                _ => format!("`{}/`", s.path),
+            };
+            out.push_str(&format!("- {}: {}\n", subject, s.text.replace('\n', " ")));
+        }
+        out.push('\n');
+    }
+
+    out.push_str("## Symbols\n");
+    if facts.symbols.is_empty() {
+        out.push_str("- (none indexed)\n");
+    }
+    for s in &facts.symbols {
+        match s.start_line {
+            Some(line) => out.push_str(&format!("- {} `{}` (line {})\n", s.kind, s.label, line)),
+            None => out.push_str(&format!("- {} `{}`\n", s.kind, s.label)),
+        }
+    }
+
+    render_edges(&mut out, "Depends on", &facts.outbound, "->");
+    render_edges(&mut out, "Used by", &facts.inbound, "<-");
+
+    if !facts.key_callers.is_empty() {
+        out.push_str("\n## Key callers\n");
+        for (file, n) in &facts.key_callers {
+            out.push_str(&format!("- `{}` ({} call{})\n", file, n, if *n == 1 { "" } else { "s" }));
+        }
+    }
+
+    if let Some(outline) = &facts.outline {
+        out.push_str(&format!("\n## Outline\n```\n{}\n```\n", outline.trim_end()));
+    }
+    out
+}
+
+fn render_edges(out: &mut String, title: &str, edges: &[CrossEdge], arrow: &str) {
+    out.push_str(&format!("\n## {}\n", title));
+    if edges.is_empty() {
+        out.push_str("- (nothing indexed)\n");
+    }
+    for e in edges.iter().take(MAX_EDGES) {
+        out.push_str(&format!(
+            "- `{}` {} `{}` in `{}` ({})\n",
+            e.local, arrow, e.remote, e.remote_file, e.relation
+        ));
+    }
+    if edges.len() > MAX_EDGES {
+        out.push_str(&format!("- ... {} more\n", edges.len() - MAX_EDGES));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(remote_file: &str) -> CrossEdge {
+        CrossEdge {
+            local: "f".to_string(),
+            remote: "g".to_string(),
+            remote_file: remote_file.to_string(),
+            relation: "calls".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_key_callers_ranked_by_call_count() {
+        let mut import = call("c.rs");
+        import.relation = "imports".to_string();
+        let inbound = vec![call("b.rs"), call("a.rs"), call("b.rs"), import];
+        assert_eq!(
+            key_callers(&inbound),
+            vec![("b.rs".to_string(), 2), ("a.rs".to_string(), 1)]
+        );
+    }
+}
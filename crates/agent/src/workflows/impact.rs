@@ -1,11 +1,17 @@
 use crate::project::context::RepoContext;
 use anyhow::Result;
-use emry_core::diff::{DiffAnalyzer, FileDiff};
-use std::path::PathBuf;
+use emry_core::diff::{is_public_declaration, AffectedSymbol, DiffAnalyzer, FileDiff};
+use emry_core::models::{EdgeKind, Language};
+use emry_store::{Direction, GraphBackend};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use serde::Serialize;
 use std::sync::Arc;
+use surrealdb::sql::Thing;
 use crate::llm::OpenAIProvider;
 use crate::cortex::{Cortex, CortexEvent};
 use crate::cortex::context::AgentContext;
+use emry_engine::ingest::pipeline::compute_hash;
 use emry_engine::search::service::SearchService;
 
 use crate::ops::fs::FsTool;
@@ -19,6 +25,25 @@ use crate::cortex::tools::{
     search::SearchCodeTool,
 };
 
+/// Hops followed from a changed symbol through its callers.
+const CALLER_DEPTH: usize = 3;
+/// Downstream callers listed in the report.
+const MAX_REPORTED_CALLERS: usize = 40;
+
+/// Code that calls into a changed symbol, directly or through other callers.
+#[derive(Debug, Clone)]
+pub struct DownstreamCaller {
+    pub name: String,
+    pub file_path: String,
+    pub start_line: Option<usize>,
+    /// Line and column of its call on the shortest path to a changed symbol.
+    pub call_site: Option<(usize, usize)>,
+    /// Hops from the nearest changed symbol; 1 is a direct caller.
+    pub depth: usize,
+    /// Changed symbols this caller reaches.
+    pub reaches: Vec<String>,
+}
+
 pub struct ImpactWorkflow {
     ctx: Arc<RepoContext>,
     llm: OpenAIProvider,
@@ -38,29 +63,44 @@ impl ImpactWorkflow {
         Ok(Self { ctx, llm, fs, graph, search })
     }
 
-    pub async fn run_analysis<F>(&self, file_path: &str, start_line: usize, end_line: usize, mut callback: F) -> Result<String> 
+    pub async fn run_analysis<F>(&self, file_path: &str, start_line: usize, end_line: usize, callback: F) -> Result<String> 
     where F: FnMut(CortexEvent) + Send + Sync + 'static
     {
-        callback(CortexEvent::Thought(format!("Calculating precise diff impact detection for {}:{}-{}...", file_path, start_line, end_line)));
-        
         let diff = FileDiff {
             path: PathBuf::from(file_path),
             changed_ranges: vec![(start_line, end_line)],
         };
-        
+        let change = format!("`{}` (lines {}-{})", file_path, start_line, end_line);
+        self.run_diff_analysis(&change, &[diff], callback).await
+    }
+
+    /// Impact of every hunk in `diffs`, with downstream callers aggregated across files.
+    /// `change` describes the change for the prompt and the no-symbols message.
+    pub async fn run_diff_analysis<F>(&self, change: &str, diffs: &[FileDiff], mut callback: F) -> Result<String>
+    where F: FnMut(CortexEvent) + Send + Sync + 'static
+    {
+        callback(CortexEvent::Thought(format!("Calculating precise diff impact detection for {}...", change)));
+
         let affected_symbols = {
             let mut analyzer = DiffAnalyzer::new()?;
-            analyzer.find_affected_symbols(&[diff], &self.ctx.root)?
+            analyzer.find_affected_symbols(diffs, &self.ctx.root)?
         };
 
         if affected_symbols.is_empty() {
-             return Ok(format!("Analysis COMPLETE.\n\nNo code symbols were found in the changed range {}:{}-{}. This might be a change to comments, whitespace, or non-code files.\n\n**Risk Level:** Low.", file_path, start_line, end_line));
+             return Ok(format!("Analysis COMPLETE.\n\nNo code symbols were found in {}. This might be a change to comments, whitespace, or non-code files.\n\n**Risk Level:** Low.", change));
         }
 
         let symbol_context: Vec<String> = affected_symbols.iter()
-            .map(|s| format!("- {} ({})", s.name, s.kind))
+            .map(|s| format!("- {} ({}) in `{}`", s.name, s.kind, s.file_path))
             .collect();
 
+        let callers = downstream_callers(&self.ctx, &affected_symbols).await?;
+        let coverage = uncovered_changes(&self.ctx, diffs).await?;
+        let mut facts = render_impact_facts(&affected_symbols, &callers);
+        if let Some(coverage) = &coverage {
+            facts.push_str(&format!("\n## Changed Lines Uncovered by Tests\n{}\n", render_coverage(coverage)));
+        }
+
         callback(CortexEvent::Thought(format!("Identified modified symbols: {}", affected_symbols.iter().map(|s| s.name.clone()).collect::<Vec<_>>().join(", "))));
 
         let mut agent_ctx = AgentContext::new(
@@ -86,15 +126,21 @@ impl ImpactWorkflow {
 r#"You are an expert Senior Staff Engineer doing a Code Review / Impact Analysis.
 
 # THE CHANGE
-The user has modified the following file: `{file_path}` (lines {start_line}-{end_line}).
+The user has modified {change}.
 Static analysis indicates the following symbols were modified:
 {symbols}
 
+# DOWNSTREAM CALLERS (from the code graph)
+{callers}
+
+# TEST COVERAGE (changed lines no test ran)
+{coverage}
+
 # YOUR MISSION
 Analyze the **semantic impact** and **risk** of this change. Don't just list callers; explain strictly *how* they are affected.
 
 # STRATEGY
-1. **Verify Context**: Read the modified code in `{file_path}` to understand the *nature* of the change (bug fix? refactor? breaking change?).
+1. **Verify Context**: Read the modified code to understand the *nature* of the change (bug fix? refactor? breaking change?).
 2. **Trace Dependencies**: Use `find_usages` or `inspect_graph` on the modified symbols to find who calls them.
 3. **Analyze Call Sites**: REQUIRED: Read the code of at least the most critical call sites to see if the change breaks assumptions (e.g., nullability, arguments, side effects).
 4. **Conclusion**: Rate the Risk (Low/Medium/High) and explain why.
@@ -102,14 +148,251 @@ Analyze the **semantic impact** and **risk** of this change. Don't just list cal
 # OUTPUT FORMAT
 Return a Markdown report.
 "#,
-            file_path = file_path,
-            start_line = start_line,
-            end_line = end_line,
-            symbols = symbol_context.join("\n")
+            change = change,
+            symbols = symbol_context.join("\n"),
+            callers = render_callers(&callers),
+            coverage = coverage.as_deref().map_or_else(|| "- (no coverage report imported)".to_string(), render_coverage),
         );
 
         let result = cortex.run(&prompt, callback).await?;
 
-        Ok(result)
+        Ok(format!("{}\n\n{}", result.trim_end(), facts))
+    }
+}
+
+/// Callers of the changed symbols up to `CALLER_DEPTH` hops, merged across all of them.
+pub async fn downstream_callers(ctx: &RepoContext, symbols: &[AffectedSymbol]) -> Result<Vec<DownstreamCaller>> {
+    let Some(store) = ctx.surreal_store.as_ref() else {
+        return Ok(Vec::new());
+    };
+    let changed: HashSet<String> = symbols.iter().map(symbol_id).collect();
+    let mut callers: HashMap<String, DownstreamCaller> = HashMap::new();
+
+    for symbol in symbols {
+        let mut frontier = vec![symbol_id(symbol)];
+        let mut visited: HashSet<String> = frontier.iter().cloned().collect();
+        for depth in 1..=CALLER_DEPTH {
+            let mut next = Vec::new();
+            for id in &frontier {
+                for edge in store.neighbors_of_kind(id, Direction::In, &[EdgeKind::Calls]).await? {
+                    let key = edge.source.to_string();
+                    if !visited.insert(key.clone()) || changed.contains(&key) {
+                        continue;
+                    }
+                    let Some(node) = store.get_node_by_thing(&edge.source).await? else {
+                        continue;
+                    };
+                    if !ctx.in_scope(Path::new(&node.file_path)) {
+                        continue;
+                    }
+                    let call_site = edge.line.map(|line| (line, edge.column.unwrap_or(1)));
+                    let caller = callers.entry(key.clone()).or_insert_with(|| DownstreamCaller {
+                        name: node.label,
+                        file_path: node.file_path,
+                        start_line: node.start_line,
+                        call_site,
+                        depth,
+                        reaches: Vec::new(),
+                    });
+                    if depth < caller.depth {
+                        caller.depth = depth;
+                        caller.call_site = call_site;
+                    }
+                    if !caller.reaches.contains(&symbol.name) {
+                        caller.reaches.push(symbol.name.clone());
+                    }
+                    next.push(key);
+                }
+            }
+            frontier = next;
+        }
+    }
+
+    let mut callers: Vec<DownstreamCaller> = callers.into_values().collect();
+    callers.sort_by(|a, b| {
+        a.depth
+            .cmp(&b.depth)
+            .then_with(|| b.reaches.len().cmp(&a.reaches.len()))
+            .then_with(|| a.file_path.cmp(&b.file_path))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    Ok(callers)
+}
+
+/// A public symbol touched by a change, directly or through its callees.
+#[derive(Debug, Clone, Serialize)]
+pub struct ImpactedApi {
+    pub name: String,
+    pub file_path: String,
+    pub line: Option<usize>,
+    /// 0 for a changed symbol, otherwise hops to the nearest changed symbol.
+    pub depth: usize,
+    /// Changed symbols behind this one (itself, when changed directly).
+    pub reaches: Vec<String>,
+}
+
+/// Static impact of a diff, without the LLM review; what `emry check` reports.
+#[derive(Debug, Clone, Serialize)]
+pub struct ImpactCheck {
+    pub changed_files: usize,
+    pub changed_symbols: usize,
+    pub downstream_callers: usize,
+    pub public_apis: Vec<ImpactedApi>,
+}
+
+/// Changed symbols of `diffs` and their callers, keeping those that are public API.
+pub async fn check_impact(ctx: &RepoContext, diffs: &[FileDiff]) -> Result<ImpactCheck> {
+    let symbols = {
+        let mut analyzer = DiffAnalyzer::new()?;
+        analyzer.find_affected_symbols(diffs, &ctx.root)?
+    };
+    let callers = downstream_callers(ctx, &symbols).await?;
+
+    let mut sources: HashMap<String, Option<String>> = HashMap::new();
+    let mut is_public = |file_path: &str, line: Option<usize>, name: &str| {
+        let source = sources
+            .entry(file_path.to_string())
+            .or_insert_with(|| std::fs::read_to_string(ctx.root.join(file_path)).ok());
+        let declaration = line
+            .and_then(|l| source.as_deref()?.lines().nth(l.checked_sub(1)?))
+            .unwrap_or("");
+        is_public_declaration(declaration, name, &Language::from_path(Path::new(file_path)))
+    };
+
+    let mut public_apis: Vec<ImpactedApi> = symbols
+        .iter()
+        .filter(|s| is_public(&s.file_path, Some(s.start_line), &s.name))
+        .map(|s| ImpactedApi {
+            name: s.name.clone(),
+            file_path: s.file_path.clone(),
+            line: Some(s.start_line),
+            depth: 0,
+            reaches: vec![s.name.clone()],
+        })
+        .collect();
+    public_apis.extend(
+        callers
+            .iter()
+            .filter(|c| is_public(&c.file_path, c.start_line, &c.name))
+            .map(|c| ImpactedApi {
+                name: c.name.clone(),
+                file_path: c.file_path.clone(),
+                line: c.start_line,
+                depth: c.depth,
+                reaches: c.reaches.clone(),
+            }),
+    );
+
+    Ok(ImpactCheck {
+        changed_files: diffs.len(),
+        changed_symbols: symbols.len(),
+        downstream_callers: callers.len(),
+        public_apis,
+    })
+}
+
+/// Coverage of one changed file: the changed lines no test ran, or `None`
+/// when the file changed after the coverage was imported.
+#[derive(Debug, Clone)]
+pub struct ChangedFileCoverage {
+    pub file_path: String,
+    pub uncovered: Option<Vec<usize>>,
+}
+
+/// Changed lines of `diffs` that the imported coverage instrumented but no
+/// test ran, for the changed files the coverage covers. `None` when it
+/// covers none of them.
+pub async fn uncovered_changes(ctx: &RepoContext, diffs: &[FileDiff]) -> Result<Option<Vec<ChangedFileCoverage>>> {
+    let Some(store) = ctx.surreal_store.as_ref() else { return Ok(None) };
+    let paths: Vec<String> = diffs.iter().map(|d| ctx.root.join(&d.path).display().to_string()).collect();
+    let records = store.coverage_for(&paths).await?;
+    if records.is_empty() {
+        return Ok(None);
+    }
+    // Files the report doesn't mention (docs, config) weren't instrumented.
+    let files = diffs
+        .iter()
+        .zip(&paths)
+        .filter_map(|(diff, path)| {
+            let record = records.get(path)?;
+            let current = std::fs::read_to_string(path).ok().map(|content| compute_hash(&content));
+            let uncovered = (current.as_ref() == Some(&record.hash)).then(|| record.lines.uncovered_in(&diff.changed_ranges));
+            Some(ChangedFileCoverage { file_path: diff.path.display().to_string(), uncovered })
+        })
+        .collect();
+    Ok(Some(files))
+}
+
+fn render_coverage(files: &[ChangedFileCoverage]) -> String {
+    let mut out: Vec<String> = files
+        .iter()
+        .filter_map(|f| match &f.uncovered {
+            Some(lines) if lines.is_empty() => None,
+            Some(lines) => Some(format!("- `{}`: lines {}", f.file_path, line_ranges(lines))),
+            None => Some(format!("- `{}`: changed since the coverage was imported", f.file_path)),
+        })
+        .collect();
+    if out.is_empty() {
+        out.push("- (tests ran every instrumented changed line)".to_string());
+    }
+    out.join("\n")
+}
+
+/// Sorted line numbers as ranges: `3-5, 9`.
+fn line_ranges(lines: &[usize]) -> String {
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for &line in lines {
+        match ranges.last_mut() {
+            Some((_, end)) if *end + 1 == line => *end = line,
+            _ => ranges.push((line, line)),
+        }
+    }
+    ranges
+        .iter()
+        .map(|(start, end)| if start == end { start.to_string() } else { format!("{}-{}", start, end) })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn symbol_id(symbol: &AffectedSymbol) -> String {
+    Thing::from(("symbol", format!("{}::{}", symbol.file_path, symbol.name).as_str())).to_string()
+}
+
+fn render_callers(callers: &[DownstreamCaller]) -> String {
+    if callers.is_empty() {
+        return "- (no indexed callers)".to_string();
+    }
+    let mut out: Vec<String> = callers
+        .iter()
+        .take(MAX_REPORTED_CALLERS)
+        .map(|c| {
+            let hops = if c.depth == 1 { "direct".to_string() } else { format!("{} hops", c.depth) };
+            let location = match c.call_site {
+                Some((line, column)) => format!("{}:{}:{}", c.file_path, line, column),
+                None => c.file_path.clone(),
+            };
+            format!("- `{}` in `{}` ({}) -> {}", c.name, location, hops, c.reaches.join(", "))
+        })
+        .collect();
+    if callers.len() > MAX_REPORTED_CALLERS {
+        out.push(format!("- ... {} more", callers.len() - MAX_REPORTED_CALLERS));
+    }
+    out.join("\n")
+}
+
+/// Changed symbols and downstream callers, appended to the report as plain facts.
+fn render_impact_facts(symbols: &[AffectedSymbol], callers: &[DownstreamCaller]) -> String {
+    let mut out = String::from("## Changed Symbols\n");
+    for s in symbols {
+        out.push_str(&format!("- `{}` ({}) in `{}`:{}-{}\n", s.name, s.kind, s.file_path, s.start_line, s.end_line));
     }
+    let files: HashSet<&str> = callers.iter().map(|c| c.file_path.as_str()).collect();
+    out.push_str(&format!(
+        "\n## Downstream Callers ({} across {} file{})\n{}\n",
+        callers.len(),
+        files.len(),
+        if files.len() == 1 { "" } else { "s" },
+        render_callers(callers)
+    ));
+    out
 }
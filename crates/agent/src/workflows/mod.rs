@@ -1,3 +1,6 @@
 pub mod architecture;
 pub mod impact;
+pub mod doctor;
 pub mod functional;
+pub mod file_explain;
+pub mod summarize;
@@ -0,0 +1,322 @@
+//! Hierarchical summaries (`emry summarize`): every indexed file, then each
+//! directory from its files' and subdirectories' summaries, then the
+//! repository from the top-level ones.
+//!
+//! Only summaries whose input changed since the last run are rewritten, so
+//! re-running after an incremental index costs a few calls. Files with
+//! identical content (copies, vendored or generated code) share one summary:
+//! only the first is sent to the LLM and the others reuse its text. The results give
+//! `explain`, `architecture` and the agent's planning prompt high-level
+//! context without reading the code again.
+
+use crate::llm::{Message, OpenAIProvider};
+use anyhow::{bail, Result};
+use emry_core::scope::PathScope;
+use emry_core::summary::{children_hash, module_of, module_plan, SummaryLevel};
+use emry_store::summary::SummaryRecord;
+use emry_store::SurrealStore;
+use futures::stream::{self, StreamExt};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Characters of a file shown to the model; long files are summarized from their start.
+const MAX_FILE_CHARS: usize = 12_000;
+/// File summaries requested at once.
+const CONCURRENCY: usize = 8;
+const FILE_TOKENS: u32 = 160;
+const MODULE_TOKENS: u32 = 220;
+const REPO_TOKENS: u32 = 500;
+
+type Key = (SummaryLevel, String);
+
+/// A summary to keep up to date, with the hash of its current input.
+#[derive(Debug, Clone)]
+pub struct Target {
+    pub level: SummaryLevel,
+    pub path: String,
+    pub hash: String,
+    /// Summaries it is written from; empty for files.
+    children: Vec<Key>,
+    /// A file with identical content whose summary this one reuses instead
+    /// of calling the LLM.
+    pub copy_of: Option<String>,
+}
+
+/// What a run will write and remove.
+pub struct SummaryPlan {
+    /// Summaries to write, files first, then directories bottom-up.
+    pub stale: Vec<Target>,
+    /// Summaries already up to date.
+    pub current: usize,
+    /// Summaries of files and directories no longer indexed.
+    pub obsolete: Vec<Key>,
+    existing: HashMap<Key, SummaryRecord>,
+    contents: HashMap<String, String>,
+}
+
+/// What a run did, in summaries.
+#[derive(Debug, Clone, Default)]
+pub struct SummaryStats {
+    pub written: usize,
+    /// Of `written`, the ones copied from a file with identical content.
+    pub shared: usize,
+    pub kept: usize,
+    pub removed: usize,
+}
+
+/// Compare the index with the stored summaries. With `force`, every
+/// summary is planned for rewriting.
+pub async fn plan(store: &SurrealStore, force: bool) -> Result<SummaryPlan> {
+    let mut files = store.list_files().await?;
+    if files.is_empty() {
+        bail!("no files are indexed. Run 'emry index' first.");
+    }
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+
+    let mut existing = HashMap::new();
+    for level in [SummaryLevel::File, SummaryLevel::Module, SummaryLevel::Repo] {
+        for record in store.list_summaries(level).await? {
+            existing.insert((level, record.path.clone()), record);
+        }
+    }
+
+    let mut targets = Vec::new();
+    let mut hashes: HashMap<Key, String> = HashMap::new();
+    let mut contents = HashMap::new();
+    let paths: Vec<String> = files.iter().map(|f| f.path.clone()).collect();
+    for file in files {
+        hashes.insert((SummaryLevel::File, file.path.clone()), file.hash.clone());
+        targets.push(Target { level: SummaryLevel::File, path: file.path.clone(), hash: file.hash, children: Vec::new(), copy_of: None });
+        contents.insert(file.path, file.content);
+    }
+    for module in module_plan(&paths) {
+        let level = module.level();
+        let children: Vec<Key> = module
+            .files
+            .into_iter()
+            .map(|f| (SummaryLevel::File, f))
+            .chain(module.modules.into_iter().map(|m| (SummaryLevel::Module, m)))
+            .collect();
+        let hash = children_hash(children.iter().map(|key| (key.1.as_str(), hashes[key].as_str())));
+        hashes.insert((level, module.path.clone()), hash.clone());
+        targets.push(Target { level, path: module.path, hash, children, copy_of: None });
+    }
+
+    let wanted: HashSet<Key> = hashes.into_keys().collect();
+    let mut obsolete: Vec<Key> = existing.keys().filter(|key| !wanted.contains(*key)).cloned().collect();
+    obsolete.sort_by(|a, b| a.1.cmp(&b.1));
+    let (mut stale, current): (Vec<Target>, Vec<Target>) = targets.into_iter().partition(|t| {
+        force || existing.get(&(t.level, t.path.clone())).map(|r| r.hash.as_str()) != Some(t.hash.as_str())
+    });
+    link_copies(&mut stale, &current);
+
+    Ok(SummaryPlan { stale, current: current.len(), obsolete, existing, contents })
+}
+
+/// Point each stale file at an identical one (same content hash) whose
+/// summary it can reuse: one already up to date, or else the first stale
+/// copy, which is the only one summarized.
+fn link_copies(stale: &mut [Target], current: &[Target]) {
+    let mut originals: HashMap<String, String> = HashMap::new();
+    for target in current.iter().filter(|t| t.level == SummaryLevel::File) {
+        originals.entry(target.hash.clone()).or_insert_with(|| target.path.clone());
+    }
+    for target in stale.iter_mut().filter(|t| t.level == SummaryLevel::File) {
+        match originals.get(&target.hash) {
+            Some(path) => target.copy_of = Some(path.clone()),
+            None => {
+                originals.insert(target.hash.clone(), target.path.clone());
+            }
+        }
+    }
+}
+
+pub struct SummarizeWorkflow {
+    store: Arc<SurrealStore>,
+    llm: OpenAIProvider,
+}
+
+impl SummarizeWorkflow {
+    pub fn new(store: Arc<SurrealStore>, llm: OpenAIProvider) -> Self {
+        Self { store, llm }
+    }
+
+    /// Write the stale summaries of `plan` and remove the obsolete ones,
+    /// calling `progress` after each one is stored. Summaries are stored as
+    /// they are written, so an interrupted run resumes where it stopped.
+    pub async fn run(&self, plan: SummaryPlan, mut progress: impl FnMut(&Target)) -> Result<SummaryStats> {
+        let SummaryPlan { stale, current, obsolete, existing, contents } = plan;
+        for level in [SummaryLevel::File, SummaryLevel::Module, SummaryLevel::Repo] {
+            let paths: Vec<String> = obsolete.iter().filter(|(l, _)| *l == level).map(|(_, p)| p.clone()).collect();
+            if !paths.is_empty() {
+                self.store.delete_summaries(level, &paths).await?;
+            }
+        }
+
+        let mut texts: HashMap<Key, String> = existing.into_iter().map(|(key, r)| (key, r.text)).collect();
+        let (files, dirs): (Vec<Target>, Vec<Target>) = stale.into_iter().partition(|t| t.level == SummaryLevel::File);
+        let (copies, files): (Vec<Target>, Vec<Target>) = files.into_iter().partition(|t| t.copy_of.is_some());
+        let stats = SummaryStats {
+            written: files.len() + copies.len() + dirs.len(),
+            shared: copies.len(),
+            kept: current,
+            removed: obsolete.len(),
+        };
+
+        let mut summaries = stream::iter(files)
+            .map(|target| {
+                let content = contents.get(&target.path).map_or("", String::as_str);
+                async move {
+                    let text = self.summarize_file(&target.path, content).await;
+                    (target, text)
+                }
+            })
+            .buffer_unordered(CONCURRENCY);
+        while let Some((target, text)) = summaries.next().await {
+            let text = text?;
+            self.save(&target, &text).await?;
+            progress(&target);
+            texts.insert((target.level, target.path.clone()), text);
+        }
+
+        for target in copies {
+            let original = target.copy_of.clone().map(|path| (SummaryLevel::File, path));
+            let text = match original.and_then(|key| texts.get(&key).cloned()) {
+                Some(text) => text,
+                None => {
+                    let content = contents.get(&target.path).map_or("", String::as_str);
+                    self.summarize_file(&target.path, content).await?
+                }
+            };
+            self.save(&target, &text).await?;
+            progress(&target);
+            texts.insert((target.level, target.path.clone()), text);
+        }
+
+        // Bottom-up, so each directory's children are written before it.
+        for target in dirs {
+            let text = self.summarize_module(&target, &texts).await?;
+            self.save(&target, &text).await?;
+            progress(&target);
+            texts.insert((target.level, target.path.clone()), text);
+        }
+        Ok(stats)
+    }
+
+    async fn summarize_file(&self, path: &str, content: &str) -> Result<String> {
+        let mut shown: String = content.chars().take(MAX_FILE_CHARS).collect();
+        if shown.len() < content.len() {
+            shown.push_str("\n… (truncated)");
+        }
+        let messages = [
+            Message {
+                role: "system".to_string(),
+                content: "You summarize source files for developers new to a codebase. In two or three sentences \
+                          of plain text, say what the file is for and name its main types or functions. No preamble."
+                    .to_string(),
+            },
+            Message { role: "user".to_string(), content: format!("File: {}\n```\n{}\n```", path, shown) },
+        ];
+        Ok(self.llm.chat_with_limit(&messages, Some(FILE_TOKENS)).await?.trim().to_string())
+    }
+
+    async fn summarize_module(&self, target: &Target, texts: &HashMap<Key, String>) -> Result<String> {
+        let mut parts = String::new();
+        for key in &target.children {
+            let Some(text) = texts.get(key) else { continue };
+            let name = if key.0 == SummaryLevel::File { key.1.clone() } else { format!("{}/", key.1) };
+            parts.push_str(&format!("- `{}`: {}\n", name, text.replace('\n', " ")));
+        }
+        let (system, user, max_tokens) = if target.level == SummaryLevel::Repo {
+            (
+                "You summarize a codebase from summaries of its top-level files and directories. Write one \
+                 paragraph on what the project does and how it is organized, then one line per main directory \
+                 on its role. No preamble.",
+                format!("Top-level files and directories:\n{}", parts),
+                REPO_TOKENS,
+            )
+        } else {
+            (
+                "You summarize a directory of a codebase from summaries of its files and subdirectories. In two \
+                 to four sentences of plain text, say what the directory is responsible for and how its parts fit \
+                 together. No preamble.",
+                format!("Directory: {}/\nContents:\n{}", target.path, parts),
+                MODULE_TOKENS,
+            )
+        };
+        let messages = [
+            Message { role: "system".to_string(), content: system.to_string() },
+            Message { role: "user".to_string(), content: user },
+        ];
+        Ok(self.llm.chat_with_limit(&messages, Some(max_tokens)).await?.trim().to_string())
+    }
+
+    async fn save(&self, target: &Target, text: &str) -> Result<()> {
+        let created = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        self.store
+            .set_summary(SummaryRecord {
+                level: target.level,
+                path: target.path.clone(),
+                hash: target.hash.clone(),
+                text: text.to_string(),
+                created,
+            })
+            .await
+    }
+}
+
+/// The repository summary followed by those of the top-level directories
+/// (the ones `scope` may reach into), or `None` before `emry summarize` ran.
+pub async fn overview(store: &SurrealStore, scope: Option<&PathScope>) -> Result<Option<String>> {
+    let Some(repo) = store.get_summary(SummaryLevel::Repo, "").await? else {
+        return Ok(None);
+    };
+    let mut out = format!("{}\n", repo.text.trim());
+    let modules = store.list_summaries(SummaryLevel::Module).await?;
+    let top_level = modules
+        .iter()
+        .filter(|m| !m.path.contains('/'))
+        .filter(|m| scope.map_or(true, |s| s.may_contain(Path::new(&m.path))));
+    for module in top_level {
+        out.push_str(&format!("\n- `{}/`: {}", module.path, module.text.replace('\n', " ")));
+    }
+    Ok(Some(out))
+}
+
+/// The summaries of `path` (relative to the root) and of each directory
+/// containing it, innermost first.
+pub async fn enclosing_summaries(store: &SurrealStore, path: &str) -> Result<Vec<SummaryRecord>> {
+    let mut found = Vec::new();
+    if let Some(summary) = store.get_summary(SummaryLevel::File, path).await? {
+        found.push(summary);
+    }
+    let mut dir = module_of(path);
+    while !dir.is_empty() {
+        if let Some(summary) = store.get_summary(SummaryLevel::Module, dir).await? {
+            found.push(summary);
+        }
+        dir = module_of(dir);
+    }
+    Ok(found)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(path: &str, hash: &str) -> Target {
+        Target { level: SummaryLevel::File, path: path.to_string(), hash: hash.to_string(), children: Vec::new(), copy_of: None }
+    }
+
+    #[test]
+    fn test_identical_files_share_a_summary() {
+        let current = vec![file("lib/a.rs", "h1")];
+        let mut stale = vec![file("src/a.rs", "h1"), file("src/b.rs", "h2"), file("vendor/b.rs", "h2"), file("src/c.rs", "h3")];
+        link_copies(&mut stale, &current);
+
+        let copies: Vec<Option<&str>> = stale.iter().map(|t| t.copy_of.as_deref()).collect();
+        assert_eq!(copies, vec![Some("lib/a.rs"), None, Some("src/b.rs"), None]);
+    }
+}
@@ -96,6 +96,18 @@ pub enum ConfigError {
     #[error("Failed to parse environment variable {var}: {message}")]
     EnvVarError { var: String, message: String },
 
+    /// `${VAR}` in a config file names an unset environment variable
+    #[error("Environment variable {var} is not set{location}\n  Hint: export it, or write $${{{var}}} for a literal ${{{var}}}")]
+    UnsetVariable { var: String, location: String },
+
+    /// Malformed `${...}` reference in a config file
+    #[error("Invalid variable reference{location}: {message}\n  Hint: references look like ${{NAME}}; write $$ for a literal $")]
+    InvalidInterpolation { location: String, message: String },
+
+    /// `--profile` / `EMRY_PROFILE` names a profile the config does not define
+    #[error("Unknown profile '{name}'\n  Defined profiles: {available}\n  Hint: add a [profile.{name}] section to the config file")]
+    UnknownProfile { name: String, available: String },
+
     /// Config merging error
     #[error("Failed to merge configurations: {message}")]
     MergeError { message: String },
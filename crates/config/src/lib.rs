@@ -25,6 +25,7 @@
 
 pub mod error;
 pub mod loader;
+pub mod schema;
 pub mod types;
 pub mod validation;
 
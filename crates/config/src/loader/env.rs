@@ -18,7 +18,7 @@ pub fn from_env() -> Result<Option<Config>> {
 
     // Collect all EMRY_ env vars
     let env_vars: Vec<(String, String)> = env::vars()
-        .filter(|(k, _)| k.starts_with("EMRY_"))
+        .filter(|(k, _)| k.starts_with("EMRY_") && k != PROFILE_ENV)
         .collect();
 
     if env_vars.is_empty() {
@@ -60,11 +60,16 @@ fn apply_env_var(config: &mut Config, key: &str, value: &str) -> Result<()> {
         "search" => apply_search_var(&mut config.search, &field, value),
         "ranking" => apply_ranking_var(&mut config.ranking, &field, value),
         "chunking" => apply_chunking_var(&mut config.chunking, &field, value),
+        "documents" => apply_documents_var(&mut config.documents, &field, value),
+        "owners" => apply_owners_var(&mut config.owners, &field, value),
         "embedding" => apply_embedding_var(&mut config.embedding, &field, value),
+        "rerank" => apply_rerank_var(&mut config.rerank, &field, value),
         "agent" => apply_agent_var(&mut config.agent, &field, value),
         "llm" => apply_llm_var(&mut config.llm, &field, value),
         "bm25" => apply_bm25_var(&mut config.bm25, &field, value),
         "graph" => apply_graph_var(&mut config.graph, &field, value),
+        "ui" => apply_ui_var(&mut config.ui, &field, value),
+        "core" => apply_core_var(&mut config.core, &field, value),
         _ => Err(ConfigError::EnvVarError {
             var: key.to_string(),
             message: format!("Unknown section: {}", section),
@@ -94,6 +99,21 @@ fn apply_search_var(config: &mut SearchConfig, field: &str, value: &str) -> Resu
                 message: format!("Invalid integer: {}", value),
             })?;
         }
+        "snippet_source" => {
+            config.snippet_source = match value.to_lowercase().as_str() {
+                "prefer_index" => SnippetSource::PreferIndex,
+                "prefer_working_tree" => SnippetSource::PreferWorkingTree,
+                _ => {
+                    return Err(ConfigError::invalid_enum(
+                        "search.snippet_source",
+                        value,
+                        &["prefer_index", "prefer_working_tree"],
+                    ))
+                }
+            };
+        }
+        "query_expansion" => config.query_expansion = parse_bool(value)?,
+        "remote" => config.remote = (!value.is_empty()).then(|| value.to_string()),
         _ => {
             return Err(ConfigError::EnvVarError {
                 var: format!("EMRY_SEARCH_{}", field.to_uppercase()),
@@ -117,6 +137,14 @@ fn apply_ranking_var(config: &mut RankingConfig, field: &str, value: &str) -> Re
         "vector" => config.vector = parse_float(value)?,
         "graph" => config.graph = parse_float(value)?,
         "symbol" => config.symbol = parse_float(value)?,
+        "sparse" => config.sparse = parse_float(value)?,
+        "coverage" => config.coverage = parse_float(value)?,
+        "fusion" => {
+            config.fusion = FusionStrategy::from_name(value).ok_or_else(|| ConfigError::EnvVarError {
+                var: "EMRY_RANKING_FUSION".to_string(),
+                message: format!("Invalid fusion: {} (use max, weighted, rrf or zscore)", value),
+            })?;
+        }
         _ => {
             return Err(ConfigError::EnvVarError {
                 var: format!("EMRY_RANKING_{}", field.to_uppercase()),
@@ -175,11 +203,13 @@ fn apply_embedding_var(config: &mut EmbeddingConfig, field: &str, value: &str) -
                 "openai" | "external" => EmbeddingBackend::External,
                 "ollama" => EmbeddingBackend::Ollama,
                 "local" => EmbeddingBackend::Local,
+                "onnx" => EmbeddingBackend::Onnx,
+                "none" => EmbeddingBackend::None,
                 _ => {
                     return Err(ConfigError::invalid_enum(
                         "embedding.backend",
                         value,
-                        &["openai", "ollama", "local"],
+                        &["openai", "ollama", "local", "onnx", "none"],
                     ))
                 }
             };
@@ -187,6 +217,41 @@ fn apply_embedding_var(config: &mut EmbeddingConfig, field: &str, value: &str) -
         "model_name" => {
             config.model_name = value.to_string();
         }
+        "model_path" => {
+            config.model_path = Some(value.into());
+        }
+        "dimension" => {
+            config.dimension = Some(value.parse().map_err(|_| ConfigError::EnvVarError {
+                var: "EMRY_EMBEDDING_DIMENSION".to_string(),
+                message: format!("Invalid integer: {}", value),
+            })?);
+        }
+        "batch_size" => {
+            config.batch_size = value.parse().map_err(|_| ConfigError::EnvVarError {
+                var: "EMRY_EMBEDDING_BATCH_SIZE".to_string(),
+                message: format!("Invalid integer: {}", value),
+            })?;
+        }
+        "max_concurrent_requests" => {
+            config.max_concurrent_requests = value.parse().map_err(|_| ConfigError::EnvVarError {
+                var: "EMRY_EMBEDDING_MAX_CONCURRENT_REQUESTS".to_string(),
+                message: format!("Invalid integer: {}", value),
+            })?;
+        }
+        "quantization" => {
+            config.quantization = match value.to_lowercase().as_str() {
+                "none" => Quantization::None,
+                "int8" => Quantization::Int8,
+                "binary" => Quantization::Binary,
+                _ => {
+                    return Err(ConfigError::invalid_enum(
+                        "embedding.quantization",
+                        value,
+                        &["none", "int8", "binary"],
+                    ))
+                }
+            };
+        }
         _ => {
             return Err(ConfigError::EnvVarError {
                 var: format!("EMRY_EMBEDDING_{}", field.to_uppercase()),
@@ -201,6 +266,13 @@ fn apply_embedding_var(config: &mut EmbeddingConfig, field: &str, value: &str) -
 
 fn apply_agent_var(config: &mut AgentConfig, field: &str, value: &str) -> Result<()> {
     match field {
+        "strategy" => {
+            config.strategy =
+                AgentStrategy::from_name(value).ok_or_else(|| ConfigError::EnvVarError {
+                    var: "EMRY_AGENT_STRATEGY".to_string(),
+                    message: format!("Invalid strategy: {} (use react, plan_execute, rag or deep)", value),
+                })?;
+        }
         "max_per_step" => {
             config.max_per_step = value.parse().map_err(|_| ConfigError::EnvVarError {
                 var: "EMRY_AGENT_MAX_PER_STEP".to_string(),
@@ -219,6 +291,35 @@ fn apply_agent_var(config: &mut AgentConfig, field: &str, value: &str) -> Result
                 message: format!("Invalid integer: {}", value),
             })?;
         }
+        "max_cost_usd" => {
+            config.max_cost_usd = Some(value.parse().map_err(|_| ConfigError::EnvVarError {
+                var: "EMRY_AGENT_MAX_COST_USD".to_string(),
+                message: format!("Invalid number: {}", value),
+            })?);
+        }
+        "max_run_tokens" => {
+            config.max_run_tokens = Some(value.parse().map_err(|_| ConfigError::EnvVarError {
+                var: "EMRY_AGENT_MAX_RUN_TOKENS".to_string(),
+                message: format!("Invalid integer: {}", value),
+            })?);
+        }
+        "allow_shell" => config.allow_shell = parse_bool(value)?,
+        "critic" => config.critic = parse_bool(value)?,
+        "require_citations" => config.require_citations = parse_bool(value)?,
+        "shell_commands" => {
+            config.shell_commands = value
+                .split(',')
+                .map(str::trim)
+                .filter(|c| !c.is_empty())
+                .map(str::to_string)
+                .collect();
+        }
+        "shell_timeout_secs" => {
+            config.shell_timeout_secs = value.parse().map_err(|_| ConfigError::EnvVarError {
+                var: "EMRY_AGENT_SHELL_TIMEOUT_SECS".to_string(),
+                message: format!("Invalid integer: {}", value),
+            })?;
+        }
         _ => {
             return Err(ConfigError::EnvVarError {
                 var: format!("EMRY_AGENT_{}", field.to_uppercase()),
@@ -229,9 +330,58 @@ fn apply_agent_var(config: &mut AgentConfig, field: &str, value: &str) -> Result
     Ok(())
 }
 
+fn apply_core_var(config: &mut CoreConfig, field: &str, value: &str) -> Result<()> {
+    match field {
+        "respect_gitignore" => config.respect_gitignore = parse_bool(value)?,
+        "max_file_size_kb" => {
+            config.max_file_size_kb = value.parse().map_err(|_| ConfigError::EnvVarError {
+                var: "EMRY_CORE_MAX_FILE_SIZE_KB".to_string(),
+                message: format!("Invalid integer: {}", value),
+            })?;
+        }
+        "pager" => config.pager = Some(value.to_string()),
+        "read_only" => config.read_only = parse_bool(value)?,
+        "list_limit" => {
+            config.list_limit = value.parse().map_err(|_| ConfigError::EnvVarError {
+                var: "EMRY_CORE_LIST_LIMIT".to_string(),
+                message: format!("Invalid integer: {}", value),
+            })?;
+        }
+        _ => {
+            return Err(ConfigError::EnvVarError {
+                var: format!("EMRY_CORE_{}", field.to_uppercase()),
+                message: format!("Unknown field: {}", field),
+            })
+        }
+    }
+    Ok(())
+}
+
+fn apply_ui_var(config: &mut UiConfig, field: &str, value: &str) -> Result<()> {
+    match field {
+        "theme" => {
+            config.theme = match value.to_lowercase().as_str() {
+                "dark" => UiTheme::Dark,
+                "light" => UiTheme::Light,
+                "mono" => UiTheme::Mono,
+                _ => return Err(ConfigError::invalid_enum("ui.theme", value, &["dark", "light", "mono"])),
+            };
+        }
+        _ => {
+            return Err(ConfigError::EnvVarError {
+                var: format!("EMRY_UI_{}", field.to_uppercase()),
+                message: format!("Unknown field: {}", field),
+            })
+        }
+    }
+    Ok(())
+}
+
 fn apply_llm_var(config: &mut LlmConfig, field: &str, value: &str) -> Result<()> {
     match field {
         "model" => config.model = value.to_string(),
+        "planner_model" => config.planner_model = Some(value.to_string()),
+        "answer_model" => config.answer_model = Some(value.to_string()),
         "max_tokens" => {
             config.max_tokens = value.parse().map_err(|_| ConfigError::EnvVarError {
                 var: "EMRY_LLM_MAX_TOKENS".to_string(),
@@ -239,6 +389,19 @@ fn apply_llm_var(config: &mut LlmConfig, field: &str, value: &str) -> Result<()>
             })?;
         }
         "api_base" => config.api_base = Some(value.to_string()),
+        "cache_enabled" => config.cache.enabled = parse_bool(value)?,
+        "cache_ttl_secs" => {
+            config.cache.ttl_secs = value.parse().map_err(|_| ConfigError::EnvVarError {
+                var: "EMRY_LLM_CACHE_TTL_SECS".to_string(),
+                message: format!("Invalid integer: {}", value),
+            })?;
+        }
+        "cache_max_entries" => {
+            config.cache.max_entries = value.parse().map_err(|_| ConfigError::EnvVarError {
+                var: "EMRY_LLM_CACHE_MAX_ENTRIES".to_string(),
+                message: format!("Invalid integer: {}", value),
+            })?;
+        }
         _ => {
             return Err(ConfigError::EnvVarError {
                 var: format!("EMRY_LLM_{}", field.to_uppercase()),
@@ -249,6 +412,73 @@ fn apply_llm_var(config: &mut LlmConfig, field: &str, value: &str) -> Result<()>
     Ok(())
 }
 
+fn apply_documents_var(config: &mut DocumentsConfig, field: &str, value: &str) -> Result<()> {
+    match field {
+        "enabled" => config.enabled = parse_bool(value)?,
+        _ => {
+            return Err(ConfigError::EnvVarError {
+                var: format!("EMRY_DOCUMENTS_{}", field.to_uppercase()),
+                message: format!("Unknown field: {}", field),
+            })
+        }
+    }
+    Ok(())
+}
+
+fn apply_owners_var(config: &mut OwnersConfig, field: &str, value: &str) -> Result<()> {
+    match field {
+        "blame" => config.blame = parse_bool(value)?,
+        "blame_authors" => {
+            config.blame_authors = value.parse().map_err(|_| ConfigError::EnvVarError {
+                var: "EMRY_OWNERS_BLAME_AUTHORS".to_string(),
+                message: format!("Invalid integer: {}", value),
+            })?;
+        }
+        _ => {
+            return Err(ConfigError::EnvVarError {
+                var: format!("EMRY_OWNERS_{}", field.to_uppercase()),
+                message: format!("Unknown field: {}", field),
+            })
+        }
+    }
+    Ok(())
+}
+
+fn apply_rerank_var(config: &mut RerankConfig, field: &str, value: &str) -> Result<()> {
+    match field {
+        "enabled" => config.enabled = parse_bool(value)?,
+        "backend" => {
+            config.backend = match value.to_lowercase().as_str() {
+                "api" => RerankBackend::Api,
+                "onnx" => RerankBackend::Onnx,
+                _ => {
+                    return Err(ConfigError::invalid_enum(
+                        "rerank.backend",
+                        value,
+                        &["api", "onnx"],
+                    ))
+                }
+            };
+        }
+        "model" => config.model = value.to_string(),
+        "model_path" => config.model_path = Some(value.into()),
+        "api_base" => config.api_base = Some(value.to_string()),
+        "top_n" => {
+            config.top_n = value.parse().map_err(|_| ConfigError::EnvVarError {
+                var: "EMRY_RERANK_TOP_N".to_string(),
+                message: format!("Invalid integer: {}", value),
+            })?;
+        }
+        _ => {
+            return Err(ConfigError::EnvVarError {
+                var: format!("EMRY_RERANK_{}", field.to_uppercase()),
+                message: format!("Unknown field: {}", field),
+            })
+        }
+    }
+    Ok(())
+}
+
 fn apply_bm25_var(config: &mut Bm25Config, field: &str, value: &str) -> Result<()> {
     let parse_float = |v: &str| -> Result<f32> {
         v.parse().map_err(|_| ConfigError::EnvVarError {
@@ -266,6 +496,7 @@ fn apply_bm25_var(config: &mut Bm25Config, field: &str, value: &str) -> Result<(
                 message: format!("Invalid integer: {}", value),
             })?;
         }
+        "cjk_segmentation" => config.cjk_segmentation = parse_bool(value)?,
         _ => {
             return Err(ConfigError::EnvVarError {
                 var: format!("EMRY_BM25_{}", field.to_uppercase()),
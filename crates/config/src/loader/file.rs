@@ -20,6 +20,9 @@ pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Config> {
     // Get path string for error messages
     let path_str = path.to_str();
 
+    // Substitute ${VAR} references from the environment
+    let content = super::interpolate::interpolate(&content, path_str)?;
+
     // Parse based on format
     let config = match format {
         ConfigFormat::Yaml => super::formats::yaml::parse_with_path(&content, path_str)?,
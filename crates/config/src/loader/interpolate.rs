@@ -0,0 +1,121 @@
+//! Environment variable interpolation in config files
+//!
+//! `${NAME}` anywhere in a config file is replaced with the value of the
+//! environment variable `NAME` before parsing, so secrets and machine-specific
+//! paths can stay out of the file:
+//!
+//! ```toml
+//! [llm]
+//! api_base = "${LLM_API_BASE}"
+//!
+//! [embedding]
+//! model_path = "${HOME}/models/all-MiniLM-L6-v2"
+//! ```
+//!
+//! `$$` stands for a literal `$`, and a `$` not followed by `{` is kept as is.
+//! Comment lines (starting with `#`) are left untouched. Referencing an unset
+//! variable is an error rather than an empty string.
+
+use crate::{error::ConfigError, Result};
+
+/// Substitute `${NAME}` references in `content` from the process environment.
+pub fn interpolate(content: &str, path: Option<&str>) -> Result<String> {
+    interpolate_with(content, path, |name| std::env::var(name).ok())
+}
+
+/// Substitute `${NAME}` references in `content` using `lookup`.
+pub fn interpolate_with(content: &str, path: Option<&str>, lookup: impl Fn(&str) -> Option<String>) -> Result<String> {
+    let mut out = String::with_capacity(content.len());
+    for (index, line) in content.split_inclusive('\n').enumerate() {
+        if line.trim_start().starts_with('#') || !line.contains('$') {
+            out.push_str(line);
+            continue;
+        }
+        let location = || match path {
+            Some(path) => format!(" in {}, line {}", path, index + 1),
+            None => format!(" on line {}", index + 1),
+        };
+
+        let mut rest = line;
+        while let Some(at) = rest.find('$') {
+            out.push_str(&rest[..at]);
+            let after = &rest[at + 1..];
+            if let Some(after) = after.strip_prefix('$') {
+                out.push('$');
+                rest = after;
+            } else if let Some(after) = after.strip_prefix('{') {
+                let end = after.find('}').ok_or_else(|| ConfigError::InvalidInterpolation {
+                    location: location(),
+                    message: "unterminated ${".to_string(),
+                })?;
+                let name = &after[..end];
+                if !is_variable_name(name) {
+                    return Err(ConfigError::InvalidInterpolation {
+                        location: location(),
+                        message: format!("'{}' is not a variable name", name),
+                    });
+                }
+                let value = lookup(name).ok_or_else(|| ConfigError::UnsetVariable {
+                    var: name.to_string(),
+                    location: location(),
+                })?;
+                out.push_str(&value);
+                rest = &after[end + 1..];
+            } else {
+                out.push('$');
+                rest = after;
+            }
+        }
+        out.push_str(rest);
+    }
+    Ok(out)
+}
+
+fn is_variable_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lookup(name: &str) -> Option<String> {
+        match name {
+            "HOME" => Some("/home/dev".to_string()),
+            "LLM_API_BASE" => Some("http://localhost:8080".to_string()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_interpolates_and_escapes() {
+        let content = "# uses ${UNSET}\nmodel_path = \"${HOME}/models\"\napi_base = \"${LLM_API_BASE}\"\npattern = \"^a$ costs $$5 ${HOME}$${HOME}\"\n";
+        let out = interpolate_with(content, None, lookup).unwrap();
+        assert_eq!(
+            out,
+            "# uses ${UNSET}\nmodel_path = \"/home/dev/models\"\napi_base = \"http://localhost:8080\"\npattern = \"^a$ costs $5 /home/dev${HOME}\"\n"
+        );
+    }
+
+    #[test]
+    fn test_errors_name_the_variable_and_line() {
+        let err = interpolate_with("a = 1\nkey = \"${OPENAI_API_KEY}\"\n", Some(".emry.toml"), lookup).unwrap_err();
+        match err {
+            ConfigError::UnsetVariable { var, location } => {
+                assert_eq!(var, "OPENAI_API_KEY");
+                assert_eq!(location, " in .emry.toml, line 2");
+            }
+            other => panic!("unexpected error: {}", other),
+        }
+        assert!(matches!(
+            interpolate_with("key = \"${HOME\"", None, lookup),
+            Err(ConfigError::InvalidInterpolation { .. })
+        ));
+        assert!(matches!(
+            interpolate_with("key = \"${1X}\"", None, lookup),
+            Err(ConfigError::InvalidInterpolation { .. })
+        ));
+    }
+}
@@ -15,14 +15,47 @@ pub fn merge(mut base: Config, overlay: Config) -> Config {
     base.bm25 = merge_bm25(base.bm25, overlay.bm25);
     base.graph = merge_graph(base.graph, overlay.graph);
     base.chunking = merge_chunking(base.chunking, overlay.chunking);
+    base.documents = merge_documents(base.documents, overlay.documents);
+    base.owners = merge_owners(base.owners, overlay.owners);
     base.embedding = merge_embedding(base.embedding, overlay.embedding);
+    base.rerank = merge_rerank(base.rerank, overlay.rerank);
     base.agent = merge_agent(base.agent, overlay.agent);
     base.llm = merge_llm(base.llm, overlay.llm);
     base.core = merge_core(base.core, overlay.core);
+    base.ui = merge_ui(base.ui, overlay.ui);
+    base.profile.extend(overlay.profile);
 
     base
 }
 
+/// Apply a profile's overrides to `base`, like any other overlay
+pub fn merge_profile(mut base: Config, profile: ProfileConfig) -> Config {
+    base.search = merge_search(base.search, profile.search);
+    base.ranking = merge_ranking(base.ranking, profile.ranking);
+    base.rerank = merge_rerank(base.rerank, profile.rerank);
+    base.agent = merge_agent(base.agent, profile.agent);
+    base.llm = merge_llm(base.llm, profile.llm);
+    base
+}
+
+fn merge_ui(base: UiConfig, overlay: UiConfig) -> UiConfig {
+    UiConfig {
+        theme: if overlay.theme != UiTheme::default() {
+            overlay.theme
+        } else {
+            base.theme
+        },
+        colors: UiColors {
+            heading: overlay.colors.heading.or(base.colors.heading),
+            location: overlay.colors.location.or(base.colors.location),
+            prompt: overlay.colors.prompt.or(base.colors.prompt),
+            success: overlay.colors.success.or(base.colors.success),
+            warning: overlay.colors.warning.or(base.colors.warning),
+            error: overlay.colors.error.or(base.colors.error),
+        },
+    }
+}
+
 fn merge_search(base: SearchConfig, overlay: SearchConfig) -> SearchConfig {
     let default = SearchConfig::default();
     SearchConfig {
@@ -36,6 +69,17 @@ fn merge_search(base: SearchConfig, overlay: SearchConfig) -> SearchConfig {
         } else {
             base.top_k
         },
+        snippet_source: if overlay.snippet_source != default.snippet_source {
+            overlay.snippet_source
+        } else {
+            base.snippet_source
+        },
+        query_expansion: if overlay.query_expansion != default.query_expansion {
+            overlay.query_expansion
+        } else {
+            base.query_expansion
+        },
+        remote: overlay.remote.or(base.remote),
     }
 }
 
@@ -62,6 +106,26 @@ fn merge_ranking(base: RankingConfig, overlay: RankingConfig) -> RankingConfig {
         } else {
             base.symbol
         },
+        sparse: if (overlay.sparse - default.sparse).abs() > 0.001 {
+            overlay.sparse
+        } else {
+            base.sparse
+        },
+        coverage: if (overlay.coverage - default.coverage).abs() > 0.001 {
+            overlay.coverage
+        } else {
+            base.coverage
+        },
+        fusion: if overlay.fusion != default.fusion {
+            overlay.fusion
+        } else {
+            base.fusion
+        },
+        intent_profiles: if overlay.intent_profiles != default.intent_profiles {
+            overlay.intent_profiles
+        } else {
+            base.intent_profiles
+        },
     }
 }
 
@@ -83,6 +147,7 @@ fn merge_bm25(base: Bm25Config, overlay: Bm25Config) -> Bm25Config {
         } else {
             base.avg_len
         },
+        cjk_segmentation: overlay.cjk_segmentation || base.cjk_segmentation,
     }
 }
 
@@ -156,14 +221,91 @@ fn merge_embedding(base: EmbeddingConfig, overlay: EmbeddingConfig) -> Embedding
         } else {
             base.model_name
         },
+        model_path: overlay.model_path.or(base.model_path),
+        dimension: overlay.dimension.or(base.dimension),
+        batch_size: if overlay.batch_size != default.batch_size {
+            overlay.batch_size
+        } else {
+            base.batch_size
+        },
+        max_concurrent_requests: if overlay.max_concurrent_requests != default.max_concurrent_requests {
+            overlay.max_concurrent_requests
+        } else {
+            base.max_concurrent_requests
+        },
+        quantization: if overlay.quantization != default.quantization {
+            overlay.quantization
+        } else {
+            base.quantization
+        },
+    }
+}
+
+
+
+fn merge_documents(base: DocumentsConfig, overlay: DocumentsConfig) -> DocumentsConfig {
+    let default = DocumentsConfig::default();
+    DocumentsConfig {
+        enabled: if overlay.enabled != default.enabled {
+            overlay.enabled
+        } else {
+            base.enabled
+        },
     }
 }
 
+fn merge_owners(base: OwnersConfig, overlay: OwnersConfig) -> OwnersConfig {
+    let default = OwnersConfig::default();
+    OwnersConfig {
+        blame: if overlay.blame != default.blame {
+            overlay.blame
+        } else {
+            base.blame
+        },
+        blame_authors: if overlay.blame_authors != default.blame_authors {
+            overlay.blame_authors
+        } else {
+            base.blame_authors
+        },
+    }
+}
 
+fn merge_rerank(base: RerankConfig, overlay: RerankConfig) -> RerankConfig {
+    let default = RerankConfig::default();
+    RerankConfig {
+        enabled: if overlay.enabled != default.enabled {
+            overlay.enabled
+        } else {
+            base.enabled
+        },
+        backend: if overlay.backend != default.backend {
+            overlay.backend
+        } else {
+            base.backend
+        },
+        model: if overlay.model != default.model {
+            overlay.model
+        } else {
+            base.model
+        },
+        model_path: overlay.model_path.or(base.model_path),
+        api_base: overlay.api_base.or(base.api_base),
+        top_n: if overlay.top_n != default.top_n {
+            overlay.top_n
+        } else {
+            base.top_n
+        },
+    }
+}
 
 fn merge_agent(base: AgentConfig, overlay: AgentConfig) -> AgentConfig {
     let default = AgentConfig::default();
     AgentConfig {
+        strategy: if overlay.strategy != default.strategy {
+            overlay.strategy
+        } else {
+            base.strategy
+        },
         max_per_step: if overlay.max_per_step != default.max_per_step {
             overlay.max_per_step
         } else {
@@ -197,6 +339,33 @@ fn merge_agent(base: AgentConfig, overlay: AgentConfig) -> AgentConfig {
         } else {
             base.step_timeout_secs
         },
+        max_cost_usd: overlay.max_cost_usd.or(base.max_cost_usd),
+        max_run_tokens: overlay.max_run_tokens.or(base.max_run_tokens),
+        allow_shell: if overlay.allow_shell != default.allow_shell {
+            overlay.allow_shell
+        } else {
+            base.allow_shell
+        },
+        shell_commands: if overlay.shell_commands != default.shell_commands {
+            overlay.shell_commands
+        } else {
+            base.shell_commands
+        },
+        shell_timeout_secs: if overlay.shell_timeout_secs != default.shell_timeout_secs {
+            overlay.shell_timeout_secs
+        } else {
+            base.shell_timeout_secs
+        },
+        critic: if overlay.critic != default.critic {
+            overlay.critic
+        } else {
+            base.critic
+        },
+        require_citations: if overlay.require_citations != default.require_citations {
+            overlay.require_citations
+        } else {
+            base.require_citations
+        },
     }
 }
 
@@ -208,6 +377,8 @@ fn merge_llm(base: LlmConfig, overlay: LlmConfig) -> LlmConfig {
         } else {
             base.model
         },
+        planner_model: overlay.planner_model.or(base.planner_model),
+        answer_model: overlay.answer_model.or(base.answer_model),
         max_tokens: if overlay.max_tokens != default.max_tokens {
             overlay.max_tokens
         } else {
@@ -219,6 +390,11 @@ fn merge_llm(base: LlmConfig, overlay: LlmConfig) -> LlmConfig {
         } else {
             base.timeout_secs
         },
+        cache: if overlay.cache != default.cache {
+            overlay.cache
+        } else {
+            base.cache
+        },
     }
 }
 
@@ -240,6 +416,23 @@ fn merge_core(base: CoreConfig, overlay: CoreConfig) -> CoreConfig {
         } else {
             base.auto_index_on_search
         },
+        respect_gitignore: if overlay.respect_gitignore != default.respect_gitignore {
+            overlay.respect_gitignore
+        } else {
+            base.respect_gitignore
+        },
+        max_file_size_kb: if overlay.max_file_size_kb != default.max_file_size_kb {
+            overlay.max_file_size_kb
+        } else {
+            base.max_file_size_kb
+        },
+        pager: overlay.pager.or(base.pager),
+        list_limit: if overlay.list_limit != default.list_limit {
+            overlay.list_limit
+        } else {
+            base.list_limit
+        },
+        read_only: overlay.read_only || base.read_only,
     }
 }
 
@@ -252,10 +445,12 @@ mod tests {
         let base = SearchConfig {
             mode: SearchMode::Lexical,
             top_k: 10,
+            ..Default::default()
         };
         let overlay = SearchConfig {
             mode: SearchMode::Semantic,
             top_k: 20,
+            ..Default::default()
         };
         let merged = merge_search(base, overlay);
         assert_eq!(merged.mode, SearchMode::Semantic);
@@ -267,6 +462,7 @@ mod tests {
         let base = SearchConfig {
             mode: SearchMode::Semantic,
             top_k: 20,
+            ..Default::default()
         };
         let overlay = SearchConfig::default();
         let merged = merge_search(base, overlay);
@@ -3,11 +3,24 @@
 pub mod env;
 pub mod file;
 pub mod formats;
+pub mod interpolate;
 pub mod merge;
+pub mod provenance;
 
-use crate::{Config, Result, Validate};
+use crate::{error::ConfigError, Config, Result, Validate, PROFILE_ENV};
 use std::path::{Path, PathBuf};
 
+/// Config files looked up by `Config::load`, in order of preference
+pub const DEFAULT_CONFIG_FILES: &[&str] = &[".emry.toml", ".emry.yml", ".emry.yaml", ".emry.json"];
+
+/// The first of `DEFAULT_CONFIG_FILES` present in the current directory
+pub fn find_config_file() -> Option<PathBuf> {
+    DEFAULT_CONFIG_FILES
+        .iter()
+        .map(PathBuf::from)
+        .find(|path| path.exists())
+}
+
 /// Format for configuration files
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ConfigFormat {
@@ -33,7 +46,10 @@ pub enum ConfigSource {
 /// Builder for loading and merging configurations
 ///
 /// Supports layered configuration with proper precedence:
-/// defaults < file < environment < explicit overrides
+/// defaults < file < profile < environment < explicit overrides
+///
+/// The profile is the one passed to `with_profile`, or `EMRY_PROFILE` when
+/// environment variables are read.
 ///
 /// # Example
 ///
@@ -48,6 +64,7 @@ pub enum ConfigSource {
 /// ```
 pub struct ConfigBuilder {
     sources: Vec<ConfigSource>,
+    profile: Option<String>,
 }
 
 impl ConfigBuilder {
@@ -55,6 +72,7 @@ impl ConfigBuilder {
     pub fn new() -> Self {
         Self {
             sources: Vec::new(),
+            profile: None,
         }
     }
 
@@ -71,6 +89,12 @@ impl ConfigBuilder {
         self
     }
 
+    /// Apply a profile defined in the config file (takes precedence over `EMRY_PROFILE`)
+    pub fn with_profile(mut self, name: impl Into<String>) -> Self {
+        self.profile = Some(name.into());
+        self
+    }
+
     /// Add explicit config overlay (for programmatic use)
     pub fn with_config(mut self, config: Config) -> Self {
         self.sources.push(ConfigSource::Explicit(config));
@@ -82,6 +106,11 @@ impl ConfigBuilder {
     /// Merges all sources in order, with later sources taking precedence.
     pub fn build(self) -> Result<Config> {
         let mut config = Config::default();
+        let reads_env = self.sources.iter().any(|s| matches!(s, ConfigSource::Environment));
+        let mut profile = self
+            .profile
+            .or_else(|| if reads_env { std::env::var(PROFILE_ENV).ok() } else { None })
+            .filter(|p| !p.is_empty());
 
         for source in self.sources {
             match source {
@@ -90,6 +119,8 @@ impl ConfigBuilder {
                     config = merge::merge(config, file_config);
                 }
                 ConfigSource::Environment => {
+                    // Profiles override the file, but not explicit env vars.
+                    config = apply_profile(config, profile.take())?;
                     if let Some(env_config) = env::from_env()? {
                         config = merge::merge(config, env_config);
                     }
@@ -100,6 +131,8 @@ impl ConfigBuilder {
             }
         }
 
+        config = apply_profile(config, profile.take())?;
+
         // Final validation
         config.validate()?;
         Ok(config)
@@ -111,6 +144,23 @@ impl ConfigBuilder {
     }
 }
 
+/// Overlay the profile called `name`, if any, from the profiles defined in `config`
+pub fn apply_profile(config: Config, name: Option<String>) -> Result<Config> {
+    let Some(name) = name else {
+        return Ok(config);
+    };
+    match config.profile.get(&name).cloned() {
+        Some(profile) => Ok(merge::merge_profile(config, profile)),
+        None => {
+            let available: Vec<&str> = config.profile.keys().map(String::as_str).collect();
+            Err(ConfigError::UnknownProfile {
+                name,
+                available: if available.is_empty() { "(none)".to_string() } else { available.join(", ") },
+            })
+        }
+    }
+}
+
 impl Default for ConfigBuilder {
     fn default() -> Self {
         Self::new()
@@ -128,22 +178,10 @@ impl Config {
     /// If no file is found, returns default configuration.
     /// Also applies environment variable overlays.
     pub fn load() -> Result<Self> {
-        // Try each default location
-        let default_paths = [
-            ".emry.toml",
-            ".emry.yml",
-            ".emry.yaml",
-            ".emry.json",
-        ];
-
         let mut builder = ConfigBuilder::new();
 
-        // Find first existing file
-        for path in &default_paths {
-            if Path::new(path).exists() {
-                builder = builder.with_file(path);
-                break;
-            }
+        if let Some(path) = find_config_file() {
+            builder = builder.with_file(path);
         }
 
         // Always apply env var overlay
@@ -184,4 +222,19 @@ mod tests {
         assert_eq!(config.search.top_k, 25);
         env::remove_var("EMRY_SEARCH_TOP_K");
     }
+
+    #[test]
+    fn test_builder_with_profile() {
+        let mut base = Config::default();
+        let mut fast = crate::ProfileConfig::default();
+        fast.search.top_k = 3;
+        base.profile.insert("fast".to_string(), fast);
+
+        let config = ConfigBuilder::new().with_config(base.clone()).with_profile("fast").build().unwrap();
+        assert_eq!(config.search.top_k, 3);
+        assert!(matches!(
+            ConfigBuilder::new().with_config(base).with_profile("deep").build(),
+            Err(ConfigError::UnknownProfile { .. })
+        ));
+    }
 }
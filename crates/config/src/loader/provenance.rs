@@ -0,0 +1,132 @@
+//! Where each effective setting comes from
+//!
+//! Loads the same layers as `Config::load` / `Config::from_file` and reports,
+//! for every leaf setting, whether its value is the default, set by the
+//! config file, by the selected profile or by an `EMRY_*` environment variable.
+
+use crate::loader::{apply_profile, env, file, find_config_file, merge};
+use crate::{Config, Result, Validate, PROFILE_ENV};
+use serde::Serialize;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+/// The layer that set a value
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", content = "source", rename_all = "lowercase")]
+pub enum Origin {
+    /// Built-in default
+    Default,
+    /// Config file
+    File(PathBuf),
+    /// Profile selected with `--profile` or `EMRY_PROFILE`
+    Profile(String),
+    /// `EMRY_*` environment variable
+    Env,
+}
+
+/// One leaf setting of the effective config
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigEntry {
+    /// Dotted key, e.g. `search.top_k`
+    pub key: String,
+    pub value: Value,
+    pub origin: Origin,
+}
+
+/// Load the effective config from `path` (or the default file, if any) and
+/// the environment, along with the origin of every setting.
+pub fn load_with_provenance(path: Option<&Path>) -> Result<(Config, Vec<ConfigEntry>)> {
+    let path = path.map(Path::to_path_buf).or_else(find_config_file);
+
+    let defaults = Config::default();
+    let from_file = match &path {
+        Some(path) => merge::merge(defaults.clone(), file::load_from_file(path)?),
+        None => defaults.clone(),
+    };
+    let profile = std::env::var(PROFILE_ENV).ok().filter(|p| !p.is_empty());
+    let from_profile = apply_profile(from_file.clone(), profile.clone())?;
+    let effective = match env::from_env()? {
+        Some(env_config) => merge::merge(from_profile.clone(), env_config),
+        None => from_profile.clone(),
+    };
+    effective.validate()?;
+
+    let mut layers = vec![(to_value(&defaults), Origin::Default)];
+    if let Some(path) = path {
+        layers.push((to_value(&from_file), Origin::File(path)));
+    }
+    if let Some(profile) = profile {
+        layers.push((to_value(&from_profile), Origin::Profile(profile)));
+    }
+    layers.push((to_value(&effective), Origin::Env));
+    Ok((effective, origins(&layers)))
+}
+
+fn to_value(config: &Config) -> Value {
+    serde_json::to_value(config).unwrap_or(Value::Null)
+}
+
+/// Attribute each leaf of the last layer (the effective config) to the
+/// topmost layer that changed it from the one below.
+fn origins(layers: &[(Value, Origin)]) -> Vec<ConfigEntry> {
+    let Some((effective, _)) = layers.last() else {
+        return Vec::new();
+    };
+    flatten(effective)
+        .into_iter()
+        .map(|(key, value)| {
+            let origin = layers
+                .windows(2)
+                .rev()
+                .find(|pair| lookup(&pair[0].0, &key) != lookup(&pair[1].0, &key))
+                .map_or(Origin::Default, |pair| pair[1].1.clone());
+            ConfigEntry { key, value, origin }
+        })
+        .collect()
+}
+
+/// Leaf values keyed by dotted path; arrays count as leaves.
+fn flatten(value: &Value) -> Vec<(String, Value)> {
+    fn walk(prefix: &str, value: &Value, out: &mut Vec<(String, Value)>) {
+        match value {
+            Value::Object(map) => {
+                for (k, v) in map {
+                    let key = if prefix.is_empty() { k.clone() } else { format!("{}.{}", prefix, k) };
+                    walk(&key, v, out);
+                }
+            }
+            leaf => out.push((prefix.to_string(), leaf.clone())),
+        }
+    }
+    let mut out = Vec::new();
+    walk("", value, &mut out);
+    out
+}
+
+fn lookup<'a>(value: &'a Value, key: &str) -> Option<&'a Value> {
+    key.split('.').try_fold(value, |v, part| v.get(part))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_origins() {
+        let path = PathBuf::from(".emry.toml");
+        let layers = [
+            (json!({"search": {"top_k": 10, "mode": "hybrid"}, "agent": {"max_steps": 8}, "ui": {"theme": "dark"}}), Origin::Default),
+            (json!({"search": {"top_k": 20, "mode": "hybrid"}, "agent": {"max_steps": 8}, "ui": {"theme": "dark"}}), Origin::File(path.clone())),
+            (json!({"search": {"top_k": 20, "mode": "hybrid"}, "agent": {"max_steps": 3}, "ui": {"theme": "dark"}}), Origin::Profile("fast".to_string())),
+            (json!({"search": {"top_k": 20, "mode": "lexical"}, "agent": {"max_steps": 3}, "ui": {"theme": "dark"}}), Origin::Env),
+        ];
+
+        let entries = origins(&layers);
+        let origin = |key: &str| entries.iter().find(|e| e.key == key).map(|e| e.origin.clone());
+        assert_eq!(origin("search.top_k"), Some(Origin::File(path.clone())));
+        assert_eq!(origin("agent.max_steps"), Some(Origin::Profile("fast".to_string())));
+        assert_eq!(origin("search.mode"), Some(Origin::Env));
+        assert_eq!(origin("ui.theme"), Some(Origin::Default));
+    }
+}
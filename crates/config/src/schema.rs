@@ -0,0 +1,202 @@
+//! JSON Schema and a commented config scaffold, both derived from the config types
+//!
+//! The schema comes from `schemars` derives on every config struct, so doc
+//! comments become descriptions editors show on hover. The scaffold walks the
+//! same schema alongside `Config::default()` to list every setting with its
+//! description and default value.
+
+use crate::Config;
+use serde_json::{Map, Value};
+
+/// JSON Schema of the config file format
+pub fn json_schema() -> Value {
+    serde_json::to_value(schemars::schema_for!(Config)).unwrap_or(Value::Null)
+}
+
+/// A `.emry.toml` listing every setting with its description and default,
+/// commented out so later default changes still apply.
+pub fn template() -> String {
+    let schema = json_schema();
+    let defaults = serde_json::to_value(Config::default()).unwrap_or(Value::Null);
+    let mut out = String::from(
+        "# emry configuration\n\
+         #\n\
+         # Every setting is listed with its default value, commented out.\n\
+         # Uncomment a line to change it; `emry config show` prints the\n\
+         # effective values and `emry config schema` the full JSON Schema.\n",
+    );
+    if let Value::Object(sections) = &defaults {
+        write_table(&mut out, "", sections, &schema, &schema);
+    }
+    out
+}
+
+/// Render a config value as TOML, e.g. `0.3`, `"hybrid"` or `["*.rs"]`.
+///
+/// Returns `None` for unset optional values, which TOML cannot express.
+pub fn toml_value(value: &Value) -> Option<String> {
+    Some(match value {
+        Value::Null => return None,
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => match (n.as_i64(), n.as_f64()) {
+            (Some(i), _) => i.to_string(),
+            // Config floats are f32; print them without the widening noise.
+            (None, Some(f)) => {
+                let text = (f as f32).to_string();
+                if text.contains('.') { text } else { format!("{}.0", text) }
+            }
+            _ => n.to_string(),
+        },
+        Value::String(s) => toml::Value::String(s.clone()).to_string(),
+        Value::Array(items) => format!("[{}]", items.iter().filter_map(toml_value).collect::<Vec<_>>().join(", ")),
+        Value::Object(map) => format!(
+            "{{ {} }}",
+            map.iter()
+                .filter_map(|(k, v)| Some(format!("{} = {}", k, toml_value(v)?)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    })
+}
+
+/// Set dotted keys (`ranking.fusion`) in TOML text to values rendered by
+/// `toml_value`, leaving the rest of the text as written.
+///
+/// A key is replaced where it is set, uncommented where a scaffold from
+/// `template` lists it, or else added to its table, which is appended to
+/// the text when missing.
+pub fn set_toml_values(text: &str, values: &[(String, String)]) -> String {
+    let mut lines: Vec<String> = text.lines().map(str::to_string).collect();
+    for (key, value) in values {
+        let (table, name) = key.rsplit_once('.').unwrap_or(("", key.as_str()));
+        let setting = format!("{} = {}", name, value);
+        let header = format!("[{}]", table);
+        let start = if table.is_empty() {
+            Some(0)
+        } else {
+            lines.iter().position(|l| l.trim() == header).map(|i| i + 1)
+        };
+        let Some(start) = start else {
+            if lines.last().is_some_and(|l| !l.trim().is_empty()) {
+                lines.push(String::new());
+            }
+            lines.push(header);
+            lines.push(setting);
+            continue;
+        };
+        let end = lines[start..]
+            .iter()
+            .position(|l| l.trim_start().starts_with('['))
+            .map_or(lines.len(), |i| start + i);
+
+        let assigns = |line: &str| line.strip_prefix(name).is_some_and(|rest| rest.trim_start().starts_with('='));
+        let set = (start..end).find(|&i| assigns(lines[i].trim_start()));
+        let scaffolded = || {
+            (start..end).find(|&i| lines[i].trim_start().strip_prefix('#').is_some_and(|l| assigns(l.trim_start())))
+        };
+        match set.or_else(scaffolded) {
+            Some(i) => lines[i] = setting,
+            None => {
+                // After the table's last setting, before any comments that describe the next table.
+                let last = (start..end).rev().find(|&i| {
+                    let line = lines[i].trim_start();
+                    !line.is_empty() && !line.starts_with('#')
+                });
+                lines.insert(last.map_or(start, |i| i + 1), setting);
+            }
+        }
+    }
+    let mut out = lines.join("\n");
+    out.push('\n');
+    out
+}
+
+/// Plain values of `table` first, then each nested table under its own header.
+fn write_table(out: &mut String, prefix: &str, table: &Map<String, Value>, schema: &Value, root: &Value) {
+    let properties = resolve(schema, root).get("properties");
+    let property = |key: &str| properties.and_then(|p| p.get(key)).unwrap_or(&Value::Null);
+
+    for (key, value) in table.iter().filter(|(_, v)| !v.is_object()) {
+        write_description(out, property(key), root);
+        match toml_value(value) {
+            Some(value) => out.push_str(&format!("# {} = {}\n", key, value)),
+            None => out.push_str(&format!("# {} = (not set)\n", key)),
+        }
+    }
+    for (key, value) in table {
+        let Value::Object(nested) = value else { continue };
+        let name = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+        out.push('\n');
+        write_description(out, property(key), root);
+        out.push_str(&format!("[{}]\n", name));
+        write_table(out, &name, nested, property(key), root);
+    }
+}
+
+fn write_description(out: &mut String, schema: &Value, root: &Value) {
+    let description = schema
+        .get("description")
+        .or_else(|| resolve(schema, root).get("description"))
+        .and_then(Value::as_str);
+    if let Some(description) = description {
+        out.push_str("#\n");
+        for line in description.lines() {
+            if line.is_empty() {
+                out.push_str("#\n");
+            } else {
+                out.push_str(&format!("# {}\n", line));
+            }
+        }
+    }
+}
+
+/// Follow `$ref`s (possibly wrapped in a single-item `allOf`) to the definition.
+fn resolve<'a>(schema: &'a Value, root: &'a Value) -> &'a Value {
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        let name = reference.trim_start_matches("#/definitions/");
+        return root.get("definitions").and_then(|d| d.get(name)).unwrap_or(&Value::Null);
+    }
+    match schema.get("allOf").and_then(Value::as_array).map(Vec::as_slice) {
+        Some([inner]) => resolve(inner, root),
+        _ => schema,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_template_lists_every_section_and_parses() {
+        let template = template();
+        for section in ["[core]", "[search]", "[ranking.intent_profiles.definition]", "[llm.cache]", "[ui.colors]"] {
+            assert!(template.contains(section), "missing {}", section);
+        }
+        assert!(template.contains("# top_k = "));
+        let config: Config = toml::from_str(&template).unwrap();
+        assert_eq!(config.search.top_k, Config::default().search.top_k);
+    }
+
+    #[test]
+    fn test_set_toml_values() {
+        let values = vec![
+            ("ranking.fusion".to_string(), "\"rrf\"".to_string()),
+            ("ranking.sparse".to_string(), "0.3".to_string()),
+            ("graph.decay".to_string(), "0.5".to_string()),
+        ];
+        let config: Config = toml::from_str(&set_toml_values(&template(), &values)).unwrap();
+        assert_eq!(config.ranking.fusion, crate::FusionStrategy::Rrf);
+        assert_eq!(config.ranking.sparse, 0.3);
+        assert_eq!(config.graph.decay, 0.5);
+
+        let text = set_toml_values("# tuned\n[ranking]\nsparse = 0.6 # was 0\n", &values);
+        assert_eq!(text, "# tuned\n[ranking]\nsparse = 0.3\nfusion = \"rrf\"\n\n[graph]\ndecay = 0.5\n");
+    }
+
+    #[test]
+    fn test_toml_value() {
+        assert_eq!(toml_value(&serde_json::to_value(0.3f32).unwrap()).unwrap(), "0.3");
+        assert_eq!(toml_value(&serde_json::json!(["*.rs", 2])).unwrap(), "[\"*.rs\", 2]");
+        assert_eq!(toml_value(&Value::Null), None);
+    }
+}
@@ -1,13 +1,25 @@
 //! Agent configuration - SINGLE SOURCE OF TRUTH
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 /// Agent behavior limits and budgets
 ///
 /// This is the ONLY definition of AgentConfig.
 /// All crates should import from emry-config.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct AgentConfig {
+    /// Loop strategy driving the agent
+    ///
+    /// `react` interleaves reasoning and tool calls one step at a time;
+    /// `plan_execute` drafts a tool plan up front, runs it, then synthesizes;
+    /// `rag` skips tools: one search, then one call answering from the top
+    /// chunks and the repository map; `deep` splits the question into
+    /// sub-questions, investigates each with its own ReAct sub-agent sharing
+    /// the step budget, then answers from their findings.
+    #[serde(default)]
+    pub strategy: AgentStrategy,
+
     /// Max results per tool call
     ///
     /// Limits how many results each search/retrieval operation returns.
@@ -45,11 +57,101 @@ pub struct AgentConfig {
     /// Time limit for each agent step (best-effort).
     #[serde(default = "default_step_timeout")]
     pub step_timeout_secs: u64,
+
+    /// Max estimated LLM spend per question (USD)
+    ///
+    /// The agent stops before its next step once the calls made for one
+    /// question are estimated to cost more; unset means no limit. Calls to
+    /// models without a known price don't count toward it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_cost_usd: Option<f64>,
+
+    /// Max LLM tokens per question
+    ///
+    /// Prompt and completion tokens of every call made for one question;
+    /// the agent stops before its next step past it. Unlike `max_tokens`,
+    /// which caps each answer, this bounds the whole run. Unset means no limit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_run_tokens: Option<u64>,
+
+    /// Check answers against the code they cite
+    ///
+    /// Before an answer is returned, the `path:line` spans it cites are read
+    /// back and the answer model corrects claims they don't support. Costs
+    /// one more call per question, and answers are no longer streamed.
+    #[serde(default)]
+    pub critic: bool,
+
+    /// Check `emry ask` citations against the index
+    ///
+    /// Asks for `path:line` citations, marks those whose file or lines aren't
+    /// in the index `[unverified]` and appends the verified ones as Sources.
+    /// Answers are printed once checked instead of streamed.
+    #[serde(default)]
+    pub require_citations: bool,
+
+    /// Let the agent run commands
+    ///
+    /// Gives `emry ask` and `emry chat` the `run_command` tool, which runs
+    /// one of `shell_commands` in the repository (never through a shell) and
+    /// returns its output. Off by default, never offered over MCP, and
+    /// unavailable in read-only mode or when a scope is set.
+    #[serde(default)]
+    pub allow_shell: bool,
+
+    /// Commands `run_command` may run
+    ///
+    /// Program names as typed, e.g. `cargo` or `rg`; the agent chooses the
+    /// arguments, so list only programs you'd let it run with any. Build and
+    /// test tools run the project's own code (`cargo test`, `build.rs`,
+    /// `go run`), which is not confined to the repository.
+    #[serde(default = "default_shell_commands")]
+    pub shell_commands: Vec<String>,
+
+    /// Timeout per command (seconds)
+    ///
+    /// `run_command` kills a command still running after this long.
+    #[serde(default = "default_shell_timeout")]
+    pub shell_timeout_secs: u64,
+}
+
+/// Agent loop strategy enum
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AgentStrategy {
+    /// Thought/action/observation loop
+    React,
+    /// Plan all tool calls first, execute them, then answer
+    PlanExecute,
+    /// Search once and answer from the retrieved code in a single call
+    Rag,
+    /// Split into sub-questions, one ReAct sub-agent each, then answer
+    Deep,
+}
+
+impl Default for AgentStrategy {
+    fn default() -> Self {
+        AgentStrategy::React
+    }
+}
+
+impl AgentStrategy {
+    /// Parse a strategy name as used in config files and env vars
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().replace('-', "_").as_str() {
+            "react" => Some(Self::React),
+            "plan_execute" => Some(Self::PlanExecute),
+            "rag" => Some(Self::Rag),
+            "deep" => Some(Self::Deep),
+            _ => None,
+        }
+    }
 }
 
 impl Default for AgentConfig {
     fn default() -> Self {
         Self {
+            strategy: AgentStrategy::default(),
             max_per_step: default_max_per_step(),
             max_observations: default_max_observations(),
             max_tokens: default_max_tokens(),
@@ -57,6 +159,13 @@ impl Default for AgentConfig {
             max_steps: default_max_steps(),
             max_total_evidence_lines: default_max_total_lines(),
             step_timeout_secs: default_step_timeout(),
+            max_cost_usd: None,
+            max_run_tokens: None,
+            critic: false,
+            require_citations: false,
+            allow_shell: false,
+            shell_commands: default_shell_commands(),
+            shell_timeout_secs: default_shell_timeout(),
         }
     }
 }
@@ -81,6 +190,34 @@ impl crate::validation::Validate for AgentConfig {
             });
         }
 
+        if self.max_cost_usd.is_some_and(|cost| cost <= 0.0 || !cost.is_finite()) {
+            return Err(crate::error::ConfigError::ValidationError {
+                field: "agent.max_cost_usd".to_string(),
+                message: "max_cost_usd must be > 0".to_string(),
+            });
+        }
+
+        if self.max_run_tokens == Some(0) {
+            return Err(crate::error::ConfigError::ValidationError {
+                field: "agent.max_run_tokens".to_string(),
+                message: "max_run_tokens must be > 0".to_string(),
+            });
+        }
+
+        if self.shell_timeout_secs == 0 {
+            return Err(crate::error::ConfigError::ValidationError {
+                field: "agent.shell_timeout_secs".to_string(),
+                message: "shell_timeout_secs must be > 0".to_string(),
+            });
+        }
+
+        if let Some(command) = self.shell_commands.iter().find(|c| c.is_empty() || c.contains(['/', '\\', ' '])) {
+            return Err(crate::error::ConfigError::ValidationError {
+                field: "agent.shell_commands".to_string(),
+                message: format!("'{}' is not a program name", command),
+            });
+        }
+
         if self.step_timeout_secs == 0 {
             return Err(crate::error::ConfigError::ValidationError {
                 field: "agent.step_timeout_secs".to_string(),
@@ -118,6 +255,14 @@ fn default_step_timeout() -> u64 {
     30
 }
 
+fn default_shell_commands() -> Vec<String> {
+    ["cargo", "go", "pytest", "grep", "rg", "ls", "wc"].iter().map(|c| c.to_string()).collect()
+}
+
+fn default_shell_timeout() -> u64 {
+    120
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -127,6 +272,7 @@ mod tests {
     fn test_default_is_valid() {
         let config = AgentConfig::default();
         assert!(config.validate().is_ok());
+        assert!(!config.allow_shell);
     }
 
     #[test]
@@ -138,6 +284,27 @@ mod tests {
         assert!(config.validate().is_err());
     }
 
+    #[test]
+    fn test_strategy_from_name() {
+        assert_eq!(AgentStrategy::from_name("react"), Some(AgentStrategy::React));
+        assert_eq!(
+            AgentStrategy::from_name("plan-execute"),
+            Some(AgentStrategy::PlanExecute)
+        );
+        assert_eq!(AgentStrategy::from_name("RAG"), Some(AgentStrategy::Rag));
+        assert_eq!(AgentStrategy::from_name("deep"), Some(AgentStrategy::Deep));
+        assert_eq!(AgentStrategy::from_name("swarm"), None);
+    }
+
+    #[test]
+    fn test_shell_commands_must_be_program_names() {
+        let config = AgentConfig {
+            shell_commands: vec!["/bin/sh".to_string()],
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+    }
+
     #[test]
     fn test_zero_max_tokens_invalid() {
         let config = AgentConfig {
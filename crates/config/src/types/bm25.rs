@@ -1,12 +1,13 @@
 //! BM25 algorithm parameters
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 /// BM25 (Best Matching 25) algorithm parameters
 ///
 /// BM25 is a ranking function used for lexical search.
 /// These parameters control term frequency saturation and document length normalization.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Bm25Config {
     /// Term frequency saturation parameter
     ///
@@ -32,6 +33,14 @@ pub struct Bm25Config {
     /// Used for length normalization. This should match your typical code chunk size.
     #[serde(default = "default_avg_len")]
     pub avg_len: usize,
+
+    /// Match Chinese, Japanese and Korean text by character bigrams
+    ///
+    /// The lexical analyzer splits on spaces and character classes, so a run
+    /// of CJK text is a single token and only matches verbatim. With this set,
+    /// queries containing CJK text also search the bigrams stored per chunk.
+    #[serde(default)]
+    pub cjk_segmentation: bool,
 }
 
 impl Default for Bm25Config {
@@ -40,6 +49,7 @@ impl Default for Bm25Config {
             k1: default_k1(),
             b: default_b(),
             avg_len: default_avg_len(),
+            cjk_segmentation: false,
         }
     }
 }
@@ -1,9 +1,10 @@
 //! Code chunking configuration
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 /// Configuration for code chunking
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ChunkingConfig {
     /// Maximum tokens per chunk
     ///
@@ -40,7 +41,7 @@ pub struct ChunkingConfig {
 }
 
 /// Chunking strategy when token limit is exceeded
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum SplitStrategy {
     /// Drop overflow tokens
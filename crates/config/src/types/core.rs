@@ -1,9 +1,10 @@
 //! Core configuration (paths, storage, file scanning)
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 /// Core configuration for file scanning and storage
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct CoreConfig {
     /// Glob patterns for files to include in indexing
     ///
@@ -20,6 +21,32 @@ pub struct CoreConfig {
     /// Automatically index on search if index is stale
     #[serde(default = "default_auto_index")]
     pub auto_index_on_search: bool,
+
+    /// Skip files matched by `.gitignore` (at any depth), `.git/info/exclude`
+    /// and `.ignore` files, and hidden files
+    #[serde(default = "default_respect_gitignore")]
+    pub respect_gitignore: bool,
+
+    /// Files larger than this are not indexed (generated bundles, minified
+    /// code, data dumps); 0 disables the limit
+    #[serde(default = "default_max_file_size_kb")]
+    pub max_file_size_kb: u64,
+
+    /// Command that output taller than the terminal is paged through;
+    /// unset uses `$PAGER`, then `less -FRX`, and `""` disables paging
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pager: Option<String>,
+
+    /// Items listed before output is cut with an "N more, use --all" hint;
+    /// 0 lists everything
+    #[serde(default = "default_list_limit")]
+    pub list_limit: usize,
+
+    /// Never write to the index or the repository: the store rejects writes,
+    /// and commands that exist to write (`index`, `watch`, `apply`, ...) refuse
+    /// to run; for indexes mounted read-only or owned by another user
+    #[serde(default)]
+    pub read_only: bool,
 }
 
 impl Default for CoreConfig {
@@ -28,6 +55,11 @@ impl Default for CoreConfig {
             include_paths: default_include_paths(),
             exclude_paths: vec![],
             auto_index_on_search: default_auto_index(),
+            respect_gitignore: default_respect_gitignore(),
+            max_file_size_kb: default_max_file_size_kb(),
+            pager: None,
+            list_limit: default_list_limit(),
+            read_only: false,
         }
     }
 }
@@ -65,6 +97,18 @@ fn default_auto_index() -> bool {
     true
 }
 
+fn default_respect_gitignore() -> bool {
+    true
+}
+
+fn default_max_file_size_kb() -> u64 {
+    1024
+}
+
+fn default_list_limit() -> usize {
+    50
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
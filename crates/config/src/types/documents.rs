@@ -0,0 +1,27 @@
+//! Non-code document indexing configuration
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Indexing of Markdown, YAML, TOML and JSON files alongside code
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DocumentsConfig {
+    /// Chunk and embed documents so they participate in search
+    ///
+    /// Markdown is split at headings; YAML, TOML and JSON at top-level keys
+    /// or tables. Documents contribute no symbols or graph edges.
+    #[serde(default)]
+    pub enabled: bool,
+}
+
+impl Default for DocumentsConfig {
+    fn default() -> Self {
+        Self { enabled: false }
+    }
+}
+
+impl crate::validation::Validate for DocumentsConfig {
+    fn validate(&self) -> crate::error::Result<()> {
+        Ok(())
+    }
+}
@@ -1,9 +1,11 @@
 //! Embedding provider configuration
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 
 /// Embedding provider configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct EmbeddingConfig {
     /// Embedding backend to use
     #[serde(default)]
@@ -16,10 +18,35 @@ pub struct EmbeddingConfig {
     /// - Ollama: "nomic-embed-text", "mxbai-embed-large"
     #[serde(default = "default_model_name")]
     pub model_name: String,
+
+    /// Directory holding `model.onnx` and `tokenizer.json` for the onnx backend
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model_path: Option<PathBuf>,
+
+    /// Embedding dimension override for models with a non-default size
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dimension: Option<usize>,
+
+    /// Chunks sent per embedding request; batches fill across files
+    #[serde(default = "default_batch_size")]
+    pub batch_size: usize,
+
+    /// Embedding requests in flight at once while indexing
+    #[serde(default = "default_max_concurrent_requests")]
+    pub max_concurrent_requests: usize,
+
+    /// How chunk embeddings are kept in the vector index
+    ///
+    /// `int8` and `binary` index 16-bit copies of the embeddings instead of
+    /// the 32-bit vectors and rescore candidates with the full embeddings,
+    /// which stay stored: the index shrinks, the store on disk grows, so this
+    /// does not reduce disk usage.
+    #[serde(default)]
+    pub quantization: Quantization,
 }
 
 /// Embedding backend options
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum EmbeddingBackend {
     /// OpenAI API (requires OPENAI_API_KEY)
@@ -31,6 +58,39 @@ pub enum EmbeddingBackend {
 
     /// Local Ollama server
     Ollama,
+
+    /// In-process ONNX sentence-transformer (requires the `onnx` build feature)
+    Onnx,
+
+    /// No embeddings; only lexical and graph search are available
+    None,
+}
+
+/// Vector index storage options
+///
+/// Quantized indexes search compact vectors and rescore the best candidates
+/// against the full-precision embeddings, which are still stored.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Quantization {
+    /// Index the full-precision vectors
+    None,
+
+    /// Each dimension scaled per vector to a signed byte's range
+    Int8,
+
+    /// Each dimension's sign, compared by Hamming distance
+    Binary,
+}
+
+impl Quantization {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Quantization::None => "none",
+            Quantization::Int8 => "int8",
+            Quantization::Binary => "binary",
+        }
+    }
 }
 
 impl Default for EmbeddingConfig {
@@ -38,6 +98,11 @@ impl Default for EmbeddingConfig {
         Self {
             backend: EmbeddingBackend::Ollama,
             model_name: default_model_name(),
+            model_path: None,
+            dimension: None,
+            batch_size: default_batch_size(),
+            max_concurrent_requests: default_max_concurrent_requests(),
+            quantization: Quantization::default(),
         }
     }
 }
@@ -48,10 +113,37 @@ impl Default for EmbeddingBackend {
     }
 }
 
+impl Default for Quantization {
+    fn default() -> Self {
+        Quantization::None
+    }
+}
+
 impl crate::validation::Validate for EmbeddingConfig {
     fn validate(&self) -> crate::error::Result<()> {
         use crate::error::ConfigError;
 
+        if self.dimension == Some(0) {
+            return Err(ConfigError::ValidationError {
+                field: "embedding.dimension".to_string(),
+                message: "dimension must be > 0".to_string(),
+            });
+        }
+
+        if self.batch_size == 0 {
+            return Err(ConfigError::ValidationError {
+                field: "embedding.batch_size".to_string(),
+                message: "batch_size must be > 0".to_string(),
+            });
+        }
+
+        if self.max_concurrent_requests == 0 {
+            return Err(ConfigError::ValidationError {
+                field: "embedding.max_concurrent_requests".to_string(),
+                message: "max_concurrent_requests must be > 0".to_string(),
+            });
+        }
+
         if self.model_name.is_empty() {
             return Err(ConfigError::ValidationError {
                 field: "embedding.model_name".to_string(),
@@ -75,6 +167,17 @@ impl crate::validation::Validate for EmbeddingConfig {
             EmbeddingBackend::Local => {
                 // No additional validation needed
             }
+            EmbeddingBackend::Onnx => {
+                if self.model_path.is_none() {
+                    return Err(ConfigError::ValidationError {
+                        field: "embedding.model_path".to_string(),
+                        message: "model_path is required when backend is 'onnx'".to_string(),
+                    });
+                }
+            }
+            EmbeddingBackend::None => {
+                // No additional validation needed
+            }
         }
 
         Ok(())
@@ -85,6 +188,14 @@ fn default_model_name() -> String {
     "nomic-embed-text".to_string()
 }
 
+fn default_batch_size() -> usize {
+    128
+}
+
+fn default_max_concurrent_requests() -> usize {
+    4
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -105,6 +216,33 @@ mod tests {
         assert!(config.validate().is_err());
     }
 
+    #[test]
+    fn test_zero_batching_invalid() {
+        let config = EmbeddingConfig {
+            batch_size: 0,
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+
+        let config = EmbeddingConfig {
+            max_concurrent_requests: 0,
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_onnx_requires_model_path() {
+        let mut config = EmbeddingConfig {
+            backend: EmbeddingBackend::Onnx,
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+
+        config.model_path = Some(PathBuf::from("models/all-MiniLM-L6-v2"));
+        assert!(config.validate().is_ok());
+    }
+
     #[test]
     fn test_backend_serialization() {
         assert_eq!(
@@ -115,5 +253,22 @@ mod tests {
             serde_json::to_string(&EmbeddingBackend::Ollama).unwrap(),
             "\"ollama\""
         );
+        assert_eq!(
+            serde_json::to_string(&EmbeddingBackend::None).unwrap(),
+            "\"none\""
+        );
+        assert_eq!(
+            serde_json::to_string(&EmbeddingBackend::Onnx).unwrap(),
+            "\"onnx\""
+        );
+    }
+
+    #[test]
+    fn test_quantization_serialization() {
+        assert_eq!(serde_json::to_string(&Quantization::Int8).unwrap(), "\"int8\"");
+        let config: EmbeddingConfig = serde_json::from_str(r#"{"quantization": "binary"}"#).unwrap();
+        assert_eq!(config.quantization, Quantization::Binary);
+        let config: EmbeddingConfig = serde_json::from_str("{}").unwrap();
+        assert_eq!(config.quantization, Quantization::None);
     }
 }
@@ -1,10 +1,11 @@
 //! Graph traversal configuration
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 /// Graph traversal and scoring configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct GraphConfig {
     /// Maximum depth for graph traversal
     ///
@@ -1,9 +1,10 @@
 //! LLM configuration
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 /// LLM (Large Language Model) configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct LlmConfig {
     /// Model name
     ///
@@ -11,6 +12,20 @@ pub struct LlmConfig {
     #[serde(default = "default_model")]
     pub model: String,
 
+    /// Model for the agent's tool steps
+    ///
+    /// Picks tools and plans, so a cheaper, faster model usually does.
+    /// Defaults to the main model.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub planner_model: Option<String>,
+
+    /// Model for the agent's final answers
+    ///
+    /// Writes the answer from what the steps gathered, and runs the critic
+    /// pass (`agent.critic`). Defaults to the main model.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub answer_model: Option<String>,
+
     /// Maximum tokens for LLM responses
     #[serde(default = "default_max_tokens")]
     pub max_tokens: u32,
@@ -24,15 +39,48 @@ pub struct LlmConfig {
     /// Timeout for LLM API calls in seconds
     #[serde(default = "default_timeout_secs")]
     pub timeout_secs: u64,
+
+    /// Response cache for repeated prompts
+    #[serde(default)]
+    pub cache: LlmCacheConfig,
+}
+
+/// Cache of LLM responses keyed by a hash of the request, stored in the index
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct LlmCacheConfig {
+    /// Serve identical requests (model, messages, schema, token limit) from the cache
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Seconds a cached response stays valid
+    #[serde(default = "default_cache_ttl_secs")]
+    pub ttl_secs: u64,
+
+    /// Cached responses kept; the oldest are dropped beyond this
+    #[serde(default = "default_cache_max_entries")]
+    pub max_entries: usize,
 }
 
 impl Default for LlmConfig {
     fn default() -> Self {
         Self {
             model: default_model(),
+            planner_model: None,
+            answer_model: None,
             max_tokens: default_max_tokens(),
             api_base: None,
             timeout_secs: default_timeout_secs(),
+            cache: LlmCacheConfig::default(),
+        }
+    }
+}
+
+impl Default for LlmCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            ttl_secs: default_cache_ttl_secs(),
+            max_entries: default_cache_max_entries(),
         }
     }
 }
@@ -48,6 +96,15 @@ impl crate::validation::Validate for LlmConfig {
             });
         }
 
+        for (field, model) in [("llm.planner_model", &self.planner_model), ("llm.answer_model", &self.answer_model)] {
+            if model.as_ref().is_some_and(|m| m.is_empty()) {
+                return Err(ConfigError::ValidationError {
+                    field: field.to_string(),
+                    message: "Model name cannot be empty (leave it unset to use the main model)".to_string(),
+                });
+            }
+        }
+
         if self.max_tokens == 0 {
             return Err(ConfigError::ValidationError {
                 field: "llm.max_tokens".to_string(),
@@ -62,6 +119,20 @@ impl crate::validation::Validate for LlmConfig {
             });
         }
 
+        if self.cache.ttl_secs == 0 {
+            return Err(ConfigError::ValidationError {
+                field: "llm.cache.ttl_secs".to_string(),
+                message: "ttl_secs must be > 0".to_string(),
+            });
+        }
+
+        if self.cache.max_entries == 0 {
+            return Err(ConfigError::ValidationError {
+                field: "llm.cache.max_entries".to_string(),
+                message: "max_entries must be > 0 (set enabled: false to turn the cache off)".to_string(),
+            });
+        }
+
         // Validate API base URL if provided
         if let Some(api_base) = &self.api_base {
             if api_base.is_empty() {
@@ -99,6 +170,14 @@ fn default_timeout_secs() -> u64 {
     60
 }
 
+fn default_cache_ttl_secs() -> u64 {
+    7 * 24 * 60 * 60
+}
+
+fn default_cache_max_entries() -> usize {
+    1000
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -128,6 +207,18 @@ mod tests {
         assert!(config.validate().is_err());
     }
 
+    #[test]
+    fn test_zero_cache_ttl_invalid() {
+        let config = LlmConfig {
+            cache: LlmCacheConfig {
+                ttl_secs: 0,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+    }
+
     #[test]
     fn test_invalid_api_base() {
         let config = LlmConfig {
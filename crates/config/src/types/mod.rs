@@ -7,31 +7,43 @@ pub mod agent;
 pub mod bm25;
 pub mod chunking;
 pub mod core;
+pub mod documents;
 pub mod embedding;
 pub mod graph;
 pub mod llm;
+pub mod owners;
+pub mod profile;
 pub mod ranking;
+pub mod rerank;
 pub mod search;
+pub mod ui;
 
 // Re-export all types for convenience
-pub use agent::AgentConfig;
+pub use agent::{AgentConfig, AgentStrategy};
 pub use bm25::Bm25Config;
 pub use chunking::{ChunkingConfig, SplitStrategy};
 pub use core::CoreConfig;
-pub use embedding::{EmbeddingBackend, EmbeddingConfig};
+pub use documents::DocumentsConfig;
+pub use embedding::{EmbeddingBackend, EmbeddingConfig, Quantization};
 pub use graph::GraphConfig;
-pub use llm::LlmConfig;
-pub use ranking::RankingConfig;
-pub use search::{SearchConfig, SearchMode};
+pub use llm::{LlmCacheConfig, LlmConfig};
+pub use owners::OwnersConfig;
+pub use profile::{ProfileConfig, PROFILE_ENV};
+pub use ranking::{FusionStrategy, IntentProfiles, KindBoosts, RankingConfig};
+pub use rerank::{RerankBackend, RerankConfig};
+pub use search::{SearchConfig, SearchMode, SnippetSource};
+pub use ui::{UiColors, UiConfig, UiTheme};
 
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /// Main configuration struct aggregating all settings
 ///
 /// This is the top-level configuration that users interact with.
 /// It's organized by functional area for clarity.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Config {
     /// Core settings (paths, storage)
     #[serde(default)]
@@ -57,10 +69,22 @@ pub struct Config {
     #[serde(default)]
     pub chunking: ChunkingConfig,
 
+    /// Markdown/YAML/TOML/JSON document indexing
+    #[serde(default)]
+    pub documents: DocumentsConfig,
+
+    /// CODEOWNERS and git blame ownership of indexed files
+    #[serde(default)]
+    pub owners: OwnersConfig,
+
     /// Embedding provider settings
     #[serde(default)]
     pub embedding: EmbeddingConfig,
 
+    /// Cross-encoder reranking of top hits
+    #[serde(default)]
+    pub rerank: RerankConfig,
+
     /// Agent behavior limits
     #[serde(default)]
     pub agent: AgentConfig,
@@ -68,6 +92,14 @@ pub struct Config {
     /// LLM settings
     #[serde(default)]
     pub llm: LlmConfig,
+
+    /// Terminal colors
+    #[serde(default)]
+    pub ui: UiConfig,
+
+    /// Named overrides, e.g. `[profile.fast]`, selected with `--profile` or `EMRY_PROFILE`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profile: BTreeMap<String, ProfileConfig>,
 }
 
 impl Default for Config {
@@ -79,9 +111,14 @@ impl Default for Config {
             bm25: Bm25Config::default(),
             graph: GraphConfig::default(),
             chunking: ChunkingConfig::default(),
+            documents: DocumentsConfig::default(),
+            owners: OwnersConfig::default(),
             embedding: EmbeddingConfig::default(),
+            rerank: RerankConfig::default(),
             agent: AgentConfig::default(),
             llm: LlmConfig::default(),
+            ui: UiConfig::default(),
+            profile: BTreeMap::new(),
         }
     }
 }
@@ -95,9 +132,16 @@ impl crate::validation::Validate for Config {
         self.bm25.validate()?;
         self.graph.validate()?;
         self.chunking.validate()?;
+        self.documents.validate()?;
+        self.owners.validate()?;
         self.embedding.validate()?;
+        self.rerank.validate()?;
         self.agent.validate()?;
         self.llm.validate()?;
+        self.ui.validate()?;
+        for profile in self.profile.values() {
+            profile.validate()?;
+        }
 
         Ok(())
     }
@@ -0,0 +1,41 @@
+//! Code ownership configuration
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Owner metadata attached to indexed files
+///
+/// CODEOWNERS rules are always applied when the repository has the file;
+/// blame authorship is opt-in because it runs `git blame` once per file.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct OwnersConfig {
+    /// Record each file's top authors by `git blame` line count
+    ///
+    /// Files are blamed when they are first indexed and again after they change.
+    #[serde(default)]
+    pub blame: bool,
+
+    /// Number of blame authors kept per file
+    #[serde(default = "default_blame_authors")]
+    pub blame_authors: usize,
+}
+
+impl Default for OwnersConfig {
+    fn default() -> Self {
+        Self {
+            blame: false,
+            blame_authors: default_blame_authors(),
+        }
+    }
+}
+
+impl crate::validation::Validate for OwnersConfig {
+    fn validate(&self) -> crate::error::Result<()> {
+        crate::validation::validate_positive("owners.blame_authors", self.blame_authors, 0)?;
+        Ok(())
+    }
+}
+
+fn default_blame_authors() -> usize {
+    3
+}
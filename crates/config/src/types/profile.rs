@@ -0,0 +1,47 @@
+//! Named configuration profiles
+
+use super::{AgentConfig, LlmConfig, RankingConfig, RerankConfig, SearchConfig};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Environment variable naming the profile to apply
+pub const PROFILE_ENV: &str = "EMRY_PROFILE";
+
+/// Overrides applied on top of the config file when the profile is selected
+///
+/// Selected with `--profile <name>` or `EMRY_PROFILE`, e.g. a `fast` profile
+/// for cheap local search and a `deep` one for LLM-assisted exploration.
+/// Like any overlay, only values that differ from the defaults take effect.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct ProfileConfig {
+    /// Search behavior
+    #[serde(default)]
+    pub search: SearchConfig,
+
+    /// Ranking weights for hybrid search
+    #[serde(default)]
+    pub ranking: RankingConfig,
+
+    /// Cross-encoder reranking of top hits
+    #[serde(default)]
+    pub rerank: RerankConfig,
+
+    /// Agent behavior limits
+    #[serde(default)]
+    pub agent: AgentConfig,
+
+    /// LLM settings
+    #[serde(default)]
+    pub llm: LlmConfig,
+}
+
+impl crate::validation::Validate for ProfileConfig {
+    fn validate(&self) -> crate::error::Result<()> {
+        self.search.validate()?;
+        self.ranking.validate()?;
+        self.rerank.validate()?;
+        self.agent.validate()?;
+        self.llm.validate()?;
+        Ok(())
+    }
+}
@@ -1,12 +1,13 @@
 //! Ranking weights configuration
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 /// Ranking weights for hybrid search
 ///
 /// These weights determine how different scoring signals are combined.
 /// All weights should be in [0, 1] and ideally sum to 1.0 for normalized scores.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct RankingConfig {
     /// Weight for lexical (BM25) scoring
     ///
@@ -39,6 +40,112 @@ pub struct RankingConfig {
     /// Recommended: 0.1-0.2
     #[serde(default = "default_symbol")]
     pub symbol: f32,
+
+    /// Weight for sparse term-weight scoring
+    ///
+    /// Scores chunks by their index-time term weights times how rare each
+    /// query term is across the index, so rare identifiers outrank common
+    /// words. 0 disables the sparse source.
+    /// Recommended: 0.3-0.6 for identifier-heavy queries
+    #[serde(default)]
+    pub sparse: f32,
+
+    /// Boost for chunks exercised by tests
+    ///
+    /// Scales a chunk's score by `1 + coverage * r`, where `r` is the share of
+    /// its lines that ran in the coverage imported with `emry coverage import`.
+    /// 0 disables the boost.
+    /// Recommended: 0.1-0.3
+    #[serde(default)]
+    pub coverage: f32,
+
+    /// How per-source result lists are merged into one ranking
+    ///
+    /// `max` keeps each chunk's best source score; `weighted` sums scores
+    /// normalized per source and scaled by `lexical`/`vector`; `rrf` sums
+    /// reciprocal ranks and ignores score scales; `zscore` sums per-source
+    /// standardized scores.
+    #[serde(default)]
+    pub fusion: FusionStrategy,
+
+    /// Chunk-kind boosts applied per detected query intent
+    ///
+    /// "What is X" favours definitions, "who uses X" favours call sites and
+    /// configuration questions favour config files.
+    #[serde(default)]
+    pub intent_profiles: IntentProfiles,
+}
+
+/// Fusion strategy for merging per-source search results
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FusionStrategy {
+    /// Best score across sources
+    #[default]
+    Max,
+    /// Weighted sum of max-normalized scores
+    Weighted,
+    /// Reciprocal Rank Fusion
+    Rrf,
+    /// Sum of per-source z-scores
+    Zscore,
+}
+
+impl FusionStrategy {
+    /// Parse a strategy name as used in config files and env vars
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().replace(['-', '_'], "").as_str() {
+            "max" => Some(Self::Max),
+            "weighted" => Some(Self::Weighted),
+            "rrf" => Some(Self::Rrf),
+            "zscore" => Some(Self::Zscore),
+            _ => None,
+        }
+    }
+}
+
+/// Kind boosts for each query intent
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct IntentProfiles {
+    /// Definition-seeking queries ("what is X", "where is X defined")
+    #[serde(default = "default_definition_profile")]
+    pub definition: KindBoosts,
+
+    /// Usage-seeking queries ("who uses X", "callers of X")
+    #[serde(default = "default_usage_profile")]
+    pub usage: KindBoosts,
+
+    /// Configuration-seeking queries ("which setting controls X")
+    #[serde(default = "default_config_profile")]
+    pub config: KindBoosts,
+}
+
+/// Multiplicative score boosts by chunk kind
+///
+/// A boost of 0.3 scales a matching chunk's score by 1.3.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct KindBoosts {
+    /// Chunks that define the queried symbol
+    #[serde(default)]
+    pub definition: f32,
+
+    /// Chunks that call or reference the queried symbol
+    #[serde(default)]
+    pub usage: f32,
+
+    /// Chunks from configuration files
+    #[serde(default)]
+    pub config: f32,
+}
+
+impl Default for IntentProfiles {
+    fn default() -> Self {
+        Self {
+            definition: default_definition_profile(),
+            usage: default_usage_profile(),
+            config: default_config_profile(),
+        }
+    }
 }
 
 impl Default for RankingConfig {
@@ -48,6 +155,10 @@ impl Default for RankingConfig {
             vector: default_vector(),
             graph: default_graph(),
             symbol: default_symbol(),
+            sparse: 0.0,
+            coverage: 0.0,
+            fusion: FusionStrategy::default(),
+            intent_profiles: IntentProfiles::default(),
         }
     }
 }
@@ -61,6 +172,23 @@ impl crate::validation::Validate for RankingConfig {
         validate_range("ranking.vector", self.vector, 0.0, 1.0)?;
         validate_range("ranking.graph", self.graph, 0.0, 1.0)?;
         validate_range("ranking.symbol", self.symbol, 0.0, 1.0)?;
+        validate_range("ranking.sparse", self.sparse, 0.0, 1.0)?;
+        validate_range("ranking.coverage", self.coverage, 0.0, 1.0)?;
+
+        let profiles = &self.intent_profiles;
+        for (intent, boosts) in [
+            ("definition", &profiles.definition),
+            ("usage", &profiles.usage),
+            ("config", &profiles.config),
+        ] {
+            for (kind, value) in [
+                ("definition", boosts.definition),
+                ("usage", boosts.usage),
+                ("config", boosts.config),
+            ] {
+                validate_range(format!("ranking.intent_profiles.{}.{}", intent, kind), value, 0.0, 1.0)?;
+            }
+        }
 
         // Validate primary weights (lexical + vector) sum to ~1.0
         let weights = vec![
@@ -94,6 +222,18 @@ fn default_symbol() -> f32 {
     0.15 // Boost for symbol matches
 }
 
+fn default_definition_profile() -> KindBoosts {
+    KindBoosts { definition: 0.3, ..Default::default() }
+}
+
+fn default_usage_profile() -> KindBoosts {
+    KindBoosts { usage: 0.3, ..Default::default() }
+}
+
+fn default_config_profile() -> KindBoosts {
+    KindBoosts { config: 0.3, ..Default::default() }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -131,6 +271,7 @@ mod tests {
             vector: 0.5,
             graph: 0.15,
             symbol: 0.1,
+            ..Default::default()
         };
         assert!(config.validate().is_ok());
     }
@@ -0,0 +1,126 @@
+//! Cross-encoder reranking configuration
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Reranking of the top fused search hits with a cross-encoder
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RerankConfig {
+    /// Enable the rerank stage
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Where the cross-encoder runs
+    #[serde(default)]
+    pub backend: RerankBackend,
+
+    /// Model name sent to the rerank API
+    ///
+    /// Examples: "rerank-v3.5" (Cohere), "jina-reranker-v2-base-multilingual" (Jina)
+    #[serde(default = "default_model")]
+    pub model: String,
+
+    /// Directory holding `model.onnx` and `tokenizer.json` for the onnx backend
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model_path: Option<PathBuf>,
+
+    /// Base URL of a Cohere-compatible `/rerank` endpoint
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_base: Option<String>,
+
+    /// Number of top hits passed through the cross-encoder
+    ///
+    /// Hits below this cut keep their fused order.
+    /// Recommended: 10-50
+    #[serde(default = "default_top_n")]
+    pub top_n: usize,
+}
+
+/// Cross-encoder backend options
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RerankBackend {
+    /// Remote rerank API (requires RERANK_API_KEY)
+    Api,
+
+    /// In-process ONNX cross-encoder (requires the `onnx` build feature)
+    Onnx,
+}
+
+impl Default for RerankConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            backend: RerankBackend::default(),
+            model: default_model(),
+            model_path: None,
+            api_base: None,
+            top_n: default_top_n(),
+        }
+    }
+}
+
+impl Default for RerankBackend {
+    fn default() -> Self {
+        RerankBackend::Api
+    }
+}
+
+impl crate::validation::Validate for RerankConfig {
+    fn validate(&self) -> crate::error::Result<()> {
+        use crate::error::ConfigError;
+        use crate::validation::validate_positive;
+
+        validate_positive("rerank.top_n", self.top_n, 0)?;
+
+        if self.enabled && self.backend == RerankBackend::Onnx && self.model_path.is_none() {
+            return Err(ConfigError::ValidationError {
+                field: "rerank.model_path".to_string(),
+                message: "model_path is required when backend is 'onnx'".to_string(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+fn default_model() -> String {
+    "rerank-v3.5".to_string()
+}
+
+fn default_top_n() -> usize {
+    20
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validation::Validate;
+
+    #[test]
+    fn test_default_is_valid() {
+        let config = RerankConfig::default();
+        assert!(!config.enabled);
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_onnx_requires_model_path() {
+        let config = RerankConfig {
+            enabled: true,
+            backend: RerankBackend::Onnx,
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_zero_top_n_invalid() {
+        let config = RerankConfig {
+            top_n: 0,
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+    }
+}
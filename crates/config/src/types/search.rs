@@ -1,9 +1,10 @@
 //! Search configuration
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 /// Search behavior configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct SearchConfig {
     /// Search mode to use
     #[serde(default)]
@@ -12,10 +13,26 @@ pub struct SearchConfig {
     /// Number of top results to return
     #[serde(default = "default_top_k")]
     pub top_k: usize,
+
+    /// Where result snippets are read from
+    ///
+    /// Either way, hits whose file changed since indexing are marked "modified since index".
+    #[serde(default)]
+    pub snippet_source: SnippetSource,
+
+    /// Expand queries with terms derived from the index: identifier parts,
+    /// symbol names sharing most of the query's words, and import aliases
+    #[serde(default)]
+    pub query_expansion: bool,
+
+    /// Address (`host:port`) of an emry daemon started with `--listen`, whose
+    /// results `emry search` merges with this repository's
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote: Option<String>,
 }
 
 /// Search mode enum
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum SearchMode {
     /// Lexical (BM25) search only
@@ -26,15 +43,34 @@ pub enum SearchMode {
     Hybrid,
 }
 
+/// Snippet source preference
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SnippetSource {
+    /// Show the chunk as it was indexed
+    PreferIndex,
+    /// Show the current file span, falling back to the index if the file is gone
+    PreferWorkingTree,
+}
+
 impl Default for SearchConfig {
     fn default() -> Self {
         Self {
             mode: SearchMode::Hybrid,
             top_k: default_top_k(),
+            snippet_source: SnippetSource::default(),
+            query_expansion: false,
+            remote: None,
         }
     }
 }
 
+impl Default for SnippetSource {
+    fn default() -> Self {
+        SnippetSource::PreferIndex
+    }
+}
+
 impl Default for SearchMode {
     fn default() -> Self {
         SearchMode::Hybrid
@@ -99,6 +135,14 @@ mod tests {
         assert!(config.validate().is_err());
     }
 
+    #[test]
+    fn test_snippet_source_serialization() {
+        assert_eq!(
+            serde_json::to_string(&SnippetSource::PreferWorkingTree).unwrap(),
+            "\"prefer_working_tree\""
+        );
+    }
+
     #[test]
     fn test_search_mode_serialization() {
         assert_eq!(
@@ -0,0 +1,115 @@
+//! Terminal output configuration
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Color words accepted in style overrides, besides `0`-`255` palette indices.
+const STYLE_WORDS: &[&str] = &[
+    "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white",
+    "bright", "bold", "dim", "italic", "underlined", "blink", "reverse", "hidden",
+];
+
+/// Colors of headers, locations, prompts and status messages
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct UiConfig {
+    /// Base palette
+    #[serde(default)]
+    pub theme: UiTheme,
+
+    /// Per-role overrides of the theme
+    #[serde(default)]
+    pub colors: UiColors,
+}
+
+/// Built-in palettes
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum UiTheme {
+    /// Cyan and blue accents for dark backgrounds
+    Dark,
+    /// Blue and magenta accents that stay readable on light backgrounds
+    Light,
+    /// No colors, only bold and dim
+    Mono,
+}
+
+/// Style overrides as dotted style strings, e.g. `"magenta.bold"`,
+/// `"bright.cyan"` or `"208"` for a 256-color palette index
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct UiColors {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub heading: Option<String>,
+    /// File locations of results and sources
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub success: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warning: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl Default for UiTheme {
+    fn default() -> Self {
+        UiTheme::Dark
+    }
+}
+
+impl UiColors {
+    /// `(role, override)` pairs.
+    pub fn entries(&self) -> [(&'static str, &Option<String>); 6] {
+        [
+            ("heading", &self.heading),
+            ("location", &self.location),
+            ("prompt", &self.prompt),
+            ("success", &self.success),
+            ("warning", &self.warning),
+            ("error", &self.error),
+        ]
+    }
+}
+
+impl crate::validation::Validate for UiConfig {
+    fn validate(&self) -> crate::error::Result<()> {
+        use crate::error::ConfigError;
+
+        for (role, style) in self.colors.entries() {
+            let Some(style) = style else { continue };
+            let valid = !style.is_empty()
+                && style.split('.').all(|word| {
+                    let word = word.strip_prefix("on_").unwrap_or(word);
+                    STYLE_WORDS.contains(&word) || word.parse::<u8>().is_ok()
+                });
+            if !valid {
+                return Err(ConfigError::ValidationError {
+                    field: format!("ui.colors.{}", role),
+                    message: format!("unrecognized style '{}' (expected e.g. 'cyan', 'bright.blue.bold' or '208')", style),
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validation::Validate;
+
+    #[test]
+    fn test_color_overrides_validated() {
+        let mut config = UiConfig::default();
+        assert!(config.validate().is_ok());
+
+        config.colors.heading = Some("bright.magenta.bold".to_string());
+        config.colors.location = Some("208".to_string());
+        assert!(config.validate().is_ok());
+
+        config.colors.prompt = Some("teal".to_string());
+        assert!(config.validate().is_err());
+    }
+}
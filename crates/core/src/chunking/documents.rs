@@ -0,0 +1,390 @@
+//! Structure-aware chunking for non-code documents.
+//!
+//! Markdown is split into heading sections, each carrying its heading trail
+//! as the scope path. YAML and JSON are split at top-level keys (YAML goes
+//! one level deeper when a block is too large) and TOML at table headers, so
+//! a chunk's scope path is the key path of the config it holds. Sections
+//! still over `max_chars` are split at line boundaries.
+
+use super::generic::{compute_line_offsets, count_non_whitespace, make_chunk_from_span};
+use super::splitter::enforce_token_limits;
+use super::Chunker;
+use crate::models::{Chunk, Language};
+use anyhow::{anyhow, Result};
+use emry_config::ChunkingConfig;
+use std::path::Path;
+
+pub struct DocumentChunker {
+    language: Language,
+    config: ChunkingConfig,
+}
+
+/// A byte span of the document and the key/heading path it belongs to.
+#[derive(Debug, Clone, PartialEq)]
+struct Section {
+    start: usize,
+    end: usize,
+    kind: &'static str,
+    path: Vec<String>,
+}
+
+impl DocumentChunker {
+    pub fn new(language: Language, config: ChunkingConfig) -> Self {
+        Self { language, config }
+    }
+}
+
+impl Chunker for DocumentChunker {
+    fn chunk(&self, content: &str, file_path: &Path) -> Result<Vec<Chunk>> {
+        let max_chars = self.config.max_chars;
+        let sections = match self.language {
+            Language::Markdown => markdown_sections(content),
+            Language::Yaml => yaml_sections(content, max_chars),
+            Language::Toml => toml_sections(content),
+            Language::Json => json_sections(content),
+            _ => return Err(anyhow!("{:?} is not a document format", self.language)),
+        };
+
+        let line_offsets = compute_line_offsets(content);
+        let mut chunks = Vec::new();
+        for section in sections {
+            let text = &content[section.start..section.end];
+            if text.trim().is_empty() {
+                continue;
+            }
+            let parent_scope = section.path.len().checked_sub(2).map(|i| section.path[i].clone());
+            let spans = if count_non_whitespace(text) <= max_chars {
+                vec![(section.start, section.end, section.kind.to_string())]
+            } else {
+                split_at_lines(content, section.start, section.end, max_chars)
+                    .into_iter()
+                    .map(|(s, e)| (s, e, format!("{}_part", section.kind)))
+                    .collect()
+            };
+            for (start, end, kind) in spans {
+                let end = start + content[start..end].trim_end().len();
+                if end == start {
+                    continue;
+                }
+                chunks.push(make_chunk_from_span(
+                    start,
+                    end,
+                    kind,
+                    parent_scope.clone(),
+                    section.path.clone(),
+                    self.language.clone(),
+                    content,
+                    file_path,
+                    &line_offsets,
+                ));
+            }
+        }
+
+        enforce_token_limits(chunks, &self.config)
+    }
+}
+
+/// Lines of `content` with their starting byte offsets.
+fn lines_with_offsets(content: &str) -> impl Iterator<Item = (usize, &str)> {
+    content.split_inclusive('\n').scan(0, |offset, line| {
+        let start = *offset;
+        *offset += line.len();
+        Some((start, line))
+    })
+}
+
+/// Cut `start..end` into pieces of at most `max_chars` non-whitespace chars, at line ends.
+pub(super) fn split_at_lines(content: &str, start: usize, end: usize, max_chars: usize) -> Vec<(usize, usize)> {
+    let mut pieces = Vec::new();
+    let mut piece_start = start;
+    let mut size = 0;
+    for (offset, line) in lines_with_offsets(&content[start..end]) {
+        let line_start = start + offset;
+        let line_size = count_non_whitespace(line);
+        if size > 0 && size + line_size > max_chars {
+            pieces.push((piece_start, line_start));
+            piece_start = line_start;
+            size = 0;
+        }
+        size += line_size;
+    }
+    if piece_start < end {
+        pieces.push((piece_start, end));
+    }
+    pieces
+}
+
+/// `(level, title)` of an ATX heading line (`## Title`).
+fn atx_heading(line: &str) -> Option<(usize, String)> {
+    let indent = line.len() - line.trim_start_matches(' ').len();
+    if indent > 3 {
+        return None;
+    }
+    let rest = line.trim();
+    let level = rest.chars().take_while(|&c| c == '#').count();
+    if level == 0 || level > 6 {
+        return None;
+    }
+    let title = &rest[level..];
+    if !title.is_empty() && !title.starts_with([' ', '\t']) {
+        return None;
+    }
+    Some((level, title.trim().trim_end_matches('#').trim_end().to_string()))
+}
+
+/// One section per heading; a heading with no body of its own stays with the next one.
+fn markdown_sections(content: &str) -> Vec<Section> {
+    let mut sections = Vec::new();
+    let mut trail: Vec<(usize, String)> = Vec::new();
+    let mut path: Vec<String> = Vec::new();
+    let mut start = 0;
+    let mut has_body = false;
+    let mut in_fence = false;
+
+    for (offset, line) in lines_with_offsets(content) {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+        } else if !in_fence {
+            if let Some((level, title)) = atx_heading(line) {
+                if has_body {
+                    sections.push(Section { start, end: offset, kind: "markdown_section", path: path.clone() });
+                    start = offset;
+                }
+                trail.retain(|(l, _)| *l < level);
+                trail.push((level, title));
+                path = trail.iter().map(|(l, t)| format!("{} {}", "#".repeat(*l), t)).collect();
+                has_body = false;
+                continue;
+            }
+        }
+        if !line.trim().is_empty() {
+            has_body = true;
+        }
+    }
+    if start < content.len() {
+        sections.push(Section { start, end: content.len(), kind: "markdown_section", path });
+    }
+    sections
+}
+
+/// Key of a `key: value` / `key:` line, with quotes removed.
+fn yaml_key(line: &str) -> Option<String> {
+    let t = line.trim();
+    if t.is_empty() || t.starts_with('#') || t.starts_with('-') {
+        return None;
+    }
+    let (key, rest) = t.split_once(':')?;
+    if !(rest.is_empty() || rest.starts_with([' ', '\t'])) {
+        return None;
+    }
+    Some(key.trim().trim_matches(|c| c == '"' || c == '\'').to_string())
+}
+
+fn indent_of(line: &str) -> usize {
+    line.len() - line.trim_start_matches(' ').len()
+}
+
+fn yaml_sections(content: &str, max_chars: usize) -> Vec<Section> {
+    let mut sections = Vec::new();
+    for section in yaml_keys_at(content, 0, content.len(), 0, &[]) {
+        let text = &content[section.start..section.end];
+        if count_non_whitespace(text) <= max_chars || section.path.is_empty() {
+            sections.push(section);
+            continue;
+        }
+        // Descend one level: split a large block at its child keys.
+        let mut content_lines = lines_with_offsets(text)
+            .filter(|(_, l)| !l.trim().is_empty() && !l.trim_start().starts_with('#'));
+        content_lines.next(); // the key line itself
+        let child_indent = content_lines.next().map(|(_, l)| indent_of(l));
+        match child_indent {
+            Some(indent) if indent > 0 => {
+                sections.extend(yaml_keys_at(content, section.start, section.end, indent, &section.path))
+            }
+            _ => sections.push(section),
+        }
+    }
+    sections
+}
+
+/// Sections of `start..end` starting at each key indented by exactly `indent`.
+///
+/// Comment lines directly above a key belong to that key's section; `---`
+/// document separators close the current section.
+fn yaml_keys_at(content: &str, start: usize, end: usize, indent: usize, parent: &[String]) -> Vec<Section> {
+    let mut sections = Vec::new();
+    let mut current = Section { start, end, kind: "yaml_key", path: parent.to_vec() };
+    let mut lead: Option<usize> = None;
+
+    for (offset, line) in lines_with_offsets(&content[start..end]) {
+        let at = start + offset;
+        let t = line.trim();
+        let is_separator = indent == 0 && (t == "---" || t == "...");
+        let key = if indent_of(line) == indent { yaml_key(line) } else { None };
+
+        if is_separator || key.is_some() {
+            let boundary = if is_separator { at } else { lead.unwrap_or(at) };
+            if boundary > current.start {
+                current.end = boundary;
+                sections.push(current.clone());
+            }
+            let mut path = parent.to_vec();
+            path.extend(key);
+            current = Section { start: boundary, end, kind: "yaml_key", path };
+            lead = None;
+        } else if t.starts_with('#') && indent_of(line) <= indent {
+            lead.get_or_insert(at);
+        } else if !t.is_empty() {
+            lead = None;
+        }
+    }
+    current.end = end;
+    if current.start < end {
+        sections.push(current);
+    }
+    sections
+}
+
+/// One section per `[table]` / `[[array]]` header, with keys before the first table as the root.
+fn toml_sections(content: &str) -> Vec<Section> {
+    let mut sections = Vec::new();
+    let mut current = Section { start: 0, end: content.len(), kind: "toml_table", path: Vec::new() };
+    let mut lead: Option<usize> = None;
+
+    for (offset, line) in lines_with_offsets(content) {
+        let t = line.trim();
+        // Headers start in column 0; indented `[` lines are array values.
+        let header = if line.starts_with('[') {
+            t.strip_prefix("[[")
+                .and_then(|r| r.split_once("]]"))
+                .or_else(|| t.strip_prefix('[').and_then(|r| r.split_once(']')))
+                .map(|(name, _)| name.trim().to_string())
+        } else {
+            None
+        };
+
+        if let Some(name) = header {
+            let boundary = lead.unwrap_or(offset);
+            if boundary > current.start {
+                current.end = boundary;
+                sections.push(current.clone());
+            }
+            current = Section { start: boundary, end: content.len(), kind: "toml_table", path: vec![name] };
+            lead = None;
+        } else if t.starts_with('#') {
+            lead.get_or_insert(offset);
+        } else if !t.is_empty() {
+            lead = None;
+        }
+    }
+    if current.start < content.len() {
+        sections.push(current);
+    }
+    sections
+}
+
+/// One section per member of the root object; other documents stay whole.
+fn json_sections(content: &str) -> Vec<Section> {
+    let whole = vec![Section { start: 0, end: content.len(), kind: "json_member", path: Vec::new() }];
+    let bytes = content.as_bytes();
+    let Some(root) = bytes.iter().position(|b| !b.is_ascii_whitespace()) else {
+        return Vec::new();
+    };
+    if bytes[root] != b'{' {
+        return whole;
+    }
+
+    // (member start, key) for each key at depth 1.
+    let mut members: Vec<(usize, String)> = Vec::new();
+    let mut depth = 0usize;
+    let mut expect_key = false;
+    let mut i = root;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'"' => {
+                let begin = i;
+                i += 1;
+                while i < bytes.len() && bytes[i] != b'"' {
+                    i += if bytes[i] == b'\\' { 2 } else { 1 };
+                }
+                if depth == 1 && expect_key {
+                    let key = content.get(begin + 1..i.min(bytes.len())).unwrap_or("").to_string();
+                    // Start members at their line so indentation stays with them.
+                    let line_start = content[..begin].rfind('\n').map_or(0, |n| n + 1);
+                    let start = if content[line_start..begin].trim().is_empty() { line_start } else { begin };
+                    members.push((start, key));
+                    expect_key = false;
+                }
+            }
+            b'{' | b'[' => {
+                depth += 1;
+                expect_key = depth == 1;
+            }
+            b'}' | b']' => depth = depth.saturating_sub(1),
+            b',' if depth == 1 => expect_key = true,
+            _ => {}
+        }
+        i += 1;
+    }
+
+    if members.is_empty() {
+        return whole;
+    }
+    let mut sections = Vec::with_capacity(members.len());
+    for (idx, (start, key)) in members.iter().enumerate() {
+        let start = if idx == 0 { 0 } else { *start };
+        let end = members.get(idx + 1).map_or(content.len(), |(next, _)| *next);
+        sections.push(Section { start, end, kind: "json_member", path: vec![key.clone()] });
+    }
+    sections
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(language: Language, content: &str, max_chars: usize) -> Vec<Chunk> {
+        let config = ChunkingConfig { max_chars, ..Default::default() };
+        DocumentChunker::new(language, config).chunk(content, Path::new("doc")).unwrap()
+    }
+
+    #[test]
+    fn test_markdown_sections_follow_headings() {
+        let md = "# Guide\n\nIntro.\n\n## Install\n### From source\n\n```sh\n# not a heading\n```\n\n## Usage\n\nRun it.\n";
+        let chunks = chunk(Language::Markdown, md, 1000);
+        let paths: Vec<Vec<String>> = chunks.iter().map(|c| c.scope_path.clone()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                vec!["# Guide".to_string()],
+                vec!["# Guide".to_string(), "## Install".to_string(), "### From source".to_string()],
+                vec!["# Guide".to_string(), "## Usage".to_string()],
+            ]
+        );
+        // The body-less "## Install" heading stays with its subsection.
+        assert!(chunks[1].content.starts_with("## Install"));
+        assert_eq!(chunks[2].start_line, 12);
+    }
+
+    #[test]
+    fn test_config_key_paths() {
+        let yaml = "# server settings\nserver:\n  port: 80\n  tls:\n    cert: a.pem\ndb:\n  url: x\n";
+        let chunks = chunk(Language::Yaml, yaml, 1000);
+        assert_eq!(chunks.len(), 2);
+        assert!(chunks[0].content.starts_with("# server settings"));
+        assert_eq!(chunks[1].scope_path, vec!["db".to_string()]);
+
+        // Over the size limit, a block splits at its child keys.
+        let chunks = chunk(Language::Yaml, yaml, 12);
+        assert!(chunks.iter().any(|c| c.scope_path == vec!["server".to_string(), "tls".to_string()]));
+
+        let toml = "name = \"x\"\n\n[dependencies]\nserde = \"1\"\n\n[[bin]]\nname = \"y\"\n";
+        let paths: Vec<Vec<String>> = chunk(Language::Toml, toml, 1000).into_iter().map(|c| c.scope_path).collect();
+        assert_eq!(paths, vec![vec![], vec!["dependencies".to_string()], vec!["bin".to_string()]]);
+
+        let json = "{\n  \"name\": \"x\",\n  \"scripts\": { \"a\": \"b\" }\n}\n";
+        let paths: Vec<Vec<String>> = chunk(Language::Json, json, 1000).into_iter().map(|c| c.scope_path).collect();
+        assert_eq!(paths, vec![vec!["name".to_string()], vec!["scripts".to_string()]]);
+    }
+}
@@ -185,6 +185,9 @@ impl Chunker for GenericChunker {
                         embedding: None,
                         parent_scope: None,
                         scope_path: Vec::new(),
+                        tags: Vec::new(),
+                        doc: None,
+                        doc_embedding: None,
                     });
                 }
             }
@@ -195,7 +198,7 @@ impl Chunker for GenericChunker {
     }
 }
 
-fn compute_line_offsets(content: &str) -> Vec<usize> {
+pub(super) fn compute_line_offsets(content: &str) -> Vec<usize> {
     let mut offsets = Vec::new();
     offsets.push(0);
     for (i, b) in content.bytes().enumerate() {
@@ -213,7 +216,7 @@ fn byte_to_line(byte: usize, offsets: &[usize]) -> usize {
     }
 }
 
-fn count_non_whitespace(s: &str) -> usize {
+pub(super) fn count_non_whitespace(s: &str) -> usize {
     s.chars().filter(|c| !c.is_whitespace()).count()
 }
 
@@ -283,7 +286,7 @@ fn first_named_child_of_kind<'a>(
     None
 }
 
-fn make_chunk_from_span(
+pub(super) fn make_chunk_from_span(
     start_byte: usize,
     end_byte: usize,
     node_type: String,
@@ -314,6 +317,9 @@ fn make_chunk_from_span(
         embedding: None,
         parent_scope,
         scope_path,
+        tags: Vec::new(),
+        doc: None,
+        doc_embedding: None,
     }
 }
 
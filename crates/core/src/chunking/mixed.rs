@@ -0,0 +1,122 @@
+//! Chunking for files that embed other languages.
+//!
+//! Each sub-language found by `regions` is chunked on its own masked view of
+//! the file: by `GenericChunker` when it has a grammar, otherwise as text split
+//! at line ends. Chunks carry their sub-language and the file's line numbers.
+
+use super::documents::split_at_lines;
+use super::generic::{compute_line_offsets, count_non_whitespace, make_chunk_from_span};
+use super::languages::get_language_support;
+use super::splitter::enforce_token_limits;
+use super::{Chunker, GenericChunker};
+use crate::models::{Chunk, Language};
+use crate::regions::{languages, mask, regions, Region};
+use anyhow::Result;
+use emry_config::ChunkingConfig;
+use std::path::Path;
+
+pub struct MixedChunker {
+    language: Language,
+    config: ChunkingConfig,
+}
+
+impl MixedChunker {
+    pub fn new(language: Language, config: ChunkingConfig) -> Self {
+        Self { language, config }
+    }
+}
+
+impl Chunker for MixedChunker {
+    fn chunk(&self, content: &str, file_path: &Path) -> Result<Vec<Chunk>> {
+        let regions = regions(&self.language, content);
+        if regions.is_empty() {
+            if self.language.is_template() {
+                return Ok(Vec::new());
+            }
+            return GenericChunker::with_config(self.language, self.config.clone()).chunk(content, file_path);
+        }
+
+        let mut chunks = Vec::new();
+        for language in languages(&regions) {
+            let view = mask(content, &regions, &language);
+            if get_language_support(language).is_some() {
+                chunks.extend(GenericChunker::with_config(language, self.config.clone()).chunk(&view, file_path)?);
+            } else {
+                chunks.extend(self.text_chunks(language, content, &view, &regions, file_path)?);
+            }
+        }
+        chunks.sort_by_key(|c| (c.start_line, c.start_byte));
+        Ok(chunks)
+    }
+}
+
+impl MixedChunker {
+    /// Chunks of a sub-language without a grammar. Its regions are grouped
+    /// while only same-line code of other languages (an ERB tag in a line of
+    /// markup) separates them, and each group is split at line ends.
+    fn text_chunks(
+        &self,
+        language: Language,
+        content: &str,
+        view: &str,
+        regions: &[Region],
+        file_path: &Path,
+    ) -> Result<Vec<Chunk>> {
+        let mut groups: Vec<(usize, usize)> = Vec::new();
+        for region in regions.iter().filter(|r| r.language == language) {
+            match groups.last_mut() {
+                Some((_, end)) if !content[*end..region.start].contains('\n') => *end = region.end,
+                _ => groups.push((region.start, region.end)),
+            }
+        }
+
+        let line_offsets = compute_line_offsets(view);
+        let node_type = format!("{}_region", language.to_string().to_lowercase());
+        let mut chunks = Vec::new();
+        for (start, end) in groups {
+            let pieces = if count_non_whitespace(&view[start..end]) <= self.config.max_chars {
+                vec![(start, end)]
+            } else {
+                split_at_lines(view, start, end, self.config.max_chars)
+            };
+            for (start, end) in pieces {
+                let text = &view[start..end];
+                let start = start + (text.len() - text.trim_start().len());
+                let end = start + view[start..end].trim_end().len();
+                if end > start {
+                    chunks.push(make_chunk_from_span(
+                        start,
+                        end,
+                        node_type.clone(),
+                        None,
+                        Vec::new(),
+                        language,
+                        view,
+                        file_path,
+                        &line_offsets,
+                    ));
+                }
+            }
+        }
+        enforce_token_limits(chunks, &self.config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vue_chunks_carry_sub_language() {
+        let sfc = "<template>\n  <button @click=\"save\">Save</button>\n</template>\n\n<script lang=\"ts\">\nexport function save(): void {\n  console.log('saved');\n}\n</script>\n\n<style>\nbutton { color: red; }\n</style>\n";
+        let chunks = MixedChunker::new(Language::Vue, ChunkingConfig::default())
+            .chunk(sfc, Path::new("Button.vue"))
+            .unwrap();
+        let ts = chunks.iter().find(|c| c.language == Language::TypeScript).expect("script chunk");
+        assert!(ts.content.contains("export function save()"));
+        assert_eq!(ts.start_line, 6);
+        let css = chunks.iter().find(|c| c.language == Language::Css).expect("style chunk");
+        assert_eq!((css.content.as_str(), css.start_line), ("button { color: red; }", 12));
+        assert!(chunks.iter().any(|c| c.language == Language::Html && c.content.contains("<button")));
+    }
+}
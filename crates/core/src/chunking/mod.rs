@@ -1,16 +1,32 @@
+pub mod documents;
 pub mod generic;
+pub mod mixed;
 pub mod splitter;
 pub mod tokenizer;
 pub mod languages;
 
 pub use emry_config::{ChunkingConfig, SplitStrategy};
+pub use documents::DocumentChunker;
 pub use generic::GenericChunker;
+pub use mixed::MixedChunker;
 pub use splitter::enforce_token_limits;
 
-use crate::models::Chunk;
+use crate::models::{Chunk, Language};
 use anyhow::Result;
 use std::path::Path;
 
 pub trait Chunker {
     fn chunk(&self, content: &str, file_path: &Path) -> Result<Vec<Chunk>>;
 }
+
+/// Chunker for a file's language: syntax-aware for code, structure-aware for
+/// documents, and region by region for files that embed other languages.
+pub fn chunker_for(language: Language, config: ChunkingConfig) -> Box<dyn Chunker> {
+    if language.is_document() {
+        Box::new(DocumentChunker::new(language, config))
+    } else if language.is_template() || matches!(language, Language::JavaScript | Language::TypeScript) {
+        Box::new(MixedChunker::new(language, config))
+    } else {
+        Box::new(GenericChunker::with_config(language, config))
+    }
+}
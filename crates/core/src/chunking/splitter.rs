@@ -117,6 +117,9 @@ fn create_sub_chunk_from_text(original: &Chunk, text: &str, index: usize) -> Chu
         embedding: None,
         parent_scope: original.parent_scope.clone(),
         scope_path: original.scope_path.clone(),
+        tags: original.tags.clone(),
+        doc: original.doc.clone(),
+        doc_embedding: None,
     }
 }
 
@@ -141,6 +144,9 @@ mod tests {
             embedding: None,
             parent_scope: None,
             scope_path: Vec::new(),
+            tags: Vec::new(),
+            doc: None,
+            doc_embedding: None,
         }
     }
 
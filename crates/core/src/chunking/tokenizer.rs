@@ -1,6 +1,8 @@
-use tiktoken_rs::cl100k_base;
+use once_cell::sync::Lazy;
+use tiktoken_rs::{cl100k_base, CoreBPE};
+
+static BPE: Lazy<CoreBPE> = Lazy::new(|| cl100k_base().expect("Failed to load tokenizer"));
 
 pub fn count_tokens(text: &str) -> usize {
-    let bpe = cl100k_base().unwrap();
-    bpe.encode_with_special_tokens(text).len()
+    BPE.encode_with_special_tokens(text).len()
 }
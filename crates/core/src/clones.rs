@@ -0,0 +1,74 @@
+//! Clone groups from pairs of similar chunks.
+//!
+//! Pairs are linked transitively, so a group holds every chunk reachable
+//! through pairs at or above the threshold; its similarity is that of its
+//! weakest pair, a lower bound for any two directly linked members.
+
+use std::collections::HashMap;
+
+/// Two chunks, by index, and the cosine similarity of their embeddings.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SimilarPair {
+    pub a: usize,
+    pub b: usize,
+    pub similarity: f32,
+}
+
+/// Members of a clone group, ascending, and its weakest pair's similarity.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CloneSet {
+    pub members: Vec<usize>,
+    pub similarity: f32,
+}
+
+/// Connected components of `pairs` over chunks `0..n`, largest first.
+pub fn group_pairs(n: usize, pairs: &[SimilarPair]) -> Vec<CloneSet> {
+    let mut parent: Vec<usize> = (0..n).collect();
+    fn find(parent: &mut [usize], mut i: usize) -> usize {
+        while parent[i] != i {
+            parent[i] = parent[parent[i]];
+            i = parent[i];
+        }
+        i
+    }
+    for pair in pairs {
+        let (a, b) = (find(&mut parent, pair.a), find(&mut parent, pair.b));
+        if a != b {
+            parent[a.max(b)] = a.min(b);
+        }
+    }
+
+    let mut sets: HashMap<usize, CloneSet> = HashMap::new();
+    for pair in pairs {
+        let root = find(&mut parent, pair.a);
+        let set = sets.entry(root).or_insert(CloneSet { members: Vec::new(), similarity: 1.0 });
+        set.similarity = set.similarity.min(pair.similarity);
+    }
+    for i in 0..n {
+        let root = find(&mut parent, i);
+        if let Some(set) = sets.get_mut(&root) {
+            set.members.push(i);
+        }
+    }
+    let mut sets: Vec<CloneSet> = sets.into_values().collect();
+    sets.sort_by(|x, y| y.members.len().cmp(&x.members.len()).then_with(|| x.members.cmp(&y.members)));
+    sets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pairs_group_transitively() {
+        let pair = |a, b, similarity| SimilarPair { a, b, similarity };
+        let sets = group_pairs(7, &[pair(0, 3, 0.97), pair(3, 5, 0.92), pair(1, 4, 1.0), pair(5, 0, 0.95)]);
+        assert_eq!(
+            sets,
+            vec![
+                CloneSet { members: vec![0, 3, 5], similarity: 0.92 },
+                CloneSet { members: vec![1, 4], similarity: 1.0 },
+            ]
+        );
+    }
+}
@@ -0,0 +1,196 @@
+//! Line coverage read from test coverage reports.
+//!
+//! Two formats are understood: LCOV tracefiles (`SF:`/`DA:` records, as
+//! written by `cargo llvm-cov --lcov`, `c8`, `genhtml` inputs) and Cobertura
+//! XML (`coverage.py xml`, `cargo tarpaulin --out Xml`, JaCoCo converters).
+//! Only line hits are kept, reduced to whether each instrumented line ran;
+//! a file reported more than once has its hits summed. Paths are returned as
+//! written, along with Cobertura's `<source>` roots they may be relative to.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Instrumented lines of one file, each sorted ascending.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileCoverage {
+    /// Lines that ran at least once
+    pub covered: Vec<usize>,
+    /// Lines that never ran
+    pub uncovered: Vec<usize>,
+}
+
+impl FileCoverage {
+    fn from_hits(hits: BTreeMap<usize, u64>) -> Self {
+        let mut coverage = Self::default();
+        for (line, count) in hits {
+            if count > 0 {
+                coverage.covered.push(line);
+            } else {
+                coverage.uncovered.push(line);
+            }
+        }
+        coverage
+    }
+
+    /// Share of the instrumented lines in `start..=end` that ran, or `None`
+    /// when the report has no lines there.
+    pub fn ratio(&self, start: usize, end: usize) -> Option<f32> {
+        let count = |lines: &[usize]| lines.iter().filter(|l| (start..=end).contains(*l)).count();
+        let (covered, uncovered) = (count(&self.covered), count(&self.uncovered));
+        (covered + uncovered > 0).then(|| covered as f32 / (covered + uncovered) as f32)
+    }
+
+    /// Instrumented lines within any of `ranges` (inclusive) that never ran.
+    pub fn uncovered_in(&self, ranges: &[(usize, usize)]) -> Vec<usize> {
+        self.uncovered
+            .iter()
+            .copied()
+            .filter(|line| ranges.iter().any(|(start, end)| (*start..=*end).contains(line)))
+            .collect()
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct CoverageReport {
+    /// Directories the report's relative paths are rooted at (Cobertura only)
+    pub sources: Vec<String>,
+    /// Path as written in the report -> its lines
+    pub files: BTreeMap<String, FileCoverage>,
+}
+
+/// Parse an LCOV or Cobertura report, telling them apart by content.
+pub fn parse_report(content: &str) -> Result<CoverageReport> {
+    let report = if content.trim_start().starts_with('<') {
+        parse_cobertura(content)
+    } else {
+        parse_lcov(content)
+    };
+    if report.files.is_empty() {
+        return Err(anyhow!("no line coverage found (expected an LCOV tracefile or Cobertura XML)"));
+    }
+    Ok(report)
+}
+
+fn parse_lcov(content: &str) -> CoverageReport {
+    let mut hits: BTreeMap<String, BTreeMap<usize, u64>> = BTreeMap::new();
+    let mut current: Option<String> = None;
+    for line in content.lines() {
+        let line = line.trim();
+        if let Some(path) = line.strip_prefix("SF:") {
+            current = Some(path.to_string());
+        } else if line == "end_of_record" {
+            current = None;
+        } else if let (Some(path), Some(data)) = (&current, line.strip_prefix("DA:")) {
+            // DA:<line>,<hits>[,<checksum>]
+            let mut fields = data.split(',');
+            let (Some(Ok(number)), Some(Ok(count))) =
+                (fields.next().map(str::parse::<usize>), fields.next().map(str::parse::<u64>))
+            else {
+                continue;
+            };
+            *hits.entry(path.clone()).or_default().entry(number).or_default() += count;
+        }
+    }
+    CoverageReport { sources: Vec::new(), files: into_files(hits) }
+}
+
+fn parse_cobertura(content: &str) -> CoverageReport {
+    let mut sources = Vec::new();
+    let mut hits: BTreeMap<String, BTreeMap<usize, u64>> = BTreeMap::new();
+    let mut current: Option<String> = None;
+    let mut rest = content;
+    while let Some(open) = rest.find('<') {
+        let Some(close) = rest[open..].find('>') else { break };
+        let tag = &rest[open + 1..open + close];
+        let after = &rest[open + close + 1..];
+        match tag_name(tag) {
+            "source" => {
+                if let Some(end) = after.find("</source>") {
+                    sources.push(unescape(after[..end].trim()));
+                }
+            }
+            "class" => current = attribute(tag, "filename"),
+            "/class" => current = None,
+            "line" => {
+                let number = attribute(tag, "number").and_then(|n| n.parse::<usize>().ok());
+                let count = attribute(tag, "hits").and_then(|h| h.parse::<u64>().ok());
+                if let (Some(path), Some(number), Some(count)) = (&current, number, count) {
+                    *hits.entry(path.clone()).or_default().entry(number).or_default() += count;
+                }
+            }
+            _ => {}
+        }
+        rest = after;
+    }
+    CoverageReport { sources, files: into_files(hits) }
+}
+
+fn into_files(hits: BTreeMap<String, BTreeMap<usize, u64>>) -> BTreeMap<String, FileCoverage> {
+    hits.into_iter().map(|(path, lines)| (path, FileCoverage::from_hits(lines))).collect()
+}
+
+fn tag_name(tag: &str) -> &str {
+    tag.split(|c: char| c.is_whitespace() || (c == '/' && !tag.starts_with('/')))
+        .next()
+        .unwrap_or("")
+}
+
+/// Value of attribute `name` in the inside of a tag.
+fn attribute(tag: &str, name: &str) -> Option<String> {
+    let mut rest = tag;
+    while let Some(at) = rest.find(name) {
+        let before = rest[..at].chars().last();
+        let value = rest[at + name.len()..].trim_start().strip_prefix('=').map(str::trim_start);
+        if before.is_some_and(char::is_whitespace) {
+            if let Some(value) = value {
+                let quote = value.chars().next()?;
+                let end = value[1..].find(quote)?;
+                return Some(unescape(&value[1..1 + end]));
+            }
+        }
+        rest = &rest[at + name.len()..];
+    }
+    None
+}
+
+fn unescape(s: &str) -> String {
+    s.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parses_lcov_and_cobertura() {
+        let lcov = "TN:\nSF:src/lib.rs\nDA:1,3\nDA:2,0\nDA:5,1,abc\nend_of_record\nSF:src/lib.rs\nDA:2,4\nend_of_record\n";
+        let report = parse_report(lcov).unwrap();
+        let lib = &report.files["src/lib.rs"];
+        assert_eq!(lib.covered, vec![1, 2, 5]);
+        assert!(lib.uncovered.is_empty());
+
+        let xml = r#"<?xml version="1.0" ?>
+<coverage line-rate="0.5">
+  <sources><source>/work/app</source></sources>
+  <packages><package name="app"><classes>
+    <class name="a.py" filename="pkg/a.py" line-rate="0.5">
+      <lines><line number="1" hits="2"/><line number="3" hits="0" branch="false"/><line number="4" hits="0"/></lines>
+    </class>
+  </classes></package></packages>
+</coverage>"#;
+        let report = parse_report(xml).unwrap();
+        assert_eq!(report.sources, vec!["/work/app".to_string()]);
+        let a = &report.files["pkg/a.py"];
+        assert_eq!((a.covered.clone(), a.uncovered.clone()), (vec![1], vec![3, 4]));
+        assert_eq!(a.ratio(1, 3), Some(0.5));
+        assert_eq!(a.ratio(5, 9), None);
+        assert_eq!(a.uncovered_in(&[(2, 3), (10, 12)]), vec![3]);
+
+        assert!(parse_report("not a report").is_err());
+    }
+}
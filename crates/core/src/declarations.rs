@@ -0,0 +1,200 @@
+//! Signatures, visibility and doc comments of definitions, read from the
+//! tree-sitter node each symbol's tag resolves to.
+
+use crate::models::Language;
+use tree_sitter::Node;
+
+/// Longest signature kept, in characters; longer ones end in `…`.
+const MAX_SIGNATURE: usize = 200;
+
+/// The definition's header: its text up to the body, whitespace collapsed.
+/// Definitions without a body (declarations, aliases, bindings of a value
+/// without a function) keep their first line.
+pub(crate) fn signature(node: Node, source: &str) -> Option<String> {
+    let body = node
+        .child_by_field_name("body")
+        .or_else(|| node.child_by_field_name("value").and_then(|v| v.child_by_field_name("body")));
+    let text = match body {
+        Some(body) => source.get(node.start_byte()..body.start_byte())?,
+        None => source.get(node.start_byte()..node.end_byte())?.lines().next()?,
+    };
+    let collapsed = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let trimmed = collapsed.trim_end_matches(|c: char| c == '{' || c == ':' || c == '=' || c.is_whitespace());
+    if trimmed.is_empty() {
+        return None;
+    }
+    Some(match trimmed.char_indices().nth(MAX_SIGNATURE) {
+        Some((cut, _)) => format!("{}…", &trimmed[..cut]),
+        None => trimmed.to_string(),
+    })
+}
+
+/// Visibility as the language spells it (`pub`, `pub(crate)`, `public`,
+/// `protected`...), else as its conventions imply: Python's leading
+/// underscore, Go's capitalisation, Java's package default, module-private
+/// JavaScript and TypeScript declarations that aren't exported.
+pub(crate) fn visibility(node: Node, language: &Language, name: &str, source: &str) -> Option<String> {
+    let text = |n: Node| n.utf8_text(source.as_bytes()).ok().map(str::to_string);
+    match language {
+        Language::Rust => {
+            if let Some(modifier) = children(node).into_iter().find(|c| c.kind() == "visibility_modifier") {
+                return text(modifier);
+            }
+            // Trait items are as visible as their trait.
+            match ancestor(node, "trait_item", 2) {
+                Some(trait_item) => visibility(trait_item, language, name, source),
+                None => Some("private".to_string()),
+            }
+        }
+        Language::Python => {
+            let dunder = name.starts_with("__") && name.ends_with("__");
+            Some(if name.starts_with('_') && !dunder { "private" } else { "public" }.to_string())
+        }
+        Language::Go => Some(if name.starts_with(char::is_uppercase) { "public" } else { "private" }.to_string()),
+        Language::Java | Language::CSharp | Language::JavaScript | Language::TypeScript => {
+            let modifiers = children(node)
+                .into_iter()
+                .flat_map(|c| if c.kind().contains("modifier") { std::iter::once(c).chain(children(c)).collect() } else { vec![c] });
+            for modifier in modifiers {
+                if let Some(word) = text(modifier).filter(|t| matches!(t.as_str(), "public" | "private" | "protected" | "internal")) {
+                    return Some(word);
+                }
+            }
+            Some(
+                match language {
+                    Language::Java => "package",
+                    Language::CSharp => "private",
+                    _ if ancestor(node, "export_statement", 3).is_some() || ancestor(node, "class_body", 1).is_some() => "public",
+                    _ => "private",
+                }
+                .to_string(),
+            )
+        }
+        Language::C | Language::Cpp => {
+            let is_static = children(node).into_iter().any(|c| c.kind() == "storage_class_specifier" && text(c).as_deref() == Some("static"));
+            Some(if is_static { "private" } else { "public" }.to_string())
+        }
+        _ => None,
+    }
+}
+
+/// The definition's documentation, comment markers stripped: Python's
+/// docstring, else the comments directly above it (Rust only counts `///`
+/// and `/** */`).
+pub(crate) fn doc_comment(node: Node, language: &Language, source: &str) -> Option<String> {
+    let text = |n: Node| n.utf8_text(source.as_bytes()).ok();
+    if *language == Language::Python {
+        let first = node.child_by_field_name("body")?.named_child(0)?;
+        let string = first.named_child(0).filter(|s| first.kind() == "expression_statement" && s.kind() == "string")?;
+        let quoted = text(string)?;
+        let inner = ["\"\"\"", "'''", "\"", "'"]
+            .iter()
+            .find_map(|q| quoted.strip_prefix(q).and_then(|s| s.strip_suffix(q)))
+            .unwrap_or(quoted);
+        return clean(inner.lines().map(str::trim));
+    }
+
+    // Comments sit above the outermost node starting the declaration.
+    let mut anchor = node;
+    while let Some(parent) = anchor.parent() {
+        if !matches!(parent.kind(), "export_statement" | "lexical_declaration" | "variable_declaration" | "declaration") {
+            break;
+        }
+        anchor = parent;
+    }
+    let mut comments = Vec::new();
+    let mut row = anchor.start_position().row;
+    let mut sibling = anchor.prev_named_sibling();
+    while let Some(prev) = sibling {
+        if prev.end_position().row + 1 < row {
+            break;
+        }
+        if prev.kind().contains("comment") {
+            let comment = text(prev)?;
+            if *language == Language::Rust && !(comment.starts_with("///") || comment.starts_with("/**")) {
+                break;
+            }
+            comments.push(comment);
+        } else if !matches!(prev.kind(), "attribute_item" | "decorator" | "annotation" | "marker_annotation") {
+            break;
+        }
+        row = prev.start_position().row;
+        sibling = prev.prev_named_sibling();
+    }
+    comments.reverse();
+    clean(comments.iter().flat_map(|c| c.lines()).map(strip_comment_marker))
+}
+
+/// `line` of a comment without its marker (`///`, `//`, `/**`, `*`, `#`...) and `*/`.
+pub(crate) fn strip_comment_marker(line: &str) -> &str {
+    let line = line.trim();
+    let line = line.strip_suffix("*/").unwrap_or(line);
+    ["///", "//!", "//", "/**", "/*", "*", "#"]
+        .iter()
+        .find_map(|marker| line.strip_prefix(marker))
+        .unwrap_or(line)
+        .trim()
+}
+
+/// `lines` joined, without leading and trailing blank lines; `None` if all are blank.
+fn clean<'a>(lines: impl Iterator<Item = &'a str>) -> Option<String> {
+    let text = lines.collect::<Vec<_>>().join("\n");
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+fn children(node: Node) -> Vec<Node> {
+    let mut cursor = node.walk();
+    node.children(&mut cursor).collect()
+}
+
+/// The nearest ancestor of `kind` at most `levels` up.
+fn ancestor<'t>(node: Node<'t>, kind: &str, levels: usize) -> Option<Node<'t>> {
+    let mut curr = node;
+    for _ in 0..levels {
+        curr = curr.parent()?;
+        if curr.kind() == kind {
+            return Some(curr);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::Language;
+    use crate::tags_extractor::TagsExtractor;
+    use std::path::Path;
+
+    #[test]
+    fn test_signatures_visibility_and_docs() {
+        let rust = r#"
+/// Parses the input.
+///
+/// Fails on empty input.
+#[inline]
+pub fn parse(input: &str) -> Result<Ast, Error> {
+    todo!()
+}
+
+// not a doc comment
+fn helper() {}
+"#;
+        let mut extractor = TagsExtractor::new().unwrap();
+        let symbols = extractor.extract_symbols(rust, Path::new("lib.rs"), &Language::Rust).unwrap();
+        let parse = symbols.iter().find(|s| s.name == "parse").unwrap();
+        assert_eq!(parse.signature.as_deref(), Some("pub fn parse(input: &str) -> Result<Ast, Error>"));
+        assert_eq!(parse.visibility.as_deref(), Some("pub"));
+        assert_eq!(parse.doc_comment.as_deref(), Some("Parses the input.\n\nFails on empty input."));
+        let helper = symbols.iter().find(|s| s.name == "helper").unwrap();
+        assert_eq!(helper.visibility.as_deref(), Some("private"));
+        assert_eq!(helper.doc_comment, None);
+
+        let python = "class Store:\n    def _load(self, path: str) -> bytes:\n        \"\"\"Read the raw file.\"\"\"\n        return b''\n";
+        let symbols = extractor.extract_symbols(python, Path::new("store.py"), &Language::Python).unwrap();
+        let load = symbols.iter().find(|s| s.name == "_load").unwrap();
+        assert_eq!(load.signature.as_deref(), Some("def _load(self, path: str) -> bytes"));
+        assert_eq!(load.visibility.as_deref(), Some("private"));
+        assert_eq!(load.doc_comment.as_deref(), Some("Read the raw file."));
+    }
+}
@@ -63,3 +63,259 @@ impl DiffAnalyzer {
         Ok(affected)
     }
 }
+
+/// Parse `git diff` output into the changed line ranges of each file, on the new side.
+///
+/// Deleted files are skipped; a pure deletion inside a file is recorded as the
+/// line it happened at, so the enclosing symbol still counts as changed.
+pub fn parse_unified_diff(text: &str) -> Vec<FileDiff> {
+    let mut diffs: Vec<FileDiff> = Vec::new();
+    let mut current: Option<FileDiff> = None;
+
+    for line in text.lines() {
+        if let Some(target) = line.strip_prefix("+++ ") {
+            diffs.extend(current.take().filter(|d| !d.changed_ranges.is_empty()));
+            let target = target.split('\t').next().unwrap_or(target);
+            if target != "/dev/null" {
+                let path = target.strip_prefix("b/").unwrap_or(target);
+                current = Some(FileDiff { path: PathBuf::from(path), changed_ranges: Vec::new() });
+            }
+        } else if let Some(hunk) = line.strip_prefix("@@ ") {
+            let Some(diff) = current.as_mut() else { continue };
+            // @@ -a,b +c,d @@
+            let Some(new_side) = hunk.split_whitespace().find_map(|part| part.strip_prefix('+')) else {
+                continue;
+            };
+            let (start, count): (usize, usize) = match new_side.split_once(',') {
+                Some((start, count)) => (start.parse().unwrap_or(0), count.parse().unwrap_or(1)),
+                None => (new_side.parse().unwrap_or(0), 1),
+            };
+            let range = if count == 0 {
+                (start.max(1), start.max(1))
+            } else {
+                (start, start + count - 1)
+            };
+            diff.changed_ranges.push(range);
+        }
+    }
+    diffs.extend(current.filter(|d| !d.changed_ranges.is_empty()));
+    diffs
+}
+
+/// Whether a symbol declared on `line` is part of its file's public API,
+/// judged from the language's visibility convention: `pub` in Rust, `export`
+/// in JS/TS, `public` in Java/C#/PHP, a capitalised name in Go, no leading
+/// underscore in Python and no `static` in C/C++. Ruby has no per-line marker,
+/// so its symbols always count.
+pub fn is_public_declaration(line: &str, name: &str, language: &Language) -> bool {
+    let line = line.trim_start();
+    let has_word = |word: &str| line.split_whitespace().any(|w| w == word);
+    match language {
+        Language::Rust => {
+            line.starts_with("pub ")
+                || (line.starts_with("pub(")
+                    && !["pub(crate)", "pub(super)", "pub(self)", "pub(in "].iter().any(|p| line.starts_with(p)))
+        }
+        Language::JavaScript | Language::TypeScript => line.starts_with("export "),
+        Language::Java | Language::CSharp | Language::Php => has_word("public"),
+        Language::Go => name.chars().next().is_some_and(|c| c.is_uppercase()),
+        Language::Python => !name.starts_with('_'),
+        Language::C | Language::Cpp => !has_word("static"),
+        Language::Ruby => true,
+        _ => false,
+    }
+}
+
+/// Unified diff turning `old` into `new`, with `a/` and `b/` path prefixes so
+/// `git apply` takes it as is.
+///
+/// Lines are compared pairwise, which fits in-place edits such as renames:
+/// both texts must have the same number of lines. Returns an empty string
+/// when nothing changed.
+pub fn unified_diff(path: &str, old: &str, new: &str) -> String {
+    const CONTEXT: usize = 3;
+    let old_lines: Vec<&str> = old.split_inclusive('\n').collect();
+    let new_lines: Vec<&str> = new.split_inclusive('\n').collect();
+    debug_assert_eq!(old_lines.len(), new_lines.len(), "unified_diff needs line-aligned texts");
+    let changed: Vec<usize> = (0..old_lines.len().min(new_lines.len()))
+        .filter(|&i| old_lines[i] != new_lines[i])
+        .collect();
+    if changed.is_empty() {
+        return String::new();
+    }
+
+    let mut out = format!("--- a/{}\n+++ b/{}\n", path, path);
+    let mut first = 0;
+    while first < changed.len() {
+        // Changes closer than twice the context share a hunk.
+        let mut last = first;
+        while last + 1 < changed.len() && changed[last + 1] - changed[last] <= 2 * CONTEXT {
+            last += 1;
+        }
+        let start = changed[first].saturating_sub(CONTEXT);
+        let end = (changed[last] + CONTEXT + 1).min(old_lines.len());
+        out.push_str(&format!("@@ -{},{} +{},{} @@\n", start + 1, end - start, start + 1, end - start));
+        for i in start..end {
+            if old_lines[i] == new_lines[i] {
+                push_diff_line(&mut out, ' ', old_lines[i]);
+            } else {
+                push_diff_line(&mut out, '-', old_lines[i]);
+                push_diff_line(&mut out, '+', new_lines[i]);
+            }
+        }
+        first = last + 1;
+    }
+    out
+}
+
+/// Unified diff of a file going from `old` to `new`, either of which is
+/// `None` when the file doesn't exist, so creations and deletions come out
+/// against `/dev/null`.
+///
+/// Unlike [`unified_diff`], lines may be added and removed: they are matched
+/// by longest common subsequence, after setting aside the common prefix and
+/// suffix. Returns an empty string when nothing changed.
+pub fn file_diff(path: &str, old: Option<&str>, new: Option<&str>) -> String {
+    const CONTEXT: usize = 3;
+    if old == new {
+        return String::new();
+    }
+    let split = |text: Option<&str>| -> Vec<String> {
+        text.map_or_else(Vec::new, |t| t.split_inclusive('\n').map(str::to_string).collect())
+    };
+    let (a, b) = (split(old), split(new));
+    let prefix = a.iter().zip(&b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..].iter().rev().zip(b[prefix..].iter().rev()).take_while(|(x, y)| x == y).count();
+    let middle = crate::patch::common_lines(&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+    let matched = (0..prefix)
+        .map(|i| (i, i))
+        .chain(middle.into_iter().map(|(i, j)| (i + prefix, j + prefix)))
+        .chain((0..suffix).map(|k| (a.len() - suffix + k, b.len() - suffix + k)))
+        .chain(std::iter::once((a.len(), b.len())));
+
+    // The edit script: each line's marker and its old and new indices.
+    let mut script: Vec<(char, usize, usize)> = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    for (mi, mj) in matched {
+        script.extend((i..mi).map(|k| ('-', k, j)));
+        script.extend((j..mj).map(|k| ('+', mi, k)));
+        if mi < a.len() {
+            script.push((' ', mi, mj));
+        }
+        (i, j) = (mi + 1, mj + 1);
+    }
+    let changed: Vec<usize> = (0..script.len()).filter(|&k| script[k].0 != ' ').collect();
+
+    let mut out = format!(
+        "--- {}\n+++ {}\n",
+        old.map_or("/dev/null".to_string(), |_| format!("a/{}", path)),
+        new.map_or("/dev/null".to_string(), |_| format!("b/{}", path))
+    );
+    let mut first = 0;
+    while first < changed.len() {
+        let mut last = first;
+        while last + 1 < changed.len() && changed[last + 1] - changed[last] <= 2 * CONTEXT {
+            last += 1;
+        }
+        let start = changed[first].saturating_sub(CONTEXT);
+        let end = (changed[last] + CONTEXT + 1).min(script.len());
+        let hunk = &script[start..end];
+        let old_count = hunk.iter().filter(|(m, _, _)| *m != '+').count();
+        let new_count = hunk.iter().filter(|(m, _, _)| *m != '-').count();
+        // An empty side is numbered by the line before it.
+        let line = |index: usize, count: usize| if count == 0 { index } else { index + 1 };
+        out.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            line(hunk[0].1, old_count),
+            old_count,
+            line(hunk[0].2, new_count),
+            new_count
+        ));
+        for &(marker, oi, ni) in hunk {
+            push_diff_line(&mut out, marker, if marker == '+' { &b[ni] } else { &a[oi] });
+        }
+        first = last + 1;
+    }
+    out
+}
+
+fn push_diff_line(out: &mut String, marker: char, line: &str) {
+    out.push(marker);
+    out.push_str(line);
+    if !line.ends_with('\n') {
+        out.push_str("\n\\ No newline at end of file\n");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_unified_diff_hunks() {
+        let text = "\
+diff --git a/src/lib.rs b/src/lib.rs
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -10,2 +10,3 @@ fn parse()
+@@ -40 +41,0 @@ fn render()
+diff --git a/old.rs b/old.rs
+--- a/old.rs
++++ /dev/null
+@@ -1,5 +0,0 @@
+diff --git a/src/new.rs b/src/new.rs
+--- /dev/null
++++ b/src/new.rs
+@@ -0,0 +1 @@
+";
+        let diffs = parse_unified_diff(text);
+        assert_eq!(diffs.len(), 2);
+        assert_eq!(diffs[0].path, PathBuf::from("src/lib.rs"));
+        assert_eq!(diffs[0].changed_ranges, vec![(10, 12), (41, 41)]);
+        assert_eq!(diffs[1].path, PathBuf::from("src/new.rs"));
+        assert_eq!(diffs[1].changed_ranges, vec![(1, 1)]);
+    }
+
+    #[test]
+    fn test_public_declarations() {
+        assert!(is_public_declaration("    pub async fn open(path: &Path)", "open", &Language::Rust));
+        assert!(!is_public_declaration("pub(crate) fn helper()", "helper", &Language::Rust));
+        assert!(!is_public_declaration("fn helper()", "helper", &Language::Rust));
+        assert!(is_public_declaration("export function parse(src) {", "parse", &Language::TypeScript));
+        assert!(is_public_declaration("func Serve(addr string) error {", "Serve", &Language::Go));
+        assert!(!is_public_declaration("func serve(addr string) error {", "serve", &Language::Go));
+        assert!(!is_public_declaration("def _load(self):", "_load", &Language::Python));
+    }
+
+    #[test]
+    fn test_unified_diff_groups_nearby_changes() {
+        let old: String = (1..=20).map(|i| format!("line {}\n", i)).collect();
+        let new = old.replace("line 2\n", "LINE 2\n").replace("line 6\n", "LINE 6\n").replace("line 18\n", "LINE 18\n");
+        let diff = unified_diff("src/lib.rs", &old, &new);
+        let hunks: Vec<&str> = diff.lines().filter(|l| l.starts_with("@@")).collect();
+        assert_eq!(hunks, vec!["@@ -1,9 +1,9 @@", "@@ -15,6 +15,6 @@"]);
+        assert!(diff.starts_with("--- a/src/lib.rs\n+++ b/src/lib.rs\n"));
+        assert_eq!(parse_unified_diff(&diff)[0].changed_ranges, vec![(1, 9), (15, 20)]);
+        assert_eq!(unified_diff("src/lib.rs", &old, &old), "");
+    }
+
+    #[test]
+    fn test_file_diff_round_trips_insertions_and_deletions() {
+        use crate::patch::parse_patch;
+
+        let old: String = (1..=20).map(|i| format!("line {}\n", i)).collect();
+        let new = old
+            .replace("line 3\n", "")
+            .replace("line 10\n", "line 10\nadded 1\nadded 2\n")
+            .replace("line 20\n", "line 20");
+        let diff = file_diff("src/lib.rs", Some(&old), Some(&new));
+        let patches = parse_patch(&diff).unwrap();
+        assert_eq!(patches[0].apply(Some(&old)).content.unwrap(), new);
+
+        let created = parse_patch(&file_diff("src/new.rs", None, Some("fn main() {}\n"))).unwrap();
+        assert_eq!(created[0].apply(None).content.unwrap(), "fn main() {}\n");
+        let deleted = parse_patch(&file_diff("src/lib.rs", Some(&old), None)).unwrap();
+        assert_eq!(deleted[0].apply(Some(&old)).content, None);
+        assert_eq!(file_diff("src/lib.rs", Some(&old), Some(&old)), "");
+    }
+}
@@ -0,0 +1,138 @@
+//! Documentation of chunks.
+//!
+//! A chunk's documentation is the doc comment directly above it (`///` and
+//! `//!` lines, `/** */` blocks; attributes and decorators in between are
+//! skipped) followed by the doc comments and Python docstrings inside it,
+//! markers stripped. It is stored and embedded apart from the code, so
+//! `emry search --docs` matches prose against prose.
+
+use crate::declarations::strip_comment_marker;
+use crate::models::{Chunk, Language};
+
+/// A doc comment or docstring, by 1-based line range.
+#[derive(Debug, Clone, PartialEq)]
+struct DocBlock {
+    start_line: usize,
+    end_line: usize,
+    text: String,
+}
+
+/// Set the `doc` of each of `chunks` of a file with `content`.
+pub fn attach_docs(chunks: &mut [Chunk], content: &str, language: &Language) {
+    let lines: Vec<&str> = content.lines().collect();
+    let blocks = doc_blocks(&lines, language);
+    if blocks.is_empty() {
+        return;
+    }
+    for chunk in chunks.iter_mut() {
+        // Step over attributes and decorators to the line a leading comment would end on.
+        let mut above = chunk.start_line.saturating_sub(1);
+        while above > 0 && is_annotation(lines.get(above - 1).copied().unwrap_or("")) {
+            above -= 1;
+        }
+        let texts: Vec<&str> = blocks
+            .iter()
+            .filter(|b| b.end_line == above || (b.start_line >= chunk.start_line && b.end_line <= chunk.end_line))
+            .map(|b| b.text.as_str())
+            .collect();
+        chunk.doc = (!texts.is_empty()).then(|| texts.join("\n\n"));
+    }
+}
+
+fn is_annotation(line: &str) -> bool {
+    let line = line.trim_start();
+    line.starts_with("#[") || line.starts_with('@') || line.starts_with('[')
+}
+
+fn doc_blocks(lines: &[&str], language: &Language) -> Vec<DocBlock> {
+    let mut blocks = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i].trim_start();
+        let end = if *language == Language::Python {
+            docstring_end(lines, i)
+        } else if is_doc_line(line) {
+            let mut end = i;
+            while end + 1 < lines.len() && is_doc_line(lines[end + 1].trim_start()) {
+                end += 1;
+            }
+            Some(end)
+        } else if line.starts_with("/**") && !line.starts_with("/**/") {
+            (i..lines.len()).find(|&j| lines[j].contains("*/") && (j > i || line[3..].contains("*/")))
+        } else {
+            None
+        };
+        let Some(end) = end else {
+            i += 1;
+            continue;
+        };
+        let text = if *language == Language::Python {
+            let joined = lines[i..=end].iter().map(|l| l.trim()).collect::<Vec<_>>().join("\n");
+            let body = joined.trim_start_matches(['r', 'u', 'R', 'U']);
+            let quote = &body[..3];
+            body.trim_start_matches(quote).trim_end_matches(quote).trim().to_string()
+        } else {
+            lines[i..=end].iter().map(|l| strip_comment_marker(l)).collect::<Vec<_>>().join("\n").trim().to_string()
+        };
+        if !text.is_empty() {
+            blocks.push(DocBlock { start_line: i + 1, end_line: end + 1, text });
+        }
+        i = end + 1;
+    }
+    blocks
+}
+
+fn is_doc_line(line: &str) -> bool {
+    (line.starts_with("///") && !line.starts_with("////")) || line.starts_with("//!")
+}
+
+/// Last line of the triple-quoted string opening line `start`, if one does.
+fn docstring_end(lines: &[&str], start: usize) -> Option<usize> {
+    let line = lines[start].trim().trim_start_matches(['r', 'u', 'R', 'U']);
+    let quote = ["\"\"\"", "'''"].into_iter().find(|q| line.starts_with(q))?;
+    if line.len() >= 6 && line[3..].contains(quote) {
+        return Some(start);
+    }
+    (start + 1..lines.len()).find(|&j| lines[j].contains(quote))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn chunk(start_line: usize, end_line: usize) -> Chunk {
+        Chunk {
+            id: String::new(),
+            language: Language::Rust,
+            file_path: PathBuf::new(),
+            start_line,
+            end_line,
+            start_byte: None,
+            end_byte: None,
+            node_type: String::new(),
+            content_hash: String::new(),
+            content: String::new(),
+            embedding: None,
+            parent_scope: None,
+            scope_path: Vec::new(),
+            tags: Vec::new(),
+            doc: None,
+            doc_embedding: None,
+        }
+    }
+
+    #[test]
+    fn test_leading_and_inner_docs() {
+        let rust = "/// Opens the store.\n///\n/// Creates it when missing.\n#[must_use]\npub fn open() {\n    // not documentation\n}\n";
+        let mut chunks = vec![chunk(5, 7)];
+        attach_docs(&mut chunks, rust, &Language::Rust);
+        assert_eq!(chunks[0].doc.as_deref(), Some("Opens the store.\n\nCreates it when missing."));
+
+        let python = "def load(path):\n    \"\"\"Read the file at `path`.\n\n    Returns bytes.\n    \"\"\"\n    return open(path).read()\n\ndef other():\n    pass\n";
+        let mut chunks = vec![chunk(1, 6), chunk(8, 9)];
+        attach_docs(&mut chunks, python, &Language::Python);
+        assert_eq!(chunks[0].doc.as_deref(), Some("Read the file at `path`.\n\nReturns bytes."));
+        assert_eq!(chunks[1].doc, None);
+    }
+}
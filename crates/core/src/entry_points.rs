@@ -0,0 +1,243 @@
+//! Entry points: symbols something outside the code runs.
+//!
+//! A symbol is an entry point when it is called by the runtime, a framework,
+//! a test harness or another package rather than by code in this repository:
+//! `main`, HTTP route handlers, CLI subcommand handlers, tests and the public
+//! items of a library root (`lib.rs`, `index.ts`, `__init__.py`). Detection
+//! reads the attributes, decorators and annotations around a definition and
+//! the registration calls that name it (`app.get("/x", handler)`,
+//! `.route("/x", get(handler))`, `RunE: runServe`), so it is a heuristic:
+//! dynamic registration is missed.
+
+use crate::models::{Language, Symbol};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryPointKind {
+    Main,
+    Route,
+    Command,
+    Test,
+    LibraryRoot,
+}
+
+impl EntryPointKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EntryPointKind::Main => "main",
+            EntryPointKind::Route => "route",
+            EntryPointKind::Command => "command",
+            EntryPointKind::Test => "test",
+            EntryPointKind::LibraryRoot => "library_root",
+        }
+    }
+}
+
+/// Route decorators and attributes, matched after the leading `@` or `#[`.
+const ROUTE_MARKERS: &[&str] = &[
+    "get(", "post(", "put(", "delete(", "patch(", "route(", "websocket(", "api_view",
+    "GetMapping", "PostMapping", "PutMapping", "DeleteMapping", "PatchMapping", "RequestMapping",
+    "Get(", "Post(", "Put(", "Delete(", "Patch(", "HttpGet", "HttpPost", "HttpPut", "HttpDelete",
+];
+/// Calls that register a handler passed to them by name.
+const ROUTE_REGISTRATIONS: &[&str] = &[
+    ".get(", ".post(", ".put(", ".delete(", ".patch(", ".all(", ".route(", ".HandleFunc(", ".Handle(",
+];
+/// Directories whose `handle_*`/`cmd_*` functions serve CLI subcommands.
+const COMMAND_DIRS: &[&str] = &["commands", "command", "cmd", "cli", "subcommands"];
+
+/// Entry points among `symbols` of the file at `path`, keyed by symbol name.
+pub fn detect_entry_points(
+    content: &str,
+    path: &Path,
+    language: &Language,
+    symbols: &[Symbol],
+) -> HashMap<String, EntryPointKind> {
+    let lines: Vec<&str> = content.lines().collect();
+    let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+    let in_command_dir = path
+        .parent()
+        .into_iter()
+        .flat_map(Path::components)
+        .any(|c| COMMAND_DIRS.contains(&c.as_os_str().to_str().unwrap_or("")));
+    let registered = registered_handlers(&lines);
+
+    let mut found = HashMap::new();
+    for symbol in symbols {
+        let callable = matches!(symbol.kind.as_str(), "function" | "method");
+        let header = header(&lines, symbol);
+        let marked = |markers: &[&str]| {
+            header.iter().any(|line| {
+                let line = line.trim_start();
+                let attr = line.strip_prefix('@').or_else(|| line.strip_prefix("#[")).or_else(|| line.strip_prefix('['));
+                // `@app.get(` and `#[actix_web::get(` name the marker after a path.
+                attr.is_some_and(|a| markers.iter().any(|m| a.starts_with(m) || a.contains(&format!(".{}", m)) || a.contains(&format!("::{}", m))))
+            })
+        };
+        let name = symbol.name.as_str();
+
+        let kind = if callable && is_test(language, file_name, name, &marked) {
+            Some(EntryPointKind::Test)
+        } else if callable && name == "main" {
+            Some(EntryPointKind::Main)
+        } else if callable && (marked(ROUTE_MARKERS) || registered.routes.contains(name)) {
+            Some(EntryPointKind::Route)
+        } else if callable
+            && (marked(&["command", "group(", "click.command"])
+                || registered.commands.contains(name)
+                || (in_command_dir && (name.starts_with("handle_") || name.starts_with("cmd_"))))
+        {
+            Some(EntryPointKind::Command)
+        } else if symbol.parent_scope.is_none() && exported_from_root(language, file_name, name, &header) {
+            Some(EntryPointKind::LibraryRoot)
+        } else {
+            None
+        };
+        if let Some(kind) = kind {
+            found.entry(symbol.name.clone()).or_insert(kind);
+        }
+    }
+    found
+}
+
+/// The attribute, decorator and annotation lines directly above `symbol`
+/// and its own lines up to the one that names it.
+fn header<'a>(lines: &[&'a str], symbol: &Symbol) -> Vec<&'a str> {
+    let start = symbol.start_line.saturating_sub(1).min(lines.len());
+    let mut first = start;
+    while first > 0 && start - first < 8 {
+        let line = lines[first - 1].trim_start();
+        if line.starts_with('@') || line.starts_with("#[") || line.starts_with('[') || line.starts_with("//") || line.starts_with('*') || line.starts_with("/*") {
+            first -= 1;
+        } else {
+            break;
+        }
+    }
+    let own = lines[start..]
+        .iter()
+        .take(8)
+        .position(|l| l.contains(symbol.name.as_str()))
+        .map_or(start, |i| start + i + 1);
+    lines[first..own.min(lines.len())].to_vec()
+}
+
+#[derive(Default)]
+struct Registered<'a> {
+    routes: std::collections::HashSet<&'a str>,
+    commands: std::collections::HashSet<&'a str>,
+}
+
+/// Names handed to route registrations and Cobra `Run`/`RunE` fields.
+fn registered_handlers<'a>(lines: &[&'a str]) -> Registered<'a> {
+    let mut registered = Registered::default();
+    for line in lines {
+        let trimmed = line.trim();
+        let target = if ROUTE_REGISTRATIONS.iter().any(|r| trimmed.contains(r)) && trimmed.contains(&['"', '\'', '`'][..]) {
+            &mut registered.routes
+        } else if trimmed.starts_with("Run:") || trimmed.starts_with("RunE:") {
+            &mut registered.commands
+        } else {
+            continue;
+        };
+        // Identifiers outside string literals.
+        let mut quote = None;
+        let mut word_start = None;
+        for (i, c) in trimmed.char_indices().chain(std::iter::once((trimmed.len(), ' '))) {
+            match quote {
+                Some(q) if c == q => quote = None,
+                Some(_) => {}
+                None if c == '"' || c == '\'' || c == '`' => quote = Some(c),
+                None if c.is_alphanumeric() || c == '_' => {
+                    word_start.get_or_insert(i);
+                    continue;
+                }
+                None => {}
+            }
+            if let Some(start) = word_start.take() {
+                // Methods called on the router (`.get(`) aren't handlers.
+                if !trimmed[..start].ends_with('.') && c != '(' {
+                    target.insert(&trimmed[start..i]);
+                }
+            }
+        }
+    }
+    registered
+}
+
+fn is_test(language: &Language, file_name: &str, name: &str, marked: &dyn Fn(&[&str]) -> bool) -> bool {
+    match language {
+        Language::Rust => marked(&["test]", "tokio::test", "rstest", "test_case"]),
+        Language::Python => {
+            name.starts_with("test_") && (file_name.starts_with("test_") || file_name.ends_with("_test.py") || file_name == "tests.py")
+        }
+        Language::Go => file_name.ends_with("_test.go") && ["Test", "Benchmark", "Example", "Fuzz"].iter().any(|p| name.starts_with(p)),
+        Language::Java | Language::CSharp => marked(&["Test", "ParameterizedTest", "Fact", "Theory", "TestMethod"]),
+        _ => false,
+    }
+}
+
+/// Whether a top-level definition is public API of a library root file.
+fn exported_from_root(language: &Language, file_name: &str, name: &str, header: &[&str]) -> bool {
+    let signature = header.last().map(|l| l.trim_start()).unwrap_or("");
+    match language {
+        Language::Rust => file_name == "lib.rs" && signature.starts_with("pub "),
+        Language::JavaScript | Language::TypeScript => {
+            matches!(file_name, "index.ts" | "index.js" | "index.tsx" | "index.mjs" | "mod.ts") && signature.starts_with("export ")
+        }
+        Language::Python => file_name == "__init__.py" && !name.starts_with('_'),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn symbol(name: &str, kind: &str, line: usize) -> Symbol {
+        Symbol {
+            id: format!("{}:{}", name, line),
+            name: name.to_string(),
+            kind: kind.to_string(),
+            file_path: PathBuf::new(),
+            start_line: line,
+            end_line: line + 1,
+            fqn: name.to_string(),
+            language: Language::Rust,
+            doc_comment: None,
+            parent_scope: None,
+            signature: None,
+            visibility: None,
+        }
+    }
+
+    #[test]
+    fn test_detects_entry_points() {
+        let rust = "#[tokio::main]\nasync fn main() {}\n#[get(\"/users\")]\nasync fn list_users() {}\nfn helper() {}\nlet app = Router::new().route(\"/health\", get(health));\nasync fn health() {}\n#[test]\nfn test_helper() {}\npub fn open() {}\n";
+        let symbols: Vec<Symbol> = [("main", 2), ("list_users", 4), ("helper", 5), ("health", 7), ("test_helper", 9), ("open", 10)]
+            .iter()
+            .map(|(n, l)| symbol(n, "function", *l))
+            .collect();
+        let found = detect_entry_points(rust, Path::new("src/lib.rs"), &Language::Rust, &symbols);
+        assert_eq!(found.get("main"), Some(&EntryPointKind::Main));
+        assert_eq!(found.get("list_users"), Some(&EntryPointKind::Route));
+        assert_eq!(found.get("health"), Some(&EntryPointKind::Route));
+        assert_eq!(found.get("test_helper"), Some(&EntryPointKind::Test));
+        assert_eq!(found.get("open"), Some(&EntryPointKind::LibraryRoot));
+        assert_eq!(found.get("helper"), None);
+
+        let python = "import click\n\n@app.post(\"/items\")\ndef create_item():\n    pass\n\n@cli.command()\ndef serve():\n    pass\n\ndef _private():\n    pass\n";
+        let symbols = vec![symbol("create_item", "function", 4), symbol("serve", "function", 8), symbol("_private", "function", 11)];
+        let found = detect_entry_points(python, Path::new("app/main.py"), &Language::Python, &symbols);
+        assert_eq!(found.get("create_item"), Some(&EntryPointKind::Route));
+        assert_eq!(found.get("serve"), Some(&EntryPointKind::Command));
+        assert_eq!(found.get("_private"), None);
+
+        let handler = vec![symbol("handle_search", "function", 1)];
+        let found = detect_entry_points("pub async fn handle_search() {}\n", Path::new("cli/src/commands/search.rs"), &Language::Rust, &handler);
+        assert_eq!(found.get("handle_search"), Some(&EntryPointKind::Command));
+    }
+}
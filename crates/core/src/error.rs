@@ -0,0 +1,152 @@
+//! Cross-crate error taxonomy.
+//!
+//! Crates keep their own `thiserror` enums (`ConfigError`, `StoreError`,
+//! `LlmError`) and convert into `EmryError` at their public boundary, so
+//! callers holding an `anyhow::Error` can recover the failure class with
+//! [`ErrorKind::of`] and map it to an exit code or HTTP status.
+
+use emry_config::ConfigError;
+use std::path::PathBuf;
+
+pub type BoxError = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum EmryError {
+    #[error("Index not found at {}. Run `emry index --full` first.", path.display())]
+    IndexNotFound { path: PathBuf },
+
+    #[error("The emry daemon is serving this index. Run `emry daemon stop` first.")]
+    DaemonRunning,
+
+    #[error("Embedder unavailable: {0}")]
+    EmbedderUnavailable(#[source] BoxError),
+
+    #[error("Invalid query: {0}")]
+    InvalidQuery(String),
+
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+
+    #[error("Storage error: {0}")]
+    Storage(#[source] BoxError),
+
+    #[error("LLM request failed: {0}")]
+    Llm(#[source] BoxError),
+
+    #[error("Not found: {0}")]
+    NotFound(String),
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// Failure class of an error, independent of where it was raised.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    IndexNotFound,
+    EmbedderUnavailable,
+    InvalidQuery,
+    Config,
+    Storage,
+    Llm,
+    NotFound,
+    Io,
+    Internal,
+}
+
+impl EmryError {
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            EmryError::IndexNotFound { .. } => ErrorKind::IndexNotFound,
+            EmryError::DaemonRunning => ErrorKind::Storage,
+            EmryError::EmbedderUnavailable(_) => ErrorKind::EmbedderUnavailable,
+            EmryError::InvalidQuery(_) => ErrorKind::InvalidQuery,
+            EmryError::Config(_) => ErrorKind::Config,
+            EmryError::Storage(_) => ErrorKind::Storage,
+            EmryError::Llm(_) => ErrorKind::Llm,
+            EmryError::NotFound(_) => ErrorKind::NotFound,
+            EmryError::Io(_) => ErrorKind::Io,
+        }
+    }
+}
+
+impl ErrorKind {
+    /// Classify an `anyhow::Error` by the first typed error in its chain.
+    pub fn of(err: &anyhow::Error) -> ErrorKind {
+        for cause in err.chain() {
+            if let Some(e) = cause.downcast_ref::<EmryError>() {
+                return e.kind();
+            }
+            if cause.downcast_ref::<ConfigError>().is_some() {
+                return ErrorKind::Config;
+            }
+            if cause.downcast_ref::<std::io::Error>().is_some() {
+                return ErrorKind::Io;
+            }
+        }
+        ErrorKind::Internal
+    }
+
+    /// Stable identifier used in machine-readable error output.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ErrorKind::IndexNotFound => "INDEX_NOT_FOUND",
+            ErrorKind::EmbedderUnavailable => "EMBEDDER_UNAVAILABLE",
+            ErrorKind::InvalidQuery => "INVALID_QUERY",
+            ErrorKind::Config => "CONFIG_ERROR",
+            ErrorKind::Storage => "STORAGE_ERROR",
+            ErrorKind::Llm => "LLM_ERROR",
+            ErrorKind::NotFound => "NOT_FOUND",
+            ErrorKind::Io => "IO_ERROR",
+            ErrorKind::Internal => "INTERNAL",
+        }
+    }
+
+    /// Process exit code (0 is success, 2 is reserved for CLI usage errors).
+    ///
+    /// These values are part of the CLI contract; see the README before changing them.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            ErrorKind::Internal => 1,
+            ErrorKind::Config => 3,
+            ErrorKind::IndexNotFound => 4,
+            ErrorKind::EmbedderUnavailable => 5,
+            ErrorKind::Storage => 6,
+            ErrorKind::Llm => 7,
+            ErrorKind::NotFound => 8,
+            ErrorKind::InvalidQuery => 9,
+            ErrorKind::Io => 10,
+        }
+    }
+
+    pub fn http_status(&self) -> u16 {
+        match self {
+            ErrorKind::InvalidQuery => 400,
+            ErrorKind::IndexNotFound | ErrorKind::NotFound => 404,
+            ErrorKind::Llm => 502,
+            ErrorKind::EmbedderUnavailable => 503,
+            ErrorKind::Config | ErrorKind::Storage | ErrorKind::Io | ErrorKind::Internal => 500,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kind_survives_context() {
+        let err = anyhow::Error::from(EmryError::IndexNotFound { path: PathBuf::from(".codeindex") })
+            .context("Search failed");
+        assert_eq!(ErrorKind::of(&err), ErrorKind::IndexNotFound);
+        assert_eq!(ErrorKind::of(&err).exit_code(), 4);
+        assert_eq!(ErrorKind::of(&err).code(), "INDEX_NOT_FOUND");
+    }
+
+    #[test]
+    fn test_untyped_error_is_internal() {
+        let err = anyhow::anyhow!("boom");
+        assert_eq!(ErrorKind::of(&err), ErrorKind::Internal);
+        assert_eq!(ErrorKind::of(&err).http_status(), 500);
+    }
+}
@@ -0,0 +1,267 @@
+//! Import specifiers rewritten to repo paths using the project's own config.
+//!
+//! Edge resolution matches an import's module against file paths, which only
+//! works when the module already looks like one. Aliases don't: `@app/*` from
+//! tsconfig `paths`, `emry_core::models` for a Cargo workspace member, or
+//! `from ..util import x` in Python. `ImportResolver` rewrites these to the
+//! repo-relative path they refer to, keeping the imported name as the last
+//! segment: `@app/auth/login` -> `src/app/auth/login`,
+//! `emry_core::models::Language` -> `crates/core/src/models/Language`.
+
+use crate::models::Language;
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+
+#[derive(Debug, Clone, Default)]
+pub struct ImportResolver {
+    /// tsconfig/jsconfig `paths` as (pattern, first target relative to the repo root),
+    /// longest pattern first.
+    ts_paths: Vec<(String, String)>,
+    /// Crate name as written in paths (`emry_core`) -> its `src` directory.
+    crates: HashMap<String, PathBuf>,
+}
+
+impl ImportResolver {
+    /// Read `tsconfig.json` (or `jsconfig.json`) and `Cargo.toml` at `root`.
+    /// A missing or unreadable file leaves its kind of import unchanged.
+    pub fn load(root: &Path) -> Self {
+        let ts_paths = ["tsconfig.json", "jsconfig.json"]
+            .iter()
+            .find_map(|name| std::fs::read_to_string(root.join(name)).ok())
+            .map(|text| ts_paths(&text))
+            .unwrap_or_default();
+        Self { ts_paths, crates: cargo_crates(root) }
+    }
+
+    /// `name` with its module part rewritten to a repo path, or unchanged when no rule applies.
+    /// `importer` is the repo-relative path of the importing file.
+    pub fn normalize(&self, name: &str, language: &Language, importer: &Path) -> String {
+        let resolved = match language {
+            Language::JavaScript | Language::TypeScript => self.resolve_js(name, importer),
+            Language::Rust => self.resolve_rust(name, importer),
+            Language::Python => resolve_python(name, importer),
+            _ => None,
+        };
+        resolved.unwrap_or_else(|| name.to_string())
+    }
+
+    fn resolve_js(&self, name: &str, importer: &Path) -> Option<String> {
+        if name == "." || name.starts_with("./") || name.starts_with("../") {
+            return normalize(&importer.parent()?.join(name));
+        }
+        self.ts_paths.iter().find_map(|(pattern, target)| match pattern.strip_suffix('*') {
+            Some(prefix) => {
+                let rest = name.strip_prefix(prefix)?;
+                Some(target.replacen('*', rest, 1))
+            }
+            None => {
+                let rest = name.strip_prefix(pattern.as_str())?;
+                if !rest.is_empty() && !rest.starts_with('/') {
+                    return None;
+                }
+                let target = Path::new(target).with_extension("");
+                Some(format!("{}{}", target.to_string_lossy(), rest))
+            }
+        })
+    }
+
+    fn resolve_rust(&self, name: &str, importer: &Path) -> Option<String> {
+        // Grouped, glob and renamed uses keep their original form.
+        if name.contains(['{', '*']) || name.contains(char::is_whitespace) {
+            return None;
+        }
+        let (first, rest) = name.split_once("::")?;
+        let src = match first {
+            "crate" => self
+                .crates
+                .values()
+                .filter(|src| src.parent().map_or(true, |dir| importer.starts_with(dir)))
+                .max_by_key(|src| src.components().count())?,
+            name => self.crates.get(name)?,
+        };
+        normalize(&src.join(rest.replace("::", "/")))
+    }
+}
+
+/// `from .mod import x` is recorded as `.mod.x` and `from . import x` as `..x`,
+/// so a single name after the dots means one of them belongs to the `from` part.
+fn resolve_python(name: &str, importer: &Path) -> Option<String> {
+    let dots = name.len() - name.trim_start_matches('.').len();
+    let rest = &name[dots..];
+    if dots == 0 || rest.is_empty() {
+        return None;
+    }
+    let levels = if rest.contains('.') { dots } else { dots - 1 };
+    let mut base = importer.parent()?;
+    for _ in 1..levels {
+        base = base.parent()?;
+    }
+    normalize(&base.join(rest.replace('.', "/")))
+}
+
+/// `paths` of a tsconfig, resolved against its `baseUrl`.
+fn ts_paths(text: &str) -> Vec<(String, String)> {
+    let Ok(config) = serde_json::from_str::<serde_json::Value>(&strip_jsonc(text)) else {
+        return Vec::new();
+    };
+    let options = &config["compilerOptions"];
+    let base = Path::new(options["baseUrl"].as_str().unwrap_or("."));
+    let mut paths: Vec<(String, String)> = options["paths"]
+        .as_object()
+        .into_iter()
+        .flatten()
+        .filter_map(|(pattern, targets)| {
+            let target = targets.as_array()?.first()?.as_str()?;
+            // Only a trailing wildcard is supported, which covers nearly every config.
+            if pattern.find('*').is_some_and(|i| i != pattern.len() - 1) {
+                return None;
+            }
+            Some((pattern.clone(), normalize(&base.join(target))?))
+        })
+        .collect();
+    paths.sort_by_key(|(pattern, _)| std::cmp::Reverse(pattern.len()));
+    paths
+}
+
+/// tsconfig allows comments and trailing commas; drop both outside strings.
+fn strip_jsonc(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    let mut in_string = false;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if in_string {
+            out.push(c);
+            if c == '\\' {
+                if let Some(&next) = chars.get(i + 1) {
+                    out.push(next);
+                    i += 1;
+                }
+            } else if c == '"' {
+                in_string = false;
+            }
+        } else if c == '/' && chars.get(i + 1) == Some(&'/') {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+            continue;
+        } else if c == '/' && chars.get(i + 1) == Some(&'*') {
+            i += 2;
+            while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                i += 1;
+            }
+            i += 2;
+            continue;
+        } else if c == ',' && chars[i + 1..].iter().find(|c| !c.is_whitespace()).is_some_and(|c| *c == '}' || *c == ']') {
+            // trailing comma
+        } else {
+            in_string = c == '"';
+            out.push(c);
+        }
+        i += 1;
+    }
+    out
+}
+
+/// Crates of the root package and workspace members, keyed by their name in paths.
+fn cargo_crates(root: &Path) -> HashMap<String, PathBuf> {
+    let mut crates = HashMap::new();
+    let Some(manifest) = read_manifest(&root.join("Cargo.toml")) else {
+        return crates;
+    };
+
+    let mut members = vec![PathBuf::new()];
+    let patterns = manifest.get("workspace").and_then(|w| w.get("members")).and_then(|m| m.as_array());
+    for pattern in patterns.into_iter().flatten().filter_map(|p| p.as_str()) {
+        match pattern.strip_suffix("/*") {
+            Some(dir) => {
+                let Ok(entries) = std::fs::read_dir(root.join(dir)) else {
+                    continue;
+                };
+                let mut dirs: Vec<PathBuf> = entries
+                    .flatten()
+                    .filter(|e| e.path().join("Cargo.toml").is_file())
+                    .map(|e| Path::new(dir).join(e.file_name()))
+                    .collect();
+                dirs.sort();
+                members.extend(dirs);
+            }
+            None => members.push(PathBuf::from(pattern)),
+        }
+    }
+
+    for member in members {
+        let Some(manifest) = read_manifest(&root.join(&member).join("Cargo.toml")) else {
+            continue;
+        };
+        let name = manifest
+            .get("lib")
+            .and_then(|l| l.get("name"))
+            .or_else(|| manifest.get("package").and_then(|p| p.get("name")))
+            .and_then(|n| n.as_str());
+        if let Some(name) = name {
+            crates.insert(name.replace('-', "_"), member.join("src"));
+        }
+    }
+    crates
+}
+
+fn read_manifest(path: &Path) -> Option<toml::Value> {
+    toml::from_str(&std::fs::read_to_string(path).ok()?).ok()
+}
+
+/// Repo-relative path with `.` and `..` resolved lexically; `None` if it leaves the repo.
+fn normalize(path: &Path) -> Option<String> {
+    let mut parts: Vec<String> = Vec::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                parts.pop()?;
+            }
+            Component::Normal(part) => parts.push(part.to_string_lossy().into_owned()),
+            Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+    Some(parts.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rewrites_aliases_and_relative_imports() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::write(
+            root.join("tsconfig.json"),
+            r#"{
+                // path aliases
+                "compilerOptions": { "baseUrl": ".", "paths": { "@app/*": ["src/app/*"], "@config": ["src/config/index.ts"], } }
+            }"#,
+        )
+        .unwrap();
+        std::fs::write(root.join("Cargo.toml"), "[workspace]\nmembers = [\"crates/*\"]\n").unwrap();
+        std::fs::create_dir_all(root.join("crates/core")).unwrap();
+        std::fs::write(root.join("crates/core/Cargo.toml"), "[package]\nname = \"emry-core\"\n").unwrap();
+
+        let resolver = ImportResolver::load(root);
+        let ts = |name: &str| resolver.normalize(name, &Language::TypeScript, Path::new("src/pages/home.ts"));
+        assert_eq!(ts("@app/auth/login"), "src/app/auth/login");
+        assert_eq!(ts("@config/loadConfig"), "src/config/index/loadConfig");
+        assert_eq!(ts("../lib/http/get"), "src/lib/http/get");
+        assert_eq!(ts("react/useState"), "react/useState");
+
+        let rust = |name: &str| resolver.normalize(name, &Language::Rust, Path::new("crates/core/src/diff.rs"));
+        assert_eq!(rust("emry_core::models::Language"), "crates/core/src/models/Language");
+        assert_eq!(rust("crate::models::Language"), "crates/core/src/models/Language");
+        assert_eq!(rust("std::fs::read"), "std::fs::read");
+
+        let py = |name: &str| resolver.normalize(name, &Language::Python, Path::new("pkg/api/views.py"));
+        assert_eq!(py(".models.User"), "pkg/api/models/User");
+        assert_eq!(py("..utils"), "pkg/api/utils");
+        assert_eq!(py("..core.db.connect"), "pkg/core/db/connect");
+    }
+}
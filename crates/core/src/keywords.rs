@@ -0,0 +1,255 @@
+//! Salient keywords per chunk, stored as tags at index time.
+//!
+//! Terms are the parts of identifiers (`parseHttpRequest` -> `parse`, `http`,
+//! `request`) and the words of comments, lowercased, with stop words and
+//! language keywords removed. Each chunk's terms are weighted by TF-IDF using
+//! the chunks of the same file as the document set, so tags favour what sets
+//! a chunk apart from its neighbours rather than what the whole file is about.
+//!
+//! The same terms, plus whole compound identifiers, make up each chunk's
+//! sparse term-weight vector; search weighs them by corpus-wide rarity.
+//! Chinese, Japanese and Korean text has no spaces to split on, so its
+//! overlapping character bigrams join the sparse terms as well.
+
+use crate::models::Chunk;
+use std::collections::HashMap;
+
+/// Tags kept per chunk.
+pub const MAX_TAGS: usize = 6;
+const MIN_TERM_LEN: usize = 3;
+
+const STOP_WORDS: &[&str] = &[
+    // English
+    "the", "and", "for", "with", "this", "that", "from", "are", "not", "but", "you", "all", "any",
+    "can", "has", "have", "was", "will", "into", "its", "our", "out", "one", "two", "also", "than",
+    "then", "when", "where", "which", "while", "each", "only", "other", "such", "should", "would",
+    "could", "may", "must", "about", "over", "under", "more", "most", "just", "like", "here",
+    "there", "these", "those", "they", "them", "what", "who", "how", "why", "does", "did", "been",
+    "being", "were", "per", "via", "see", "todo", "fixme", "note",
+    // Keywords and ubiquitous identifiers across the indexed languages
+    "let", "mut", "const", "pub", "crate", "impl", "self", "super", "struct", "enum", "trait",
+    "mod", "use", "match", "return", "else", "elif", "loop", "break", "continue", "function",
+    "func", "var", "def", "class", "import", "package", "new", "true", "false", "null", "nil",
+    "none", "some", "string", "str", "int", "usize", "isize", "u32", "u64", "i32", "i64", "f32",
+    "f64", "bool", "void", "char", "static", "public", "private", "protected", "async", "await",
+    "try", "catch", "throw", "throws", "type", "dyn", "ref", "box", "vec", "option",
+    "result", "clone", "unwrap", "iter", "len", "get", "set", "args", "arg", "val", "value",
+    "err", "foreach", "extends", "implements", "interface", "export", "default", "lambda",
+    "yield", "pass", "raise", "except", "finally",
+    "namespace", "using", "include", "define", "ifdef", "endif", "typedef", "sizeof", "auto",
+];
+
+/// Identifier-like tokens of `text`: runs of ASCII alphanumerics and underscores.
+fn identifiers(text: &str) -> impl Iterator<Item = &str> {
+    text.split(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .filter(|t| !t.is_empty())
+}
+
+fn is_term(part: &str) -> bool {
+    part.len() >= MIN_TERM_LEN && !part.chars().all(|c| c.is_ascii_digit()) && !STOP_WORDS.contains(&part)
+}
+
+/// Terms of `text` in order of appearance, with repeats.
+pub fn extract_terms(text: &str) -> Vec<String> {
+    let mut terms = Vec::new();
+    for token in identifiers(text) {
+        for part in split_identifier(token) {
+            let part = part.to_ascii_lowercase();
+            if is_term(&part) {
+                terms.push(part);
+            }
+        }
+    }
+    terms
+}
+
+/// Whether `c` belongs to a script written without spaces between words:
+/// Han ideographs, hiragana, katakana and Hangul.
+pub fn is_cjk(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x3040..=0x30FF | 0x3400..=0x4DBF | 0x4E00..=0x9FFF | 0xF900..=0xFAFF
+            | 0x1100..=0x11FF | 0x3130..=0x318F | 0xAC00..=0xD7AF | 0x20000..=0x2A6DF
+    )
+}
+
+/// Overlapping character bigrams of each run of CJK characters in `text`,
+/// with repeats; a run of one character yields that character.
+pub fn cjk_bigrams(text: &str) -> Vec<String> {
+    let mut terms = Vec::new();
+    let mut run: Vec<char> = Vec::new();
+    for c in text.chars().chain(std::iter::once(' ')) {
+        if is_cjk(c) {
+            run.push(c);
+            continue;
+        }
+        match run.len() {
+            0 => {}
+            1 => terms.push(run[0].to_string()),
+            _ => terms.extend(run.windows(2).map(|pair| pair.iter().collect::<String>())),
+        }
+        run.clear();
+    }
+    terms
+}
+
+/// Terms for the sparse index: the parts `extract_terms` yields plus each
+/// compound identifier whole (`parse_http_request`), since a rare identifier
+/// is usually only rare as a whole, and the bigrams of any CJK text.
+pub fn sparse_terms(text: &str) -> Vec<String> {
+    let mut terms = Vec::new();
+    for token in identifiers(text) {
+        let parts = split_identifier(token);
+        if parts.len() > 1 {
+            let whole = token.trim_matches('_').to_ascii_lowercase();
+            if is_term(&whole) {
+                terms.push(whole);
+            }
+        }
+        for part in parts {
+            let part = part.to_ascii_lowercase();
+            if is_term(&part) {
+                terms.push(part);
+            }
+        }
+    }
+    terms.extend(cjk_bigrams(text));
+    terms
+}
+
+/// Term weights of `text` for the sparse index, sorted by term: saturated
+/// term frequency, L2-normalised so long chunks don't win on length alone.
+pub fn sparse_vector(text: &str) -> Vec<(String, f32)> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for term in sparse_terms(text) {
+        *counts.entry(term).or_insert(0) += 1;
+    }
+    let mut weights: Vec<(String, f32)> = counts
+        .into_iter()
+        .map(|(term, tf)| (term, (tf as f32).ln_1p()))
+        .collect();
+    let norm = weights.iter().map(|(_, w)| w * w).sum::<f32>().sqrt();
+    for (_, w) in weights.iter_mut() {
+        *w /= norm;
+    }
+    weights.sort_by(|a, b| a.0.cmp(&b.0));
+    weights
+}
+
+/// Split on underscores, lower-to-upper case changes, the end of an acronym
+/// (`HTTPRequest` -> `HTTP`, `Request`) and letter/digit boundaries.
+fn split_identifier(ident: &str) -> Vec<&str> {
+    let chars: Vec<(usize, char)> = ident.char_indices().collect();
+    let mut parts = Vec::new();
+    let mut start = 0;
+    for (i, &(pos, c)) in chars.iter().enumerate() {
+        if c == '_' {
+            if pos > start {
+                parts.push(&ident[start..pos]);
+            }
+            start = pos + 1;
+            continue;
+        }
+        let Some(&(_, prev)) = i.checked_sub(1).map(|p| &chars[p]) else {
+            continue;
+        };
+        let next = chars.get(i + 1).map(|&(_, n)| n);
+        let boundary = (prev.is_ascii_lowercase() && c.is_ascii_uppercase())
+            || (prev.is_ascii_uppercase() && c.is_ascii_uppercase() && next.is_some_and(|n| n.is_ascii_lowercase()))
+            || prev.is_ascii_digit() != c.is_ascii_digit();
+        if boundary && pos > start {
+            parts.push(&ident[start..pos]);
+            start = pos;
+        }
+    }
+    if start < ident.len() {
+        parts.push(&ident[start..]);
+    }
+    parts
+}
+
+/// Top `max` keywords for each text, treating the texts as one document set.
+pub fn chunk_keywords(texts: &[&str], max: usize) -> Vec<Vec<String>> {
+    let term_counts: Vec<HashMap<String, usize>> = texts
+        .iter()
+        .map(|text| {
+            let mut counts = HashMap::new();
+            for term in extract_terms(text) {
+                *counts.entry(term).or_insert(0) += 1;
+            }
+            counts
+        })
+        .collect();
+
+    let mut doc_freq: HashMap<&str, usize> = HashMap::new();
+    for counts in &term_counts {
+        for term in counts.keys() {
+            *doc_freq.entry(term.as_str()).or_insert(0) += 1;
+        }
+    }
+
+    let n = texts.len() as f32;
+    term_counts
+        .iter()
+        .map(|counts| {
+            let mut scored: Vec<(&str, f32)> = counts
+                .iter()
+                .map(|(term, &tf)| {
+                    let idf = ((n + 1.0) / (doc_freq[term.as_str()] as f32 + 1.0)).ln() + 1.0;
+                    (term.as_str(), (tf as f32).ln_1p() * idf)
+                })
+                .collect();
+            scored.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+            scored.into_iter().take(max).map(|(term, _)| term.to_string()).collect()
+        })
+        .collect()
+}
+
+/// Fill in `tags` for the chunks of one file.
+pub fn tag_chunks(chunks: &mut [Chunk]) {
+    let texts: Vec<&str> = chunks.iter().map(|c| c.content.as_str()).collect();
+    let tags = chunk_keywords(&texts, MAX_TAGS);
+    for (chunk, tags) in chunks.iter_mut().zip(tags) {
+        chunk.tags = tags;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cjk_bigrams() {
+        assert_eq!(cjk_bigrams("// 解析请求 for 用户"), vec!["解析", "析请", "请求", "用户"]);
+        assert_eq!(cjk_bigrams("x = 値; 한국어"), vec!["値", "한국", "국어"]);
+        assert!(cjk_bigrams("plain ascii").is_empty());
+        assert!(sparse_terms("fn parse() {} // 解析").contains(&"解析".to_string()));
+    }
+
+    #[test]
+    fn test_split_identifiers_and_drop_stop_words() {
+        assert_eq!(
+            extract_terms("fn parseHTTPRequest(raw_body: &str) // retry the oauth2 token"),
+            vec!["parse", "http", "request", "raw", "body", "retry", "oauth", "token"]
+        );
+    }
+
+    #[test]
+    fn test_keywords_prefer_terms_distinct_to_a_chunk() {
+        let texts = ["load_config(cache_entry)", "save_config(flush_writer)"];
+        let tags = chunk_keywords(&texts, 3);
+        assert_eq!(tags[0], vec!["cache", "entry", "load"]);
+        assert_eq!(tags[1], vec!["flush", "save", "writer"]);
+    }
+
+    #[test]
+    fn test_sparse_vector_keeps_compound_identifiers() {
+        let vector = sparse_vector("let req = parse_http_request(raw); parse_http_request(body)");
+        let terms: Vec<&str> = vector.iter().map(|(t, _)| t.as_str()).collect();
+        assert_eq!(terms, vec!["body", "http", "parse", "parse_http_request", "raw", "req", "request"]);
+        let norm: f32 = vector.iter().map(|(_, w)| w * w).sum();
+        assert!((norm - 1.0).abs() < 1e-5);
+        let weight = |term: &str| vector.iter().find(|(t, _)| t == term).unwrap().1;
+        assert!(weight("parse_http_request") > weight("raw"));
+    }
+}
@@ -1,10 +1,28 @@
 pub mod chunking;
+pub mod clones;
+pub mod coverage;
+pub mod declarations;
+pub mod doc_comments;
+pub mod entry_points;
+pub mod error;
+pub mod import_paths;
+pub mod keywords;
 
 pub mod models;
+pub mod owners;
+pub mod pagerank;
+pub mod quantize;
+pub mod patch;
+pub mod regions;
 pub mod relations;
+pub mod rename;
+pub mod rust_types;
 pub mod scanner;
+pub mod scope;
+pub mod supertypes;
 pub mod symbols;
 pub mod tags_extractor;
 pub mod traits;
 pub mod map;
 pub mod diff;
+pub mod summary;
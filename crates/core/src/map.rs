@@ -1,9 +1,34 @@
+use crate::chunking::tokenizer::count_tokens;
 use crate::models::Language;
 use crate::tags_extractor::TagsExtractor;
 use anyhow::Result;
 use globset::{Glob, GlobSetBuilder};
 use ignore::WalkBuilder;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+/// One directory or source file of the codebase map.
+#[derive(Debug, Clone)]
+pub struct MapEntry {
+    /// Path relative to the map's root
+    pub path: PathBuf,
+    pub is_dir: bool,
+    /// Up to five top-level classes, functions, interfaces and structs of a file
+    pub symbols: Vec<String>,
+    /// Entry points of a file as `name (kind)`; left empty here and filled
+    /// from the index by callers that have one
+    pub entry_points: Vec<String>,
+}
+
+/// An indexed symbol and its PageRank over the call and import graph.
+#[derive(Debug, Clone)]
+pub struct RankedSymbol {
+    /// Path of the defining file relative to the map's root
+    pub path: PathBuf,
+    pub name: String,
+    pub kind: String,
+    pub line: Option<usize>,
+    pub rank: f64,
+}
 
 /// Generates a high-level map of the codebase.
 /// 
@@ -12,11 +37,79 @@ use std::path::Path;
 /// 
 /// It respects .gitignore files and the provided exclude patterns.
 pub fn generate_codebase_map(root_path: &Path, max_depth: usize, exclude_patterns: &[String]) -> Result<String> {
-    let mut map = String::new();
-    let mut extractor = TagsExtractor::new()?;
+    Ok(render_codebase_map(root_path, &codebase_entries(root_path, max_depth, exclude_patterns)?))
+}
 
+/// The outline `generate_codebase_map` prints for `entries`.
+pub fn render_codebase_map(root_path: &Path, entries: &[MapEntry]) -> String {
+    let mut map = String::new();
     map.push_str(&format!("# Codebase Map for {}\n\n", root_path.display()));
 
+    for entry in entries {
+        let depth = entry.path.components().count();
+        let indent = "  ".repeat(depth.saturating_sub(1));
+        let name = entry.path.file_name().unwrap_or_default().to_string_lossy();
+
+        if entry.is_dir {
+            map.push_str(&format!("{}- {}/\n", indent, name));
+        } else {
+            map.push_str(&format!("{}- {}\n", indent, name));
+            if !entry.symbols.is_empty() {
+                map.push_str(&format!("{}  (Symbols: {})\n", indent, entry.symbols.join(", ")));
+            }
+            if !entry.entry_points.is_empty() {
+                map.push_str(&format!("{}  (Entry points: {})\n", indent, entry.entry_points.join(", ")));
+            }
+        }
+    }
+
+    map
+}
+
+/// The highest-ranked prefix of `symbols` (sorted by rank, descending) that
+/// fits in `max_tokens`, grouped by file: files in the order of their best
+/// symbol, and each file's symbols by rank.
+pub fn render_ranked_map(root_path: &Path, symbols: &[RankedSymbol], max_tokens: usize) -> String {
+    let (mut low, mut high) = (0, symbols.len());
+    while low < high {
+        let mid = (low + high + 1) / 2;
+        if count_tokens(&ranked_listing(root_path, &symbols[..mid])) <= max_tokens {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    ranked_listing(root_path, &symbols[..low])
+}
+
+fn ranked_listing(root_path: &Path, symbols: &[RankedSymbol]) -> String {
+    let mut files: Vec<(&Path, Vec<&RankedSymbol>)> = Vec::new();
+    for symbol in symbols {
+        match files.iter_mut().find(|(path, _)| *path == symbol.path) {
+            Some((_, listed)) => listed.push(symbol),
+            None => files.push((symbol.path.as_path(), vec![symbol])),
+        }
+    }
+
+    let mut map = format!("# Repository Map for {}\n\n", root_path.display());
+    for (path, listed) in files {
+        map.push_str(&format!("{}:\n", path.display()));
+        for symbol in listed {
+            match symbol.line {
+                Some(line) => map.push_str(&format!("  {} ({} L{})\n", symbol.name, symbol.kind, line)),
+                None => map.push_str(&format!("  {} ({})\n", symbol.name, symbol.kind)),
+            }
+        }
+    }
+    map
+}
+
+/// The directories and source files `generate_codebase_map` outlines, in
+/// walk order (parents before their children, siblings by name).
+pub fn codebase_entries(root_path: &Path, max_depth: usize, exclude_patterns: &[String]) -> Result<Vec<MapEntry>> {
+    let mut entries = Vec::new();
+    let mut extractor = TagsExtractor::new()?;
+
     let mut builder = GlobSetBuilder::new();
     for pat in exclude_patterns {
         if let Ok(glob) = Glob::new(pat) {
@@ -56,29 +149,22 @@ pub fn generate_codebase_map(root_path: &Path, max_depth: usize, exclude_pattern
                     continue;
                 }
 
-                let depth = relative_path.components().count();
-                let indent = "  ".repeat(depth.saturating_sub(1));
-
                 if entry.file_type().map_or(false, |ft| ft.is_dir()) {
-                    map.push_str(&format!("{}- {}/\n", indent, relative_path.file_name().unwrap_or_default().to_string_lossy()));
+                    entries.push(MapEntry { path: relative_path.to_path_buf(), is_dir: true, symbols: Vec::new(), entry_points: Vec::new() });
                 } else {
                     let language = Language::from_path(path);
                     if language != Language::Unknown {
-                        map.push_str(&format!("{}- {}\n", indent, relative_path.file_name().unwrap_or_default().to_string_lossy()));
-                        
+                        let mut symbols = Vec::new();
                         if let Ok(content) = std::fs::read_to_string(path) {
-                            if let Ok(symbols) = extractor.extract_symbols(&content, path, &language) {
-                                let top_level_symbols: Vec<String> = symbols.into_iter()
+                            if let Ok(extracted) = extractor.extract_symbols(&content, path, &language) {
+                                symbols = extracted.into_iter()
                                     .filter(|s| s.kind == "class" || s.kind == "function" || s.kind == "interface" || s.kind == "struct")
                                     .map(|s| s.name)
                                     .take(5)
                                     .collect();
-                                
-                                if !top_level_symbols.is_empty() {
-                                    map.push_str(&format!("{}  (Symbols: {})\n", indent, top_level_symbols.join(", ")));
-                                }
                             }
                         }
+                        entries.push(MapEntry { path: relative_path.to_path_buf(), is_dir: false, symbols, entry_points: Vec::new() });
                     }
                 }
             }
@@ -86,7 +172,7 @@ pub fn generate_codebase_map(root_path: &Path, max_depth: usize, exclude_pattern
         }
     }
 
-    Ok(map)
+    Ok(entries)
 }
 
 #[cfg(test)]
@@ -124,4 +210,33 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_ranked_map_keeps_top_symbols_within_budget() {
+        let symbol = |path: &str, name: &str, rank| RankedSymbol {
+            path: PathBuf::from(path),
+            name: name.to_string(),
+            kind: "function".to_string(),
+            line: Some(1),
+            rank,
+        };
+        let symbols = vec![
+            symbol("src/store.rs", "open_store", 0.4),
+            symbol("src/main.rs", "run", 0.3),
+            symbol("src/store.rs", "close_store", 0.2),
+            symbol("src/util.rs", "rarely_used_helper", 0.1),
+        ];
+        let root = Path::new("/repo");
+
+        let full = render_ranked_map(root, &symbols, 10_000);
+        let store = full.find("src/store.rs:").unwrap();
+        assert!(store < full.find("src/main.rs:").unwrap());
+        assert!(full.find("close_store").unwrap() < full.find("src/main.rs:").unwrap());
+
+        let budget = count_tokens(&ranked_listing(root, &symbols[..3]));
+        let fitted = render_ranked_map(root, &symbols, budget);
+        assert!(count_tokens(&fitted) <= budget);
+        assert!(fitted.contains("close_store"));
+        assert!(!fitted.contains("rarely_used_helper"));
+    }
 }
@@ -14,6 +14,18 @@ pub enum Language {
     Ruby,
     Php,
     CSharp,
+    Markdown,
+    Yaml,
+    Toml,
+    Json,
+    Vue,
+    Svelte,
+    Erb,
+    Jinja,
+    /// Markup around the code of a template file (see `regions`).
+    Html,
+    /// Stylesheets embedded in components and CSS-in-JS (see `regions`).
+    Css,
     Unknown,
 }
 
@@ -31,6 +43,14 @@ impl Language {
             "rb" => Language::Ruby,
             "php" => Language::Php,
             "cs" => Language::CSharp,
+            "md" | "markdown" => Language::Markdown,
+            "yaml" | "yml" => Language::Yaml,
+            "toml" => Language::Toml,
+            "json" => Language::Json,
+            "vue" => Language::Vue,
+            "svelte" => Language::Svelte,
+            "erb" => Language::Erb,
+            "j2" | "jinja" | "jinja2" => Language::Jinja,
             _ => Language::Unknown,
         }
     }
@@ -48,10 +68,30 @@ impl Language {
             "ruby" => Language::Ruby,
             "php" => Language::Php,
             "csharp" => Language::CSharp,
+            "markdown" => Language::Markdown,
+            "yaml" => Language::Yaml,
+            "toml" => Language::Toml,
+            "json" => Language::Json,
+            "vue" => Language::Vue,
+            "svelte" => Language::Svelte,
+            "erb" => Language::Erb,
+            "jinja" => Language::Jinja,
+            "html" => Language::Html,
+            "css" => Language::Css,
             _ => Language::Unknown,
         }
     }
 
+    /// Prose and config formats, indexed only when `documents.enabled` is set.
+    pub fn is_document(&self) -> bool {
+        matches!(self, Language::Markdown | Language::Yaml | Language::Toml | Language::Json)
+    }
+
+    /// Files made of regions in other languages, analyzed region by region (see `regions`).
+    pub fn is_template(&self) -> bool {
+        matches!(self, Language::Vue | Language::Svelte | Language::Erb | Language::Jinja)
+    }
+
     pub fn from_path(path: &std::path::Path) -> Self {
         path.extension()
             .and_then(|e| e.to_str())
@@ -82,6 +122,14 @@ pub struct Chunk {
     pub embedding: Option<Vec<f32>>,
     pub parent_scope: Option<String>,
     pub scope_path: Vec<String>,
+    /// Salient keywords, filled in at index time (see `keywords`).
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Doc comments above and within the chunk, filled in at index time (see `doc_comments`).
+    #[serde(default)]
+    pub doc: Option<String>,
+    #[serde(default)]
+    pub doc_embedding: Option<Vec<f32>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -96,6 +144,12 @@ pub struct Symbol {
     pub language: Language,
     pub doc_comment: Option<String>,
     pub parent_scope: Option<String>,
+    /// Declaration up to the body, e.g. `pub fn parse(input: &str) -> Ast`
+    #[serde(default)]
+    pub signature: Option<String>,
+    /// `pub`, `public`, `private`, `package`... as written or implied
+    #[serde(default)]
+    pub visibility: Option<String>,
 }
 
 
@@ -109,15 +163,267 @@ pub struct ScoredChunk {
     pub graph_distance: Option<usize>,
     pub graph_path: Option<Vec<String>>,
     pub symbol_boost: Option<f32>,
+    #[serde(default)]
+    pub rerank_score: Option<f32>,
+    /// How each ranking stage scored the chunk; only recorded when explaining.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ranking: Vec<RankingStep>,
     pub chunk: crate::models::Chunk,
 }
 
+/// One ranking stage's effect on a hit, recorded by `emry search --explain-ranking`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "stage", rename_all = "snake_case")]
+pub enum RankingStep {
+    /// A source returned the chunk at `rank` (1-based) with its own `score`.
+    Retrieved {
+        source: String,
+        rank: usize,
+        score: f32,
+        /// Query terms that occur in the chunk (lexical sources).
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        matched_terms: Vec<String>,
+        /// Cosine distance between query and chunk embeddings (vector source).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        distance: Option<f32>,
+    },
+    /// Fusion (`ranking.fusion`) credited the chunk `contribution` for `source`.
+    Fused { source: String, contribution: f32 },
+    /// The query's intent matched the chunk, multiplying its score by `factor`.
+    Intent { intent: String, reasons: Vec<String>, factor: f32 },
+    /// The chunk's symbol `to` is `hops` call/import edges from top-hit symbol `from`.
+    Graph { from: String, to: String, hops: usize, boost: f32 },
+    /// Tests ran `ratio` of the chunk's instrumented lines, multiplying its score by `factor`.
+    Coverage { ratio: f32, factor: f32 },
+    /// The cross-encoder scored the chunk `score` and reordered the top hits by it.
+    Reranked { score: f32 },
+}
+
+/// Serializable ranked search hit; the stable output schema for `emry search --json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchHit {
+    pub rank: usize,
+    pub chunk_id: String,
+    pub file_path: String,
+    pub start_line: usize,
+    pub end_line: usize,
+    pub snippet: String,
+    /// The file on disk no longer matches the indexed chunk.
+    #[serde(default)]
+    pub modified_since_index: bool,
+    /// Unified diff from the indexed chunk to the working tree (only with `--show-diff`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diff: Option<String>,
+    /// Index-time keywords of the chunk.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    pub score: f32,
+    pub scores: HitScores,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub graph_path: Option<Vec<String>>,
+    /// Per-stage ranking trace (see `RankingStep`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ranking: Vec<RankingStep>,
+    /// Index the hit came from when local and remote results are merged:
+    /// `local` or the remote server's address.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub origin: Option<String>,
+    /// Other places the same text is indexed; identical chunks are stored once.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub duplicates: Vec<ChunkLocation>,
+}
+
+/// Where a chunk sits in the repository.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkLocation {
+    pub file_path: String,
+    pub start_line: usize,
+    pub end_line: usize,
+}
+
+/// Per-component scores behind a hit's final score; absent components did not contribute.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HitScores {
+    /// The lexical source's own score: BM25 for full-text matches
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lexical: Option<f32>,
+    /// Cosine similarity between the query and chunk embeddings
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vector: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub graph: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rerank: Option<f32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchOutput {
+    pub query: String,
+    /// `query` with misspelled identifiers corrected, when it has any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub did_you_mean: Option<String>,
+    pub hits: Vec<SearchHit>,
+    /// Matches per lexical source before truncation to the limit.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub totals: Vec<SourceTotal>,
+    /// Without hits: why files containing the query are missing from the index.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub not_indexed: Vec<NotIndexed>,
+}
+
+/// A likely reason a search came back empty.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NotIndexed {
+    /// The file containing the query, relative to the repository root;
+    /// `None` when the reason concerns the whole index.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_path: Option<String>,
+    #[serde(flatten)]
+    pub reason: NotIndexedReason,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum NotIndexedReason {
+    /// Nothing has been indexed.
+    EmptyIndex,
+    /// Matches a `core.exclude_paths` pattern or a built-in exclude.
+    Excluded { pattern: String },
+    /// Matches none of `core.include_paths`.
+    NotIncluded,
+    /// Hidden, or matched by a `.gitignore`, `.ignore` or `.git/info/exclude`.
+    Ignored,
+    /// No language is registered for the extension.
+    UnsupportedLanguage { extension: String },
+    /// A document (Markdown, YAML, ...) while `documents.enabled` is off.
+    DocumentsDisabled,
+    /// Larger than `core.max_file_size_kb`.
+    TooLarge { size_kb: u64, limit_kb: u64 },
+    /// Contains NUL bytes despite its extension.
+    Binary,
+    /// Scanned, but created after the last index run.
+    AddedSinceIndex,
+    /// Indexed, but changed since.
+    ModifiedSinceIndex,
+}
+
+impl std::fmt::Display for NotIndexedReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::EmptyIndex => write!(f, "the index is empty; run `emry index`"),
+            Self::Excluded { pattern } => write!(f, "excluded by the glob '{}'", pattern),
+            Self::NotIncluded => write!(f, "matches none of core.include_paths"),
+            Self::Ignored => write!(f, "hidden or ignored by .gitignore (see core.respect_gitignore)"),
+            Self::UnsupportedLanguage { extension } if extension.is_empty() => write!(f, "files without an extension are not indexed"),
+            Self::UnsupportedLanguage { extension } => write!(f, "'.{}' files are not a supported language", extension),
+            Self::DocumentsDisabled => write!(f, "documents are not indexed unless documents.enabled is set"),
+            Self::TooLarge { size_kb, limit_kb } => {
+                write!(f, "{} KB is over core.max_file_size_kb ({} KB), so it was skipped", size_kb, limit_kb)
+            }
+            Self::Binary => write!(f, "looks binary, so it was skipped"),
+            Self::AddedSinceIndex => write!(f, "added after the last index run; run `emry index`"),
+            Self::ModifiedSinceIndex => write!(f, "changed since the last index run; run `emry index`"),
+        }
+    }
+}
+
+/// Number of chunks one search source matched, ignoring the result limit.
+///
+/// Counted before scope and exact-match filters, so it is an upper bound.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceTotal {
+    pub source: String,
+    pub matches: usize,
+}
+
+impl SearchHit {
+    /// Build a hit from a scored chunk; `rank` is 1-based.
+    pub fn from_scored(rank: usize, scored: &ScoredChunk) -> Self {
+        Self {
+            rank,
+            chunk_id: scored.chunk.id.clone(),
+            file_path: scored.chunk.file_path.display().to_string(),
+            start_line: scored.chunk.start_line,
+            end_line: scored.chunk.end_line,
+            snippet: scored.chunk.content.clone(),
+            modified_since_index: false,
+            diff: None,
+            tags: scored.chunk.tags.clone(),
+            score: scored.score,
+            scores: HitScores {
+                lexical: scored.lexical_score,
+                vector: scored.vector_score,
+                graph: scored.graph_boost,
+                symbol: scored.symbol_boost,
+                rerank: scored.rerank_score,
+            },
+            graph_path: scored.graph_path.clone(),
+            ranking: scored.ranking.clone(),
+            origin: None,
+            duplicates: Vec::new(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ContextualResult {
     pub chunks: Vec<ScoredChunk>,
     pub paths: Vec<paths::Path>,
 }
 
+/// Canonical edge vocabulary shared by every graph consumer.
+///
+/// The string forms match the relation table names used in the store.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum EdgeKind {
+    Calls,
+    Imports,
+    Defines,
+    Contains,
+    Inherits,
+    Implements,
+}
+
+impl EdgeKind {
+    pub const ALL: [EdgeKind; 6] = [
+        EdgeKind::Calls,
+        EdgeKind::Imports,
+        EdgeKind::Defines,
+        EdgeKind::Contains,
+        EdgeKind::Inherits,
+        EdgeKind::Implements,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EdgeKind::Calls => "calls",
+            EdgeKind::Imports => "imports",
+            EdgeKind::Defines => "defines",
+            EdgeKind::Contains => "contains",
+            EdgeKind::Inherits => "inherits",
+            EdgeKind::Implements => "implements",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|k| k.as_str() == name)
+    }
+
+    /// Edges that describe code dependencies rather than containment.
+    pub fn is_dependency(&self) -> bool {
+        matches!(self, EdgeKind::Calls | EdgeKind::Imports | EdgeKind::Inherits | EdgeKind::Implements)
+    }
+}
+
+impl std::fmt::Display for EdgeKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 pub mod paths {
     use serde::{Deserialize, Serialize};
 
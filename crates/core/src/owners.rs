@@ -0,0 +1,188 @@
+//! Code ownership: CODEOWNERS rules and git blame authorship.
+//!
+//! CODEOWNERS patterns follow gitignore rules as GitHub and GitLab apply
+//! them: a pattern with a slash other than a trailing one is anchored at the
+//! repository root, one without matches at any depth, a directory pattern
+//! covers everything below it, and the last matching line decides a file's
+//! owners. A matching line without owners leaves the file unowned. GitLab
+//! `[Section]` headers are skipped and their rules read as one list.
+
+use anyhow::{Context, Result};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Where GitHub and GitLab look for the file, in their order.
+const CODEOWNERS_LOCATIONS: &[&str] = &[".github/CODEOWNERS", "CODEOWNERS", "docs/CODEOWNERS", ".gitlab/CODEOWNERS"];
+
+#[derive(Debug)]
+struct Rule {
+    pattern: String,
+    matcher: GlobSet,
+    owners: Vec<String>,
+}
+
+/// A parsed CODEOWNERS file.
+#[derive(Debug, Default)]
+pub struct CodeOwners {
+    rules: Vec<Rule>,
+}
+
+/// The CODEOWNERS line that assigns a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OwnerRule<'a> {
+    pub pattern: &'a str,
+    pub owners: &'a [String],
+}
+
+impl CodeOwners {
+    /// The CODEOWNERS file of the repository at `root`, if it has one.
+    pub fn find(root: &Path) -> Result<Option<(PathBuf, CodeOwners)>> {
+        let Some(path) = CODEOWNERS_LOCATIONS.iter().map(|l| root.join(l)).find(|p| p.is_file()) else {
+            return Ok(None);
+        };
+        let content = std::fs::read_to_string(&path).with_context(|| format!("failed to read {}", path.display()))?;
+        let owners = CodeOwners::parse(&content)?;
+        Ok(Some((path, owners)))
+    }
+
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut rules = Vec::new();
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with('[') || line.starts_with("^[") {
+                continue;
+            }
+            let line = line.split(" #").next().unwrap_or(line);
+            let mut parts = line.split_whitespace();
+            let Some(pattern) = parts.next() else { continue };
+            let pattern = pattern.replace("\\#", "#");
+            let owners = parts.map(str::to_string).collect();
+            let mut matcher = GlobSetBuilder::new();
+            for glob in globs(&pattern) {
+                matcher.add(
+                    GlobBuilder::new(&glob)
+                        .literal_separator(true)
+                        .build()
+                        .with_context(|| format!("invalid CODEOWNERS pattern '{}'", pattern))?,
+                );
+            }
+            rules.push(Rule { pattern, matcher: matcher.build()?, owners });
+        }
+        Ok(Self { rules })
+    }
+
+    /// The rule deciding the owners of `path`, relative to the repository root.
+    pub fn rule_for(&self, path: &Path) -> Option<OwnerRule<'_>> {
+        self.rules
+            .iter()
+            .rev()
+            .find(|r| r.matcher.is_match(path))
+            .map(|r| OwnerRule { pattern: &r.pattern, owners: &r.owners })
+    }
+
+    /// Owners of `path`, relative to the repository root; empty when unowned.
+    pub fn owners_of(&self, path: &Path) -> Vec<String> {
+        self.rule_for(path).map(|r| r.owners.to_vec()).unwrap_or_default()
+    }
+}
+
+/// Globs, relative to the root, matching what a CODEOWNERS pattern covers.
+fn globs(pattern: &str) -> Vec<String> {
+    let directory = pattern.ends_with('/');
+    let trimmed = pattern.trim_end_matches('/');
+    let anchored = trimmed.contains('/');
+    let base = trimmed.trim_start_matches('/');
+    let base = if anchored || base.starts_with("**") { base.to_string() } else { format!("**/{}", base) };
+    if base.ends_with("**") {
+        return vec![base];
+    }
+    // A name can be a file or a directory; a trailing slash means only a directory.
+    let mut globs = vec![format!("{}/**", base)];
+    if !directory {
+        globs.push(base);
+    }
+    globs
+}
+
+/// Whether `owner` (a CODEOWNERS handle, team or email) is the one asked for
+/// by `query`, ignoring case and the leading `@`.
+pub fn owner_matches(owner: &str, query: &str) -> bool {
+    owner.trim_start_matches('@').eq_ignore_ascii_case(query.trim_start_matches('@'))
+}
+
+/// Lines of a file last changed by one author.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuthorShare {
+    pub name: String,
+    pub email: String,
+    pub lines: usize,
+}
+
+/// Authors of a file by lines owned, most first, from `git blame
+/// --line-porcelain` output. Uncommitted lines aren't counted.
+pub fn parse_blame_porcelain(output: &str) -> Vec<AuthorShare> {
+    let mut shares: HashMap<String, AuthorShare> = HashMap::new();
+    let mut name = "";
+    for line in output.lines() {
+        if let Some(author) = line.strip_prefix("author ") {
+            name = author;
+        } else if let Some(mail) = line.strip_prefix("author-mail ") {
+            let email = mail.trim_start_matches('<').trim_end_matches('>');
+            if email == "not.committed.yet" {
+                continue;
+            }
+            shares
+                .entry(email.to_lowercase())
+                .or_insert_with(|| AuthorShare { name: name.to_string(), email: email.to_string(), lines: 0 })
+                .lines += 1;
+        }
+    }
+    let mut shares: Vec<AuthorShare> = shares.into_values().collect();
+    shares.sort_by(|a, b| b.lines.cmp(&a.lines).then_with(|| a.email.cmp(&b.email)));
+    shares
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_last_matching_rule_wins() {
+        let owners = CodeOwners::parse(
+            "# Default owners\n* @org/core\n\n*.md @docs-team\n/crates/store/ @org/storage alice@example.com\ndocs/ @docs-team\n/crates/store/src/backup.rs\n[Frontend]\nweb/**/*.ts @org/web # typescript\n",
+        )
+        .unwrap();
+        let of = |p: &str| owners.owners_of(Path::new(p));
+        assert_eq!(of("src/main.rs"), vec!["@org/core"]);
+        assert_eq!(of("README.md"), vec!["@docs-team"]);
+        assert_eq!(of("guide/docs/intro.txt"), vec!["@docs-team"]);
+        assert_eq!(of("crates/store/src/lib.rs"), vec!["@org/storage", "alice@example.com"]);
+        assert_eq!(of("vendor/crates/store/lib.rs"), vec!["@org/core"]);
+        assert!(of("crates/store/src/backup.rs").is_empty());
+        assert_eq!(of("web/app/components/button.ts"), vec!["@org/web"]);
+        assert_eq!(owners.rule_for(Path::new("README.md")).unwrap().pattern, "*.md");
+
+        assert!(owner_matches("@org/storage", "org/storage"));
+        assert!(owner_matches("Alice@Example.com", "alice@example.com"));
+        assert!(!owner_matches("@org/storage", "storage"));
+    }
+
+    #[test]
+    fn test_blame_porcelain_counts_lines_per_author() {
+        let blame = "\
+a1 1 1 2\nauthor Alice\nauthor-mail <alice@example.com>\nsummary init\n\tfn main() {\n\
+a1 2 2\nauthor Alice\nauthor-mail <alice@example.com>\n\t}\n\
+b2 3 3 1\nauthor Bob\nauthor-mail <bob@example.com>\n\t// note\n\
+0000 4 4 1\nauthor Not Committed Yet\nauthor-mail <not.committed.yet>\n\t// wip\n";
+        let shares = parse_blame_porcelain(blame);
+        assert_eq!(
+            shares,
+            vec![
+                AuthorShare { name: "Alice".into(), email: "alice@example.com".into(), lines: 2 },
+                AuthorShare { name: "Bob".into(), email: "bob@example.com".into(), lines: 1 },
+            ]
+        );
+    }
+}
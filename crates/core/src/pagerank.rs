@@ -0,0 +1,64 @@
+//! Personalized PageRank over the code graph.
+//!
+//! Nodes are symbols (and files, as import sources) and edges point from a
+//! caller or importer to what it uses, so rank flows to the definitions the
+//! rest of the code depends on. Teleports, and the rank of nodes without
+//! outgoing edges, go to the personalization vector: uniform by default, or
+//! weighted toward the part of the code a map is focused on.
+
+/// Probability of following an edge rather than teleporting.
+const DAMPING: f64 = 0.85;
+const MAX_ITERATIONS: usize = 100;
+/// Stop once no rank changes by more than this in total.
+const TOLERANCE: f64 = 1e-9;
+
+/// Rank of each node `0..n` over `edges` (`from -> to`), summing to 1.
+/// `personalization` weighs teleports to each node; it is normalized, and
+/// `None` or all zeros means uniform.
+pub fn pagerank(n: usize, edges: &[(usize, usize)], personalization: Option<&[f64]>) -> Vec<f64> {
+    if n == 0 {
+        return Vec::new();
+    }
+    let teleport: Vec<f64> = match personalization.map(|p| (p, p.iter().sum::<f64>())) {
+        Some((p, total)) if p.len() == n && total > 0.0 => p.iter().map(|w| w / total).collect(),
+        _ => vec![1.0 / n as f64; n],
+    };
+    let mut out_degree = vec![0usize; n];
+    for &(from, _) in edges {
+        out_degree[from] += 1;
+    }
+
+    let mut rank = teleport.clone();
+    for _ in 0..MAX_ITERATIONS {
+        let dangling: f64 = (0..n).filter(|&i| out_degree[i] == 0).map(|i| rank[i]).sum();
+        let mut next: Vec<f64> = teleport.iter().map(|t| (1.0 - DAMPING + DAMPING * dangling) * t).collect();
+        for &(from, to) in edges {
+            next[to] += DAMPING * rank[from] / out_degree[from] as f64;
+        }
+        let change: f64 = next.iter().zip(&rank).map(|(a, b)| (a - b).abs()).sum();
+        rank = next;
+        if change < TOLERANCE {
+            break;
+        }
+    }
+    rank
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rank_flows_to_shared_callees_and_focus() {
+        // 0, 1 and 2 all call 3; 3 calls 4; 5 is isolated.
+        let edges = [(0, 3), (1, 3), (2, 3), (3, 4)];
+        let rank = pagerank(6, &edges, None);
+        assert!((rank.iter().sum::<f64>() - 1.0).abs() < 1e-6);
+        assert!(rank[3] > rank[0] && rank[4] > rank[3]);
+        assert!((rank[0] - rank[5]).abs() < 1e-9);
+
+        let focused = pagerank(6, &edges, Some(&[0.0, 0.0, 0.0, 0.0, 0.0, 1.0]));
+        assert!(focused[5] > rank[5]);
+        assert!(focused[0] < rank[0]);
+    }
+}
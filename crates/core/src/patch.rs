@@ -0,0 +1,403 @@
+//! Applying unified diffs to a working tree that may have moved on
+//!
+//! Each hunk is first looked for verbatim near the line it names. When the
+//! surrounding code was edited since the patch was made, the hunk is merged
+//! three ways instead: the patch's old side is the base, the working-tree
+//! region between the hunk's context anchors is "ours" and the patch's new
+//! side is "theirs". Changes that touch different lines combine; changes to
+//! the same lines are reported as conflicts and leave the region untouched.
+
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// What a file patch does to its file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PatchKind {
+    Modify,
+    Create,
+    Delete,
+}
+
+/// The hunks of one file in a unified diff.
+#[derive(Debug, Clone)]
+pub struct FilePatch {
+    /// Path as written in the diff, without the `a/` or `b/` prefix
+    pub path: PathBuf,
+    pub kind: PatchKind,
+    pub hunks: Vec<Hunk>,
+}
+
+/// One `@@` section; lines keep their line terminators.
+#[derive(Debug, Clone)]
+pub struct Hunk {
+    /// 1-based first line of the old side
+    pub old_start: usize,
+    /// Context and removed lines
+    pub old: Vec<String>,
+    /// Context and added lines
+    pub new: Vec<String>,
+    /// Context lines before the first change
+    pub lead: usize,
+    /// Context lines after the last change
+    pub trail: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HunkStatus {
+    /// Found as written (possibly at a shifted line) and applied
+    Applied,
+    /// The working tree already contains the new side
+    AlreadyApplied,
+    /// Combined with working-tree edits to its context
+    Merged,
+    /// Could not be placed or overlaps working-tree edits
+    Conflict,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HunkResult {
+    pub old_start: usize,
+    pub status: HunkStatus,
+}
+
+/// Outcome of applying one file patch.
+#[derive(Debug, Clone)]
+pub struct FileResult {
+    /// Content after the patch; `None` when the file should not exist
+    pub content: Option<String>,
+    pub hunks: Vec<HunkResult>,
+}
+
+impl FileResult {
+    pub fn has_conflicts(&self) -> bool {
+        self.hunks.iter().any(|h| h.status == HunkStatus::Conflict)
+    }
+}
+
+/// Parse `git diff`-style text into per-file patches. Headers such as
+/// `diff --git` and `index` are skipped; binary patches are not supported.
+pub fn parse_patch(text: &str) -> Result<Vec<FilePatch>> {
+    let mut patches: Vec<FilePatch> = Vec::new();
+    let mut old_path: Option<String> = None;
+    // Lines still expected on each side of the current hunk, and the marker of the last line.
+    let (mut old_left, mut new_left, mut last) = (0usize, 0usize, ' ');
+
+    for raw in text.split_inclusive('\n') {
+        let line = raw.strip_suffix('\n').unwrap_or(raw);
+        if line.starts_with('\\') {
+            // "\ No newline at end of file" applies to the line before it.
+            let strip = |line: Option<&mut String>| {
+                if let Some(line) = line {
+                    if line.ends_with('\n') {
+                        line.pop();
+                    }
+                }
+            };
+            if let Some(hunk) = patches.last_mut().and_then(|p| p.hunks.last_mut()) {
+                if last != '+' {
+                    strip(hunk.old.last_mut());
+                }
+                if last != '-' {
+                    strip(hunk.new.last_mut());
+                }
+            }
+            continue;
+        }
+        if old_left > 0 || new_left > 0 {
+            let hunk = patches.last_mut().and_then(|p| p.hunks.last_mut()).ok_or_else(|| anyhow!("hunk outside a file"))?;
+            let (marker, body) = match line.chars().next() {
+                Some(marker) => (marker, &raw[marker.len_utf8()..]),
+                // Some tools drop the space of empty context lines.
+                None => (' ', "\n"),
+            };
+            match marker {
+                ' ' if old_left > 0 && new_left > 0 => {
+                    hunk.old.push(body.to_string());
+                    hunk.new.push(body.to_string());
+                    old_left -= 1;
+                    new_left -= 1;
+                }
+                '-' if old_left > 0 => {
+                    hunk.old.push(body.to_string());
+                    old_left -= 1;
+                }
+                '+' if new_left > 0 => {
+                    hunk.new.push(body.to_string());
+                    new_left -= 1;
+                }
+                _ => return Err(anyhow!("malformed hunk at line {:?}", line)),
+            }
+            last = marker;
+            continue;
+        }
+
+        if let Some(path) = line.strip_prefix("--- ") {
+            old_path = Some(diff_path(path, "a/"));
+        } else if let Some(path) = line.strip_prefix("+++ ") {
+            let new_path = diff_path(path, "b/");
+            let old = old_path.take().unwrap_or_else(|| new_path.clone());
+            let (path, kind) = match (old.as_str(), new_path.as_str()) {
+                ("/dev/null", _) => (new_path, PatchKind::Create),
+                (_, "/dev/null") => (old, PatchKind::Delete),
+                _ => (new_path, PatchKind::Modify),
+            };
+            patches.push(FilePatch { path: PathBuf::from(path), kind, hunks: Vec::new() });
+        } else if let Some(header) = line.strip_prefix("@@ ") {
+            let patch = patches.last_mut().ok_or_else(|| anyhow!("hunk before any file header"))?;
+            let (old_start, old_count, new_count) = parse_hunk_header(header)?;
+            patch.hunks.push(Hunk { old_start, old: Vec::new(), new: Vec::new(), lead: 0, trail: 0 });
+            old_left = old_count;
+            new_left = new_count;
+        }
+    }
+    if old_left > 0 || new_left > 0 {
+        return Err(anyhow!("patch ends in the middle of a hunk"));
+    }
+
+    for hunk in patches.iter_mut().flat_map(|p| p.hunks.iter_mut()) {
+        let (old, new) = (&hunk.old, &hunk.new);
+        let common = old.len().min(new.len());
+        let lead = (0..common).take_while(|&i| old[i] == new[i]).count();
+        let trail = (1..=common - lead).take_while(|&k| old[old.len() - k] == new[new.len() - k]).count();
+        (hunk.lead, hunk.trail) = (lead, trail);
+    }
+    Ok(patches)
+}
+
+fn diff_path(path: &str, prefix: &str) -> String {
+    let path = path.split('\t').next().unwrap_or(path).trim_end();
+    path.strip_prefix(prefix).unwrap_or(path).to_string()
+}
+
+/// `-a,b +c,d @@ ...` into `(a, b, d)`; a missing count means 1.
+fn parse_hunk_header(header: &str) -> Result<(usize, usize, usize)> {
+    let range = |part: Option<&str>, sign: char| -> Result<(usize, usize)> {
+        let part = part.and_then(|p| p.strip_prefix(sign)).ok_or_else(|| anyhow!("bad hunk header @@ {}", header))?;
+        let (start, count) = part.split_once(',').unwrap_or((part, "1"));
+        Ok((start.parse()?, count.parse()?))
+    };
+    let mut parts = header.split_whitespace();
+    let (old_start, old_count) = range(parts.next(), '-')?;
+    let (_, new_count) = range(parts.next(), '+')?;
+    Ok((old_start, old_count, new_count))
+}
+
+impl FilePatch {
+    /// Apply the hunks to `current`, the file's content (`None` if it does not exist).
+    ///
+    /// Conflicting hunks leave their region as it is, so the content is only
+    /// meaningful to write when [`FileResult::has_conflicts`] is false.
+    pub fn apply(&self, current: Option<&str>) -> FileResult {
+        let whole = |hunks: &[Hunk], new: bool| -> String {
+            hunks.iter().flat_map(|h| if new { &h.new } else { &h.old }).map(String::as_str).collect()
+        };
+        let all = |status: HunkStatus| -> Vec<HunkResult> { self.hunks.iter().map(|h| HunkResult { old_start: h.old_start, status }).collect() };
+
+        match (self.kind, current) {
+            (PatchKind::Create, None) => FileResult { content: Some(whole(&self.hunks, true)), hunks: all(HunkStatus::Applied) },
+            (PatchKind::Create, Some(current)) => {
+                let status = if current == whole(&self.hunks, true) { HunkStatus::AlreadyApplied } else { HunkStatus::Conflict };
+                FileResult { content: Some(current.to_string()), hunks: all(status) }
+            }
+            (PatchKind::Delete, None) => FileResult { content: None, hunks: all(HunkStatus::AlreadyApplied) },
+            (PatchKind::Delete, Some(current)) => {
+                let status = if current == whole(&self.hunks, false) { HunkStatus::Applied } else { HunkStatus::Conflict };
+                let content = if status == HunkStatus::Applied { None } else { Some(current.to_string()) };
+                FileResult { content, hunks: all(status) }
+            }
+            (PatchKind::Modify, None) => FileResult { content: None, hunks: all(HunkStatus::Conflict) },
+            (PatchKind::Modify, Some(current)) => {
+                let mut lines: Vec<String> = current.split_inclusive('\n').map(str::to_string).collect();
+                let mut results = Vec::new();
+                // Where the working tree's numbering stands relative to the patch's old side,
+                // and the first line later hunks may touch.
+                let (mut offset, mut floor) = (0isize, 0usize);
+                for hunk in &self.hunks {
+                    let anchor = if hunk.old.is_empty() { hunk.old_start } else { hunk.old_start.saturating_sub(1) };
+                    let expected = (anchor as isize + offset).max(floor as isize) as usize;
+                    let (status, region) = apply_hunk(&lines, hunk, expected, floor);
+                    if let Some((start, end, replacement)) = region {
+                        let added = replacement.len();
+                        lines.splice(start..end, replacement);
+                        offset = (start + added) as isize - (anchor + hunk.old.len()) as isize;
+                        floor = start + added;
+                    }
+                    results.push(HunkResult { old_start: hunk.old_start, status });
+                }
+                FileResult { content: Some(lines.concat()), hunks: results }
+            }
+        }
+    }
+}
+
+/// Status of one hunk and, unless it conflicts, the lines `start..end` to
+/// replace and their replacement.
+fn apply_hunk(lines: &[String], hunk: &Hunk, expected: usize, floor: usize) -> (HunkStatus, Option<(usize, usize, Vec<String>)>) {
+    if let Some(start) = find_block(lines, &hunk.old, expected, floor) {
+        return (HunkStatus::Applied, Some((start, start + hunk.old.len(), hunk.new.clone())));
+    }
+    if let Some(start) = find_block(lines, &hunk.new, expected, floor) {
+        return (HunkStatus::AlreadyApplied, Some((start, start + hunk.new.len(), hunk.new.clone())));
+    }
+
+    // Pin the region down by its leading and trailing context, then merge what lies between.
+    let lead = &hunk.old[..hunk.lead];
+    let trail = &hunk.old[hunk.old.len() - hunk.trail..];
+    let Some(start) = find_anchor(lines, lead, expected, floor) else {
+        return (HunkStatus::Conflict, None);
+    };
+    let trail_expected = (start + hunk.old.len()).saturating_sub(trail.len());
+    let Some(trail_start) = find_anchor(lines, trail, trail_expected, start) else {
+        return (HunkStatus::Conflict, None);
+    };
+    let end = (trail_start + trail.len()).min(lines.len());
+    match merge3(&hunk.old, &lines[start..end], &hunk.new) {
+        Some(merged) => (HunkStatus::Merged, Some((start, end, merged))),
+        None => (HunkStatus::Conflict, None),
+    }
+}
+
+/// Where `anchor` starts in `lines`. Context lines edited since the patch was
+/// made are tolerated: failing a full match, the longest run of `anchor` still
+/// present places it.
+fn find_anchor(lines: &[String], anchor: &[String], expected: usize, floor: usize) -> Option<usize> {
+    if anchor.is_empty() {
+        return find_block(lines, anchor, expected, floor);
+    }
+    for len in (1..=anchor.len()).rev() {
+        for skip in 0..=anchor.len() - len {
+            if let Some(pos) = find_block(lines, &anchor[skip..skip + len], expected + skip, floor + skip) {
+                return Some(pos - skip);
+            }
+        }
+    }
+    None
+}
+
+/// Start of the occurrence of `block` in `lines` at or after `floor` that is
+/// closest to `expected`.
+fn find_block(lines: &[String], block: &[String], expected: usize, floor: usize) -> Option<usize> {
+    if block.is_empty() {
+        return Some(expected.clamp(floor, lines.len().max(floor)));
+    }
+    let last = lines.len().checked_sub(block.len())?;
+    let matches_at = |pos: usize| pos >= floor && pos <= last && lines[pos..pos + block.len()] == *block;
+    (0..=lines.len().max(expected)).find_map(|distance| {
+        [expected.checked_sub(distance), expected.checked_add(distance)]
+            .into_iter()
+            .flatten()
+            .find(|&pos| matches_at(pos))
+    })
+}
+
+/// Three-way merge of line sequences: changes from `base` to `ours` and to
+/// `theirs` are combined, or `None` when both change the same lines differently.
+pub fn merge3(base: &[String], ours: &[String], theirs: &[String]) -> Option<Vec<String>> {
+    let in_ours: HashMap<usize, usize> = common_lines(base, ours).into_iter().collect();
+    let in_theirs: HashMap<usize, usize> = common_lines(base, theirs).into_iter().collect();
+
+    let mut out = Vec::new();
+    let (mut b, mut o, mut t) = (0, 0, 0);
+    // Lines unchanged on both sides split the sequences into regions merged one by one.
+    for i in 0..base.len() {
+        let (Some(&j), Some(&k)) = (in_ours.get(&i), in_theirs.get(&i)) else {
+            continue;
+        };
+        out.extend_from_slice(resolve(&base[b..i], &ours[o..j], &theirs[t..k])?);
+        out.push(base[i].clone());
+        (b, o, t) = (i + 1, j + 1, k + 1);
+    }
+    out.extend_from_slice(resolve(&base[b..], &ours[o..], &theirs[t..])?);
+    Some(out)
+}
+
+fn resolve<'a>(base: &[String], ours: &'a [String], theirs: &'a [String]) -> Option<&'a [String]> {
+    if ours == theirs || theirs == base {
+        Some(ours)
+    } else if ours == base {
+        Some(theirs)
+    } else {
+        None
+    }
+}
+
+/// Index pairs of a longest common subsequence of `a` and `b`.
+pub(crate) fn common_lines(a: &[String], b: &[String]) -> Vec<(usize, usize)> {
+    let mut table = vec![vec![0u32; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            table[i][j] = if a[i] == b[j] { table[i + 1][j + 1] + 1 } else { table[i + 1][j].max(table[i][j + 1]) };
+        }
+    }
+    let (mut i, mut j, mut pairs) = (0, 0, Vec::new());
+    while i < a.len() && j < b.len() {
+        if a[i] == b[j] {
+            pairs.push((i, j));
+            i += 1;
+            j += 1;
+        } else if table[i + 1][j] >= table[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff::unified_diff;
+
+    fn numbered(n: usize) -> String {
+        (1..=n).map(|i| format!("line {}\n", i)).collect()
+    }
+
+    #[test]
+    fn test_round_trip_with_shifted_lines() {
+        let old = numbered(20);
+        let new = old.replace("line 10\n", "LINE 10\n");
+        let patches = parse_patch(&unified_diff("src/lib.rs", &old, &new)).unwrap();
+        assert_eq!(patches.len(), 1);
+        assert_eq!(patches[0].path, PathBuf::from("src/lib.rs"));
+        assert_eq!((patches[0].hunks[0].lead, patches[0].hunks[0].trail), (3, 3));
+
+        let shifted = format!("header\nheader\n{}", old);
+        let result = patches[0].apply(Some(&shifted));
+        assert_eq!(result.hunks[0].status, HunkStatus::Applied);
+        assert_eq!(result.content.unwrap(), format!("header\nheader\n{}", new));
+
+        let again = patches[0].apply(Some(&new));
+        assert_eq!(again.hunks[0].status, HunkStatus::AlreadyApplied);
+        assert_eq!(again.content.unwrap(), new);
+    }
+
+    #[test]
+    fn test_merges_edits_to_context_and_detects_conflicts() {
+        let old = numbered(20);
+        let new = old.replace("line 10\n", "LINE 10\n");
+        let patch = &parse_patch(&unified_diff("a.txt", &old, &new)).unwrap()[0];
+
+        let edited = old.replace("line 7\n", "line seven\n");
+        let merged = patch.apply(Some(&edited));
+        assert_eq!(merged.hunks[0].status, HunkStatus::Merged);
+        assert_eq!(merged.content.unwrap(), edited.replace("line 10\n", "LINE 10\n"));
+
+        let clashing = old.replace("line 10\n", "line ten\n");
+        let conflict = patch.apply(Some(&clashing));
+        assert!(conflict.has_conflicts());
+        assert_eq!(conflict.content.unwrap(), clashing);
+    }
+
+    #[test]
+    fn test_create_and_delete() {
+        let text = "--- /dev/null\n+++ b/new.txt\n@@ -0,0 +1,2 @@\n+one\n+two\n\\ No newline at end of file\n--- a/old.txt\n+++ /dev/null\n@@ -1 +0,0 @@\n-gone\n";
+        let patches = parse_patch(text).unwrap();
+        assert_eq!(patches[0].kind, PatchKind::Create);
+        assert_eq!(patches[0].apply(None).content.unwrap(), "one\ntwo");
+        assert_eq!(patches[1].kind, PatchKind::Delete);
+        assert_eq!(patches[1].apply(Some("gone\n")).content, None);
+        assert!(patches[1].apply(Some("changed\n")).has_conflicts());
+    }
+}
@@ -0,0 +1,52 @@
+//! Quantized copies of embeddings for the vector index (`embedding.quantization`).
+//!
+//! The index searches the compact vectors for candidates and rescores them
+//! with the full-precision embedding, so quantization only needs to keep the
+//! true nearest neighbours among the candidates, not to rank them exactly.
+//! Values are stored as `i16` because that is the smallest element type the
+//! HNSW index accepts, so a binary copy takes as much room as an int8 one and
+//! both sit next to the full-precision embedding rather than replacing it.
+
+pub use emry_config::Quantization;
+
+/// Candidates fetched from a quantized index per result kept after rescoring.
+pub const RESCORE_FACTOR: usize = 4;
+
+/// The quantized form of `vector`, or `None` when `mode` keeps full precision.
+pub fn quantize(vector: &[f32], mode: Quantization) -> Option<Vec<i16>> {
+    match mode {
+        Quantization::None => None,
+        Quantization::Int8 => Some(int8(vector)),
+        Quantization::Binary => Some(binary(vector)),
+    }
+}
+
+/// Each component scaled to -127..=127 by the vector's largest magnitude;
+/// cosine similarity ignores the scale, so it needs no storing.
+fn int8(vector: &[f32]) -> Vec<i16> {
+    let max = vector.iter().fold(0f32, |m, x| m.max(x.abs()));
+    if max == 0.0 || !max.is_finite() {
+        return vec![0; vector.len()];
+    }
+    let scale = 127.0 / max;
+    vector.iter().map(|x| (x * scale).round().clamp(-127.0, 127.0) as i16).collect()
+}
+
+/// 1 for each positive component, 0 otherwise.
+fn binary(vector: &[f32]) -> Vec<i16> {
+    vector.iter().map(|&x| i16::from(x > 0.0)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quantize() {
+        let v = [0.5, -0.25, 0.0, 0.125];
+        assert_eq!(quantize(&v, Quantization::None), None);
+        assert_eq!(quantize(&v, Quantization::Int8), Some(vec![127, -64, 0, 32]));
+        assert_eq!(quantize(&v, Quantization::Binary), Some(vec![1, 0, 0, 1]));
+        assert_eq!(quantize(&[0.0, 0.0], Quantization::Int8), Some(vec![0, 0]));
+    }
+}
@@ -0,0 +1,323 @@
+//! Languages embedded in one file
+//!
+//! Vue and Svelte components, ERB and Jinja templates, and JavaScript with
+//! CSS-in-JS mix languages in one file. `regions` splits such a file into
+//! byte ranges of one sub-language each: `<script>` bodies are JavaScript or
+//! TypeScript (after their `lang` attribute), `<style>` bodies and large
+//! styled-components/emotion template literals CSS, ERB's `<% %>` tags Ruby,
+//! and the rest the host's markup. `mask` keeps one sub-language of the file
+//! and blanks out everything else, so its grammar parses just its own code
+//! while line numbers and byte offsets stay those of the file.
+//!
+//! Detection is lexical, not a full HTML parse: `<script>` and `<style>`
+//! elements are found wherever they appear, and Jinja tags inside them are
+//! left in place for the grammar to recover from.
+
+use crate::models::Language;
+use crate::tags_extractor::ts_language;
+use std::borrow::Cow;
+
+/// A byte range of a file written in one sub-language.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Region {
+    pub language: Language,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// CSS-in-JS literals spanning fewer lines stay part of the surrounding code.
+const MIN_EMBEDDED_CSS_LINES: usize = 4;
+
+/// Template tags whose literal is a stylesheet (besides `styled.x` and `styled(X)`).
+const CSS_TAGS: &[&str] = &["css", "keyframes", "createGlobalStyle", "injectGlobal"];
+
+/// Sub-language regions of `content`, in file order; empty when the whole
+/// file is `language`. Template delimiters (`<%`, `%>`) belong to no region.
+pub fn regions(language: &Language, content: &str) -> Vec<Region> {
+    match language {
+        Language::Vue | Language::Svelte => fill(content.len(), embedded_elements(content), Language::Html),
+        Language::Jinja => fill(content.len(), embedded_elements(content), Language::Jinja),
+        Language::Erb => {
+            let (tags, code) = erb_tags(content);
+            let mut regions = carve(fill(content.len(), embedded_elements(content), Language::Html), &tags);
+            regions.extend(code);
+            regions.sort_by_key(|r| r.start);
+            regions
+        }
+        Language::JavaScript | Language::TypeScript => {
+            let css = css_in_js(language, content);
+            if css.is_empty() {
+                Vec::new()
+            } else {
+                fill(content.len(), css, *language)
+            }
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Sub-languages of `regions`, in order of first appearance.
+pub fn languages(regions: &[Region]) -> Vec<Language> {
+    let mut languages: Vec<Language> = Vec::new();
+    for region in regions {
+        if !languages.contains(&region.language) {
+            languages.push(region.language);
+        }
+    }
+    languages
+}
+
+/// `content` with every byte outside the `language` regions blanked,
+/// keeping line breaks so positions are unchanged.
+pub fn mask(content: &str, regions: &[Region], language: &Language) -> String {
+    let mut out: Vec<u8> = content.bytes().map(|b| if b == b'\n' { b'\n' } else { b' ' }).collect();
+    for region in regions.iter().filter(|r| r.language == *language) {
+        out[region.start..region.end].copy_from_slice(&content.as_bytes()[region.start..region.end]);
+    }
+    // Regions start and end at ASCII delimiters, so this only fails on a detection bug.
+    String::from_utf8(out).unwrap_or_else(|e| String::from_utf8_lossy(e.as_bytes()).into_owned())
+}
+
+/// One copy of `content` per sub-language of a template file (Vue, Svelte,
+/// ERB, Jinja), or the file itself for anything else.
+pub fn views<'a>(language: &Language, content: &'a str) -> Vec<(Language, Cow<'a, str>)> {
+    let regions = if language.is_template() { regions(language, content) } else { Vec::new() };
+    if regions.is_empty() {
+        return vec![(*language, Cow::Borrowed(content))];
+    }
+    languages(&regions)
+        .into_iter()
+        .map(|l| {
+            let view = mask(content, &regions, &l);
+            (l, Cow::Owned(view))
+        })
+        .collect()
+}
+
+/// `embedded` plus `host` regions covering the gaps between them.
+fn fill(len: usize, embedded: Vec<Region>, host: Language) -> Vec<Region> {
+    let mut regions = Vec::with_capacity(embedded.len() * 2 + 1);
+    let mut pos = 0;
+    for region in embedded {
+        if region.start > pos {
+            regions.push(Region { language: host, start: pos, end: region.start });
+        }
+        pos = region.end;
+        regions.push(region);
+    }
+    if pos < len {
+        regions.push(Region { language: host, start: pos, end: len });
+    }
+    regions
+}
+
+/// `regions` with the `cuts` spans taken out of them.
+fn carve(regions: Vec<Region>, cuts: &[(usize, usize)]) -> Vec<Region> {
+    let mut out = Vec::with_capacity(regions.len() + cuts.len());
+    for region in regions {
+        let mut start = region.start;
+        for &(cut_start, cut_end) in cuts.iter().filter(|(s, e)| *s < region.end && *e > region.start) {
+            if cut_start > start {
+                out.push(Region { language: region.language, start, end: cut_start });
+            }
+            start = start.max(cut_end);
+        }
+        if start < region.end {
+            out.push(Region { start, ..region });
+        }
+    }
+    out
+}
+
+/// Bodies of `<script>` and `<style>` elements.
+fn embedded_elements(content: &str) -> Vec<Region> {
+    // ASCII lowercasing keeps byte offsets.
+    let lower = content.to_ascii_lowercase();
+    let mut found = Vec::new();
+    let mut pos = 0;
+    while let Some(offset) = lower[pos..].find('<') {
+        let open = pos + offset;
+        let Some(tag) = ["script", "style"].into_iter().find(|t| lower[open + 1..].starts_with(t)) else {
+            pos = open + 1;
+            continue;
+        };
+        let name_end = open + 1 + tag.len();
+        // `<scripts>` or `<style-guide>` are other elements.
+        if !lower[name_end..].starts_with(|c: char| c == '>' || c == '/' || c.is_ascii_whitespace()) {
+            pos = name_end;
+            continue;
+        }
+        let Some(gt) = lower[name_end..].find('>') else { break };
+        let attrs = &content[name_end..name_end + gt];
+        let body_start = name_end + gt + 1;
+        if attrs.trim_end().ends_with('/') {
+            pos = body_start;
+            continue;
+        }
+        let body_end = lower[body_start..].find(&format!("</{}", tag)).map_or(content.len(), |i| body_start + i);
+        let language = if tag == "style" { Some(Language::Css) } else { script_language(attrs) };
+        if let Some(language) = language {
+            if body_end > body_start {
+                found.push(Region { language, start: body_start, end: body_end });
+            }
+        }
+        pos = body_end;
+    }
+    found
+}
+
+/// Language of a `<script>` body from its attributes; `None` for non-code
+/// types such as Vue 2's `text/x-template`.
+fn script_language(attrs: &str) -> Option<Language> {
+    let lang = attribute(attrs, "lang").unwrap_or_default().to_ascii_lowercase();
+    let kind = attribute(attrs, "type").unwrap_or_default().to_ascii_lowercase();
+    if kind.contains("template") || kind.contains("html") || kind.contains("json") {
+        return None;
+    }
+    if matches!(lang.as_str(), "ts" | "tsx" | "typescript") || kind.contains("typescript") {
+        Some(Language::TypeScript)
+    } else {
+        Some(Language::JavaScript)
+    }
+}
+
+/// Value of attribute `name` in an open tag's attribute text.
+fn attribute<'a>(attrs: &'a str, name: &str) -> Option<&'a str> {
+    let mut rest = attrs;
+    while let Some(i) = rest.find(name) {
+        let before = rest[..i].chars().next_back();
+        let after = rest[i + name.len()..].trim_start();
+        rest = &rest[i + name.len()..];
+        if !before.map_or(true, |c| c.is_ascii_whitespace()) {
+            continue;
+        }
+        let Some(value) = after.strip_prefix('=') else { continue };
+        let value = value.trim_start();
+        return Some(match value.chars().next() {
+            Some(quote @ ('"' | '\'')) => value[1..].split(quote).next().unwrap_or(""),
+            _ => value.split(|c: char| c.is_ascii_whitespace() || c == '>').next().unwrap_or(""),
+        });
+    }
+    None
+}
+
+/// Spans of ERB tags with their delimiters, and the Ruby code inside them.
+/// Comments (`<%#`) and escaped delimiters (`<%%`) stay markup.
+fn erb_tags(content: &str) -> (Vec<(usize, usize)>, Vec<Region>) {
+    let (mut tags, mut code) = (Vec::new(), Vec::new());
+    let mut pos = 0;
+    while let Some(offset) = content[pos..].find("<%") {
+        let open = pos + offset;
+        let rest = &content[open + 2..];
+        if rest.starts_with('%') || rest.starts_with('#') {
+            pos = open + 3;
+            continue;
+        }
+        let Some(close) = rest.find("%>") else { break };
+        let body = &rest[..close];
+        let lead = body.len() - body.trim_start_matches(['=', '-']).len();
+        let trail = if body.ends_with('-') { 1 } else { 0 };
+        let (start, end) = (open + 2 + lead, open + 2 + close - trail);
+        if end > start {
+            code.push(Region { language: Language::Ruby, start, end });
+        }
+        tags.push((open, open + 2 + close + 2));
+        pos = open + 2 + close + 2;
+    }
+    (tags, code)
+}
+
+/// Bodies of styled-components/emotion template literals of at least
+/// `MIN_EMBEDDED_CSS_LINES` lines.
+fn css_in_js(language: &Language, content: &str) -> Vec<Region> {
+    if !content.contains('`') || !(content.contains("styled") || CSS_TAGS.iter().any(|t| content.contains(t))) {
+        return Vec::new();
+    }
+    let Some(grammar) = ts_language(language) else { return Vec::new() };
+    let mut parser = tree_sitter::Parser::new();
+    if parser.set_language(&grammar).is_err() {
+        return Vec::new();
+    }
+    let Some(tree) = parser.parse(content, None) else { return Vec::new() };
+
+    let mut found = Vec::new();
+    let mut stack = vec![tree.root_node()];
+    while let Some(node) = stack.pop() {
+        stack.extend(node.named_children(&mut node.walk()));
+        if node.kind() != "call_expression" {
+            continue;
+        }
+        let (Some(function), Some(literal)) = (node.child_by_field_name("function"), node.child_by_field_name("arguments")) else {
+            continue;
+        };
+        if literal.kind() != "template_string" || !is_css_tag(&content[function.byte_range()]) {
+            continue;
+        }
+        let (start, end) = (literal.start_byte() + 1, literal.end_byte().saturating_sub(1));
+        if end > start && content[start..end].lines().count() >= MIN_EMBEDDED_CSS_LINES {
+            found.push(Region { language: Language::Css, start, end });
+        }
+    }
+    found.sort_by_key(|r| r.start);
+    // Nested literals (`${css`...`}` inside a styled block) are already covered by the outer one.
+    let mut outer: Vec<Region> = Vec::with_capacity(found.len());
+    for region in found {
+        if outer.last().map_or(true, |last| region.start >= last.end) {
+            outer.push(region);
+        }
+    }
+    outer
+}
+
+fn is_css_tag(function: &str) -> bool {
+    CSS_TAGS.contains(&function) || function.starts_with("styled.") || function.starts_with("styled(")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spans<'a>(content: &'a str, regions: &[Region]) -> Vec<(Language, &'a str)> {
+        regions.iter().map(|r| (r.language, content[r.start..r.end].trim())).collect()
+    }
+
+    #[test]
+    fn test_vue_regions() {
+        let sfc = "<template>\n  <p>{{ msg }}</p>\n</template>\n<script lang=\"ts\">\nexport default { name: 'Hello' }\n</script>\n<style scoped>\np { color: red; }\n</style>\n";
+        let regions = regions(&Language::Vue, sfc);
+        assert_eq!(
+            spans(sfc, &regions),
+            vec![
+                (Language::Html, "<template>\n  <p>{{ msg }}</p>\n</template>\n<script lang=\"ts\">"),
+                (Language::TypeScript, "export default { name: 'Hello' }"),
+                (Language::Html, "</script>\n<style scoped>"),
+                (Language::Css, "p { color: red; }"),
+                (Language::Html, "</style>"),
+            ]
+        );
+
+        let ts = mask(sfc, &regions, &Language::TypeScript);
+        assert_eq!(ts.len(), sfc.len());
+        assert_eq!(ts.lines().nth(4), Some("export default { name: 'Hello' }"));
+        assert!(ts.lines().nth(1).unwrap().trim().is_empty());
+    }
+
+    #[test]
+    fn test_erb_regions() {
+        let erb = "<ul>\n<% items.each do |item| %>\n  <li><%= item.name -%></li>\n<% end %>\n<%# a comment %>\n</ul>\n";
+        let regions = regions(&Language::Erb, erb);
+        let ruby: Vec<&str> = spans(erb, &regions).into_iter().filter(|(l, _)| *l == Language::Ruby).map(|(_, s)| s).collect();
+        assert_eq!(ruby, vec!["items.each do |item|", "item.name", "end"]);
+        let html = mask(erb, &regions, &Language::Html);
+        assert!(html.contains("<li>") && html.contains("<%# a comment %>") && !html.contains("items"));
+    }
+
+    #[test]
+    fn test_css_in_js_regions() {
+        let js = "const Button = styled.button`\n  color: red;\n  padding: 4px;\n  margin: 0;\n`;\nconst small = css`color: blue;`;\n";
+        let regions = regions(&Language::JavaScript, js);
+        let css: Vec<&str> = spans(js, &regions).into_iter().filter(|(l, _)| *l == Language::Css).map(|(_, s)| s).collect();
+        assert_eq!(css, vec!["color: red;\n  padding: 4px;\n  margin: 0;"]);
+        assert!(super::regions(&Language::JavaScript, "const x = 1;\n").is_empty());
+    }
+}
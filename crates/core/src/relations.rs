@@ -1,4 +1,5 @@
 use crate::models::Language;
+use crate::rust_types::TypeEnv;
 use tree_sitter::Node;
 use anyhow::{Result, anyhow};
 
@@ -8,6 +9,10 @@ pub struct RelationRef {
     pub alias: Option<String>,
     pub context: Option<String>,
     pub line: usize,
+    /// 1-based byte column where the call or import starts
+    pub column: usize,
+    /// Type owning the called function, when known (Rust only; see `rust_types`)
+    pub receiver_type: Option<String>,
 }
 
 
@@ -27,6 +32,17 @@ pub fn extract_calls_imports(
         Language::Python => extract_python_calls_imports(content),
         Language::Rust => extract_rust_calls_imports(content),
         Language::Go => extract_go_calls_imports(content),
+        _ if language.is_template() => {
+            let (mut calls, mut imports) = (Vec::new(), Vec::new());
+            for (sub, view) in crate::regions::views(language, content) {
+                if !sub.is_template() {
+                    let (c, i) = extract_calls_imports(&sub, &view)?;
+                    calls.extend(c);
+                    imports.extend(i);
+                }
+            }
+            Ok((calls, imports))
+        }
         _ => Ok((Vec::new(), Vec::new())),
     }
 }
@@ -76,6 +92,8 @@ fn extract_js_ts_calls_imports(
                                          alias: None,
                                          context: Some(obj_name.to_string()),
                                          line: node.start_position().row + 1,
+                                         column: node.start_position().column + 1,
+                                         receiver_type: None,
                                      });
                                  }
                              }
@@ -85,6 +103,8 @@ fn extract_js_ts_calls_imports(
                                 alias: None,
                                 context: None,
                                 line: node.start_position().row + 1,
+                                column: node.start_position().column + 1,
+                                receiver_type: None,
                             });
                         }
                     }
@@ -122,6 +142,8 @@ fn extract_js_ts_calls_imports(
                                                     alias: final_alias,
                                                     context: None,
                                                     line: node.start_position().row + 1,
+                                                    column: node.start_position().column + 1,
+                                                    receiver_type: None,
                                                 });
                                             }
                                         }
@@ -133,6 +155,8 @@ fn extract_js_ts_calls_imports(
                                             alias: Some(name.to_string()),
                                             context: None,
                                             line: node.start_position().row + 1,
+                                            column: node.start_position().column + 1,
+                                            receiver_type: None,
                                         });
                                     }
                                 }
@@ -166,6 +190,8 @@ fn extract_java_calls_imports(content: &str) -> Result<(Vec<RelationRef>, Vec<Re
                                 alias: None,
                                 context: None,
                                 line: node.start_position().row + 1,
+                                column: node.start_position().column + 1,
+                                receiver_type: None,
                             });
                         }
                     }
@@ -182,6 +208,8 @@ fn extract_java_calls_imports(content: &str) -> Result<(Vec<RelationRef>, Vec<Re
                                     alias: None,
                                     context: None,
                                     line: node.start_position().row + 1,
+                                    column: node.start_position().column + 1,
+                                    receiver_type: None,
                                 });
                             }
                         }
@@ -220,6 +248,8 @@ fn extract_c_cpp_calls_imports(
                                 alias: None,
                                 context: None,
                                 line: node.start_position().row + 1,
+                                column: node.start_position().column + 1,
+                                receiver_type: None,
                             });
                         }
                     }
@@ -234,6 +264,8 @@ fn extract_c_cpp_calls_imports(
                                 alias: None,
                                 context: None,
                                 line: node.start_position().row + 1,
+                                column: node.start_position().column + 1,
+                                receiver_type: None,
                             });
                         }
                     }
@@ -264,6 +296,8 @@ fn extract_csharp_calls_imports(content: &str) -> Result<(Vec<RelationRef>, Vec<
                                 alias: None,
                                 context: None,
                                 line: node.start_position().row + 1,
+                                column: node.start_position().column + 1,
+                                receiver_type: None,
                             });
                         }
                     }
@@ -278,6 +312,8 @@ fn extract_csharp_calls_imports(content: &str) -> Result<(Vec<RelationRef>, Vec<
                                 alias: None,
                                 context: None,
                                 line: node.start_position().row + 1,
+                                column: node.start_position().column + 1,
+                                receiver_type: None,
                             });
                         }
                     }
@@ -315,6 +351,8 @@ fn extract_python_calls_imports(content: &str) -> Result<(Vec<RelationRef>, Vec<
                                             alias: None,
                                             context: Some(obj_name.to_string()),
                                             line: node.start_position().row + 1,
+                                            column: node.start_position().column + 1,
+                                            receiver_type: None,
                                         });
                                     }
                                 }
@@ -326,6 +364,8 @@ fn extract_python_calls_imports(content: &str) -> Result<(Vec<RelationRef>, Vec<
                                 alias: None,
                                 context: None,
                                 line: node.start_position().row + 1,
+                                column: node.start_position().column + 1,
+                                receiver_type: None,
                             });
                         }
                     }
@@ -341,6 +381,8 @@ fn extract_python_calls_imports(content: &str) -> Result<(Vec<RelationRef>, Vec<
                                 alias: None,
                                 context: None,
                                 line: node.start_position().row + 1,
+                                column: node.start_position().column + 1,
+                                receiver_type: None,
                             });
                         }
                     } else if child.kind() == "aliased_import" {
@@ -357,6 +399,8 @@ fn extract_python_calls_imports(content: &str) -> Result<(Vec<RelationRef>, Vec<
                                      alias: Some(alias.to_string()),
                                      context: None,
                                      line: node.start_position().row + 1,
+                                     column: node.start_position().column + 1,
+                                     receiver_type: None,
                                  });
                              }
                          }
@@ -376,6 +420,8 @@ fn extract_python_calls_imports(content: &str) -> Result<(Vec<RelationRef>, Vec<
                                          alias: None,
                                          context: None,
                                          line: node.start_position().row + 1,
+                                         column: node.start_position().column + 1,
+                                         receiver_type: None,
                                      });
                                  }
                              } else if child.kind() == "aliased_import" {
@@ -392,6 +438,8 @@ fn extract_python_calls_imports(content: &str) -> Result<(Vec<RelationRef>, Vec<
                                              alias: Some(alias.to_string()),
                                              context: None,
                                              line: node.start_position().row + 1,
+                                             column: node.start_position().column + 1,
+                                             receiver_type: None,
                                          });
                                      }
                                  }
@@ -412,6 +460,7 @@ fn extract_rust_calls_imports(content: &str) -> Result<(Vec<RelationRef>, Vec<Re
         .set_language(&tree_sitter_rust::LANGUAGE.into())
         .map_err(|e| anyhow!("Failed to set language: {}", e))?;
     let tree = parser.parse(content, None).ok_or_else(|| anyhow!("Failed to parse content"))?;
+    let types = TypeEnv::new(tree.root_node(), content);
     let mut calls = Vec::new();
     let mut imports = Vec::new();
     
@@ -435,11 +484,14 @@ fn extract_rust_calls_imports(content: &str) -> Result<(Vec<RelationRef>, Vec<Re
                                 }
                             }
 
+                            let receiver_type = context.as_deref().and_then(|c| types.receiver_type(node, c));
                             calls.push(RelationRef {
                                 name,
                                 alias: None,
                                 context,
                                 line: node.start_position().row + 1,
+                                column: node.start_position().column + 1,
+                                receiver_type,
                             });
                         }
                     }
@@ -456,6 +508,8 @@ fn extract_rust_calls_imports(content: &str) -> Result<(Vec<RelationRef>, Vec<Re
                             alias: None,
                             context: None,
                             line: node.start_position().row + 1,
+                            column: node.start_position().column + 1,
+                            receiver_type: None,
                         });
                     }
                 }
@@ -485,6 +539,8 @@ fn extract_go_calls_imports(content: &str) -> Result<(Vec<RelationRef>, Vec<Rela
                             alias: None,
                             context: None,
                             line: node.start_position().row + 1,
+                            column: node.start_position().column + 1,
+                            receiver_type: None,
                         });
                     }
                 }
@@ -499,6 +555,8 @@ fn extract_go_calls_imports(content: &str) -> Result<(Vec<RelationRef>, Vec<Rela
                                 alias: None,
                                 context: None,
                                 line: node.start_position().row + 1,
+                                column: node.start_position().column + 1,
+                                receiver_type: None,
                             });
                         }
                     }
@@ -543,6 +601,7 @@ fn my_func() {
         
         let foo_call = find_call(&calls, "foo").unwrap();
         assert_eq!(foo_call.line, 3, "Line number mismatch for foo()");
+        assert_eq!(foo_call.column, 13, "Column mismatch for foo()");
         
         let bar_call = find_call(&calls, "obj.bar").unwrap();
         assert_eq!(bar_call.context, Some("obj".to_string()), "Context not captured for obj.bar");
@@ -0,0 +1,138 @@
+//! Occurrences of a name for workspace-wide renames
+//!
+//! Every whole-word match of the name is an occurrence. Where a tree-sitter
+//! grammar is bundled, matches that parse as identifier nodes are marked
+//! verified; the rest sit in comments, strings or files without a grammar
+//! and need a look before they are rewritten.
+
+use crate::models::Language;
+use crate::regions::views;
+use crate::tags_extractor::ts_language;
+use std::collections::HashSet;
+
+/// One whole-word match of a name in a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Occurrence {
+    /// 1-based line of the match
+    pub line: usize,
+    /// Byte offset of the match
+    pub start: usize,
+    /// Byte offset just past the match
+    pub end: usize,
+    /// Whether the match is an identifier node in the syntax tree
+    pub verified: bool,
+}
+
+/// Whether `name` can stand in for an identifier in the supported languages.
+pub fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_') && chars.all(is_word_char)
+}
+
+/// Whole-word matches of `name` in `content`, in file order.
+pub fn find_occurrences(content: &str, language: &Language, name: &str) -> Vec<Occurrence> {
+    if name.is_empty() {
+        return Vec::new();
+    }
+    let identifiers = identifier_starts(content, language, name);
+    content
+        .match_indices(name)
+        .map(|(start, _)| (start, start + name.len()))
+        .filter(|&(start, end)| {
+            !content[..start].chars().next_back().is_some_and(is_word_char)
+                && !content[end..].chars().next().is_some_and(is_word_char)
+        })
+        .map(|(start, end)| Occurrence {
+            line: content[..start].matches('\n').count() + 1,
+            start,
+            end,
+            verified: identifiers.contains(&start),
+        })
+        .collect()
+}
+
+/// `content` with each of `occurrences` replaced by `new_name`.
+pub fn replace_occurrences<'a>(
+    content: &str,
+    occurrences: impl IntoIterator<Item = &'a Occurrence>,
+    new_name: &str,
+) -> String {
+    let mut ranges: Vec<(usize, usize)> = occurrences.into_iter().map(|o| (o.start, o.end)).collect();
+    ranges.sort_unstable();
+
+    let mut out = String::with_capacity(content.len());
+    let mut pos = 0;
+    for (start, end) in ranges {
+        if start < pos {
+            continue;
+        }
+        out.push_str(&content[pos..start]);
+        out.push_str(new_name);
+        pos = end;
+    }
+    out.push_str(&content[pos..]);
+    out
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Start offsets of identifier leaves spelling `name`. Grammars name these
+/// `identifier`, `type_identifier`, `field_identifier` and so on. Template
+/// files are parsed one embedded language at a time.
+fn identifier_starts(content: &str, language: &Language, name: &str) -> HashSet<usize> {
+    let mut starts = HashSet::new();
+    for (language, view) in views(language, content) {
+        let Some(grammar) = ts_language(&language) else {
+            continue;
+        };
+        let mut parser = tree_sitter::Parser::new();
+        if parser.set_language(&grammar).is_err() {
+            continue;
+        }
+        let Some(tree) = parser.parse(view.as_ref(), None) else {
+            continue;
+        };
+
+        let mut stack = vec![tree.root_node()];
+        while let Some(node) = stack.pop() {
+            if node.child_count() > 0 {
+                stack.extend(node.children(&mut node.walk()));
+            } else if node.kind().ends_with("identifier") && view.get(node.byte_range()) == Some(name) {
+                starts.insert(node.start_byte());
+            }
+        }
+    }
+    starts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verifies_identifiers_only() {
+        let content = "// parse the input\nfn parse(s: &str) -> Parsed {\n    let msg = \"parse failed\";\n    parse_inner(s)\n}\nfn main() { parse(\"x\"); }\n";
+        let found = find_occurrences(content, &Language::Rust, "parse");
+        let summary: Vec<(usize, bool)> = found.iter().map(|o| (o.line, o.verified)).collect();
+        assert_eq!(summary, vec![(1, false), (2, true), (3, false), (6, true)]);
+
+        let verified: Vec<&Occurrence> = found.iter().filter(|o| o.verified).collect();
+        let renamed = replace_occurrences(content, verified, "parse_args");
+        assert!(renamed.contains("fn parse_args(s: &str)"));
+        assert!(renamed.contains("parse_args(\"x\")"));
+        assert!(renamed.contains("// parse the input"));
+        assert!(renamed.contains("parse_inner(s)"));
+    }
+
+    #[test]
+    fn test_unknown_language_is_unverified() {
+        let found = find_occurrences("call parse here", &Language::Unknown, "parse");
+        assert_eq!(found.len(), 1);
+        assert!(!found[0].verified);
+        assert!(is_identifier("parse_args"));
+        assert!(!is_identifier("2fast"));
+        assert!(!is_identifier("a::b"));
+    }
+}
@@ -0,0 +1,243 @@
+//! Receiver types of Rust calls, inferred from the syntax of one file.
+//!
+//! No type checking happens here: the type of `x` in `x.save()` is read off
+//! the nearest binding of `x` in scope, from its annotation (`x: &Store`) or
+//! from an initializer whose type is evident (`Store::open(..)?`,
+//! `Store { .. }`). `self` is the type of the enclosing `impl`, and
+//! `self.field` the declared type of the field when the struct is defined in
+//! the same file. Smart pointers (`Arc`, `Rc`, `Box`) are seen through, since
+//! method calls auto-deref. Anything else is left unknown.
+
+use std::collections::HashMap;
+use tree_sitter::Node;
+
+/// Pointer types whose methods are their pointee's.
+const WRAPPERS: &[&str] = &["Arc", "Rc", "Box", "Cow"];
+/// Methods whose result has the receiver's type, for `Store::open(..).unwrap()`.
+const PASS_THROUGH: &[&str] = &["unwrap", "expect", "clone", "unwrap_or_default", "to_owned"];
+
+pub struct TypeEnv<'a> {
+    content: &'a str,
+    /// Struct name -> field name -> field type as written.
+    fields: HashMap<String, HashMap<String, String>>,
+}
+
+impl<'a> TypeEnv<'a> {
+    pub fn new(root: Node<'a>, content: &'a str) -> Self {
+        let mut env = Self { content, fields: HashMap::new() };
+        let mut stack = vec![root];
+        while let Some(node) = stack.pop() {
+            if node.kind() == "struct_item" {
+                env.collect_fields(node);
+            }
+            stack.extend(node.named_children(&mut node.walk()));
+        }
+        env
+    }
+
+    fn collect_fields(&mut self, item: Node) {
+        let (Some(name), Some(body)) = (item.child_by_field_name("name"), item.child_by_field_name("body")) else {
+            return;
+        };
+        let struct_name = self.text(name).to_string();
+        let fields = self.fields.entry(struct_name).or_default();
+        for field in body.named_children(&mut body.walk()) {
+            if let (Some(field_name), Some(ty)) = (field.child_by_field_name("name"), field.child_by_field_name("type")) {
+                fields.insert(self.content[field_name.byte_range()].to_string(), self.content[ty.byte_range()].to_string());
+            }
+        }
+    }
+
+    fn text(&self, node: Node) -> &'a str {
+        &self.content[node.byte_range()]
+    }
+
+    /// The type a call's `context` refers to: a type path (`Store::open`,
+    /// `Self::new`) or the receiver of a method call (`store.save()`).
+    /// `None` for module paths and receivers of unknown type.
+    pub fn receiver_type(&self, call: Node, context: &str) -> Option<String> {
+        let function = call.child_by_field_name("function")?;
+        if function.kind() == "field_expression" {
+            return self.expr_type(function.child_by_field_name("value")?);
+        }
+        normalize_type(context, self.self_type(call).as_deref())
+    }
+
+    /// The type of the innermost `impl` around `node`.
+    fn self_type(&self, node: Node) -> Option<String> {
+        let mut curr = node;
+        while let Some(parent) = curr.parent() {
+            if parent.kind() == "impl_item" {
+                return normalize_type(self.text(parent.child_by_field_name("type")?), None);
+            }
+            curr = parent;
+        }
+        None
+    }
+
+    fn expr_type(&self, expr: Node) -> Option<String> {
+        match expr.kind() {
+            "self" => self.self_type(expr),
+            "identifier" => self.binding_type(expr, self.text(expr)),
+            "field_expression" => {
+                let owner = self.expr_type(expr.child_by_field_name("value")?)?;
+                let field = self.text(expr.child_by_field_name("field")?);
+                let ty = self.fields.get(type_name(&owner))?.get(field)?;
+                normalize_type(ty, Some(&owner))
+            }
+            "try_expression" | "parenthesized_expression" => self.expr_type(expr.named_child(0)?),
+            "reference_expression" => self.expr_type(expr.child_by_field_name("value")?),
+            "struct_expression" => normalize_type(self.text(expr.child_by_field_name("name")?), self.self_type(expr).as_deref()),
+            "call_expression" => {
+                let function = expr.child_by_field_name("function")?;
+                match function.kind() {
+                    "field_expression" => {
+                        let method = self.text(function.child_by_field_name("field")?);
+                        if !PASS_THROUGH.contains(&method) {
+                            return None;
+                        }
+                        self.expr_type(function.child_by_field_name("value")?)
+                    }
+                    // Associated functions of a type are taken to be constructors.
+                    "scoped_identifier" => {
+                        normalize_type(self.text(function.child_by_field_name("path")?), self.self_type(expr).as_deref())
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// The type of `name` at `at`: the closest preceding `let` in an enclosing
+    /// block, else a parameter of the enclosing function or closure.
+    fn binding_type(&self, at: Node, name: &str) -> Option<String> {
+        let mut curr = at;
+        while let Some(parent) = curr.parent() {
+            match parent.kind() {
+                "block" => {
+                    let lets = parent
+                        .named_children(&mut parent.walk())
+                        .filter(|s| s.kind() == "let_declaration" && s.end_byte() <= at.start_byte())
+                        .filter(|s| s.child_by_field_name("pattern").is_some_and(|p| self.binds(p, name)))
+                        .last();
+                    if let Some(binding) = lets {
+                        return match binding.child_by_field_name("type") {
+                            Some(ty) => normalize_type(self.text(ty), self.self_type(binding).as_deref()),
+                            None => self.expr_type(binding.child_by_field_name("value")?),
+                        };
+                    }
+                }
+                "function_item" | "closure_expression" => {
+                    let params = parent.child_by_field_name("parameters")?;
+                    for param in params.named_children(&mut params.walk()) {
+                        if param.child_by_field_name("pattern").is_some_and(|p| self.binds(p, name)) {
+                            return normalize_type(self.text(param.child_by_field_name("type")?), self.self_type(parent).as_deref());
+                        }
+                    }
+                    // Closures capture the bindings around them; functions don't.
+                    if parent.kind() == "function_item" {
+                        return None;
+                    }
+                }
+                _ => {}
+            }
+            curr = parent;
+        }
+        None
+    }
+
+    /// Whether `pattern` is `name` or `mut name`.
+    fn binds(&self, pattern: Node, name: &str) -> bool {
+        let pattern = match pattern.kind() {
+            "mut_pattern" => pattern.named_child(0),
+            _ => Some(pattern),
+        };
+        pattern.is_some_and(|p| p.kind() == "identifier" && self.text(p) == name)
+    }
+}
+
+/// The type a written type or type path names, without references,
+/// lifetimes, smart pointers or generic arguments (`&'a mut Arc<Store<T>>`
+/// -> `Store`). `Self` becomes `self_type`. `None` unless the last segment
+/// is capitalised, so module paths (`fs::read`) are not taken for types.
+pub fn normalize_type(written: &str, self_type: Option<&str>) -> Option<String> {
+    let mut ty = written.trim();
+    loop {
+        ty = ty.trim_start_matches('&').trim_start();
+        if ty.starts_with('\'') {
+            ty = ty.split_once(char::is_whitespace).map_or("", |(_, rest)| rest).trim_start();
+        }
+        let stripped = ["mut ", "dyn ", "impl "].iter().find_map(|p| ty.strip_prefix(p));
+        if let Some(rest) = stripped {
+            ty = rest.trim_start();
+            continue;
+        }
+        match (ty.find('<'), ty.rfind('>')) {
+            (Some(open), Some(close)) if open < close && WRAPPERS.contains(&type_name(&ty[..open])) => {
+                ty = &ty[open + 1..close];
+            }
+            _ => break,
+        }
+    }
+    let path = ty.split('<').next()?.trim().trim_end_matches("::");
+    if path == "Self" {
+        return self_type.map(str::to_string);
+    }
+    type_name(path).starts_with(|c: char| c.is_ascii_uppercase()).then(|| path.to_string())
+}
+
+/// The last segment of a type path.
+pub fn type_name(path: &str) -> &str {
+    path.rsplit("::").next().unwrap_or(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Language;
+    use crate::relations::extract_calls_imports;
+
+    #[test]
+    fn test_normalize_type() {
+        assert_eq!(normalize_type("&'a mut Arc<Store<T>>", None).as_deref(), Some("Store"));
+        assert_eq!(normalize_type("crate::db::Store", None).as_deref(), Some("crate::db::Store"));
+        assert_eq!(normalize_type("Self", Some("Store")).as_deref(), Some("Store"));
+        assert_eq!(normalize_type("fs", None), None);
+    }
+
+    #[test]
+    fn test_receiver_types_of_rust_calls() {
+        let code = r#"
+struct Indexer {
+    store: Arc<Store>,
+}
+
+impl Indexer {
+    fn run(&self, cache: &mut Cache) {
+        self.store.save();
+        cache.evict();
+        let client = Client::connect(&url)?;
+        client.send();
+        let other: Other = make();
+        other.send();
+        self.flush();
+        Self::helper();
+        helpers::format();
+        unknown().send();
+    }
+}
+"#;
+        let (calls, _) = extract_calls_imports(&Language::Rust, code).unwrap();
+        let receiver = |name: &str| calls.iter().find(|c| c.name == name).unwrap().receiver_type.clone();
+        assert_eq!(receiver("self.store.save").as_deref(), Some("Store"));
+        assert_eq!(receiver("cache.evict").as_deref(), Some("Cache"));
+        assert_eq!(receiver("client.send").as_deref(), Some("Client"));
+        assert_eq!(receiver("other.send").as_deref(), Some("Other"));
+        assert_eq!(receiver("self.flush").as_deref(), Some("Indexer"));
+        assert_eq!(receiver("helper").as_deref(), Some("Indexer"));
+        assert_eq!(receiver("connect").as_deref(), Some("Client"));
+        assert_eq!(receiver("format"), None);
+        assert_eq!(receiver("unknown().send"), None);
+    }
+}
@@ -1,7 +1,11 @@
-use crate::models::Language;
-use emry_config::CoreConfig;
+use crate::models::{Language, NotIndexedReason};
+use emry_config::{CoreConfig, DocumentsConfig};
 use globset::{Glob, GlobSet, GlobSetBuilder};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
 use ignore::WalkBuilder;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::io::Read;
 use std::path::{Path, PathBuf};
 use tracing::{trace};
 
@@ -11,15 +15,149 @@ pub struct ScannedFile {
     pub language: Language,
 }
 
-pub fn scan_repo(root: &Path, config: &CoreConfig) -> Vec<ScannedFile> {
-    const DEFAULT_EXCLUDES: &[&str] = &[
-        "node_modules/**",
-        "dist/**",
-        "build/**",
-        "target/**",
-        ".git/**",
-    ];
+/// Paths no scan should descend into, on top of `core.exclude_paths`.
+const DEFAULT_EXCLUDES: &[&str] = &[
+    "node_modules/**",
+    "dist/**",
+    "build/**",
+    "target/**",
+    ".git/**",
+    ".codeindex/**",
+];
 
+/// Bytes inspected for NUL bytes when deciding whether a file is binary.
+const BINARY_SNIFF_BYTES: usize = 8000;
+
+/// Files `files_containing` reads at most, and the largest it reads.
+const CONTAINING_MAX_FILES: usize = 20_000;
+const CONTAINING_MAX_BYTES: u64 = 16 * 1024 * 1024;
+
+/// Why a file with a known language was left out of a scan.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum SkipReason {
+    /// Larger than `core.max_file_size_kb`.
+    TooLarge { size_kb: u64 },
+    /// Contains NUL bytes despite its extension.
+    Binary,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkippedFile {
+    pub path: PathBuf,
+    #[serde(flatten)]
+    pub reason: SkipReason,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ScanReport {
+    pub files: Vec<ScannedFile>,
+    pub skipped: Vec<SkippedFile>,
+}
+
+pub fn scan_repo(root: &Path, config: &CoreConfig, documents: &DocumentsConfig) -> ScanReport {
+    trace!("Scanning root: {}", root.display());
+    let mut report = ScanReport::default();
+    for path in walk_repo(root, config, config.respect_gitignore) {
+        if let Some(ext) = path.extension().and_then(|s| s.to_str()) {
+            let lang = Language::from_extension(ext);
+            if lang.is_document() && !documents.enabled {
+                continue;
+            }
+            if lang == Language::Unknown {
+                continue;
+            }
+            match skip_reason(&path, config.max_file_size_kb) {
+                Some(reason) => report.skipped.push(SkippedFile { path, reason }),
+                None => report.files.push(ScannedFile { path, language: lang }),
+            }
+        }
+    }
+    report
+}
+
+/// Whether a file is too large (`max_size_kb` > 0) or looks binary, the way
+/// git decides: a NUL byte in its first few kilobytes.
+fn skip_reason(path: &Path, max_size_kb: u64) -> Option<SkipReason> {
+    let size = std::fs::metadata(path).ok()?.len();
+    if max_size_kb > 0 && size > max_size_kb * 1024 {
+        return Some(SkipReason::TooLarge { size_kb: size.div_ceil(1024) });
+    }
+    let mut head = Vec::with_capacity(BINARY_SNIFF_BYTES);
+    std::fs::File::open(path).ok()?.take(BINARY_SNIFF_BYTES as u64).read_to_end(&mut head).ok()?;
+    head.contains(&0).then_some(SkipReason::Binary)
+}
+
+/// Why `scan_repo` leaves out `path`, or `None` when it scans it. `walked`
+/// is what `walk_repo` returns with `config.respect_gitignore`.
+pub fn exclusion(
+    root: &Path,
+    path: &Path,
+    config: &CoreConfig,
+    documents: &DocumentsConfig,
+    walked: &HashSet<PathBuf>,
+) -> Option<NotIndexedReason> {
+    let rel = path.strip_prefix(root).unwrap_or(path).to_string_lossy().replace('\\', "/");
+    let matches = |pattern: &str| Glob::new(pattern).is_ok_and(|g| g.compile_matcher().is_match(&rel));
+    if let Some(pattern) = config
+        .exclude_paths
+        .iter()
+        .map(String::as_str)
+        .chain(DEFAULT_EXCLUDES.iter().copied())
+        .find(|p| matches(p))
+    {
+        return Some(NotIndexedReason::Excluded { pattern: pattern.to_string() });
+    }
+    if !config.include_paths.is_empty() && !config.include_paths.iter().any(|p| matches(p)) {
+        return Some(NotIndexedReason::NotIncluded);
+    }
+    if !walked.contains(path) {
+        return Some(NotIndexedReason::Ignored);
+    }
+    let extension = path.extension().and_then(|s| s.to_str()).unwrap_or("");
+    let lang = Language::from_extension(extension);
+    if lang == Language::Unknown {
+        return Some(NotIndexedReason::UnsupportedLanguage { extension: extension.to_string() });
+    }
+    if lang.is_document() && !documents.enabled {
+        return Some(NotIndexedReason::DocumentsDisabled);
+    }
+    match skip_reason(path, config.max_file_size_kb)? {
+        SkipReason::TooLarge { size_kb } => Some(NotIndexedReason::TooLarge { size_kb, limit_kb: config.max_file_size_kb }),
+        SkipReason::Binary => Some(NotIndexedReason::Binary),
+    }
+}
+
+/// Up to `limit` files under `root` containing every one of `terms`
+/// (lowercase), ignoring case. Ignore files, hidden files and excludes don't
+/// apply, so files the index leaves out are found too; `.git` and
+/// `.codeindex` are skipped.
+pub fn files_containing(root: &Path, terms: &[String], limit: usize) -> Vec<PathBuf> {
+    WalkBuilder::new(root)
+        .standard_filters(false)
+        .filter_entry(|e| !matches!(e.file_name().to_str(), Some(".git" | ".codeindex")))
+        .build()
+        .filter_map(|entry| entry.ok())
+        .filter(|e| e.file_type().is_some_and(|t| t.is_file()))
+        .take(CONTAINING_MAX_FILES)
+        .filter(|e| e.metadata().is_ok_and(|m| m.len() <= CONTAINING_MAX_BYTES))
+        .filter(|e| {
+            std::fs::read(e.path()).is_ok_and(|bytes| {
+                let text = String::from_utf8_lossy(&bytes).to_lowercase();
+                terms.iter().all(|t| text.contains(t.as_str()))
+            })
+        })
+        .map(|e| e.into_path())
+        .take(limit)
+        .collect()
+}
+
+/// Files under `root` matching `core.include_paths` and neither
+/// `core.exclude_paths` nor the default excludes. With `respect_ignore`,
+/// `.gitignore` files at every depth (inside a git repository or not),
+/// `.git/info/exclude` and `.ignore` files apply and hidden files are skipped.
+/// Indexing and regex search share this walk, so they agree on what exists.
+pub fn walk_repo(root: &Path, config: &CoreConfig, respect_ignore: bool) -> Vec<PathBuf> {
     let include_set = build_globset(if config.include_paths.is_empty() {
         vec!["**/*".to_string()]
     } else {
@@ -30,58 +168,96 @@ pub fn scan_repo(root: &Path, config: &CoreConfig) -> Vec<ScannedFile> {
     exclude_patterns.extend(DEFAULT_EXCLUDES.iter().map(|s| s.to_string()));
     let exclude_set = build_globset(exclude_patterns);
 
-    let mut files = Vec::new();
-    let builder = WalkBuilder::new(root);
-    let walker = builder.build();
+    let walker = WalkBuilder::new(root)
+        .hidden(respect_ignore)
+        .ignore(respect_ignore)
+        .git_ignore(respect_ignore)
+        .git_exclude(respect_ignore)
+        .git_global(respect_ignore)
+        .parents(respect_ignore)
+        .require_git(false)
+        .build();
 
-    println!("Scanning root: {}", root.display());
+    let mut files = Vec::new();
     for result in walker {
-        match result {
-            Ok(entry) => {
-                if entry.file_type().map_or(false, |ft| ft.is_file()) {
-                    let path = entry.path();
-                    let rel_path = path.strip_prefix(root).unwrap_or(path);
-                    let rel_str = rel_path.to_string_lossy();
-
-                    if let Some(set) = &include_set {
-                        if !set.is_match(rel_str.as_ref()) {
-                            continue;
-                        }
-                    }
-                    if let Some(set) = &exclude_set {
-                        if set.is_match(rel_str.as_ref()) {
-                            continue;
-                        }
-                    }
-
-                    if let Some(ext) = path.extension().and_then(|s| s.to_str()) {
-                        let lang = Language::from_extension(ext);
-                        if path.to_string_lossy().contains("search.rs") {
-                            trace!(
-                                "Path: {}, Ext: {:?}, Lang: {:?}",
-                                path.display(),
-                                ext,
-                                lang
-                            );
-                        }
-                        if lang != Language::Unknown {
-                            files.push(ScannedFile {
-                                path: path.to_path_buf(),
-                                language: lang,
-                            });
-                        }
-                    }
-                }
-            }
+        let entry = match result {
+            Ok(entry) => entry,
             Err(err) => {
                 eprintln!("Error scanning path: {}", err);
+                continue;
             }
+        };
+        if !entry.file_type().map_or(false, |ft| ft.is_file()) {
+            continue;
         }
-    }
+        let path = entry.path();
+        let rel_path = path.strip_prefix(root).unwrap_or(path);
+        let rel_str = rel_path.to_string_lossy();
 
+        if let Some(set) = &include_set {
+            if !set.is_match(rel_str.as_ref()) {
+                continue;
+            }
+        }
+        if let Some(set) = &exclude_set {
+            if set.is_match(rel_str.as_ref()) {
+                continue;
+            }
+        }
+        files.push(path.to_path_buf());
+    }
     files
 }
 
+/// `walk_repo`'s rules for single paths, cheap enough to check each file
+/// event against: the include and exclude globs and, with
+/// `core.respect_gitignore`, hidden paths and the root's `.gitignore`,
+/// `.ignore` and `.git/info/exclude`. Ignore files in subdirectories are not
+/// read, so a path it keeps may still be left out of a walk.
+pub struct PathFilter {
+    root: PathBuf,
+    include: Option<GlobSet>,
+    exclude: Option<GlobSet>,
+    ignore: Option<Gitignore>,
+}
+
+impl PathFilter {
+    pub fn new(root: &Path, config: &CoreConfig) -> Self {
+        let mut exclude_patterns: Vec<String> = config.exclude_paths.clone();
+        exclude_patterns.extend(DEFAULT_EXCLUDES.iter().map(|s| s.to_string()));
+        let ignore = config.respect_gitignore.then(|| {
+            let mut builder = GitignoreBuilder::new(root);
+            for file in [".gitignore", ".ignore", ".git/info/exclude"] {
+                // Missing files are fine; they just add no rules.
+                let _ = builder.add(root.join(file));
+            }
+            builder.build().ok()
+        });
+        Self {
+            root: root.to_path_buf(),
+            include: build_globset(config.include_paths.clone()),
+            exclude: build_globset(exclude_patterns),
+            ignore: ignore.flatten(),
+        }
+    }
+
+    /// Whether `walk_repo` leaves `path` out.
+    pub fn excludes(&self, path: &Path) -> bool {
+        let Ok(rel) = path.strip_prefix(&self.root) else { return true };
+        let rel_str = rel.to_string_lossy();
+        if self.include.as_ref().is_some_and(|set| !set.is_match(rel_str.as_ref())) {
+            return true;
+        }
+        if self.exclude.as_ref().is_some_and(|set| set.is_match(rel_str.as_ref())) {
+            return true;
+        }
+        self.ignore.as_ref().is_some_and(|ignore| {
+            rel.components().any(|c| c.as_os_str().to_string_lossy().starts_with('.'))
+                || ignore.matched_path_or_any_parents(rel, false).is_ignore()
+        })
+    }
+}
+
 pub fn build_globset(patterns: Vec<String>) -> Option<GlobSet> {
     if patterns.is_empty() {
         return None;
@@ -102,3 +278,123 @@ pub fn build_globset(patterns: Vec<String>) -> Option<GlobSet> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_walk_honors_nested_gitignore_without_git() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("app/generated")).unwrap();
+        std::fs::write(root.join("main.rs"), "fn main() {}").unwrap();
+        std::fs::write(root.join("app/lib.rs"), "").unwrap();
+        std::fs::write(root.join("app/generated/api.rs"), "").unwrap();
+        std::fs::write(root.join("app/.gitignore"), "generated/\n").unwrap();
+
+        let config = CoreConfig::default();
+        let rel = |paths: Vec<PathBuf>| {
+            let mut rel: Vec<String> = paths
+                .iter()
+                .map(|p| p.strip_prefix(root).unwrap().to_string_lossy().replace('\\', "/"))
+                .collect();
+            rel.sort();
+            rel
+        };
+        assert_eq!(rel(walk_repo(root, &config, true)), vec!["app/lib.rs", "main.rs"]);
+        assert_eq!(
+            rel(walk_repo(root, &config, false)),
+            vec!["app/.gitignore", "app/generated/api.rs", "app/lib.rs", "main.rs"]
+        );
+    }
+
+    #[test]
+    fn test_path_filter_follows_walk_rules() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::write(root.join(".gitignore"), "generated/\n*.log\n").unwrap();
+        let filter = PathFilter::new(root, &CoreConfig::default());
+
+        assert!(!filter.excludes(&root.join("src/main.rs")));
+        assert!(filter.excludes(&root.join("target/debug/build.rs")));
+        assert!(filter.excludes(&root.join(".codeindex/branches/main/surreal.db/000012.log")));
+        assert!(filter.excludes(&root.join("generated/api.rs")));
+        assert!(filter.excludes(&root.join("src/debug.log")));
+        assert!(filter.excludes(&root.join(".github/workflows/ci.yml")));
+        assert!(filter.excludes(Path::new("/elsewhere/main.rs")));
+
+        let config = CoreConfig { respect_gitignore: false, ..Default::default() };
+        assert!(!PathFilter::new(root, &config).excludes(&root.join("generated/api.rs")));
+    }
+
+    #[test]
+    fn test_skips_large_and_binary_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::write(root.join("small.rs"), "fn main() {}").unwrap();
+        std::fs::write(root.join("bundle.js"), "x".repeat(3 * 1024)).unwrap();
+        std::fs::write(root.join("blob.py"), b"\x00\x01binary").unwrap();
+
+        let config = CoreConfig { max_file_size_kb: 2, ..Default::default() };
+        let report = scan_repo(root, &config, &DocumentsConfig::default());
+        assert_eq!(report.files.len(), 1);
+        let mut reasons: Vec<(String, SkipReason)> = report
+            .skipped
+            .into_iter()
+            .map(|s| (s.path.file_name().unwrap().to_string_lossy().into_owned(), s.reason))
+            .collect();
+        reasons.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(
+            reasons,
+            vec![
+                ("blob.py".to_string(), SkipReason::Binary),
+                ("bundle.js".to_string(), SkipReason::TooLarge { size_kb: 3 }),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_exclusion_explains_left_out_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        for dir in ["vendor", "node_modules/pkg", "generated"] {
+            std::fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        let files = [
+            ("main.rs", "fn main() { connect_pool() }".to_string()),
+            ("vendor/pool.rs", "fn connect_pool() {}".to_string()),
+            ("node_modules/pkg/index.js", "connect_pool()".to_string()),
+            ("generated/api.rs", "connect_pool();".to_string()),
+            ("notes.xyz", "CONNECT_POOL".to_string()),
+            ("big.rs", format!("// connect_pool\n{}", "x".repeat(3 * 1024 - 16))),
+            (".gitignore", "generated/\n".to_string()),
+        ];
+        for (path, content) in &files {
+            std::fs::write(root.join(path), content).unwrap();
+        }
+
+        let config = CoreConfig { exclude_paths: vec!["vendor/**".to_string()], max_file_size_kb: 2, ..Default::default() };
+        let documents = DocumentsConfig::default();
+        let walked: HashSet<PathBuf> = walk_repo(root, &config, config.respect_gitignore).into_iter().collect();
+        let mut found: Vec<(String, Option<NotIndexedReason>)> = files_containing(root, &["connect_pool".to_string()], 10)
+            .into_iter()
+            .map(|p| {
+                let reason = exclusion(root, &p, &config, &documents, &walked);
+                (p.strip_prefix(root).unwrap().to_string_lossy().replace('\\', "/"), reason)
+            })
+            .collect();
+        found.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(
+            found,
+            vec![
+                ("big.rs".to_string(), Some(NotIndexedReason::TooLarge { size_kb: 3, limit_kb: 2 })),
+                ("generated/api.rs".to_string(), Some(NotIndexedReason::Ignored)),
+                ("main.rs".to_string(), None),
+                ("node_modules/pkg/index.js".to_string(), Some(NotIndexedReason::Excluded { pattern: "node_modules/**".to_string() })),
+                ("notes.xyz".to_string(), Some(NotIndexedReason::UnsupportedLanguage { extension: "xyz".to_string() })),
+                ("vendor/pool.rs".to_string(), Some(NotIndexedReason::Excluded { pattern: "vendor/**".to_string() })),
+            ]
+        );
+    }
+}
@@ -0,0 +1,80 @@
+//! Path scopes restricting which files retrieval may return.
+//!
+//! A scope is a set of globs relative to the repo root (`services/auth/**`).
+//! A pattern without glob characters is taken as a directory and also
+//! matches everything beneath it.
+
+use crate::error::EmryError;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use std::path::{Component, Path, PathBuf};
+
+const GLOB_CHARS: &[char] = &['*', '?', '[', '{'];
+
+#[derive(Debug, Clone)]
+pub struct PathScope {
+    patterns: Vec<String>,
+    set: GlobSet,
+    /// Directory part of each pattern before its first glob character.
+    roots: Vec<PathBuf>,
+}
+
+impl PathScope {
+    pub fn new(patterns: &[String]) -> Result<Self, EmryError> {
+        let mut builder = GlobSetBuilder::new();
+        let mut roots = Vec::with_capacity(patterns.len());
+        for raw in patterns {
+            let pattern = raw.trim_start_matches("./").trim_end_matches('/');
+            let invalid = |e: globset::Error| EmryError::InvalidQuery(format!("invalid scope '{}': {}", raw, e));
+            builder.add(Glob::new(pattern).map_err(invalid)?);
+
+            match pattern.find(GLOB_CHARS) {
+                Some(i) => roots.push(PathBuf::from(pattern[..i].rsplit_once('/').map_or("", |(dir, _)| dir))),
+                None => {
+                    builder.add(Glob::new(&format!("{}/**", pattern)).map_err(invalid)?);
+                    roots.push(PathBuf::from(pattern));
+                }
+            }
+        }
+        let set = builder
+            .build()
+            .map_err(|e| EmryError::InvalidQuery(format!("invalid scope: {}", e)))?;
+        Ok(Self { patterns: patterns.to_vec(), set, roots })
+    }
+
+    pub fn patterns(&self) -> &[String] {
+        &self.patterns
+    }
+
+    /// Whether a repo-relative file path is inside the scope.
+    pub fn contains(&self, path: &Path) -> bool {
+        self.set.is_match(normalize(path))
+    }
+
+    /// Whether a repo-relative directory is in scope or leads to something that is,
+    /// so it can still be listed while navigating towards the scoped files.
+    pub fn may_contain(&self, dir: &Path) -> bool {
+        let dir = normalize(dir);
+        self.contains(&dir) || self.roots.iter().any(|root| root.starts_with(&dir) || dir.starts_with(root))
+    }
+}
+
+fn normalize(path: &Path) -> PathBuf {
+    path.components().filter(|c| !matches!(c, Component::CurDir)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_and_directory_scopes() {
+        let scope = PathScope::new(&["services/auth/**".to_string(), "./lib/util".to_string()]).unwrap();
+        assert!(scope.contains(Path::new("services/auth/token.rs")));
+        assert!(scope.contains(Path::new("./lib/util/strings.rs")));
+        assert!(!scope.contains(Path::new("services/billing/invoice.rs")));
+
+        assert!(scope.may_contain(Path::new("services")));
+        assert!(scope.may_contain(Path::new("")));
+        assert!(!scope.may_contain(Path::new("services/billing")));
+    }
+}
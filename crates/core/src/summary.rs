@@ -0,0 +1,122 @@
+//! Bottom-up summaries of a codebase: each file, then each directory
+//! (module) from its files' and subdirectories' summaries, then the
+//! repository from the top-level ones.
+//!
+//! A summary is keyed by level and path and carries the hash of its input:
+//! the file's content hash for files, and a hash of the children's hashes
+//! for modules and the repository, so a changed file invalidates exactly
+//! the summaries above it.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SummaryLevel {
+    File,
+    Module,
+    Repo,
+}
+
+impl SummaryLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SummaryLevel::File => "file",
+            SummaryLevel::Module => "module",
+            SummaryLevel::Repo => "repo",
+        }
+    }
+}
+
+/// A directory to summarize from its direct children.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModulePlan {
+    /// Directory relative to the repository root; empty for the root, which
+    /// is summarized at [`SummaryLevel::Repo`].
+    pub path: String,
+    pub files: Vec<String>,
+    pub modules: Vec<String>,
+}
+
+impl ModulePlan {
+    pub fn level(&self) -> SummaryLevel {
+        if self.path.is_empty() {
+            SummaryLevel::Repo
+        } else {
+            SummaryLevel::Module
+        }
+    }
+}
+
+/// The directory `path` (relative, `/`-separated) is in; empty at the root.
+pub fn module_of(path: &str) -> &str {
+    path.rsplit_once('/').map_or("", |(dir, _)| dir)
+}
+
+/// Every directory holding one of `files`, deepest first so each comes after
+/// its subdirectories, ending with the root.
+pub fn module_plan(files: &[String]) -> Vec<ModulePlan> {
+    let mut dirs: BTreeMap<String, (BTreeSet<String>, BTreeSet<String>)> = BTreeMap::new();
+    dirs.entry(String::new()).or_default();
+    for file in files {
+        let mut dir = module_of(file).to_string();
+        dirs.entry(dir.clone()).or_default().0.insert(file.clone());
+        while !dir.is_empty() {
+            let parent = module_of(&dir).to_string();
+            dirs.entry(parent.clone()).or_default().1.insert(dir);
+            dir = parent;
+        }
+    }
+
+    let mut plan: Vec<ModulePlan> = dirs
+        .into_iter()
+        .map(|(path, (files, modules))| ModulePlan { path, files: files.into_iter().collect(), modules: modules.into_iter().collect() })
+        .collect();
+    plan.sort_by(|a, b| depth(&b.path).cmp(&depth(&a.path)).then_with(|| a.path.cmp(&b.path)));
+    plan
+}
+
+fn depth(path: &str) -> usize {
+    if path.is_empty() {
+        0
+    } else {
+        path.matches('/').count() + 1
+    }
+}
+
+/// Hash of a summary's inputs, from each child's path and hash in order.
+pub fn children_hash<'a>(children: impl IntoIterator<Item = (&'a str, &'a str)>) -> String {
+    let mut hasher = Sha256::new();
+    for (path, hash) in children {
+        hasher.update(path.as_bytes());
+        hasher.update([0]);
+        hasher.update(hash.as_bytes());
+        hasher.update([0]);
+    }
+    hex::encode(hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_module_plan_is_bottom_up() {
+        let files: Vec<String> =
+            ["README.md", "src/lib.rs", "src/store/mod.rs", "src/store/sql/query.rs"].iter().map(|s| s.to_string()).collect();
+        let plan = module_plan(&files);
+        let order: Vec<&str> = plan.iter().map(|m| m.path.as_str()).collect();
+        assert_eq!(order, vec!["src/store/sql", "src/store", "src", ""]);
+
+        assert_eq!(plan[1].files, vec!["src/store/mod.rs"]);
+        assert_eq!(plan[1].modules, vec!["src/store/sql"]);
+        assert_eq!(plan[3].files, vec!["README.md"]);
+        assert_eq!(plan[3].modules, vec!["src"]);
+        assert_eq!(plan[3].level(), SummaryLevel::Repo);
+        assert_eq!(plan[0].level(), SummaryLevel::Module);
+
+        assert_ne!(children_hash([("a", "1"), ("b", "2")]), children_hash([("a", "1"), ("b", "3")]));
+        assert_ne!(children_hash([("ab", "")]), children_hash([("a", "b")]));
+    }
+}
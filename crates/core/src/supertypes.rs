@@ -0,0 +1,288 @@
+//! Inheritance and interface implementation declared in source.
+//!
+//! Each declaration is read off the syntax tree: `extends`/`implements`
+//! clauses (Java, TypeScript, JavaScript), base lists (Python, C#, C++),
+//! `impl Trait for Type` and supertraits (Rust). Types are reduced to their
+//! last path segment without generic arguments (`java.util.List<T>` ->
+//! `List`); resolving them to symbols is left to the store, by name.
+//! C# base lists don't say which entries are interfaces, so names following
+//! the `IName` convention are taken to be.
+
+use crate::models::{EdgeKind, Language};
+use crate::rust_types::{normalize_type, type_name};
+use crate::tags_extractor::ts_language;
+use anyhow::{anyhow, Result};
+use tree_sitter::Node;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SupertypeRef {
+    /// The declaring (or, for Rust, implementing) type
+    pub name: String,
+    pub supertype: String,
+    /// `Inherits` or `Implements`
+    pub kind: EdgeKind,
+    /// Line of the supertype in the declaration
+    pub line: usize,
+}
+
+/// Every supertype declared in `content`, in source order.
+pub fn extract_supertypes(language: &Language, content: &str) -> Result<Vec<SupertypeRef>> {
+    if language.is_template() {
+        let mut out = Vec::new();
+        for (sub, view) in crate::regions::views(language, content) {
+            if !sub.is_template() {
+                out.extend(extract_supertypes(&sub, &view)?);
+            }
+        }
+        return Ok(out);
+    }
+    let Some(grammar) = ts_language(language) else {
+        return Ok(Vec::new());
+    };
+    let mut parser = tree_sitter::Parser::new();
+    parser.set_language(&grammar).map_err(|e| anyhow!("Failed to set language: {}", e))?;
+    let tree = parser.parse(content, None).ok_or_else(|| anyhow!("Failed to parse content"))?;
+
+    let mut found = Declarations { content, out: Vec::new() };
+    let mut stack = vec![tree.root_node()];
+    while let Some(node) = stack.pop() {
+        match language {
+            Language::Rust => found.rust(node),
+            Language::Java => found.java(node),
+            Language::JavaScript | Language::TypeScript => found.js_ts(node),
+            Language::Python => found.python(node),
+            Language::CSharp => found.csharp(node),
+            Language::Cpp => found.cpp(node),
+            _ => {}
+        }
+        stack.extend(node.named_children(&mut node.walk()));
+    }
+    found.out.sort_by_key(|s| s.line);
+    Ok(found.out)
+}
+
+struct Declarations<'a> {
+    content: &'a str,
+    out: Vec<SupertypeRef>,
+}
+
+impl<'a> Declarations<'a> {
+    fn text(&self, node: Node) -> &'a str {
+        &self.content[node.byte_range()]
+    }
+
+    fn push(&mut self, name: Option<String>, supertype: Node, kind: EdgeKind) {
+        let (Some(name), Some(written)) = (name, supertype_name(self.text(supertype))) else {
+            return;
+        };
+        if name != written {
+            self.out.push(SupertypeRef {
+                name,
+                supertype: written,
+                kind,
+                line: supertype.start_position().row + 1,
+            });
+        }
+    }
+
+    fn name(&self, decl: Node) -> Option<String> {
+        decl.child_by_field_name("name").map(|n| self.text(n).to_string())
+    }
+
+    fn rust(&mut self, node: Node) {
+        match node.kind() {
+            "impl_item" => {
+                let (Some(tr), Some(ty)) = (node.child_by_field_name("trait"), node.child_by_field_name("type")) else {
+                    return;
+                };
+                let name = normalize_type(self.text(ty), None).map(|t| type_name(&t).to_string());
+                self.push(name, tr, EdgeKind::Implements);
+            }
+            "trait_item" => {
+                let Some(bounds) = node.child_by_field_name("bounds") else { return };
+                for bound in bounds.named_children(&mut bounds.walk()) {
+                    self.push(self.name(node), bound, EdgeKind::Inherits);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn java(&mut self, node: Node) {
+        if !matches!(node.kind(), "class_declaration" | "enum_declaration" | "record_declaration" | "interface_declaration") {
+            return;
+        }
+        for clause in node.named_children(&mut node.walk()) {
+            let kind = match clause.kind() {
+                "superclass" | "extends_interfaces" => EdgeKind::Inherits,
+                "super_interfaces" => EdgeKind::Implements,
+                _ => continue,
+            };
+            for ty in list_items(clause) {
+                self.push(self.name(node), ty, kind);
+            }
+        }
+    }
+
+    fn js_ts(&mut self, node: Node) {
+        match node.kind() {
+            "class_declaration" | "abstract_class_declaration" | "class" => {
+                let Some(heritage) = node.named_children(&mut node.walk()).find(|c| c.kind() == "class_heritage") else {
+                    return;
+                };
+                for clause in heritage.named_children(&mut heritage.walk()) {
+                    match clause.kind() {
+                        "extends_clause" => {
+                            let values: Vec<Node> = clause.children_by_field_name("value", &mut clause.walk()).collect();
+                            for value in values {
+                                self.push(self.name(node), value, EdgeKind::Inherits);
+                            }
+                        }
+                        "implements_clause" => {
+                            for ty in clause.named_children(&mut clause.walk()) {
+                                self.push(self.name(node), ty, EdgeKind::Implements);
+                            }
+                        }
+                        // JavaScript: `class A extends B` has the expression directly.
+                        _ => self.push(self.name(node), clause, EdgeKind::Inherits),
+                    }
+                }
+            }
+            "interface_declaration" => {
+                for clause in node.named_children(&mut node.walk()).filter(|c| c.kind() == "extends_type_clause") {
+                    for ty in clause.named_children(&mut clause.walk()) {
+                        self.push(self.name(node), ty, EdgeKind::Inherits);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn python(&mut self, node: Node) {
+        if node.kind() != "class_definition" {
+            return;
+        }
+        let Some(bases) = node.child_by_field_name("superclasses") else { return };
+        for base in bases.named_children(&mut bases.walk()) {
+            // `metaclass=ABCMeta` and other class keywords are not bases.
+            if base.kind() != "keyword_argument" {
+                self.push(self.name(node), base, EdgeKind::Inherits);
+            }
+        }
+    }
+
+    fn csharp(&mut self, node: Node) {
+        let kind = node.kind();
+        if !matches!(kind, "class_declaration" | "struct_declaration" | "record_declaration" | "interface_declaration") {
+            return;
+        }
+        let Some(bases) = node.named_children(&mut node.walk()).find(|c| c.kind() == "base_list") else {
+            return;
+        };
+        for base in bases.named_children(&mut bases.walk()) {
+            let edge = match kind {
+                "interface_declaration" => EdgeKind::Inherits,
+                "struct_declaration" => EdgeKind::Implements,
+                _ if supertype_name(self.text(base)).is_some_and(|n| is_interface_name(&n)) => EdgeKind::Implements,
+                _ => EdgeKind::Inherits,
+            };
+            self.push(self.name(node), base, edge);
+        }
+    }
+
+    fn cpp(&mut self, node: Node) {
+        if !matches!(node.kind(), "class_specifier" | "struct_specifier") {
+            return;
+        }
+        let Some(bases) = node.named_children(&mut node.walk()).find(|c| c.kind() == "base_class_clause") else {
+            return;
+        };
+        for base in bases.named_children(&mut bases.walk()) {
+            if base.kind() != "access_specifier" {
+                self.push(self.name(node), base, EdgeKind::Inherits);
+            }
+        }
+    }
+}
+
+/// Named children of a clause, looking through a `type_list`.
+fn list_items(clause: Node) -> Vec<Node> {
+    let mut items = Vec::new();
+    for child in clause.named_children(&mut clause.walk()) {
+        if child.kind() == "type_list" {
+            items.extend(child.named_children(&mut child.walk()));
+        } else {
+            items.push(child);
+        }
+    }
+    items
+}
+
+/// The last segment of a written type, without generic arguments or
+/// constructor arguments (`crate::fmt::Display` -> `Display`,
+/// `Base<T>(x)` -> `Base`). `None` for lifetimes, `?Sized` and the like.
+fn supertype_name(written: &str) -> Option<String> {
+    let path = written.split(['<', '(', '[']).next()?.trim();
+    let name = path.rsplit(['.', ':']).next()?.trim();
+    name.starts_with(|c: char| c.is_alphabetic() || c == '_').then(|| name.to_string())
+}
+
+/// `IDisposable`, `IRepository`: the C# convention for interface names.
+fn is_interface_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next() == Some('I') && chars.next().is_some_and(|c| c.is_ascii_uppercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edges(language: Language, code: &str) -> Vec<(String, String, EdgeKind)> {
+        extract_supertypes(&language, code)
+            .unwrap()
+            .into_iter()
+            .map(|s| (s.name, s.supertype, s.kind))
+            .collect()
+    }
+
+    fn edge(name: &str, supertype: &str, kind: EdgeKind) -> (String, String, EdgeKind) {
+        (name.to_string(), supertype.to_string(), kind)
+    }
+
+    #[test]
+    fn test_extracts_supertypes() {
+        let rust = r#"
+trait Store: Send + Sync + 'static {}
+impl<T> std::fmt::Display for &Wrapper<T> {}
+impl Store for SurrealStore {}
+impl SurrealStore {}
+"#;
+        assert_eq!(
+            edges(Language::Rust, rust),
+            vec![
+                edge("Store", "Send", EdgeKind::Inherits),
+                edge("Store", "Sync", EdgeKind::Inherits),
+                edge("Wrapper", "Display", EdgeKind::Implements),
+                edge("SurrealStore", "Store", EdgeKind::Implements),
+            ]
+        );
+
+        let java = "class Cache extends Base<String> implements Closeable, java.io.Serializable {}\ninterface Store extends Readable {}\n";
+        assert_eq!(
+            edges(Language::Java, java),
+            vec![
+                edge("Cache", "Base", EdgeKind::Inherits),
+                edge("Cache", "Closeable", EdgeKind::Implements),
+                edge("Cache", "Serializable", EdgeKind::Implements),
+                edge("Store", "Readable", EdgeKind::Inherits),
+            ]
+        );
+
+        let python = "class Handler(web.RequestHandler, Generic[T], metaclass=ABCMeta):\n    pass\n";
+        assert_eq!(
+            edges(Language::Python, python),
+            vec![edge("Handler", "RequestHandler", EdgeKind::Inherits), edge("Handler", "Generic", EdgeKind::Inherits)]
+        );
+    }
+}
@@ -1,8 +1,18 @@
 use crate::models::{Language, Symbol};
 use crate::tags_extractor::TagsExtractor;
 use anyhow::Result;
+use serde::Serialize;
 use std::path::Path;
 
+/// A symbol and the symbols defined within its line range.
+#[derive(Debug, Clone, Serialize)]
+pub struct SymbolTree {
+    #[serde(flatten)]
+    pub symbol: Symbol,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<SymbolTree>,
+}
+
 pub fn extract_symbols(content: &str, path: &Path, language: &Language) -> Result<Vec<Symbol>> {
     let mut extractor = TagsExtractor::new()?;
     extractor.extract_symbols(content, path, language)
@@ -18,4 +28,34 @@ pub fn extract_code_item(content: &str, path: &Path, language: &Language, node_p
     extractor.extract_code_item(content, path, language, node_path)
 }
 
+/// `symbols` of one file nested by line range, in source order. A symbol is
+/// a child of the innermost other symbol whose range strictly contains it.
+pub fn symbol_tree(mut symbols: Vec<Symbol>) -> Vec<SymbolTree> {
+    symbols.sort_by(|a, b| a.start_line.cmp(&b.start_line).then(b.end_line.cmp(&a.end_line)));
+    symbols.dedup_by(|a, b| a.name == b.name && a.start_line == b.start_line && a.end_line == b.end_line);
 
+    fn attach(open: &mut Vec<SymbolTree>, roots: &mut Vec<SymbolTree>, done: SymbolTree) {
+        match open.last_mut() {
+            Some(parent) => parent.children.push(done),
+            None => roots.push(done),
+        }
+    }
+    let mut roots = Vec::new();
+    let mut open: Vec<SymbolTree> = Vec::new();
+    for symbol in symbols {
+        while let Some(top) = open.last() {
+            let (start, end) = (top.symbol.start_line, top.symbol.end_line);
+            let inside = symbol.start_line >= start && symbol.end_line <= end;
+            if inside && (symbol.start_line, symbol.end_line) != (start, end) {
+                break;
+            }
+            let done = open.pop().expect("checked above");
+            attach(&mut open, &mut roots, done);
+        }
+        open.push(SymbolTree { symbol, children: Vec::new() });
+    }
+    while let Some(done) = open.pop() {
+        attach(&mut open, &mut roots, done);
+    }
+    roots
+}
@@ -3,9 +3,34 @@ use std::collections::HashMap;
 use std::path::Path;
 use tree_sitter_tags::{TagsConfiguration, TagsContext};
 
+use crate::declarations;
 use crate::models::{Language, Symbol};
 use std::path::PathBuf;
 
+/// Tree-sitter grammar for `language`, if one is bundled.
+pub(crate) fn ts_language(language: &Language) -> Option<tree_sitter::Language> {
+    match language {
+        Language::Rust => Some(tree_sitter_rust::LANGUAGE.into()),
+        Language::Python => Some(tree_sitter_python::LANGUAGE.into()),
+        Language::JavaScript => Some(tree_sitter_javascript::LANGUAGE.into()),
+        Language::TypeScript => Some(tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into()),
+        Language::Go => Some(tree_sitter_go::LANGUAGE.into()),
+        Language::Java => Some(tree_sitter_java::LANGUAGE.into()),
+        Language::C => Some(tree_sitter_c::LANGUAGE.into()),
+        Language::Cpp => Some(tree_sitter_cpp::LANGUAGE.into()),
+        Language::CSharp => Some(tree_sitter_c_sharp::LANGUAGE.into()),
+        _ => None,
+    }
+}
+
+/// Whether tree-sitter recovered from syntax errors while parsing `content`.
+/// `None` when the language has no grammar.
+pub fn has_parse_errors(content: &str, language: &Language) -> Option<bool> {
+    let mut parser = tree_sitter::Parser::new();
+    parser.set_language(&ts_language(language)?).ok()?;
+    parser.parse(content, None).map(|tree| tree.root_node().has_error())
+}
+
 pub struct TagsExtractor {
     context: TagsContext,
     configs: HashMap<Language, TagsConfiguration>,
@@ -93,22 +118,21 @@ impl TagsExtractor {
         path: &Path,
         language: &Language,
     ) -> Result<Vec<Symbol>> {
+        if language.is_template() {
+            // Symbols of each embedded language that has a tags config.
+            let mut symbols = Vec::new();
+            for (sub, view) in crate::regions::views(language, content) {
+                if self.configs.contains_key(&sub) {
+                    symbols.extend(self.extract_symbols(&view, path, &sub)?);
+                }
+            }
+            return Ok(symbols);
+        }
         let config = self.configs.get(language)
             .ok_or_else(|| anyhow::anyhow!("No tags config for {:?}", language))?;
         
         let mut parser = tree_sitter::Parser::new();
-        let lang_ts = match language {
-            Language::Rust => Some(tree_sitter_rust::LANGUAGE.into()),
-            Language::Python => Some(tree_sitter_python::LANGUAGE.into()),
-            Language::JavaScript => Some(tree_sitter_javascript::LANGUAGE.into()),
-            Language::TypeScript => Some(tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into()),
-            Language::Go => Some(tree_sitter_go::LANGUAGE.into()),
-            Language::Java => Some(tree_sitter_java::LANGUAGE.into()),
-            Language::C => Some(tree_sitter_c::LANGUAGE.into()),
-            Language::Cpp => Some(tree_sitter_cpp::LANGUAGE.into()),
-            Language::CSharp => Some(tree_sitter_c_sharp::LANGUAGE.into()),
-            _ => None,
-        };
+        let lang_ts = ts_language(language);
         
         let tree = if let Some(lang) = lang_ts {
             if parser.set_language(&lang).is_ok() {
@@ -145,6 +169,9 @@ impl TagsExtractor {
             let mut start_byte = tag.line_range.start;
             let mut end_byte = tag.line_range.end;
             let mut parent_scope = None;
+            let mut doc_comment = tag.docs;
+            let mut signature = None;
+            let mut visibility = None;
             
             if let Some(tree) = &tree {
                 if let Some(node) = tree.root_node().descendant_for_byte_range(tag.name_range.start, tag.name_range.end) {
@@ -153,6 +180,9 @@ impl TagsExtractor {
                         if is_definition_node(parent.kind(), language) {
                             start_byte = parent.start_byte();
                             end_byte = parent.end_byte();
+                            doc_comment = declarations::doc_comment(parent, language, content);
+                            signature = declarations::signature(parent, content);
+                            visibility = declarations::visibility(parent, language, &name, content);
                             break;
                         }
                         curr = parent;
@@ -174,8 +204,10 @@ impl TagsExtractor {
                 end_line,
                 fqn: name.clone(), 
                 language: *language,
-                doc_comment: tag.docs,
+                doc_comment,
                 parent_scope,
+                signature,
+                visibility,
             });
         }
         
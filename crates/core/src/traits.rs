@@ -11,6 +11,13 @@ pub trait Embedder: Send + Sync {
     async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>>;
 }
 
+/// Cross-encoder that scores (query, document) pairs jointly.
+#[async_trait]
+pub trait Reranker: Send + Sync {
+    /// One relevance score per document, in input order; higher is more relevant.
+    async fn score(&self, query: &str, documents: &[String]) -> Result<Vec<f32>>;
+}
+
 #[async_trait]
 pub trait LLM: Send + Sync {
     async fn complete(&self, prompt: &str) -> Result<String>;
@@ -1,2 +1,3 @@
 pub mod pipeline;
 pub mod service;
+pub mod writer;
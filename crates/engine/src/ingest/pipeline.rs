@@ -1,11 +1,16 @@
 use anyhow::{Context, Result};
 use emry_config::Config;
-use emry_core::chunking::{Chunker, GenericChunker};
+use emry_core::chunking::chunker_for;
+use emry_core::doc_comments::attach_docs;
+use emry_core::entry_points::{detect_entry_points, EntryPointKind};
+use emry_core::keywords::tag_chunks;
 use emry_core::models::Language;
 use emry_core::relations::{extract_calls_imports, RelationRef};
+use emry_core::supertypes::{extract_supertypes, SupertypeRef};
 use emry_core::symbols::extract_symbols;
 use emry_core::traits::Embedder;
 use sha2::{Digest, Sha256};
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
 use tracing::{error, warn};
@@ -38,6 +43,9 @@ pub struct PreparedFile {
     pub chunk_symbol_edges: Vec<(String, String)>,
     pub call_edges: Vec<(String, RelationRef)>,
     pub import_edges: Vec<(String, RelationRef)>,
+    pub supertype_edges: Vec<SupertypeRef>,
+    /// Symbols run from outside the code (`main`, routes, tests...), by name
+    pub entry_points: std::collections::HashMap<String, EntryPointKind>,
 }
 
 pub async fn analyze_source_files(
@@ -73,37 +81,75 @@ pub async fn analyze_source_files(
     .await
 }
 
+/// Embed every chunk of `prepared_files`, and separately its docs. Texts
+/// are pooled across files into requests of `embedding.batch_size`, with up
+/// to `embedding.max_concurrent_requests` of them in flight. Identical texts
+/// are embedded once and share the vector.
 pub async fn generate_embeddings(
     prepared_files: &mut [PreparedFile],
     embedder: Arc<dyn Embedder + Send + Sync>,
+    config: &Config,
 ) {
+    let batch_size = config.embedding.batch_size.max(1);
     let mut all_chunks_refs: Vec<&mut emry_core::models::Chunk> = Vec::new();
     for file in prepared_files.iter_mut() {
         for chunk in &mut file.chunks {
             all_chunks_refs.push(chunk);
         }
     }
+    if all_chunks_refs.is_empty() {
+        return;
+    }
 
-    if !all_chunks_refs.is_empty() {
-        for (i, chunk_batch) in all_chunks_refs.chunks_mut(512).enumerate() {
-            let batch_texts: Vec<String> =
-                chunk_batch.iter().map(|c| c.content.clone()).collect();
-            if let Ok(embeddings) = embedder.embed_batch(&batch_texts).await {
-                if embeddings.len() == chunk_batch.len() {
-                    for (chunk, emb) in chunk_batch.iter_mut().zip(embeddings) {
-                        chunk.embedding = Some(emb);
-                    }
-                } else {
-                    warn!(
-                        "Embedding count mismatch in global batch {} (got {}, expected {})",
-                        i,
-                        embeddings.len(),
-                        chunk_batch.len()
-                    );
+    // Position of each chunk's text, and of its docs, among the distinct texts.
+    let mut texts: Vec<String> = Vec::new();
+    let mut text_index: HashMap<String, usize> = HashMap::new();
+    let mut slot_of = |text: &String| {
+        *text_index.entry(text.clone()).or_insert_with(|| {
+            texts.push(text.clone());
+            texts.len() - 1
+        })
+    };
+    let slots: Vec<(usize, Option<usize>)> = all_chunks_refs
+        .iter()
+        .map(|c| (slot_of(&c.content), c.doc.as_ref().map(&mut slot_of)))
+        .collect();
+
+    let batches: Vec<Vec<String>> = texts.chunks(batch_size).map(|batch| batch.to_vec()).collect();
+    let results: Vec<(usize, Result<Vec<Vec<f32>>>)> = stream::iter(batches.into_iter().enumerate())
+        .map(|(i, batch_texts)| {
+            let embedder = embedder.clone();
+            async move { (i, embedder.embed_batch(&batch_texts).await) }
+        })
+        .buffer_unordered(config.embedding.max_concurrent_requests.max(1))
+        .collect()
+        .await;
+
+    let mut embeddings: Vec<Option<Vec<f32>>> = vec![None; texts.len()];
+    for (i, result) in results {
+        let start = i * batch_size;
+        let end = (start + batch_size).min(texts.len());
+        match result {
+            Ok(batch) if batch.len() == end - start => {
+                for (slot, emb) in embeddings[start..end].iter_mut().zip(batch) {
+                    *slot = Some(emb);
                 }
-            } else {
-                error!("Failed to embed global batch {}", i);
             }
+            Ok(batch) => warn!(
+                "Embedding count mismatch in global batch {} (got {}, expected {})",
+                i,
+                batch.len(),
+                end - start
+            ),
+            Err(e) => error!("Failed to embed global batch {}: {}", i, e),
+        }
+    }
+    for (chunk, (slot, doc_slot)) in all_chunks_refs.into_iter().zip(slots) {
+        if let Some(emb) = &embeddings[slot] {
+            chunk.embedding = Some(emb.clone());
+        }
+        if let Some(emb) = doc_slot.and_then(|slot| embeddings[slot].as_ref()) {
+            chunk.doc_embedding = Some(emb.clone());
         }
     }
 }
@@ -118,17 +164,27 @@ fn prepare_file(
     input: &FileInput,
     config: &Config,
 ) -> Result<PreparedFile> {
-    let chunker = GenericChunker::with_config(input.language.clone(), config.chunking.clone());
+    let chunker = chunker_for(input.language.clone(), config.chunking.clone());
     let mut chunks = chunker.chunk(&input.content, &input.path)?;
     for chunk in chunks.iter_mut() {
         if chunk.content_hash.is_empty() {
             chunk.content_hash = compute_hash(&chunk.content);
         }
     }
+    tag_chunks(&mut chunks);
+    if !input.language.is_document() {
+        attach_docs(&mut chunks, &input.content, &input.language);
+    }
 
     let mut symbols: Vec<emry_core::models::Symbol> = Vec::new();
     let mut chunk_symbol_edges: Vec<(String, String)> = Vec::new();
-    match extract_symbols(&input.content, &input.path, &input.language) {
+    // Documents have no symbols or call/import relations.
+    let extracted = if input.language.is_document() {
+        Ok(Vec::new())
+    } else {
+        extract_symbols(&input.content, &input.path, &input.language)
+    };
+    match extracted {
         Ok(syms) => {
 
             for sym in syms {
@@ -164,6 +220,17 @@ fn prepare_file(
         import_edges.push((caller_node, imp));
     }
 
+    let supertype_edges = if input.language.is_document() {
+        Vec::new()
+    } else {
+        extract_supertypes(&input.language, &input.content).unwrap_or_else(|e| {
+            warn!("Failed to extract supertypes for {}: {}", input.path.display(), e);
+            Vec::new()
+        })
+    };
+
+    let entry_points = detect_entry_points(&input.content, &input.path, &input.language, &symbols);
+
     Ok(PreparedFile {
         path: input.path.clone(),
         language: input.language.clone(),
@@ -177,6 +244,8 @@ fn prepare_file(
         chunk_symbol_edges,
         call_edges,
         import_edges,
+        supertype_edges,
+        entry_points,
     })
 }
 
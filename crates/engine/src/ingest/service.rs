@@ -1,10 +1,13 @@
 use anyhow::Result;
-use emry_core::chunking::{GenericChunker, Chunker};
+use emry_core::chunking::chunker_for;
+use emry_core::entry_points::detect_entry_points;
 use emry_core::models::Language;
+use emry_core::import_paths::ImportResolver;
+use emry_core::keywords::sparse_vector;
 use emry_core::symbols::extract_symbols;
 use emry_core::traits::Embedder;
 use emry_core::relations::RelationRef;
-use emry_store::{SurrealStore, FileRecord, ChunkRecord, SymbolRecord};
+use emry_store::{SurrealStore, FileRecord, ChunkRecord, SymbolRecord, SymbolHistoryRecord};
 use std::path::Path;
 use std::sync::Arc;
 use surrealdb::sql::Thing;
@@ -13,6 +16,7 @@ use super::pipeline::compute_hash;
 pub struct IngestionService {
     store: Arc<SurrealStore>,
     embedder: Option<Arc<dyn Embedder + Send + Sync>>,
+    imports: ImportResolver,
 }
 
 pub struct IngestionContext {
@@ -47,6 +51,44 @@ impl IngestionContext {
             chunk_to_symbol,
         }
     }
+
+    /// Current version of every symbol in this file, for `SurrealStore::record_symbol_versions`.
+    pub fn symbol_versions(&self, commit_id: &str, timestamp: u64) -> Vec<SymbolHistoryRecord> {
+        let file = &self.file;
+        let file_path = file.path.to_string_lossy().to_string();
+        let lines: Vec<&str> = file.content.lines().collect();
+
+        let mut seen = std::collections::HashSet::new();
+        let mut versions = Vec::new();
+        for s in &file.symbols {
+            let symbol = Thing::from(("symbol", format!("{}::{}", file_path, s.name).as_str())).to_string();
+            // Overloads share a storage id; record one version per id.
+            if !seen.insert(symbol.clone()) {
+                continue;
+            }
+
+            let start = s.start_line.saturating_sub(1).min(lines.len());
+            let end = s.end_line.min(lines.len()).max(start);
+            let chunks = file.chunks.iter()
+                .filter(|c| c.start_line <= s.end_line && c.end_line >= s.start_line)
+                .map(|c| Thing::from(("chunk", c.id.as_str())).to_string())
+                .collect();
+
+            versions.push(SymbolHistoryRecord {
+                id: None,
+                symbol,
+                name: s.name.clone(),
+                file_path: file_path.clone(),
+                commit_id: commit_id.to_string(),
+                timestamp,
+                content_hash: compute_hash(&lines[start..end].join("\n")),
+                start_line: s.start_line,
+                end_line: s.end_line,
+                chunks,
+            });
+        }
+        versions
+    }
 }
 
 impl IngestionService {
@@ -54,7 +96,13 @@ impl IngestionService {
         store: Arc<SurrealStore>,
         embedder: Option<Arc<dyn Embedder + Send + Sync>>,
     ) -> Self {
-        Self { store, embedder }
+        Self { store, embedder, imports: ImportResolver::default() }
+    }
+
+    /// Rewrite import aliases (tsconfig paths, workspace crates) to repo paths before resolving edges.
+    pub fn with_import_resolver(mut self, imports: ImportResolver) -> Self {
+        self.imports = imports;
+        self
     }
 
     pub async fn ingest_file(&self, path: &str, content: &str) -> Result<()> {
@@ -64,8 +112,9 @@ impl IngestionService {
         );
         
         let chunking_config = emry_config::ChunkingConfig::default();
-        let chunker = GenericChunker::with_config(language.clone(), chunking_config);
-        let core_chunks = chunker.chunk(content, file_path)?;
+        let chunker = chunker_for(language.clone(), chunking_config);
+        let mut core_chunks = chunker.chunk(content, file_path)?;
+        emry_core::keywords::tag_chunks(&mut core_chunks);
         
         let mut chunks_with_embeddings = core_chunks.clone();
         if let Some(embedder) = &self.embedder {
@@ -80,6 +129,7 @@ impl IngestionService {
         }
         
         let core_symbols = extract_symbols(content, file_path, &language).unwrap_or_default();
+        let entry_points = detect_entry_points(content, file_path, &language, &core_symbols);
         
         let file_id = Thing::from(("file", path));
         
@@ -93,18 +143,29 @@ impl IngestionService {
         };
         
         let chunk_records: Vec<ChunkRecord> = chunks_with_embeddings.into_iter().map(|c| {
+            let (sparse_terms, sparse_weights) = sparse_vector(&c.content).into_iter().unzip();
             ChunkRecord {
                 id: None,
+                content_hash: compute_hash(&c.content),
                 content: c.content,
                 embedding: c.embedding.filter(|v| !v.is_empty()),
+                embedding_q: None,
                 file: file_id.clone(),
                 start_line: c.start_line,
                 end_line: c.end_line,
                 scopes: c.scope_path,
+                tags: c.tags,
+                sparse_terms,
+                sparse_weights,
+                language: (c.language != language).then(|| c.language.to_string()),
+                duplicate_of: None,
+                doc: c.doc,
+                doc_embedding: c.doc_embedding.filter(|v| !v.is_empty()),
             }
         }).collect();
         
         let symbol_records: Vec<SymbolRecord> = core_symbols.into_iter().map(|s| {
+            let entry_kind = entry_points.get(&s.name).copied();
             SymbolRecord {
                 id: Some(Thing::from(("symbol", format!("{}::{}", path, s.name).as_str()))),
                 name: s.name,
@@ -113,6 +174,8 @@ impl IngestionService {
                 start_line: s.start_line,
                 end_line: s.end_line,
                 parent_scope: s.parent_scope,
+                entry_point: entry_kind.is_some(),
+                entry_kind,
             }
         }).collect();
         
@@ -156,14 +219,24 @@ impl IngestionService {
         };
 
         let chunk_records: Vec<ChunkRecord> = chunks_with_embeddings.into_iter().map(|c| {
+            let (sparse_terms, sparse_weights) = sparse_vector(&c.content).into_iter().unzip();
             ChunkRecord {
                 id: Some(Thing::from(("chunk", c.id.as_str()))),
+                content_hash: compute_hash(&c.content),
                 content: c.content,
                 embedding: c.embedding.filter(|v| !v.is_empty()),
+                embedding_q: None,
                 file: file_id.clone(),
                 start_line: c.start_line,
                 end_line: c.end_line,
                 scopes: c.scope_path,
+                tags: c.tags,
+                sparse_terms,
+                sparse_weights,
+                language: (c.language != file.language).then(|| c.language.to_string()),
+                duplicate_of: None,
+                doc: c.doc,
+                doc_embedding: c.doc_embedding.filter(|v| !v.is_empty()),
             }
         }).collect();
 
@@ -179,6 +252,8 @@ impl IngestionService {
                 start_line: s.start_line,
                 end_line: s.end_line,
                 parent_scope: s.parent_scope.clone(),
+                entry_point: file.entry_points.contains_key(&s.name),
+                entry_kind: file.entry_points.get(&s.name).copied(),
             }
         }).collect();
         
@@ -194,7 +269,7 @@ impl IngestionService {
         
 
 
-    /// Pass 2: Ingest edges (Calls, Imports)
+    /// Pass 2: Ingest edges (Calls, Imports, Inherits, Implements)
     pub async fn ingest_edges(&self, ctx: &IngestionContext) -> Result<()> {
         let file = &ctx.file;
         let file_id_str = file.path.to_string_lossy().to_string();
@@ -216,7 +291,11 @@ impl IngestionService {
         }).collect();
 
         let translated_import_edges: Vec<(String, RelationRef)> = file.import_edges.iter().filter_map(|(importer, relation)| {
-             
+             let relation = &RelationRef {
+                 name: self.imports.normalize(&relation.name, &file.language, &file.path),
+                 ..relation.clone()
+             };
+
              if let Some(symbol_id) = chunk_to_symbol.get(importer) {
                  return Some((symbol_id.clone(), relation.clone()));
              }
@@ -233,6 +312,7 @@ impl IngestionService {
         }).collect();
         
         self.store.add_file_edges(&translated_edges, &translated_import_edges).await?;
+        self.store.add_supertype_edges(&file_id_str, &file.supertype_edges).await?;
         Ok(())
     }
 }
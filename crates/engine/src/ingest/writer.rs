@@ -0,0 +1,91 @@
+//! Bounded write queue for node ingestion.
+//!
+//! Analysis and embedding push prepared files into the queue while a single
+//! worker drains it into the store, so database writes overlap with the CPU
+//! and network work for the next files. When the worker falls behind the
+//! queue fills and `push` waits, which bounds how many prepared files (and
+//! their embeddings) are held in memory.
+
+use super::service::{IngestionContext, IngestionService};
+use anyhow::{anyhow, Result};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::error;
+
+/// Files taken off the queue per worker wakeup.
+const DRAIN_BATCH: usize = 16;
+
+/// Cloneable view of queue counters, for progress reporting from another task.
+#[derive(Clone, Default)]
+pub struct WriteProgress {
+    depth: Arc<AtomicUsize>,
+    written: Arc<AtomicUsize>,
+}
+
+impl WriteProgress {
+    /// Files queued but not yet picked up by the writer.
+    pub fn depth(&self) -> usize {
+        self.depth.load(Ordering::Relaxed)
+    }
+
+    /// Files written so far (including ones whose write failed and was logged).
+    pub fn written(&self) -> usize {
+        self.written.load(Ordering::Relaxed)
+    }
+}
+
+pub struct NodeWriteQueue {
+    tx: mpsc::Sender<IngestionContext>,
+    progress: WriteProgress,
+    worker: JoinHandle<Vec<IngestionContext>>,
+}
+
+impl NodeWriteQueue {
+    /// Start the writer with room for `capacity` files in flight.
+    pub fn spawn(service: Arc<IngestionService>, capacity: usize) -> Self {
+        let (tx, mut rx) = mpsc::channel::<IngestionContext>(capacity.max(1));
+        let progress = WriteProgress::default();
+
+        let worker = {
+            let progress = progress.clone();
+            tokio::spawn(async move {
+                let mut done = Vec::new();
+                let mut batch = Vec::with_capacity(DRAIN_BATCH);
+                while rx.recv_many(&mut batch, DRAIN_BATCH).await > 0 {
+                    progress.depth.fetch_sub(batch.len(), Ordering::Relaxed);
+                    for ctx in batch.drain(..) {
+                        if let Err(e) = service.ingest_nodes(&ctx).await {
+                            error!("Failed to ingest nodes for {}: {}", ctx.file.path.display(), e);
+                        }
+                        progress.written.fetch_add(1, Ordering::Relaxed);
+                        done.push(ctx);
+                    }
+                }
+                done
+            })
+        };
+
+        Self { tx, progress, worker }
+    }
+
+    /// Queue a file for writing, waiting while the queue is full.
+    pub async fn push(&self, ctx: IngestionContext) -> Result<()> {
+        self.progress.depth.fetch_add(1, Ordering::Relaxed);
+        self.tx.send(ctx).await.map_err(|_| {
+            self.progress.depth.fetch_sub(1, Ordering::Relaxed);
+            anyhow!("node writer stopped")
+        })
+    }
+
+    pub fn progress(&self) -> WriteProgress {
+        self.progress.clone()
+    }
+
+    /// Close the queue, wait for pending writes, and return the contexts in write order.
+    pub async fn finish(self) -> Result<Vec<IngestionContext>> {
+        drop(self.tx);
+        Ok(self.worker.await?)
+    }
+}
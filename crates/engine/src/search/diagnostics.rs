@@ -0,0 +1,60 @@
+//! Explanations for searches that find nothing.
+//!
+//! Files in the working tree containing every query word are checked
+//! against the scanner's rules and the file store, and each gets the first
+//! reason it can't be found: left out of the scan (excluded, ignored,
+//! unsupported, too large, binary), added after the last index run, or
+//! changed since. Files that are indexed and current are not reported.
+
+use super::matching::MatchOptions;
+use crate::ingest::pipeline::compute_hash;
+use anyhow::Result;
+use emry_config::Config;
+use emry_core::models::{NotIndexed, NotIndexedReason};
+use emry_core::scanner::{exclusion, files_containing, walk_repo};
+use emry_store::SurrealStore;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Files containing the query that are explained at most.
+const MAX_EXPLAINED: usize = 10;
+
+/// Why `query` likely matched nothing in the index of the repository at `root`.
+pub async fn explain_empty(store: &SurrealStore, config: &Config, root: &Path, query: &str) -> Result<Vec<NotIndexed>> {
+    if store.count_files().await? == 0 {
+        return Ok(vec![NotIndexed { file_path: None, reason: NotIndexedReason::EmptyIndex }]);
+    }
+    let (query, _) = MatchOptions::parse(query);
+    let terms: Vec<String> = query
+        .split_whitespace()
+        .map(|t| t.trim_matches(|c: char| !c.is_alphanumeric() && c != '_').to_lowercase())
+        .filter(|t| !t.is_empty())
+        .collect();
+    if terms.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let walked: HashSet<PathBuf> = walk_repo(root, &config.core, config.core.respect_gitignore).into_iter().collect();
+    let mut explained = Vec::new();
+    for path in files_containing(root, &terms, MAX_EXPLAINED) {
+        let reason = match exclusion(root, &path, &config.core, &config.documents, &walked) {
+            Some(reason) => Some(reason),
+            None => index_state(store, &path).await?,
+        };
+        if let Some(reason) = reason {
+            let relative = path.strip_prefix(root).unwrap_or(&path);
+            explained.push(NotIndexed { file_path: Some(relative.display().to_string()), reason });
+        }
+    }
+    Ok(explained)
+}
+
+/// Whether a scanned file is missing from the index or stale there.
+async fn index_state(store: &SurrealStore, path: &Path) -> Result<Option<NotIndexedReason>> {
+    let absolute = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let Some(file) = store.get_file(&absolute.to_string_lossy()).await? else {
+        return Ok(Some(NotIndexedReason::AddedSinceIndex));
+    };
+    let current = std::fs::read_to_string(path).unwrap_or_default();
+    Ok((file.hash != compute_hash(&current)).then_some(NotIndexedReason::ModifiedSinceIndex))
+}
@@ -0,0 +1,121 @@
+//! Retrieval quality over a labeled query set
+//!
+//! Each labeled query lists what a good search should return, as repository
+//! relative paths or `path:line`. A hit is relevant when it comes from a
+//! labeled file and, for `path:line` labels, its span covers that line.
+//! Quality is the mean reciprocal rank of the first relevant hit, with the
+//! share of labels found in the results as a tie-breaker.
+
+use serde::{Deserialize, Serialize};
+
+/// One query of a labeled set, as stored in the query file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LabeledQuery {
+    pub query: String,
+    /// `path` or `path:line`, relative to the repository root
+    pub relevant: Vec<String>,
+}
+
+/// A parsed entry of `LabeledQuery::relevant`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Label {
+    pub path: String,
+    pub line: Option<usize>,
+}
+
+impl Label {
+    pub fn parse(label: &str) -> Self {
+        let label = label.trim().trim_start_matches("./");
+        match label.rsplit_once(':') {
+            Some((path, line)) if !path.is_empty() => match line.parse() {
+                Ok(line) => Label { path: path.to_string(), line: Some(line) },
+                Err(_) => Label { path: label.to_string(), line: None },
+            },
+            _ => Label { path: label.to_string(), line: None },
+        }
+    }
+
+    /// Whether a hit spanning `start..=end` of the repository-relative `path` is this label.
+    pub fn matches(&self, path: &str, start: usize, end: usize) -> bool {
+        path == self.path && self.line.map_or(true, |line| start <= line && line <= end)
+    }
+}
+
+/// A ranked hit reduced to what labels are matched against.
+#[derive(Debug, Clone)]
+pub struct HitSpan {
+    /// Relative to the repository root, with `/` separators
+    pub path: String,
+    pub start_line: usize,
+    pub end_line: usize,
+}
+
+/// Averages over a query set; higher is better for both.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct Metrics {
+    /// Mean reciprocal rank of the first relevant hit (0 when none is returned)
+    pub mrr: f32,
+    /// Mean share of each query's labels matched by some hit
+    pub recall: f32,
+}
+
+impl Metrics {
+    /// Score one query's ranked hits against its labels.
+    pub fn of_query(labels: &[Label], hits: &[HitSpan]) -> Self {
+        let relevant = |hit: &HitSpan| labels.iter().any(|l| l.matches(&hit.path, hit.start_line, hit.end_line));
+        let mrr = hits.iter().position(relevant).map_or(0.0, |i| 1.0 / (i + 1) as f32);
+        let found = labels
+            .iter()
+            .filter(|l| hits.iter().any(|h| l.matches(&h.path, h.start_line, h.end_line)))
+            .count();
+        let recall = if labels.is_empty() { 0.0 } else { found as f32 / labels.len() as f32 };
+        Metrics { mrr, recall }
+    }
+
+    pub fn mean(per_query: &[Metrics]) -> Self {
+        if per_query.is_empty() {
+            return Metrics::default();
+        }
+        let n = per_query.len() as f32;
+        Metrics {
+            mrr: per_query.iter().map(|m| m.mrr).sum::<f32>() / n,
+            recall: per_query.iter().map(|m| m.recall).sum::<f32>() / n,
+        }
+    }
+
+    /// Whether `self` ranks better than `other`: higher MRR, then higher recall.
+    pub fn beats(&self, other: &Metrics) -> bool {
+        const EPSILON: f32 = 1e-6;
+        self.mrr > other.mrr + EPSILON || ((self.mrr - other.mrr).abs() <= EPSILON && self.recall > other.recall + EPSILON)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hit(path: &str, start_line: usize, end_line: usize) -> HitSpan {
+        HitSpan { path: path.to_string(), start_line, end_line }
+    }
+
+    #[test]
+    fn test_label_parse() {
+        assert_eq!(Label::parse("src/lib.rs:42"), Label { path: "src/lib.rs".into(), line: Some(42) });
+        assert_eq!(Label::parse("./src/lib.rs"), Label { path: "src/lib.rs".into(), line: None });
+        assert_eq!(Label::parse("C:notes"), Label { path: "C:notes".into(), line: None });
+    }
+
+    #[test]
+    fn test_metrics_of_query() {
+        let labels = vec![Label::parse("src/fusion.rs"), Label::parse("src/pipeline.rs:120")];
+        let hits = vec![hit("src/other.rs", 1, 10), hit("src/pipeline.rs", 100, 130), hit("src/pipeline.rs", 1, 20)];
+        let metrics = Metrics::of_query(&labels, &hits);
+        assert_eq!(metrics.mrr, 0.5);
+        assert_eq!(metrics.recall, 0.5);
+
+        let mean = Metrics::mean(&[metrics, Metrics { mrr: 1.0, recall: 1.0 }]);
+        assert_eq!(mean, Metrics { mrr: 0.75, recall: 0.75 });
+        assert!(mean.beats(&metrics));
+        assert!(!metrics.beats(&metrics));
+    }
+}
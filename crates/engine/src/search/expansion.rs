@@ -0,0 +1,152 @@
+//! Query expansion from the index's own vocabulary (`search.query_expansion`).
+//!
+//! Adds terms the query implies but doesn't spell out: the parts of compound
+//! identifiers (`parseHttpRequest` -> `parse http request`), symbols whose
+//! name shares most of the query's words (`parse request` ->
+//! `parse_http_request`), and the symbols behind import aliases
+//! (`np` -> `numpy`). The terms ride along as request keywords, so every
+//! source sees them.
+
+use anyhow::Result;
+use emry_core::keywords::extract_terms;
+use emry_store::SurrealStore;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use tokio::sync::OnceCell;
+
+/// Symbol names added per query.
+const MAX_SYMBOL_EXPANSIONS: usize = 5;
+/// Share of a symbol name's terms that must appear in the query.
+const MIN_SYMBOL_OVERLAP: f32 = 0.5;
+
+pub struct QueryExpander {
+    store: Arc<SurrealStore>,
+    /// Loaded on first use and kept for the expander's lifetime.
+    vocabulary: OnceCell<Vocabulary>,
+}
+
+#[derive(Debug, Default)]
+struct Vocabulary {
+    /// Distinct symbol names with their terms.
+    symbols: Vec<(String, Vec<String>)>,
+    /// Lowercased alias -> names imported under it.
+    aliases: HashMap<String, Vec<String>>,
+}
+
+impl Vocabulary {
+    async fn load(store: &SurrealStore) -> Result<Self> {
+        let mut names: Vec<String> = store.list_all_symbols().await?.into_iter().map(|s| s.label).collect();
+        names.sort();
+        names.dedup();
+        let symbols = names
+            .into_iter()
+            .filter_map(|name| {
+                let mut terms = extract_terms(&name);
+                terms.sort();
+                terms.dedup();
+                (!terms.is_empty()).then_some((name, terms))
+            })
+            .collect();
+
+        let mut aliases: HashMap<String, Vec<String>> = HashMap::new();
+        for (alias, name) in store.list_import_aliases().await? {
+            let names = aliases.entry(alias.to_lowercase()).or_default();
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+        Ok(Self { symbols, aliases })
+    }
+}
+
+impl QueryExpander {
+    pub fn new(store: Arc<SurrealStore>) -> Self {
+        Self { store, vocabulary: OnceCell::new() }
+    }
+
+    /// Terms to search for alongside `query`, excluding words it already contains.
+    pub async fn expand(&self, query: &str) -> Result<Vec<String>> {
+        let vocabulary = self.vocabulary.get_or_try_init(|| Vocabulary::load(&self.store)).await?;
+        Ok(expand_with(vocabulary, query))
+    }
+}
+
+fn expand_with(vocabulary: &Vocabulary, query: &str) -> Vec<String> {
+    let tokens: Vec<&str> = query
+        .split(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .filter(|t| !t.is_empty())
+        .collect();
+    let mut seen: HashSet<String> = tokens.iter().map(|t| t.to_lowercase()).collect();
+    let mut expanded = Vec::new();
+    let mut push = |term: &str| {
+        if seen.insert(term.to_lowercase()) {
+            expanded.push(term.to_string());
+        }
+    };
+
+    for token in &tokens {
+        let parts = extract_terms(token);
+        if parts.len() > 1 {
+            parts.iter().for_each(|p| push(p));
+        }
+    }
+
+    let query_terms: HashSet<String> = extract_terms(query).into_iter().collect();
+    let mut matches: Vec<(&str, f32, usize)> = vocabulary
+        .symbols
+        .iter()
+        .filter_map(|(name, terms)| {
+            let shared = terms.iter().filter(|t| query_terms.contains(*t)).count();
+            let overlap = shared as f32 / terms.len() as f32;
+            (shared > 0 && overlap >= MIN_SYMBOL_OVERLAP).then_some((name.as_str(), overlap, shared))
+        })
+        .collect();
+    // Closest overlap first, then the names covering more of the query, then shorter names.
+    matches.sort_by(|a, b| {
+        b.1.total_cmp(&a.1).then(b.2.cmp(&a.2)).then(a.0.len().cmp(&b.0.len())).then(a.0.cmp(b.0))
+    });
+    let mut added = 0;
+    for (name, _, _) in matches {
+        if added == MAX_SYMBOL_EXPANSIONS {
+            break;
+        }
+        if !seen.contains(&name.to_lowercase()) {
+            push(name);
+            added += 1;
+        }
+    }
+
+    for token in &tokens {
+        if let Some(names) = vocabulary.aliases.get(&token.to_lowercase()) {
+            names.iter().for_each(|n| push(n));
+        }
+    }
+    expanded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expands_identifiers_symbols_and_aliases() {
+        let vocabulary = Vocabulary {
+            symbols: ["parse_http_request", "HttpClient", "render_template", "parse"]
+                .iter()
+                .map(|name| {
+                    let mut terms = extract_terms(name);
+                    terms.sort();
+                    (name.to_string(), terms)
+                })
+                .collect(),
+            aliases: HashMap::from([("np".to_string(), vec!["numpy".to_string()])]),
+        };
+
+        let expanded = expand_with(&vocabulary, "parseRequest np");
+        assert_eq!(expanded, vec!["parse", "request", "parse_http_request", "numpy"]);
+
+        // Words already in the query are not repeated.
+        let expanded = expand_with(&vocabulary, "http client");
+        assert_eq!(expanded, vec!["HttpClient"]);
+    }
+}
@@ -0,0 +1,195 @@
+use emry_config::{FusionStrategy, RankingConfig};
+use emry_core::models::RankingStep;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use super::pipeline::{Candidate, DedupFuser, Fuser};
+
+/// Rank offset for Reciprocal Rank Fusion; 60 is the value from the original paper.
+const RRF_K: f32 = 60.0;
+
+/// The fuser selected by `ranking.fusion`.
+pub fn fuser_for(ranking: &RankingConfig) -> Arc<dyn Fuser> {
+    match ranking.fusion {
+        FusionStrategy::Max => Arc::new(DedupFuser),
+        FusionStrategy::Weighted => Arc::new(WeightedFuser { lexical: ranking.lexical, vector: ranking.vector }),
+        FusionStrategy::Rrf => Arc::new(RrfFuser { k: RRF_K }),
+        FusionStrategy::Zscore => Arc::new(ZScoreFuser),
+    }
+}
+
+/// Sums scores normalized to each source's top hit, scaled by the vector
+/// weight for the vector source and the lexical weight for the others.
+pub struct WeightedFuser {
+    pub lexical: f32,
+    pub vector: f32,
+}
+
+impl Fuser for WeightedFuser {
+    fn fuse(&self, lists: Vec<Vec<Candidate>>) -> Vec<Candidate> {
+        sum_contributions(lists, |list| {
+            let weight = match list.first().map(|c| c.source.as_str()) {
+                Some("vector") => self.vector,
+                _ => self.lexical,
+            };
+            let top = list.first().map(|c| c.score).filter(|s| *s > 0.0).unwrap_or(1.0);
+            list.iter().map(|c| weight * c.score / top).collect()
+        })
+    }
+}
+
+/// Reciprocal Rank Fusion: each source adds `1 / (k + rank)`, so only the
+/// order within a source matters, not the scale of its scores.
+pub struct RrfFuser {
+    pub k: f32,
+}
+
+impl Fuser for RrfFuser {
+    fn fuse(&self, lists: Vec<Vec<Candidate>>) -> Vec<Candidate> {
+        sum_contributions(lists, |list| {
+            (0..list.len()).map(|rank| 1.0 / (self.k + rank as f32 + 1.0)).collect()
+        })
+    }
+}
+
+/// Sums each source's scores standardized to zero mean and unit variance.
+pub struct ZScoreFuser;
+
+impl Fuser for ZScoreFuser {
+    fn fuse(&self, lists: Vec<Vec<Candidate>>) -> Vec<Candidate> {
+        sum_contributions(lists, |list| {
+            let n = list.len() as f32;
+            let mean = list.iter().map(|c| c.score).sum::<f32>() / n;
+            let std = (list.iter().map(|c| (c.score - mean).powi(2)).sum::<f32>() / n).sqrt();
+            list.iter()
+                .map(|c| if std > f32::EPSILON { (c.score - mean) / std } else { 0.0 })
+                .collect()
+        })
+    }
+}
+
+/// Merge lists by chunk id, scoring each chunk with the sum of what every
+/// source contributes for it. `contribution` sees one list in score order and
+/// returns a value per candidate. Component scores are merged as in `DedupFuser`,
+/// and traced candidates (see `SearchRequest::explain`) get a `Fused` step per source.
+fn sum_contributions(lists: Vec<Vec<Candidate>>, contribution: impl Fn(&[Candidate]) -> Vec<f32>) -> Vec<Candidate> {
+    let mut merged: Vec<Candidate> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+
+    for mut list in lists {
+        if list.is_empty() {
+            continue;
+        }
+        list.sort_by(|a, b| b.score.total_cmp(&a.score));
+        let scores = contribution(&list);
+        for (mut candidate, score) in list.into_iter().zip(scores) {
+            if !candidate.trace.is_empty() {
+                candidate.trace.push(RankingStep::Fused { source: candidate.source.clone(), contribution: score });
+            }
+            let slot = candidate.id().and_then(|id| match index.get(&id) {
+                Some(&i) => Some(i),
+                None => {
+                    index.insert(id, merged.len());
+                    None
+                }
+            });
+            match slot {
+                Some(i) => {
+                    let kept = &mut merged[i];
+                    kept.score += score;
+                    kept.lexical_score = kept.lexical_score.or(candidate.lexical_score);
+                    kept.vector_score = kept.vector_score.or(candidate.vector_score);
+                    kept.trace.append(&mut candidate.trace);
+                }
+                None => {
+                    candidate.score = score;
+                    merged.push(candidate);
+                }
+            }
+        }
+    }
+
+    merged.sort_by(|a, b| b.score.total_cmp(&a.score));
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use emry_store::ChunkRecord;
+    use surrealdb::sql::Thing;
+
+    fn candidate(id: &str, source: &str, score: f32) -> Candidate {
+        Candidate {
+            chunk: ChunkRecord {
+                id: Some(Thing::from(("chunk", id))),
+                content: String::new(),
+                embedding: None,
+                embedding_q: None,
+                file: Thing::from(("file", "src/lib.rs")),
+                start_line: 1,
+                end_line: 1,
+                scopes: Vec::new(),
+                tags: Vec::new(),
+                sparse_terms: Vec::new(),
+                sparse_weights: Vec::new(),
+                language: None,
+                content_hash: String::new(),
+                duplicate_of: None,
+                doc: None,
+                doc_embedding: None,
+            },
+            score,
+            source: source.to_string(),
+            lexical_score: None,
+            vector_score: None,
+            graph_score: None,
+            graph_distance: None,
+            rerank_score: None,
+            trace: Vec::new(),
+        }
+    }
+
+    fn order(fused: &[Candidate]) -> Vec<String> {
+        fused.iter().map(|c| c.chunk.id.as_ref().unwrap().id.to_raw()).collect()
+    }
+
+    #[test]
+    fn test_rrf_ignores_score_scale() {
+        // The lexical source's scores dwarf the vector source's; only ranks should count.
+        let lists = vec![
+            vec![candidate("a", "lexical", 90.0), candidate("b", "lexical", 80.0)],
+            vec![candidate("b", "vector", 0.9), candidate("c", "vector", 0.8)],
+        ];
+        let fused = RrfFuser { k: RRF_K }.fuse(lists);
+        assert_eq!(order(&fused), vec!["b", "a", "c"]);
+        assert!((fused[0].score - (1.0 / 62.0 + 1.0 / 61.0)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_zscore_and_weighted_sum_per_source() {
+        let lists = || {
+            vec![
+                vec![candidate("a", "lexical", 3.0), candidate("b", "lexical", 1.0)],
+                vec![candidate("b", "vector", 0.9), candidate("a", "vector", 0.1)],
+            ]
+        };
+        let fused = ZScoreFuser.fuse(lists());
+        assert_eq!(fused.len(), 2);
+        assert!(fused.iter().all(|c| c.score.abs() < 1e-6));
+
+        let fused = WeightedFuser { lexical: 0.3, vector: 0.7 }.fuse(lists());
+        assert_eq!(order(&fused), vec!["b", "a"]);
+    }
+
+    #[test]
+    fn test_max_fusion_orders_by_score() {
+        let lists = vec![
+            vec![candidate("a", "lexical", 0.5), candidate("c", "lexical", 0.25)],
+            vec![candidate("c", "vector", 1.0), candidate("b", "vector", 0.75)],
+        ];
+        let fused = DedupFuser.fuse(lists);
+        assert_eq!(order(&fused), vec!["c", "b", "a"]);
+        assert_eq!(fused[0].score, 1.0);
+    }
+}
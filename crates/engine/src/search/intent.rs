@@ -0,0 +1,197 @@
+use emry_config::{IntentProfiles, KindBoosts};
+use emry_core::models::{Language, RankingStep};
+use std::path::Path;
+
+use super::pipeline::{Candidate, Scorer, SearchRequest};
+
+/// What a query is looking for, detected from its phrasing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueryIntent {
+    /// "what is X", "where is X defined"
+    Definition,
+    /// "who uses X", "callers of X"
+    Usage,
+    /// "which setting controls X", "env var for X"
+    Config,
+    General,
+}
+
+impl QueryIntent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QueryIntent::Definition => "definition",
+            QueryIntent::Usage => "usage",
+            QueryIntent::Config => "config",
+            QueryIntent::General => "general",
+        }
+    }
+}
+
+const USAGE_WORDS: &[&str] = &[
+    "uses", "used", "calls", "called", "caller", "callers", "usage", "usages", "references",
+    "referenced", "invoked", "invokes",
+];
+const DEFINITION_PHRASES: &[&str] = &[
+    "what is", "what's", "what are", "what does", "defined", "definition", "declaration",
+    "declared", "implementation of", "signature of", "where is",
+];
+const CONFIG_WORDS: &[&str] = &[
+    "config", "configuration", "configure", "setting", "settings", "env", "environment",
+    "option", "options", "flag", "flags", "yaml", "yml", "toml",
+];
+/// Words never taken as the symbol a query is about.
+const QUERY_WORDS: &[&str] = &[
+    "what", "whats", "what's", "who", "where", "which", "how", "why", "is", "are", "does", "do",
+    "the", "a", "an", "of", "to", "for", "in", "uses", "use", "used", "calls", "call", "called",
+    "callers", "caller", "defined", "definition", "declared", "declaration", "usages", "usage",
+    "references", "implementation", "signature", "from", "sites", "site", "gets", "invoked",
+];
+const DEFINITION_KEYWORDS: &[&str] = &[
+    "fn", "def", "class", "struct", "enum", "trait", "interface", "type", "func", "function",
+    "const", "let", "var", "impl", "module", "mod", "record",
+];
+const MODIFIERS: &[&str] = &[
+    "pub", "pub(crate)", "export", "default", "async", "static", "public", "private", "protected",
+    "abstract", "final", "unsafe", "extern",
+];
+
+/// Classify a query with word and phrase heuristics. Usage and config are
+/// checked first, so "where is X used" and "what is the config for X" are not
+/// mistaken for definition lookups.
+pub fn detect_intent(query: &str) -> QueryIntent {
+    let q = query.to_lowercase();
+    let words: Vec<&str> = q.split(|c: char| !c.is_alphanumeric() && c != '_').collect();
+    if words.iter().any(|w| USAGE_WORDS.contains(w)) {
+        return QueryIntent::Usage;
+    }
+    if words.iter().any(|w| CONFIG_WORDS.contains(w)) {
+        return QueryIntent::Config;
+    }
+    if DEFINITION_PHRASES.iter().any(|p| q.contains(p)) {
+        return QueryIntent::Definition;
+    }
+    QueryIntent::General
+}
+
+/// Identifier-like words of the query, lowercased.
+fn query_terms(query: &str) -> Vec<String> {
+    query
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .map(|w| w.to_lowercase())
+        .filter(|w| w.len() > 1 && !QUERY_WORDS.contains(&w.as_str()))
+        .collect()
+}
+
+/// Whether a line declares one of `terms`, e.g. `pub async fn term(`.
+fn declares(line: &str, terms: &[String]) -> bool {
+    let mut words = line.split_whitespace().skip_while(|w| MODIFIERS.contains(w));
+    let (Some(keyword), Some(name)) = (words.next(), words.next()) else {
+        return false;
+    };
+    if !DEFINITION_KEYWORDS.contains(&keyword) {
+        return false;
+    }
+    let name: String = name
+        .chars()
+        .take_while(|c| c.is_alphanumeric() || *c == '_')
+        .collect::<String>()
+        .to_lowercase();
+    terms.iter().any(|t| *t == name)
+}
+
+/// Whether a line calls or references one of `terms` (`term(`, `.term`, `term::`).
+fn references(line: &str, terms: &[String]) -> bool {
+    let line = line.to_lowercase();
+    terms.iter().any(|t| {
+        line.match_indices(t.as_str()).any(|(i, _)| {
+            let before = line[..i].chars().next_back();
+            let after = line[i + t.len()..].chars().next();
+            let bounded = !before.is_some_and(|c| c.is_alphanumeric() || c == '_')
+                && !after.is_some_and(|c| c.is_alphanumeric() || c == '_');
+            bounded && (matches!(after, Some('(') | Some(':')) || before == Some('.'))
+        })
+    })
+}
+
+/// Boosts candidates whose kind suits the query's intent, per `ranking.intent_profiles`.
+pub struct IntentScorer {
+    profiles: IntentProfiles,
+}
+
+impl IntentScorer {
+    pub fn new(profiles: IntentProfiles) -> Self {
+        Self { profiles }
+    }
+
+    fn boosts(&self, intent: QueryIntent) -> Option<&KindBoosts> {
+        match intent {
+            QueryIntent::Definition => Some(&self.profiles.definition),
+            QueryIntent::Usage => Some(&self.profiles.usage),
+            QueryIntent::Config => Some(&self.profiles.config),
+            QueryIntent::General => None,
+        }
+    }
+}
+
+impl Scorer for IntentScorer {
+    fn score(&self, req: &SearchRequest<'_>, candidate: &mut Candidate) {
+        let intent = detect_intent(req.query);
+        let Some(boosts) = self.boosts(intent) else {
+            return;
+        };
+        let terms = query_terms(req.query);
+        let chunk = &candidate.chunk;
+
+        let mut boost = 0.0;
+        let mut reasons = Vec::new();
+        if boosts.config > 0.0 {
+            let path = chunk.file.id.to_raw();
+            let lower = path.to_lowercase();
+            if Language::from_path(Path::new(&path)).is_document() || lower.contains("config") || lower.contains("settings") {
+                boost += boosts.config;
+                reasons.push("config or documentation file".to_string());
+            }
+        }
+        if !terms.is_empty() && (boosts.definition > 0.0 || boosts.usage > 0.0) {
+            let defines = chunk.content.lines().any(|l| declares(l, &terms));
+            let uses = chunk.content.lines().any(|l| !declares(l, &terms) && references(l, &terms));
+            if defines {
+                boost += boosts.definition;
+                reasons.push(format!("declares {}", terms.join(" / ")));
+            }
+            if uses {
+                boost += boosts.usage;
+                reasons.push(format!("references {}", terms.join(" / ")));
+            }
+        }
+        candidate.score *= 1.0 + boost;
+        if req.explain && boost > 0.0 {
+            candidate.trace.push(RankingStep::Intent { intent: intent.as_str().to_string(), reasons, factor: 1.0 + boost });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_intent() {
+        assert_eq!(detect_intent("what is SearchPipeline"), QueryIntent::Definition);
+        assert_eq!(detect_intent("where is parse_config used"), QueryIntent::Usage);
+        assert_eq!(detect_intent("who calls open_store"), QueryIntent::Usage);
+        assert_eq!(detect_intent("which setting controls the rerank top_n"), QueryIntent::Config);
+        assert_eq!(detect_intent("vector index rebuild"), QueryIntent::General);
+    }
+
+    #[test]
+    fn test_definition_and_usage_lines() {
+        let terms = query_terms("who calls open_store");
+        assert_eq!(terms, vec!["open_store"]);
+        assert!(declares("pub async fn open_store(path: &Path) {", &terms));
+        assert!(!declares("let store = open_store(path);", &terms));
+        assert!(references("let store = open_store(path);", &terms));
+        assert!(references("self.open_store", &terms));
+        assert!(!references("// reopen_stores later", &terms));
+    }
+}
@@ -0,0 +1,114 @@
+//! Case-sensitive and whole-word lexical matching.
+//!
+//! The full-text analyzer lowercases and stems, so `Map` and `map` (or
+//! `map` and `mapping`) hit the same chunks. A query can ask for exact
+//! matches with the modifiers `case:yes` and `word:yes` (`emry search
+//! --case-sensitive --word` adds them); `ExactMatchFilter` then drops lexical
+//! candidates whose content does not contain every query word accordingly.
+//! Vector candidates are left alone.
+
+use super::pipeline::{Candidate, CandidateFilter, SearchRequest};
+
+/// How strictly lexical candidates must contain the query words.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MatchOptions {
+    pub case_sensitive: bool,
+    pub whole_word: bool,
+}
+
+impl MatchOptions {
+    pub fn is_exact(&self) -> bool {
+        self.case_sensitive || self.whole_word
+    }
+
+    /// Split `query` into its text and modifiers. `case:yes` / `word:yes`
+    /// (or `no`) tokens are removed wherever they appear.
+    pub fn parse(query: &str) -> (String, Self) {
+        let mut options = Self::default();
+        let mut words = Vec::new();
+        for token in query.split_whitespace() {
+            match token.split_once(':') {
+                Some(("case", value @ ("yes" | "no"))) => options.case_sensitive = value == "yes",
+                Some(("word", value @ ("yes" | "no"))) => options.whole_word = value == "yes",
+                _ => words.push(token),
+            }
+        }
+        (words.join(" "), options)
+    }
+
+    /// The modifiers to append to a query for these options.
+    pub fn modifiers(&self) -> String {
+        let mut out = String::new();
+        if self.case_sensitive {
+            out.push_str(" case:yes");
+        }
+        if self.whole_word {
+            out.push_str(" word:yes");
+        }
+        out
+    }
+
+    /// Whether `text` contains every word of `query` under these options.
+    pub fn matches(&self, query: &str, text: &str) -> bool {
+        let (text, query) = if self.case_sensitive {
+            (text.to_string(), query.to_string())
+        } else {
+            (text.to_lowercase(), query.to_lowercase())
+        };
+        query
+            .split(|c: char| !is_word_char(c))
+            .filter(|w| !w.is_empty())
+            .all(|word| self.contains(&text, word))
+    }
+
+    fn contains(&self, text: &str, word: &str) -> bool {
+        if !self.whole_word {
+            return text.contains(word);
+        }
+        text.match_indices(word).any(|(at, _)| {
+            let before = text[..at].chars().next_back();
+            let after = text[at + word.len()..].chars().next();
+            !before.is_some_and(is_word_char) && !after.is_some_and(is_word_char)
+        })
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Drops lexical candidates that miss the request's exact-match options.
+pub struct ExactMatchFilter;
+
+impl CandidateFilter for ExactMatchFilter {
+    fn keep(&self, req: &SearchRequest<'_>, candidate: &Candidate) -> bool {
+        if !req.matching.is_exact() || candidate.vector_score.is_some() || candidate.lexical_score.is_none() {
+            return true;
+        }
+        req.matching.matches(req.query, &candidate.chunk.content)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_match() {
+        let (query, options) = MatchOptions::parse("Map case:yes word:yes");
+        assert_eq!(query, "Map");
+        assert_eq!(options, MatchOptions { case_sensitive: true, whole_word: true });
+        assert!(options.matches(&query, "let m = Map::new();"));
+        assert!(!options.matches(&query, "let m = map.get(k);"));
+        assert!(!options.matches(&query, "let m = HashMap::new();"));
+
+        let (query, options) = MatchOptions::parse("map word:yes");
+        assert!(options.matches(&query, "Map::new()"));
+        assert!(!options.matches(&query, "mapping"));
+
+        // Unknown keys and values stay part of the query.
+        let (query, options) = MatchOptions::parse("lang:rust case:maybe");
+        assert_eq!(query, "lang:rust case:maybe");
+        assert!(!options.is_exact());
+    }
+}
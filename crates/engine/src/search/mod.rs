@@ -1,3 +1,11 @@
+pub mod diagnostics;
+pub mod eval;
+pub mod expansion;
+pub mod fusion;
+pub mod intent;
+pub mod matching;
+pub mod pipeline;
+pub mod rerank;
 pub mod service;
-
-
+pub mod snippet;
+pub mod spelling;
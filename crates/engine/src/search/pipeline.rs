@@ -0,0 +1,845 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use emry_core::models::RankingStep;
+use emry_core::scope::PathScope;
+use emry_core::traits::Embedder;
+use emry_store::{ChunkRecord, SurrealStore};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::Arc;
+use tracing::error;
+
+use super::matching::{ExactMatchFilter, MatchOptions};
+
+/// Inputs shared by every stage of a search.
+pub struct SearchRequest<'a> {
+    pub query: &'a str,
+    pub limit: usize,
+    pub keywords: Option<&'a [String]>,
+    /// Case-sensitive / whole-word modifiers, applied by `ExactMatchFilter`.
+    pub matching: MatchOptions,
+    /// Record each stage's effect in `Candidate::trace`.
+    pub explain: bool,
+    /// Files the sources search, as indexed paths (the ids of their `file`
+    /// records, which are absolute); `None` for all. The pipeline sets it from
+    /// the filters that name their files.
+    pub files: Option<&'a [String]>,
+}
+
+impl SearchRequest<'_> {
+    /// Query text with any expansion keywords appended.
+    pub fn expanded_query(&self) -> String {
+        if let Some(kws) = self.keywords {
+            format!("{} {}", self.query, kws.join(" "))
+        } else {
+            self.query.to_string()
+        }
+    }
+
+    /// Terms of the (expanded) query that occur in `chunk`, for ranking traces.
+    pub fn matched_terms(&self, chunk: &ChunkRecord) -> Vec<String> {
+        let content = chunk.content.to_lowercase();
+        let mut terms = emry_core::keywords::extract_terms(&self.expanded_query());
+        terms.sort();
+        terms.dedup();
+        terms.retain(|t| content.contains(t.as_str()));
+        terms
+    }
+}
+
+/// A chunk moving through the pipeline along with its running score.
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    pub chunk: ChunkRecord,
+    pub score: f32,
+    /// Name of the source that produced this candidate.
+    pub source: String,
+    pub lexical_score: Option<f32>,
+    pub vector_score: Option<f32>,
+    pub graph_score: Option<f32>,
+    /// Hops from the nearest top hit, when a graph boost was applied.
+    pub graph_distance: Option<usize>,
+    pub rerank_score: Option<f32>,
+    /// Ranking steps so far, when the request asks to explain.
+    pub trace: Vec<RankingStep>,
+}
+
+impl Candidate {
+    pub fn id(&self) -> Option<String> {
+        self.chunk.id.as_ref().map(|t| t.to_string())
+    }
+}
+
+/// Produces an initial ranked list of candidates.
+#[async_trait]
+pub trait CandidateSource: Send + Sync {
+    fn name(&self) -> &str;
+    async fn fetch(&self, req: &SearchRequest<'_>) -> Result<Vec<Candidate>>;
+
+    /// Chunks this source matches regardless of `req.limit`, before filters;
+    /// `None` when it ranks rather than matches (nearest neighbours).
+    async fn count(&self, _req: &SearchRequest<'_>) -> Result<Option<usize>> {
+        Ok(None)
+    }
+}
+
+/// Drops candidates that should never be returned.
+pub trait CandidateFilter: Send + Sync {
+    fn keep(&self, req: &SearchRequest<'_>, candidate: &Candidate) -> bool;
+
+    /// The only files whose chunks `keep` accepts, when the filter can list
+    /// them. Sources then search just those files, so a narrow filter doesn't
+    /// leave the result list short.
+    fn files(&self) -> Option<&HashSet<String>> {
+        None
+    }
+}
+
+/// Adjusts candidate scores within a single source list.
+pub trait Scorer: Send + Sync {
+    fn score(&self, req: &SearchRequest<'_>, candidate: &mut Candidate);
+}
+
+/// Merges the per-source lists into one.
+pub trait Fuser: Send + Sync {
+    fn fuse(&self, lists: Vec<Vec<Candidate>>) -> Vec<Candidate>;
+}
+
+/// Final pass over the fused list (reranking, diversification, truncation).
+#[async_trait]
+pub trait PostProcessor: Send + Sync {
+    async fn process(&self, req: &SearchRequest<'_>, candidates: Vec<Candidate>) -> Result<Vec<Candidate>>;
+}
+
+/// Composable search: sources -> filters -> scorers -> fuser -> post-processors.
+pub struct SearchPipeline {
+    sources: Vec<Arc<dyn CandidateSource>>,
+    filters: Vec<Arc<dyn CandidateFilter>>,
+    scorers: Vec<Arc<dyn Scorer>>,
+    fuser: Arc<dyn Fuser>,
+    post_processors: Vec<Arc<dyn PostProcessor>>,
+}
+
+impl SearchPipeline {
+    pub fn builder() -> SearchPipelineBuilder {
+        SearchPipelineBuilder::default()
+    }
+
+    /// Vector (with centrality rerank) and full-text sources, merged by id, then
+    /// boosted by test coverage when `ranking.coverage` is set and graph-boosted
+    /// as `graph` and `ranking.graph` configure.
+    pub fn default_for(
+        store: Arc<SurrealStore>,
+        embedder: Option<Arc<dyn Embedder + Send + Sync>>,
+        graph: &emry_config::GraphConfig,
+        ranking: &emry_config::RankingConfig,
+    ) -> Self {
+        Self::default_builder(store, embedder, graph, ranking).build()
+    }
+
+    /// The stages of `default_for`, left open for additional post-processors.
+    pub fn default_builder(
+        store: Arc<SurrealStore>,
+        embedder: Option<Arc<dyn Embedder + Send + Sync>>,
+        graph: &emry_config::GraphConfig,
+        ranking: &emry_config::RankingConfig,
+    ) -> SearchPipelineBuilder {
+        let mut builder = Self::builder();
+        if let Some(embedder) = embedder {
+            builder = builder.source(Arc::new(VectorSource::new(store.clone(), embedder)));
+        }
+        builder = builder
+            .source(Arc::new(LexicalSource::new(store.clone())))
+            .source(Arc::new(TagSource::new(store.clone())))
+            .filter(Arc::new(ExactMatchFilter));
+        if ranking.coverage > 0.0 {
+            builder = builder.post_processor(Arc::new(CoverageBoost::new(store.clone(), ranking.coverage)));
+        }
+        builder.post_processor(Arc::new(GraphBoost::new(store, graph, ranking)))
+    }
+
+    /// Search over documentation only: BM25 over each chunk's doc comments,
+    /// and nearest doc embeddings when an embedder is available.
+    pub fn docs_for(store: Arc<SurrealStore>, embedder: Option<Arc<dyn Embedder + Send + Sync>>) -> Self {
+        let mut builder = Self::builder();
+        if let Some(embedder) = embedder {
+            builder = builder.source(Arc::new(DocVectorSource::new(store.clone(), embedder)));
+        }
+        builder.source(Arc::new(DocLexicalSource::new(store))).build()
+    }
+
+    /// Add a source to an already built pipeline.
+    pub fn with_source(mut self, source: Arc<dyn CandidateSource>) -> Self {
+        self.sources.push(source);
+        self
+    }
+
+    /// Replace the fuser of an already built pipeline.
+    pub fn with_fuser(mut self, fuser: Arc<dyn Fuser>) -> Self {
+        self.fuser = fuser;
+        self
+    }
+
+    /// Add a filter to an already built pipeline.
+    pub fn with_filter(mut self, filter: Arc<dyn CandidateFilter>) -> Self {
+        self.filters.push(filter);
+        self
+    }
+
+    /// Add a scorer to an already built pipeline.
+    pub fn with_scorer(mut self, scorer: Arc<dyn Scorer>) -> Self {
+        self.scorers.push(scorer);
+        self
+    }
+
+    pub async fn run(&self, req: &SearchRequest<'_>) -> Result<Vec<Candidate>> {
+        let files = self.filtered_files();
+        let req = &SearchRequest { files: files.as_deref().or(req.files), ..*req };
+        let mut lists = Vec::with_capacity(self.sources.len());
+
+        for source in &self.sources {
+            // A failing source degrades the result set instead of failing the search.
+            let mut list = match source.fetch(req).await {
+                Ok(list) => list,
+                Err(e) => {
+                    error!("{} search failed: {}", source.name(), e);
+                    continue;
+                }
+            };
+
+            if req.explain {
+                for (rank, c) in list.iter_mut().enumerate() {
+                    // Sources with more to say (e.g. vector distance) record their own step.
+                    if c.trace.is_empty() {
+                        c.trace.push(RankingStep::Retrieved {
+                            source: source.name().to_string(),
+                            rank: rank + 1,
+                            score: c.score,
+                            matched_terms: req.matched_terms(&c.chunk),
+                            distance: None,
+                        });
+                    }
+                }
+            }
+            list.retain(|c| self.filters.iter().all(|f| f.keep(req, c)));
+            for candidate in list.iter_mut() {
+                for scorer in &self.scorers {
+                    scorer.score(req, candidate);
+                }
+            }
+            lists.push(list);
+        }
+
+        let mut fused = self.fuser.fuse(lists);
+        for post in &self.post_processors {
+            fused = post.process(req, fused).await?;
+        }
+        Ok(fused)
+    }
+
+    /// Match counts of the sources that can tell, by source name.
+    pub async fn count(&self, req: &SearchRequest<'_>) -> Vec<(String, usize)> {
+        let files = self.filtered_files();
+        let req = &SearchRequest { files: files.as_deref().or(req.files), ..*req };
+        let mut counts = Vec::new();
+        for source in &self.sources {
+            match source.count(req).await {
+                Ok(Some(count)) => counts.push((source.name().to_string(), count)),
+                Ok(None) => {}
+                Err(e) => error!("{} count failed: {}", source.name(), e),
+            }
+        }
+        counts
+    }
+
+    /// The files every filter listing its files accepts, sorted; `None` when no filter lists them.
+    fn filtered_files(&self) -> Option<Vec<String>> {
+        let mut allowed: Option<Vec<String>> = None;
+        for files in self.filters.iter().filter_map(|f| f.files()) {
+            allowed = Some(match allowed {
+                None => files.iter().cloned().collect(),
+                Some(allowed) => allowed.into_iter().filter(|f| files.contains(f)).collect(),
+            });
+        }
+        allowed.map(|mut files| {
+            files.sort();
+            files
+        })
+    }
+}
+
+#[derive(Default)]
+pub struct SearchPipelineBuilder {
+    sources: Vec<Arc<dyn CandidateSource>>,
+    filters: Vec<Arc<dyn CandidateFilter>>,
+    scorers: Vec<Arc<dyn Scorer>>,
+    fuser: Option<Arc<dyn Fuser>>,
+    post_processors: Vec<Arc<dyn PostProcessor>>,
+}
+
+impl SearchPipelineBuilder {
+    pub fn source(mut self, source: Arc<dyn CandidateSource>) -> Self {
+        self.sources.push(source);
+        self
+    }
+
+    pub fn filter(mut self, filter: Arc<dyn CandidateFilter>) -> Self {
+        self.filters.push(filter);
+        self
+    }
+
+    pub fn scorer(mut self, scorer: Arc<dyn Scorer>) -> Self {
+        self.scorers.push(scorer);
+        self
+    }
+
+    pub fn fuser(mut self, fuser: Arc<dyn Fuser>) -> Self {
+        self.fuser = Some(fuser);
+        self
+    }
+
+    pub fn post_processor(mut self, post: Arc<dyn PostProcessor>) -> Self {
+        self.post_processors.push(post);
+        self
+    }
+
+    pub fn build(self) -> SearchPipeline {
+        SearchPipeline {
+            sources: self.sources,
+            filters: self.filters,
+            scorers: self.scorers,
+            fuser: self.fuser.unwrap_or_else(|| Arc::new(DedupFuser)),
+            post_processors: self.post_processors,
+        }
+    }
+}
+
+/// Distinct terms in a stable order, as sparse lookups take them.
+fn sorted_terms(mut terms: Vec<String>) -> Vec<String> {
+    terms.sort();
+    terms.dedup();
+    terms
+}
+
+fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm = |v: &[f32]| v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let denom = norm(a) * norm(b);
+    if denom > 0.0 { dot / denom } else { 0.0 }
+}
+
+/// Candidates in store order with a rank-based score so list position
+/// survives fusion: 1 / (rank + 1). Each chunk's store score (BM25, cosine
+/// similarity) is kept as the component `component` sets.
+fn ranked(scored: Vec<(ChunkRecord, f32)>, source: &str, component: impl Fn(&mut Candidate, f32)) -> Vec<Candidate> {
+    scored
+        .into_iter()
+        .enumerate()
+        .map(|(rank, (chunk, store_score))| {
+            let mut candidate = Candidate {
+                chunk,
+                score: 1.0 / (rank as f32 + 1.0),
+                source: source.to_string(),
+                lexical_score: None,
+                vector_score: None,
+                graph_score: None,
+                graph_distance: None,
+                rerank_score: None,
+                trace: Vec::new(),
+            };
+            component(&mut candidate, store_score);
+            candidate
+        })
+        .collect()
+}
+
+pub struct VectorSource {
+    store: Arc<SurrealStore>,
+    embedder: Arc<dyn Embedder + Send + Sync>,
+}
+
+impl VectorSource {
+    pub fn new(store: Arc<SurrealStore>, embedder: Arc<dyn Embedder + Send + Sync>) -> Self {
+        Self { store, embedder }
+    }
+}
+
+#[async_trait]
+impl CandidateSource for VectorSource {
+    fn name(&self) -> &str {
+        "vector"
+    }
+
+    async fn fetch(&self, req: &SearchRequest<'_>) -> Result<Vec<Candidate>> {
+        let embedding = self.embedder.embed(&req.expanded_query()).await?;
+        let scored = self.store.search_with_rerank(embedding.clone(), req.limit, req.files).await?;
+        let mut candidates = ranked(scored, self.name(), |c, similarity| c.vector_score = Some(similarity));
+        for (rank, c) in candidates.iter_mut().enumerate() {
+            if req.explain {
+                c.trace.push(RankingStep::Retrieved {
+                    source: self.name().to_string(),
+                    rank: rank + 1,
+                    score: c.score,
+                    matched_terms: Vec::new(),
+                    distance: c.chunk.embedding.as_deref().map(|e| 1.0 - cosine(&embedding, e)),
+                });
+            }
+        }
+        Ok(candidates)
+    }
+}
+
+pub struct LexicalSource {
+    store: Arc<SurrealStore>,
+}
+
+impl LexicalSource {
+    pub fn new(store: Arc<SurrealStore>) -> Self {
+        Self { store }
+    }
+}
+
+#[async_trait]
+impl CandidateSource for LexicalSource {
+    fn name(&self) -> &str {
+        "lexical"
+    }
+
+    async fn fetch(&self, req: &SearchRequest<'_>) -> Result<Vec<Candidate>> {
+        let scored = self.store.search_fts(&req.expanded_query(), req.limit, req.files).await?;
+        Ok(ranked(scored, self.name(), |c, bm25| c.lexical_score = Some(bm25)))
+    }
+
+    async fn count(&self, req: &SearchRequest<'_>) -> Result<Option<usize>> {
+        Ok(Some(self.store.count_fts(&req.expanded_query(), req.files).await?))
+    }
+}
+
+/// Chunks whose index-time tags match the request's expansion keywords.
+///
+/// Contributes nothing to plain searches; with keywords (smart search, the
+/// agent's `search_code`) it surfaces chunks the query text alone misses.
+pub struct TagSource {
+    store: Arc<SurrealStore>,
+}
+
+impl TagSource {
+    pub fn new(store: Arc<SurrealStore>) -> Self {
+        Self { store }
+    }
+
+    /// Tags are normalized terms, so normalize the keywords the same way.
+    fn terms(req: &SearchRequest<'_>) -> Vec<String> {
+        let Some(keywords) = req.keywords else {
+            return Vec::new();
+        };
+        let mut terms = emry_core::keywords::extract_terms(&keywords.join(" "));
+        terms.sort();
+        terms.dedup();
+        terms
+    }
+}
+
+#[async_trait]
+impl CandidateSource for TagSource {
+    fn name(&self) -> &str {
+        "tags"
+    }
+
+    async fn fetch(&self, req: &SearchRequest<'_>) -> Result<Vec<Candidate>> {
+        let terms = Self::terms(req);
+        if terms.is_empty() {
+            return Ok(Vec::new());
+        }
+        let chunks = self.store.search_by_tags(&terms, req.limit, req.files).await?;
+        // Scored by the number of query terms among the chunk's tags.
+        let scored = chunks
+            .into_iter()
+            .map(|c| {
+                let shared = c.tags.iter().filter(|t| terms.iter().any(|q| q.eq_ignore_ascii_case(t))).count();
+                (c, shared as f32)
+            })
+            .collect();
+        Ok(ranked(scored, self.name(), |c, shared| c.lexical_score = Some(shared)))
+    }
+
+    async fn count(&self, req: &SearchRequest<'_>) -> Result<Option<usize>> {
+        let terms = Self::terms(req);
+        if terms.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(self.store.count_by_tags(&terms, req.files).await?))
+    }
+}
+
+/// Chunks scored by their index-time sparse term-weight vectors.
+///
+/// Query terms are weighted by rarity across the index, so a rare identifier
+/// outranks chunks that merely repeat common words. Scores are normalised to
+/// the top hit and scaled by `ranking.sparse`.
+pub struct SparseSource {
+    store: Arc<SurrealStore>,
+    weight: f32,
+}
+
+impl SparseSource {
+    pub fn new(store: Arc<SurrealStore>, weight: f32) -> Self {
+        Self { store, weight }
+    }
+}
+
+#[async_trait]
+impl CandidateSource for SparseSource {
+    fn name(&self) -> &str {
+        "sparse"
+    }
+
+    async fn fetch(&self, req: &SearchRequest<'_>) -> Result<Vec<Candidate>> {
+        let terms = sorted_terms(emry_core::keywords::sparse_terms(&req.expanded_query()));
+        if terms.is_empty() {
+            return Ok(Vec::new());
+        }
+        let scored = self.store.search_sparse(&terms, req.limit, req.files).await?;
+        let top = scored.first().map(|(_, s)| *s).filter(|s| *s > 0.0).unwrap_or(1.0);
+        Ok(scored
+            .into_iter()
+            .map(|(chunk, score)| {
+                let score = self.weight * score / top;
+                Candidate {
+                    chunk,
+                    score,
+                    source: self.name().to_string(),
+                    lexical_score: Some(score),
+                    vector_score: None,
+                    graph_score: None,
+                    graph_distance: None,
+                    rerank_score: None,
+                    trace: Vec::new(),
+                }
+            })
+            .collect())
+    }
+
+    async fn count(&self, req: &SearchRequest<'_>) -> Result<Option<usize>> {
+        let terms = sorted_terms(emry_core::keywords::sparse_terms(&req.expanded_query()));
+        Ok(Some(if terms.is_empty() { 0 } else { self.store.count_sparse(&terms, req.files).await? }))
+    }
+}
+
+/// Chunks matching the CJK character bigrams of the query (`bm25.cjk_segmentation`).
+///
+/// Bigrams are stored with each chunk's sparse terms, so this is a sparse
+/// search restricted to them; scores are normalised to the top hit.
+pub struct CjkSource {
+    store: Arc<SurrealStore>,
+}
+
+impl CjkSource {
+    pub fn new(store: Arc<SurrealStore>) -> Self {
+        Self { store }
+    }
+}
+
+#[async_trait]
+impl CandidateSource for CjkSource {
+    fn name(&self) -> &str {
+        "cjk"
+    }
+
+    async fn fetch(&self, req: &SearchRequest<'_>) -> Result<Vec<Candidate>> {
+        let terms = sorted_terms(emry_core::keywords::cjk_bigrams(req.query));
+        if terms.is_empty() {
+            return Ok(Vec::new());
+        }
+        let scored = self.store.search_sparse(&terms, req.limit, req.files).await?;
+        let top = scored.first().map(|(_, s)| *s).filter(|s| *s > 0.0).unwrap_or(1.0);
+        Ok(scored
+            .into_iter()
+            .map(|(chunk, score)| {
+                let score = score / top;
+                Candidate {
+                    chunk,
+                    score,
+                    source: self.name().to_string(),
+                    lexical_score: Some(score),
+                    vector_score: None,
+                    graph_score: None,
+                    graph_distance: None,
+                    rerank_score: None,
+                    trace: Vec::new(),
+                }
+            })
+            .collect())
+    }
+
+    async fn count(&self, req: &SearchRequest<'_>) -> Result<Option<usize>> {
+        let terms = sorted_terms(emry_core::keywords::cjk_bigrams(req.query));
+        if terms.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(self.store.count_sparse(&terms, req.files).await?))
+    }
+}
+
+/// Chunks whose doc comments match the query under BM25 (`emry search --docs`).
+pub struct DocLexicalSource {
+    store: Arc<SurrealStore>,
+}
+
+impl DocLexicalSource {
+    pub fn new(store: Arc<SurrealStore>) -> Self {
+        Self { store }
+    }
+}
+
+#[async_trait]
+impl CandidateSource for DocLexicalSource {
+    fn name(&self) -> &str {
+        "docs"
+    }
+
+    async fn fetch(&self, req: &SearchRequest<'_>) -> Result<Vec<Candidate>> {
+        let scored = self.store.search_docs_fts(&req.expanded_query(), req.limit, req.files).await?;
+        Ok(ranked(scored, self.name(), |c, bm25| c.lexical_score = Some(bm25)))
+    }
+
+    async fn count(&self, req: &SearchRequest<'_>) -> Result<Option<usize>> {
+        Ok(Some(self.store.count_docs_fts(&req.expanded_query(), req.files).await?))
+    }
+}
+
+/// Chunks whose doc comments' embeddings are nearest the query's (`emry search --docs`).
+pub struct DocVectorSource {
+    store: Arc<SurrealStore>,
+    embedder: Arc<dyn Embedder + Send + Sync>,
+}
+
+impl DocVectorSource {
+    pub fn new(store: Arc<SurrealStore>, embedder: Arc<dyn Embedder + Send + Sync>) -> Self {
+        Self { store, embedder }
+    }
+}
+
+#[async_trait]
+impl CandidateSource for DocVectorSource {
+    fn name(&self) -> &str {
+        "doc_vector"
+    }
+
+    async fn fetch(&self, req: &SearchRequest<'_>) -> Result<Vec<Candidate>> {
+        let embedding = self.embedder.embed(&req.expanded_query()).await?;
+        let scored = self.store.search_docs_vector(embedding.clone(), req.limit, req.files).await?;
+        let mut candidates = ranked(scored, self.name(), |c, similarity| c.vector_score = Some(similarity));
+        for (rank, c) in candidates.iter_mut().enumerate() {
+            if req.explain {
+                c.trace.push(RankingStep::Retrieved {
+                    source: self.name().to_string(),
+                    rank: rank + 1,
+                    score: c.score,
+                    matched_terms: Vec::new(),
+                    distance: c.chunk.doc_embedding.as_deref().map(|e| 1.0 - cosine(&embedding, e)),
+                });
+            }
+        }
+        Ok(candidates)
+    }
+}
+
+/// Keeps only candidates whose file is inside a path scope.
+pub struct ScopeFilter {
+    scope: PathScope,
+}
+
+impl ScopeFilter {
+    pub fn new(scope: PathScope) -> Self {
+        Self { scope }
+    }
+}
+
+impl CandidateFilter for ScopeFilter {
+    fn keep(&self, _req: &SearchRequest<'_>, candidate: &Candidate) -> bool {
+        self.scope.contains(Path::new(&candidate.chunk.file.id.to_raw()))
+    }
+}
+
+/// Keeps only candidates from a fixed set of files, e.g. the files one owner owns.
+pub struct FileSetFilter {
+    files: HashSet<String>,
+}
+
+impl FileSetFilter {
+    pub fn new(files: impl IntoIterator<Item = String>) -> Self {
+        Self { files: files.into_iter().collect() }
+    }
+}
+
+impl CandidateFilter for FileSetFilter {
+    fn keep(&self, _req: &SearchRequest<'_>, candidate: &Candidate) -> bool {
+        self.files.contains(&candidate.chunk.file.id.to_raw())
+    }
+
+    fn files(&self) -> Option<&HashSet<String>> {
+        Some(&self.files)
+    }
+}
+
+/// Concatenates all lists and keeps one candidate per chunk id, merging
+/// component scores, best fused score first.
+pub struct DedupFuser;
+
+impl Fuser for DedupFuser {
+    fn fuse(&self, lists: Vec<Vec<Candidate>>) -> Vec<Candidate> {
+        let mut results: Vec<Candidate> = lists.into_iter().flatten().collect();
+        results.sort_by(|a, b| a.chunk.id.cmp(&b.chunk.id));
+        results.dedup_by(|dup, kept| {
+            if dup.chunk.id != kept.chunk.id {
+                return false;
+            }
+            kept.lexical_score = kept.lexical_score.or(dup.lexical_score);
+            kept.vector_score = kept.vector_score.or(dup.vector_score);
+            kept.score = kept.score.max(dup.score);
+            kept.trace.append(&mut dup.trace);
+            true
+        });
+        results.sort_by(|a, b| b.score.total_cmp(&a.score));
+        results
+    }
+}
+
+/// Boosts candidates that sit near the top hits in the call/import graph.
+///
+/// Distances come from the store's precomputed neighbourhoods, so this costs
+/// two lookups per search regardless of graph size. Without neighbourhoods
+/// (e.g. an index built before they existed) candidates pass through unchanged
+/// apart from being ordered by score.
+pub struct GraphBoost {
+    store: Arc<SurrealStore>,
+    seeds: usize,
+    max_hops: usize,
+    decay: f32,
+    weight: f32,
+}
+
+impl GraphBoost {
+    pub fn new(store: Arc<SurrealStore>, graph: &emry_config::GraphConfig, ranking: &emry_config::RankingConfig) -> Self {
+        Self {
+            store,
+            seeds: 3,
+            max_hops: graph.max_depth,
+            decay: graph.decay,
+            weight: ranking.graph * graph.path_weight,
+        }
+    }
+}
+
+#[async_trait]
+impl PostProcessor for GraphBoost {
+    async fn process(&self, req: &SearchRequest<'_>, mut candidates: Vec<Candidate>) -> Result<Vec<Candidate>> {
+        let by_score = |a: &Candidate, b: &Candidate| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal);
+        candidates.sort_by(by_score);
+        if candidates.len() < 2 || self.weight <= 0.0 {
+            return Ok(candidates);
+        }
+
+        let chunk_ids: Vec<_> = candidates.iter().filter_map(|c| c.chunk.id.clone()).collect();
+        let symbols = self.store.symbols_for_chunks(&chunk_ids).await?;
+        let symbols_of = |c: &Candidate| c.id().and_then(|id| symbols.get(&id)).cloned().unwrap_or_default();
+
+        let seed_symbols: Vec<String> = candidates.iter().take(self.seeds).flat_map(symbols_of).collect();
+        if seed_symbols.is_empty() {
+            return Ok(candidates);
+        }
+
+        // Hops to each reachable symbol, and the seed symbol it is reached from.
+        let mut distance: HashMap<String, (usize, String)> = HashMap::new();
+        for record in self.store.get_neighborhoods(&seed_symbols).await? {
+            for entry in record.reachable {
+                let hops = entry.hops as usize;
+                if hops <= self.max_hops {
+                    let d = distance.entry(entry.node).or_insert((hops, record.node.clone()));
+                    if hops < d.0 {
+                        *d = (hops, record.node.clone());
+                    }
+                }
+            }
+        }
+
+        for candidate in candidates.iter_mut().skip(self.seeds) {
+            let nearest = symbols_of(&*candidate)
+                .into_iter()
+                .filter_map(|s| distance.get(&s).map(|(hops, seed)| (*hops, seed.clone(), s)))
+                .min_by_key(|(hops, _, _)| *hops);
+            if let Some((hops, seed, symbol)) = nearest {
+                let boost = self.weight * self.decay.powi(hops as i32 - 1);
+                candidate.score += boost;
+                candidate.graph_score = Some(boost);
+                candidate.graph_distance = Some(hops);
+                if req.explain {
+                    candidate.trace.push(RankingStep::Graph { from: seed, to: symbol, hops, boost });
+                }
+            }
+        }
+
+        candidates.sort_by(by_score);
+        Ok(candidates)
+    }
+}
+
+/// Scales each candidate by how much of it the imported test coverage
+/// exercised: a chunk whose instrumented lines all ran gets `1 + weight`
+/// times its score, so tested implementations rise above dead experiments.
+/// Chunks of files without (current) coverage are left as they are.
+pub struct CoverageBoost {
+    store: Arc<SurrealStore>,
+    weight: f32,
+}
+
+impl CoverageBoost {
+    pub fn new(store: Arc<SurrealStore>, weight: f32) -> Self {
+        Self { store, weight }
+    }
+}
+
+#[async_trait]
+impl PostProcessor for CoverageBoost {
+    async fn process(&self, req: &SearchRequest<'_>, mut candidates: Vec<Candidate>) -> Result<Vec<Candidate>> {
+        let mut files: Vec<_> = candidates.iter().map(|c| c.chunk.file.clone()).collect();
+        files.sort();
+        files.dedup();
+        let coverage = self.store.indexed_coverage(&files).await?;
+        if coverage.is_empty() {
+            return Ok(candidates);
+        }
+
+        for candidate in candidates.iter_mut() {
+            let chunk = &candidate.chunk;
+            let ratio = coverage.get(&chunk.file.id.to_raw()).and_then(|c| c.ratio(chunk.start_line, chunk.end_line));
+            if let Some(ratio) = ratio {
+                let factor = 1.0 + self.weight * ratio;
+                candidate.score *= factor;
+                if req.explain {
+                    candidate.trace.push(RankingStep::Coverage { ratio, factor });
+                }
+            }
+        }
+        candidates.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+        Ok(candidates)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filtered_files_intersects_file_filters() {
+        let paths = |ps: &[&str]| ps.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+        let pipeline = SearchPipeline::builder().filter(Arc::new(ExactMatchFilter)).build();
+        assert_eq!(pipeline.filtered_files(), None);
+
+        let pipeline = pipeline
+            .with_filter(Arc::new(FileSetFilter::new(paths(&["src/b.rs", "src/a.rs", "lib/c.py"]))))
+            .with_filter(Arc::new(FileSetFilter::new(paths(&["src/a.rs", "src/b.rs", "src/d.rs"]))));
+        assert_eq!(pipeline.filtered_files(), Some(paths(&["src/a.rs", "src/b.rs"])));
+    }
+}
@@ -0,0 +1,59 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use emry_core::models::RankingStep;
+use emry_core::traits::Reranker;
+use std::sync::Arc;
+use tracing::error;
+
+use super::pipeline::{Candidate, PostProcessor, SearchRequest};
+
+/// Reorders the top `top_n` candidates by cross-encoder relevance.
+///
+/// Candidates below the cut keep their fused order. If the reranker fails the
+/// list is returned unchanged, matching how failing sources are handled.
+pub struct RerankStage {
+    reranker: Arc<dyn Reranker>,
+    top_n: usize,
+}
+
+impl RerankStage {
+    pub fn new(reranker: Arc<dyn Reranker>, top_n: usize) -> Self {
+        Self { reranker, top_n }
+    }
+}
+
+#[async_trait]
+impl PostProcessor for RerankStage {
+    async fn process(&self, req: &SearchRequest<'_>, mut candidates: Vec<Candidate>) -> Result<Vec<Candidate>> {
+        let n = self.top_n.min(candidates.len());
+        if n < 2 {
+            return Ok(candidates);
+        }
+
+        let documents: Vec<String> = candidates[..n].iter().map(|c| c.chunk.content.clone()).collect();
+        let scores = match self.reranker.score(req.query, &documents).await {
+            Ok(scores) if scores.len() == n => scores,
+            Ok(scores) => {
+                error!("rerank returned {} scores for {} documents", scores.len(), n);
+                return Ok(candidates);
+            }
+            Err(e) => {
+                error!("rerank failed: {}", e);
+                return Ok(candidates);
+            }
+        };
+
+        for (candidate, score) in candidates[..n].iter_mut().zip(scores) {
+            candidate.rerank_score = Some(score);
+            if req.explain {
+                candidate.trace.push(RankingStep::Reranked { score });
+            }
+        }
+        candidates[..n].sort_by(|a, b| {
+            b.rerank_score
+                .partial_cmp(&a.rerank_score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        Ok(candidates)
+    }
+}
@@ -1,12 +1,32 @@
 use anyhow::Result;
-use emry_core::traits::Embedder;
+use emry_config::{GraphConfig, RankingConfig};
+use emry_core::models::{ChunkLocation, EdgeKind};
+use futures::future::join_all;
+use std::collections::HashMap;
+use tracing::error;
+use emry_core::traits::{Embedder, Reranker};
 use emry_store::{SurrealStore, ChunkRecord};
 use std::sync::Arc;
-use tracing::error;
+
+use super::expansion::QueryExpander;
+use super::matching::MatchOptions;
+use super::pipeline::{CandidateFilter, CandidateSource, Fuser, Scorer, SearchPipeline, SearchRequest};
+use super::rerank::RerankStage;
+use super::spelling::SpellCorrector;
 
 pub struct SearchService {
     store: Arc<SurrealStore>,
-    embedder: Option<Arc<dyn Embedder + Send + Sync>>,
+    pipeline: SearchPipeline,
+    expander: Option<Arc<QueryExpander>>,
+    speller: SpellCorrector,
+}
+
+/// A deduplicated result of `search_many`.
+#[derive(Debug, Clone)]
+pub struct MultiSearchHit {
+    pub chunk: ChunkRecord,
+    /// Indices into the input queries that returned this chunk.
+    pub queries: Vec<usize>,
 }
 
 impl SearchService {
@@ -17,49 +37,208 @@ impl SearchService {
     pub fn new(
         store: Arc<SurrealStore>,
         embedder: Option<Arc<dyn Embedder + Send + Sync>>,
+        graph: &GraphConfig,
+        ranking: &RankingConfig,
     ) -> Self {
-        Self { store, embedder }
+        let pipeline = SearchPipeline::default_for(store.clone(), embedder, graph, ranking);
+        Self { speller: SpellCorrector::new(store.clone()), store, pipeline, expander: None }
     }
 
-    fn format_query(query: &str, keywords: Option<&[String]>) -> String {
-        if let Some(kws) = keywords {
-            format!("{} {}", query, kws.join(" "))
-        } else {
-            query.to_string()
+    /// Default pipeline followed by a cross-encoder pass over the top `top_n` hits.
+    pub fn with_reranker(
+        store: Arc<SurrealStore>,
+        embedder: Option<Arc<dyn Embedder + Send + Sync>>,
+        reranker: Arc<dyn Reranker>,
+        top_n: usize,
+        graph: &GraphConfig,
+        ranking: &RankingConfig,
+    ) -> Self {
+        let pipeline = SearchPipeline::default_builder(store.clone(), embedder, graph, ranking)
+            .post_processor(Arc::new(RerankStage::new(reranker, top_n)))
+            .build();
+        Self { speller: SpellCorrector::new(store.clone()), store, pipeline, expander: None }
+    }
+
+    /// Search doc comments and docstrings instead of code (see `SearchPipeline::docs_for`).
+    pub fn for_docs(store: Arc<SurrealStore>, embedder: Option<Arc<dyn Embedder + Send + Sync>>) -> Self {
+        let pipeline = SearchPipeline::docs_for(store.clone(), embedder);
+        Self { speller: SpellCorrector::new(store.clone()), store, pipeline, expander: None }
+    }
+
+    /// Use a custom pipeline (extra sources, filters, rerankers) instead of the default.
+    pub fn with_pipeline(store: Arc<SurrealStore>, pipeline: SearchPipeline) -> Self {
+        Self { speller: SpellCorrector::new(store.clone()), store, pipeline, expander: None }
+    }
+
+    /// Fetch candidates from `source` too (e.g. a `SparseSource`).
+    pub fn with_source(mut self, source: Arc<dyn CandidateSource>) -> Self {
+        self.pipeline = self.pipeline.with_source(source);
+        self
+    }
+
+    /// Merge per-source results with `fuser` (see `fusion::fuser_for`).
+    pub fn with_fuser(mut self, fuser: Arc<dyn Fuser>) -> Self {
+        self.pipeline = self.pipeline.with_fuser(fuser);
+        self
+    }
+
+    /// Adjust every source's candidates with `scorer` (e.g. an `IntentScorer`).
+    pub fn with_scorer(mut self, scorer: Arc<dyn Scorer>) -> Self {
+        self.pipeline = self.pipeline.with_scorer(scorer);
+        self
+    }
+
+    /// Drop results the filter rejects (e.g. a `ScopeFilter`) from every search.
+    pub fn with_filter(mut self, filter: Arc<dyn CandidateFilter>) -> Self {
+        self.pipeline = self.pipeline.with_filter(filter);
+        self
+    }
+
+    /// Add `expander`'s terms to the keywords of every search.
+    pub fn with_query_expander(mut self, expander: Arc<QueryExpander>) -> Self {
+        self.expander = Some(expander);
+        self
+    }
+
+    /// `query` with misspelled identifiers replaced by the closest symbol
+    /// names, or `None` when it has none. Failures are logged and give `None`.
+    pub async fn did_you_mean(&self, query: &str) -> Option<String> {
+        match self.speller.did_you_mean(query).await {
+            Ok(corrected) => corrected,
+            Err(e) => {
+                error!("spell correction for '{}' failed: {}", query, e);
+                None
+            }
+        }
+    }
+
+    /// Other locations of each hit's text, by chunk id. A failed lookup is
+    /// logged and the hits go without them.
+    pub async fn duplicate_locations(&self, chunk_ids: &[String]) -> HashMap<String, Vec<ChunkLocation>> {
+        match self.store.duplicate_locations(chunk_ids).await {
+            Ok(locations) => locations,
+            Err(e) => {
+                error!("duplicate lookup failed: {}", e);
+                HashMap::new()
+            }
         }
     }
 
+    /// `keywords` plus the expander's terms for `query`. A failed expansion
+    /// is logged and the search runs with `keywords` alone.
+    async fn expand_keywords(&self, query: &str, keywords: Option<&[String]>) -> Option<Vec<String>> {
+        let mut all = keywords.map(|k| k.to_vec());
+        if let Some(expander) = &self.expander {
+            match expander.expand(query).await {
+                Ok(terms) if !terms.is_empty() => all.get_or_insert_with(Vec::new).extend(terms),
+                Ok(_) => {}
+                Err(e) => error!("query expansion for '{}' failed: {}", query, e),
+            }
+        }
+        all
+    }
+
     pub async fn search(&self, query: &str, limit: usize, keywords: Option<&[String]>) -> Result<Vec<ChunkRecord>> {
-        let mut results = Vec::new();
-        
-        let search_query = query.to_string();
+        let (query, matching) = MatchOptions::parse(query);
+        let keywords = self.expand_keywords(&query, keywords).await;
+        let req = SearchRequest { query: &query, limit, keywords: keywords.as_deref(), matching, explain: false, files: None };
+        let candidates = self.pipeline.run(&req).await?;
+        Ok(candidates.into_iter().map(|c| c.chunk).collect())
+    }
 
-        if let Some(embedder) = &self.embedder {
-            let embed_query = Self::format_query(&search_query, keywords);
+    /// Run several queries concurrently and merge their results.
+    ///
+    /// Hits are interleaved by rank across queries and deduplicated by chunk id.
+    /// A failing query is logged and skipped unless every query fails.
+    pub async fn search_many(&self, queries: &[String], limit: usize) -> Result<Vec<MultiSearchHit>> {
+        let results = join_all(queries.iter().map(|q| self.search(q, limit, None))).await;
 
-            if let Ok(embedding) = embedder.embed(&embed_query).await {
-                match self.store.search_with_rerank(embedding, limit).await {
-                    Ok(vec_results) => {
-                        results.extend(vec_results);
-                    }
-                    Err(e) => error!("Vector search failed: {}", e),
+        let mut lists = Vec::with_capacity(results.len());
+        let mut first_err = None;
+        for (query, result) in queries.iter().zip(results) {
+            match result {
+                Ok(list) => lists.push(list),
+                Err(e) => {
+                    error!("search for '{}' failed: {}", query, e);
+                    lists.push(Vec::new());
+                    first_err.get_or_insert(e);
                 }
             }
         }
-        
-        let fts_query = Self::format_query(query, keywords);
+        if let Some(e) = first_err {
+            if lists.iter().all(|l| l.is_empty()) {
+                return Err(e);
+            }
+        }
 
-        match self.store.search_fts(&fts_query, limit).await {
-            Ok(fts_results) => {
-                results.extend(fts_results);
+        let mut hits: Vec<MultiSearchHit> = Vec::new();
+        let mut index_by_id: HashMap<String, usize> = HashMap::new();
+        let depth = lists.iter().map(|l| l.len()).max().unwrap_or(0);
+        let mut iters: Vec<_> = lists.into_iter().map(|l| l.into_iter()).collect();
+        for _ in 0..depth {
+            for (qi, iter) in iters.iter_mut().enumerate() {
+                let Some(chunk) = iter.next() else { continue };
+                let key = chunk.id.as_ref().map(|t| t.to_string());
+                if let Some(&existing) = key.as_ref().and_then(|k| index_by_id.get(k)) {
+                    hits[existing].queries.push(qi);
+                    continue;
+                }
+                if let Some(k) = key {
+                    index_by_id.insert(k, hits.len());
+                }
+                hits.push(MultiSearchHit { chunk, queries: vec![qi] });
             }
-            Err(e) => error!("FTS search failed: {}", e),
         }
-        
-        results.sort_by(|a, b| a.id.cmp(&b.id));
-        results.dedup_by(|a, b| a.id == b.id);
-        
-        Ok(results)
+        Ok(hits)
+    }
+
+    /// How many chunks each lexical source matches for `query`, ignoring any limit.
+    pub async fn totals(&self, query: &str, keywords: Option<&[String]>) -> Vec<emry_core::models::SourceTotal> {
+        let (query, matching) = MatchOptions::parse(query);
+        let keywords = self.expand_keywords(&query, keywords).await;
+        let req = SearchRequest { query: &query, limit: 0, keywords: keywords.as_deref(), matching, explain: false, files: None };
+        self.pipeline
+            .count(&req)
+            .await
+            .into_iter()
+            .map(|(source, matches)| emry_core::models::SourceTotal { source, matches })
+            .collect()
+    }
+
+    /// Like `search`, but keeps the per-component scores of each hit.
+    pub async fn search_scored(&self, query: &str, limit: usize, keywords: Option<&[String]>) -> Result<Vec<emry_core::models::ScoredChunk>> {
+        self.scored(query, limit, keywords, false).await
+    }
+
+    /// Like `search_scored`, with each hit's per-stage ranking trace in `ScoredChunk::ranking`.
+    pub async fn search_explained(&self, query: &str, limit: usize, keywords: Option<&[String]>) -> Result<Vec<emry_core::models::ScoredChunk>> {
+        self.scored(query, limit, keywords, true).await
+    }
+
+    async fn scored(&self, query: &str, limit: usize, keywords: Option<&[String]>, explain: bool) -> Result<Vec<emry_core::models::ScoredChunk>> {
+        let (query, matching) = MatchOptions::parse(query);
+        let keywords = self.expand_keywords(&query, keywords).await;
+        let req = SearchRequest { query: &query, limit, keywords: keywords.as_deref(), matching, explain, files: None };
+        let candidates = self.pipeline.run(&req).await?;
+        Ok(candidates
+            .into_iter()
+            .map(|c| {
+                let file_id = c.chunk.file.id.to_string();
+                let path = std::path::PathBuf::from(file_id.strip_prefix("file:").unwrap_or(&file_id));
+                emry_core::models::ScoredChunk {
+                    score: c.score,
+                    lexical_score: c.lexical_score,
+                    vector_score: c.vector_score,
+                    graph_boost: c.graph_score,
+                    graph_distance: c.graph_distance,
+                    graph_path: None,
+                    symbol_boost: None,
+                    rerank_score: c.rerank_score,
+                    ranking: c.trace,
+                    chunk: to_core_chunk(&c.chunk, path),
+                }
+            })
+            .collect())
     }
 
     pub async fn search_with_context(&self, query: &str, limit: usize, keywords: Option<&[String]>) -> Result<emry_core::models::ContextGraph> {
@@ -103,21 +282,7 @@ impl SearchService {
             let file_id = c.file.id.to_string();
             let path = file_map.get(&file_id).cloned().unwrap_or_else(|| std::path::PathBuf::from(&file_id));
             
-            let core_chunk = emry_core::models::Chunk {
-                id: c.id.as_ref().map(|t| t.to_string()).unwrap_or_default(),
-                language: emry_core::models::Language::Unknown, 
-                file_path: path,
-                start_line: c.start_line,
-                end_line: c.end_line,
-                start_byte: None,
-                end_byte: None,
-                node_type: "chunk".to_string(),
-                content_hash: "".to_string(),
-                content: c.content.clone(),
-                embedding: c.embedding.clone(),
-                parent_scope: None,
-                scope_path: c.scopes.clone(),
-            };
+            let core_chunk = to_core_chunk(c, path);
             
             emry_core::models::ScoredChunk {
                 score: if anchor_ids.contains(&core_chunk.id) { 1.0 } else { 0.5 },
@@ -127,6 +292,8 @@ impl SearchService {
                 graph_distance: None,
                 graph_path: None,
                 symbol_boost: None,
+                rerank_score: None,
+                ranking: Vec::new(),
                 chunk: core_chunk,
             }
         }).collect();
@@ -163,7 +330,7 @@ impl SearchService {
 
         let in_edges = self.store.get_neighbors(anchor_id, "in").await?;
         for edge in in_edges {
-            if edge.relation != "contains" { continue; }
+            if edge.kind() != Some(EdgeKind::Contains) { continue; }
             
             let symbol_id = edge.source.to_string();
             let Some(symbol_node) = self.store.get_node(&symbol_id).await? else { continue; };
@@ -179,14 +346,16 @@ impl SearchService {
                 language: emry_core::models::Language::Unknown,
                 doc_comment: None,
                 parent_scope: None,
+                signature: None,
+                visibility: None,
             };
             related_symbols.push(sym);
-            edges.push((symbol_id.clone(), anchor_id.to_string(), "contains".to_string()));
+            edges.push((symbol_id.clone(), anchor_id.to_string(), EdgeKind::Contains.to_string()));
             
             // Parent/Siblings
             if let Ok(parent_edges) = self.store.get_neighbors(anchor_id, "in").await {
                 for parent_edge in parent_edges {
-                    if parent_edge.relation == "contains" {
+                    if parent_edge.kind() == Some(EdgeKind::Contains) {
                         self.process_siblings(&parent_edge.source.to_string(), anchor_id, context_chunks, edges).await?;
                     }
                 }
@@ -208,6 +377,8 @@ impl SearchService {
                             language: emry_core::models::Language::Unknown,
                             doc_comment: None,
                             parent_scope: None,
+                            signature: None,
+                            visibility: None,
                         };
                         related_symbols.push(target_sym);
                         edges.push((symbol_id.clone(), target_id, out_edge.relation));
@@ -228,14 +399,14 @@ impl SearchService {
         if let Ok(Some(_)) = self.store.get_node(parent_id).await {
             if let Ok(child_edges) = self.store.get_neighbors(parent_id, "out").await {
                 for child_edge in child_edges {
-                    if child_edge.relation == "contains" {
+                    if child_edge.kind() == Some(EdgeKind::Contains) {
                         let child_chunk_id = child_edge.target.to_string();
                         if child_chunk_id != current_chunk_id {
                             if let Ok(Some(chunk_rec)) = self.store.get_chunk(&child_chunk_id).await {
                                 context_chunks.push(chunk_rec);
                             }
                         }
-                        edges.push((parent_id.to_string(), child_chunk_id, "contains".to_string()));
+                        edges.push((parent_id.to_string(), child_chunk_id, EdgeKind::Contains.to_string()));
                     }
                 }
             }
@@ -243,3 +414,24 @@ impl SearchService {
         Ok(())
     }
 }
+
+fn to_core_chunk(c: &ChunkRecord, file_path: std::path::PathBuf) -> emry_core::models::Chunk {
+    emry_core::models::Chunk {
+        id: c.id.as_ref().map(|t| t.to_string()).unwrap_or_default(),
+        language: c.language.as_deref().map_or(emry_core::models::Language::Unknown, emry_core::models::Language::from_name),
+        file_path,
+        start_line: c.start_line,
+        end_line: c.end_line,
+        start_byte: None,
+        end_byte: None,
+        node_type: "chunk".to_string(),
+        content_hash: "".to_string(),
+        content: c.content.clone(),
+        embedding: c.embedding.clone(),
+        parent_scope: None,
+        scope_path: c.scopes.clone(),
+        tags: c.tags.clone(),
+        doc: c.doc.clone(),
+        doc_embedding: None,
+    }
+}
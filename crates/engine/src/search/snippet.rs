@@ -0,0 +1,128 @@
+//! Result snippets from the index or the working tree.
+//!
+//! The index stores each chunk's content as of the last run; the file on disk
+//! may have moved on since. Which one a result shows is a config choice
+//! (`search.snippet_source`), but in both cases the hit records whether the
+//! two diverge so callers can flag stale results.
+
+use emry_config::SnippetSource;
+use similar::TextDiff;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnippetOrigin {
+    Index,
+    WorkingTree,
+}
+
+#[derive(Debug, Clone)]
+pub struct Snippet {
+    pub text: String,
+    pub origin: SnippetOrigin,
+    /// The file's current lines no longer contain the indexed chunk (or the file is gone).
+    pub modified: bool,
+    /// Current content of the chunk's line span, if the file is readable.
+    pub current: Option<String>,
+}
+
+/// Lines `start..=end` (1-based) of `root/path`, or `None` if the file can't be read.
+pub fn read_span(root: &Path, path: &Path, start: usize, end: usize) -> Option<String> {
+    let content = std::fs::read_to_string(root.join(path)).ok()?;
+    let lines: Vec<&str> = content.lines().collect();
+    let from = start.saturating_sub(1).min(lines.len());
+    let to = end.min(lines.len()).max(from);
+    Some(lines[from..to].join("\n"))
+}
+
+/// Choose the snippet for a hit at `path:start-end` whose indexed content is `indexed`.
+pub fn resolve_snippet(
+    root: &Path,
+    path: &Path,
+    start: usize,
+    end: usize,
+    indexed: &str,
+    source: SnippetSource,
+) -> Snippet {
+    let current = read_span(root, path, start, end);
+    let modified = !current.as_deref().map_or(false, |c| is_unchanged(indexed, c));
+
+    match (source, &current) {
+        (SnippetSource::PreferWorkingTree, Some(text)) => Snippet {
+            text: text.clone(),
+            origin: SnippetOrigin::WorkingTree,
+            modified,
+            current,
+        },
+        _ => Snippet {
+            text: indexed.to_string(),
+            origin: SnippetOrigin::Index,
+            modified,
+            current,
+        },
+    }
+}
+
+/// Unified diff from the indexed chunk to the current span at the same lines.
+///
+/// `None` when the file can no longer be read.
+pub fn span_diff(path: &Path, start: usize, indexed: &str, current: Option<&str>) -> Option<String> {
+    // Spans are joined without a trailing newline; normalize so that doesn't show up as a change.
+    let indexed = format!("{}\n", indexed.trim_end_matches('\n'));
+    let current = format!("{}\n", current?.trim_end_matches('\n'));
+    let label = path.display().to_string();
+    let diff = TextDiff::from_lines(&indexed, &current);
+    let hunks = diff
+        .unified_diff()
+        .context_radius(3)
+        .header(&format!("{} (indexed)", label), &format!("{} (working tree)", label))
+        .to_string();
+    // Hunk headers count from the chunk's first line; shift them to file line numbers.
+    Some(shift_hunk_headers(&hunks, start.saturating_sub(1)))
+}
+
+fn shift_hunk_headers(diff: &str, offset: usize) -> String {
+    diff.lines()
+        .map(|line| match line.strip_prefix("@@ ").and_then(|l| l.strip_suffix(" @@")) {
+            Some(ranges) => {
+                let shifted: Vec<String> = ranges
+                    .split(' ')
+                    .map(|r| {
+                        let (sign, rest) = r.split_at(1);
+                        let (first, len) = rest.split_once(',').map_or((rest, None), |(a, b)| (a, Some(b)));
+                        let first = first.parse::<usize>().map(|n| n + offset).unwrap_or(0);
+                        match len {
+                            Some(len) => format!("{}{},{}", sign, first, len),
+                            None => format!("{}{}", sign, first),
+                        }
+                    })
+                    .collect();
+                format!("@@ {} @@", shifted.join(" "))
+            }
+            None => line.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Chunks can start or end mid-line, so the current span only has to contain the indexed text.
+fn is_unchanged(indexed: &str, current: &str) -> bool {
+    current.contains(indexed.trim())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partial_line_chunk_is_unchanged() {
+        assert!(is_unchanged("fn main() {}", "    fn main() {}  "));
+        assert!(!is_unchanged("fn main() {}", "fn main() { run(); }"));
+    }
+
+    #[test]
+    fn test_span_diff_uses_file_line_numbers() {
+        let diff = span_diff(Path::new("a.rs"), 10, "one\ntwo\n", Some("one\n2\n")).unwrap();
+        assert!(diff.contains("@@ -10,2 +10,2 @@"), "{}", diff);
+        assert!(diff.contains("-two") && diff.contains("+2"));
+    }
+}
@@ -0,0 +1,166 @@
+//! "Did you mean" for misspelled identifiers, from the index's symbol names.
+//!
+//! Only identifier-shaped words of a query are checked (`serach_ranked`,
+//! `parseHtppRequest`): natural-language words are too often missing from
+//! the symbol vocabulary for a correction to be trusted. A word is replaced
+//! by the closest symbol name within one edit (two for names longer than
+//! eight characters), counting a swap of adjacent letters as one edit. Words
+//! that are a symbol name, or the start of one, are left alone.
+
+use anyhow::Result;
+use emry_store::SurrealStore;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::OnceCell;
+
+/// Shorter words are too ambiguous to correct.
+const MIN_WORD_LEN: usize = 5;
+/// Words up to this length are corrected within one edit, longer ones within two.
+const SHORT_WORD_LEN: usize = 8;
+
+pub struct SpellCorrector {
+    store: Arc<SurrealStore>,
+    /// Loaded on first use and kept for the corrector's lifetime.
+    vocabulary: OnceCell<Vocabulary>,
+}
+
+#[derive(Debug, Default)]
+struct Vocabulary {
+    /// Lowercased name -> (name as defined, number of definitions).
+    names: HashMap<String, (String, usize)>,
+}
+
+impl Vocabulary {
+    fn from_names<I: IntoIterator<Item = String>>(names: I) -> Self {
+        let mut vocabulary = Self::default();
+        for name in names {
+            vocabulary.names.entry(name.to_lowercase()).or_insert_with(|| (name, 0)).1 += 1;
+        }
+        vocabulary
+    }
+
+    async fn load(store: &SurrealStore) -> Result<Self> {
+        Ok(Self::from_names(store.list_all_symbols().await?.into_iter().map(|s| s.label)))
+    }
+
+    /// The closest name to `word`, or `None` when `word` needs no correction.
+    fn correct(&self, word: &str) -> Option<&str> {
+        let lower = word.to_lowercase();
+        if self.names.contains_key(&lower) {
+            return None;
+        }
+        let max = if word.len() <= SHORT_WORD_LEN { 1 } else { 2 };
+        let mut best: Option<(usize, usize, &str)> = None;
+        for (key, (name, count)) in &self.names {
+            if key.starts_with(&lower) {
+                return None;
+            }
+            if key.len().abs_diff(lower.len()) > max {
+                continue;
+            }
+            let Some(distance) = edit_distance(&lower, key, max) else { continue };
+            // Fewest edits, then the most widely defined name, then alphabetical.
+            let candidate = (distance, usize::MAX - count, name.as_str());
+            if best.map_or(true, |b| candidate < b) {
+                best = Some(candidate);
+            }
+        }
+        best.map(|(_, _, name)| name)
+    }
+}
+
+impl SpellCorrector {
+    pub fn new(store: Arc<SurrealStore>) -> Self {
+        Self { store, vocabulary: OnceCell::new() }
+    }
+
+    /// `query` with its misspelled identifiers corrected, or `None` when
+    /// nothing was changed.
+    pub async fn did_you_mean(&self, query: &str) -> Result<Option<String>> {
+        let vocabulary = self.vocabulary.get_or_try_init(|| Vocabulary::load(&self.store)).await?;
+        Ok(correct_query(vocabulary, query))
+    }
+}
+
+fn correct_query(vocabulary: &Vocabulary, query: &str) -> Option<String> {
+    let mut corrected = String::with_capacity(query.len());
+    let mut changed = false;
+    let mut rest = query;
+    while !rest.is_empty() {
+        let word_len = rest.find(|c: char| !is_ident_char(c)).unwrap_or(rest.len());
+        if word_len == 0 {
+            let c = rest.chars().next().unwrap();
+            corrected.push(c);
+            rest = &rest[c.len_utf8()..];
+            continue;
+        }
+        let word = &rest[..word_len];
+        match is_identifier(word).then(|| vocabulary.correct(word)).flatten() {
+            Some(name) => {
+                corrected.push_str(name);
+                changed = true;
+            }
+            None => corrected.push_str(word),
+        }
+        rest = &rest[word_len..];
+    }
+    changed.then_some(corrected)
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Whether `word` looks like code: long enough, and snake_case or camelCase.
+fn is_identifier(word: &str) -> bool {
+    if word.chars().count() < MIN_WORD_LEN || word.chars().all(|c| c.is_ascii_digit() || c == '_') {
+        return false;
+    }
+    let snake = word.trim_matches('_').contains('_');
+    let camel = word.chars().zip(word.chars().skip(1)).any(|(a, b)| a.is_lowercase() && b.is_uppercase());
+    snake || camel
+}
+
+/// Optimal string alignment distance between `a` and `b`, or `None` when it exceeds `max`.
+fn edit_distance(a: &str, b: &str, max: usize) -> Option<usize> {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut prev2: Vec<usize> = vec![0; b.len() + 1];
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        let mut row = vec![i; b.len() + 1];
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            row[j] = (prev[j] + 1).min(row[j - 1] + 1).min(prev[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                row[j] = row[j].min(prev2[j - 2] + 1);
+            }
+        }
+        if row.iter().min().is_some_and(|&m| m > max) {
+            return None;
+        }
+        prev2 = std::mem::replace(&mut prev, row);
+    }
+    Some(prev[b.len()]).filter(|&d| d <= max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_corrects_misspelled_identifiers_only() {
+        let vocabulary = Vocabulary::from_names(
+            ["search_ranked", "search_scored", "parseHttpRequest", "render", "search_ranked_hits"].map(String::from),
+        );
+
+        assert_eq!(correct_query(&vocabulary, "serach_ranked case:yes").as_deref(), Some("search_ranked case:yes"));
+        assert_eq!(correct_query(&vocabulary, "where is parseHtppRequest used").as_deref(), Some("where is parseHttpRequest used"));
+        // Known names, prefixes of names and plain words are left alone.
+        assert_eq!(correct_query(&vocabulary, "search_scored"), None);
+        assert_eq!(correct_query(&vocabulary, "search_rank"), None);
+        assert_eq!(correct_query(&vocabulary, "rendre the page"), None);
+        // Too far from any name.
+        assert_eq!(correct_query(&vocabulary, "fetch_ranked"), None);
+    }
+}
@@ -0,0 +1,59 @@
+//! Backend-neutral graph access.
+//!
+//! Graph consumers only need this surface; `SurrealStore` is the one
+//! implementation today.
+
+use crate::{SurrealGraphEdge, SurrealGraphNode, SurrealStore};
+use anyhow::Result;
+use async_trait::async_trait;
+use emry_core::models::EdgeKind;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    In,
+    Out,
+}
+
+impl Direction {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Direction::In => "in",
+            Direction::Out => "out",
+        }
+    }
+}
+
+/// Read-side graph access with the canonical `EdgeKind` vocabulary.
+///
+/// CLI graph commands, ranking boosts and agent tools traverse the graph
+/// through this trait so they agree on node resolution and edge kinds.
+#[async_trait]
+pub trait GraphBackend: Send + Sync {
+    async fn get_node(&self, id: &str) -> Result<Option<SurrealGraphNode>>;
+    async fn find_nodes_by_label(&self, label: &str, file_filter: Option<&str>) -> Result<Vec<SurrealGraphNode>>;
+    async fn neighbors(&self, id: &str, direction: Direction) -> Result<Vec<SurrealGraphEdge>>;
+
+    /// Neighbors restricted to the given edge kinds.
+    async fn neighbors_of_kind(&self, id: &str, direction: Direction, kinds: &[EdgeKind]) -> Result<Vec<SurrealGraphEdge>> {
+        let edges = self.neighbors(id, direction).await?;
+        Ok(edges
+            .into_iter()
+            .filter(|e| e.kind().is_some_and(|k| kinds.contains(&k)))
+            .collect())
+    }
+}
+
+#[async_trait]
+impl GraphBackend for SurrealStore {
+    async fn get_node(&self, id: &str) -> Result<Option<SurrealGraphNode>> {
+        SurrealStore::get_node(self, id).await
+    }
+
+    async fn find_nodes_by_label(&self, label: &str, file_filter: Option<&str>) -> Result<Vec<SurrealGraphNode>> {
+        SurrealStore::find_nodes_by_label(self, label, file_filter).await
+    }
+
+    async fn neighbors(&self, id: &str, direction: Direction) -> Result<Vec<SurrealGraphEdge>> {
+        SurrealStore::get_neighbors(self, id, direction.as_str()).await
+    }
+}
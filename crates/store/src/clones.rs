@@ -0,0 +1,46 @@
+//! Chunk embeddings and their nearest neighbours, for clone detection.
+
+use crate::SurrealStore;
+use anyhow::Result;
+use serde::Deserialize;
+use surrealdb::sql::Thing;
+
+/// A chunk with an embedding.
+#[derive(Debug, Clone, Deserialize)]
+pub struct EmbeddedChunk {
+    pub id: Thing,
+    pub path: String,
+    pub start_line: usize,
+    pub end_line: usize,
+    pub embedding: Vec<f32>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Neighbor {
+    pub id: Thing,
+    pub similarity: f32,
+}
+
+impl SurrealStore {
+    /// Every chunk with an embedding of its own, i.e. no duplicates.
+    pub async fn embedded_chunks(&self) -> Result<Vec<EmbeddedChunk>> {
+        let mut res = self
+            .db
+            .query("SELECT id, file.path AS path, start_line, end_line, embedding FROM chunk WHERE embedding != NONE")
+            .await?;
+        Ok(res.take(0)?)
+    }
+
+    /// The `k` chunks nearest to `embedding` by cosine similarity, nearest first.
+    pub async fn nearest_chunks(&self, embedding: Vec<f32>, k: usize) -> Result<Vec<Neighbor>> {
+        let mut res = self
+            .db
+            .query(format!(
+                "SELECT id, vector::similarity::cosine(embedding, $query_vec) AS similarity FROM chunk WHERE embedding <|{}, cosine|> $query_vec ORDER BY similarity DESC",
+                k
+            ))
+            .bind(("query_vec", embedding))
+            .await?;
+        Ok(res.take(0)?)
+    }
+}
@@ -0,0 +1,163 @@
+//! Precomputed bounded neighbourhoods over dependency edges (`calls`,
+//! `imports`, `inherits`, `implements`).
+//!
+//! Query-time graph boosts need "how far is symbol B from symbol A". Walking
+//! the graph per query is too slow for hot nodes, so each symbol's reachable
+//! set (both directions, up to a few hops) is stored in the `neighborhood`
+//! table and read back with a single record lookup.
+
+use crate::models::{NeighborhoodRecord, ReachEntry};
+use crate::SurrealStore;
+use anyhow::Result;
+use emry_core::models::EdgeKind;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet, VecDeque};
+use surrealdb::sql::Thing;
+
+/// Hard cap on stored hops, independent of `graph.max_depth`.
+pub const MAX_PRECOMPUTED_HOPS: usize = 3;
+
+/// Entries kept per node; hubs would otherwise store most of the graph.
+const MAX_REACH_ENTRIES: usize = 256;
+
+#[derive(Debug, Deserialize)]
+struct EdgePair {
+    r#in: Thing,
+    out: Thing,
+}
+
+type Adjacency = HashMap<String, Vec<String>>;
+
+/// Nodes reachable from `start` within `max_hops`, nearest first, excluding `start`.
+pub fn bounded_reach(adj: &Adjacency, start: &str, max_hops: usize) -> Vec<ReachEntry> {
+    let mut seen: HashSet<&str> = HashSet::from([start]);
+    let mut queue = VecDeque::from([(start, 0usize)]);
+    let mut out = Vec::new();
+
+    while let Some((node, hops)) = queue.pop_front() {
+        if hops == max_hops || out.len() >= MAX_REACH_ENTRIES {
+            continue;
+        }
+        for next in adj.get(node).into_iter().flatten() {
+            if seen.insert(next.as_str()) {
+                out.push(ReachEntry { node: next.clone(), hops: (hops + 1) as u8 });
+                queue.push_back((next.as_str(), hops + 1));
+            }
+        }
+    }
+    out.truncate(MAX_REACH_ENTRIES);
+    out
+}
+
+impl SurrealStore {
+    async fn load_adjacency(&self) -> Result<Adjacency> {
+        let mut adj: Adjacency = HashMap::new();
+        for kind in EdgeKind::ALL.into_iter().filter(EdgeKind::is_dependency) {
+            let table = kind.as_str();
+            let mut res = self.db.query(format!("SELECT in, out FROM {}", table)).await?;
+            let pairs: Vec<EdgePair> = res.take(0)?;
+            for pair in pairs {
+                let (a, b) = (pair.r#in.to_string(), pair.out.to_string());
+                adj.entry(a.clone()).or_default().push(b.clone());
+                adj.entry(b).or_default().push(a);
+            }
+        }
+        Ok(adj)
+    }
+
+    async fn write_neighborhoods(&self, adj: &Adjacency, nodes: &[String], max_hops: usize) -> Result<usize> {
+        let mut written = 0;
+        for node in nodes {
+            let reachable = bounded_reach(adj, node, max_hops);
+            if reachable.is_empty() {
+                let _: Option<NeighborhoodRecord> = self.db.delete(("neighborhood", node.as_str())).await?;
+                continue;
+            }
+            let record = NeighborhoodRecord { id: None, node: node.clone(), reachable };
+            let _: Option<NeighborhoodRecord> = self.db.upsert(("neighborhood", node.as_str()))
+                .content(record)
+                .await?;
+            written += 1;
+        }
+        Ok(written)
+    }
+
+    /// Recompute every node's neighbourhood from scratch. Returns the number of records written.
+    pub async fn rebuild_neighborhoods(&self, max_hops: usize) -> Result<usize> {
+        self.ensure_writable()?;
+        let max_hops = max_hops.min(MAX_PRECOMPUTED_HOPS);
+        let adj = self.load_adjacency().await?;
+        self.db.query("DELETE neighborhood").await?;
+        let nodes: Vec<String> = adj.keys().cloned().collect();
+        self.write_neighborhoods(&adj, &nodes, max_hops).await
+    }
+
+    /// Recompute only the neighbourhoods that can contain one of `changed`.
+    ///
+    /// Reachability is symmetric here, so those are exactly the nodes within
+    /// `max_hops` of a changed node.
+    pub async fn refresh_neighborhoods(&self, changed: &[String], max_hops: usize) -> Result<usize> {
+        self.ensure_writable()?;
+        let max_hops = max_hops.min(MAX_PRECOMPUTED_HOPS);
+        let adj = self.load_adjacency().await?;
+
+        let mut affected: HashSet<String> = changed.iter().cloned().collect();
+        for node in changed {
+            affected.extend(bounded_reach(&adj, node, max_hops).into_iter().map(|e| e.node));
+        }
+        let affected: Vec<String> = affected.into_iter().collect();
+        self.write_neighborhoods(&adj, &affected, max_hops).await
+    }
+
+    /// Stored neighbourhoods for the given node ids; nodes without one are omitted.
+    pub async fn get_neighborhoods(&self, nodes: &[String]) -> Result<Vec<NeighborhoodRecord>> {
+        let ids: Vec<Thing> = nodes.iter().map(|n| Thing::from(("neighborhood", n.as_str()))).collect();
+        let mut res = self.db.query("SELECT * FROM $ids").bind(("ids", ids)).await?;
+        let records: Vec<NeighborhoodRecord> = res.take(0)?;
+        Ok(records)
+    }
+
+    /// Symbols that contain each chunk, keyed by chunk id.
+    pub async fn symbols_for_chunks(&self, chunks: &[Thing]) -> Result<HashMap<String, Vec<String>>> {
+        let mut res = self.db
+            .query("SELECT in, out FROM contains WHERE out IN $chunks")
+            .bind(("chunks", chunks.to_vec()))
+            .await?;
+        let pairs: Vec<EdgePair> = res.take(0)?;
+
+        let mut map: HashMap<String, Vec<String>> = HashMap::new();
+        for pair in pairs {
+            map.entry(pair.out.to_string()).or_default().push(pair.r#in.to_string());
+        }
+        Ok(map)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn adjacency(edges: &[(&str, &str)]) -> Adjacency {
+        let mut adj: Adjacency = HashMap::new();
+        for (a, b) in edges {
+            adj.entry(a.to_string()).or_default().push(b.to_string());
+            adj.entry(b.to_string()).or_default().push(a.to_string());
+        }
+        adj
+    }
+
+    #[test]
+    fn test_bounded_reach_respects_hops() {
+        let adj = adjacency(&[("a", "b"), ("b", "c"), ("c", "d")]);
+        let reach = bounded_reach(&adj, "a", 2);
+        let got: Vec<(&str, u8)> = reach.iter().map(|e| (e.node.as_str(), e.hops)).collect();
+        assert_eq!(got, vec![("b", 1), ("c", 2)]);
+    }
+
+    #[test]
+    fn test_bounded_reach_keeps_shortest_distance() {
+        let adj = adjacency(&[("a", "b"), ("b", "c"), ("a", "c")]);
+        let reach = bounded_reach(&adj, "a", 3);
+        assert!(reach.iter().all(|e| e.hops == 1));
+    }
+}
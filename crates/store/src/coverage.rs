@@ -0,0 +1,77 @@
+//! Test coverage imported from LCOV or Cobertura reports.
+//!
+//! One `coverage` record per file, keyed by path like `file`, holding which
+//! instrumented lines ran and the hash of the file content the report was
+//! taken against. Records are only trusted while that hash still matches:
+//! lookups by indexed file skip files re-indexed with other content, since
+//! their chunks no longer line up with the report.
+
+use crate::SurrealStore;
+use anyhow::Result;
+use emry_core::coverage::FileCoverage;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use surrealdb::sql::Thing;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CoverageRecord {
+    pub path: String,
+    /// Hash of the file content when the report was imported
+    pub hash: String,
+    pub imported: u64,
+    pub lines: FileCoverage,
+}
+
+#[derive(Debug, Deserialize)]
+struct FileHash {
+    path: String,
+    hash: String,
+}
+
+impl SurrealStore {
+    /// Store `record`, replacing the file's earlier coverage.
+    pub async fn set_coverage(&self, record: CoverageRecord) -> Result<()> {
+        self.ensure_writable()?;
+        let path = record.path.clone();
+        let _: Option<CoverageRecord> = self.db.upsert(("coverage", path.as_str())).content(record).await?;
+        Ok(())
+    }
+
+    /// Coverage of each of `paths` that has any, keyed by path.
+    pub async fn coverage_for(&self, paths: &[String]) -> Result<HashMap<String, CoverageRecord>> {
+        let ids: Vec<Thing> = paths.iter().map(|p| Thing::from(("coverage", p.as_str()))).collect();
+        let mut res = self.db.query("SELECT * FROM $ids").bind(("ids", ids)).await?;
+        let records: Vec<CoverageRecord> = res.take(0)?;
+        Ok(records.into_iter().map(|r| (r.path.clone(), r)).collect())
+    }
+
+    /// Coverage of the indexed `files` whose content is unchanged since the
+    /// report was imported, keyed by file path.
+    pub async fn indexed_coverage(&self, files: &[Thing]) -> Result<HashMap<String, FileCoverage>> {
+        let mut res = self.db.query("SELECT path, hash FROM $files").bind(("files", files.to_vec())).await?;
+        let hashes: Vec<FileHash> = res.take(0)?;
+        let paths: Vec<String> = hashes.iter().map(|f| f.path.clone()).collect();
+        let mut records = self.coverage_for(&paths).await?;
+        Ok(hashes
+            .into_iter()
+            .filter_map(|f| {
+                let record = records.remove(&f.path)?;
+                (record.hash == f.hash).then_some((f.path, record.lines))
+            })
+            .collect())
+    }
+
+    /// Number of files with imported coverage.
+    pub async fn count_coverage(&self) -> Result<usize> {
+        let mut res = self.db.query("SELECT count() FROM coverage GROUP ALL").await?;
+        let count: Option<serde_json::Value> = res.take(0)?;
+        Ok(count.and_then(|v| v.get("count").and_then(|c| c.as_u64())).unwrap_or(0) as usize)
+    }
+
+    /// Forget all imported coverage.
+    pub async fn clear_coverage(&self) -> Result<()> {
+        self.ensure_writable()?;
+        self.db.query("DELETE coverage").await?;
+        Ok(())
+    }
+}
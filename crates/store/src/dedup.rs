@@ -0,0 +1,309 @@
+//! Deduplication of identical chunks.
+//!
+//! Chunks are keyed by the SHA-256 of their text. The first chunk stored
+//! with a given text is canonical and keeps the content, embedding, tags,
+//! sparse terms and docs; every later copy (vendored files, generated code,
+//! license headers) is stored with its own location but none of those, and points at
+//! the canonical chunk through `duplicate_of`. The BM25, vector, tag and
+//! sparse indexes therefore hold each text once, and a search hit lists the
+//! other places its text occurs. When the canonical chunk's file is
+//! re-ingested or deleted, one of its duplicates takes over.
+
+use crate::{ChunkRecord, SurrealStore};
+use anyhow::Result;
+use emry_core::models::ChunkLocation;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use surrealdb::sql::Thing;
+
+#[derive(Deserialize)]
+struct HashRow {
+    id: Thing,
+    content_hash: String,
+}
+
+#[derive(Deserialize)]
+struct DuplicateRow {
+    id: Thing,
+    duplicate_of: Thing,
+}
+
+#[derive(Deserialize)]
+struct CopyRow {
+    duplicate_of: Thing,
+    kept_file: Option<Thing>,
+    file: Thing,
+    start_line: usize,
+    end_line: usize,
+}
+
+#[derive(Deserialize)]
+struct LocationRow {
+    duplicate_of: Thing,
+    path: String,
+    start_line: usize,
+    end_line: usize,
+}
+
+/// Restriction of a chunk search to some files, bound as `$files` and `$kept`.
+///
+/// Only the canonical copy of a text is searchable, so besides the chunks of
+/// the files the search admits canonical chunks elsewhere that have a copy in
+/// them, and `relocate` reports those hits at the copy.
+#[derive(Default)]
+pub(crate) struct FileFilter {
+    restricted: bool,
+    /// Records of the files searched
+    pub(crate) files: Vec<Thing>,
+    /// Canonical chunks outside the files with a copy in them
+    pub(crate) kept: Vec<Thing>,
+    copies: HashMap<Thing, CopyRow>,
+}
+
+impl FileFilter {
+    /// The condition to append to a chunk query's `WHERE` clause.
+    pub(crate) fn condition(&self) -> &'static str {
+        if self.restricted {
+            " AND (file IN $files OR id IN $kept)"
+        } else {
+            ""
+        }
+    }
+
+    /// Move a hit on a canonical chunk outside the files to its copy inside them.
+    pub(crate) fn relocate(&self, chunk: &mut ChunkRecord) {
+        if let Some(copy) = chunk.id.as_ref().and_then(|id| self.copies.get(id)) {
+            chunk.file = copy.file.clone();
+            chunk.start_line = copy.start_line;
+            chunk.end_line = copy.end_line;
+        }
+    }
+
+    /// `relocate` for a scored hit.
+    pub(crate) fn relocate_scored(&self, (mut chunk, score): (ChunkRecord, f32)) -> (ChunkRecord, f32) {
+        self.relocate(&mut chunk);
+        (chunk, score)
+    }
+}
+
+impl ChunkRecord {
+    /// Turn this chunk into a duplicate of `canonical`, dropping what it shares.
+    pub(crate) fn share(&mut self, canonical: Thing) {
+        self.content = String::new();
+        self.embedding = None;
+        self.embedding_q = None;
+        self.tags = Vec::new();
+        self.sparse_terms = Vec::new();
+        self.sparse_weights = Vec::new();
+        self.doc = None;
+        self.doc_embedding = None;
+        self.duplicate_of = Some(canonical);
+    }
+}
+
+impl SurrealStore {
+    /// The filter restricting a chunk search to `files` (their indexed paths),
+    /// or none when `files` is `None`.
+    pub(crate) async fn file_filter(&self, files: Option<&[String]>) -> Result<FileFilter> {
+        let Some(files) = files else { return Ok(FileFilter::default()) };
+        let files: Vec<Thing> = files.iter().map(|path| Thing::from(("file", path.as_str()))).collect();
+        let mut res = self
+            .db
+            .query("SELECT duplicate_of, duplicate_of.file AS kept_file, file, start_line, end_line FROM chunk WHERE file IN $files AND duplicate_of != NONE ORDER BY file, start_line")
+            .bind(("files", files.clone()))
+            .await?;
+        let rows: Vec<CopyRow> = res.take(0)?;
+        let within: HashSet<&Thing> = files.iter().collect();
+        let mut copies: HashMap<Thing, CopyRow> = HashMap::new();
+        for row in rows {
+            if !row.kept_file.as_ref().is_some_and(|f| within.contains(f)) {
+                copies.entry(row.duplicate_of.clone()).or_insert(row);
+            }
+        }
+        let kept = copies.keys().cloned().collect();
+        Ok(FileFilter { restricted: true, files, kept, copies })
+    }
+
+    /// Canonical chunks by content hash, for the hashes among `hashes` already stored.
+    pub(crate) async fn canonical_chunks(&self, mut hashes: Vec<String>) -> Result<HashMap<String, Thing>> {
+        hashes.retain(|h| !h.is_empty());
+        if hashes.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let mut res = self
+            .db
+            .query("SELECT id, content_hash FROM chunk WHERE content_hash IN $hashes AND duplicate_of = NONE")
+            .bind(("hashes", hashes))
+            .await?;
+        let rows: Vec<HashRow> = res.take(0)?;
+        Ok(rows.into_iter().map(|r| (r.content_hash, r.id)).collect())
+    }
+
+    /// Before the chunks of `file` are deleted, hand each canonical one
+    /// among them over to a duplicate in another file.
+    pub(crate) async fn release_chunks(&self, file: &Thing) -> Result<()> {
+        let mut res = self
+            .db
+            .query("LET $own = (SELECT VALUE id FROM chunk WHERE file = $file AND duplicate_of = NONE); SELECT id, duplicate_of FROM chunk WHERE duplicate_of IN $own AND file != $file ORDER BY id")
+            .bind(("file", file.clone()))
+            .await?;
+        let rows: Vec<DuplicateRow> = res.take(1)?;
+        let mut heirs: HashMap<Thing, Thing> = HashMap::new();
+        for row in rows {
+            heirs.entry(row.duplicate_of).or_insert(row.id);
+        }
+        for (old, heir) in heirs {
+            self.db
+                .query(
+                    "LET $src = (SELECT * FROM ONLY $old);
+                     UPDATE $heir SET content = $src.content, embedding = $src.embedding, embedding_q = $src.embedding_q, tags = $src.tags, sparse_terms = $src.sparse_terms, sparse_weights = $src.sparse_weights, doc = $src.doc, doc_embedding = $src.doc_embedding, duplicate_of = NONE;
+                     UPDATE chunk SET duplicate_of = $heir WHERE duplicate_of = $old AND file != $file;",
+                )
+                .bind(("old", old))
+                .bind(("heir", heir))
+                .bind(("file", file.clone()))
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Give a duplicate chunk the text, embedding, terms and docs of its canonical chunk.
+    pub(crate) async fn fill_duplicate(&self, chunk: &mut ChunkRecord) -> Result<()> {
+        let Some(canonical) = chunk.duplicate_of.clone() else { return Ok(()) };
+        let mut res = self.db.query("SELECT * FROM ONLY $id").bind(("id", canonical)).await?;
+        if let Some(original) = res.take::<Option<ChunkRecord>>(0)? {
+            chunk.content = original.content;
+            chunk.embedding = original.embedding;
+            chunk.tags = original.tags;
+            chunk.sparse_terms = original.sparse_terms;
+            chunk.sparse_weights = original.sparse_weights;
+            chunk.doc = original.doc;
+            chunk.doc_embedding = original.doc_embedding;
+        }
+        Ok(())
+    }
+
+    /// Other locations of each of the canonical chunks `ids`, by chunk id.
+    pub async fn duplicate_locations(&self, ids: &[String]) -> Result<HashMap<String, Vec<ChunkLocation>>> {
+        let things: Vec<Thing> = ids.iter().filter_map(|id| surrealdb::sql::thing(id).ok()).collect();
+        if things.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let mut res = self
+            .db
+            .query("SELECT duplicate_of, file.path AS path, start_line, end_line FROM chunk WHERE duplicate_of IN $ids ORDER BY path, start_line")
+            .bind(("ids", things))
+            .await?;
+        Ok(by_canonical(res.take(0)?))
+    }
+
+    /// Locations of every duplicate chunk, by the id of the chunk it duplicates.
+    pub async fn all_duplicate_locations(&self) -> Result<HashMap<String, Vec<ChunkLocation>>> {
+        let mut res = self
+            .db
+            .query("SELECT duplicate_of, file.path AS path, start_line, end_line FROM chunk WHERE duplicate_of != NONE ORDER BY path, start_line")
+            .await?;
+        Ok(by_canonical(res.take(0)?))
+    }
+
+    /// Number of chunks stored as duplicates.
+    pub async fn count_duplicate_chunks(&self) -> Result<usize> {
+        let mut res = self.db.query("SELECT count() FROM chunk WHERE duplicate_of != NONE GROUP ALL").await?;
+        let count: Option<crate::CountWrapper> = res.take(0)?;
+        Ok(count.map(|c| c.count).unwrap_or(0))
+    }
+}
+
+fn by_canonical(rows: Vec<LocationRow>) -> HashMap<String, Vec<ChunkLocation>> {
+    let mut locations: HashMap<String, Vec<ChunkLocation>> = HashMap::new();
+    for row in rows {
+        locations.entry(row.duplicate_of.to_string()).or_default().push(ChunkLocation {
+            file_path: row.path,
+            start_line: row.start_line,
+            end_line: row.end_line,
+        });
+    }
+    locations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{chunk, file};
+
+    const LEXER: &str = "fn lexer() {}";
+
+    /// A copy of the lexer chunk with id `id` in `path`.
+    fn copy(id: &str, path: &str) -> ChunkRecord {
+        ChunkRecord { content_hash: "lexer".to_string(), embedding: Some(vec![1.0, 0.0, 0.0, 0.0]), ..chunk(id, path, LEXER) }
+    }
+
+    async fn store_with_copies(dir: &tempfile::TempDir, copies: &[(&str, &str)]) -> Result<SurrealStore> {
+        let store = SurrealStore::open(&dir.path().join("db"), Some(4)).await?;
+        for (id, path) in copies {
+            store.add_file_nodes(&file(path), &[copy(id, path)], &[], &HashMap::new()).await?;
+        }
+        Ok(store)
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_reads_through_to_canonical() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let store = store_with_copies(&dir, &[("a", "/repo/a.rs"), ("b", "/vendor/b.rs")]).await?;
+
+        let canonical = store.canonical_chunks(vec!["lexer".to_string(), "other".to_string()]).await?;
+        assert_eq!(canonical.get("lexer"), Some(&Thing::from(("chunk", "a"))));
+        assert_eq!(canonical.len(), 1);
+
+        let b = store.get_chunk("chunk:b").await?.unwrap();
+        assert_eq!(b.duplicate_of, Some(Thing::from(("chunk", "a"))));
+        assert_eq!(b.content, LEXER);
+        assert_eq!(b.embedding, Some(vec![1.0, 0.0, 0.0, 0.0]));
+
+        let hits = store.search_fts("lexer", 10, None).await?;
+        assert_eq!(hits.len(), 1);
+        let locations = store.duplicate_locations(&["chunk:a".to_string()]).await?;
+        assert_eq!(locations["chunk:a"].iter().map(|l| l.file_path.as_str()).collect::<Vec<_>>(), ["/vendor/b.rs"]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_deleting_canonical_copy_hands_over() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let store = store_with_copies(&dir, &[("a", "/repo/a.rs"), ("b", "/vendor/b.rs"), ("c", "/vendor/c.rs")]).await?;
+
+        store.delete_file("/repo/a.rs").await?;
+
+        let b = store.get_chunk("chunk:b").await?.unwrap();
+        assert_eq!(b.duplicate_of, None);
+        assert_eq!(b.content, LEXER);
+        assert_eq!(b.embedding, Some(vec![1.0, 0.0, 0.0, 0.0]));
+        let c = store.get_chunk("chunk:c").await?.unwrap();
+        assert_eq!(c.duplicate_of, Some(Thing::from(("chunk", "b"))));
+
+        let hits = store.search_fts("lexer", 10, None).await?;
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].0.id, Some(Thing::from(("chunk", "b"))));
+        let nearest = store.search_vector(vec![1.0, 0.0, 0.0, 0.0], 1).await?;
+        assert_eq!(nearest.first().and_then(|c| c.id.clone()), Some(Thing::from(("chunk", "b"))));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_reingesting_changed_canonical_copy() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let store = store_with_copies(&dir, &[("a", "/repo/a.rs"), ("b", "/vendor/b.rs")]).await?;
+
+        let changed = ChunkRecord { content_hash: "parser".to_string(), ..chunk("a", "/repo/a.rs", "fn parser() {}") };
+        store.add_file_nodes(&file("/repo/a.rs"), &[changed], &[], &HashMap::new()).await?;
+
+        let b = store.get_chunk("chunk:b").await?.unwrap();
+        assert_eq!(b.duplicate_of, None);
+        assert_eq!(b.content, LEXER);
+        let lexer = store.search_fts("lexer", 10, None).await?;
+        assert_eq!(lexer.iter().map(|(c, _)| c.id.clone()).collect::<Vec<_>>(), [Some(Thing::from(("chunk", "b")))]);
+        let parser = store.search_fts("parser", 10, None).await?;
+        assert_eq!(parser.iter().map(|(c, _)| c.id.clone()).collect::<Vec<_>>(), [Some(Thing::from(("chunk", "a")))]);
+        Ok(())
+    }
+}
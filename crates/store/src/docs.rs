@@ -0,0 +1,67 @@
+//! Search over the documentation of chunks.
+//!
+//! A chunk's `doc` (its doc comments and docstrings) has its own BM25 index
+//! and, with embeddings, its own vector index, so a query can target what the
+//! code says about itself rather than the code.
+
+use crate::{ChunkRecord, CountWrapper, ScoredResult, SurrealStore};
+use anyhow::Result;
+
+impl SurrealStore {
+    /// Chunks whose docs match `query` with their BM25 scores, best first.
+    pub async fn search_docs_fts(&self, query: &str, limit: usize, files: Option<&[String]>) -> Result<Vec<(ChunkRecord, f32)>> {
+        let filter = self.file_filter(files).await?;
+        let results: Vec<ScoredResult> = self
+            .db
+            .query(format!(
+                "SELECT *, search::score(2) AS score FROM chunk WHERE doc @2@ $query{} ORDER BY score DESC LIMIT $limit",
+                filter.condition()
+            ))
+            .bind(("query", query.to_string()))
+            .bind(("limit", limit))
+            .bind(("files", filter.files.clone()))
+            .bind(("kept", filter.kept.clone()))
+            .await?
+            .take(0)?;
+        Ok(results.into_iter().map(|r| filter.relocate_scored(r.into_scored())).collect())
+    }
+
+    /// Number of chunks `search_docs_fts` would match without a limit.
+    pub async fn count_docs_fts(&self, query: &str, files: Option<&[String]>) -> Result<usize> {
+        let filter = self.file_filter(files).await?;
+        let count: Option<CountWrapper> = self
+            .db
+            .query(format!("SELECT count() FROM chunk WHERE doc @2@ $query{} GROUP ALL", filter.condition()))
+            .bind(("query", query.to_string()))
+            .bind(("files", filter.files))
+            .bind(("kept", filter.kept))
+            .await?
+            .take(0)?;
+        Ok(count.map(|c| c.count).unwrap_or(0))
+    }
+
+    /// Chunks whose doc embeddings are nearest `embedding` with their cosine
+    /// similarity to it, nearest first.
+    pub async fn search_docs_vector(&self, embedding: Vec<f32>, limit: usize, files: Option<&[String]>) -> Result<Vec<(ChunkRecord, f32)>> {
+        let filter = self.file_filter(files).await?;
+        let results: Vec<ScoredResult> = self
+            .db
+            .query(format!(
+                "SELECT *, vector::similarity::cosine(doc_embedding, $query_vec) AS score FROM chunk WHERE doc_embedding <|{}, cosine|> $query_vec{} ORDER BY score DESC",
+                limit.max(1),
+                filter.condition()
+            ))
+            .bind(("query_vec", embedding))
+            .bind(("files", filter.files.clone()))
+            .bind(("kept", filter.kept.clone()))
+            .await?
+            .take(0)?;
+        Ok(results.into_iter().map(|r| filter.relocate_scored(r.into_scored())).collect())
+    }
+
+    /// Number of chunks with documentation.
+    pub async fn count_documented_chunks(&self) -> Result<usize> {
+        let count: Option<CountWrapper> = self.db.query("SELECT count() FROM chunk WHERE doc != NONE GROUP ALL").await?.take(0)?;
+        Ok(count.map(|c| c.count).unwrap_or(0))
+    }
+}
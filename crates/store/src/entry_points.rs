@@ -0,0 +1,61 @@
+//! Entry point symbols and the functions nothing reaches.
+//!
+//! `entry_point` is set on `symbol` records at index time (see
+//! `emry_core::entry_points`). A function or method with no incoming `calls`
+//! or `imports` edge that isn't an entry point is a dead code candidate: the
+//! index saw nothing run it, though dynamic dispatch and calls the resolver
+//! couldn't tie to a definition can still reach it.
+
+use crate::SurrealStore;
+use anyhow::Result;
+use emry_core::entry_points::EntryPointKind;
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::Thing;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntryPointNode {
+    pub id: Thing,
+    pub label: String,
+    pub kind: String,
+    pub file_path: String,
+    #[serde(default)]
+    pub start_line: Option<usize>,
+    pub entry_kind: EntryPointKind,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnreferencedSymbol {
+    pub id: Thing,
+    pub label: String,
+    pub kind: String,
+    pub file_path: String,
+    #[serde(default)]
+    pub start_line: Option<usize>,
+    #[serde(default)]
+    pub end_line: Option<usize>,
+    #[serde(default)]
+    pub parent_scope: Option<String>,
+}
+
+impl SurrealStore {
+    /// Every entry point, by file and line.
+    pub async fn entry_points(&self) -> Result<Vec<EntryPointNode>> {
+        let mut res = self.db
+            .query("SELECT id, name as label, kind, file.path as file_path, start_line, entry_kind FROM symbol WHERE entry_point = true AND file != NONE ORDER BY file_path, start_line")
+            .await?;
+        Ok(res.take(0)?)
+    }
+
+    /// Functions and methods that are neither entry points nor called or
+    /// imported anywhere in the index, by file and line.
+    pub async fn unreferenced_symbols(&self) -> Result<Vec<UnreferencedSymbol>> {
+        let mut res = self.db
+            .query(
+                "SELECT id, name as label, kind, file.path as file_path, start_line, end_line, parent_scope FROM symbol \
+                 WHERE kind IN ['function', 'method'] AND entry_point != true AND file != NONE \
+                 AND count(<-calls) = 0 AND count(<-imports) = 0 ORDER BY file_path, start_line",
+            )
+            .await?;
+        Ok(res.take(0)?)
+    }
+}
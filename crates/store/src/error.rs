@@ -0,0 +1,24 @@
+use emry_core::error::EmryError;
+use std::path::PathBuf;
+
+#[derive(Debug, thiserror::Error)]
+pub enum StoreError {
+    #[error("failed to open index at {}: {source}", path.display())]
+    Open {
+        path: PathBuf,
+        #[source]
+        source: surrealdb::Error,
+    },
+
+    #[error("failed to initialize schema: {0}")]
+    Schema(#[source] surrealdb::Error),
+
+    #[error("the index is open read-only (--read-only or core.read_only)")]
+    ReadOnly,
+}
+
+impl From<StoreError> for EmryError {
+    fn from(e: StoreError) -> Self {
+        EmryError::Storage(Box::new(e))
+    }
+}
@@ -0,0 +1,176 @@
+//! Canonical dump of the index for comparing builds.
+//!
+//! The database files of two identical builds still differ byte for byte, so
+//! builds are compared through this snapshot instead: every table sorted by
+//! a content-derived key, chunk text reduced to a hash and edges identified
+//! by their endpoints rather than by their generated record ids. With an
+//! index built by `emry index --deterministic`, the same commit always gives
+//! the same snapshot. Embedding vectors are left out, since remote embedding
+//! APIs do not return bit-identical floats; only whether a chunk has one is kept.
+
+use crate::SurrealStore;
+use anyhow::Result;
+use emry_core::models::EdgeKind;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use surrealdb::sql::Thing;
+
+#[derive(Debug, Serialize)]
+pub struct IndexSnapshot {
+    pub files: Vec<FileEntry>,
+    pub chunks: Vec<ChunkEntry>,
+    pub symbols: Vec<SymbolEntry>,
+    pub edges: Vec<EdgeEntry>,
+    pub commits: Vec<CommitEntry>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FileEntry {
+    pub path: String,
+    pub language: String,
+    pub hash: String,
+    pub last_modified: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ChunkEntry {
+    pub id: String,
+    pub file: String,
+    pub start_line: usize,
+    pub end_line: usize,
+    /// SHA-256 of the chunk text
+    pub content_hash: String,
+    pub embedded: bool,
+    pub scopes: Vec<String>,
+    pub tags: Vec<String>,
+    pub sparse_terms: Vec<String>,
+    pub sparse_weights: Vec<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SymbolEntry {
+    pub id: String,
+    pub name: String,
+    pub kind: String,
+    pub file: String,
+    pub start_line: usize,
+    pub end_line: usize,
+    pub parent_scope: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entry_kind: Option<emry_core::entry_points::EntryPointKind>,
+}
+
+#[derive(Debug, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+pub struct EdgeEntry {
+    pub kind: String,
+    pub from: String,
+    pub to: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CommitEntry {
+    pub commit_id: String,
+    pub timestamp: u64,
+    pub note: String,
+    pub changed_symbols: usize,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChunkRow {
+    id: Thing,
+    content: String,
+    embedded: bool,
+    file: Thing,
+    start_line: usize,
+    end_line: usize,
+    #[serde(default)]
+    scopes: Vec<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    sparse_terms: Vec<String>,
+    #[serde(default)]
+    sparse_weights: Vec<f32>,
+    #[serde(default)]
+    language: Option<String>,
+}
+
+impl SurrealStore {
+    /// Every file, chunk, symbol, edge and indexing run, in a stable order.
+    pub async fn export_snapshot(&self) -> Result<IndexSnapshot> {
+        let mut files: Vec<FileEntry> = self
+            .list_files()
+            .await?
+            .into_iter()
+            .map(|f| FileEntry { path: f.path, language: f.language, hash: f.hash, last_modified: f.last_modified })
+            .collect();
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+
+        let mut res = self
+            .db
+            // Duplicates read through to their canonical chunk, so the snapshot
+            // does not depend on which copy was indexed first.
+            .query("SELECT id, duplicate_of.content ?? content AS content, (duplicate_of.embedding ?? embedding) != NONE AS embedded, file, start_line, end_line, scopes, duplicate_of.tags ?? tags AS tags, duplicate_of.sparse_terms ?? sparse_terms AS sparse_terms, duplicate_of.sparse_weights ?? sparse_weights AS sparse_weights, language FROM chunk")
+            .await?;
+        let rows: Vec<ChunkRow> = res.take(0)?;
+        let mut chunks: Vec<ChunkEntry> = rows
+            .into_iter()
+            .map(|r| ChunkEntry {
+                id: r.id.to_string(),
+                file: r.file.to_string(),
+                start_line: r.start_line,
+                end_line: r.end_line,
+                content_hash: hex::encode(Sha256::digest(r.content.as_bytes())),
+                embedded: r.embedded,
+                scopes: r.scopes,
+                tags: r.tags,
+                sparse_terms: r.sparse_terms,
+                sparse_weights: r.sparse_weights,
+                language: r.language,
+            })
+            .collect();
+        chunks.sort_by(|a, b| a.id.cmp(&b.id));
+
+        let mut res = self.db.query("SELECT * FROM symbol").await?;
+        let rows: Vec<crate::SymbolRecord> = res.take(0)?;
+        let mut symbols: Vec<SymbolEntry> = rows
+            .into_iter()
+            .map(|s| SymbolEntry {
+                id: s.id.map(|id| id.to_string()).unwrap_or_default(),
+                name: s.name,
+                kind: s.kind,
+                file: s.file.to_string(),
+                start_line: s.start_line,
+                end_line: s.end_line,
+                parent_scope: s.parent_scope,
+                entry_kind: s.entry_kind,
+            })
+            .collect();
+        symbols.sort_by(|a, b| (&a.id, a.start_line).cmp(&(&b.id, b.start_line)));
+
+        let mut edges = Vec::new();
+        for kind in EdgeKind::ALL {
+            for (from, to) in self.list_edges(kind).await? {
+                edges.push(EdgeEntry { kind: kind.as_str().to_string(), from, to });
+            }
+        }
+        edges.sort();
+
+        let mut res = self.db.query("SELECT * FROM commit_log").await?;
+        let rows: Vec<crate::CommitLogRecord> = res.take(0)?;
+        let mut commits: Vec<CommitEntry> = rows
+            .into_iter()
+            .map(|c| CommitEntry {
+                commit_id: c.commit_id,
+                timestamp: c.timestamp,
+                note: c.note,
+                changed_symbols: c.changed_symbols,
+            })
+            .collect();
+        commits.sort_by(|a, b| (a.timestamp, &a.commit_id, &a.note).cmp(&(b.timestamp, &b.commit_id, &b.note)));
+
+        Ok(IndexSnapshot { files, chunks, symbols, edges, commits })
+    }
+}
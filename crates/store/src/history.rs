@@ -0,0 +1,76 @@
+//! Per-symbol content history across index runs.
+//!
+//! Every run hashes each re-indexed symbol's source and appends a
+//! `symbol_history` entry only when the hash differs from the symbol's latest
+//! one, so the table grows with actual edits rather than with index runs.
+
+use crate::models::SymbolHistoryRecord;
+use crate::SurrealStore;
+use anyhow::Result;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+#[derive(Debug, Deserialize)]
+struct LatestHash {
+    symbol: String,
+    content_hash: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Count {
+    count: usize,
+}
+
+impl SurrealStore {
+    /// Append the versions whose content changed since the symbol's last entry.
+    ///
+    /// Returns the number of entries written, i.e. the symbols that changed.
+    pub async fn record_symbol_versions(&self, versions: Vec<SymbolHistoryRecord>) -> Result<usize> {
+        self.ensure_writable()?;
+        if versions.is_empty() {
+            return Ok(0);
+        }
+
+        let ids: Vec<String> = versions.iter().map(|v| v.symbol.clone()).collect();
+        let mut res = self.db
+            .query("SELECT symbol, content_hash, timestamp FROM symbol_history WHERE symbol IN $ids ORDER BY timestamp DESC")
+            .bind(("ids", ids))
+            .await?;
+        let rows: Vec<LatestHash> = res.take(0)?;
+
+        let mut latest: HashMap<String, String> = HashMap::new();
+        for row in rows {
+            latest.entry(row.symbol).or_insert(row.content_hash);
+        }
+
+        let changed: Vec<SymbolHistoryRecord> = versions
+            .into_iter()
+            .filter(|v| latest.get(&v.symbol) != Some(&v.content_hash))
+            .collect();
+        let written = changed.len();
+        if written > 0 {
+            let _: Vec<SymbolHistoryRecord> = self.db.insert("symbol_history").content(changed).await?;
+        }
+        Ok(written)
+    }
+
+    /// History entries for a symbol name or full symbol id, newest first.
+    pub async fn symbol_history(&self, symbol: &str) -> Result<Vec<SymbolHistoryRecord>> {
+        let mut res = self.db
+            .query("SELECT * FROM symbol_history WHERE name = $symbol OR symbol = $symbol ORDER BY timestamp DESC")
+            .bind(("symbol", symbol.to_string()))
+            .await?;
+        let entries: Vec<SymbolHistoryRecord> = res.take(0)?;
+        Ok(entries)
+    }
+
+    /// Number of index runs recorded at or after `timestamp`.
+    pub async fn count_commits_since(&self, timestamp: u64) -> Result<usize> {
+        let mut res = self.db
+            .query("SELECT count() FROM commit_log WHERE timestamp >= $ts GROUP ALL")
+            .bind(("ts", timestamp))
+            .await?;
+        let counts: Vec<Count> = res.take(0)?;
+        Ok(counts.first().map(|c| c.count).unwrap_or(0))
+    }
+}
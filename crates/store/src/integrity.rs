@@ -0,0 +1,84 @@
+//! Consistency checks between the store's tables.
+//!
+//! `delete_file` removes a file's chunks and symbols but not the edges that
+//! point at them, and an interrupted or failed write can leave a file record
+//! without chunks or chunks without embeddings. These queries find such
+//! leftovers so `emry doctor --repair` can clear them and reindex the files.
+
+use crate::SurrealStore;
+use anyhow::Result;
+use emry_core::models::EdgeKind;
+use serde::Deserialize;
+use surrealdb::sql::Thing;
+
+/// An edge with a missing endpoint.
+#[derive(Debug, Clone)]
+pub struct DanglingEdge {
+    pub id: String,
+    pub kind: EdgeKind,
+    /// File of the surviving source node, whose edges need resolving again.
+    pub source_file: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DanglingRow {
+    id: Thing,
+    in_file: Option<String>,
+    in_path: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PathRow {
+    path: Option<String>,
+}
+
+impl SurrealStore {
+    /// Paths of files that have at least one chunk.
+    pub async fn files_with_chunks(&self) -> Result<Vec<String>> {
+        let mut res = self.db.query("SELECT file.path AS path FROM chunk GROUP BY path").await?;
+        let rows: Vec<PathRow> = res.take(0)?;
+        Ok(rows.into_iter().filter_map(|r| r.path).collect())
+    }
+
+    /// Paths of files with at least one chunk that has no embedding of its
+    /// own nor shares one as a duplicate.
+    pub async fn files_missing_embeddings(&self) -> Result<Vec<String>> {
+        let mut res = self
+            .db
+            .query("SELECT file.path AS path FROM chunk WHERE embedding = NONE AND duplicate_of = NONE GROUP BY path")
+            .await?;
+        let rows: Vec<PathRow> = res.take(0)?;
+        Ok(rows.into_iter().filter_map(|r| r.path).collect())
+    }
+
+    /// Edges of every relation whose source or target record no longer exists.
+    pub async fn dangling_edges(&self) -> Result<Vec<DanglingEdge>> {
+        let mut edges = Vec::new();
+        for kind in EdgeKind::ALL {
+            let mut res = self
+                .db
+                .query(format!(
+                    "SELECT id, in.file.path AS in_file, in.path AS in_path FROM {} WHERE in.id = NONE OR out.id = NONE",
+                    kind.as_str()
+                ))
+                .await?;
+            let rows: Vec<DanglingRow> = res.take(0)?;
+            edges.extend(rows.into_iter().map(|r| DanglingEdge {
+                id: r.id.to_string(),
+                kind,
+                source_file: r.in_file.or(r.in_path),
+            }));
+        }
+        Ok(edges)
+    }
+
+    /// Delete edges by record id; returns how many ids were given.
+    pub async fn delete_edges(&self, ids: &[String]) -> Result<usize> {
+        self.ensure_writable()?;
+        let things = ids.iter().map(|id| surrealdb::sql::thing(id)).collect::<Result<Vec<Thing>, _>>()?;
+        if !things.is_empty() {
+            self.db.query("DELETE $ids").bind(("ids", things)).await?;
+        }
+        Ok(ids.len())
+    }
+}
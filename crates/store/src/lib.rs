@@ -1,53 +1,173 @@
 mod models;
+pub mod backend;
+pub mod closure;
+pub mod clones;
+pub mod coverage;
+pub mod dedup;
+pub mod docs;
+pub mod entry_points;
+pub mod error;
+pub mod export;
+pub mod history;
+pub mod integrity;
+pub mod llm_cache;
+pub mod name_filter;
+pub mod owners;
+pub mod quantization;
+pub mod read_only;
+pub mod summary;
 
 use anyhow::Result;
-pub use models::{ChunkRecord, FileRecord, SymbolRecord, SurrealGraphNode, SurrealGraphEdge, CommitLogRecord};
+pub use backend::{Direction, GraphBackend};
+pub use error::StoreError;
+pub use models::{ChunkRecord, FileRecord, SymbolRecord, SurrealGraphNode, SurrealGraphEdge, CallSite, CommitLogRecord, NeighborhoodRecord, ReachEntry, SymbolHistoryRecord};
+use emry_core::error::EmryError;
+use emry_core::models::EdgeKind;
+use emry_core::quantize::Quantization;
 use emry_core::relations::RelationRef;
+use emry_core::rust_types::{normalize_type, type_name};
+use emry_core::supertypes::SupertypeRef;
+use dedup::FileFilter;
+use name_filter::NameFilter;
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
+use std::sync::{Arc, RwLock};
 use surrealdb::engine::local::RocksDb;
 use surrealdb::Surreal;
 use surrealdb::sql::Thing;
 
+/// Edges per `INSERT RELATION` statement during edge ingestion.
+const RELATE_BATCH_SIZE: usize = 500;
+
 #[derive(Clone)]
 pub struct SurrealStore {
     db: Surreal<surrealdb::engine::local::Db>,
+    vector_index: bool,
+    /// How the vector index stores embeddings; see `quantization`.
+    quantization: Quantization,
+    /// Shared across clones; `None` until `warm_symbol_filter` runs.
+    symbol_filter: Arc<RwLock<Option<NameFilter>>>,
+    /// Set by `open_read_only`; write methods fail with `StoreError::ReadOnly`.
+    read_only: bool,
+    /// The copy a read-only store reads from, removed on drop. Declared
+    /// after `db` so the database closes before the copy is removed.
+    _snapshot: Option<Arc<tempfile::TempDir>>,
 }
 
 impl SurrealStore {
     pub async fn new(path: &Path, vector_dimension: usize) -> Result<Self> {
-        let db = Surreal::new::<RocksDb>(path).await?;
-        db.use_ns("emry").use_db("main").await?;
+        Self::open(path, Some(vector_dimension)).await
+    }
+
+    /// Open the store, defining the HNSW vector index only when a dimension is given.
+    ///
+    /// Lexical-only installs pass `None` and never pay for the vector index.
+    pub async fn open(path: &Path, vector_dimension: Option<usize>) -> Result<Self> {
+        Self::open_quantized(path, vector_dimension, Quantization::None).await
+    }
+
+    /// Like `open`, with a new index's vectors quantized as `quantization`;
+    /// an existing index keeps the mode it was built with.
+    pub async fn open_quantized(path: &Path, vector_dimension: Option<usize>, quantization: Quantization) -> Result<Self> {
+        let open_err = |source| EmryError::from(StoreError::Open { path: path.to_path_buf(), source });
+        let db = Surreal::new::<RocksDb>(path).await.map_err(open_err)?;
+        db.use_ns("emry").use_db("main").await.map_err(open_err)?;
         
-        Self::init_schema(&db, vector_dimension).await?;
+        let quantization = Self::index_quantization(&db, quantization, vector_dimension.is_some())
+            .await
+            .map_err(|e| EmryError::from(StoreError::Schema(e)))?;
+        Self::init_schema(&db, vector_dimension, quantization)
+            .await
+            .map_err(|e| EmryError::from(StoreError::Schema(e)))?;
         
-        Ok(Self { db })
+        Ok(Self {
+            db,
+            vector_index: vector_dimension.is_some(),
+            quantization,
+            symbol_filter: Arc::new(RwLock::new(None)),
+            read_only: false,
+            _snapshot: None,
+        })
     }
 
-    async fn init_schema(db: &Surreal<surrealdb::engine::local::Db>, vector_dimension: usize) -> Result<()> {
-        let query = format!("DEFINE INDEX chunk_embedding ON chunk FIELDS embedding HNSW DIMENSION {} DIST COSINE M 16 EFC 64", vector_dimension);
-        db.query(query).await?;
+    async fn init_schema(db: &Surreal<surrealdb::engine::local::Db>, vector_dimension: Option<usize>, quantization: Quantization) -> surrealdb::Result<()> {
+        if let Some(dim) = vector_dimension {
+            // A quantized index only reads the full vectors back for rescoring, so they get no index of their own.
+            let query = match quantization {
+                Quantization::None => format!("DEFINE INDEX chunk_embedding ON chunk FIELDS embedding HNSW DIMENSION {} DIST COSINE M 16 EFC 64", dim),
+                Quantization::Int8 => format!("DEFINE INDEX chunk_embedding_q ON chunk FIELDS embedding_q HNSW DIMENSION {} DIST COSINE TYPE I16 M 16 EFC 64", dim),
+                Quantization::Binary => format!("DEFINE INDEX chunk_embedding_q ON chunk FIELDS embedding_q HNSW DIMENSION {} DIST HAMMING TYPE I16 M 16 EFC 64", dim),
+            };
+            db.query(query).await?;
+            let query = format!("DEFINE INDEX chunk_doc_embedding ON chunk FIELDS doc_embedding HNSW DIMENSION {} DIST COSINE M 16 EFC 64", dim);
+            db.query(query).await?;
+        }
         
         db.query("DEFINE ANALYZER code_analyzer TOKENIZERS class, blank FILTERS lowercase, ascii, snowball(english)").await?;
         db.query("DEFINE INDEX chunk_content ON chunk FIELDS content SEARCH ANALYZER code_analyzer BM25").await?;
+        db.query("DEFINE INDEX chunk_doc ON chunk FIELDS doc SEARCH ANALYZER code_analyzer BM25").await?;
+        db.query("DEFINE INDEX chunk_tags ON chunk FIELDS tags").await?;
+        db.query("DEFINE INDEX chunk_sparse_terms ON chunk FIELDS sparse_terms").await?;
+        db.query("DEFINE INDEX chunk_content_hash ON chunk FIELDS content_hash").await?;
+        db.query("DEFINE INDEX chunk_duplicate_of ON chunk FIELDS duplicate_of").await?;
         
         db.query("DEFINE INDEX unique_calls ON TABLE calls COLUMNS in, out UNIQUE").await?;
         db.query("DEFINE INDEX unique_imports ON TABLE imports COLUMNS in, out UNIQUE").await?;
         db.query("DEFINE INDEX unique_defines ON TABLE defines COLUMNS in, out UNIQUE").await?;
         db.query("DEFINE INDEX unique_contains ON TABLE contains COLUMNS in, out UNIQUE").await?;
+        db.query("DEFINE INDEX unique_inherits ON TABLE inherits COLUMNS in, out UNIQUE").await?;
+        db.query("DEFINE INDEX unique_implements ON TABLE implements COLUMNS in, out UNIQUE").await?;
+
+        db.query("DEFINE INDEX symbol_history_symbol ON TABLE symbol_history COLUMNS symbol").await?;
+        db.query("DEFINE INDEX symbol_history_name ON TABLE symbol_history COLUMNS name").await?;
+        db.query("DEFINE INDEX llm_cache_created ON TABLE llm_cache COLUMNS created").await?;
         
         Ok(())
     }
     
+    /// The raw database; queries through it bypass the read-only check.
     pub fn db(&self) -> &Surreal<surrealdb::engine::local::Db> {
         &self.db
     }
 
-    pub async fn add_commit(&self, commit_id: String, timestamp: u64, note: String) -> Result<()> {
+    /// Load every symbol name into the in-memory filter used by exact-name lookups.
+    ///
+    /// Call after bulk node ingestion and before edge resolution; symbols added
+    /// later through `add_file_nodes` are inserted into the filter as they arrive.
+    pub async fn warm_symbol_filter(&self) -> Result<()> {
+        let mut res = self.db.query("SELECT VALUE name FROM symbol").await?;
+        let names: Vec<String> = res.take(0)?;
+        let filter = NameFilter::from_names(names.iter());
+        *self.symbol_filter.write().unwrap() = Some(filter);
+        Ok(())
+    }
+
+    /// Symbols with exactly this name; skips the query when the filter rules the name out.
+    async fn symbols_named(&self, name: &str) -> Result<Vec<SurrealGraphNode>> {
+        if let Some(filter) = self.symbol_filter.read().unwrap().as_ref() {
+            if !filter.may_contain(name) {
+                return Ok(Vec::new());
+            }
+        }
+        let mut res = self.db.query("SELECT id, name as label, kind, file.path as file_path, start_line FROM symbol WHERE name = $name")
+            .bind(("name", name.to_string()))
+            .await?;
+        Ok(res.take(0)?)
+    }
+
+    /// Whether this store was opened with a vector index.
+    pub fn has_vector_index(&self) -> bool {
+        self.vector_index
+    }
+
+    pub async fn add_commit(&self, commit_id: String, timestamp: u64, note: String, changed_symbols: usize) -> Result<()> {
+        self.ensure_writable()?;
         let record = CommitLogRecord {
             id: None,
             commit_id,
             timestamp,
             note,
+            changed_symbols,
         };
         let _: Vec<CommitLogRecord> = self.db.insert("commit_log").content(record).await?;
         Ok(())
@@ -68,6 +188,7 @@ impl SurrealStore {
         symbols: Vec<SymbolRecord>,
         call_edges: Vec<(String, String)>,
     ) -> Result<()> {
+        self.ensure_writable()?;
         let mut file_content = file.clone();
         file_content.id = None;
         let _: Option<FileRecord> = self.db.upsert(("file", &file.path))
@@ -125,10 +246,7 @@ impl SurrealStore {
 
             // 1. Try to find specific symbol matching name AND qualifier (if present)
             // We fetch candidates with the same name
-            let mut res = self.db.query("SELECT id, name as label, kind, file.path as file_path FROM symbol WHERE name = $name")
-                .bind(("name", name.to_string()))
-                .await?;
-            let candidates: Vec<SurrealGraphNode> = res.take(0)?;
+            let candidates = self.symbols_named(name).await?;
 
             let target_node = if let Some(qual) = qualifier {
                 // Filter candidates where file path contains the qualifier
@@ -154,6 +272,10 @@ impl SurrealStore {
     }
 
     pub async fn search_vector(&self, embedding: Vec<f32>, limit: usize) -> Result<Vec<ChunkRecord>> {
+        if self.quantization != Quantization::None {
+            let results = self.search_quantized(embedding, limit, &FileFilter::default()).await?;
+            return Ok(results.into_iter().map(ScoredResult::into_chunk_record).collect());
+        }
         let results: Vec<ChunkRecord> = self.db.query("SELECT * FROM chunk WHERE embedding <|10, cosine|> $query_vec LIMIT $limit")
             .bind(("query_vec", embedding))
             .bind(("limit", limit))
@@ -162,15 +284,121 @@ impl SurrealStore {
         Ok(results)
     }
 
-    pub async fn search_fts(&self, query: &str, limit: usize) -> Result<Vec<ChunkRecord>> {
-        let results: Vec<ChunkRecord> = self.db.query("SELECT * FROM chunk WHERE content @1@ $query LIMIT $limit")
+    /// Chunks matching `query` with their BM25 scores, best first. This and the other chunk searches
+    /// take `files` to search only chunks of those files (their indexed
+    /// paths, i.e. `file` record ids), inside the query rather than after it;
+    /// a text whose canonical copy is elsewhere is found at its copy in them.
+    pub async fn search_fts(&self, query: &str, limit: usize, files: Option<&[String]>) -> Result<Vec<(ChunkRecord, f32)>> {
+        let filter = self.file_filter(files).await?;
+        let results: Vec<ScoredResult> = self.db.query(format!("SELECT *, search::score(1) AS score FROM chunk WHERE content @1@ $query{} ORDER BY score DESC LIMIT $limit", filter.condition()))
             .bind(("query", query.to_string()))
             .bind(("limit", limit))
+            .bind(("files", filter.files.clone()))
+            .bind(("kept", filter.kept.clone()))
+            .await?
+            .take(0)?;
+        Ok(results.into_iter().map(|r| filter.relocate_scored(r.into_scored())).collect())
+    }
+
+    /// Number of chunks `search_fts` would match without a limit.
+    pub async fn count_fts(&self, query: &str, files: Option<&[String]>) -> Result<usize> {
+        let filter = self.file_filter(files).await?;
+        let count: Option<CountWrapper> = self.db.query(format!("SELECT count() FROM chunk WHERE content @1@ $query{} GROUP ALL", filter.condition()))
+            .bind(("query", query.to_string()))
+            .bind(("files", filter.files))
+            .bind(("kept", filter.kept))
+            .await?
+            .take(0)?;
+        Ok(count.map(|c| c.count).unwrap_or(0))
+    }
+
+    /// Number of chunks tagged with any of `tags`.
+    pub async fn count_by_tags(&self, tags: &[String], files: Option<&[String]>) -> Result<usize> {
+        let tags: Vec<String> = tags.iter().map(|t| t.to_lowercase()).collect();
+        let filter = self.file_filter(files).await?;
+        let count: Option<CountWrapper> = self.db.query(format!("SELECT count() FROM chunk WHERE tags CONTAINSANY $tags{} GROUP ALL", filter.condition()))
+            .bind(("tags", tags))
+            .bind(("files", filter.files))
+            .bind(("kept", filter.kept))
+            .await?
+            .take(0)?;
+        Ok(count.map(|c| c.count).unwrap_or(0))
+    }
+
+    /// Number of chunks whose sparse vector holds any of `terms`.
+    pub async fn count_sparse(&self, terms: &[String], files: Option<&[String]>) -> Result<usize> {
+        let filter = self.file_filter(files).await?;
+        let count: Option<CountWrapper> = self.db.query(format!("SELECT count() FROM chunk WHERE sparse_terms CONTAINSANY $terms{} GROUP ALL", filter.condition()))
+            .bind(("terms", terms.to_vec()))
+            .bind(("files", filter.files))
+            .bind(("kept", filter.kept))
+            .await?
+            .take(0)?;
+        Ok(count.map(|c| c.count).unwrap_or(0))
+    }
+
+    /// Chunks tagged with any of `tags`, most shared tags first.
+    pub async fn search_by_tags(&self, tags: &[String], limit: usize, files: Option<&[String]>) -> Result<Vec<ChunkRecord>> {
+        let tags: Vec<String> = tags.iter().map(|t| t.to_lowercase()).collect();
+        let filter = self.file_filter(files).await?;
+        let mut results: Vec<ChunkRecord> = self.db.query(format!("SELECT * FROM chunk WHERE tags CONTAINSANY $tags{}", filter.condition()))
+            .bind(("tags", tags.clone()))
+            .bind(("files", filter.files.clone()))
+            .bind(("kept", filter.kept.clone()))
             .await?
             .take(0)?;
+        results.iter_mut().for_each(|c| filter.relocate(c));
+        let overlap = |c: &ChunkRecord| c.tags.iter().filter(|t| tags.contains(t)).count();
+        results.sort_by_key(|c| std::cmp::Reverse(overlap(c)));
+        results.truncate(limit);
         Ok(results)
     }
 
+    /// Chunks scored by their sparse vectors against `terms`: the sum of each
+    /// matched term's chunk weight times its IDF over the whole index, or over
+    /// `files` when the search is restricted to them.
+    pub async fn search_sparse(&self, terms: &[String], limit: usize, files: Option<&[String]>) -> Result<Vec<(ChunkRecord, f32)>> {
+        let filter = self.file_filter(files).await?;
+        let cond = filter.condition();
+        let mut res = self.db.query(format!("SELECT * FROM chunk WHERE sparse_terms CONTAINSANY $terms{}; SELECT count() FROM chunk WHERE duplicate_of = NONE{} GROUP ALL", cond, cond))
+            .bind(("terms", terms.to_vec()))
+            .bind(("files", filter.files.clone()))
+            .bind(("kept", filter.kept.clone()))
+            .await?;
+        let mut chunks: Vec<ChunkRecord> = res.take(0)?;
+        chunks.iter_mut().for_each(|c| filter.relocate(c));
+        let total: Option<CountWrapper> = res.take(1)?;
+        let total = total.map(|c| c.count).unwrap_or(0).max(chunks.len()) as f32;
+
+        // Every chunk holding a query term is in `chunks`, so document frequencies come from them.
+        let mut doc_freq: HashMap<&str, usize> = HashMap::new();
+        for chunk in &chunks {
+            for term in chunk.sparse_terms.iter().filter(|t| terms.contains(t)) {
+                *doc_freq.entry(term.as_str()).or_insert(0) += 1;
+            }
+        }
+        let idf: HashMap<String, f32> = doc_freq
+            .into_iter()
+            .map(|(term, df)| (term.to_string(), ((total + 1.0) / (df as f32 + 1.0)).ln() + 1.0))
+            .collect();
+
+        let mut scored: Vec<(ChunkRecord, f32)> = chunks
+            .into_iter()
+            .map(|chunk| {
+                let score = chunk
+                    .sparse_terms
+                    .iter()
+                    .zip(&chunk.sparse_weights)
+                    .filter_map(|(term, weight)| idf.get(term).map(|idf| weight * idf))
+                    .sum();
+                (chunk, score)
+            })
+            .collect();
+        scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+        scored.truncate(limit);
+        Ok(scored)
+    }
+
     pub async fn add_file_nodes(
         &self,
         file: &FileRecord,
@@ -178,6 +406,7 @@ impl SurrealStore {
         symbols: &[SymbolRecord],
         chunk_to_symbol: &std::collections::HashMap<String, String>,
     ) -> Result<()> {
+        self.ensure_writable()?;
         // 1. Delete old data for this file
         // Use the file path directly as the ID
         let file_id_str = &file.path;
@@ -187,6 +416,7 @@ impl SurrealStore {
         
         // Delete chunks for this file (they reference the file Thing)
         let file_thing = Thing::from(("file", file_id_str.as_str()));
+        self.release_chunks(&file_thing).await?;
         let _ = self.db.query("DELETE chunk WHERE file = $file")
             .bind(("file", file_thing.clone()))
             .await?;
@@ -204,12 +434,23 @@ impl SurrealStore {
             .await?;
             
         // 3. Create Chunks
+        // A chunk identical to one already stored only points at it.
+        let mut canonical = self.canonical_chunks(chunks.iter().map(|c| c.content_hash.clone()).collect()).await?;
         for chunk in chunks {
             if let Some(id) = &chunk.id {
                 // Extract the raw string ID from the Thing
                 let id_str = id.id.to_string();
                 let mut chunk_content = chunk.clone();
                 chunk_content.id = None;
+                if !chunk.content_hash.is_empty() {
+                    match canonical.get(&chunk.content_hash) {
+                        Some(original) => chunk_content.share(original.clone()),
+                        None => {
+                            canonical.insert(chunk.content_hash.clone(), id.clone());
+                        }
+                    }
+                }
+                chunk_content.embedding_q = self.quantized(chunk_content.embedding.as_deref());
                 let _: Option<ChunkRecord> = self.db.upsert(("chunk", id_str))
                     .content(chunk_content)
                     .await?;
@@ -235,6 +476,11 @@ impl SurrealStore {
                 .content(symbol_content)
                 .await?;
         }
+        if let Some(filter) = self.symbol_filter.write().unwrap().as_mut() {
+            for symbol in symbols {
+                filter.insert(&symbol.name);
+            }
+        }
         
         // 5. Link Chunks to Symbols (Contains relation)
         // This is intra-file, so we can do it in pass 1
@@ -296,23 +542,28 @@ impl SurrealStore {
     fn prioritize_candidate(
         candidates: &[SurrealGraphNode],
         caller_id: &str,
+    ) -> Option<SurrealGraphNode> {
+        Self::nearest_candidate(candidates, Self::extract_file_from_id(caller_id).as_deref())
+    }
+
+    /// `prioritize_candidate` for a caller known only by its file.
+    fn nearest_candidate(
+        candidates: &[SurrealGraphNode],
+        caller_file: Option<&str>,
     ) -> Option<SurrealGraphNode> {
         if candidates.is_empty() {
             return None;
         }
         
-        // Extract caller file path from ID
-        let caller_file = Self::extract_file_from_id(caller_id);
-        
         // 1. Same file (highest priority)
-        if let Some(caller_path) = &caller_file {
-            if let Some(c) = candidates.iter().find(|c| &c.file_path == caller_path) {
+        if let Some(caller_path) = caller_file {
+            if let Some(c) = candidates.iter().find(|c| c.file_path == caller_path) {
                 return Some(c.clone());
             }
         }
         
         // 2. Same directory
-        if let Some(caller_path) = &caller_file {
+        if let Some(caller_path) = caller_file {
             if let Some(caller_dir) = std::path::Path::new(caller_path).parent() {
                 let caller_dir_str = caller_dir.to_string_lossy();
                 if let Some(c) = candidates.iter().find(|c| {
@@ -327,7 +578,7 @@ impl SurrealStore {
         }
         
         // 3. Parent directory (one level up)
-        if let Some(caller_path) = &caller_file {
+        if let Some(caller_path) = caller_file {
             if let Some(caller_dir) = std::path::Path::new(caller_path).parent() {
                 if let Some(caller_parent) = caller_dir.parent() {
                     let parent_str = caller_parent.to_string_lossy();
@@ -369,9 +620,10 @@ impl SurrealStore {
         call_edges: &[(String, RelationRef)],
         import_edges: &[(String, RelationRef)],
     ) -> Result<()> {
+        self.ensure_writable()?;
         // 1. Build Local Scope Map from Imports
         // Map: local_name -> full_import_path
-        let mut scope_map: std::collections::HashMap<String, String> = std::collections::HashMap::new();
+        let mut scope_map: HashMap<String, String> = HashMap::new();
         
         for (_, relation) in import_edges {
             if let Some(alias) = &relation.alias {
@@ -393,197 +645,215 @@ impl SurrealStore {
             }
         }
         
-        // 2. Add Call Edges with Polyglot Resolution
+        // 2. Resolve each call to (lookup name, module path hint)
+        //
+        // RESOLUTION STRATEGY:
+        // 1. Context Resolution: If context exists, try to map it to a module/type.
+        // 2. Scope Resolution: If name is in scope, use full path.
+        // 3. Global Search: Fallback.
+        //
+        // Rust calls whose receiver type is known (see `emry_core::rust_types`)
+        // only resolve to methods of that type, found through `use` like any
+        // other name; other Rust method calls are left unresolved.
+        let mut calls: Vec<(&str, &str, Option<String>)> = Vec::with_capacity(call_edges.len());
+        let mut owners: Vec<Option<&str>> = Vec::with_capacity(call_edges.len());
         for (caller_id, call) in call_edges {
-            let name = &call.name;
-            let context = &call.context; // e.g., "obj" in "obj.method()"
-
-            // RESOLUTION STRATEGY:
-            // 1. Context Resolution: If context exists, try to map it to a module/type.
-            // 2. Scope Resolution: If name is in scope, use full path.
-            // 3. Global Search: Fallback.
-
-            let target_node = if let Some(ctx) = context {
-                // Case A: Method call on an object/module (ctx.name())
-                
-                // Check if context is an alias in scope
-                // e.g. import mod as m; m.func() -> ctx="m", maps to "mod"
-                if let Some(full_module_path) = scope_map.get(ctx) {
-                    // We are looking for symbol 'name' in module 'full_module_path'
-                    // Query: name='name', file_path contains 'full_module_path'
-                    
-                    let mut res = self.db.query("SELECT id, name as label, kind, file.path as file_path FROM symbol WHERE name = $name")
-                        .bind(("name", name.to_string()))
-                        .await?;
-                    let candidates: Vec<SurrealGraphNode> = res.take(0)?;
-                    
-                    // Normalize path separators for matching
-                let mod_path_slash = if full_module_path.contains('/') {
-                    full_module_path.to_string()
-                } else {
-                    full_module_path.replace("::", "/").replace('.', "/")
-                };
-                
-                candidates.iter().find(|c| c.file_path.contains(&mod_path_slash)).cloned()
-                    .or_else(|| Self::prioritize_candidate(&candidates, caller_id))
-                } else {
-                    // Context is not an import alias. It might be a variable or a direct module name.
-                    // e.g. "std::fs::read()" -> ctx="std::fs" (if parser split it) or just name="std::fs::read"
-                    // Or "x.method()" where x is a local variable.
-                    
-                    // Try to find 'name' globally, filtering by context in file path
-                    let mut res = self.db.query("SELECT id, name as label, kind, file.path as file_path FROM symbol WHERE name = $name")
-                        .bind(("name", name.to_string()))
-                        .await?;
-                    let candidates: Vec<SurrealGraphNode> = res.take(0)?;
-                    
-                let ctx_slash = if ctx.contains('/') {
-                    ctx.to_string()
+            let name = call.name.as_str();
+            owners.push(call.receiver_type.as_deref().map(type_name));
+            let (lookup, hint) = if let Some(owner) = &call.receiver_type {
+                let method = name.rsplit('.').next().unwrap_or(name);
+                let (type_part, module_part) = split_qualified(owner);
+                let module = if module_part.is_empty() {
+                    scope_map.get(type_part).map(|full| split_qualified(full).1).unwrap_or("")
                 } else {
-                    ctx.replace("::", "/").replace('.', "/")
+                    module_part
                 };
-                candidates.iter().find(|c| c.file_path.contains(&ctx_slash)).cloned()
-                     .or_else(|| Self::prioritize_candidate(&candidates, caller_id))
-                }
+                (method, (!module.is_empty()).then(|| module_path(module)))
+            } else if let Some(ctx) = &call.context {
+                // Case A: Method call on an object/module (ctx.name())
+                // An import alias (import mod as m; m.func()) maps to its module;
+                // otherwise the context itself (a module path or local variable) is the hint.
+                let module = scope_map.get(ctx).unwrap_or(ctx);
+                (name, Some(module_path(module)))
             } else if let Some(full_path) = scope_map.get(name) {
                 // Case B: Direct call to imported symbol (name())
-                
-                // Extract symbol name from full path (last part)
-                let symbol_part = if let Some(idx) = full_path.rfind("::") {
-                    &full_path[idx+2..]
-                } else if let Some(idx) = full_path.rfind('.') {
-                    if full_path.contains('/') {
-                         if let Some(idx) = full_path.rfind('/') {
-                             &full_path[idx+1..]
-                         } else {
-                             full_path.as_str()
-                         }
-                    } else {
-                        &full_path[idx+1..]
-                    }
-                } else {
-                    full_path.as_str()
-                };
-                
-                // Extract module part (everything before)
-                let module_part = if let Some(idx) = full_path.rfind("::") {
-                    &full_path[..idx]
-                } else if let Some(idx) = full_path.rfind('.') {
-                     if full_path.contains('/') {
-                         if let Some(idx) = full_path.rfind('/') {
-                             &full_path[..idx]
-                         } else {
-                             ""
-                         }
-                     } else {
-                        &full_path[..idx]
-                     }
-                } else {
-                    ""
-                };
-                
-                let mut res = self.db.query("SELECT id, name as label, kind, file.path as file_path FROM symbol WHERE name = $name")
-                    .bind(("name", symbol_part.to_string()))
-                    .await?;
-                let candidates: Vec<SurrealGraphNode> = res.take(0)?;
-                
-                let mod_path_slash = if module_part.contains('/') {
-                    module_part.to_string()
-                } else {
-                    module_part.replace("::", "/").replace('.', "/")
-                };
-                
-                candidates.iter().find(|c| c.file_path.contains(&mod_path_slash)).cloned()
-                    .or_else(|| Self::prioritize_candidate(&candidates, caller_id))
+                let (symbol_part, module_part) = split_qualified(full_path);
+                (symbol_part, Some(module_path(module_part)))
             } else {
                 // Case C: Global Search (No context, not in scope)
-                // e.g. "print()" or implicit global
-                
-                let mut res = self.db.query("SELECT id, name as label, kind, file.path as file_path FROM symbol WHERE name = $name")
-                    .bind(("name", name.to_string()))
-                    .await?;
-                let candidates: Vec<SurrealGraphNode> = res.take(0)?;
-                
-                // Priority: same file > same directory > parent directory > first match
-                Self::prioritize_candidate(&candidates, caller_id)
+                (name, None)
             };
-            
-            if let Some(target) = target_node {
-                 let _ = self.db.query("RELATE $from->calls->$to")
-                    .bind(("from", surrealdb::sql::thing(caller_id)?))
-                    .bind(("to", target.id))
-                    .await;
+            calls.push((caller_id.as_str(), lookup, hint));
+        }
+
+        // 3. Resolve each import to its symbol and module
+        let imports: Vec<(&str, &str, Option<String>)> = import_edges
+            .iter()
+            .map(|(importer_id, relation)| {
+                let (symbol_part, module_part) = split_qualified(&relation.name);
+                (importer_id.as_str(), symbol_part, Some(module_path(module_part)))
+            })
+            .collect();
+
+        // 4. One lookup for every name this file references
+        let names: HashSet<&str> = calls.iter().chain(imports.iter()).map(|(_, name, _)| *name).collect();
+        let by_name = self.symbols_named_many(&names).await?;
+
+        // 5. Pick targets and write all edges of a kind in batched statements
+        let mut call_rows = Vec::new();
+        for (((from, name, hint), (_, call)), owner) in calls.iter().zip(call_edges).zip(&owners) {
+            let found = by_name.get(*name);
+            let narrowed = found.and_then(|c| Self::rust_candidates(c, *owner, from));
+            if let Some(target) = Self::resolve_target(narrowed.as_ref().or(found), hint.as_deref(), from) {
+                call_rows.push(EdgeRow {
+                    r#in: surrealdb::sql::thing(from)?,
+                    out: target.id,
+                    alias: None,
+                    line: Some(call.line),
+                    column: Some(call.column),
+                });
             }
         }
+        // One edge per caller and callee; it records the first call site.
+        call_rows.sort_by_key(|r| (r.line, r.column));
+        let mut import_rows = Vec::new();
+        for ((from, name, hint), (_, relation)) in imports.iter().zip(import_edges) {
+            if let Some(target) = Self::resolve_target(by_name.get(*name), hint.as_deref(), from) {
+                import_rows.push(EdgeRow {
+                    r#in: surrealdb::sql::thing(from)?,
+                    out: target.id,
+                    alias: relation.alias.clone(),
+                    line: None,
+                    column: None,
+                });
+            }
+        }
+
+        self.relate_many("calls", call_rows).await?;
+        self.relate_many("imports", import_rows).await?;
         
-        // 3. Add Import Edges
-        for (importer_id, relation) in import_edges {
-             let full_path = &relation.name;
-             
-             // Extract symbol part for query
-             let symbol_part = if let Some(idx) = full_path.rfind("::") {
-                &full_path[idx+2..]
-            } else if let Some(idx) = full_path.rfind('.') {
-                 if full_path.contains('/') {
-                     if let Some(idx) = full_path.rfind('/') {
-                         &full_path[idx+1..]
-                     } else {
-                         full_path.as_str()
-                     }
-                 } else {
-                    &full_path[idx+1..]
-                 }
-            } else {
-                full_path.as_str()
-            };
-            
-            // Extract module part for filtering
-            let module_part = if let Some(idx) = full_path.rfind("::") {
-                &full_path[..idx]
-            } else if let Some(idx) = full_path.rfind('.') {
-                 if full_path.contains('/') {
-                     if let Some(idx) = full_path.rfind('/') {
-                         &full_path[..idx]
-                     } else {
-                         ""
-                     }
-                 } else {
-                    &full_path[..idx]
-                 }
-            } else {
-                ""
+        Ok(())
+    }
+
+    /// Write `inherits` and `implements` edges for the supertypes declared in
+    /// `file_path`. Both ends are looked up by name among type symbols, the
+    /// ones nearest to the declaring file first; unresolved names (library
+    /// types) are skipped.
+    pub async fn add_supertype_edges(&self, file_path: &str, supertypes: &[SupertypeRef]) -> Result<()> {
+        self.ensure_writable()?;
+        let names: HashSet<&str> = supertypes
+            .iter()
+            .flat_map(|s| [s.name.as_str(), s.supertype.as_str()])
+            .collect();
+        let by_name = self.symbols_named_many(&names).await?;
+        let resolve = |name: &str| {
+            let types: Vec<SurrealGraphNode> = by_name
+                .get(name)?
+                .iter()
+                .filter(|c| matches!(c.kind.as_str(), "class" | "interface" | "enum" | "type"))
+                .cloned()
+                .collect();
+            Self::nearest_candidate(&types, Some(file_path))
+        };
+
+        let (mut inherits, mut implements) = (Vec::new(), Vec::new());
+        for supertype in supertypes {
+            let (Some(sub), Some(sup)) = (resolve(&supertype.name), resolve(&supertype.supertype)) else {
+                continue;
             };
-            
-             let mut res = self.db.query("SELECT id, name as label, kind, file.path as file_path FROM symbol WHERE name = $name")
-                .bind(("name", symbol_part.to_string()))
+            let row = EdgeRow { r#in: sub.id, out: sup.id, alias: None, line: None, column: None };
+            match supertype.kind {
+                EdgeKind::Implements => implements.push(row),
+                _ => inherits.push(row),
+            }
+        }
+
+        self.relate_many(EdgeKind::Inherits.as_str(), inherits).await?;
+        self.relate_many(EdgeKind::Implements.as_str(), implements).await?;
+        Ok(())
+    }
+
+    /// Symbols for each of `names`, keyed by name, in a single query.
+    ///
+    /// Names the symbol filter rules out are dropped before querying.
+    async fn symbols_named_many(&self, names: &HashSet<&str>) -> Result<HashMap<String, Vec<SurrealGraphNode>>> {
+        let names: Vec<String> = {
+            let filter = self.symbol_filter.read().unwrap();
+            names
+                .iter()
+                .filter(|n| filter.as_ref().map_or(true, |f| f.may_contain(n)))
+                .map(|n| n.to_string())
+                .collect()
+        };
+        if names.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let mut res = self.db.query("SELECT id, name as label, kind, file.path as file_path, parent_scope FROM symbol WHERE name IN $names")
+            .bind(("names", names))
+            .await?;
+        let nodes: Vec<SurrealGraphNode> = res.take(0)?;
+
+        let mut by_name: HashMap<String, Vec<SurrealGraphNode>> = HashMap::new();
+        for node in nodes {
+            by_name.entry(node.label.clone()).or_default().push(node);
+        }
+        Ok(by_name)
+    }
+
+    /// Narrow Rust call candidates by type: to methods of `owner` when the
+    /// receiver type is known, and away from methods for plain calls from
+    /// Rust, which can only reach free functions. `None` when no narrowing applies.
+    fn rust_candidates(
+        candidates: &[SurrealGraphNode],
+        owner: Option<&str>,
+        from_id: &str,
+    ) -> Option<Vec<SurrealGraphNode>> {
+        let owner_of = |c: &SurrealGraphNode| c.parent_scope.as_deref().and_then(|s| normalize_type(s, None));
+        let narrowed = match owner {
+            Some(owner) => candidates.iter().filter(|c| owner_of(c).is_some_and(|s| type_name(&s) == owner)).cloned().collect(),
+            None if Self::extract_file_from_id(from_id)?.ends_with(".rs") => candidates
+                .iter()
+                .filter(|c| !(c.file_path.ends_with(".rs") && owner_of(c).is_some()))
+                .cloned()
+                .collect(),
+            None => return None,
+        };
+        Some(narrowed)
+    }
+
+    /// Prefer a candidate whose file path contains the module hint, else fall back to proximity.
+    fn resolve_target(
+        candidates: Option<&Vec<SurrealGraphNode>>,
+        hint: Option<&str>,
+        from_id: &str,
+    ) -> Option<SurrealGraphNode> {
+        let candidates = candidates?;
+        hint.and_then(|h| candidates.iter().find(|c| c.file_path.contains(h)).cloned())
+            .or_else(|| Self::prioritize_candidate(candidates, from_id))
+    }
+
+    /// Insert edges into `table` in multi-row statements; existing edges are skipped.
+    async fn relate_many(&self, table: &str, mut rows: Vec<EdgeRow>) -> Result<()> {
+        let mut seen = HashSet::new();
+        rows.retain(|r| seen.insert((r.r#in.to_string(), r.out.to_string())));
+
+        for batch in rows.chunks(RELATE_BATCH_SIZE) {
+            self.db.query(format!("INSERT IGNORE RELATION INTO {} $edges", table))
+                .bind(("edges", batch.to_vec()))
                 .await?;
-             let candidates: Vec<SurrealGraphNode> = res.take(0)?;
-             
-             let mod_path_slash = if module_part.contains('/') {
-                module_part.to_string()
-            } else {
-                module_part.replace("::", "/").replace('.', "/")
-            };
-            
-             let target = candidates.iter().find(|c| c.file_path.contains(&mod_path_slash)).cloned()
-                .or_else(|| Self::prioritize_candidate(&candidates, importer_id));
-             
-             if let Some(t) = target {
-                 let _ = self.db.query("RELATE $from->imports->$to")
-                    .bind(("from", surrealdb::sql::thing(importer_id)?))
-                    .bind(("to", t.id))
-                    .await;
-             }
         }
-        
         Ok(())
     }
+
     pub async fn delete_file(&self, path: &str) -> Result<()> {
+        self.ensure_writable()?;
         let file_thing = surrealdb::sql::Thing::from(("file", path));
         
         let _: Option<FileRecord> = self.db.delete(("file", path)).await?;
         
         // Delete Chunks
+        self.release_chunks(&file_thing).await?;
         let _ = self.db.query("DELETE chunk WHERE file = $file")
             .bind(("file", file_thing.clone()))
             .await?;
@@ -597,6 +867,7 @@ impl SurrealStore {
     }
 
     pub async fn add_graph_edge(&self, from: (String, String), to: (String, String), relation: &str) -> Result<()> {
+        self.ensure_writable()?;
         let res = self.db.query(format!("RELATE $from->{}->$to", relation))
             .bind(("from", from))
             .bind(("to", to))
@@ -623,9 +894,9 @@ impl SurrealStore {
         let table = &thing.tb;
         
         let sql = match table.as_str() {
-            "symbol" => "SELECT id, name as label, kind, file.path as file_path FROM $id",
+            "symbol" => "SELECT id, name as label, kind, file.path as file_path, start_line FROM $id",
             "file" => "SELECT id, path as label, 'file' as kind, path as file_path FROM $id",
-            "chunk" => "SELECT id, 'chunk' as label, 'chunk' as kind, file.path as file_path FROM $id",
+            "chunk" => "SELECT id, 'chunk' as label, 'chunk' as kind, file.path as file_path, start_line FROM $id",
             _ => return Ok(None),
         };
         
@@ -686,8 +957,8 @@ impl SurrealStore {
         let thing = surrealdb::sql::thing(id)?;
         
         let sql = match direction {
-            "out" => "SELECT in as source, out as target, type::table(id) as relation FROM $id->?",
-            "in" => "SELECT in as source, out as target, type::table(id) as relation FROM $id<-?",
+            "out" => "SELECT in as source, out as target, type::table(id) as relation, line, column FROM $id->?",
+            "in" => "SELECT in as source, out as target, type::table(id) as relation, line, column FROM $id<-?",
             _ => return Ok(Vec::new()),
         };
 
@@ -698,12 +969,65 @@ impl SurrealStore {
 
     pub async fn list_all_symbols(&self) -> Result<Vec<SurrealGraphNode>> {
         // Fetch all symbols with their file paths
-        let mut res = self.db.query("SELECT id, name as label, kind, file.path as file_path FROM symbol")
+        let mut res = self.db.query("SELECT id, name as label, kind, file.path as file_path, start_line FROM symbol")
+            .await?;
+        let symbols: Vec<SurrealGraphNode> = res.take(0)?;
+        Ok(symbols)
+    }
+
+    /// Symbols defined in one file, in source order.
+    pub async fn list_file_symbols(&self, path: &str) -> Result<Vec<SurrealGraphNode>> {
+        let file = surrealdb::sql::Thing::from(("file", path));
+        let mut res = self.db.query("SELECT id, name as label, kind, file.path as file_path, start_line FROM symbol WHERE file = $file ORDER BY start_line")
+            .bind(("file", file))
             .await?;
         let symbols: Vec<SurrealGraphNode> = res.take(0)?;
         Ok(symbols)
     }
 
+    /// File nodes without their content, labelled by path.
+    pub async fn list_file_nodes(&self) -> Result<Vec<SurrealGraphNode>> {
+        let mut res = self.db.query("SELECT id, path as label, 'file' as kind, path as file_path FROM file")
+            .await?;
+        let files: Vec<SurrealGraphNode> = res.take(0)?;
+        Ok(files)
+    }
+
+    /// Chunk nodes without their content, labelled `path:start-end`.
+    pub async fn list_chunk_nodes(&self) -> Result<Vec<SurrealGraphNode>> {
+        let mut res = self.db.query("SELECT id, string::concat(file.path, ':', <string> start_line, '-', <string> end_line) as label, 'chunk' as kind, file.path as file_path, start_line FROM chunk")
+            .await?;
+        let chunks: Vec<SurrealGraphNode> = res.take(0)?;
+        Ok(chunks)
+    }
+
+    /// Every edge of one relation as `(source, target)` record ids.
+    pub async fn list_edges(&self, kind: EdgeKind) -> Result<Vec<(String, String)>> {
+        let mut res = self.db.query(format!("SELECT in, out FROM {}", kind.as_str())).await?;
+        let rows: Vec<EdgeRow> = res.take(0)?;
+        Ok(rows.into_iter().map(|r| (r.r#in.to_string(), r.out.to_string())).collect())
+    }
+
+    /// `(alias, imported symbol name)` for every import made under another name.
+    pub async fn list_import_aliases(&self) -> Result<Vec<(String, String)>> {
+        let mut res = self.db.query("SELECT alias, out.name AS name FROM imports WHERE alias != NONE").await?;
+        let rows: Vec<serde_json::Value> = res.take(0)?;
+        Ok(rows
+            .into_iter()
+            .filter_map(|row| Some((row.get("alias")?.as_str()?.to_string(), row.get("name")?.as_str()?.to_string())))
+            .collect())
+    }
+
+    /// `(path, language)` of every indexed file, by path.
+    pub async fn list_file_languages(&self) -> Result<Vec<(String, String)>> {
+        let mut res = self.db.query("SELECT path, language FROM file ORDER BY path").await?;
+        let rows: Vec<serde_json::Value> = res.take(0)?;
+        Ok(rows
+            .into_iter()
+            .filter_map(|row| Some((row.get("path")?.as_str()?.to_string(), row.get("language")?.as_str()?.to_string())))
+            .collect())
+    }
+
     pub async fn list_files(&self) -> Result<Vec<FileRecord>> {
         let mut res = self.db.query("SELECT * FROM file").await?;
         let files: Vec<FileRecord> = res.take(0)?;
@@ -723,7 +1047,10 @@ impl SurrealStore {
         let mut res = self.db.query("SELECT * FROM $id")
             .bind(("id", thing))
             .await?;
-        let chunk: Option<ChunkRecord> = res.take(0)?;
+        let mut chunk: Option<ChunkRecord> = res.take(0)?;
+        if let Some(chunk) = chunk.as_mut() {
+            self.fill_duplicate(chunk).await?;
+        }
         Ok(chunk)
     }
 
@@ -737,6 +1064,19 @@ impl SurrealStore {
         }
         Ok(0)
     }
+
+    /// `(total, with embedding)` chunk counts, leaving out duplicates.
+    pub async fn count_embedded_chunks(&self) -> Result<(usize, usize)> {
+        let mut res = self.db
+            .query("SELECT count() FROM chunk WHERE duplicate_of = NONE GROUP ALL; SELECT count() FROM chunk WHERE embedding != NONE GROUP ALL")
+            .await?;
+        let total: Option<serde_json::Value> = res.take(0)?;
+        let embedded: Option<serde_json::Value> = res.take(1)?;
+        let count = |val: Option<serde_json::Value>| {
+            val.and_then(|v| v.get("count").and_then(|c| c.as_u64())).unwrap_or(0) as usize
+        };
+        Ok((count(total), count(embedded)))
+    }
     pub async fn find_references(&self, symbol_id: &str) -> Result<Vec<SurrealGraphNode>> {
         // Find all nodes that call this symbol
         // The 'calls' table has 'in' (caller) and 'out' (callee)
@@ -755,27 +1095,46 @@ impl SurrealStore {
         Ok(references)
     }
 
+    /// Where each caller of `symbol_id` calls it (the first call, as edges
+    /// record one call site per caller), by file and position.
+    pub async fn call_sites(&self, symbol_id: &str) -> Result<Vec<CallSite>> {
+        let thing = surrealdb::sql::thing(symbol_id)?;
+        let mut res = self.db.query("SELECT in.file.path as file_path, line, column FROM calls WHERE out = $id AND line != NONE ORDER BY file_path, line")
+            .bind(("id", thing))
+            .await?;
+        Ok(res.take(0)?)
+    }
+
     pub async fn find_definition(&self, symbol_name: &str) -> Result<Vec<SurrealGraphNode>> {
         // Find symbols with this name
         // This is similar to find_nodes_by_label but exact match
-        let mut res = self.db.query("SELECT id, name as label, kind, file.path as file_path FROM symbol WHERE name = $name")
-            .bind(("name", symbol_name.to_string()))
-            .await?;
-            
-        let definitions: Vec<SurrealGraphNode> = res.take(0)?;
+        let definitions = self.symbols_named(symbol_name).await?;
         Ok(definitions)
     }
 
-    pub async fn search_with_rerank(&self, embedding: Vec<f32>, limit: usize) -> Result<Vec<ChunkRecord>> {
+    /// The `limit` chunks nearest `embedding` with their cosine similarity to
+    /// it, ordered by similarity boosted by the centrality of their symbol.
+    pub async fn search_with_rerank(&self, embedding: Vec<f32>, limit: usize, files: Option<&[String]>) -> Result<Vec<(ChunkRecord, f32)>> {
         // 1. Initial Retrieval (fetch more than needed)
         let initial_limit = limit * 3;
+        let filter = self.file_filter(files).await?;
         
-        let mut res = self.db.query("SELECT *, vector::similarity::cosine(embedding, $query_vec) as score FROM chunk WHERE embedding <|10, cosine|> $query_vec LIMIT $limit")
-            .bind(("query_vec", embedding))
-            .bind(("limit", initial_limit))
-            .await?;
-            
-        let results: Vec<ScoredResult> = res.take(0)?;
+        let results: Vec<ScoredResult> = if self.quantization == Quantization::None {
+            let query = format!(
+                "SELECT *, vector::similarity::cosine(embedding, $query_vec) as score FROM chunk WHERE embedding <|{}, cosine|> $query_vec{} LIMIT $limit",
+                initial_limit.max(1),
+                filter.condition()
+            );
+            let mut res = self.db.query(query)
+                .bind(("query_vec", embedding))
+                .bind(("limit", initial_limit))
+                .bind(("files", filter.files.clone()))
+                .bind(("kept", filter.kept.clone()))
+                .await?;
+            res.take(0)?
+        } else {
+            self.search_quantized(embedding, initial_limit, &filter).await?
+        };
 
         // 2. Rerank based on Graph Centrality
         let mut reranked = Vec::new();
@@ -819,13 +1178,13 @@ impl SurrealStore {
                 final_score
             );
             
-            reranked.push((result.into_chunk_record(), final_score));
+            reranked.push((result.into_scored(), final_score));
         }
         
         // Sort by final score descending
         reranked.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
         
-        Ok(reranked.into_iter().map(|(c, _)| c).take(limit).collect())
+        Ok(reranked.into_iter().map(|(c, _)| filter.relocate_scored(c)).take(limit).collect())
     }
 
     pub async fn get_type_definition(&self, symbol_name: &str) -> Result<Option<SurrealGraphNode>> {
@@ -955,6 +1314,41 @@ pub struct CentralNode {
     pub in_degree: usize,
 }
 
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+struct EdgeRow {
+    r#in: Thing,
+    out: Thing,
+    /// Local name of an `import x as y` edge.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    alias: Option<String>,
+    /// Call site of a `calls` edge, in the caller's file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    line: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    column: Option<usize>,
+}
+
+/// Split `a::b::c`, `a.b.c` or `a/b/c` into (symbol, module).
+fn split_qualified(full_path: &str) -> (&str, &str) {
+    if let Some(idx) = full_path.rfind("::") {
+        (&full_path[idx + 2..], &full_path[..idx])
+    } else if let Some(idx) = full_path.rfind('/').or_else(|| full_path.rfind('.')) {
+        // Path-style imports ("./utils/helper.js") split on the last '/', not the extension.
+        (&full_path[idx + 1..], &full_path[..idx])
+    } else {
+        (full_path, "")
+    }
+}
+
+/// Module path with `/` separators, for matching against file paths.
+fn module_path(module: &str) -> String {
+    if module.contains('/') {
+        module.to_string()
+    } else {
+        module.replace("::", "/").replace('.', "/")
+    }
+}
+
 #[derive(serde::Deserialize)]
 struct ScoredResult {
     id: Option<Thing>,
@@ -964,19 +1358,50 @@ struct ScoredResult {
     start_line: usize,
     end_line: usize,
     scopes: Vec<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    sparse_terms: Vec<String>,
+    #[serde(default)]
+    sparse_weights: Vec<f32>,
+    #[serde(default)]
+    language: Option<String>,
+    #[serde(default)]
+    content_hash: String,
+    #[serde(default)]
+    duplicate_of: Option<Thing>,
+    #[serde(default)]
+    doc: Option<String>,
+    #[serde(default)]
+    doc_embedding: Option<Vec<f32>>,
     score: f32,
 }
 
 impl ScoredResult {
+    /// The chunk and its score.
+    fn into_scored(self) -> (ChunkRecord, f32) {
+        let score = self.score;
+        (self.into_chunk_record(), score)
+    }
+
     fn into_chunk_record(self) -> ChunkRecord {
         ChunkRecord {
             id: self.id,
             content: self.content,
             embedding: self.embedding,
+            embedding_q: None,
             file: self.file,
             start_line: self.start_line,
             end_line: self.end_line,
             scopes: self.scopes,
+            tags: self.tags,
+            sparse_terms: self.sparse_terms,
+            sparse_weights: self.sparse_weights,
+            language: self.language,
+            content_hash: self.content_hash,
+            duplicate_of: self.duplicate_of,
+            doc: self.doc,
+            doc_embedding: self.doc_embedding,
         }
     }
 }
@@ -990,3 +1415,155 @@ struct SymbolIdWrapper {
 struct CountWrapper {
     count: usize,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_qualified() {
+        assert_eq!(split_qualified("std::fs::read"), ("read", "std::fs"));
+        assert_eq!(split_qualified("os.path.join"), ("join", "os.path"));
+        assert_eq!(split_qualified("./utils/helper.js"), ("helper.js", "./utils"));
+        assert_eq!(split_qualified("src/app/auth/login"), ("login", "src/app/auth"));
+        assert_eq!(split_qualified("print"), ("print", ""));
+        assert_eq!(module_path("os.path"), "os/path");
+    }
+
+    pub(crate) fn file(path: &str) -> FileRecord {
+        FileRecord {
+            id: None,
+            path: path.to_string(),
+            language: "rust".to_string(),
+            content: String::new(),
+            hash: path.to_string(),
+            last_modified: 0,
+        }
+    }
+
+    pub(crate) fn chunk(id: &str, path: &str, content: &str) -> ChunkRecord {
+        ChunkRecord {
+            id: Some(Thing::from(("chunk", id))),
+            content: content.to_string(),
+            embedding: None,
+            embedding_q: None,
+            file: Thing::from(("file", path)),
+            start_line: 1,
+            end_line: 1,
+            scopes: Vec::new(),
+            tags: Vec::new(),
+            sparse_terms: Vec::new(),
+            sparse_weights: Vec::new(),
+            language: None,
+            content_hash: String::new(),
+            duplicate_of: None,
+            doc: None,
+            doc_embedding: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fts_restricted_to_files() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let store = SurrealStore::open(&dir.path().join("db"), None).await?;
+        let no_symbols = HashMap::new();
+        for (path, id) in [("/repo/src/a.rs", "a"), ("/repo/src/b.rs", "b")] {
+            store
+                .add_file_nodes(&file(path), &[chunk(id, path, "fn lexer() {}")], &[], &no_symbols)
+                .await?;
+        }
+
+        assert_eq!(store.search_fts("lexer", 10, None).await?.len(), 2);
+        assert_eq!(store.count_fts("lexer", None).await?, 2);
+
+        let only_a = ["/repo/src/a.rs".to_string()];
+        let hits = store.search_fts("lexer", 10, Some(&only_a)).await?;
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].0.file, Thing::from(("file", "/repo/src/a.rs")));
+        assert_eq!(store.count_fts("lexer", Some(&only_a)).await?, 1);
+        assert_eq!(store.count_fts("lexer", Some(&[])).await?, 0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_read_only_store_rejects_writes() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("db");
+        let no_symbols = HashMap::new();
+        {
+            let store = SurrealStore::open(&path, None).await?;
+            store.add_file_nodes(&file("/repo/a.rs"), &[chunk("a", "/repo/a.rs", "fn lexer() {}")], &[], &no_symbols).await?;
+        }
+
+        let first = SurrealStore::open_read_only(&path, None).await?;
+        // A second read-only store in the same process gets its own copy.
+        let second = SurrealStore::open_read_only(&path, None).await?;
+        assert!(first.is_read_only());
+        assert_eq!(first.search_fts("lexer", 10, None).await?.len(), 1);
+        assert_eq!(second.search_fts("lexer", 10, None).await?.len(), 1);
+
+        let err = first
+            .add_file_nodes(&file("/repo/b.rs"), &[chunk("b", "/repo/b.rs", "fn parser() {}")], &[], &no_symbols)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("read-only"), "{}", err);
+        assert!(first.search_fts("parser", 10, None).await?.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_restricted_fts_finds_duplicate_copy() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let store = SurrealStore::open(&dir.path().join("db"), None).await?;
+        let no_symbols = HashMap::new();
+        // The vendored copy is stored first and keeps the text.
+        for (path, id, line) in [("/repo/vendor/lex.rs", "v", 1), ("/repo/src/lex.rs", "s", 7)] {
+            let copy = ChunkRecord { content_hash: "h".to_string(), start_line: line, end_line: line, ..chunk(id, path, "fn lexer() {}") };
+            store.add_file_nodes(&file(path), &[copy], &[], &no_symbols).await?;
+        }
+
+        let src = ["/repo/src/lex.rs".to_string()];
+        let hits = store.search_fts("lexer", 10, Some(&src)).await?;
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].0.file, Thing::from(("file", "/repo/src/lex.rs")));
+        assert_eq!(hits[0].0.start_line, 7);
+        assert_eq!(store.count_fts("lexer", Some(&src)).await?, 1);
+
+        let vendor = ["/repo/vendor/lex.rs".to_string()];
+        let hits = store.search_fts("lexer", 10, Some(&vendor)).await?;
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].0.file, Thing::from(("file", "/repo/vendor/lex.rs")));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_quantized_search_keeps_top_hit() -> Result<()> {
+        let vectors = [
+            ("a", [1.0, 0.0, 0.0, 0.0]),
+            ("b", [0.0, 1.0, 0.0, 0.0]),
+            ("c", [0.0, 0.0, 1.0, 0.0]),
+            ("d", [0.0, 0.0, 0.0, 1.0]),
+            ("e", [0.7, 0.7, 0.0, 0.0]),
+            ("f", [-1.0, 0.2, 0.0, 0.0]),
+        ];
+        let path = "/repo/src/v.rs";
+        let chunks: Vec<ChunkRecord> = vectors
+            .iter()
+            .map(|(id, v)| ChunkRecord { embedding: Some(v.to_vec()), ..chunk(id, path, id) })
+            .collect();
+        // Nearest to `a`, but the same sign pattern as `e`: binary needs the rescoring.
+        let query = vec![0.9, 0.1, 0.0, 0.0];
+
+        for mode in [Quantization::None, Quantization::Int8, Quantization::Binary] {
+            let dir = tempfile::tempdir()?;
+            let store = SurrealStore::open_quantized(&dir.path().join("db"), Some(4), mode).await?;
+            assert_eq!(store.quantization(), mode);
+            store.add_file_nodes(&file(path), &chunks, &[], &HashMap::new()).await?;
+
+            let hits = store.search_vector(query.clone(), 1).await?;
+            let top = hits.first().and_then(|c| c.id.clone());
+            assert_eq!(top, Some(Thing::from(("chunk", "a"))), "{}", mode.as_str());
+        }
+        Ok(())
+    }
+}
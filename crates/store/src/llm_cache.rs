@@ -0,0 +1,64 @@
+//! Cached LLM responses, keyed by a hash of the request.
+//!
+//! Entries carry their creation time; lookups ignore entries older than the
+//! caller's TTL, and `prune_llm_cache` deletes those plus the oldest entries
+//! beyond the size limit.
+
+use crate::SurrealStore;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::Thing;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct LlmCacheRecord {
+    model: String,
+    response: String,
+    created: u64,
+}
+
+impl SurrealStore {
+    /// The response cached under `key`, unless it was stored before `not_before`.
+    pub async fn cached_llm_response(&self, key: &str, not_before: u64) -> Result<Option<String>> {
+        let mut res = self.db
+            .query("SELECT * FROM $id WHERE created >= $not_before")
+            .bind(("id", Thing::from(("llm_cache", key))))
+            .bind(("not_before", not_before))
+            .await?;
+        let record: Option<LlmCacheRecord> = res.take(0)?;
+        Ok(record.map(|r| r.response))
+    }
+
+    /// Store `response` under `key`, replacing any earlier entry.
+    pub async fn cache_llm_response(&self, key: &str, model: &str, response: &str, created: u64) -> Result<()> {
+        self.ensure_writable()?;
+        let record = LlmCacheRecord {
+            model: model.to_string(),
+            response: response.to_string(),
+            created,
+        };
+        let _: Option<LlmCacheRecord> = self.db.upsert(("llm_cache", key)).content(record).await?;
+        Ok(())
+    }
+
+    /// Delete entries stored before `not_before`, then the oldest beyond `max_entries`.
+    pub async fn prune_llm_cache(&self, not_before: u64, max_entries: usize) -> Result<()> {
+        self.ensure_writable()?;
+        self.db
+            .query("DELETE llm_cache WHERE created < $not_before")
+            .bind(("not_before", not_before))
+            .await?;
+
+        let mut res = self.db.query("SELECT count() FROM llm_cache GROUP ALL").await?;
+        let count: Option<serde_json::Value> = res.take(0)?;
+        let count = count.and_then(|v| v.get("count").and_then(|c| c.as_u64())).unwrap_or(0) as usize;
+        if count > max_entries {
+            let mut res = self.db
+                .query("SELECT VALUE id FROM llm_cache ORDER BY created ASC LIMIT $excess")
+                .bind(("excess", count - max_entries))
+                .await?;
+            let oldest: Vec<Thing> = res.take(0)?;
+            self.db.query("DELETE $ids").bind(("ids", oldest)).await?;
+        }
+        Ok(())
+    }
+}
@@ -1,4 +1,6 @@
 use serde::{Deserialize, Serialize};
+use emry_core::entry_points::EntryPointKind;
+use emry_core::models::EdgeKind;
 use surrealdb::sql::Thing;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -16,10 +18,37 @@ pub struct ChunkRecord {
     pub id: Option<Thing>,
     pub content: String,
     pub embedding: Option<Vec<f32>>,
+    /// `embedding` quantized for the vector index when `embedding.quantization`
+    /// is set; filled in by the store when the chunk is written.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub embedding_q: Option<Vec<i16>>,
     pub file: Thing,
     pub start_line: usize,
     pub end_line: usize,
     pub scopes: Vec<String>,
+    /// Salient keywords extracted at index time.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Terms of the chunk's sparse vector; `sparse_weights[i]` weighs `sparse_terms[i]`.
+    #[serde(default)]
+    pub sparse_terms: Vec<String>,
+    #[serde(default)]
+    pub sparse_weights: Vec<f32>,
+    /// Sub-language of a region of a mixed-language file (e.g. `TypeScript` in
+    /// a Vue component); `None` when the chunk is in the file's own language.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// SHA-256 of `content`; chunks with equal hashes are deduplicated.
+    #[serde(default)]
+    pub content_hash: String,
+    /// The chunk holding this one's text, embedding and terms when it is an
+    /// identical copy; see `dedup`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duplicate_of: Option<Thing>,    /// Doc comments above and within the chunk, searched by `emry search --docs`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doc: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doc_embedding: Option<Vec<f32>>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -31,6 +60,12 @@ pub struct SymbolRecord {
     pub start_line: usize,
     pub end_line: usize,
     pub parent_scope: Option<String>,
+    /// Run from outside the code: `main`, a route or command handler, a test
+    /// or public API of a library root
+    #[serde(default)]
+    pub entry_point: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entry_kind: Option<EntryPointKind>,
 }
 
 // Edge Relations
@@ -47,6 +82,11 @@ pub struct SurrealGraphNode {
     pub label: String,
     pub kind: String,
     pub file_path: String,
+    #[serde(default)]
+    pub start_line: Option<usize>,
+    /// Enclosing type or module, where selected
+    #[serde(default)]
+    pub parent_scope: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -55,6 +95,43 @@ pub struct SurrealGraphEdge {
     pub target: Thing,
     pub relation: String,
     pub target_node: Option<SurrealGraphNode>, // Optional: if we fetch target details
+    /// Call site (1-based line and byte column in the source's file), for `calls` edges
+    #[serde(default)]
+    pub line: Option<usize>,
+    #[serde(default)]
+    pub column: Option<usize>,
+}
+
+impl SurrealGraphEdge {
+    /// Canonical kind of this edge, or `None` for relations outside the taxonomy.
+    pub fn kind(&self) -> Option<EdgeKind> {
+        EdgeKind::from_name(&self.relation)
+    }
+}
+
+/// Where a caller invokes a symbol: the caller's file and the call's
+/// 1-based line and byte column.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CallSite {
+    pub file_path: String,
+    pub line: usize,
+    #[serde(default)]
+    pub column: Option<usize>,
+}
+
+/// A node reachable from a neighbourhood's origin, `hops` edges away.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ReachEntry {
+    pub node: String,
+    pub hops: u8,
+}
+
+/// Precomputed bounded reachability set of one node over dependency edges.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NeighborhoodRecord {
+    pub id: Option<Thing>,
+    pub node: String,
+    pub reachable: Vec<ReachEntry>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -63,4 +140,24 @@ pub struct CommitLogRecord {
     pub commit_id: String,
     pub timestamp: u64,
     pub note: String,
+    /// Symbols whose content changed in this run (0 for runs before history tracking).
+    #[serde(default)]
+    pub changed_symbols: usize,
+}
+
+/// One version of a symbol's source, recorded when its content hash changes.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SymbolHistoryRecord {
+    pub id: Option<Thing>,
+    /// Symbol record id, e.g. `symbol:⟨src/lib.rs::parse⟩`.
+    pub symbol: String,
+    pub name: String,
+    pub file_path: String,
+    pub commit_id: String,
+    pub timestamp: u64,
+    pub content_hash: String,
+    pub start_line: usize,
+    pub end_line: usize,
+    /// Chunk record ids overlapping the symbol in this version.
+    pub chunks: Vec<String>,
 }
@@ -0,0 +1,87 @@
+//! Bloom filter over symbol names.
+//!
+//! Edge resolution looks up every call and import target by exact name, and
+//! most of those names (std/library calls) are not defined in the repo. The
+//! filter answers "definitely not defined" from memory so those misses never
+//! reach the database. False positives only cost the query we would have run
+//! anyway; removed symbols are never cleared, which is safe for the same reason.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+#[derive(Debug, Clone)]
+pub struct NameFilter {
+    bits: Vec<u64>,
+    num_bits: u64,
+    num_hashes: u32,
+}
+
+impl NameFilter {
+    /// Size the filter for `expected` names at roughly a 1% false-positive rate.
+    pub fn with_capacity(expected: usize) -> Self {
+        // m = -n ln(p) / ln(2)^2 with p = 0.01, i.e. ~9.6 bits per name; k = (m/n) ln 2 ~= 7.
+        let num_bits = ((expected.max(64) as f64) * 9.6).ceil() as u64;
+        Self {
+            bits: vec![0; num_bits.div_ceil(64) as usize],
+            num_bits,
+            num_hashes: 7,
+        }
+    }
+
+    pub fn from_names<I, S>(names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        I::IntoIter: ExactSizeIterator,
+        S: AsRef<str>,
+    {
+        let names = names.into_iter();
+        let mut filter = Self::with_capacity(names.len());
+        for name in names {
+            filter.insert(name.as_ref());
+        }
+        filter
+    }
+
+    pub fn insert(&mut self, name: &str) {
+        for bit in self.bit_positions(name) {
+            self.bits[(bit / 64) as usize] |= 1 << (bit % 64);
+        }
+    }
+
+    /// `false` means the name was never inserted; `true` means it probably was.
+    pub fn may_contain(&self, name: &str) -> bool {
+        self.bit_positions(name)
+            .all(|bit| self.bits[(bit / 64) as usize] & (1 << (bit % 64)) != 0)
+    }
+
+    /// Double hashing (Kirsch-Mitzenmacher): position_i = h1 + i * h2.
+    fn bit_positions(&self, name: &str) -> impl Iterator<Item = u64> + '_ {
+        let mut hasher = DefaultHasher::new();
+        name.hash(&mut hasher);
+        let h1 = hasher.finish();
+        0xa5a5_a5a5_u32.hash(&mut hasher);
+        let h2 = hasher.finish() | 1;
+        (0..self.num_hashes as u64).map(move |i| h1.wrapping_add(i.wrapping_mul(h2)) % self.num_bits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_false_negatives() {
+        let names: Vec<String> = (0..1000).map(|i| format!("symbol_{}", i)).collect();
+        let filter = NameFilter::from_names(names.iter());
+        assert!(names.iter().all(|n| filter.may_contain(n)));
+    }
+
+    #[test]
+    fn test_misses_are_mostly_rejected() {
+        let filter = NameFilter::from_names((0..1000).map(|i| format!("symbol_{}", i)));
+        let false_positives = (0..1000)
+            .filter(|i| filter.may_contain(&format!("missing_{}", i)))
+            .count();
+        assert!(false_positives < 50, "false positives: {}", false_positives);
+    }
+}
@@ -0,0 +1,95 @@
+//! Owners and blame authors on `file` records.
+//!
+//! `owners` holds the CODEOWNERS owners of the file and `authors` its top
+//! `git blame` authors. Both are set after ingestion and dropped whenever the
+//! file is re-ingested, since that replaces the record: owners are applied to
+//! every file on each index run, and a file without `authors` is blamed again.
+
+use crate::SurrealStore;
+use anyhow::Result;
+use emry_core::owners::{owner_matches, AuthorShare};
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::Thing;
+
+/// Files per `UPDATE` batch.
+const UPDATE_BATCH_SIZE: usize = 500;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileOwnership {
+    pub path: String,
+    #[serde(default)]
+    pub owners: Vec<String>,
+    /// Top blame authors; `None` until the file has been blamed
+    #[serde(default)]
+    pub authors: Option<Vec<AuthorShare>>,
+}
+
+impl FileOwnership {
+    /// Whether `owner` owns this file through CODEOWNERS or is its top blame author.
+    pub fn owned_by(&self, owner: &str) -> bool {
+        self.owners.iter().any(|o| owner_matches(o, owner))
+            || self
+                .authors
+                .as_ref()
+                .and_then(|a| a.first())
+                .is_some_and(|top| owner_matches(&top.email, owner) || top.name.eq_ignore_ascii_case(owner))
+    }
+}
+
+#[derive(Clone, Serialize)]
+struct OwnersRow {
+    id: Thing,
+    owners: Vec<String>,
+}
+
+impl SurrealStore {
+    /// Set the CODEOWNERS owners of each `(path, owners)` file.
+    pub async fn set_file_owners(&self, owners: Vec<(String, Vec<String>)>) -> Result<()> {
+        self.ensure_writable()?;
+        let rows: Vec<OwnersRow> = owners
+            .into_iter()
+            .map(|(path, owners)| OwnersRow { id: Thing::from(("file", path.as_str())), owners })
+            .collect();
+        for batch in rows.chunks(UPDATE_BATCH_SIZE) {
+            self.db
+                .query("FOR $row IN $rows { UPDATE $row.id SET owners = $row.owners; }")
+                .bind(("rows", batch.to_vec()))
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Record the blame authors of the file at `path`.
+    pub async fn set_file_authors(&self, path: &str, authors: Vec<AuthorShare>) -> Result<()> {
+        self.ensure_writable()?;
+        self.db
+            .query("UPDATE $id SET authors = $authors")
+            .bind(("id", Thing::from(("file", path))))
+            .bind(("authors", authors))
+            .await?;
+        Ok(())
+    }
+
+    /// Paths of indexed files not blamed since they were last ingested.
+    pub async fn files_without_authors(&self) -> Result<Vec<String>> {
+        let mut res = self.db.query("SELECT VALUE path FROM file WHERE authors = NONE").await?;
+        Ok(res.take(0)?)
+    }
+
+    /// Owners and authors of every indexed file, by path.
+    pub async fn file_ownership(&self) -> Result<Vec<FileOwnership>> {
+        let mut res = self.db.query("SELECT path, owners, authors FROM file ORDER BY path").await?;
+        Ok(res.take(0)?)
+    }
+
+    /// Paths of the files `owner` owns, see [`FileOwnership::owned_by`].
+    pub async fn files_owned_by(&self, owner: &str) -> Result<Vec<String>> {
+        Ok(self
+            .file_ownership()
+            .await?
+            .into_iter()
+            .filter(|f| f.owned_by(owner))
+            .map(|f| f.path)
+            .collect())
+    }
+}
@@ -0,0 +1,91 @@
+//! Quantized vector search (`embedding.quantization`).
+//!
+//! With int8 or binary quantization, each chunk also stores `embedding_q`, a
+//! compact copy of its embedding, and the HNSW index is built over that copy
+//! instead of the full vectors. A search takes [`RESCORE_FACTOR`] times the
+//! requested number of candidates from the compact index, then rescores them
+//! by cosine similarity with their full-precision embeddings, so results are
+//! ranked as before while the vectors in the index take half the memory.
+//! Only the index shrinks: each chunk keeps its full embedding for rescoring,
+//! so the store on disk grows by the size of the copies. Cutting disk usage
+//! is not done: it needs an 8-bit or bit-packed element type in the HNSW
+//! index and the full vectors kept outside the chunk records.
+//!
+//! The mode an index was built with is recorded in `meta:vectors`, and the
+//! store keeps using it whatever the config says: changing the setting takes
+//! a full rebuild.
+
+use crate::dedup::FileFilter;
+use crate::{ScoredResult, SurrealStore};
+use anyhow::Result;
+use emry_core::quantize::{quantize, Quantization, RESCORE_FACTOR};
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::Thing;
+use surrealdb::Surreal;
+
+/// Smallest candidate list the HNSW search keeps while walking the graph.
+const MIN_EF: usize = 64;
+
+#[derive(Serialize, Deserialize)]
+struct VectorsMeta {
+    quantization: Quantization,
+}
+
+impl SurrealStore {
+    /// How the vector index stores embeddings.
+    pub fn quantization(&self) -> Quantization {
+        self.quantization
+    }
+
+    /// The mode the index in `db` was built with. An index without a record
+    /// takes `configured` if no chunk has an embedding yet, and full precision
+    /// if chunks were embedded before the setting existed; with `record`, the
+    /// result is stored for later opens.
+    pub(crate) async fn index_quantization(
+        db: &Surreal<surrealdb::engine::local::Db>,
+        configured: Quantization,
+        record: bool,
+    ) -> surrealdb::Result<Quantization> {
+        let meta: Option<VectorsMeta> = db.select(("meta", "vectors")).await?;
+        if let Some(meta) = meta {
+            return Ok(meta.quantization);
+        }
+        let mut res = db.query("SELECT VALUE id FROM chunk WHERE embedding != NONE LIMIT 1").await?;
+        let embedded: Vec<Thing> = res.take(0)?;
+        let quantization = if embedded.is_empty() { configured } else { Quantization::None };
+        if record {
+            let _: Option<VectorsMeta> = db.upsert(("meta", "vectors")).content(VectorsMeta { quantization }).await?;
+        }
+        Ok(quantization)
+    }
+
+    /// The compact copy of `embedding` stored for this index, if it is quantized.
+    pub(crate) fn quantized(&self, embedding: Option<&[f32]>) -> Option<Vec<i16>> {
+        embedding.and_then(|e| quantize(e, self.quantization))
+    }
+
+    /// The `limit` chunks nearest `embedding` (among those `filter` admits),
+    /// found through the quantized index and scored with their
+    /// full-precision embeddings, nearest first.
+    pub(crate) async fn search_quantized(&self, embedding: Vec<f32>, limit: usize, filter: &FileFilter) -> Result<Vec<ScoredResult>> {
+        let Some(query_q) = quantize(&embedding, self.quantization) else {
+            return Ok(Vec::new());
+        };
+        let candidates = limit * RESCORE_FACTOR;
+        let mut res = self
+            .db
+            .query(format!(
+                "SELECT *, vector::similarity::cosine(embedding, $query_vec) AS score FROM chunk WHERE embedding_q <|{},{}|> $query_q{} ORDER BY score DESC LIMIT $limit",
+                candidates,
+                candidates.max(MIN_EF),
+                filter.condition()
+            ))
+            .bind(("query_vec", embedding))
+            .bind(("query_q", query_q))
+            .bind(("limit", limit))
+            .bind(("files", filter.files.clone()))
+            .bind(("kept", filter.kept.clone()))
+            .await?;
+        Ok(res.take(0)?)
+    }
+}
@@ -0,0 +1,74 @@
+//! Read-only stores for indexes that must not change.
+//!
+//! A store opened with [`SurrealStore::open_read_only`] skips schema setup and
+//! rejects every write method with [`StoreError::ReadOnly`]. RocksDB takes a
+//! lock file and writes its log and manifest whenever it opens a database,
+//! even just to read it, so the index is never opened in place: it is copied
+//! to a private temporary directory and the copy is opened instead, then
+//! removed when the last clone of the store is dropped.
+
+use crate::{SurrealStore, StoreError};
+use anyhow::Result;
+use emry_core::error::EmryError;
+use emry_core::quantize::Quantization;
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+use surrealdb::engine::local::RocksDb;
+use surrealdb::Surreal;
+
+impl SurrealStore {
+    /// Open the store for reading only; see the module docs.
+    ///
+    /// `vector_dimension` only records whether the index has vectors: the
+    /// vector index itself, and its quantization, were set up when the index
+    /// was built.
+    pub async fn open_read_only(path: &Path, vector_dimension: Option<usize>) -> Result<Self> {
+        let open_err = |source| EmryError::from(StoreError::Open { path: path.to_path_buf(), source });
+        if !path.exists() {
+            return Err(EmryError::IndexNotFound { path: path.to_path_buf() }.into());
+        }
+        let snapshot = tempfile::Builder::new().prefix("emry-read-only-").tempdir()?;
+        copy_dir(path, snapshot.path())?;
+        let db = Surreal::new::<RocksDb>(snapshot.path()).await.map_err(open_err)?;
+        db.use_ns("emry").use_db("main").await.map_err(open_err)?;
+        let quantization = Self::index_quantization(&db, Quantization::None, false)
+            .await
+            .map_err(|e| EmryError::from(StoreError::Schema(e)))?;
+
+        Ok(Self {
+            db,
+            vector_index: vector_dimension.is_some(),
+            quantization,
+            symbol_filter: Arc::new(RwLock::new(None)),
+            read_only: true,
+            _snapshot: Some(Arc::new(snapshot)),
+        })
+    }
+
+    /// Whether writes are rejected.
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Fail with [`StoreError::ReadOnly`] on a read-only store.
+    pub(crate) fn ensure_writable(&self) -> Result<()> {
+        if self.read_only {
+            return Err(EmryError::from(StoreError::ReadOnly).into());
+        }
+        Ok(())
+    }
+}
+
+fn copy_dir(from: &Path, to: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(to)?;
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else if entry.file_name() != "LOCK" {
+            std::fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,85 @@
+//! Summaries of files, modules and the repository, written by `emry summarize`.
+//!
+//! One `summary` record per level and path, keyed `level:path`, holding the
+//! text and the hash of the input it was written from (see
+//! `emry_core::summary`). Callers compare that hash with the current one to
+//! tell stale summaries apart.
+
+use crate::SurrealStore;
+use anyhow::Result;
+use emry_core::summary::SummaryLevel;
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::Thing;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SummaryRecord {
+    pub level: SummaryLevel,
+    /// File or directory relative to the repository root; empty for the repository
+    pub path: String,
+    /// Hash of the input the summary was written from
+    pub hash: String,
+    pub text: String,
+    pub created: u64,
+}
+
+fn summary_key(level: SummaryLevel, path: &str) -> String {
+    format!("{}:{}", level.as_str(), path)
+}
+
+fn summary_id(level: SummaryLevel, path: &str) -> Thing {
+    Thing::from(("summary", summary_key(level, path).as_str()))
+}
+
+impl SurrealStore {
+    /// Store `record`, replacing the earlier summary at its level and path.
+    pub async fn set_summary(&self, record: SummaryRecord) -> Result<()> {
+        self.ensure_writable()?;
+        let key = summary_key(record.level, &record.path);
+        let _: Option<SummaryRecord> = self.db.upsert(("summary", key.as_str())).content(record).await?;
+        Ok(())
+    }
+
+    pub async fn get_summary(&self, level: SummaryLevel, path: &str) -> Result<Option<SummaryRecord>> {
+        let mut res = self.db.query("SELECT * FROM $id").bind(("id", summary_id(level, path))).await?;
+        let record: Option<SummaryRecord> = res.take(0)?;
+        Ok(record)
+    }
+
+    /// Every summary at `level`, by path.
+    pub async fn list_summaries(&self, level: SummaryLevel) -> Result<Vec<SummaryRecord>> {
+        let mut res = self.db
+            .query("SELECT * FROM summary WHERE level = $level ORDER BY path")
+            .bind(("level", level))
+            .await?;
+        let records: Vec<SummaryRecord> = res.take(0)?;
+        Ok(records)
+    }
+
+    /// Paths of the summaries at `level` written from input `hash`; for
+    /// files, the copies with identical content, which share one summary.
+    pub async fn summary_paths_with_hash(&self, level: SummaryLevel, hash: &str) -> Result<Vec<String>> {
+        let mut res = self.db
+            .query("SELECT VALUE path FROM summary WHERE level = $level AND hash = $hash")
+            .bind(("level", level))
+            .bind(("hash", hash.to_string()))
+            .await?;
+        let mut paths: Vec<String> = res.take(0)?;
+        paths.sort();
+        Ok(paths)
+    }
+
+    /// Delete the summaries at `level` for `paths`.
+    pub async fn delete_summaries(&self, level: SummaryLevel, paths: &[String]) -> Result<()> {
+        self.ensure_writable()?;
+        let ids: Vec<Thing> = paths.iter().map(|p| summary_id(level, p)).collect();
+        self.db.query("DELETE $ids").bind(("ids", ids)).await?;
+        Ok(())
+    }
+
+    /// Forget all summaries.
+    pub async fn clear_summaries(&self) -> Result<()> {
+        self.ensure_writable()?;
+        self.db.query("DELETE summary").await?;
+        Ok(())
+    }
+}